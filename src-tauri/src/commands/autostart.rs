@@ -0,0 +1,151 @@
+// Autostart Commands
+//
+// 开机自启动相关的 Tauri 命令
+//
+// 系统中的自启动项可能在应用外被修改，因此以系统实际状态为准，
+// 并同步回配置中的 general.auto_start
+
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Runtime, State};
+use tauri_plugin_autostart::ManagerExt;
+use tokio::sync::RwLock;
+
+use crate::modules::config::{PartialAppConfig, PartialGeneralConfig};
+use crate::modules::ConfigModule;
+use crate::shared::{AppError, AppResult};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetAutostartRequest {
+    pub enabled: bool,
+}
+
+/// 配置与系统自启动状态的同步结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutostartSync {
+    /// 两者一致，无需处理
+    InSync,
+    /// 配置与系统不一致，需要把配置改为系统的实际状态
+    UpdateConfig(bool),
+}
+
+/// 比较配置与系统的自启动状态
+pub fn reconcile_autostart(config_enabled: bool, os_enabled: bool) -> AutostartSync {
+    if config_enabled == os_enabled {
+        AutostartSync::InSync
+    } else {
+        AutostartSync::UpdateConfig(os_enabled)
+    }
+}
+
+/// 按系统实际状态同步配置，返回是否修改了配置
+pub async fn sync_autostart_config(
+    config_module: &RwLock<ConfigModule>,
+    os_enabled: bool,
+) -> AppResult<bool> {
+    let config_module = config_module.read().await;
+    let config_enabled = config_module
+        .get_all()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?
+        .general
+        .auto_start;
+
+    let AutostartSync::UpdateConfig(enabled) = reconcile_autostart(config_enabled, os_enabled)
+    else {
+        return Ok(false);
+    };
+
+    tracing::info!(
+        "[autostart] Config says {}, system says {}, updating config",
+        config_enabled,
+        os_enabled
+    );
+    config_module
+        .update(PartialAppConfig {
+            general: Some(PartialGeneralConfig {
+                auto_start: Some(enabled),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?;
+    Ok(true)
+}
+
+/// 查询系统中的自启动状态
+pub fn os_autostart_enabled<R: Runtime>(app: &AppHandle<R>) -> AppResult<bool> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+/// 启用或禁用开机自启动，返回设置后系统的实际状态
+#[tauri::command]
+pub async fn autostart_set(
+    app: AppHandle,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    request: SetAutostartRequest,
+) -> AppResult<bool> {
+    let launcher = app.autolaunch();
+    let result = if request.enabled {
+        launcher.enable()
+    } else {
+        launcher.disable()
+    };
+    result.map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    let enabled = os_autostart_enabled(&app)?;
+    sync_autostart_config(&config_module, enabled).await?;
+    Ok(enabled)
+}
+
+/// 查询系统中的自启动状态，并同步到配置
+#[tauri::command]
+pub async fn autostart_is_enabled(
+    app: AppHandle,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<bool> {
+    let enabled = os_autostart_enabled(&app)?;
+    sync_autostart_config(&config_module, enabled).await?;
+    Ok(enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_prefers_system_state() {
+        assert_eq!(reconcile_autostart(true, true), AutostartSync::InSync);
+        assert_eq!(reconcile_autostart(false, false), AutostartSync::InSync);
+        assert_eq!(
+            reconcile_autostart(true, false),
+            AutostartSync::UpdateConfig(false)
+        );
+        assert_eq!(
+            reconcile_autostart(false, true),
+            AutostartSync::UpdateConfig(true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_updates_drifted_config() {
+        let config_module = RwLock::new(ConfigModule::new_in_memory());
+
+        // 配置为关闭，系统中已被外部启用
+        assert!(sync_autostart_config(&config_module, true).await.unwrap());
+        let config = config_module.read().await.get_all().await.unwrap();
+        assert!(config.general.auto_start);
+
+        // 已一致时不修改
+        assert!(!sync_autostart_config(&config_module, true).await.unwrap());
+
+        // 配置为开启，系统中已被外部禁用
+        assert!(sync_autostart_config(&config_module, false).await.unwrap());
+        let config = config_module.read().await.get_all().await.unwrap();
+        assert!(!config.general.auto_start);
+    }
+}
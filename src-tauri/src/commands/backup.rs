@@ -0,0 +1,68 @@
+// Backup Commands
+//
+// 数据备份相关的 Tauri 命令
+
+use serde::Deserialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::RwLock;
+
+use crate::infrastructure::{BackupStore, FsBackupStore};
+use crate::modules::ConfigModule;
+use crate::shared::{AppError, AppResult};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreBackupRequest {
+    pub name: String,
+}
+
+fn backup_store(app: &AppHandle) -> AppResult<FsBackupStore> {
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    Ok(FsBackupStore::new(data_dir))
+}
+
+/// 立即创建备份，返回备份名称
+#[tauri::command]
+pub async fn backup_create(
+    app: AppHandle,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<String> {
+    let keep = config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?
+        .backup
+        .keep;
+
+    let store = backup_store(&app)?;
+    let name = crate::infrastructure::create_backup(&store, chrono::Utc::now(), keep as usize)?;
+
+    tracing::info!("[backup_create] Created backup: {}", name);
+    Ok(name)
+}
+
+/// 列出所有备份（最新的在前）
+#[tauri::command]
+pub async fn backup_list(app: AppHandle) -> AppResult<Vec<String>> {
+    let mut names = backup_store(&app)?.list()?;
+    names.sort_by(|a, b| b.cmp(a));
+    Ok(names)
+}
+
+/// 从备份恢复数据文件
+///
+/// 已加载的会话和配置不会自动刷新，恢复后需重启应用
+#[tauri::command]
+pub async fn backup_restore(app: AppHandle, request: RestoreBackupRequest) -> AppResult<()> {
+    let store = backup_store(&app)?;
+    crate::infrastructure::restore_backup(&store, &request.name)?;
+
+    tracing::info!("[backup_restore] Restored backup: {}", request.name);
+    Ok(())
+}
@@ -4,16 +4,37 @@
 // 完全通过 ChatModule 的六边形架构处理业务逻辑
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use tauri::State;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use tauri_plugin_fs::FsExt;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::infrastructure::{AppEvent, EventBus};
-use crate::modules::chat::infrastructure::LLMAdapterRegistry;
-use crate::modules::chat::ports::{LLMProviderConfig, ProviderType};
-use crate::modules::chat::{ChatModule, MessageId, MessageRole, SendMessageCommand, SessionId};
-use crate::shared::{AppResult, Emotion, Message, MessageChunk, MessageRole as SharedMessageRole, text_to_phonemes};
+use crate::infrastructure::{
+    AppEvent, AppState, AuditLog, CancelSignal, CancellationRegistry, EventBus,
+};
+use crate::modules::chat::domain::{
+    resolve_settings, EffectiveSettings, MessageCompleteEvent, MessageSentEvent, SearchSnippet,
+    SessionOverrides, SettingSource, SettingsLayers,
+};
+use crate::modules::chat::infrastructure::{
+    read_scoped_image_attachment, AttachmentError, LLMAdapterRegistry, OpenAIAdapter,
+    ProviderHealthSummary, DEMO_MODEL, MAX_ATTACHMENT_BYTES,
+};
+use crate::modules::chat::ports::{
+    CapabilitySource, HealthStatus, HttpClientOptions, LLMError, LLMPort, LLMProviderConfig,
+    ModelFeature, ModelSupport, ProviderType,
+};
+use crate::modules::config::{AppConfig, LLMProviderConfig as SavedProviderConfig};
+use crate::modules::ConfigModule;
+use crate::modules::chat::{
+    ChatModule, DeleteMessageCommand, EditMessageCommand, GetSessionQuery, MessageId, MessageRole, ResponseTiming, SearchMessagesQuery,
+    SendMessageCommand, Session, SessionId,
+};
+use crate::shared::{AppError, AppResult, CodeFenceTracker, Emotion, FinishReason, Message, MessageChunk, MessageRole as SharedMessageRole, Preset, text_to_phonemes};
 
 /// 前端 Provider 配置
 #[derive(Debug, Clone, Deserialize)]
@@ -27,6 +48,28 @@ pub struct FrontendProviderConfig {
     pub models: Vec<String>,
     #[serde(default)]
     pub is_default: bool,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub cold_start_timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub http: HttpClientOptions,
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// 附加到每个请求的请求头
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    /// 模型保持加载的时长（仅 Ollama）
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// 上下文窗口大小（仅 Ollama）
+    #[serde(default)]
+    pub num_ctx: Option<u32>,
+    /// anthropic-version 请求头（仅 Claude），未设置时使用默认版本
+    #[serde(default)]
+    pub anthropic_version: Option<String>,
+    #[serde(default)]
+    pub anthropic_beta: Vec<String>,
 }
 
 impl From<FrontendProviderConfig> for LLMProviderConfig {
@@ -37,6 +80,7 @@ impl From<FrontendProviderConfig> for LLMProviderConfig {
             config.base_url,
             config.provider_type
         );
+        let defaults = LLMProviderConfig::default();
         LLMProviderConfig {
             id: config.id,
             name: config.name,
@@ -48,30 +92,113 @@ impl From<FrontendProviderConfig> for LLMProviderConfig {
                 .first()
                 .cloned()
                 .unwrap_or_else(|| "gpt-3.5-turbo".to_string()),
-            timeout_secs: 60,
+            timeout_secs: config.timeout_secs.unwrap_or(defaults.timeout_secs),
+            cold_start_timeout_secs: config
+                .cold_start_timeout_secs
+                .unwrap_or(defaults.cold_start_timeout_secs),
             max_retries: 3,
+            http: config.http,
+            proxy_url: config.proxy_url.filter(|url| !url.trim().is_empty()),
+            extra_headers: config.extra_headers,
+            keep_alive: config.keep_alive.filter(|keep_alive| !keep_alive.trim().is_empty()),
+            num_ctx: config.num_ctx,
+            anthropic_version: config
+                .anthropic_version
+                .unwrap_or(defaults.anthropic_version),
+            anthropic_beta: config.anthropic_beta,
+        }
+    }
+}
+
+/// 消息处理失败信息
+#[derive(Debug)]
+struct ProcessError {
+    error: String,
+    /// 已生成但未能保存的内容
+    content: Option<String>,
+}
+
+impl From<String> for ProcessError {
+    fn from(error: String) -> Self {
+        Self {
+            error,
+            content: None,
         }
     }
 }
 
+impl From<&str> for ProcessError {
+    fn from(error: &str) -> Self {
+        Self::from(error.to_string())
+    }
+}
+
+/// 一次生成的结果：助手消息 ID、情感和各阶段耗时
+type GenerationResult = Result<(MessageId, Option<Emotion>, ResponseTiming), ProcessError>;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendMessageRequest {
     pub session_id: Uuid,
     pub content: String,
     pub provider_config: Option<FrontendProviderConfig>,
+    /// 助手回复的开头（如角色名），提供商不支持预填时忽略
+    #[serde(default)]
+    pub assistant_prefix: Option<String>,
+    /// 请求 ID，用于通过 `chat_stop_generation` 取消，未指定时自动生成
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// 温度（0.0 - 2.0），未指定时使用提供商默认值
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// 最大生成 token 数，未指定时使用提供商默认值
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// 停止序列，未指定时角色扮演模式使用默认的用户发言标签
+    #[serde(default)]
+    pub stop_sequences: Option<Vec<String>>,
+    /// 角色扮演中用户的名字，设置后截掉模型越界生成的用户发言
+    #[serde(default)]
+    pub user_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageWithFilesRequest {
+    pub session_id: Uuid,
+    pub content: String,
+    /// 本地图片文件路径
+    pub file_paths: Vec<String>,
+    pub provider_config: Option<FrontendProviderConfig>,
+    #[serde(default)]
+    pub assistant_prefix: Option<String>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(default)]
+    pub user_name: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SendMessageResponse {
     pub message_id: Uuid,
+    /// 本次生成的请求 ID，事件中携带同一 ID
+    pub request_id: String,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StopGenerationRequest {
     pub session_id: Uuid,
+    /// 要取消的请求 ID
+    #[serde(default)]
+    pub request_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -80,22 +207,65 @@ pub struct RegenerateRequest {
     pub session_id: Uuid,
     pub user_content: String,
     pub provider_config: Option<FrontendProviderConfig>,
+    /// 要重新生成的助手消息 ID，未指定时按历史末尾推断
+    #[serde(default)]
+    pub message_id: Option<Uuid>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(default)]
+    pub user_name: Option<String>,
+    /// 助手回复的开头（如角色名），提供商不支持预填时忽略
+    #[serde(default)]
+    pub assistant_prefix: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetMessagesRequest {
     pub session_id: Uuid,
-    pub page: u32,
-    pub limit: u32,
+    /// 默认第 1 页
+    #[serde(default)]
+    pub page: Option<u32>,
+    /// 默认 50 条，最多 200 条
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertContextBreakRequest {
+    pub session_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertContextBreakResponse {
+    pub message_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearSessionMessagesRequest {
+    pub session_id: Uuid,
 }
 
 /// 发送消息命令 - 使用 ChatModule 的六边形架构
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn chat_send_message(
     chat_module: State<'_, Arc<RwLock<ChatModule>>>,
     event_bus: State<'_, Arc<RwLock<EventBus>>>,
     llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    cancellations: State<'_, Arc<CancellationRegistry>>,
+    state: State<'_, AppState>,
     request: SendMessageRequest,
 ) -> AppResult<SendMessageResponse> {
     tracing::info!(
@@ -103,66 +273,363 @@ pub async fn chat_send_message(
         request.session_id
     );
 
-    let session_id_domain = SessionId::from(request.session_id);
-    let content = request.content.clone();
-    let provider_config = request.provider_config.clone();
+    let mut command = SendMessageCommand::new(
+        SessionId::from(request.session_id),
+        request.content,
+        None,
+        true,
+    );
+    let defaults = session_defaults(
+        &*chat_module.read().await,
+        &config_module,
+        &state,
+        command.session_id,
+    )
+    .await;
+    command.assistant_prefix = request.assistant_prefix.or(defaults.assistant_prefix);
+    command.system_prompt = defaults.system_prompt;
+    command.model = defaults.model;
+    command.temperature = request.temperature.or(defaults.temperature);
+    command.max_tokens = request.max_tokens;
+    command.stop_sequences = request.stop_sequences;
+    command.user_name = request
+        .user_name
+        .or(defaults.user_name)
+        .filter(|name| !name.trim().is_empty());
+    apply_configured_limits(&mut command, &config_module).await;
+    if let Some(request_id) = request.request_id {
+        command = command.with_request_id(request_id);
+    }
+    // 在开始生成前拒绝无效内容，让调用方直接收到错误
+    command
+        .validate()
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    let request_id = command.request_id.clone();
+
+    let generation = process_message_with_module(
+        command,
+        request.provider_config,
+        chat_module.inner().clone(),
+        event_bus.inner().clone(),
+        llm_registry.inner().clone(),
+        audit_log.inner().clone(),
+        generation_options(&config_module).await,
+    );
+    spawn_generation(
+        "chat_send_message",
+        request.session_id,
+        request_id.clone(),
+        event_bus.inner().clone(),
+        cancellations.inner().clone(),
+        llm_registry.inner().clone(),
+        generation,
+    );
 
-    // 克隆资源用于异步任务
-    let event_bus_clone = event_bus.inner().clone();
-    let chat_module_clone = chat_module.inner().clone();
-    let llm_registry_clone = llm_registry.inner().clone();
-    let request_session_id = request.session_id;
+    Ok(SendMessageResponse {
+        message_id: Uuid::new_v4(),
+        request_id,
+    })
+}
 
-    // 在后台任务中处理 LLM 响应(使用 ChatModule)
-    tokio::spawn(async move {
-        let result = process_message_with_module(
-            session_id_domain,
-            content,
-            provider_config,
-            chat_module_clone.clone(),
-            event_bus_clone.clone(),
-            llm_registry_clone,
+/// 发送带本地图片附件的消息
+///
+/// 所有文件在发送前读取并校验，任一文件不在 fs 插件的访问范围内（用户选择或拖入的文件）、
+/// 不是支持的图片类型或超过大小上限时直接返回错误
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn chat_send_message_with_files(
+    app: AppHandle,
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    event_bus: State<'_, Arc<RwLock<EventBus>>>,
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    cancellations: State<'_, Arc<CancellationRegistry>>,
+    state: State<'_, AppState>,
+    request: SendMessageWithFilesRequest,
+) -> AppResult<SendMessageResponse> {
+    tracing::info!(
+        "[chat_send_message_with_files] Processing {} file(s) for session: {}",
+        request.file_paths.len(),
+        request.session_id
+    );
+
+    let scope = app.fs_scope();
+    let mut images = Vec::with_capacity(request.file_paths.len());
+    for path in &request.file_paths {
+        let image = read_scoped_image_attachment(
+            std::path::Path::new(path),
+            MAX_ATTACHMENT_BYTES,
+            |path| scope.is_allowed(path),
         )
-        .await;
+        .await
+        .map_err(attachment_error)?;
+        images.push(image);
+    }
+
+    let mut command = SendMessageCommand::new(
+        SessionId::from(request.session_id),
+        request.content,
+        None,
+        true,
+    )
+    .with_images(images);
+    let defaults = session_defaults(
+        &*chat_module.read().await,
+        &config_module,
+        &state,
+        command.session_id,
+    )
+    .await;
+    command.assistant_prefix = request.assistant_prefix.or(defaults.assistant_prefix);
+    command.system_prompt = defaults.system_prompt;
+    command.model = defaults.model;
+    command.temperature = request.temperature.or(defaults.temperature);
+    command.max_tokens = request.max_tokens;
+    command.stop_sequences = request.stop_sequences;
+    command.user_name = request
+        .user_name
+        .or(defaults.user_name)
+        .filter(|name| !name.trim().is_empty());
+    apply_configured_limits(&mut command, &config_module).await;
+    if let Some(request_id) = request.request_id {
+        command = command.with_request_id(request_id);
+    }
+    // 在开始生成前拒绝无效内容，让调用方直接收到错误
+    command
+        .validate()
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    let request_id = command.request_id.clone();
+
+    let generation = process_message_with_module(
+        command,
+        request.provider_config,
+        chat_module.inner().clone(),
+        event_bus.inner().clone(),
+        llm_registry.inner().clone(),
+        audit_log.inner().clone(),
+        generation_options(&config_module).await,
+    );
+    spawn_generation(
+        "chat_send_message",
+        request.session_id,
+        request_id.clone(),
+        event_bus.inner().clone(),
+        cancellations.inner().clone(),
+        llm_registry.inner().clone(),
+        generation,
+    );
+
+    Ok(SendMessageResponse {
+        message_id: Uuid::new_v4(),
+        request_id,
+    })
+}
+
+/// 转换附件错误：超出访问范围报告为权限错误，其余为无效附件
+fn attachment_error(error: AttachmentError) -> AppError {
+    match error {
+        AttachmentError::NotAllowed(_) => AppError::PermissionDenied(error.to_string()),
+        _ => AppError::InvalidAttachment(error.to_string()),
+    }
+}
+
+/// 应用配置的上下文长度（携带的历史消息条数）、消息字符数上限和用户情感分析开关，
+/// 读取配置失败时不限制
+async fn apply_configured_limits(
+    command: &mut SendMessageCommand,
+    config_module: &RwLock<ConfigModule>,
+) {
+    if let Ok(config) = config_module.read().await.get_all().await {
+        command.context_length = Some(config.llm.context_length as usize);
+        command.max_content_chars = Some(config.llm.max_user_message_chars as usize);
+        command.analyze_user_emotion = config.llm.analyze_user_emotion;
+    }
+}
+
+/// 会话的预设和模型配置中，请求未指定时使用的生成设置
+#[derive(Debug, Default)]
+struct SessionDefaults {
+    /// 预设的系统提示词
+    system_prompt: Option<String>,
+    /// 会话指定的模型，未指定时使用提供商的默认模型
+    model: Option<String>,
+    temperature: Option<f32>,
+    /// 预设的助手前缀
+    assistant_prefix: Option<String>,
+    /// 预设的角色扮演用户名
+    user_name: Option<String>,
+}
+
+/// 合并各层设置所需的输入，读取配置失败时不含全局层
+fn settings_layers(
+    session: &Session,
+    preset: Option<&Preset>,
+    config: Option<AppConfig>,
+) -> SettingsLayers {
+    let mut layers = SettingsLayers {
+        session: SessionOverrides::from_model_config(session.model_config()),
+        preset_system_prompt: preset.map(|preset| preset.system_prompt.clone()),
+        ..SettingsLayers::default()
+    };
+    if let Some(config) = config {
+        layers.global_provider = Some(config.llm.default_provider);
+        layers.global_context_limit = Some(config.llm.context_length);
+        layers.provider_models = config
+            .llm
+            .providers
+            .into_values()
+            .map(|provider| (provider.id, provider.models))
+            .collect();
+    }
+    layers
+}
+
+/// 会话所用的预设
+async fn session_preset(state: &AppState, session: &Session) -> Option<Preset> {
+    let preset_id = session.preset_id()?;
+    state.presets.read().await.get(&preset_id).cloned()
+}
+
+/// 按与 `chat_get_effective_settings` 相同的规则合并设置，得到发送时使用的会话设置
+///
+/// 会话不存在时返回空设置
+async fn session_defaults(
+    module: &ChatModule,
+    config_module: &RwLock<ConfigModule>,
+    state: &AppState,
+    session_id: SessionId,
+) -> SessionDefaults {
+    let Some(session) = module
+        .get_session(GetSessionQuery::new(session_id))
+        .await
+        .ok()
+        .and_then(|response| response.session)
+    else {
+        return SessionDefaults::default();
+    };
+    let preset = session_preset(state, &session).await;
+    let config = config_module.read().await.get_all().await.ok();
+    let settings = resolve_settings(&settings_layers(&session, preset.as_ref(), config));
+
+    SessionDefaults {
+        system_prompt: settings.system_prompt.value,
+        model: settings
+            .model
+            .value
+            .filter(|_| settings.model.source == SettingSource::Session),
+        temperature: settings.temperature.value,
+        assistant_prefix: preset.as_ref().and_then(|preset| preset.assistant_prefix.clone()),
+        user_name: preset.and_then(|preset| preset.user_name),
+    }
+}
+
+/// 生成时使用的配置开关
+#[derive(Debug, Clone, Default)]
+struct GenerationOptions {
+    /// 不健康提供商的自动回退
+    auto_fallback: bool,
+    /// 自动回退时依次尝试的提供商 ID（按用户配置的顺序，不含 API Key 无法读取的提供商）
+    fallback_order: Vec<String>,
+    /// 合并的流式帧
+    combined_frames: bool,
+}
+
+/// 读取生成相关的配置开关，读取失败时全部关闭
+async fn generation_options(config_module: &RwLock<ConfigModule>) -> GenerationOptions {
+    config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map(|config| GenerationOptions {
+            auto_fallback: config.llm.auto_fallback,
+            fallback_order: config
+                .llm
+                .ordered_providers()
+                .into_iter()
+                .filter(|provider| !provider.api_key_unreadable)
+                .map(|provider| provider.id.clone())
+                .collect(),
+            combined_frames: config.llm.combined_stream_frames,
+        })
+        .unwrap_or_default()
+}
+
+/// 在后台任务中运行一次生成
+///
+/// 开始时发布 MessageStart，并以请求 ID 登记取消；完成、失败或取消时发布携带同一请求 ID 的事件
+fn spawn_generation<F>(
+    label: &'static str,
+    session_id: Uuid,
+    request_id: String,
+    event_bus: Arc<RwLock<EventBus>>,
+    cancellations: Arc<CancellationRegistry>,
+    llm_registry: Arc<LLMAdapterRegistry>,
+    generation: F,
+) where
+    F: Future<Output = GenerationResult> + Send + 'static,
+{
+    // 在返回前登记，使前端拿到请求 ID 后即可取消
+    let mut signal = cancellations.register(request_id.clone());
+
+    tokio::spawn(async move {
+        event_bus.read().await.publish(AppEvent::MessageStart {
+            session_id,
+            request_id: request_id.clone(),
+        });
+
+        let result = tokio::select! {
+            result = generation => result,
+            _ = signal.cancelled() => {
+                tracing::info!("[{}] Cancelled: {}", label, request_id);
+                // 通知适配器关闭该请求的流式连接
+                llm_registry.cancel_request(&request_id).await;
+                Err(LLMError::Cancelled.to_string().into())
+            }
+        };
+        cancellations.remove(&request_id);
 
-        let event_bus = event_bus_clone.read().await;
+        let event_bus = event_bus.read().await;
 
         match result {
-            Ok((message_id, emotion)) => {
-                tracing::info!("[chat_send_message] Message processed: {}", message_id);
+            Ok((message_id, emotion, timing)) => {
+                tracing::info!("[{}] Message processed: {}", label, message_id);
                 event_bus.publish(AppEvent::MessageComplete {
-                    session_id: request_session_id,
+                    session_id,
+                    request_id,
                     message_id: message_id.into(),
                     emotion,
+                    reasoning_ms: timing.reasoning_ms,
+                    answer_ms: timing.answer_ms,
                 });
             }
             Err(error) => {
-                tracing::error!("[chat_send_message] Error: {}", error);
+                tracing::error!("[{}] Error: {}", label, error.error);
                 event_bus.publish(AppEvent::MessageError {
-                    session_id: request_session_id,
-                    error,
+                    session_id,
+                    request_id,
+                    error: error.error,
+                    content: error.content,
                 });
             }
         }
     });
-
-    Ok(SendMessageResponse {
-        message_id: Uuid::new_v4(),
-    })
 }
 
 /// 使用 ChatModule 处理消息和 LLM 调用
 async fn process_message_with_module(
-    session_id: SessionId,
-    content: String,
+    mut command: SendMessageCommand,
     provider_config: Option<FrontendProviderConfig>,
     chat_module: Arc<RwLock<ChatModule>>,
     event_bus: Arc<RwLock<EventBus>>,
     llm_registry: Arc<LLMAdapterRegistry>,
-) -> Result<(MessageId, Option<Emotion>), String> {
+    audit_log: Arc<AuditLog>,
+    options: GenerationOptions,
+) -> GenerationResult {
     // 从配置创建 LLM 适配器
     let provider_config = provider_config.ok_or("No provider configuration provided")?;
-    let provider_id = provider_config.id.clone();
+    let mut provider_id = provider_config.id.clone();
     let llm_provider_config: LLMProviderConfig = provider_config.into();
 
     let _llm = llm_registry
@@ -170,8 +637,29 @@ async fn process_message_with_module(
         .await
         .map_err(|e| format!("Failed to create LLM adapter: {}", e))?;
 
-    // 使用 ChatModule 的 SendMessageCommand (流式)
-    let command = SendMessageCommand::new(session_id, content.clone(), None, true);
+    let session_id = command.session_id;
+
+    // 提供商最近不健康时改用其他健康的提供商
+    if options.auto_fallback {
+        if let Some(fallback) = llm_registry
+            .healthy_fallback(&provider_id, &options.fallback_order)
+            .await
+        {
+            tracing::warn!(
+                "[chat_send_message] Provider {} is unhealthy, falling back to {}",
+                provider_id,
+                fallback
+            );
+            event_bus.read().await.publish(AppEvent::ProviderFellBack {
+                session_id: session_id.into(),
+                from: provider_id,
+                to: fallback.clone(),
+            });
+            provider_id = fallback;
+            // 替代提供商未必提供会话指定的模型，改用其默认模型
+            command.model = None;
+        }
+    }
 
     let module = chat_module.read().await;
 
@@ -184,36 +672,159 @@ async fn process_message_with_module(
     let assistant_message_id = response.assistant_message.id();
     drop(module); // 释放锁
 
+    // 先发布用户消息的情感，使形象在回复前做出反应
+    if let Some(emotion) = response.user_message.emotion() {
+        event_bus.read().await.publish(AppEvent::UserEmotion {
+            session_id: session_id.into(),
+            emotion: to_shared_emotion(emotion),
+        });
+    }
+
+    audit_log.record(&MessageSentEvent {
+        session_id,
+        message_id: response.user_message.id(),
+        content: response.user_message.content().to_string(),
+        is_user: true,
+        timestamp: response.user_message.created_at(),
+    });
+
     // 处理流式事件
     let event_bus_read = event_bus.read().await;
+    let mut framer = StreamFramer::new(session_id.into(), options.combined_frames);
     while let Some(event) = rx.recv().await {
         match event {
             crate::modules::chat::StreamEvent::Chunk(chunk) => {
-                // 将文本转换为口型音素序列
-                let phonemes = text_to_phonemes(&chunk);
-                
-                event_bus_read.publish(AppEvent::MessageChunk(MessageChunk {
+                event_bus_read.publish(framer.frame(chunk));
+            }
+            crate::modules::chat::StreamEvent::ContentFiltered {
+                content,
+                flagged_terms,
+            } => {
+                event_bus_read.publish(AppEvent::ContentFiltered {
                     session_id: session_id.into(),
-                    content: chunk,
-                    tokens: None,
-                    phonemes: Some(phonemes),
-                }));
+                    message_id: assistant_message_id.into(),
+                    content,
+                    flagged_terms,
+                });
+            }
+            crate::modules::chat::StreamEvent::RepetitionStopped { pattern, repeats } => {
+                event_bus_read.publish(AppEvent::RepetitionStopped {
+                    session_id: session_id.into(),
+                    message_id: assistant_message_id.into(),
+                    pattern,
+                    repeats,
+                });
             }
             crate::modules::chat::StreamEvent::Done {
                 full_content,
-                tokens_used: _,
+                tokens_used,
+                timing,
             } => {
+                llm_registry.health().record(&provider_id, true);
+                record_message_complete(
+                    &audit_log,
+                    session_id,
+                    assistant_message_id,
+                    &full_content,
+                    tokens_used,
+                );
+
                 // 分析情感
                 let emotion = analyze_emotion(&full_content);
-                return Ok((assistant_message_id, emotion));
+                return Ok((assistant_message_id, emotion, timing));
             }
             crate::modules::chat::StreamEvent::Error(err) => {
-                return Err(err);
+                // 用户取消不代表提供商不健康
+                if !matches!(err, LLMError::Cancelled) {
+                    llm_registry.health().record(&provider_id, false);
+                }
+                return Err(err.to_string().into());
+            }
+            crate::modules::chat::StreamEvent::SaveFailed {
+                full_content,
+                error,
+            } => {
+                return Err(ProcessError {
+                    error,
+                    content: Some(full_content),
+                });
             }
         }
     }
 
-    Ok((assistant_message_id, None))
+    Ok((assistant_message_id, None, ResponseTiming::default()))
+}
+
+/// 记录助手消息完成事件
+fn record_message_complete(
+    audit_log: &AuditLog,
+    session_id: SessionId,
+    message_id: MessageId,
+    content: &str,
+    tokens_used: Option<u32>,
+) {
+    audit_log.record(&MessageCompleteEvent {
+        session_id,
+        message_id,
+        content: content.to_string(),
+        emotion: None,
+        total_tokens: tokens_used,
+        timestamp: chrono::Utc::now(),
+    });
+}
+
+/// 将流式文本块转换为前端事件
+///
+/// 默认每块发布 `MessageChunk`；启用合并帧时改为发布 `StreamFrame`，
+/// 把音素和相对上一帧变化的情感一并带上，减少流式期间的 IPC 次数
+struct StreamFramer {
+    session_id: Uuid,
+    combined: bool,
+    code_fence: CodeFenceTracker,
+    /// 已输出的内容（仅合并帧用于情感分析）
+    content: String,
+    emotion: Option<Emotion>,
+}
+
+impl StreamFramer {
+    fn new(session_id: Uuid, combined: bool) -> Self {
+        Self {
+            session_id,
+            combined,
+            code_fence: CodeFenceTracker::new(),
+            content: String::new(),
+            emotion: Some(Emotion::Neutral),
+        }
+    }
+
+    fn frame(&mut self, chunk: String) -> AppEvent {
+        // 将文本转换为口型音素序列
+        let phonemes = text_to_phonemes(&chunk);
+        let in_code_block = self.code_fence.feed(&chunk);
+
+        if !self.combined {
+            return AppEvent::MessageChunk(MessageChunk {
+                session_id: self.session_id,
+                content: chunk,
+                tokens: None,
+                phonemes: Some(phonemes),
+                in_code_block,
+            });
+        }
+
+        self.content.push_str(&chunk);
+        let emotion = analyze_emotion(&self.content);
+        let changed = if emotion != self.emotion { emotion } else { None };
+        self.emotion = emotion;
+
+        AppEvent::StreamFrame {
+            session_id: self.session_id,
+            content: chunk,
+            phonemes,
+            emotion: changed,
+            in_code_block,
+        }
+    }
 }
 
 /// 简单的情感分析
@@ -232,19 +843,38 @@ fn analyze_emotion(content: &str) -> Option<Emotion> {
 }
 
 /// 停止生成
+///
+/// 按请求 ID 取消进行中的生成，请求已结束时不做任何操作
 #[tauri::command]
-pub async fn chat_stop_generation(_request: StopGenerationRequest) -> AppResult<()> {
-    // TODO: 在 ChatModule 中实现取消机制
-    tracing::warn!("[chat_stop_generation] Not yet implemented");
+pub async fn chat_stop_generation(
+    cancellations: State<'_, Arc<CancellationRegistry>>,
+    request: StopGenerationRequest,
+) -> AppResult<()> {
+    match &request.request_id {
+        Some(request_id) => {
+            if cancellations.cancel(request_id) {
+                tracing::info!("[chat_stop_generation] Cancelled: {}", request_id);
+            }
+        }
+        None => tracing::warn!(
+            "[chat_stop_generation] No request ID given for session {}",
+            request.session_id
+        ),
+    }
     Ok(())
 }
 
 /// 重新生成消息（不创建新的用户消息）
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn chat_regenerate(
     chat_module: State<'_, Arc<RwLock<ChatModule>>>,
     event_bus: State<'_, Arc<RwLock<EventBus>>>,
     llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    cancellations: State<'_, Arc<CancellationRegistry>>,
+    state: State<'_, AppState>,
     request: RegenerateRequest,
 ) -> AppResult<SendMessageResponse> {
     tracing::info!(
@@ -252,61 +882,77 @@ pub async fn chat_regenerate(
         request.session_id
     );
 
-    let session_id_domain = SessionId::from(request.session_id);
-    let content = request.user_content.clone();
-    let provider_config = request.provider_config.clone();
-
-    let event_bus_clone = event_bus.inner().clone();
-    let chat_module_clone = chat_module.inner().clone();
-    let llm_registry_clone = llm_registry.inner().clone();
-    let request_session_id = request.session_id;
-
-    tokio::spawn(async move {
-        let result = process_regenerate_with_module(
-            session_id_domain,
-            content,
-            provider_config,
-            chat_module_clone.clone(),
-            event_bus_clone.clone(),
-            llm_registry_clone,
-        )
-        .await;
-
-        let event_bus = event_bus_clone.read().await;
+    // 使用 regenerate 命令（不保存用户消息）
+    let mut command = crate::modules::chat::RegenerateCommand::new(
+        SessionId::from(request.session_id),
+        request.user_content.clone(),
+        None,
+        true,
+    );
+    if let Some(message_id) = request.message_id {
+        command = command.with_target_message(MessageId::from(message_id));
+    }
+    if let Some(request_id) = request.request_id {
+        command = command.with_request_id(request_id);
+    }
+    let defaults = session_defaults(
+        &*chat_module.read().await,
+        &config_module,
+        &state,
+        command.session_id,
+    )
+    .await;
+    command.assistant_prefix = request.assistant_prefix.or(defaults.assistant_prefix);
+    command.system_prompt = defaults.system_prompt;
+    command.model = defaults.model;
+    command.temperature = request.temperature.or(defaults.temperature);
+    command.max_tokens = request.max_tokens;
+    command.stop_sequences = request.stop_sequences;
+    command.user_name = request
+        .user_name
+        .or(defaults.user_name)
+        .filter(|name| !name.trim().is_empty());
+    // 在开始生成前拒绝无效参数，让调用方直接收到错误
+    command
+        .validate()
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    let request_id = command.request_id.clone();
 
-        match result {
-            Ok((message_id, emotion)) => {
-                tracing::info!("[chat_regenerate] Message regenerated: {}", message_id);
-                event_bus.publish(AppEvent::MessageComplete {
-                    session_id: request_session_id,
-                    message_id: message_id.into(),
-                    emotion,
-                });
-            }
-            Err(error) => {
-                tracing::error!("[chat_regenerate] Error: {}", error);
-                event_bus.publish(AppEvent::MessageError {
-                    session_id: request_session_id,
-                    error,
-                });
-            }
-        }
-    });
+    let generation = process_regenerate_with_module(
+        command,
+        request.provider_config,
+        chat_module.inner().clone(),
+        event_bus.inner().clone(),
+        llm_registry.inner().clone(),
+        audit_log.inner().clone(),
+        generation_options(&config_module).await.combined_frames,
+    );
+    spawn_generation(
+        "chat_regenerate",
+        request.session_id,
+        request_id.clone(),
+        event_bus.inner().clone(),
+        cancellations.inner().clone(),
+        llm_registry.inner().clone(),
+        generation,
+    );
 
     Ok(SendMessageResponse {
         message_id: Uuid::new_v4(),
+        request_id,
     })
 }
 
 /// 使用 ChatModule 处理重新生成（不保存用户消息）
 async fn process_regenerate_with_module(
-    session_id: SessionId,
-    user_content: String,
+    command: crate::modules::chat::RegenerateCommand,
     provider_config: Option<FrontendProviderConfig>,
     chat_module: Arc<RwLock<ChatModule>>,
     event_bus: Arc<RwLock<EventBus>>,
     llm_registry: Arc<LLMAdapterRegistry>,
-) -> Result<(MessageId, Option<Emotion>), String> {
+    audit_log: Arc<AuditLog>,
+    combined_frames: bool,
+) -> GenerationResult {
     let provider_config = provider_config.ok_or("No provider configuration provided")?;
     let provider_id = provider_config.id.clone();
     let llm_provider_config: LLMProviderConfig = provider_config.into();
@@ -316,9 +962,7 @@ async fn process_regenerate_with_module(
         .await
         .map_err(|e| format!("Failed to create LLM adapter: {}", e))?;
 
-    // 使用 regenerate 命令（不保存用户消息）
-    let command = crate::modules::chat::RegenerateCommand::new(session_id, user_content, None, true);
-
+    let session_id = command.session_id;
     let module = chat_module.read().await;
 
     let (response, mut rx) = module
@@ -330,32 +974,63 @@ async fn process_regenerate_with_module(
     drop(module);
 
     let event_bus_read = event_bus.read().await;
+    let mut framer = StreamFramer::new(session_id.into(), combined_frames);
     while let Some(event) = rx.recv().await {
         match event {
             crate::modules::chat::StreamEvent::Chunk(chunk) => {
-                let phonemes = text_to_phonemes(&chunk);
-                
-                event_bus_read.publish(AppEvent::MessageChunk(MessageChunk {
+                event_bus_read.publish(framer.frame(chunk));
+            }
+            crate::modules::chat::StreamEvent::ContentFiltered {
+                content,
+                flagged_terms,
+            } => {
+                event_bus_read.publish(AppEvent::ContentFiltered {
+                    session_id: session_id.into(),
+                    message_id: assistant_message_id.into(),
+                    content,
+                    flagged_terms,
+                });
+            }
+            crate::modules::chat::StreamEvent::RepetitionStopped { pattern, repeats } => {
+                event_bus_read.publish(AppEvent::RepetitionStopped {
                     session_id: session_id.into(),
-                    content: chunk,
-                    tokens: None,
-                    phonemes: Some(phonemes),
-                }));
+                    message_id: assistant_message_id.into(),
+                    pattern,
+                    repeats,
+                });
             }
             crate::modules::chat::StreamEvent::Done {
                 full_content,
-                tokens_used: _,
+                tokens_used,
+                timing,
             } => {
+                record_message_complete(
+                    &audit_log,
+                    session_id,
+                    assistant_message_id,
+                    &full_content,
+                    tokens_used,
+                );
+
                 let emotion = analyze_emotion(&full_content);
-                return Ok((assistant_message_id, emotion));
+                return Ok((assistant_message_id, emotion, timing));
             }
             crate::modules::chat::StreamEvent::Error(e) => {
-                return Err(e);
+                return Err(e.to_string().into());
+            }
+            crate::modules::chat::StreamEvent::SaveFailed {
+                full_content,
+                error,
+            } => {
+                return Err(ProcessError {
+                    error,
+                    content: Some(full_content),
+                });
             }
         }
     }
 
-    Err("Stream ended unexpectedly".to_string())
+    Err("Stream ended unexpectedly".into())
 }
 
 /// 获取消息列表 - 使用 ChatModule 的 Query
@@ -368,7 +1043,7 @@ pub async fn chat_get_messages(
 
     let module = chat_module.read().await;
     let query =
-        crate::modules::chat::ListMessagesQuery::new(session_id, request.page, request.limit);
+        crate::modules::chat::ListMessagesQuery::paged(session_id, request.page, request.limit);
 
     let response = module
         .list_messages(query)
@@ -376,53 +1051,321 @@ pub async fn chat_get_messages(
         .map_err(|e| crate::shared::AppError::Unknown(e.to_string()))?;
 
     // 转换 domain Message 到 shared Message
-    let messages: Vec<Message> = response
-        .messages
-        .into_iter()
-        .map(|msg| Message {
-            id: msg.id().into(),
-            session_id: request.session_id,
-            role: match msg.role() {
-                MessageRole::User => SharedMessageRole::User,
-                MessageRole::Assistant => SharedMessageRole::Assistant,
-                _ => SharedMessageRole::System,
-            },
-            content: msg.content().to_string(),
-            tokens: None,
-            emotion: msg.emotion().map(|e| match e {
-                crate::modules::chat::domain::Emotion::Neutral => Emotion::Neutral,
-                crate::modules::chat::domain::Emotion::Happy => Emotion::Happy,
-                crate::modules::chat::domain::Emotion::Sad => Emotion::Sad,
-                crate::modules::chat::domain::Emotion::Angry => Emotion::Angry,
-                crate::modules::chat::domain::Emotion::Surprised => Emotion::Surprised,
-                crate::modules::chat::domain::Emotion::Thinking => Emotion::Thinking,
-            }),
-            created_at: msg.created_at(),
-        })
-        .collect();
+    let messages: Vec<Message> = response.messages.iter().map(to_shared_message).collect();
 
     Ok(messages)
 }
 
-/// 获取模型列表请求
+/// 转换 domain Message 到 shared Message
+fn to_shared_role(role: MessageRole) -> SharedMessageRole {
+    match role {
+        MessageRole::User => SharedMessageRole::User,
+        MessageRole::Assistant => SharedMessageRole::Assistant,
+        MessageRole::System => SharedMessageRole::System,
+        MessageRole::ContextBreak => SharedMessageRole::ContextBreak,
+    }
+}
+
+fn to_shared_emotion(emotion: crate::modules::chat::domain::Emotion) -> Emotion {
+    match emotion {
+        crate::modules::chat::domain::Emotion::Neutral => Emotion::Neutral,
+        crate::modules::chat::domain::Emotion::Happy => Emotion::Happy,
+        crate::modules::chat::domain::Emotion::Sad => Emotion::Sad,
+        crate::modules::chat::domain::Emotion::Angry => Emotion::Angry,
+        crate::modules::chat::domain::Emotion::Surprised => Emotion::Surprised,
+        crate::modules::chat::domain::Emotion::Thinking => Emotion::Thinking,
+    }
+}
+
+fn to_shared_message(msg: &crate::modules::chat::domain::Message) -> Message {
+    Message {
+        id: msg.id().into(),
+        session_id: msg.session_id().into(),
+        role: to_shared_role(msg.role()),
+        content: msg.content().to_string(),
+        tokens: None,
+        emotion: msg.emotion().map(to_shared_emotion),
+        created_at: msg.created_at(),
+        finish_reason: msg.finish_reason().map(|reason| match reason {
+            crate::modules::chat::FinishReason::Stop => FinishReason::Stop,
+            crate::modules::chat::FinishReason::Length => FinishReason::Length,
+            crate::modules::chat::FinishReason::ContentFilter => FinishReason::ContentFilter,
+            crate::modules::chat::FinishReason::FunctionCall => FinishReason::FunctionCall,
+        }),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct FetchModelsRequest {
-    pub provider_config: FrontendProviderConfig,
+pub struct EditMessageRequest {
+    pub message_id: Uuid,
+    pub content: String,
 }
 
-/// 模型信息响应
-#[derive(Debug, Serialize)]
+/// 编辑消息内容（用户消息和助手消息均可），返回编辑后的消息
+#[tauri::command]
+pub async fn chat_edit_message(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: EditMessageRequest,
+) -> AppResult<Message> {
+    let command = EditMessageCommand::new(MessageId::from(request.message_id), request.content);
+
+    let module = chat_module.read().await;
+    let response = module
+        .edit_message(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(to_shared_message(&response.message))
+}
+
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ModelInfoResponse {
-    pub id: String,
-    pub name: String,
-    pub owned_by: Option<String>,
+pub struct DeleteMessageRequest {
+    pub message_id: Uuid,
+    /// 删除用户消息时一并删除紧随其后的助手回复
+    #[serde(default)]
+    pub cascade: bool,
 }
 
-/// 获取 API 提供商的模型列表
+/// 删除单条消息，返回是否确实删除了消息
 #[tauri::command]
-pub async fn chat_fetch_models(
+pub async fn chat_delete_message(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: DeleteMessageRequest,
+) -> AppResult<bool> {
+    let command =
+        DeleteMessageCommand::new(MessageId::from(request.message_id)).with_cascade(request.cascade);
+
+    let module = chat_module.read().await;
+    let response = module
+        .delete_message(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(response.removed())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMessagesRequest {
+    pub query: String,
+    /// 为空时搜索所有会话
+    #[serde(default)]
+    pub session_id: Option<Uuid>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSearchResult {
+    pub session_id: Uuid,
+    pub message_id: Uuid,
+    pub role: SharedMessageRole,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub snippet: SearchSnippet,
+}
+
+/// 全文搜索消息（不区分大小写），最新的在前
+#[tauri::command]
+pub async fn chat_search_messages(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: SearchMessagesRequest,
+) -> AppResult<Vec<MessageSearchResult>> {
+    let mut query = SearchMessagesQuery::new(request.query);
+    if let Some(session_id) = request.session_id {
+        query = query.in_session(SessionId::from(session_id));
+    }
+    if let Some(limit) = request.limit {
+        query = query.with_limit(limit);
+    }
+
+    let module = chat_module.read().await;
+    let response = module
+        .search_messages(query)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(response
+        .matches
+        .into_iter()
+        .map(|m| MessageSearchResult {
+            session_id: m.session_id.into(),
+            message_id: m.message_id.into(),
+            role: to_shared_role(m.role),
+            created_at: m.created_at,
+            snippet: m.snippet,
+        })
+        .collect())
+}
+
+/// 插入上下文断点 - 之后的消息不再携带断点之前的历史
+#[tauri::command]
+pub async fn chat_insert_context_break(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: InsertContextBreakRequest,
+) -> AppResult<InsertContextBreakResponse> {
+    let session_id = SessionId::from(request.session_id);
+
+    let module = chat_module.read().await;
+    let response = module
+        .insert_context_break(crate::modules::chat::InsertContextBreakCommand::new(
+            session_id,
+        ))
+        .await
+        .map_err(|e| crate::shared::AppError::Unknown(e.to_string()))?;
+
+    Ok(InsertContextBreakResponse {
+        message_id: response.message.id().into(),
+    })
+}
+
+/// 清空会话的全部消息，保留会话本身；预设设置了开场白时重新插入
+///
+/// 返回删除的消息数量
+#[tauri::command]
+pub async fn chat_clear_session_messages(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    state: State<'_, AppState>,
+    request: ClearSessionMessagesRequest,
+) -> AppResult<usize> {
+    let session_id = SessionId::from(request.session_id);
+
+    let module = chat_module.read().await;
+    let session = module
+        .get_session(GetSessionQuery::new(session_id))
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?
+        .session
+        .ok_or_else(|| AppError::SessionNotFound(request.session_id.to_string()))?;
+
+    let mut command = crate::modules::chat::ClearSessionMessagesCommand::new(session_id);
+    if let Some(preset_id) = session.preset_id() {
+        command.greeting = state
+            .presets
+            .read()
+            .await
+            .get(&preset_id)
+            .and_then(|preset| preset.greeting.clone());
+    }
+
+    let response = module
+        .clear_session_messages(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    tracing::info!(
+        "[chat_clear_session_messages] Cleared {} messages from session {}",
+        response.deleted_messages,
+        request.session_id
+    );
+    Ok(response.deleted_messages)
+}
+
+/// 建议回复请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestRepliesRequest {
+    pub session_id: Uuid,
+    pub provider_id: String,
+    /// 建议数，最多 5 条
+    pub count: usize,
+}
+
+/// 根据当前对话生成几条用户可能发送的简短回复（不保存任何消息）
+#[tauri::command]
+pub async fn chat_suggest_replies(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: SuggestRepliesRequest,
+) -> AppResult<Vec<String>> {
+    let query = crate::modules::chat::SuggestRepliesQuery::new(
+        SessionId::from(request.session_id),
+        request.count,
+    );
+
+    let module = chat_module.read().await;
+    let response = module
+        .suggest_replies(query, &request.provider_id)
+        .await
+        .map_err(|e| crate::shared::AppError::Unknown(e.to_string()))?;
+
+    Ok(response.suggestions)
+}
+
+/// 获取会话生效设置请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetEffectiveSettingsRequest {
+    pub session_id: Uuid,
+}
+
+/// 获取会话合并各层后实际生效的设置，并标注每项的来源
+#[tauri::command]
+pub async fn chat_get_effective_settings(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    state: State<'_, AppState>,
+    request: GetEffectiveSettingsRequest,
+) -> AppResult<EffectiveSettings> {
+    let session = chat_module
+        .read()
+        .await
+        .get_session(GetSessionQuery::new(SessionId::from(request.session_id)))
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?
+        .session
+        .ok_or_else(|| AppError::SessionNotFound(request.session_id.to_string()))?;
+
+    let config = config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?;
+
+    let preset = session_preset(&state, &session).await;
+    let layers = settings_layers(&session, preset.as_ref(), Some(config));
+
+    Ok(resolve_settings(&layers))
+}
+
+/// 模型列表请求的默认超时（独立于生成请求的超时）
+const FETCH_MODELS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 获取模型列表请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchModelsRequest {
+    pub provider_config: FrontendProviderConfig,
+    /// 请求 ID，用于通过 `chat_cancel_fetch_models` 取消
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// 超时（秒），默认 10 秒
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// 取消获取模型列表请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelFetchModelsRequest {
+    pub request_id: String,
+}
+
+/// 模型信息响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfoResponse {
+    pub id: String,
+    pub name: String,
+    pub owned_by: Option<String>,
+}
+
+/// 获取 API 提供商的模型列表
+#[tauri::command]
+pub async fn chat_fetch_models(
+    registry: State<'_, Arc<CancellationRegistry>>,
     request: FetchModelsRequest,
 ) -> AppResult<Vec<ModelInfoResponse>> {
     tracing::info!(
@@ -431,12 +1374,287 @@ pub async fn chat_fetch_models(
         request.provider_config.provider_type
     );
 
-    let config = &request.provider_config;
+    let timeout = request
+        .timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(FETCH_MODELS_TIMEOUT);
+    let signal = request
+        .request_id
+        .as_deref()
+        .map(|id| registry.register(id));
+
+    let result = fetch_models_with_timeout(&request.provider_config, timeout, signal).await;
+
+    if let Some(request_id) = &request.request_id {
+        registry.remove(request_id);
+    }
+    result
+}
+
+/// 取消进行中的模型列表请求
+#[tauri::command]
+pub async fn chat_cancel_fetch_models(
+    registry: State<'_, Arc<CancellationRegistry>>,
+    request: CancelFetchModelsRequest,
+) -> AppResult<()> {
+    if registry.cancel(&request.request_id) {
+        tracing::info!(
+            "[chat_cancel_fetch_models] Cancelled: {}",
+            request.request_id
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetProviderRequest {
+    pub provider_id: String,
+}
+
+/// 丢弃提供商缓存的适配器，下次请求时重新创建（用于连接池卡住等情况）
+///
+/// 返回是否确实移除了缓存的适配器
+#[tauri::command]
+pub async fn chat_reset_provider(
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    request: ResetProviderRequest,
+) -> AppResult<bool> {
+    let removed = llm_registry.invalidate(&request.provider_id).await;
+    tracing::info!(
+        "[chat_reset_provider] Provider {} reset (cached adapter removed: {})",
+        request.provider_id,
+        removed
+    );
+    Ok(removed)
+}
+
+/// 单个提供商健康检查的超时
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 提供商健康状态响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealthResponse {
+    pub provider_id: String,
+    #[serde(flatten)]
+    pub status: HealthStatus,
+}
+
+/// 并发检查所有已注册提供商的健康状态
+///
+/// 单个提供商出错或超时只会标记为不健康，不会导致整个命令失败
+#[tauri::command]
+pub async fn chat_health_check_all(
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+) -> AppResult<Vec<ProviderHealthResponse>> {
+    let results = llm_registry.health_check_all(HEALTH_CHECK_TIMEOUT).await;
+    tracing::info!(
+        "[chat_health_check_all] Checked {} providers ({} unhealthy)",
+        results.len(),
+        results.iter().filter(|(_, s)| !s.is_healthy).count()
+    );
+    Ok(results
+        .into_iter()
+        .map(|(provider_id, status)| ProviderHealthResponse {
+            provider_id,
+            status,
+        })
+        .collect())
+}
+
+/// 健康状态面板复用缓存检查结果的有效期
+const PROVIDERS_HEALTH_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// 健康状态面板同时检查的提供商数量上限
+const PROVIDERS_HEALTH_CONCURRENCY: usize = 4;
+
+/// 健康状态面板中的一行
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStatusResponse {
+    pub provider_id: String,
+    pub name: String,
+    pub is_healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl From<ProviderHealthSummary> for ProviderStatusResponse {
+    fn from(summary: ProviderHealthSummary) -> Self {
+        Self {
+            provider_id: summary.provider_id,
+            name: summary.name,
+            is_healthy: summary.status.is_healthy,
+            latency_ms: summary.status.latency_ms,
+            error: summary.status.error_message,
+        }
+    }
+}
+
+/// 已保存的提供商配置转换为适配器配置，设置中未保存的选项使用默认值
+fn saved_provider_config(provider: SavedProviderConfig) -> LLMProviderConfig {
+    let defaults = LLMProviderConfig::default();
+    LLMProviderConfig {
+        provider_type: ProviderType::resolve(&provider.provider_type),
+        default_model: provider
+            .models
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| defaults.default_model.clone()),
+        id: provider.id,
+        name: provider.name,
+        base_url: provider.base_url,
+        api_key: provider.api_key,
+        ..defaults
+    }
+}
+
+/// 汇总所有已配置提供商的健康状态，供设置界面的状态表一次获取
+///
+/// 最近检查过的提供商直接使用缓存结果，其余以有限并发重新检查；尚未使用过的提供商按已保存的配置创建适配器
+#[tauri::command]
+pub async fn chat_providers_health(
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<Vec<ProviderStatusResponse>> {
+    let configured = config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?
+        .llm
+        .providers
+        .into_values()
+        .map(saved_provider_config)
+        .collect();
+    let summaries = llm_registry
+        .providers_health(
+            configured,
+            HEALTH_CHECK_TIMEOUT,
+            PROVIDERS_HEALTH_MAX_AGE,
+            PROVIDERS_HEALTH_CONCURRENCY,
+        )
+        .await;
+    Ok(summaries.into_iter().map(Into::into).collect())
+}
+
+/// 查询模型功能支持请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSupportsRequest {
+    pub provider_config: FrontendProviderConfig,
+    pub model: String,
+    pub feature: ModelFeature,
+}
+
+/// 查询模型是否支持指定功能（如图片输入），用于决定是否在界面上启用相应功能
+#[tauri::command]
+pub async fn chat_model_supports(
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    request: ModelSupportsRequest,
+) -> AppResult<ModelSupport> {
+    let config: LLMProviderConfig = request.provider_config.into();
+    let llm = llm_registry
+        .get_or_create(&config)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    Ok(resolve_model_support(llm.as_ref(), &request.model, request.feature).await)
+}
+
+/// 优先使用服务端的模型列表，不支持或获取失败时使用适配器内置的列表
+///
+/// 服务端列表只说明模型存在，不含功能信息，功能支持仍以内置列表为准，
+/// 内置列表中没有的模型视为未知
+async fn resolve_model_support(
+    llm: &dyn LLMPort,
+    model: &str,
+    feature: ModelFeature,
+) -> ModelSupport {
+    let info = llm.provider_info();
+    if info.supports_model_listing {
+        match llm.list_models().await {
+            Ok(models) if models.iter().any(|m| m.id == model) => {
+                return ModelSupport::resolve(
+                    &info.models,
+                    model,
+                    feature,
+                    CapabilitySource::Fetched,
+                )
+            }
+            Ok(_) => {
+                return ModelSupport {
+                    supported: false,
+                    source: CapabilitySource::Unknown,
+                }
+            }
+            Err(e) => tracing::warn!(
+                "[chat_model_supports] Failed to list models, using bundled list: {}",
+                e
+            ),
+        }
+    }
+    ModelSupport::resolve(&info.models, model, feature, CapabilitySource::Bundled)
+}
+
+/// 估算 Token 数量请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateTokensRequest {
+    pub text: String,
+    /// 目标模型，提供时可按模型细化估算
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// 估算任意文本的 Token 数量（近似值，用于输入时的实时提示）
+#[tauri::command]
+pub async fn chat_estimate_tokens(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: EstimateTokensRequest,
+) -> AppResult<u32> {
+    let module = chat_module.read().await;
+    Ok(module.estimate_tokens(&request.text, request.model.as_deref()))
+}
+
+/// 在超时和取消信号的限制下获取模型列表
+async fn fetch_models_with_timeout(
+    config: &FrontendProviderConfig,
+    timeout: Duration,
+    signal: Option<CancelSignal>,
+) -> AppResult<Vec<ModelInfoResponse>> {
+    let cancelled = async move {
+        match signal {
+            Some(mut signal) => signal.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        result = tokio::time::timeout(timeout, fetch_models(config)) => {
+            result.map_err(|_| {
+                tracing::warn!("[chat_fetch_models] Timed out after {:?}", timeout);
+                crate::shared::AppError::NetworkError(format!(
+                    "Fetching models timed out after {:?}",
+                    timeout
+                ))
+            })?
+        }
+        _ = cancelled => {
+            tracing::info!("[chat_fetch_models] Cancelled");
+            Err(crate::shared::AppError::Unknown("Fetching models cancelled".to_string()))
+        }
+    }
+}
+
+/// 请求提供商的模型列表接口
+async fn fetch_models(config: &FrontendProviderConfig) -> AppResult<Vec<ModelInfoResponse>> {
     let base_url = config.base_url.trim_end_matches('/');
     
     // 构建请求 URL
     let url = match config.provider_type {
-        ProviderType::OpenAI | ProviderType::Custom => format!("{}/models", base_url),
+        ProviderType::OpenAI | ProviderType::Custom => return fetch_openai_models(config).await,
         ProviderType::Claude => {
             // Claude 不支持列出模型，返回预定义列表
             return Ok(vec![
@@ -447,7 +1665,16 @@ pub async fn chat_fetch_models(
                 ModelInfoResponse { id: "claude-3-opus-20240229".to_string(), name: "Claude 3 Opus".to_string(), owned_by: Some("anthropic".to_string()) },
             ]);
         }
+        ProviderType::Demo => {
+            // 演示模式离线回复，只有一个固定模型
+            return Ok(vec![ModelInfoResponse {
+                id: DEMO_MODEL.to_string(),
+                name: "Demo".to_string(),
+                owned_by: None,
+            }]);
+        }
         ProviderType::Ollama => format!("{}/api/tags", base_url),
+        ProviderType::Gemini => format!("{}/models", base_url),
     };
 
     tracing::debug!("[chat_fetch_models] Requesting: {}", url);
@@ -455,16 +1682,15 @@ pub async fn chat_fetch_models(
     let client = reqwest::Client::new();
     
     let response = match config.provider_type {
-        ProviderType::Ollama => {
-            client.get(&url).send().await
-        }
-        _ => {
+        ProviderType::Gemini => {
             client
                 .get(&url)
-                .header("Authorization", format!("Bearer {}", config.api_key))
+                .header("x-goog-api-key", &config.api_key)
+                .query(&[("pageSize", "1000")])
                 .send()
                 .await
         }
+        _ => client.get(&url).send().await,
     };
 
     let response = response.map_err(|e| {
@@ -476,6 +1702,11 @@ pub async fn chat_fetch_models(
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
         tracing::error!("[chat_fetch_models] API error: {} - {}", status, error_text);
+        if status == reqwest::StatusCode::NOT_FOUND {
+            if let Some(error) = provider_mismatch_error(config, &error_text) {
+                return Err(error);
+            }
+        }
         return Err(crate::shared::AppError::Unknown(format!(
             "API error: {} - {}",
             status, error_text
@@ -484,47 +1715,54 @@ pub async fn chat_fetch_models(
 
     // 解析响应
     let models: Vec<ModelInfoResponse> = match config.provider_type {
-        ProviderType::Ollama => {
+        ProviderType::Gemini => {
             #[derive(Deserialize)]
-            struct OllamaResponse {
-                models: Vec<OllamaModel>,
+            #[serde(rename_all = "camelCase")]
+            struct GeminiModelsResponse {
+                #[serde(default)]
+                models: Vec<GeminiModel>,
             }
             #[derive(Deserialize)]
-            struct OllamaModel {
+            #[serde(rename_all = "camelCase")]
+            struct GeminiModel {
                 name: String,
+                display_name: Option<String>,
             }
-            let resp: OllamaResponse = response.json().await.map_err(|e| {
+            let resp: GeminiModelsResponse = response.json().await.map_err(|e| {
                 crate::shared::AppError::Unknown(format!("Failed to parse response: {}", e))
             })?;
             resp.models
                 .into_iter()
-                .map(|m| ModelInfoResponse {
-                    id: m.name.clone(),
-                    name: m.name,
-                    owned_by: Some("ollama".to_string()),
+                .map(|m| {
+                    // 模型名形如 models/gemini-2.5-flash
+                    let id = m.name.strip_prefix("models/").unwrap_or(&m.name).to_string();
+                    ModelInfoResponse {
+                        name: m.display_name.unwrap_or_else(|| id.clone()),
+                        id,
+                        owned_by: Some("google".to_string()),
+                    }
                 })
                 .collect()
         }
         _ => {
-            // OpenAI 兼容格式
+            // Ollama
             #[derive(Deserialize)]
-            struct OpenAIModelsResponse {
-                data: Vec<OpenAIModel>,
+            struct OllamaResponse {
+                models: Vec<OllamaModel>,
             }
             #[derive(Deserialize)]
-            struct OpenAIModel {
-                id: String,
-                owned_by: Option<String>,
+            struct OllamaModel {
+                name: String,
             }
-            let resp: OpenAIModelsResponse = response.json().await.map_err(|e| {
+            let resp: OllamaResponse = response.json().await.map_err(|e| {
                 crate::shared::AppError::Unknown(format!("Failed to parse response: {}", e))
             })?;
-            resp.data
+            resp.models
                 .into_iter()
                 .map(|m| ModelInfoResponse {
-                    id: m.id.clone(),
-                    name: m.id,
-                    owned_by: m.owned_by,
+                    id: m.name.clone(),
+                    name: m.name,
+                    owned_by: Some("ollama".to_string()),
                 })
                 .collect()
         }
@@ -533,3 +1771,417 @@ pub async fn chat_fetch_models(
     tracing::info!("[chat_fetch_models] Found {} models", models.len());
     Ok(models)
 }
+
+/// 通过 OpenAI 适配器请求 OpenAI 兼容的 `/models` 接口
+async fn fetch_openai_models(
+    config: &FrontendProviderConfig,
+) -> AppResult<Vec<ModelInfoResponse>> {
+    let adapter = OpenAIAdapter::new(config.clone().into())
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+    let models = adapter.fetch_models().await.map_err(|e| {
+        tracing::error!("[chat_fetch_models] Request failed: {}", e);
+        match e {
+            LLMError::ApiError { code, message } => {
+                if code.starts_with("404") {
+                    if let Some(error) = provider_mismatch_error(config, &message) {
+                        return error;
+                    }
+                }
+                AppError::Unknown(format!("API error: {} - {}", code, message))
+            }
+            e => AppError::Unknown(format!("Failed to fetch models: {}", e)),
+        }
+    })?;
+
+    tracing::info!("[chat_fetch_models] Found {} models", models.len());
+    Ok(models
+        .into_iter()
+        .map(|m| ModelInfoResponse {
+            id: m.id.clone(),
+            name: m.id,
+            owned_by: m.owned_by,
+        })
+        .collect())
+}
+
+/// 模型列表接口返回 404 时，检查是否选错了提供商类型
+///
+/// 先根据 base_url 推测，无法判断时再根据错误响应的格式推测（Claude 的错误体为 `{"type":"error",...}`）
+fn provider_mismatch_error(config: &FrontendProviderConfig, error_body: &str) -> Option<AppError> {
+    let detected = ProviderType::detect_from_url(&config.base_url).or_else(|| {
+        let body: serde_json::Value = serde_json::from_str(error_body).ok()?;
+        (body["type"] == "error" && body["error"]["type"].is_string())
+            .then_some(ProviderType::Claude)
+    })?;
+    if detected == config.provider_type {
+        return None;
+    }
+
+    let label = match detected {
+        ProviderType::OpenAI => "OpenAI",
+        ProviderType::Claude => "Claude",
+        ProviderType::Ollama => "Ollama",
+        ProviderType::Gemini => "Gemini",
+        ProviderType::Custom => "custom",
+        ProviderType::Demo => "demo",
+    };
+    tracing::warn!(
+        "[chat_fetch_models] Provider type {:?} looks wrong for {}, detected {:?}",
+        config.provider_type,
+        config.base_url,
+        detected
+    );
+    Some(AppError::ConfigError(format!(
+        "Models endpoint not found (404). This looks like a {} endpoint; try setting the provider type to \"{}\"",
+        label,
+        detected.as_str()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::infrastructure::adapters::llm::MockHttpServer;
+    use std::time::Instant;
+
+    fn provider(base_url: String) -> FrontendProviderConfig {
+        FrontendProviderConfig {
+            id: "slow".to_string(),
+            name: "Slow".to_string(),
+            provider_type: ProviderType::OpenAI,
+            base_url,
+            api_key: String::new(),
+            models: vec![],
+            is_default: false,
+            timeout_secs: None,
+            cold_start_timeout_secs: None,
+            http: HttpClientOptions::default(),
+            proxy_url: None,
+            extra_headers: HashMap::new(),
+            keep_alive: None,
+            num_ctx: None,
+            anthropic_version: None,
+            anthropic_beta: Vec::new(),
+        }
+    }
+
+    /// 接受连接但从不响应的服务器
+    async fn slow_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_models_respects_timeout() {
+        let config = provider(slow_server().await);
+
+        let started = Instant::now();
+        let result = fetch_models_with_timeout(&config, Duration::from_millis(200), None).await;
+
+        assert!(matches!(result, Err(crate::shared::AppError::NetworkError(_))));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_models_cancel() {
+        let config = provider(slow_server().await);
+        let registry = Arc::new(CancellationRegistry::new());
+        let signal = registry.register("fetch-1");
+
+        let canceller = registry.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel("fetch-1");
+        });
+
+        let started = Instant::now();
+        let result = fetch_models_with_timeout(&config, FETCH_MODELS_TIMEOUT, Some(signal)).await;
+
+        assert!(matches!(result, Err(crate::shared::AppError::Unknown(_))));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_model_supports_vision_from_bundled_list() {
+        let registry = LLMAdapterRegistry::new();
+        let config: LLMProviderConfig = provider("http://127.0.0.1:9".to_string()).into();
+        let llm = registry.get_or_create(&config).await.unwrap();
+
+        // 服务不可达时使用内置列表
+        let support = resolve_model_support(llm.as_ref(), "gpt-4o", ModelFeature::Vision).await;
+        assert!(support.supported);
+        assert_eq!(support.source, CapabilitySource::Bundled);
+
+        let support =
+            resolve_model_support(llm.as_ref(), "gpt-3.5-turbo", ModelFeature::Vision).await;
+        assert!(!support.supported);
+        assert_eq!(support.source, CapabilitySource::Bundled);
+    }
+
+    #[tokio::test]
+    async fn test_model_supports_fetched_model_outside_bundled_list() {
+        let base_url = MockHttpServer::fixed(
+            "200 OK",
+            r#"{"data":[{"id":"gpt-4o"},{"id":"qwen2.5-vl-72b-instruct"}]}"#,
+        )
+        .await
+        .url();
+        let registry = LLMAdapterRegistry::new();
+        let config: LLMProviderConfig = provider(base_url).into();
+        let llm = registry.get_or_create(&config).await.unwrap();
+
+        // 内置列表中的模型使用已知功能信息
+        let support = resolve_model_support(llm.as_ref(), "gpt-4o", ModelFeature::Vision).await;
+        assert!(support.supported);
+        assert_eq!(support.source, CapabilitySource::Fetched);
+
+        // 服务端有但内置列表中没有的模型无法确定功能支持
+        let support = resolve_model_support(
+            llm.as_ref(),
+            "qwen2.5-vl-72b-instruct",
+            ModelFeature::Vision,
+        )
+        .await;
+        assert_eq!(support.source, CapabilitySource::Unknown);
+
+        // 服务端列表中没有的模型
+        let support =
+            resolve_model_support(llm.as_ref(), "gpt-3.5-turbo", ModelFeature::Vision).await;
+        assert_eq!(support.source, CapabilitySource::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_models_suggests_claude_for_anthropic_404() {
+        // Anthropic 官方地址配置成 OpenAI 类型
+        let mut config = provider("https://api.anthropic.com/v1".to_string());
+        let error = provider_mismatch_error(&config, "Not Found").unwrap();
+        assert!(matches!(&error, AppError::ConfigError(message)
+            if message.contains("looks like a Claude endpoint") && message.contains("\"claude\"")));
+
+        // 代理地址无法从 URL 判断，根据 Claude 格式的错误体识别
+        let body = r#"{"type":"error","error":{"type":"not_found_error","message":"Not found"}}"#;
+        config.base_url = MockHttpServer::fixed("404 Not Found", body).await.url();
+        let result = fetch_models(&config).await;
+        assert!(matches!(&result, Err(AppError::ConfigError(message))
+            if message.contains("looks like a Claude endpoint")));
+
+        // 类型正确或无法判断时保留原始错误
+        config.provider_type = ProviderType::Claude;
+        assert!(provider_mismatch_error(&config, body).is_none());
+        config.provider_type = ProviderType::OpenAI;
+        config.base_url = MockHttpServer::fixed("404 Not Found", "Not Found").await.url();
+        let result = fetch_models(&config).await;
+        assert!(matches!(&result, Err(AppError::Unknown(message)) if message.contains("404")));
+    }
+
+    /// 接收事件直到生成结束（完成或错误）
+    async fn generation_events(
+        events: &mut tokio::sync::broadcast::Receiver<AppEvent>,
+    ) -> Vec<AppEvent> {
+        let mut received = Vec::new();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(2), events.recv())
+                .await
+                .expect("generation should finish")
+                .unwrap();
+            let finished = matches!(
+                event,
+                AppEvent::MessageComplete { .. } | AppEvent::MessageError { .. }
+            );
+            received.push(event);
+            if finished {
+                return received;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id_flows_to_completion_event() {
+        use crate::modules::chat::test_support::{
+            ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+        use crate::modules::chat::StreamEvent;
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Hello!")).await;
+        let session_id = harness.create_session().await;
+        let event_bus = Arc::new(RwLock::new(EventBus::new()));
+        let mut events = event_bus.read().await.subscribe();
+
+        let command =
+            SendMessageCommand::new(session_id, "Hi", None, true).with_request_id("req-1");
+        let (response, mut rx) = harness
+            .module
+            .send_message_stream(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+        let generation = async move {
+            while let Some(event) = rx.recv().await {
+                if let StreamEvent::Done { timing, .. } = event {
+                    return Ok((response.assistant_message.id(), None, timing));
+                }
+            }
+            Err("Stream ended unexpectedly".into())
+        };
+        spawn_generation(
+            "test",
+            session_id.into(),
+            "req-1".to_string(),
+            event_bus.clone(),
+            Arc::new(CancellationRegistry::new()),
+            Arc::new(LLMAdapterRegistry::new()),
+            generation,
+        );
+
+        let received = generation_events(&mut events).await;
+        assert!(matches!(
+            &received[0],
+            AppEvent::MessageStart { request_id, .. } if request_id == "req-1"
+        ));
+        assert!(matches!(
+            received.last(),
+            Some(AppEvent::MessageComplete { request_id, .. }) if request_id == "req-1"
+        ));
+        assert_eq!(
+            harness.llm.requests()[0].request_id.as_deref(),
+            Some("req-1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_defaults_follow_preset_and_session_config() {
+        use crate::modules::chat::test_support::{ChatTestHarness, ScriptedLLM};
+        use crate::modules::chat::ports::SessionRepository;
+        use crate::modules::chat::CreateSessionCommand;
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Hi")).await;
+        let config_module = RwLock::new(ConfigModule::new_in_memory());
+        let state = AppState::new();
+        let mut preset = Preset::new("Kizuna".to_string(), "You are Kizuna.".to_string());
+        preset.assistant_prefix = Some("Kizuna:".to_string());
+        preset.user_name = Some("Alice".to_string());
+        let preset_id = preset.id;
+        state.presets.write().await.insert(preset_id, preset);
+
+        let session_id = harness
+            .module
+            .create_session(CreateSessionCommand::new(None, Some(preset_id)))
+            .await
+            .unwrap()
+            .session
+            .id();
+        let defaults = session_defaults(&harness.module, &config_module, &state, session_id).await;
+        assert_eq!(defaults.assistant_prefix.as_deref(), Some("Kizuna:"));
+        assert_eq!(defaults.user_name.as_deref(), Some("Alice"));
+        assert_eq!(defaults.system_prompt.as_deref(), Some("You are Kizuna."));
+        // 会话未指定模型时使用提供商的默认模型
+        assert!(defaults.model.is_none());
+
+        // 没有预设的会话不使用前缀和系统提示词
+        let plain_session = harness.create_session().await;
+        let defaults =
+            session_defaults(&harness.module, &config_module, &state, plain_session).await;
+        assert!(defaults.assistant_prefix.is_none());
+        assert!(defaults.system_prompt.is_none());
+        assert!(defaults.user_name.is_none());
+
+        // 会话配置中的模型和温度用于发送
+        let mut session = harness
+            .session_repository
+            .get(plain_session)
+            .await
+            .unwrap()
+            .unwrap();
+        session.set_model_config(serde_json::json!({ "model": "gpt-4o-mini", "temperature": 0.3 }));
+        harness.session_repository.save(&session).await.unwrap();
+        let defaults =
+            session_defaults(&harness.module, &config_module, &state, plain_session).await;
+        assert_eq!(defaults.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(defaults.temperature, Some(0.3));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_targets_request_id() {
+        let event_bus = Arc::new(RwLock::new(EventBus::new()));
+        let mut events = event_bus.read().await.subscribe();
+        let registry = Arc::new(CancellationRegistry::new());
+        let session_id = Uuid::new_v4();
+
+        for request_id in ["req-1", "req-2"] {
+            spawn_generation(
+                "test",
+                session_id,
+                request_id.to_string(),
+                event_bus.clone(),
+                registry.clone(),
+                Arc::new(LLMAdapterRegistry::new()),
+                std::future::pending(),
+            );
+        }
+
+        // 同一会话的两个请求中只取消第二个
+        assert!(registry.cancel("req-2"));
+        let received = loop {
+            let received = generation_events(&mut events).await;
+            if matches!(received.last(), Some(AppEvent::MessageError { .. })) {
+                break received;
+            }
+        };
+        assert!(matches!(
+            received.last(),
+            Some(AppEvent::MessageError { request_id, .. }) if request_id == "req-2"
+        ));
+        assert!(registry.cancel("req-1"));
+    }
+
+    #[test]
+    fn test_attachment_errors_map_to_specific_variants() {
+        let error = attachment_error(AttachmentError::NotAllowed("/etc/passwd".to_string()));
+        assert!(matches!(error, AppError::PermissionDenied(ref m) if m.contains("/etc/passwd")));
+
+        let error = attachment_error(AttachmentError::UnsupportedType("a.pdf".to_string()));
+        assert!(matches!(error, AppError::InvalidAttachment(_)));
+    }
+
+    #[test]
+    fn test_combined_frame_carries_text_phonemes_and_emotion() {
+        let session_id = Uuid::new_v4();
+        let mut framer = StreamFramer::new(session_id, true);
+
+        match framer.frame("I am so happy".to_string()) {
+            AppEvent::StreamFrame {
+                session_id: frame_session,
+                content,
+                phonemes,
+                emotion,
+                in_code_block,
+            } => {
+                assert_eq!(frame_session, session_id);
+                assert_eq!(content, "I am so happy");
+                assert_eq!(phonemes, text_to_phonemes("I am so happy"));
+                assert!(!phonemes.is_empty());
+                assert_eq!(emotion, Some(Emotion::Happy));
+                assert!(!in_code_block);
+            }
+            other => panic!("expected StreamFrame, got {:?}", other),
+        }
+
+        // 情感未变化时不重复发送
+        assert!(matches!(
+            framer.frame(" today".to_string()),
+            AppEvent::StreamFrame { emotion: None, .. }
+        ));
+
+        let mut framer = StreamFramer::new(session_id, false);
+        assert!(matches!(
+            framer.frame("happy".to_string()),
+            AppEvent::MessageChunk(MessageChunk { phonemes: Some(_), .. })
+        ));
+    }
+}
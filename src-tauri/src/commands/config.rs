@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tauri::State;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::infrastructure::AppState;
-use crate::modules::config::domain::AppConfig as DomainAppConfig;
+use crate::infrastructure::{AppState, AuditLog};
+use crate::modules::chat::LLMAdapterRegistry;
+use crate::modules::config::domain::{AppConfig as DomainAppConfig, ConfigChangedEvent};
+use crate::modules::config::{
+    AutoTitleConfig, FirstRunStatus, LLMProviderConfig, PartialProviderConfig, QuietHours,
+};
 use crate::modules::ConfigModule;
 use crate::shared::{AppResult, Preset};
 
@@ -28,6 +35,7 @@ pub struct GeneralConfigResponse {
     pub theme: String,
     pub auto_start: bool,
     pub minimize_to_tray: bool,
+    pub quiet_hours: Option<QuietHours>,
 }
 
 #[derive(Debug, Serialize)]
@@ -59,6 +67,10 @@ pub struct LLMSettingsResponse {
     pub default_provider: String,
     pub stream_response: bool,
     pub context_length: u32,
+    pub max_user_message_chars: u32,
+    pub auto_title: AutoTitleConfig,
+    pub analyze_user_emotion: bool,
+    pub combined_stream_frames: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -81,6 +93,7 @@ impl From<DomainAppConfig> for AppConfigResponse {
                 theme: config.general.theme.as_str().to_string(),
                 auto_start: config.general.auto_start,
                 minimize_to_tray: config.general.minimize_to_tray,
+                quiet_hours: config.general.quiet_hours,
             },
             window: WindowConfigResponse {
                 default_mode: serde_json::to_string(&config.window.default_mode)
@@ -105,6 +118,10 @@ impl From<DomainAppConfig> for AppConfigResponse {
                 default_provider: config.llm.default_provider.clone(),
                 stream_response: config.llm.stream_response,
                 context_length: config.llm.context_length,
+                max_user_message_chars: config.llm.max_user_message_chars,
+                auto_title: config.llm.auto_title,
+                analyze_user_emotion: config.llm.analyze_user_emotion,
+                combined_stream_frames: config.llm.combined_stream_frames,
             },
             model: ModelConfigResponse {
                 default_type: config.model.default_type.clone(),
@@ -121,9 +138,11 @@ impl From<DomainAppConfig> for AppConfigResponse {
 
 #[tauri::command]
 pub async fn config_get_all(
-    config_module: State<'_, ConfigModule>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
 ) -> AppResult<AppConfigResponse> {
     let config = config_module
+        .read()
+        .await
         .get_all()
         .await
         .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
@@ -131,14 +150,148 @@ pub async fn config_get_all(
 }
 
 #[tauri::command]
-pub async fn config_reset(config_module: State<'_, ConfigModule>) -> AppResult<()> {
+pub async fn config_reset(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+) -> AppResult<()> {
     config_module
+        .read()
+        .await
         .reset()
         .await
         .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
+    audit_log.record(&ConfigChangedEvent::new("*"));
     Ok(())
 }
 
+/// 检测是否首次运行，前端据此显示设置向导
+#[tauri::command]
+pub async fn config_is_first_run(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<FirstRunStatus> {
+    config_module
+        .read()
+        .await
+        .first_run_status()
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneProviderRequest {
+    pub source_id: String,
+}
+
+/// 复制提供商配置，返回新配置供编辑
+#[tauri::command]
+pub async fn config_clone_provider(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: CloneProviderRequest,
+) -> AppResult<LLMProviderConfig> {
+    let provider = config_module
+        .read()
+        .await
+        .clone_provider(&request.source_id)
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
+
+    audit_log.record(&ConfigChangedEvent::new("llm.providers"));
+    Ok(provider)
+}
+
+/// 按显示顺序列出提供商
+#[tauri::command]
+pub async fn config_list_providers(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<Vec<LLMProviderConfig>> {
+    config_module
+        .read()
+        .await
+        .list_providers()
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorderProvidersRequest {
+    pub ordered_ids: Vec<String>,
+}
+
+/// 重排提供商显示顺序，ID 集合必须与现有提供商完全一致
+#[tauri::command]
+pub async fn config_reorder_providers(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: ReorderProvidersRequest,
+) -> AppResult<Vec<LLMProviderConfig>> {
+    let providers = config_module
+        .read()
+        .await
+        .reorder_providers(request.ordered_ids)
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
+
+    audit_log.record(&ConfigChangedEvent::new("llm.providers"));
+    Ok(providers)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetQuietHoursRequest {
+    /// 免打扰时段（`HH:MM`），为空时取消
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// 设置免打扰时段，开始晚于结束时跨越午夜
+#[tauri::command]
+pub async fn config_set_quiet_hours(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: SetQuietHoursRequest,
+) -> AppResult<Option<QuietHours>> {
+    let quiet_hours = config_module
+        .read()
+        .await
+        .set_quiet_hours(request.quiet_hours)
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
+
+    audit_log.record(&ConfigChangedEvent::new("general.quietHours"));
+    Ok(quiet_hours)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProviderRequest {
+    pub id: String,
+    pub partial: PartialProviderConfig,
+}
+
+/// 部分更新提供商配置，未提供的字段（包括 API Key）保持不变
+#[tauri::command]
+pub async fn config_update_provider(
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: UpdateProviderRequest,
+) -> AppResult<LLMProviderConfig> {
+    let provider = config_module
+        .read()
+        .await
+        .update_provider(&request.id, request.partial)
+        .await
+        .map_err(|e| crate::shared::AppError::ConfigError(e.to_string()))?;
+
+    // 丢弃按旧配置创建的适配器
+    llm_registry.invalidate(&provider.id).await;
+
+    audit_log.record(&ConfigChangedEvent::new("llm.providers"));
+    Ok(provider)
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreatePresetRequest {
@@ -147,6 +300,9 @@ pub struct CreatePresetRequest {
     pub avatar: Option<String>,
     pub model_type: Option<String>,
     pub model_path: Option<String>,
+    pub assistant_prefix: Option<String>,
+    pub greeting: Option<String>,
+    pub user_name: Option<String>,
 }
 
 #[tauri::command]
@@ -170,6 +326,9 @@ pub async fn preset_create(
     if let Some(model_path) = request.model_path {
         preset.model_path = model_path;
     }
+    preset.assistant_prefix = request.assistant_prefix;
+    preset.greeting = request.greeting;
+    preset.user_name = request.user_name.filter(|name| !name.trim().is_empty());
 
     let id = preset.id;
     let mut presets = state.presets.write().await;
@@ -193,3 +352,163 @@ pub async fn preset_delete(
     presets.remove(&request.id);
     Ok(())
 }
+
+/// 导出全部预设为 JSON 数组，按创建时间排序
+#[tauri::command]
+pub async fn preset_export(state: State<'_, AppState>) -> AppResult<String> {
+    let presets = state.presets.read().await;
+    export_presets(&presets)
+}
+
+/// 导入时与已有预设同名的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PresetConflictPolicy {
+    /// 作为新预设导入，与同名预设并存
+    #[default]
+    KeepBoth,
+    /// 覆盖同名预设的内容，保留其 ID
+    Replace,
+    /// 跳过同名预设
+    Skip,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPresetsRequest {
+    pub json: String,
+    #[serde(default)]
+    pub on_conflict: PresetConflictPolicy,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPresetsResponse {
+    /// 新增或覆盖的预设
+    pub imported: Vec<Preset>,
+    /// 因同名而跳过的预设名称
+    pub skipped: Vec<String>,
+}
+
+/// 导入预设，全部校验通过后才会写入
+#[tauri::command]
+pub async fn preset_import(
+    state: State<'_, AppState>,
+    request: ImportPresetsRequest,
+) -> AppResult<ImportPresetsResponse> {
+    let mut presets = state.presets.write().await;
+    import_presets(&mut presets, &request.json, request.on_conflict)
+}
+
+fn export_presets(presets: &HashMap<Uuid, Preset>) -> AppResult<String> {
+    let mut list: Vec<&Preset> = presets.values().collect();
+    list.sort_by_key(|preset| preset.created_at);
+    serde_json::to_string_pretty(&list)
+        .map_err(|e| crate::shared::AppError::SerializationError(e.to_string()))
+}
+
+fn import_presets(
+    presets: &mut HashMap<Uuid, Preset>,
+    json: &str,
+    on_conflict: PresetConflictPolicy,
+) -> AppResult<ImportPresetsResponse> {
+    let incoming: Vec<Preset> = serde_json::from_str(json)
+        .map_err(|e| crate::shared::AppError::SerializationError(e.to_string()))?;
+    for (index, preset) in incoming.iter().enumerate() {
+        preset.validate().map_err(|e| {
+            crate::shared::AppError::ConfigError(format!("Preset #{}: {}", index + 1, e))
+        })?;
+    }
+
+    let mut imported = Vec::new();
+    let mut skipped = Vec::new();
+    for mut preset in incoming {
+        let existing_id = presets
+            .values()
+            .find(|existing| existing.name == preset.name)
+            .map(|existing| existing.id);
+
+        preset.id = match (existing_id, on_conflict) {
+            (Some(_), PresetConflictPolicy::Skip) => {
+                skipped.push(preset.name);
+                continue;
+            }
+            (Some(id), PresetConflictPolicy::Replace) => id,
+            _ => Uuid::new_v4(),
+        };
+        presets.insert(preset.id, preset.clone());
+        imported.push(preset);
+    }
+
+    Ok(ImportPresetsResponse { imported, skipped })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str) -> Preset {
+        Preset::new(name.to_string(), format!("You are {}.", name))
+    }
+
+    #[test]
+    fn test_presets_round_trip_with_fresh_ids() {
+        let mut source = HashMap::new();
+        for preset in [preset("Hiyori"), preset("Haru")] {
+            source.insert(preset.id, preset);
+        }
+        let json = export_presets(&source).unwrap();
+
+        let mut target = HashMap::new();
+        let response = import_presets(&mut target, &json, PresetConflictPolicy::KeepBoth).unwrap();
+
+        assert_eq!(response.imported.len(), 2);
+        assert!(response.skipped.is_empty());
+        for imported in &response.imported {
+            assert!(!source.contains_key(&imported.id));
+            assert_eq!(target[&imported.id].name, imported.name);
+            let original = source.values().find(|p| p.name == imported.name).unwrap();
+            assert_eq!(imported.system_prompt, original.system_prompt);
+        }
+    }
+
+    #[test]
+    fn test_import_conflict_policies_and_validation() {
+        let existing = preset("Hiyori");
+        let existing_id = existing.id;
+        let mut presets = HashMap::from([(existing_id, existing)]);
+
+        let mut updated = preset("Hiyori");
+        updated.system_prompt = "You are a cheerful assistant.".to_string();
+        let json = serde_json::to_string(&[updated]).unwrap();
+
+        let response = import_presets(&mut presets, &json, PresetConflictPolicy::Skip).unwrap();
+        assert_eq!(response.skipped, vec!["Hiyori".to_string()]);
+        assert_eq!(presets.len(), 1);
+
+        let response = import_presets(&mut presets, &json, PresetConflictPolicy::Replace).unwrap();
+        assert_eq!(response.imported[0].id, existing_id);
+        assert_eq!(presets.len(), 1);
+        assert_eq!(
+            presets[&existing_id].system_prompt,
+            "You are a cheerful assistant."
+        );
+
+        import_presets(&mut presets, &json, PresetConflictPolicy::KeepBoth).unwrap();
+        assert_eq!(presets.len(), 2);
+
+        // 任意一个预设无效时整体拒绝，不写入任何预设
+        let mut invalid = preset("Broken");
+        invalid.model_type = "sprite".to_string();
+        let json = serde_json::to_string(&[preset("Valid"), invalid]).unwrap();
+        let result = import_presets(&mut presets, &json, PresetConflictPolicy::KeepBoth);
+        assert!(matches!(
+            result,
+            Err(crate::shared::AppError::ConfigError(_))
+        ));
+        assert_eq!(presets.len(), 2);
+
+        let json = serde_json::to_string(&[preset("")]).unwrap();
+        assert!(import_presets(&mut presets, &json, PresetConflictPolicy::KeepBoth).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+// Diagnostics Commands
+//
+// 诊断信息相关的 Tauri 命令
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::RwLock;
+
+use crate::infrastructure::{EventBus, FsBackupStore, StorageUsage};
+use crate::modules::chat::{
+    CacheStats, ChatModule, CompactStoresCommand, LLMAdapterRegistry, ReconcileStoresCommand,
+    ReconcileStoresResponse,
+};
+use crate::modules::ConfigModule;
+use crate::shared::{AppError, AppResult};
+
+/// 查询应用数据的磁盘占用
+#[tauri::command]
+pub async fn diagnostics_storage_usage(app: AppHandle) -> AppResult<StorageUsage> {
+    storage_usage(data_dir(&app)?).await
+}
+
+/// 立即将会话、消息和配置写入磁盘（如在备份前调用）
+#[tauri::command]
+pub async fn diagnostics_flush(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<()> {
+    flush_all(&*chat_module.read().await, &*config_module.read().await).await?;
+    tracing::info!("[diagnostics_flush] Flushed repositories to disk");
+    Ok(())
+}
+
+/// 查询各缓存（适配器、能力探测、会话和消息仓储）的命中 / 未命中次数和条目数
+#[tauri::command]
+pub async fn diagnostics_cache_stats(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+) -> AppResult<Vec<CacheStats>> {
+    Ok(cache_stats(&*chat_module.read().await, &llm_registry).await)
+}
+
+/// 清空会话和消息缓存及各适配器的能力探测结果，并重置全部统计
+#[tauri::command]
+pub async fn diagnostics_clear_caches(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    llm_registry: State<'_, Arc<LLMAdapterRegistry>>,
+) -> AppResult<()> {
+    clear_caches(&*chat_module.read().await, &llm_registry).await;
+    tracing::info!("[diagnostics_clear_caches] Cleared caches and statistics");
+    Ok(())
+}
+
+/// 重新设置 EventBus 的 AppHandle（前端收不到事件时使用）
+#[tauri::command]
+pub async fn diagnostics_reconnect_event_bus(
+    app: AppHandle,
+    event_bus: State<'_, Arc<RwLock<EventBus>>>,
+) -> AppResult<()> {
+    event_bus.write().await.set_app_handle(app);
+    tracing::info!("[diagnostics_reconnect_event_bus] Reconnected event bus");
+    Ok(())
+}
+
+/// 检查存储一致性请求
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileRequest {
+    /// 是否为孤立消息创建占位会话，默认只检查
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// 会话不存在的一组消息
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanMessagesResponse {
+    pub session_id: String,
+    pub message_count: usize,
+}
+
+/// 检查存储一致性响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileResponse {
+    pub orphan_messages: Vec<OrphanMessagesResponse>,
+    pub empty_sessions: Vec<String>,
+    /// 已为孤立消息创建的占位会话
+    pub created_sessions: Vec<String>,
+}
+
+impl From<ReconcileStoresResponse> for ReconcileResponse {
+    fn from(response: ReconcileStoresResponse) -> Self {
+        let reconciliation = response.reconciliation;
+        Self {
+            orphan_messages: reconciliation
+                .orphan_messages
+                .into_iter()
+                .map(|orphan| OrphanMessagesResponse {
+                    session_id: orphan.session_id.to_string(),
+                    message_count: orphan.message_count,
+                })
+                .collect(),
+            empty_sessions: reconciliation
+                .empty_sessions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            created_sessions: response
+                .created_sessions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// 检查会话与消息存储是否一致（如崩溃导致的孤立消息），可选修复
+#[tauri::command]
+pub async fn diagnostics_reconcile(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: ReconcileRequest,
+) -> AppResult<ReconcileResponse> {
+    let response = chat_module
+        .read()
+        .await
+        .reconcile_stores(ReconcileStoresCommand::new(request.repair))
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    tracing::info!(
+        "[diagnostics_reconcile] {} orphan groups, {} empty sessions, {} sessions created",
+        response.reconciliation.orphan_messages.len(),
+        response.reconciliation.empty_sessions.len(),
+        response.created_sessions.len()
+    );
+    Ok(response.into())
+}
+
+/// 压缩数据存储响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactResponse {
+    /// 移除的空消息条目数
+    pub removed_entries: usize,
+    /// 按保留数量清理的备份
+    pub pruned_backups: Vec<String>,
+    /// 数据目录减少的字节数
+    pub reclaimed_bytes: u64,
+}
+
+/// 压缩数据存储：移除空条目并重写存储文件，再按保留数量清理旧备份
+#[tauri::command]
+pub async fn diagnostics_compact(
+    app: AppHandle,
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+) -> AppResult<CompactResponse> {
+    let data_dir = data_dir(&app)?;
+    let keep = config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))?
+        .backup
+        .keep;
+    let before = storage_usage(data_dir.clone()).await?;
+
+    let removed_entries = chat_module
+        .read()
+        .await
+        .compact_stores(CompactStoresCommand::new())
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?
+        .removed_entries;
+    let pruned_backups =
+        crate::infrastructure::prune_backups(&FsBackupStore::new(data_dir.clone()), keep as usize)?;
+
+    let after = storage_usage(data_dir).await?;
+    let reclaimed_bytes = before.total_bytes.saturating_sub(after.total_bytes);
+    tracing::info!(
+        "[diagnostics_compact] Removed {} empty entries, pruned {} backups, reclaimed {} bytes",
+        removed_entries,
+        pruned_backups.len(),
+        reclaimed_bytes
+    );
+    Ok(CompactResponse {
+        removed_entries,
+        pruned_backups,
+        reclaimed_bytes,
+    })
+}
+
+fn data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+async fn storage_usage(data_dir: PathBuf) -> AppResult<StorageUsage> {
+    let usage =
+        tokio::task::spawn_blocking(move || crate::infrastructure::storage_usage(&data_dir))
+            .await
+            .map_err(|e| AppError::Unknown(e.to_string()))??;
+    Ok(usage)
+}
+
+async fn flush_all(chat_module: &ChatModule, config_module: &ConfigModule) -> AppResult<()> {
+    chat_module
+        .flush()
+        .await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+    config_module
+        .flush()
+        .await
+        .map_err(|e| AppError::ConfigError(e.to_string()))
+}
+
+async fn cache_stats(
+    chat_module: &ChatModule,
+    llm_registry: &LLMAdapterRegistry,
+) -> Vec<CacheStats> {
+    let mut stats = vec![
+        llm_registry.cache_stats().await,
+        llm_registry.capability_cache_stats().await,
+    ];
+    stats.extend(chat_module.cache_stats());
+    stats
+}
+
+async fn clear_caches(chat_module: &ChatModule, llm_registry: &LLMAdapterRegistry) {
+    chat_module.clear_caches();
+    llm_registry.reset_cache_stats();
+    llm_registry.clear_capability_caches().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::{CreateSessionCommand, GetSessionQuery};
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn read(path: &Path) -> String {
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flush_writes_in_memory_state_to_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let chat_module =
+            ChatModule::new_with_persistence(data_dir.clone(), Arc::new(LLMAdapterRegistry::new()))
+                .await
+                .unwrap();
+        let config_module = ConfigModule::new_with_store(data_dir.clone());
+
+        let session = chat_module
+            .create_session(CreateSessionCommand::new(Some("Flushed".to_string()), None))
+            .await
+            .unwrap()
+            .session;
+        let mut config = config_module.get_all().await.unwrap();
+        config.llm.context_length = 42;
+        config_module
+            .service()
+            .repository()
+            .save(&config)
+            .await
+            .unwrap();
+
+        // 模拟文件被外部移除，内存中的数据仍在
+        for file in ["sessions.json", "messages.json", "config.json"] {
+            let _ = std::fs::remove_file(data_dir.join(file));
+        }
+
+        flush_all(&chat_module, &config_module).await.unwrap();
+
+        assert!(read(&data_dir.join("sessions.json")).contains(&session.id().to_string()));
+        assert!(data_dir.join("messages.json").exists());
+        let config: serde_json::Value =
+            serde_json::from_str(&read(&data_dir.join("config.json"))).unwrap();
+        assert_eq!(config["llm"]["contextLength"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_cache_stats_cover_every_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let llm_registry = Arc::new(LLMAdapterRegistry::new());
+        let chat_module =
+            ChatModule::new_with_persistence(temp_dir.path().to_path_buf(), llm_registry.clone())
+                .await
+                .unwrap();
+        let session = chat_module
+            .create_session(CreateSessionCommand::new(None, None))
+            .await
+            .unwrap()
+            .session;
+        chat_module
+            .get_session(GetSessionQuery::new(session.id()))
+            .await
+            .unwrap();
+
+        let stats = cache_stats(&chat_module, &llm_registry).await;
+        let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["llm_adapters", "capabilities", "sessions", "messages"]
+        );
+        assert_eq!(stats[2].entries, 1);
+
+        clear_caches(&chat_module, &llm_registry).await;
+        let stats = cache_stats(&chat_module, &llm_registry).await;
+        assert!(stats
+            .iter()
+            .all(|s| (s.hits, s.misses, s.entries) == (0, 0, 0)));
+    }
+}
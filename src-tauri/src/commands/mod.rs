@@ -1,9 +1,15 @@
+pub mod autostart;
+pub mod backup;
 pub mod chat;
 pub mod config;
+pub mod diagnostics;
 pub mod session;
 pub mod window;
 
+pub use autostart::*;
+pub use backup::*;
 pub use chat::*;
 pub use config::*;
+pub use diagnostics::*;
 pub use session::*;
 pub use window::*;
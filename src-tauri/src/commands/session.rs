@@ -3,16 +3,24 @@
 // 使用 ChatModule 的 CQRS 命令和查询处理会话操作
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::infrastructure::{AppState, AuditLog};
+use crate::modules::chat::domain::{
+    Session as DomainSession, SessionCreatedEvent, SessionDeletedEvent,
+};
 use crate::modules::chat::{
-    ChatModule, CreateSessionCommand, DeleteSessionCommand, GetSessionQuery, ListSessionsQuery,
+    AutoTitlePolicy, ChatModule, CreateSessionCommand, DeleteSessionCommand, ExportSessionQuery,
+    ForkSessionCommand, GetSessionQuery, ImportSessionCommand, ListSessionsQuery, MessageId,
     SessionId, UpdateSessionCommand,
 };
-use crate::shared::{AppError, AppResult, Session};
+use crate::modules::config::AutoTitleConfig;
+use crate::modules::ConfigModule;
+use crate::shared::{AppError, AppResult, Preset, Session};
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -53,18 +61,66 @@ pub struct RenameSessionRequest {
     pub title: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDisplayNameRequest {
+    pub id: Uuid,
+    /// 为空时清除显示名称
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetModelConfigRequest {
+    pub id: Uuid,
+    /// 模型和采样参数（如 `temperature`、`topP`），为空时清除
+    pub model_config: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionRequest {
+    pub session_id: Uuid,
+    /// 分叉点，该消息及之前的消息会被复制到新会话
+    pub up_to_message_id: Uuid,
+}
+
+/// 转换为前端会话 DTO
+fn to_dto(session: &DomainSession) -> Session {
+    Session {
+        id: session.id().into(),
+        title: session.title().to_string(),
+        preset_id: session.preset_id(),
+        display_name: session.display_name().map(str::to_string),
+        model_config: session.model_config().cloned(),
+        created_at: session.created_at(),
+        updated_at: session.updated_at(),
+    }
+}
+
+/// 预设的名称，作为新会话的默认助手显示名称
+fn preset_display_name(presets: &HashMap<Uuid, Preset>, preset_id: Option<Uuid>) -> Option<String> {
+    preset_id
+        .and_then(|id| presets.get(&id))
+        .map(|preset| preset.name.clone())
+}
+
 /// 创建会话 - 使用 ChatModule
 #[tauri::command]
 pub async fn session_create(
     chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    state: State<'_, AppState>,
+    audit_log: State<'_, Arc<AuditLog>>,
     request: CreateSessionRequest,
 ) -> AppResult<Session> {
+    let display_name = preset_display_name(&*state.presets.read().await, request.preset_id);
     let module = chat_module.read().await;
 
     let command = CreateSessionCommand::new(
         Some("New Chat".to_string()),
         request.preset_id.map(|id| id.into()),
-    );
+    )
+    .with_display_name(display_name);
 
     let response = module
         .create_session(command)
@@ -73,14 +129,13 @@ pub async fn session_create(
 
     let domain_session = response.session;
 
-    Ok(Session {
-        id: domain_session.id().into(),
+    audit_log.record(&SessionCreatedEvent {
+        session_id: domain_session.id(),
         title: domain_session.title().to_string(),
-        model_config: None,
-        preset_id: domain_session.preset_id().map(|id| id.into()),
-        created_at: domain_session.created_at(),
-        updated_at: domain_session.updated_at(),
-    })
+        timestamp: domain_session.created_at(),
+    });
+
+    Ok(to_dto(&domain_session))
 }
 
 /// 列出会话 - 使用 ChatModule
@@ -98,18 +153,7 @@ pub async fn session_list(
         .await
         .map_err(|e| AppError::Unknown(e.to_string()))?;
 
-    let sessions: Vec<Session> = response
-        .sessions
-        .into_iter()
-        .map(|s| Session {
-            id: s.id().into(),
-            title: s.title().to_string(),
-            model_config: None,
-            preset_id: s.preset_id().map(|id| id.into()),
-            created_at: s.created_at(),
-            updated_at: s.updated_at(),
-        })
-        .collect();
+    let sessions: Vec<Session> = response.sessions.into_iter().map(|s| to_dto(&s)).collect();
 
     Ok(ListSessionsResponse {
         sessions,
@@ -137,20 +181,14 @@ pub async fn session_get(
         .session
         .ok_or_else(|| AppError::SessionNotFound(request.id.to_string()))?;
 
-    Ok(Session {
-        id: domain_session.id().into(),
-        title: domain_session.title().to_string(),
-        model_config: None,
-        preset_id: domain_session.preset_id().map(|id| id.into()),
-        created_at: domain_session.created_at(),
-        updated_at: domain_session.updated_at(),
-    })
+    Ok(to_dto(&domain_session))
 }
 
 /// 删除会话 - 使用 ChatModule
 #[tauri::command]
 pub async fn session_delete(
     chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
     request: DeleteSessionRequest,
 ) -> AppResult<()> {
     let module = chat_module.read().await;
@@ -163,6 +201,11 @@ pub async fn session_delete(
         .await
         .map_err(|e| AppError::Unknown(e.to_string()))?;
 
+    audit_log.record(&SessionDeletedEvent {
+        session_id,
+        timestamp: chrono::Utc::now(),
+    });
+
     Ok(())
 }
 
@@ -184,3 +227,181 @@ pub async fn session_rename(
 
     Ok(())
 }
+
+/// 设置会话的助手显示名称（仅用于界面展示）
+#[tauri::command]
+pub async fn session_set_display_name(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: SetDisplayNameRequest,
+) -> AppResult<Session> {
+    let module = chat_module.read().await;
+    let session_id = SessionId::from(request.id);
+
+    let command =
+        UpdateSessionCommand::new(session_id, None, None).with_display_name(request.display_name);
+
+    let response = module
+        .update_session(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(to_dto(&response.session))
+}
+
+/// 设置会话的模型配置，采样参数超出范围时截断
+#[tauri::command]
+pub async fn session_set_model_config(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: SetModelConfigRequest,
+) -> AppResult<Session> {
+    let module = chat_module.read().await;
+    let session_id = SessionId::from(request.id);
+
+    let command =
+        UpdateSessionCommand::new(session_id, None, None).with_model_config(request.model_config);
+
+    let response = module
+        .update_session(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    Ok(to_dto(&response.session))
+}
+
+/// 在指定消息处分支出新会话，返回新会话
+#[tauri::command]
+pub async fn session_fork(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: ForkSessionRequest,
+) -> AppResult<Session> {
+    let module = chat_module.read().await;
+
+    let command = ForkSessionCommand::new(
+        SessionId::from(request.session_id),
+        MessageId::from(request.up_to_message_id),
+    );
+
+    let response = module
+        .fork_session(command)
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    audit_log.record(&SessionCreatedEvent {
+        session_id: response.session.id(),
+        title: response.session.title().to_string(),
+        timestamp: response.session.created_at(),
+    });
+
+    Ok(to_dto(&response.session))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSessionRequest {
+    pub session_id: Uuid,
+}
+
+/// 导出会话及其全部消息，返回 JSON 字符串
+#[tauri::command]
+pub async fn session_export(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    request: ExportSessionRequest,
+) -> AppResult<String> {
+    let module = chat_module.read().await;
+    let export = module
+        .export_session(ExportSessionQuery::new(SessionId::from(request.session_id)))
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    serde_json::to_string_pretty(&export).map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSessionRequest {
+    pub json: String,
+}
+
+/// 从导出的 JSON 导入会话，返回新创建的会话
+#[tauri::command]
+pub async fn session_import(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    audit_log: State<'_, Arc<AuditLog>>,
+    request: ImportSessionRequest,
+) -> AppResult<Session> {
+    let module = chat_module.read().await;
+    let response = module
+        .import_session(ImportSessionCommand::new(request.json))
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    audit_log.record(&SessionCreatedEvent {
+        session_id: response.session.id(),
+        title: response.session.title().to_string(),
+        timestamp: response.session.created_at(),
+    });
+
+    Ok(to_dto(&response.session))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShouldAutoTitleRequest {
+    pub session_id: Uuid,
+}
+
+/// 按配置的 llm.autoTitle 策略判断是否应为会话自动生成标题
+#[tauri::command]
+pub async fn session_should_auto_title(
+    chat_module: State<'_, Arc<RwLock<ChatModule>>>,
+    config_module: State<'_, Arc<RwLock<ConfigModule>>>,
+    request: ShouldAutoTitleRequest,
+) -> AppResult<bool> {
+    let config = config_module
+        .read()
+        .await
+        .get_all()
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))?;
+
+    let module = chat_module.read().await;
+    module
+        .should_auto_title(
+            SessionId::from(request.session_id),
+            auto_title_policy(config.llm.auto_title),
+        )
+        .await
+        .map_err(|e| AppError::Unknown(e.to_string()))
+}
+
+fn auto_title_policy(config: AutoTitleConfig) -> AutoTitlePolicy {
+    match config {
+        AutoTitleConfig::Off => AutoTitlePolicy::Off,
+        AutoTitleConfig::AfterFirstReply => AutoTitlePolicy::AfterFirstReply,
+        AutoTitleConfig::AfterNMessages(n) => AutoTitlePolicy::AfterNMessages(n as usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_name_defaults_from_preset() {
+        let preset = Preset::new("Kizuna".to_string(), String::new());
+        let preset_id = preset.id;
+        let presets = HashMap::from([(preset_id, preset)]);
+
+        assert_eq!(
+            preset_display_name(&presets, Some(preset_id)),
+            Some("Kizuna".to_string())
+        );
+        assert_eq!(preset_display_name(&presets, Some(Uuid::new_v4())), None);
+        assert_eq!(preset_display_name(&presets, None), None);
+
+        let mut session = DomainSession::new(None, Some(preset_id));
+        session.set_display_name(preset_display_name(&presets, Some(preset_id)));
+        assert_eq!(to_dto(&session).display_name.as_deref(), Some("Kizuna"));
+    }
+}
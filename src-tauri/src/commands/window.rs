@@ -4,7 +4,7 @@ use tauri::{State, WebviewWindow};
 use tokio::sync::RwLock;
 
 use crate::infrastructure::{AppEvent, EventBus};
-use crate::modules::window::{WindowConfig, WindowLabel, WindowMode, WindowState};
+use crate::modules::window::{MonitorInfo, WindowConfig, WindowLabel, WindowMode, WindowState};
 use crate::modules::WindowModule;
 use crate::shared::{AppError, AppResult, WindowMode as SharedWindowMode};
 
@@ -20,6 +20,24 @@ pub struct SetAlwaysOnTopRequest {
     pub value: bool,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDesktopModeRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDragRegionRequest {
+    pub in_drag_region: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDragLockedRequest {
+    pub locked: bool,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateWindowRequest {
@@ -30,6 +48,27 @@ pub struct CreateWindowRequest {
     pub mode: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTitleRequest {
+    pub label: Option<String>,
+    pub title: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveToMonitorRequest {
+    pub label: Option<String>,
+    pub monitor_index: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveToMonitorResponse {
+    pub x: i32,
+    pub y: i32,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WindowInfo {
@@ -39,6 +78,7 @@ pub struct WindowInfo {
     pub is_focused: bool,
     pub width: u32,
     pub height: u32,
+    pub is_transparent: bool,
 }
 
 impl From<WindowState> for WindowInfo {
@@ -50,18 +90,21 @@ impl From<WindowState> for WindowInfo {
             is_focused: state.is_focused,
             width: state.current_size.width,
             height: state.current_size.height,
+            is_transparent: state.is_transparent,
         }
     }
 }
 
 #[tauri::command]
 pub async fn window_create(
-    window_module: State<'_, WindowModule>,
+    window_module: State<'_, Arc<WindowModule>>,
+    event_bus: State<'_, Arc<RwLock<EventBus>>>,
     request: CreateWindowRequest,
 ) -> AppResult<WindowInfo> {
     let mode = match request.mode.as_deref() {
         Some("pet") => WindowMode::Pet,
         Some("compact") => WindowMode::Compact,
+        Some("desktop") => WindowMode::Desktop,
         _ => WindowMode::Normal,
     };
 
@@ -76,9 +119,10 @@ pub async fn window_create(
         position: None,
         decorations: mode == WindowMode::Normal,
         always_on_top: mode == WindowMode::Pet,
-        transparent: mode == WindowMode::Pet,
-        resizable: mode != WindowMode::Pet,
-        skip_taskbar: mode == WindowMode::Pet,
+        always_on_bottom: mode.requires_always_on_bottom(),
+        transparent: mode.is_pet_mode(),
+        resizable: !mode.is_pet_mode(),
+        skip_taskbar: mode.is_pet_mode(),
         visible: true,
     };
 
@@ -87,11 +131,27 @@ pub async fn window_create(
         .await
         .map_err(|e| AppError::WindowError(e.to_string()))?;
 
+    // 需要透明背景的模式退回了不透明窗口时通知前端
+    let requires_transparent = window_module
+        .mode_registry()
+        .get(mode)
+        .is_some_and(|strategy| strategy.requires_transparent());
+    if requires_transparent && !state.is_transparent {
+        event_bus
+            .read()
+            .await
+            .publish(AppEvent::WindowTransparencyUnavailable {
+                label: state.label.to_string(),
+            });
+    }
+
     Ok(state.into())
 }
 
 #[tauri::command]
-pub async fn window_list(window_module: State<'_, WindowModule>) -> AppResult<Vec<WindowInfo>> {
+pub async fn window_list(
+    window_module: State<'_, Arc<WindowModule>>,
+) -> AppResult<Vec<WindowInfo>> {
     let states = window_module
         .list_windows()
         .await
@@ -101,7 +161,10 @@ pub async fn window_list(window_module: State<'_, WindowModule>) -> AppResult<Ve
 }
 
 #[tauri::command]
-pub async fn window_close(window_module: State<'_, WindowModule>, label: String) -> AppResult<()> {
+pub async fn window_close(
+    window_module: State<'_, Arc<WindowModule>>,
+    label: String,
+) -> AppResult<()> {
     window_module
         .close_window(&WindowLabel::new(label))
         .await
@@ -109,10 +172,27 @@ pub async fn window_close(window_module: State<'_, WindowModule>, label: String)
     Ok(())
 }
 
+/// 修改窗口标题，未指定 label 时作用于主窗口
+#[tauri::command]
+pub async fn window_set_title(
+    window_module: State<'_, Arc<WindowModule>>,
+    request: SetTitleRequest,
+) -> AppResult<()> {
+    let label = request
+        .label
+        .map(WindowLabel::new)
+        .unwrap_or_else(WindowLabel::main);
+
+    window_module
+        .set_title(&label, &request.title)
+        .await
+        .map_err(|e| AppError::WindowError(e.to_string()))
+}
+
 #[tauri::command]
 pub async fn window_toggle_pet_mode(
     window: WebviewWindow,
-    window_module: State<'_, WindowModule>,
+    window_module: State<'_, Arc<WindowModule>>,
     event_bus: State<'_, Arc<RwLock<EventBus>>>,
 ) -> AppResult<TogglePetModeResponse> {
     let is_decorated = window
@@ -148,9 +228,36 @@ pub async fn window_toggle_pet_mode(
     }
 }
 
+/// 将宠物窗口固定到桌面（始终位于其他窗口之下），取消后回到桌面宠物模式
+#[tauri::command]
+pub async fn window_set_desktop_mode(
+    window_module: State<'_, Arc<WindowModule>>,
+    event_bus: State<'_, Arc<RwLock<EventBus>>>,
+    request: SetDesktopModeRequest,
+) -> AppResult<()> {
+    let (result, mode) = if request.enabled {
+        (
+            window_module.switch_to_desktop_mode().await,
+            SharedWindowMode::Desktop,
+        )
+    } else {
+        (
+            window_module.switch_to_pet_mode().await,
+            SharedWindowMode::Pet,
+        )
+    };
+    result.map_err(|e| AppError::WindowError(e.to_string()))?;
+
+    event_bus
+        .read()
+        .await
+        .publish(AppEvent::WindowModeChanged { mode });
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn window_set_always_on_top(
-    window_module: State<'_, WindowModule>,
+    window_module: State<'_, Arc<WindowModule>>,
     request: SetAlwaysOnTopRequest,
 ) -> AppResult<()> {
     window_module
@@ -160,11 +267,61 @@ pub async fn window_set_always_on_top(
     Ok(())
 }
 
+/// 开始拖拽主窗口，返回是否真正发起了拖拽
 #[tauri::command]
-pub async fn window_start_dragging(window_module: State<'_, WindowModule>) -> AppResult<()> {
+pub async fn window_start_dragging(window_module: State<'_, Arc<WindowModule>>) -> AppResult<bool> {
     window_module
         .start_dragging(&WindowLabel::main())
         .await
-        .map_err(|e| AppError::WindowError(e.to_string()))?;
+        .map_err(|e| AppError::WindowError(e.to_string()))
+}
+
+/// 前端命中测试后更新指针是否位于角色区域
+#[tauri::command]
+pub async fn window_set_drag_region(
+    window_module: State<'_, Arc<WindowModule>>,
+    request: SetDragRegionRequest,
+) -> AppResult<()> {
+    window_module.set_drag_region(request.in_drag_region);
     Ok(())
 }
+
+#[tauri::command]
+pub async fn window_set_drag_locked(
+    window_module: State<'_, Arc<WindowModule>>,
+    request: SetDragLockedRequest,
+) -> AppResult<()> {
+    window_module.set_drag_locked(request.locked);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn window_list_monitors(
+    window_module: State<'_, Arc<WindowModule>>,
+) -> AppResult<Vec<MonitorInfo>> {
+    window_module
+        .list_monitors()
+        .await
+        .map_err(|e| AppError::WindowError(e.to_string()))
+}
+
+#[tauri::command]
+pub async fn window_move_to_monitor(
+    window_module: State<'_, Arc<WindowModule>>,
+    request: MoveToMonitorRequest,
+) -> AppResult<MoveToMonitorResponse> {
+    let label = request
+        .label
+        .map(WindowLabel::new)
+        .unwrap_or_else(WindowLabel::main);
+
+    let position = window_module
+        .move_to_monitor(&label, request.monitor_index)
+        .await
+        .map_err(|e| AppError::WindowError(e.to_string()))?;
+
+    Ok(MoveToMonitorResponse {
+        x: position.x,
+        y: position.y,
+    })
+}
@@ -0,0 +1,208 @@
+// Audit Log - 领域事件审计日志
+//
+// 将领域事件以 JSONL 格式追加到数据目录下的 events.log，
+// 与 tracing 日志不同，这里记录的是结构化、可查询的领域历史
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::modules::chat::domain::DomainEvent;
+use crate::modules::config::domain::ConfigChangedEvent;
+
+/// 审计日志文件名（位于应用数据目录下）
+pub const AUDIT_LOG_FILE: &str = "events.log";
+
+/// 可写入审计日志的事件
+pub trait AuditEvent: Serialize {
+    fn event_type(&self) -> &'static str;
+    fn timestamp(&self) -> DateTime<Utc>;
+}
+
+impl<E: DomainEvent + Serialize> AuditEvent for E {
+    fn event_type(&self) -> &'static str {
+        DomainEvent::event_type(self)
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        DomainEvent::timestamp(self)
+    }
+}
+
+impl AuditEvent for ConfigChangedEvent {
+    fn event_type(&self) -> &'static str {
+        "config.changed"
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+/// 审计日志中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub timestamp: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+impl AuditRecord {
+    pub fn from_event(event: &impl AuditEvent) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            event_type: event.event_type().to_string(),
+            timestamp: event.timestamp(),
+            data: serde_json::to_value(event)?,
+        })
+    }
+}
+
+/// JSONL 审计日志写入器
+///
+/// 写入前若文件将超过 `max_bytes`，先把当前文件轮转为 `<file>.1`（仅保留一份旧文件）
+pub struct AuditLogWriter {
+    path: PathBuf,
+    max_bytes: u64,
+    lock: Mutex<()>,
+}
+
+impl AuditLogWriter {
+    pub fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            path,
+            max_bytes,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 轮转后的旧日志文件路径
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    /// 追加一条记录
+    pub fn append(&self, record: &AuditRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap();
+
+        let current = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if current > 0 && current + line.len() as u64 > self.max_bytes {
+            fs::rename(&self.path, self.rotated_path())?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+}
+
+/// 审计日志
+///
+/// 未启用时所有记录操作均为空操作
+#[derive(Default)]
+pub struct AuditLog {
+    writer: Option<AuditLogWriter>,
+}
+
+impl AuditLog {
+    /// 创建未启用的审计日志
+    pub fn disabled() -> Self {
+        Self { writer: None }
+    }
+
+    /// 在数据目录下创建审计日志
+    pub fn new(data_dir: &Path, max_bytes: u64) -> Self {
+        Self {
+            writer: Some(AuditLogWriter::new(
+                data_dir.join(AUDIT_LOG_FILE),
+                max_bytes,
+            )),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// 记录领域事件，写入失败只记录警告
+    pub fn record(&self, event: &impl AuditEvent) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+
+        let result = AuditRecord::from_event(event)
+            .map_err(io::Error::from)
+            .and_then(|record| writer.append(&record));
+        if let Err(e) = result {
+            tracing::warn!("[AuditLog] Failed to record {}: {}", event.event_type(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::{SessionCreatedEvent, SessionId};
+
+    fn session_created() -> SessionCreatedEvent {
+        SessionCreatedEvent {
+            session_id: SessionId::new(),
+            title: "New Chat".to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_session_created_writes_jsonl_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path(), 1024 * 1024);
+        let event = session_created();
+
+        log.record(&event);
+
+        let content = fs::read_to_string(dir.path().join(AUDIT_LOG_FILE)).unwrap();
+        assert!(content.ends_with('\n'));
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let record: AuditRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record.event_type, "session.created");
+        assert_eq!(record.timestamp, event.timestamp);
+        assert_eq!(record.data["sessionId"], event.session_id.to_string());
+        assert_eq!(record.data["title"], "New Chat");
+    }
+
+    #[test]
+    fn test_writer_rotates_when_full() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = AuditRecord::from_event(&session_created()).unwrap();
+        let line_len = serde_json::to_string(&record).unwrap().len() as u64 + 1;
+        // 恰好容纳两行
+        let writer = AuditLogWriter::new(dir.path().join(AUDIT_LOG_FILE), line_len * 2);
+
+        for _ in 0..3 {
+            writer.append(&record).unwrap();
+        }
+
+        let current = fs::read_to_string(writer.path()).unwrap();
+        let rotated = fs::read_to_string(writer.rotated_path()).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert_eq!(rotated.lines().count(), 2);
+    }
+}
@@ -0,0 +1,279 @@
+// Backup - 数据备份
+//
+// 将会话、消息和配置文件快照到带时间戳的备份目录，
+// 并按保留数量清理旧备份
+
+use chrono::{DateTime, Utc};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 需要备份的数据文件（相对于应用数据目录）
+///
+/// 包含 API Key 的加密密钥，否则恢复后的配置中的 Key 无法解密
+pub const DATA_FILES: &[&str] = &[
+    "sessions.json",
+    "messages.json",
+    "config.json",
+    "secret.key",
+];
+
+/// 备份目录名称（位于应用数据目录下）
+pub const BACKUP_DIR_NAME: &str = "backups";
+
+/// 备份名称前缀
+const BACKUP_PREFIX: &str = "backup-";
+
+/// 备份存储抽象
+pub trait BackupStore {
+    /// 列出所有备份名称
+    fn list(&self) -> io::Result<Vec<String>>;
+
+    /// 将当前数据文件快照为指定名称的备份
+    fn snapshot(&self, name: &str) -> io::Result<()>;
+
+    /// 用指定备份覆盖当前数据文件
+    fn restore(&self, name: &str) -> io::Result<()>;
+
+    /// 删除指定备份
+    fn remove(&self, name: &str) -> io::Result<()>;
+}
+
+/// 生成备份名称
+///
+/// 名称按字典序即按时间排序
+pub fn backup_name(now: DateTime<Utc>) -> String {
+    format!("{}{}", BACKUP_PREFIX, now.format("%Y%m%d-%H%M%S-%3f"))
+}
+
+/// 计算需要清理的备份（保留最新的 `keep` 个）
+pub fn backups_to_prune(names: &[String], keep: usize) -> Vec<String> {
+    let mut backups: Vec<&String> = names
+        .iter()
+        .filter(|name| name.starts_with(BACKUP_PREFIX))
+        .collect();
+    backups.sort_by(|a, b| b.cmp(a));
+    backups.into_iter().skip(keep).cloned().collect()
+}
+
+/// 清理旧备份，返回被删除的备份名称
+pub fn prune_backups(store: &dyn BackupStore, keep: usize) -> io::Result<Vec<String>> {
+    let pruned = backups_to_prune(&store.list()?, keep);
+    for name in &pruned {
+        store.remove(name)?;
+    }
+    Ok(pruned)
+}
+
+/// 创建备份并清理旧备份，返回新备份名称
+pub fn create_backup(
+    store: &dyn BackupStore,
+    now: DateTime<Utc>,
+    keep: usize,
+) -> io::Result<String> {
+    let name = backup_name(now);
+    store.snapshot(&name)?;
+    prune_backups(store, keep)?;
+    Ok(name)
+}
+
+/// 从备份恢复
+///
+/// 仅接受 [`BackupStore::list`] 中存在的名称
+pub fn restore_backup(store: &dyn BackupStore, name: &str) -> io::Result<()> {
+    if !store.list()?.iter().any(|n| n == name) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Backup not found: {}", name),
+        ));
+    }
+    store.restore(name)
+}
+
+/// 基于文件系统的备份存储
+///
+/// 每个备份是 `<data_dir>/backups/<name>/` 下的一组数据文件副本
+pub struct FsBackupStore {
+    data_dir: PathBuf,
+    backup_dir: PathBuf,
+}
+
+impl FsBackupStore {
+    pub fn new(data_dir: PathBuf) -> Self {
+        let backup_dir = data_dir.join(BACKUP_DIR_NAME);
+        Self {
+            data_dir,
+            backup_dir,
+        }
+    }
+}
+
+impl BackupStore for FsBackupStore {
+    fn list(&self) -> io::Result<Vec<String>> {
+        if !self.backup_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.backup_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    fn snapshot(&self, name: &str) -> io::Result<()> {
+        let target = self.backup_dir.join(name);
+        std::fs::create_dir_all(&target)?;
+
+        for file in DATA_FILES {
+            let source = self.data_dir.join(file);
+            if source.exists() {
+                std::fs::copy(&source, target.join(file))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn restore(&self, name: &str) -> io::Result<()> {
+        let source = self.backup_dir.join(name);
+
+        for file in DATA_FILES {
+            let backup_file = source.join(file);
+            if backup_file.exists() {
+                std::fs::copy(&backup_file, self.data_dir.join(file))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&self, name: &str) -> io::Result<()> {
+        std::fs::remove_dir_all(self.backup_dir.join(name))
+    }
+}
+
+/// 启动定期备份任务
+pub fn spawn_periodic_backup(data_dir: PathBuf, interval: Duration, keep: usize) {
+    tauri::async_runtime::spawn(async move {
+        let store = FsBackupStore::new(data_dir);
+        let mut ticker = tokio::time::interval(interval);
+        // 跳过立即触发的第一次
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            match create_backup(&store, Utc::now(), keep) {
+                Ok(name) => tracing::info!("[Backup] Created periodic backup: {}", name),
+                Err(e) => tracing::error!("[Backup] Periodic backup failed: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    /// 内存备份存储
+    #[derive(Default)]
+    struct MemoryBackupStore {
+        backups: Mutex<BTreeSet<String>>,
+        restored: Mutex<Option<String>>,
+    }
+
+    impl BackupStore for MemoryBackupStore {
+        fn list(&self) -> io::Result<Vec<String>> {
+            Ok(self.backups.lock().unwrap().iter().cloned().collect())
+        }
+
+        fn snapshot(&self, name: &str) -> io::Result<()> {
+            self.backups.lock().unwrap().insert(name.to_string());
+            Ok(())
+        }
+
+        fn restore(&self, name: &str) -> io::Result<()> {
+            *self.restored.lock().unwrap() = Some(name.to_string());
+            Ok(())
+        }
+
+        fn remove(&self, name: &str) -> io::Result<()> {
+            self.backups.lock().unwrap().remove(name);
+            Ok(())
+        }
+    }
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_backups_to_prune_keeps_most_recent() {
+        let names: Vec<String> = [3, 1, 4, 0, 2]
+            .iter()
+            .map(|m| backup_name(at(*m)))
+            .collect();
+
+        let pruned = backups_to_prune(&names, 3);
+
+        assert_eq!(pruned, vec![backup_name(at(1)), backup_name(at(0))]);
+    }
+
+    #[test]
+    fn test_backups_to_prune_ignores_other_entries() {
+        let names = vec![backup_name(at(0)), "notes".to_string()];
+
+        assert_eq!(backups_to_prune(&names, 0), vec![backup_name(at(0))]);
+        assert!(backups_to_prune(&names, 5).is_empty());
+    }
+
+    #[test]
+    fn test_create_backup_retains_exactly_keep() {
+        let store = MemoryBackupStore::default();
+
+        for minute in 0..8 {
+            create_backup(&store, at(minute), 5).unwrap();
+        }
+
+        let expected: Vec<String> = (3..8).map(|m| backup_name(at(m))).collect();
+        assert_eq!(store.list().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_restore_unknown_backup() {
+        let store = MemoryBackupStore::default();
+        let name = create_backup(&store, at(0), 5).unwrap();
+
+        let err = restore_backup(&store, "backup-missing").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        restore_backup(&store, &name).unwrap();
+        assert_eq!(
+            store.restored.lock().unwrap().as_deref(),
+            Some(name.as_str())
+        );
+    }
+
+    #[test]
+    fn test_fs_backup_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().to_path_buf();
+        std::fs::write(data_dir.join("sessions.json"), "original").unwrap();
+
+        let store = FsBackupStore::new(data_dir.clone());
+        let name = create_backup(&store, at(0), 5).unwrap();
+        assert_eq!(store.list().unwrap(), vec![name.clone()]);
+
+        std::fs::write(data_dir.join("sessions.json"), "corrupted").unwrap();
+        restore_backup(&store, &name).unwrap();
+
+        let restored = std::fs::read_to_string(data_dir.join("sessions.json")).unwrap();
+        assert_eq!(restored, "original");
+    }
+}
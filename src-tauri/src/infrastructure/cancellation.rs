@@ -0,0 +1,97 @@
+// Cancellation Registry - 请求取消注册表
+//
+// 按前端传入的请求 ID 登记可取消的后台请求，
+// 前端可用同一 ID 中止尚未完成的请求
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// 取消信号
+pub struct CancelSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+impl CancelSignal {
+    /// 等待取消
+    ///
+    /// 对应的请求被注销后不会再被取消，此时永远挂起
+    pub async fn cancelled(&mut self) {
+        loop {
+            if *self.receiver.borrow() {
+                return;
+            }
+            if self.receiver.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+/// 请求取消注册表
+#[derive(Default)]
+pub struct CancellationRegistry {
+    senders: Mutex<HashMap<String, watch::Sender<bool>>>,
+}
+
+impl CancellationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记请求，返回其取消信号
+    ///
+    /// 重复登记同一 ID 会替换之前的登记
+    pub fn register(&self, request_id: impl Into<String>) -> CancelSignal {
+        let (sender, receiver) = watch::channel(false);
+        self.senders
+            .lock()
+            .unwrap()
+            .insert(request_id.into(), sender);
+        CancelSignal { receiver }
+    }
+
+    /// 取消请求，请求不存在（或已完成）时返回 false
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self.senders.lock().unwrap().remove(request_id) {
+            Some(sender) => sender.send(true).is_ok(),
+            None => false,
+        }
+    }
+
+    /// 注销已完成的请求
+    pub fn remove(&self, request_id: &str) {
+        self.senders.lock().unwrap().remove(request_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_registered_request() {
+        let registry = CancellationRegistry::new();
+        let mut signal = registry.register("fetch-1");
+
+        assert!(registry.cancel("fetch-1"));
+        tokio::time::timeout(Duration::from_secs(1), signal.cancelled())
+            .await
+            .expect("signal should fire");
+
+        // 取消后即注销
+        assert!(!registry.cancel("fetch-1"));
+    }
+
+    #[tokio::test]
+    async fn test_removed_request_never_cancels() {
+        let registry = CancellationRegistry::new();
+        let mut signal = registry.register("fetch-1");
+        registry.remove("fetch-1");
+
+        assert!(!registry.cancel("fetch-1"));
+        let result = tokio::time::timeout(Duration::from_millis(50), signal.cancelled()).await;
+        assert!(result.is_err());
+    }
+}
@@ -1,28 +1,111 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, RwLock};
 
+use crate::modules::config::{ConfigChangedEvent, ConfigObserver};
 use crate::shared::{Emotion, MessageChunk, WindowMode};
 
 #[derive(Clone, Debug)]
 pub enum AppEvent {
     MessageChunk(MessageChunk),
+    /// 合并的流式帧：一次刷新的文本、音素和情感变化（启用 llm.combined_stream_frames 时代替 MessageChunk）
+    StreamFrame {
+        session_id: uuid::Uuid,
+        content: String,
+        phonemes: Vec<String>,
+        /// 相对上一帧变化后的情感，未变化时为 None
+        emotion: Option<Emotion>,
+        in_code_block: bool,
+    },
+    /// 开始生成，request_id 可用于取消并关联之后的完成或错误事件
+    MessageStart {
+        session_id: uuid::Uuid,
+        request_id: String,
+    },
     MessageComplete {
         session_id: uuid::Uuid,
+        request_id: String,
         message_id: uuid::Uuid,
         emotion: Option<Emotion>,
+        /// 推理阶段耗时（毫秒）
+        reasoning_ms: Option<u64>,
+        /// 回答阶段耗时（毫秒）
+        answer_ms: Option<u64>,
     },
     MessageError {
         session_id: uuid::Uuid,
+        request_id: String,
         error: String,
+        /// 已生成但未能保存的内容
+        content: Option<String>,
     },
     WindowModeChanged {
         mode: WindowMode,
     },
+    /// 默认提供商不健康，已改用其他提供商
+    ProviderFellBack {
+        session_id: uuid::Uuid,
+        from: String,
+        to: String,
+    },
+    /// 当前环境不支持透明窗口，已改用不透明窗口
+    WindowTransparencyUnavailable {
+        label: String,
+    },
+    /// 助手回复在保存前被修改（内容过滤或截掉越界的用户发言），content 为修改后的完整内容
+    ContentFiltered {
+        session_id: uuid::Uuid,
+        message_id: uuid::Uuid,
+        content: String,
+        flagged_terms: Vec<String>,
+    },
+    /// 助手回复陷入重复循环，已提前停止并保存已生成的部分
+    RepetitionStopped {
+        session_id: uuid::Uuid,
+        message_id: uuid::Uuid,
+        pattern: String,
+        repeats: usize,
+    },
+    /// 用户消息的情感（启用用户情感分析时，在回复开始前发布）
+    UserEmotion {
+        session_id: uuid::Uuid,
+        emotion: Emotion,
+    },
+    /// 配置已变更，key 为变化的配置段或键，整体变化时为 `*`
+    ConfigChanged {
+        key: String,
+    },
+    /// 再次启动或深度链接请求新建对话，可指定预设
+    NewChatRequested {
+        preset_id: Option<uuid::Uuid>,
+    },
+    /// 再次启动时请求打开会话（深度链接）
+    OpenSessionRequested {
+        session_id: uuid::Uuid,
+    },
+}
+
+/// 设置 AppHandle 之前最多缓存的前端事件数
+const PENDING_CAPACITY: usize = 100;
+
+/// 前端事件的发送目标
+trait FrontendEmitter: Send + Sync {
+    fn emit_event(&self, event: &str, payload: serde_json::Value);
+}
+
+impl FrontendEmitter for AppHandle {
+    fn emit_event(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
 }
 
 pub struct EventBus {
     sender: broadcast::Sender<AppEvent>,
-    app_handle: Option<AppHandle>,
+    emitter: Option<Arc<dyn FrontendEmitter>>,
+    /// 尚未设置 AppHandle 时发布的前端事件，设置后按顺序补发
+    pending: Mutex<VecDeque<(&'static str, serde_json::Value)>>,
 }
 
 impl EventBus {
@@ -30,58 +113,235 @@ impl EventBus {
         let (sender, _) = broadcast::channel(100);
         Self {
             sender,
-            app_handle: None,
+            emitter: None,
+            pending: Mutex::new(VecDeque::new()),
         }
     }
 
+    /// 设置或替换 AppHandle
+    ///
+    /// 可重复调用（如主窗口重建后），缓存的事件只会补发一次
     pub fn set_app_handle(&mut self, handle: AppHandle) {
-        self.app_handle = Some(handle);
+        self.set_emitter(Arc::new(handle));
+    }
+
+    fn set_emitter(&mut self, emitter: Arc<dyn FrontendEmitter>) {
+        let pending = std::mem::take(self.pending.get_mut().unwrap_or_else(|e| e.into_inner()));
+        if !pending.is_empty() {
+            tracing::info!("[EventBus] Flushing {} buffered events", pending.len());
+        }
+        for (event, payload) in pending {
+            emitter.emit_event(event, payload);
+        }
+        self.emitter = Some(emitter);
+    }
+
+    fn emit_to_frontend<S: Serialize>(&self, event: &'static str, payload: S) {
+        let payload = match serde_json::to_value(payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("[EventBus] Failed to serialize {}: {}", event, e);
+                return;
+            }
+        };
+
+        match &self.emitter {
+            Some(emitter) => emitter.emit_event(event, payload),
+            None => {
+                let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                if pending.len() >= PENDING_CAPACITY {
+                    pending.pop_front();
+                }
+                pending.push_back((event, payload));
+            }
+        }
     }
 
     pub fn publish(&self, event: AppEvent) {
         tracing::debug!("[EventBus] Publishing event: {:?}", event);
         let _ = self.sender.send(event.clone());
 
-        if let Some(handle) = &self.app_handle {
-            match &event {
-                AppEvent::MessageChunk(chunk) => {
-                    tracing::debug!("[EventBus] Emitting llm:chunk to frontend");
-                    let _ = handle.emit("llm:chunk", chunk);
-                }
-                AppEvent::MessageComplete {
-                    session_id,
-                    message_id,
-                    emotion,
-                } => {
-                    tracing::info!("[EventBus] Emitting llm:complete to frontend");
-                    let _ = handle.emit(
-                        "llm:complete",
-                        serde_json::json!({
-                            "sessionId": session_id,
-                            "messageId": message_id,
-                            "emotion": emotion,
-                        }),
-                    );
-                }
-                AppEvent::MessageError { session_id, error } => {
-                    tracing::error!("[EventBus] Emitting llm:error to frontend: {}", error);
-                    let _ = handle.emit(
-                        "llm:error",
-                        serde_json::json!({
-                            "sessionId": session_id,
-                            "error": error,
-                        }),
-                    );
-                }
-                AppEvent::WindowModeChanged { mode } => {
-                    tracing::info!("[EventBus] Emitting window:mode_changed");
-                    let _ = handle.emit(
-                        "window:mode_changed",
-                        serde_json::json!({
-                            "mode": mode,
-                        }),
-                    );
-                }
+        match &event {
+            AppEvent::MessageChunk(chunk) => {
+                tracing::debug!("[EventBus] Emitting llm:chunk to frontend");
+                self.emit_to_frontend("llm:chunk", chunk);
+            }
+            AppEvent::StreamFrame {
+                session_id,
+                content,
+                phonemes,
+                emotion,
+                in_code_block,
+            } => {
+                tracing::debug!("[EventBus] Emitting llm:frame to frontend");
+                self.emit_to_frontend(
+                    "llm:frame",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "content": content,
+                        "phonemes": phonemes,
+                        "emotion": emotion,
+                        "inCodeBlock": in_code_block,
+                    }),
+                );
+            }
+            AppEvent::MessageStart {
+                session_id,
+                request_id,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:start to frontend");
+                self.emit_to_frontend(
+                    "llm:start",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "requestId": request_id,
+                    }),
+                );
+            }
+            AppEvent::MessageComplete {
+                session_id,
+                request_id,
+                message_id,
+                emotion,
+                reasoning_ms,
+                answer_ms,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:complete to frontend");
+                self.emit_to_frontend(
+                    "llm:complete",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "requestId": request_id,
+                        "messageId": message_id,
+                        "emotion": emotion,
+                        "reasoningMs": reasoning_ms,
+                        "answerMs": answer_ms,
+                    }),
+                );
+            }
+            AppEvent::MessageError {
+                session_id,
+                request_id,
+                error,
+                content,
+            } => {
+                tracing::error!("[EventBus] Emitting llm:error to frontend: {}", error);
+                self.emit_to_frontend(
+                    "llm:error",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "requestId": request_id,
+                        "error": error,
+                        "content": content,
+                    }),
+                );
+            }
+            AppEvent::WindowModeChanged { mode } => {
+                tracing::info!("[EventBus] Emitting window:mode_changed");
+                self.emit_to_frontend(
+                    "window:mode_changed",
+                    serde_json::json!({
+                        "mode": mode,
+                    }),
+                );
+            }
+            AppEvent::ProviderFellBack {
+                session_id,
+                from,
+                to,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:provider_fell_back");
+                self.emit_to_frontend(
+                    "llm:provider_fell_back",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "from": from,
+                        "to": to,
+                    }),
+                );
+            }
+            AppEvent::WindowTransparencyUnavailable { label } => {
+                tracing::info!("[EventBus] Emitting window:transparency_unavailable");
+                self.emit_to_frontend(
+                    "window:transparency_unavailable",
+                    serde_json::json!({
+                        "label": label,
+                    }),
+                );
+            }
+            AppEvent::ContentFiltered {
+                session_id,
+                message_id,
+                content,
+                flagged_terms,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:content_filtered");
+                self.emit_to_frontend(
+                    "llm:content_filtered",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "messageId": message_id,
+                        "content": content,
+                        "flaggedTerms": flagged_terms,
+                    }),
+                );
+            }
+            AppEvent::RepetitionStopped {
+                session_id,
+                message_id,
+                pattern,
+                repeats,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:repetition_stopped");
+                self.emit_to_frontend(
+                    "llm:repetition_stopped",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "messageId": message_id,
+                        "pattern": pattern,
+                        "repeats": repeats,
+                    }),
+                );
+            }
+            AppEvent::UserEmotion {
+                session_id,
+                emotion,
+            } => {
+                tracing::info!("[EventBus] Emitting llm:user_emotion");
+                self.emit_to_frontend(
+                    "llm:user_emotion",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                        "emotion": emotion,
+                    }),
+                );
+            }
+            AppEvent::ConfigChanged { key } => {
+                tracing::info!("[EventBus] Emitting config:changed");
+                self.emit_to_frontend(
+                    "config:changed",
+                    serde_json::json!({
+                        "key": key,
+                    }),
+                );
+            }
+            AppEvent::NewChatRequested { preset_id } => {
+                tracing::info!("[EventBus] Emitting app:new_chat");
+                self.emit_to_frontend(
+                    "app:new_chat",
+                    serde_json::json!({
+                        "presetId": preset_id,
+                    }),
+                );
+            }
+            AppEvent::OpenSessionRequested { session_id } => {
+                tracing::info!("[EventBus] Emitting app:open_session");
+                self.emit_to_frontend(
+                    "app:open_session",
+                    serde_json::json!({
+                        "sessionId": session_id,
+                    }),
+                );
             }
         }
     }
@@ -96,3 +356,80 @@ impl Default for EventBus {
         Self::new()
     }
 }
+
+/// 将配置变更转发为 [`AppEvent::ConfigChanged`] 的配置观察者
+pub struct EventBusConfigObserver {
+    event_bus: Arc<RwLock<EventBus>>,
+}
+
+impl EventBusConfigObserver {
+    pub fn new(event_bus: Arc<RwLock<EventBus>>) -> Self {
+        Self { event_bus }
+    }
+}
+
+impl ConfigObserver for EventBusConfigObserver {
+    fn on_config_changed(&self, event: &ConfigChangedEvent) {
+        // 通知在同步上下文中发出，EventBus 的锁需要异步获取
+        let event_bus = self.event_bus.clone();
+        let key = event.key.clone();
+        tauri::async_runtime::spawn(async move {
+            event_bus
+                .read()
+                .await
+                .publish(AppEvent::ConfigChanged { key });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 记录收到的事件名
+    #[derive(Default)]
+    struct RecordingEmitter {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl FrontendEmitter for RecordingEmitter {
+        fn emit_event(&self, event: &str, _payload: serde_json::Value) {
+            self.events.lock().unwrap().push(event.to_string());
+        }
+    }
+
+    impl RecordingEmitter {
+        fn events(&self) -> Vec<String> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_buffered_events_flush_once_when_handle_replaced() {
+        let mut bus = EventBus::new();
+        bus.publish(AppEvent::WindowModeChanged {
+            mode: WindowMode::Pet,
+        });
+        bus.publish(AppEvent::ConfigChanged {
+            key: "*".to_string(),
+        });
+
+        let first = Arc::new(RecordingEmitter::default());
+        bus.set_emitter(first.clone());
+        assert_eq!(
+            first.events(),
+            vec!["window:mode_changed", "config:changed"]
+        );
+
+        // 再次设置只替换目标，不重复补发
+        let second = Arc::new(RecordingEmitter::default());
+        bus.set_emitter(second.clone());
+        assert!(second.events().is_empty());
+
+        bus.publish(AppEvent::ConfigChanged {
+            key: "general".to_string(),
+        });
+        assert_eq!(first.events().len(), 2);
+        assert_eq!(second.events(), vec!["config:changed"]);
+    }
+}
@@ -0,0 +1,229 @@
+// Launch Arguments
+//
+// 解析再次启动应用时传入的参数（命令行标志或 `kizuna://` 深度链接），
+// 决定已运行的实例如何响应
+
+use uuid::Uuid;
+
+use super::AppEvent;
+
+/// 新建对话的命令行标志
+pub const NEW_CHAT_FLAG: &str = "--new-chat";
+
+/// 应用注册的 URL 协议
+pub const DEEP_LINK_SCHEME: &str = "kizuna";
+
+/// 打开会话的深度链接前缀，后接会话 ID
+pub const SESSION_DEEP_LINK_PREFIX: &str = "kizuna://session/";
+
+/// 新建对话的深度链接，可带 `?preset=<预设 ID>`
+pub const NEW_CHAT_DEEP_LINK: &str = "kizuna://new-chat";
+
+/// 再次启动时的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchAction {
+    /// 仅显示并聚焦主窗口
+    ShowWindow,
+    /// 新建对话，可指定使用的预设
+    NewChat { preset_id: Option<Uuid> },
+    /// 打开指定会话
+    OpenSession(Uuid),
+}
+
+impl LaunchAction {
+    /// 解析启动参数，第一个参数为可执行文件路径
+    ///
+    /// 深度链接优先于标志；无法识别的参数和无效的链接按普通启动处理
+    pub fn parse<S: AsRef<str>>(args: &[S]) -> Self {
+        let args: Vec<&str> = args.iter().skip(1).map(AsRef::as_ref).collect();
+
+        if let Some(action) = args.iter().find_map(|arg| Self::from_deep_link(arg)) {
+            return action;
+        }
+
+        if args.contains(&NEW_CHAT_FLAG) {
+            Self::NewChat { preset_id: None }
+        } else {
+            Self::ShowWindow
+        }
+    }
+
+    /// 解析单个 `kizuna://` 深度链接
+    ///
+    /// 不是本应用的链接、路径无法识别或会话 ID 无效时返回 None；
+    /// 新建对话链接中无效的预设 ID 会被忽略，仍然新建对话
+    pub fn from_deep_link(link: &str) -> Option<Self> {
+        let (scheme, rest) = link.trim().split_once("://")?;
+        if !scheme.eq_ignore_ascii_case(DEEP_LINK_SCHEME) {
+            return None;
+        }
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let path = path.trim_end_matches('/');
+
+        if let Some(id) = path.strip_prefix("session/") {
+            return match Uuid::parse_str(id) {
+                Ok(session_id) => Some(Self::OpenSession(session_id)),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid session deep link {}: {}", link, e);
+                    None
+                }
+            };
+        }
+
+        if path == "new-chat" {
+            let preset_id = query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(key, _)| *key == "preset")
+                .and_then(|(_, value)| match Uuid::parse_str(value) {
+                    Ok(preset_id) => Some(preset_id),
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid preset in deep link {}: {}", link, e);
+                        None
+                    }
+                });
+            return Some(Self::NewChat { preset_id });
+        }
+
+        tracing::warn!("Ignoring unknown deep link {}", link);
+        None
+    }
+
+    /// 显示窗口之外需要通知前端的事件
+    pub fn event(self) -> Option<AppEvent> {
+        match self {
+            Self::ShowWindow => None,
+            Self::NewChat { preset_id } => Some(AppEvent::NewChatRequested { preset_id }),
+            Self::OpenSession(session_id) => Some(AppEvent::OpenSessionRequested { session_id }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXE: &str = "/usr/bin/kizuna";
+
+    const NEW_CHAT: LaunchAction = LaunchAction::NewChat { preset_id: None };
+
+    #[test]
+    fn test_parse_launch_args() {
+        let id = Uuid::new_v4();
+        let link = format!("{}{}", SESSION_DEEP_LINK_PREFIX, id);
+
+        assert_eq!(LaunchAction::parse(&[EXE]), LaunchAction::ShowWindow);
+        assert_eq!(
+            LaunchAction::parse(&[EXE, "--hidden"]),
+            LaunchAction::ShowWindow
+        );
+        assert_eq!(LaunchAction::parse(&[EXE, NEW_CHAT_FLAG]), NEW_CHAT);
+        assert_eq!(
+            LaunchAction::parse(&[EXE, NEW_CHAT_FLAG, &link]),
+            LaunchAction::OpenSession(id)
+        );
+        assert_eq!(
+            LaunchAction::parse(&[EXE.to_string(), format!("{}/", link)]),
+            LaunchAction::OpenSession(id)
+        );
+        assert_eq!(
+            LaunchAction::parse(&[EXE, "kizuna://session/not-a-uuid", NEW_CHAT_FLAG]),
+            NEW_CHAT
+        );
+        assert_eq!(
+            LaunchAction::parse(&[EXE, "kizuna://settings"]),
+            LaunchAction::ShowWindow
+        );
+        // 可执行文件路径本身不参与解析
+        assert_eq!(
+            LaunchAction::parse(&[NEW_CHAT_FLAG]),
+            LaunchAction::ShowWindow
+        );
+    }
+
+    #[test]
+    fn test_session_deep_link() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            LaunchAction::from_deep_link(&format!("kizuna://session/{}", id)),
+            Some(LaunchAction::OpenSession(id))
+        );
+        assert_eq!(
+            LaunchAction::from_deep_link(&format!("KIZUNA://session/{}/?from=mail#top", id)),
+            Some(LaunchAction::OpenSession(id))
+        );
+        assert_eq!(LaunchAction::from_deep_link("kizuna://session/"), None);
+        assert_eq!(LaunchAction::from_deep_link("kizuna://session/12345"), None);
+        assert_eq!(
+            LaunchAction::from_deep_link(&format!("kizuna://session/{}/extra", id)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_new_chat_deep_link() {
+        let preset = Uuid::new_v4();
+
+        assert_eq!(
+            LaunchAction::from_deep_link(NEW_CHAT_DEEP_LINK),
+            Some(NEW_CHAT)
+        );
+        assert_eq!(
+            LaunchAction::from_deep_link(&format!("{}?preset={}", NEW_CHAT_DEEP_LINK, preset)),
+            Some(LaunchAction::NewChat {
+                preset_id: Some(preset)
+            })
+        );
+        assert_eq!(
+            LaunchAction::from_deep_link(&format!("kizuna://new-chat/?x=1&preset={}", preset)),
+            Some(LaunchAction::NewChat {
+                preset_id: Some(preset)
+            })
+        );
+        // 无效的预设 ID 退化为普通新建对话
+        assert_eq!(
+            LaunchAction::from_deep_link("kizuna://new-chat?preset=../../etc"),
+            Some(NEW_CHAT)
+        );
+        assert_eq!(
+            LaunchAction::from_deep_link("kizuna://new-chat?preset"),
+            Some(NEW_CHAT)
+        );
+    }
+
+    #[test]
+    fn test_malformed_deep_links() {
+        for link in [
+            "",
+            "kizuna:",
+            "kizuna://",
+            "kizuna://unknown/path",
+            "https://session/00000000-0000-0000-0000-000000000000",
+            "kizuna-evil://new-chat",
+            "--new-chat",
+        ] {
+            assert_eq!(LaunchAction::from_deep_link(link), None, "{}", link);
+        }
+    }
+
+    #[test]
+    fn test_actions_map_to_events() {
+        let id = Uuid::new_v4();
+
+        assert!(LaunchAction::ShowWindow.event().is_none());
+        assert!(matches!(
+            NEW_CHAT.event(),
+            Some(AppEvent::NewChatRequested { preset_id: None })
+        ));
+        assert!(matches!(
+            LaunchAction::NewChat { preset_id: Some(id) }.event(),
+            Some(AppEvent::NewChatRequested { preset_id }) if preset_id == Some(id)
+        ));
+        assert!(matches!(
+            LaunchAction::OpenSession(id).event(),
+            Some(AppEvent::OpenSessionRequested { session_id }) if session_id == id
+        ));
+    }
+}
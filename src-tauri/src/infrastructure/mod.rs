@@ -1,5 +1,15 @@
+pub mod audit_log;
+pub mod backup;
+pub mod cancellation;
 pub mod event_bus;
+pub mod launch_args;
 pub mod state;
+pub mod storage_usage;
 
+pub use audit_log::*;
+pub use backup::*;
+pub use cancellation::*;
 pub use event_bus::*;
+pub use launch_args::*;
 pub use state::*;
+pub use storage_usage::*;
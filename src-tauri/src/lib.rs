@@ -4,11 +4,17 @@ pub mod modules;
 pub mod shared;
 
 use std::sync::Arc;
+use tauri::webview::PageLoadEvent;
 use tauri::Manager;
 use tokio::sync::RwLock;
 
-use infrastructure::{AppState, EventBus};
-use modules::chat::LLMAdapterRegistry;
+use infrastructure::{
+    AppState, AuditLog, CancellationRegistry, EventBus, EventBusConfigObserver, LaunchAction,
+};
+use modules::chat::{
+    LLMAdapterRegistry, ReconcileStoresCommand, RepetitionPolicy, WordListFilter,
+};
+use modules::window::{ModeSizeConfig, TauriWindowAdapter, WindowModeRegistry, WindowSize};
 use modules::{ChatModule, ConfigModule, WindowModule};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -23,6 +29,8 @@ pub fn run() {
 
     let app_state = AppState::new();
     let event_bus = Arc::new(RwLock::new(EventBus::new()));
+    #[cfg(target_os = "macos")]
+    let deep_link_event_bus = event_bus.clone();
 
     // 初始化 LLM 适配器注册表
     let llm_registry = Arc::new(LLMAdapterRegistry::new());
@@ -41,15 +49,32 @@ pub fn run() {
             Some(vec!["--hidden"]),
         ))
         .plugin(tauri_plugin_window_state::Builder::new().build())
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.show();
-                let _ = window.set_focus();
+        .plugin(tauri_plugin_single_instance::init({
+            let event_bus = event_bus.clone();
+            move |app, args, _cwd| {
+                let action = LaunchAction::parse(&args);
+                tracing::info!("Second instance launched: {:?}", action);
+                dispatch_launch_action(app, &event_bus, action);
             }
         }))
         .manage(app_state)
         .manage(event_bus.clone())
         .manage(llm_registry.clone())
+        .manage(Arc::new(CancellationRegistry::new()))
+        .on_page_load({
+            let event_bus = event_bus.clone();
+            move |webview, payload| {
+                // 主窗口重建或重新加载后，重新设置 EventBus 的 AppHandle
+                if webview.label() != "main" || payload.event() != PageLoadEvent::Finished {
+                    return;
+                }
+                let event_bus = event_bus.clone();
+                let handle = webview.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    event_bus.write().await.set_app_handle(handle);
+                });
+            }
+        })
         .setup(move |app| {
             let handle = app.handle().clone();
             let event_bus_clone = event_bus.clone();
@@ -70,6 +95,18 @@ pub fn run() {
                 {
                     Ok(module) => {
                         tracing::info!("Chat module initialized with persistent storage");
+                        // 修复上次异常退出可能留下的孤立消息
+                        match module
+                            .reconcile_stores(ReconcileStoresCommand::new(true))
+                            .await
+                        {
+                            Ok(report) if !report.created_sessions.is_empty() => tracing::warn!(
+                                "Recovered {} sessions for orphan messages",
+                                report.created_sessions.len()
+                            ),
+                            Ok(_) => {}
+                            Err(e) => tracing::warn!("Failed to reconcile chat storage: {}", e),
+                        }
                         Arc::new(RwLock::new(module))
                     }
                     Err(e) => {
@@ -81,14 +118,93 @@ pub fn run() {
                     }
                 }
             });
-            app.manage(chat_module);
+            app.manage(chat_module.clone());
 
             // 初始化 Config 模块（使用文件存储）
-            let config_module = Arc::new(RwLock::new(ConfigModule::new_with_store(app_data_dir)));
+            let config_module = Arc::new(RwLock::new(ConfigModule::new_with_store(
+                app_data_dir.clone(),
+            )));
+
+            let app_config = tauri::async_runtime::block_on(async {
+                config_module.read().await.get_all().await
+            })
+            .unwrap_or_default();
+
+            // 启用助手回复的内容过滤（如已启用）
+            let content_filter = &app_config.content_filter;
+            if content_filter.enabled {
+                let mut filter = WordListFilter::new(content_filter.terms.clone());
+                if let Some(placeholder) = &content_filter.placeholder {
+                    filter = filter.with_placeholder(placeholder.clone());
+                }
+                tauri::async_runtime::block_on(chat_module.write())
+                    .set_content_filter(Some(Arc::new(filter)));
+            }
+
+            // 启用流式回复的重复检测（如已启用）
+            let repetition_guard = &app_config.repetition_guard;
+            if repetition_guard.enabled {
+                tauri::async_runtime::block_on(chat_module.write()).set_repetition_guard(Some(
+                    RepetitionPolicy {
+                        max_pattern_len: repetition_guard.max_pattern_len as usize,
+                        min_repeats: repetition_guard.min_repeats as usize,
+                    },
+                ));
+            }
+
+            // 初始化审计日志（如已启用）
+            let audit_log = if app_config.audit_log.enabled {
+                AuditLog::new(
+                    &app_data_dir,
+                    u64::from(app_config.audit_log.max_size_kb) * 1024,
+                )
+            } else {
+                AuditLog::disabled()
+            };
+            app.manage(Arc::new(audit_log));
+
+            // 启动定期备份（如已启用）
+            let backup_config = app_config.backup;
+            if backup_config.enabled {
+                infrastructure::spawn_periodic_backup(
+                    app_data_dir,
+                    std::time::Duration::from_secs(u64::from(backup_config.interval_minutes) * 60),
+                    backup_config.keep as usize,
+                );
+            }
+
+            // 以系统实际的自启动状态为准同步配置
+            let autostart_synced = commands::os_autostart_enabled(&handle).and_then(|enabled| {
+                tauri::async_runtime::block_on(commands::sync_autostart_config(
+                    &config_module,
+                    enabled,
+                ))
+            });
+            if let Err(e) = autostart_synced {
+                tracing::warn!("Failed to reconcile autostart state: {}", e);
+            }
+
+            // 配置变更经 EventBus 通知前端
+            tauri::async_runtime::block_on(config_module.read())
+                .service()
+                .add_observer(Arc::new(EventBusConfigObserver::new(
+                    event_bus_clone.clone(),
+                )));
+
             app.manage(config_module);
 
             // 初始化 Window 模块
-            let window_module = Arc::new(WindowModule::new(handle.clone()));
+            let pet_size = app_config.window.pet_mode_size;
+            let mode_sizes = ModeSizeConfig {
+                pet: WindowSize::new(pet_size.width, pet_size.height),
+                ..Default::default()
+            };
+            let window_adapter = TauriWindowAdapter::with_mode_registry(
+                handle.clone(),
+                WindowModeRegistry::with_sizes(&mode_sizes),
+            )
+            .with_max_windows(app_config.window.max_windows as usize);
+            let window_module = Arc::new(WindowModule::with_adapter(Arc::new(window_adapter)));
             app.manage(window_module);
 
             // 设置 EventBus 的 AppHandle
@@ -106,26 +222,104 @@ pub fn run() {
             commands::session_get,
             commands::session_delete,
             commands::session_rename,
+            commands::session_set_display_name,
+            commands::session_set_model_config,
+            commands::session_fork,
+            commands::session_export,
+            commands::session_import,
+            commands::session_should_auto_title,
             // Chat commands
             commands::chat_send_message,
+            commands::chat_send_message_with_files,
             commands::chat_regenerate,
             commands::chat_stop_generation,
             commands::chat_get_messages,
+            commands::chat_insert_context_break,
+            commands::chat_clear_session_messages,
+            commands::chat_edit_message,
+            commands::chat_delete_message,
+            commands::chat_search_messages,
+            commands::chat_suggest_replies,
+            commands::chat_get_effective_settings,
             commands::chat_fetch_models,
+            commands::chat_cancel_fetch_models,
+            commands::chat_reset_provider,
+            commands::chat_health_check_all,
+            commands::chat_providers_health,
+            commands::chat_estimate_tokens,
+            commands::chat_model_supports,
             // Window commands
             commands::window_toggle_pet_mode,
             commands::window_set_always_on_top,
+            commands::window_set_desktop_mode,
             commands::window_start_dragging,
+            commands::window_set_drag_region,
+            commands::window_set_drag_locked,
             commands::window_create,
             commands::window_list,
             commands::window_close,
+            commands::window_set_title,
+            commands::window_list_monitors,
+            commands::window_move_to_monitor,
             // Config commands
             commands::config_get_all,
             commands::config_reset,
+            commands::config_clone_provider,
+            commands::config_update_provider,
+            commands::config_list_providers,
+            commands::config_reorder_providers,
+            commands::config_set_quiet_hours,
+            commands::config_is_first_run,
+            commands::autostart_set,
+            commands::autostart_is_enabled,
             commands::preset_list,
             commands::preset_create,
             commands::preset_delete,
+            commands::preset_export,
+            commands::preset_import,
+            // Backup commands
+            commands::backup_create,
+            commands::backup_list,
+            commands::backup_restore,
+            // Diagnostics commands
+            commands::diagnostics_storage_usage,
+            commands::diagnostics_flush,
+            commands::diagnostics_cache_stats,
+            commands::diagnostics_clear_caches,
+            commands::diagnostics_reconnect_event_bus,
+            commands::diagnostics_reconcile,
+            commands::diagnostics_compact,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(move |_app, _event| {
+            // macOS 通过系统事件而不是启动参数传递 kizuna:// 链接
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls {
+                    if let Some(action) = LaunchAction::from_deep_link(url.as_str()) {
+                        tracing::info!("Deep link opened: {:?}", action);
+                        dispatch_launch_action(_app, &deep_link_event_bus, action);
+                    }
+                }
+            }
+        });
+}
+
+/// 显示并聚焦主窗口，再把启动动作对应的事件发给前端
+fn dispatch_launch_action(
+    app: &tauri::AppHandle,
+    event_bus: &Arc<RwLock<EventBus>>,
+    action: LaunchAction,
+) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if let Some(event) = action.event() {
+        let event_bus = event_bus.clone();
+        tauri::async_runtime::spawn(async move {
+            event_bus.read().await.publish(event);
+        });
+    }
 }
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{Message, SessionId};
+use crate::modules::chat::ports::{MessageRepository, SessionRepository};
+
+/// 清空会话消息命令
+///
+/// 删除会话的全部消息，会话本身（标题、设置、预设）保持不变
+#[derive(Debug, Clone)]
+pub struct ClearSessionMessagesCommand {
+    pub session_id: SessionId,
+    /// 清空后重新插入的开场白（来自预设）
+    pub greeting: Option<String>,
+}
+
+impl ClearSessionMessagesCommand {
+    pub fn new(session_id: SessionId) -> Self {
+        Self {
+            session_id,
+            greeting: None,
+        }
+    }
+
+    pub fn with_greeting(mut self, greeting: impl Into<String>) -> Self {
+        self.greeting = Some(greeting.into());
+        self
+    }
+}
+
+/// 清空会话消息响应
+#[derive(Debug, Clone)]
+pub struct ClearSessionMessagesResponse {
+    /// 删除的消息数量
+    pub deleted_messages: usize,
+    /// 重新插入的开场白消息
+    pub greeting: Option<Message>,
+}
+
+/// 清空会话消息处理器
+pub struct ClearSessionMessagesHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl ClearSessionMessagesHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ClearSessionMessagesCommand, ClearSessionMessagesResponse>
+    for ClearSessionMessagesHandler
+{
+    async fn handle(
+        &self,
+        command: ClearSessionMessagesCommand,
+    ) -> Result<ClearSessionMessagesResponse, ApplicationError> {
+        let mut session = self
+            .session_repository
+            .get(command.session_id)
+            .await?
+            .ok_or_else(|| ApplicationError::SessionNotFound(command.session_id.to_string()))?;
+
+        let deleted_messages = self
+            .message_repository
+            .delete_by_session(command.session_id)
+            .await?;
+
+        session.mark_messages_cleared();
+        self.session_repository.save(&session).await?;
+
+        let greeting = match command.greeting.filter(|g| !g.trim().is_empty()) {
+            Some(greeting) => {
+                let message = Message::new_assistant(command.session_id, greeting, None);
+                self.message_repository.save(&message).await?;
+                Some(message)
+            }
+            None => None,
+        };
+
+        Ok(ClearSessionMessagesResponse {
+            deleted_messages,
+            greeting,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::Session;
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use crate::modules::chat::ports::Pagination;
+
+    #[tokio::test]
+    async fn test_clear_keeps_session_and_reinserts_greeting() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ClearSessionMessagesHandler::new(session_repo.clone(), message_repo.clone());
+
+        let session = Session::new(Some("Keep me".to_string()), None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+        message_repo
+            .save(&Message::new_user(session_id, "Hi"))
+            .await
+            .unwrap();
+        message_repo
+            .save(&Message::new_assistant(session_id, "Hello", None))
+            .await
+            .unwrap();
+
+        let command = ClearSessionMessagesCommand::new(session_id).with_greeting("Welcome back!");
+        let response = handler.handle(command).await.unwrap();
+        assert_eq!(response.deleted_messages, 2);
+
+        let stored = session_repo.get(session_id).await.unwrap().unwrap();
+        assert_eq!(stored.title(), "Keep me");
+        assert!(stored.updated_at() >= session.updated_at());
+
+        let messages = message_repo
+            .find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap();
+        assert_eq!(messages.items.len(), 1);
+        assert_eq!(messages.items[0].content(), "Welcome back!");
+        assert_eq!(
+            Some(messages.items[0].id()),
+            response.greeting.map(|m| m.id())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clear_without_greeting_and_missing_session() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ClearSessionMessagesHandler::new(session_repo.clone(), message_repo.clone());
+
+        let session = Session::new(None, None);
+        session_repo.save(&session).await.unwrap();
+        message_repo
+            .save(&Message::new_user(session.id(), "Hi"))
+            .await
+            .unwrap();
+
+        let response = handler
+            .handle(ClearSessionMessagesCommand::new(session.id()))
+            .await
+            .unwrap();
+        assert_eq!(response.deleted_messages, 1);
+        assert!(response.greeting.is_none());
+        assert_eq!(
+            message_repo.count_by_session(session.id()).await.unwrap(),
+            0
+        );
+
+        let result = handler
+            .handle(ClearSessionMessagesCommand::new(SessionId::new()))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::SessionNotFound(_))));
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::ports::{MessageRepository, SessionRepository};
+
+/// 压缩会话与消息存储
+///
+/// 移除已没有消息的会话分组并重写存储文件；将来的 SQLite 后端对应 `VACUUM`
+#[derive(Debug, Clone, Default)]
+pub struct CompactStoresCommand;
+
+impl CompactStoresCommand {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 压缩存储响应
+#[derive(Debug, Clone)]
+pub struct CompactStoresResponse {
+    /// 移除的空会话分组数
+    pub removed_entries: usize,
+}
+
+/// 压缩存储处理器
+pub struct CompactStoresHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl CompactStoresHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CompactStoresCommand, CompactStoresResponse> for CompactStoresHandler {
+    async fn handle(
+        &self,
+        _command: CompactStoresCommand,
+    ) -> Result<CompactStoresResponse, ApplicationError> {
+        let removed_entries = self.message_repository.compact().await?;
+        // 会话存储没有空条目，重写即可去除旧格式残留
+        self.session_repository.flush().await?;
+
+        if removed_entries > 0 {
+            tracing::info!("Removed {} empty message entries", removed_entries);
+        }
+        Ok(CompactStoresResponse { removed_entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::{Message, SessionId};
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+
+    #[tokio::test]
+    async fn test_compact_reports_removed_entries() {
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = CompactStoresHandler::new(
+            Arc::new(InMemorySessionRepository::new()),
+            message_repo.clone(),
+        );
+
+        let kept = SessionId::new();
+        message_repo
+            .save(&Message::new_user(kept, "Kept"))
+            .await
+            .unwrap();
+        let deleted = Message::new_user(SessionId::new(), "Deleted");
+        message_repo.save(&deleted).await.unwrap();
+        message_repo.delete(deleted.id()).await.unwrap();
+
+        let response = handler.handle(CompactStoresCommand::new()).await.unwrap();
+        assert_eq!(response.removed_entries, 1);
+        assert_eq!(message_repo.count_by_session(kept).await.unwrap(), 1);
+        assert_eq!(message_repo.count_all_by_session().await.unwrap().len(), 1);
+    }
+}
@@ -13,11 +13,23 @@ pub struct CreateSessionCommand {
     pub title: Option<String>,
     /// 预设 ID（可选）
     pub preset_id: Option<Uuid>,
+    /// 助手显示名称（可选，通常取自预设名称）
+    pub display_name: Option<String>,
 }
 
 impl CreateSessionCommand {
     pub fn new(title: Option<String>, preset_id: Option<Uuid>) -> Self {
-        Self { title, preset_id }
+        Self {
+            title,
+            preset_id,
+            display_name: None,
+        }
+    }
+
+    /// 设置助手显示名称
+    pub fn with_display_name(mut self, display_name: Option<String>) -> Self {
+        self.display_name = display_name;
+        self
     }
 }
 
@@ -45,7 +57,10 @@ impl CommandHandler<CreateSessionCommand, CreateSessionResponse> for CreateSessi
         command: CreateSessionCommand,
     ) -> Result<CreateSessionResponse, ApplicationError> {
         // 创建新会话
-        let session = Session::new(command.title, command.preset_id);
+        let mut session = Session::new(command.title, command.preset_id);
+        if command.display_name.is_some() {
+            session.set_display_name(command.display_name);
+        }
 
         // 持久化
         self.session_repository.save(&session).await?;
@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{MessageId, MessageRole, SessionId};
+use crate::modules::chat::ports::{MessageRepository, Pagination};
+
+/// 删除单条消息命令
+///
+/// 默认只删除指定消息；删除用户消息时可通过 `cascade` 一并删除紧随其后的助手回复
+#[derive(Debug, Clone)]
+pub struct DeleteMessageCommand {
+    pub message_id: MessageId,
+    pub cascade: bool,
+}
+
+impl DeleteMessageCommand {
+    pub fn new(message_id: MessageId) -> Self {
+        Self {
+            message_id,
+            cascade: false,
+        }
+    }
+
+    pub fn with_cascade(mut self, cascade: bool) -> Self {
+        self.cascade = cascade;
+        self
+    }
+}
+
+/// 删除消息响应
+#[derive(Debug, Clone)]
+pub struct DeleteMessageResponse {
+    /// 实际删除的消息 ID，消息不存在时为空
+    pub deleted: Vec<MessageId>,
+}
+
+impl DeleteMessageResponse {
+    /// 是否删除了消息
+    pub fn removed(&self) -> bool {
+        !self.deleted.is_empty()
+    }
+}
+
+/// 删除消息处理器
+pub struct DeleteMessageHandler {
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl DeleteMessageHandler {
+    pub fn new(message_repository: Arc<dyn MessageRepository>) -> Self {
+        Self { message_repository }
+    }
+
+    /// 紧随指定消息之后的连续助手回复（包括重新生成的多条回复）
+    async fn following_replies(
+        &self,
+        message_id: MessageId,
+        session_id: SessionId,
+    ) -> Result<Vec<MessageId>, ApplicationError> {
+        let total = self.message_repository.count_by_session(session_id).await?;
+        let messages = self
+            .message_repository
+            .find_by_session(session_id, Pagination::new(1, total.max(1) as u32))
+            .await?;
+
+        Ok(messages
+            .items
+            .iter()
+            .skip_while(|m| m.id() != message_id)
+            .skip(1)
+            .take_while(|m| m.role() == MessageRole::Assistant)
+            .map(|m| m.id())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl CommandHandler<DeleteMessageCommand, DeleteMessageResponse> for DeleteMessageHandler {
+    async fn handle(
+        &self,
+        command: DeleteMessageCommand,
+    ) -> Result<DeleteMessageResponse, ApplicationError> {
+        let Some(message) = self.message_repository.get(command.message_id).await? else {
+            return Ok(DeleteMessageResponse {
+                deleted: Vec::new(),
+            });
+        };
+
+        let mut deleted = vec![message.id()];
+        if command.cascade && message.role() == MessageRole::User {
+            deleted.extend(
+                self.following_replies(message.id(), message.session_id())
+                    .await?,
+            );
+        }
+
+        for id in &deleted {
+            self.message_repository.delete(*id).await?;
+        }
+
+        Ok(DeleteMessageResponse { deleted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::Message;
+    use crate::modules::chat::infrastructure::InMemoryMessageRepository;
+
+    async fn conversation(repo: &InMemoryMessageRepository) -> (SessionId, Vec<Message>) {
+        let session_id = SessionId::new();
+        let messages = vec![
+            Message::new_user(session_id, "Q1"),
+            Message::new_assistant(session_id, "A1", None),
+            Message::new_user(session_id, "Q2"),
+            Message::new_assistant(session_id, "A2", None),
+        ];
+        for message in &messages {
+            repo.save(message).await.unwrap();
+        }
+        (session_id, messages)
+    }
+
+    async fn contents(repo: &InMemoryMessageRepository, session_id: SessionId) -> Vec<String> {
+        repo.find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap()
+            .items
+            .iter()
+            .map(|m| m.content().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_delete_without_cascade() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = DeleteMessageHandler::new(repo.clone());
+        let (session_id, messages) = conversation(&repo).await;
+
+        // 删除助手消息不影响之前的用户消息
+        let response = handler
+            .handle(DeleteMessageCommand::new(messages[1].id()))
+            .await
+            .unwrap();
+        assert!(response.removed());
+        assert_eq!(contents(&repo, session_id).await, ["Q1", "Q2", "A2"]);
+
+        // 未设置 cascade 时删除用户消息不会删除其后的回复
+        handler
+            .handle(DeleteMessageCommand::new(messages[2].id()))
+            .await
+            .unwrap();
+        assert_eq!(contents(&repo, session_id).await, ["Q1", "A2"]);
+
+        let response = handler
+            .handle(DeleteMessageCommand::new(messages[2].id()))
+            .await
+            .unwrap();
+        assert!(!response.removed());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_message_with_cascade() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = DeleteMessageHandler::new(repo.clone());
+        let (session_id, messages) = conversation(&repo).await;
+
+        let response = handler
+            .handle(DeleteMessageCommand::new(messages[0].id()).with_cascade(true))
+            .await
+            .unwrap();
+        assert_eq!(response.deleted, vec![messages[0].id(), messages[1].id()]);
+        assert_eq!(contents(&repo, session_id).await, ["Q2", "A2"]);
+
+        // cascade 只作用于用户消息
+        handler
+            .handle(DeleteMessageCommand::new(messages[3].id()).with_cascade(true))
+            .await
+            .unwrap();
+        assert_eq!(contents(&repo, session_id).await, ["Q2"]);
+    }
+}
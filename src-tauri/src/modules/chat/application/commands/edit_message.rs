@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{sanitize_user_content, Message, MessageId};
+use crate::modules::chat::ports::MessageRepository;
+
+/// 编辑消息命令
+///
+/// 用户消息和助手消息都可以编辑（如在导出前修正错别字）；上下文断点没有内容，不能编辑
+#[derive(Debug, Clone)]
+pub struct EditMessageCommand {
+    pub message_id: MessageId,
+    pub new_content: String,
+}
+
+impl EditMessageCommand {
+    pub fn new(message_id: MessageId, new_content: impl Into<String>) -> Self {
+        Self {
+            message_id,
+            new_content: new_content.into(),
+        }
+    }
+}
+
+/// 编辑消息响应
+#[derive(Debug, Clone)]
+pub struct EditMessageResponse {
+    /// 编辑后的消息
+    pub message: Message,
+}
+
+/// 编辑消息处理器
+pub struct EditMessageHandler {
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl EditMessageHandler {
+    pub fn new(message_repository: Arc<dyn MessageRepository>) -> Self {
+        Self { message_repository }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<EditMessageCommand, EditMessageResponse> for EditMessageHandler {
+    async fn handle(
+        &self,
+        command: EditMessageCommand,
+    ) -> Result<EditMessageResponse, ApplicationError> {
+        // 按清理后的内容判断，只含控制字符的内容同样视为空
+        if sanitize_user_content(&command.new_content).trim().is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "Message content cannot be empty".to_string(),
+            ));
+        }
+
+        let mut message = self
+            .message_repository
+            .get(command.message_id)
+            .await?
+            .ok_or_else(|| ApplicationError::MessageNotFound(command.message_id.to_string()))?;
+
+        if message.is_context_break() {
+            return Err(ApplicationError::ValidationError(
+                "Context breaks cannot be edited".to_string(),
+            ));
+        }
+
+        message.edit_content(command.new_content);
+        self.message_repository.update(&message).await?;
+
+        Ok(EditMessageResponse { message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::SessionId;
+    use crate::modules::chat::infrastructure::InMemoryMessageRepository;
+    use crate::modules::chat::ports::Pagination;
+
+    #[tokio::test]
+    async fn test_edit_user_and_assistant_messages() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = EditMessageHandler::new(repo.clone());
+
+        let session_id = SessionId::new();
+        let question = Message::new_user(session_id, "Helo");
+        let answer = Message::new_assistant(session_id, "Hi tehre", None);
+        repo.save(&question).await.unwrap();
+        repo.save(&answer).await.unwrap();
+
+        for (id, content) in [(question.id(), "Hello"), (answer.id(), "Hi there")] {
+            let response = handler
+                .handle(EditMessageCommand::new(id, content))
+                .await
+                .unwrap();
+            assert_eq!(response.message.content(), content);
+        }
+
+        // 编辑不改变消息顺序
+        let messages = repo
+            .find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap();
+        let contents: Vec<_> = messages.items.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["Hello", "Hi there"]);
+    }
+
+    #[tokio::test]
+    async fn test_edit_rejects_invalid_requests() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = EditMessageHandler::new(repo.clone());
+
+        let session_id = SessionId::new();
+        let message = Message::new_user(session_id, "Original");
+        let context_break = Message::new_context_break(session_id);
+        repo.save(&message).await.unwrap();
+        repo.save(&context_break).await.unwrap();
+
+        for blank in ["   ", "\0", " \u{7}\n"] {
+            let result = handler
+                .handle(EditMessageCommand::new(message.id(), blank))
+                .await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+        assert_eq!(
+            repo.get(message.id()).await.unwrap().unwrap().content(),
+            "Original"
+        );
+
+        let result = handler
+            .handle(EditMessageCommand::new(context_break.id(), "Text"))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+
+        let result = handler
+            .handle(EditMessageCommand::new(MessageId::new(), "Text"))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::MessageNotFound(_))));
+    }
+}
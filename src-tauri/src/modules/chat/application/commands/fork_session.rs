@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{MessageId, Session, SessionId};
+use crate::modules::chat::ports::{MessageRepository, Pagination, SessionRepository};
+
+/// 分支会话命令
+///
+/// 以指定消息为分叉点创建新会话，原会话保持不变
+#[derive(Debug, Clone)]
+pub struct ForkSessionCommand {
+    pub source_session_id: SessionId,
+    /// 分叉点，该消息及之前的消息会被复制到新会话
+    pub up_to_message_id: MessageId,
+}
+
+impl ForkSessionCommand {
+    pub fn new(source_session_id: SessionId, up_to_message_id: MessageId) -> Self {
+        Self {
+            source_session_id,
+            up_to_message_id,
+        }
+    }
+}
+
+/// 分支会话响应
+#[derive(Debug, Clone)]
+pub struct ForkSessionResponse {
+    /// 新创建的会话
+    pub session: Session,
+    /// 复制的消息数量
+    pub copied_messages: usize,
+}
+
+/// 分支会话处理器
+pub struct ForkSessionHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl ForkSessionHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ForkSessionCommand, ForkSessionResponse> for ForkSessionHandler {
+    async fn handle(
+        &self,
+        command: ForkSessionCommand,
+    ) -> Result<ForkSessionResponse, ApplicationError> {
+        let source = self
+            .session_repository
+            .get(command.source_session_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::SessionNotFound(command.source_session_id.to_string())
+            })?;
+
+        let total = self
+            .message_repository
+            .count_by_session(source.id())
+            .await?;
+        let messages = self
+            .message_repository
+            .find_by_session(source.id(), Pagination::new(1, total.max(1) as u32))
+            .await?
+            .items;
+
+        // 分叉点必须属于源会话
+        let end = messages
+            .iter()
+            .position(|m| m.id() == command.up_to_message_id)
+            .ok_or_else(|| {
+                ApplicationError::MessageNotFound(command.up_to_message_id.to_string())
+            })?;
+
+        let session = source.fork();
+        self.session_repository.save(&session).await?;
+
+        for message in &messages[..=end] {
+            self.message_repository
+                .save(&message.copy_to_session(session.id()))
+                .await?;
+        }
+
+        Ok(ForkSessionResponse {
+            session,
+            copied_messages: end + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::{Emotion, Message, MessageRole};
+    use crate::modules::chat::infrastructure::{FileMessageRepository, FileSessionRepository};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_fork_copies_messages_up_to_fork_point() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let session_repo = Arc::new(FileSessionRepository::new(data_dir.clone()).await.unwrap());
+        let message_repo = Arc::new(FileMessageRepository::new(data_dir.clone()).await.unwrap());
+        let handler = ForkSessionHandler::new(session_repo.clone(), message_repo.clone());
+
+        let source = Session::new(Some("Original".to_string()), None);
+        session_repo.save(&source).await.unwrap();
+        let mut messages = Vec::new();
+        for i in 1..=3 {
+            messages.push(Message::new_user(source.id(), format!("Q{}", i)));
+            messages.push(Message::new_assistant(
+                source.id(),
+                format!("A{}", i),
+                Some(Emotion::Happy),
+            ));
+        }
+        for message in &messages {
+            message_repo.save(message).await.unwrap();
+        }
+
+        let response = handler
+            .handle(ForkSessionCommand::new(source.id(), messages[2].id()))
+            .await
+            .unwrap();
+        assert_eq!(response.copied_messages, 3);
+        assert_ne!(response.session.id(), source.id());
+        assert_eq!(response.session.title(), "Original");
+
+        // 重新从磁盘加载，确认复制的消息保存在新会话下
+        let reloaded = FileMessageRepository::new(data_dir).await.unwrap();
+        let forked = reloaded
+            .find_by_session(response.session.id(), Pagination::new(1, 10))
+            .await
+            .unwrap()
+            .items;
+        assert_eq!(forked.len(), 3);
+        for (copy, original) in forked.iter().zip(&messages) {
+            assert_ne!(copy.id(), original.id());
+            assert_eq!(copy.role(), original.role());
+            assert_eq!(copy.content(), original.content());
+            assert_eq!(copy.emotion(), original.emotion());
+        }
+        assert_eq!(forked[1].role(), MessageRole::Assistant);
+        assert_eq!(reloaded.count_by_session(source.id()).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_fork_rejects_message_from_another_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().to_path_buf();
+        let session_repo = Arc::new(FileSessionRepository::new(data_dir.clone()).await.unwrap());
+        let message_repo = Arc::new(FileMessageRepository::new(data_dir).await.unwrap());
+        let handler = ForkSessionHandler::new(session_repo.clone(), message_repo.clone());
+
+        let source = Session::new(None, None);
+        session_repo.save(&source).await.unwrap();
+        let other = Message::new_user(SessionId::new(), "Elsewhere");
+        message_repo.save(&other).await.unwrap();
+
+        let result = handler
+            .handle(ForkSessionCommand::new(source.id(), other.id()))
+            .await;
+        assert!(matches!(result, Err(ApplicationError::MessageNotFound(_))));
+        assert_eq!(session_repo.count().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::queries::{SessionExport, SESSION_EXPORT_VERSION};
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{sanitize_user_content, MessageRole, Session};
+use crate::modules::chat::ports::{MessageRepository, SessionRepository};
+
+/// 导入会话命令
+///
+/// 解析 [`SessionExport`] 格式的 JSON，以新的会话 ID 和消息 ID 保存，原会话不受影响
+#[derive(Debug, Clone)]
+pub struct ImportSessionCommand {
+    pub json: String,
+}
+
+impl ImportSessionCommand {
+    pub fn new(json: impl Into<String>) -> Self {
+        Self { json: json.into() }
+    }
+
+    /// 解析并校验导出内容
+    fn parse(&self) -> Result<SessionExport, ApplicationError> {
+        let export: SessionExport = serde_json::from_str(&self.json).map_err(|e| {
+            ApplicationError::ValidationError(format!("Invalid session export: {}", e))
+        })?;
+
+        if export.version != SESSION_EXPORT_VERSION {
+            return Err(ApplicationError::ValidationError(format!(
+                "Unsupported session export version: {}",
+                export.version
+            )));
+        }
+        if let Some(stray) = export
+            .messages
+            .iter()
+            .find(|m| m.session_id() != export.session.id())
+        {
+            return Err(ApplicationError::ValidationError(format!(
+                "Message {} does not belong to the exported session",
+                stray.id()
+            )));
+        }
+
+        Ok(export)
+    }
+}
+
+/// 导入会话响应
+#[derive(Debug, Clone)]
+pub struct ImportSessionResponse {
+    /// 新创建的会话
+    pub session: Session,
+    /// 导入的消息数量
+    pub imported_messages: usize,
+}
+
+/// 导入会话处理器
+pub struct ImportSessionHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl ImportSessionHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ImportSessionCommand, ImportSessionResponse> for ImportSessionHandler {
+    async fn handle(
+        &self,
+        command: ImportSessionCommand,
+    ) -> Result<ImportSessionResponse, ApplicationError> {
+        let mut export = command.parse()?;
+
+        let session = export.session.fork();
+        self.session_repository.save(&session).await?;
+
+        // 按导出时的顺序保存，序号由仓储重新分配
+        export.messages.sort_by_key(|m| m.order_key());
+        for message in &export.messages {
+            let mut copy = message.copy_to_session(session.id());
+            // 导入的文件不可信，用户消息与输入时一样清理控制字符
+            if copy.role() == MessageRole::User
+                && sanitize_user_content(copy.content()) != copy.content()
+            {
+                copy.edit_content(message.content());
+            }
+            self.message_repository.save(&copy).await?;
+        }
+
+        Ok(ImportSessionResponse {
+            session,
+            imported_messages: export.messages.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::application::{
+        ExportSessionHandler, ExportSessionQuery, QueryHandler,
+    };
+    use crate::modules::chat::domain::{Emotion, Message, SessionId};
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use crate::modules::chat::ports::Pagination;
+
+    struct Fixture {
+        session_repo: Arc<InMemorySessionRepository>,
+        message_repo: Arc<InMemoryMessageRepository>,
+        handler: ImportSessionHandler,
+    }
+
+    fn fixture() -> Fixture {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ImportSessionHandler::new(session_repo.clone(), message_repo.clone());
+        Fixture {
+            session_repo,
+            message_repo,
+            handler,
+        }
+    }
+
+    async fn messages(repo: &InMemoryMessageRepository, session_id: SessionId) -> Vec<Message> {
+        repo.find_by_session(session_id, Pagination::new(1, 100))
+            .await
+            .unwrap()
+            .items
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let f = fixture();
+        let source = Session::new(Some("Exported".to_string()), None);
+        f.session_repo.save(&source).await.unwrap();
+        for message in [
+            Message::new_user(source.id(), "Hi"),
+            Message::new_assistant(source.id(), "Hello!", Some(Emotion::Happy)),
+            Message::new_context_break(source.id()),
+            Message::new_user(source.id(), "Bye"),
+        ] {
+            f.message_repo.save(&message).await.unwrap();
+        }
+
+        let exporter = ExportSessionHandler::new(f.session_repo.clone(), f.message_repo.clone());
+        let export = exporter
+            .handle(ExportSessionQuery::new(source.id()))
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&export).unwrap();
+
+        let response = f
+            .handler
+            .handle(ImportSessionCommand::new(json))
+            .await
+            .unwrap();
+        assert_ne!(response.session.id(), source.id());
+        assert_eq!(response.session.title(), "Exported");
+        assert_eq!(response.imported_messages, 4);
+
+        let original = messages(&f.message_repo, source.id()).await;
+        let imported = messages(&f.message_repo, response.session.id()).await;
+        assert_eq!(imported.len(), original.len());
+        for (copy, message) in imported.iter().zip(&original) {
+            assert_ne!(copy.id(), message.id());
+            assert_eq!(copy.role(), message.role());
+            assert_eq!(copy.content(), message.content());
+            assert_eq!(copy.emotion(), message.emotion());
+            assert_eq!(copy.created_at(), message.created_at());
+        }
+        assert_eq!(imported[2].role(), MessageRole::ContextBreak);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_malformed_json() {
+        let f = fixture();
+
+        let mut stray = SessionExport {
+            version: SESSION_EXPORT_VERSION,
+            session: Session::new(None, None),
+            messages: vec![Message::new_user(SessionId::new(), "Elsewhere")],
+        };
+        let inputs = [
+            "not json".to_string(),
+            r#"{"version":1,"messages":[]}"#.to_string(),
+            serde_json::to_string(&stray).unwrap(),
+            {
+                stray.version = 99;
+                stray.messages.clear();
+                serde_json::to_string(&stray).unwrap()
+            },
+        ];
+
+        for json in inputs {
+            let result = f.handler.handle(ImportSessionCommand::new(json)).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+        assert_eq!(f.session_repo.count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_import_sanitizes_user_content() {
+        let f = fixture();
+        let session = Session::new(None, None);
+        let export = SessionExport {
+            version: SESSION_EXPORT_VERSION,
+            session: session.clone(),
+            messages: vec![
+                Message::new_user(session.id(), "placeholder"),
+                Message::new_assistant(session.id(), "Reply", None),
+            ],
+        };
+        // 手工编辑过的导出文件可能包含输入时会被清理的控制字符
+        let mut json = serde_json::to_value(&export).unwrap();
+        json["messages"][0]["content"] = "he\0llo\u{7}".into();
+
+        let response = f
+            .handler
+            .handle(ImportSessionCommand::new(json.to_string()))
+            .await
+            .unwrap();
+
+        let imported = messages(&f.message_repo, response.session.id()).await;
+        assert_eq!(imported[0].content(), "hello");
+        assert_eq!(imported[1].content(), "Reply");
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{Message, SessionId};
+use crate::modules::chat::ports::{MessageRepository, SessionRepository};
+
+/// 插入上下文断点命令
+///
+/// 断点之前的消息仍保留在会话历史中，但不再发送给 LLM
+#[derive(Debug, Clone)]
+pub struct InsertContextBreakCommand {
+    pub session_id: SessionId,
+}
+
+impl InsertContextBreakCommand {
+    pub fn new(session_id: SessionId) -> Self {
+        Self { session_id }
+    }
+}
+
+/// 插入上下文断点响应
+#[derive(Debug, Clone)]
+pub struct InsertContextBreakResponse {
+    /// 已保存的断点消息
+    pub message: Message,
+}
+
+/// 插入上下文断点处理器
+pub struct InsertContextBreakHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl InsertContextBreakHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<InsertContextBreakCommand, InsertContextBreakResponse>
+    for InsertContextBreakHandler
+{
+    async fn handle(
+        &self,
+        command: InsertContextBreakCommand,
+    ) -> Result<InsertContextBreakResponse, ApplicationError> {
+        // 验证会话存在
+        let exists = self.session_repository.exists(command.session_id).await?;
+        if !exists {
+            return Err(ApplicationError::SessionNotFound(
+                command.session_id.to_string(),
+            ));
+        }
+
+        // 保存断点消息
+        let message = Message::new_context_break(command.session_id);
+        self.message_repository.save(&message).await?;
+
+        Ok(InsertContextBreakResponse { message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::Session;
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use crate::modules::chat::ports::Pagination;
+
+    #[tokio::test]
+    async fn test_insert_context_break() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = InsertContextBreakHandler::new(session_repo.clone(), message_repo.clone());
+
+        let session = Session::new(Some("Test".to_string()), None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let command = InsertContextBreakCommand::new(session_id);
+        let response = handler.handle(command).await.unwrap();
+
+        assert!(response.message.is_context_break());
+
+        let messages = message_repo
+            .find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap();
+        assert_eq!(messages.items.len(), 1);
+        assert!(messages.items[0].is_context_break());
+    }
+
+    #[tokio::test]
+    async fn test_insert_context_break_nonexistent_session() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = InsertContextBreakHandler::new(session_repo, message_repo);
+
+        let command = InsertContextBreakCommand::new(SessionId::new());
+        let result = handler.handle(command).await;
+
+        assert!(matches!(result, Err(ApplicationError::SessionNotFound(_))));
+    }
+}
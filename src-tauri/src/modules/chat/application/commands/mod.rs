@@ -1,13 +1,29 @@
 // Chat Commands - 命令定义和处理器
 
+mod clear_session_messages;
+mod compact_stores;
 mod create_session;
+mod delete_message;
 mod delete_session;
+mod edit_message;
+mod fork_session;
+mod import_session;
+mod insert_context_break;
+mod reconcile_stores;
 mod regenerate;
 mod send_message;
 mod update_session;
 
+pub use clear_session_messages::*;
+pub use compact_stores::*;
 pub use create_session::*;
+pub use delete_message::*;
 pub use delete_session::*;
+pub use edit_message::*;
+pub use fork_session::*;
+pub use import_session::*;
+pub use insert_context_break::*;
+pub use reconcile_stores::*;
 pub use regenerate::*;
 pub use send_message::*;
 pub use update_session::*;
@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::{ApplicationError, CommandHandler};
+use crate::modules::chat::domain::{reconcile, SessionId, StoreReconciliation};
+use crate::modules::chat::ports::{MessageRepository, Pagination, SessionRepository};
+
+/// 检查会话存储与消息存储是否一致
+///
+/// `repair` 为 true 时为孤立消息创建占位会话；没有消息的会话只做标记，不会删除
+#[derive(Debug, Clone)]
+pub struct ReconcileStoresCommand {
+    pub repair: bool,
+}
+
+impl ReconcileStoresCommand {
+    pub fn new(repair: bool) -> Self {
+        Self { repair }
+    }
+}
+
+/// 检查存储一致性响应
+#[derive(Debug, Clone)]
+pub struct ReconcileStoresResponse {
+    /// 发现的不一致
+    pub reconciliation: StoreReconciliation,
+    /// 已创建的占位会话
+    pub created_sessions: Vec<SessionId>,
+}
+
+/// 检查存储一致性处理器
+pub struct ReconcileStoresHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl ReconcileStoresHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ReconcileStoresCommand, ReconcileStoresResponse> for ReconcileStoresHandler {
+    async fn handle(
+        &self,
+        command: ReconcileStoresCommand,
+    ) -> Result<ReconcileStoresResponse, ApplicationError> {
+        let total = self.session_repository.count().await?;
+        let sessions = self
+            .session_repository
+            .find_all(Pagination::new(1, total.max(1) as u32))
+            .await?
+            .items;
+        let message_counts = self.message_repository.count_all_by_session().await?;
+
+        let reconciliation = reconcile(&sessions, &message_counts);
+        for orphan in &reconciliation.orphan_messages {
+            tracing::warn!(
+                "Found {} messages for missing session {}",
+                orphan.message_count,
+                orphan.session_id
+            );
+        }
+        if !reconciliation.empty_sessions.is_empty() {
+            tracing::info!(
+                "Found {} sessions without messages",
+                reconciliation.empty_sessions.len()
+            );
+        }
+
+        let mut created_sessions = Vec::new();
+        if command.repair {
+            for session in reconciliation.placeholder_sessions() {
+                self.session_repository.save(&session).await?;
+                tracing::info!("Created placeholder session {}", session.id());
+                created_sessions.push(session.id());
+            }
+        }
+
+        Ok(ReconcileStoresResponse {
+            reconciliation,
+            created_sessions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::{Message, Session, RECOVERED_SESSION_TITLE};
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+
+    #[tokio::test]
+    async fn test_repair_creates_placeholder_for_orphan_messages() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ReconcileStoresHandler::new(session_repo.clone(), message_repo.clone());
+
+        let empty = Session::new(None, None);
+        session_repo.save(&empty).await.unwrap();
+        let orphan = SessionId::new();
+        message_repo
+            .save(&Message::new_user(orphan, "Lost"))
+            .await
+            .unwrap();
+
+        let report = handler
+            .handle(ReconcileStoresCommand::new(false))
+            .await
+            .unwrap();
+        assert_eq!(report.reconciliation.orphan_messages.len(), 1);
+        assert_eq!(report.reconciliation.empty_sessions, vec![empty.id()]);
+        assert!(report.created_sessions.is_empty());
+        assert!(!session_repo.exists(orphan).await.unwrap());
+
+        let repaired = handler
+            .handle(ReconcileStoresCommand::new(true))
+            .await
+            .unwrap();
+        assert_eq!(repaired.created_sessions, vec![orphan]);
+        let recovered = session_repo.get(orphan).await.unwrap().unwrap();
+        assert_eq!(recovered.title(), RECOVERED_SESSION_TITLE);
+        // 空会话保留
+        assert!(session_repo.exists(empty.id()).await.unwrap());
+
+        let after = handler
+            .handle(ReconcileStoresCommand::new(true))
+            .await
+            .unwrap();
+        assert!(after.reconciliation.orphan_messages.is_empty());
+        assert!(after.created_sessions.is_empty());
+    }
+}
@@ -4,12 +4,22 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use super::super::{ApplicationError, CommandHandler};
-use super::StreamEvent;
-use crate::modules::chat::domain::{EmotionAnalyzer, Message, SessionId};
+use super::{
+    apply_sampling, apply_stop_sequences, check_repetition, context_history,
+    effective_assistant_prefix, finalize_reply, save_with_retry, validate_sampling, PhaseTimer,
+    StreamEvent,
+};
+use crate::modules::chat::domain::{
+    ContentFilter, ContextBuilder, EmotionAnalyzer, Message, MessageId, MessageRole, RepetitionPolicy, SessionId,
+    StreamingContentFilter,
+};
 use crate::modules::chat::ports::{
     CompletionRequest, LLMChatMessage, LLMPort, MessageRepository, Pagination, SessionRepository,
 };
 
+/// 未指定目标时携带的最近历史消息数
+const REGENERATE_CONTEXT_MESSAGES: usize = 50;
+
 /// 重新生成命令（不创建新的用户消息）
 #[derive(Debug, Clone)]
 pub struct RegenerateCommand {
@@ -17,10 +27,29 @@ pub struct RegenerateCommand {
     pub session_id: SessionId,
     /// 用户消息内容（用于构建上下文，但不保存）
     pub user_content: String,
+    /// 系统提示（如会话预设的系统提示词），None 或空白时不发送系统提示
+    pub system_prompt: Option<String>,
     /// 模型 ID
     pub model: Option<String>,
     /// 是否使用流式响应
     pub stream: bool,
+    /// 要重新生成的助手消息 ID
+    ///
+    /// 指定时只删除这条消息，并以它之前的历史构建上下文；
+    /// 未指定时按历史末尾的消息推断
+    pub target_message_id: Option<MessageId>,
+    /// 请求 ID，随补全请求传给提供商，用于取消和事件关联
+    pub request_id: String,
+    /// 温度，None 时使用提供商默认值
+    pub temperature: Option<f32>,
+    /// 最大生成 token 数，None 时使用提供商默认值
+    pub max_tokens: Option<u32>,
+    /// 停止序列，None 时角色扮演模式使用默认的用户发言标签，否则不设置
+    pub stop_sequences: Option<Vec<String>>,
+    /// 角色扮演中用户的名字，设置后启用角色扮演模式（截掉越界生成的用户发言）
+    pub user_name: Option<String>,
+    /// 助手回复的开头（预填），提供商不支持时忽略
+    pub assistant_prefix: Option<String>,
 }
 
 impl RegenerateCommand {
@@ -33,10 +62,71 @@ impl RegenerateCommand {
         Self {
             session_id,
             user_content: user_content.into(),
+            system_prompt: None,
             model,
             stream,
+            target_message_id: None,
+            request_id: uuid::Uuid::new_v4().to_string(),
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            user_name: None,
+            assistant_prefix: None,
         }
     }
+
+    /// 指定要重新生成的助手消息
+    pub fn with_target_message(mut self, message_id: MessageId) -> Self {
+        self.target_message_id = Some(message_id);
+        self
+    }
+
+    /// 使用调用方提供的请求 ID（默认随机生成）
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = request_id.into();
+        self
+    }
+
+    /// 设置系统提示
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// 设置温度
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// 设置最大生成 token 数
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// 设置停止序列
+    pub fn with_stop_sequences(mut self, stop_sequences: Vec<String>) -> Self {
+        self.stop_sequences = Some(stop_sequences);
+        self
+    }
+
+    /// 以给定的用户名启用角色扮演模式
+    pub fn with_user_name(mut self, user_name: impl Into<String>) -> Self {
+        self.user_name = Some(user_name.into());
+        self
+    }
+
+    /// 设置助手前缀
+    pub fn with_assistant_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.assistant_prefix = Some(prefix.into());
+        self
+    }
+
+    /// 校验采样参数
+    pub fn validate(&self) -> Result<(), ApplicationError> {
+        validate_sampling(self.temperature, self.max_tokens)
+    }
 }
 
 /// 重新生成响应
@@ -46,12 +136,22 @@ pub struct RegenerateResponse {
     pub assistant_message: Message,
 }
 
+/// 让新回复占据被替换回复的位置（序号和创建时间），重新生成历史中间的回复时保持对话顺序
+fn take_place_of(reply: &mut Message, replaced: Option<&Message>) {
+    if let Some(replaced) = replaced {
+        reply.set_sequence(replaced.sequence());
+        reply.set_created_at(replaced.created_at());
+    }
+}
+
 /// 重新生成命令处理器
 pub struct RegenerateHandler {
     session_repository: Arc<dyn SessionRepository>,
     message_repository: Arc<dyn MessageRepository>,
     llm_port: Arc<dyn LLMPort>,
     emotion_analyzer: EmotionAnalyzer,
+    content_filter: Option<Arc<dyn ContentFilter>>,
+    repetition_guard: Option<RepetitionPolicy>,
     default_model: String,
 }
 
@@ -67,58 +167,146 @@ impl RegenerateHandler {
             message_repository,
             llm_port,
             emotion_analyzer: EmotionAnalyzer::new(),
+            content_filter: None,
+            repetition_guard: None,
             default_model: default_model.into(),
         }
     }
 
-    /// 构建聊天上下文（包括最后一条用户消息）
+    /// 设置助手回复的内容过滤器
+    pub fn with_content_filter(mut self, content_filter: Option<Arc<dyn ContentFilter>>) -> Self {
+        self.content_filter = content_filter;
+        self
+    }
+
+    /// 设置流式回复的重复检测，None 表示不检测
+    pub fn with_repetition_guard(mut self, repetition_guard: Option<RepetitionPolicy>) -> Self {
+        self.repetition_guard = repetition_guard;
+        self
+    }
+
+    /// 设置情感分析器（如扩展了表情映射表的分析器）
+    pub fn with_emotion_analyzer(mut self, emotion_analyzer: EmotionAnalyzer) -> Self {
+        self.emotion_analyzer = emotion_analyzer;
+        self
+    }
+
+    /// 构建聊天上下文（包括最后一条用户消息），指定了目标时一并返回被替换的助手消息
     async fn build_context(
         &self,
-        session_id: SessionId,
-        user_content: &str,
-    ) -> Result<Vec<LLMChatMessage>, ApplicationError> {
-        // 获取历史消息（不包括最后一条，因为我们会用传入的 user_content）
-        let pagination = Pagination::new(1, 50);
-        let messages = self
-            .message_repository
-            .find_by_session(session_id, pagination)
-            .await?;
+        command: &RegenerateCommand,
+    ) -> Result<(Vec<LLMChatMessage>, Option<Message>), ApplicationError> {
+        let (history, replaced) = match command.target_message_id {
+            Some(target) => {
+                let (history, target) = self.history_before(command.session_id, target).await?;
+                (history, Some(target))
+            }
+            None => (self.trailing_history(command.session_id).await?, None),
+        };
 
-        let mut context = Vec::new();
+        let mut context = Vec::with_capacity(history.len() + 2);
 
         // 添加系统提示
-        context.push(LLMChatMessage {
-            role: "system".to_string(),
-            content: "You are a helpful AI assistant.".to_string(),
-        });
-
-        // 添加历史消息（排除最后一条用户消息，因为我们用传入的）
-        let mut history: Vec<_> = messages.items.into_iter().collect();
-        
-        // 1. 如果最后一条是 AI 消息（可能是我们要重新生成的那个），移除它
-        while history.last().map(|m| matches!(m.role(), crate::modules::chat::domain::MessageRole::Assistant)).unwrap_or(false) {
-            history.pop();
-        }
-
-        // 2. 如果最后一条是用户消息（我们要重新发送的那个），移除它
-        if history.last().map(|m| matches!(m.role(), crate::modules::chat::domain::MessageRole::User)).unwrap_or(false) {
-            history.pop();
+        if let Some(system_prompt) = command
+            .system_prompt
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+        {
+            context.push(LLMChatMessage {
+                role: "system".to_string(),
+                content: system_prompt.to_string(),
+                images: Vec::new(),
+            });
         }
 
         for msg in history {
             context.push(LLMChatMessage {
                 role: msg.role().to_openai_role().to_string(),
                 content: msg.content().to_string(),
+                images: Vec::new(),
             });
         }
 
         // 添加当前用户消息内容
         context.push(LLMChatMessage {
             role: "user".to_string(),
-            content: user_content.to_string(),
+            content: command.user_content.to_string(),
+            images: Vec::new(),
         });
 
-        Ok(context)
+        Ok((context, replaced))
+    }
+
+    /// 取得指定助手消息之前的历史（不含紧邻的用户消息，由 user_content 代替）和该消息
+    async fn history_before(
+        &self,
+        session_id: SessionId,
+        target: MessageId,
+    ) -> Result<(Vec<Message>, Message), ApplicationError> {
+        let target_message = self
+            .message_repository
+            .get(target)
+            .await?
+            .filter(|message| message.session_id() == session_id)
+            .ok_or_else(|| ApplicationError::MessageNotFound(target.to_string()))?;
+        if target_message.role() != MessageRole::Assistant {
+            return Err(ApplicationError::ValidationError(
+                "Only assistant messages can be regenerated".to_string(),
+            ));
+        }
+
+        let total = self.message_repository.count_by_session(session_id).await?;
+        let limit = u32::try_from(total).unwrap_or(u32::MAX).max(1);
+        let messages = self
+            .message_repository
+            .find_by_session(session_id, Pagination::new(1, limit))
+            .await?;
+        let position = messages
+            .items
+            .iter()
+            .position(|message| message.id() == target)
+            .ok_or_else(|| ApplicationError::MessageNotFound(target.to_string()))?;
+
+        let mut history: Vec<Message> = context_history(&messages.items[..position])
+            .into_iter()
+            .cloned()
+            .collect();
+        if history
+            .last()
+            .is_some_and(|m| m.role() == MessageRole::User)
+        {
+            history.pop();
+        }
+        Ok((history, target_message))
+    }
+
+    /// 取得最近的历史并按末尾的消息推断要重新生成的回复
+    async fn trailing_history(
+        &self,
+        session_id: SessionId,
+    ) -> Result<Vec<Message>, ApplicationError> {
+        // 与发送消息一致：先取全部历史，过滤断点后再保留最近的部分
+        let total = self.message_repository.count_by_session(session_id).await?;
+        let messages = self
+            .message_repository
+            .find_by_session(session_id, Pagination::new(1, total.max(1) as u32))
+            .await?;
+        let mut history = context_history(&messages.items);
+
+        // 1. 如果最后一条是 AI 消息（可能是我们要重新生成的那个），移除它
+        while history.last().is_some_and(|m| m.role() == MessageRole::Assistant) {
+            history.pop();
+        }
+
+        // 2. 如果最后一条是用户消息（我们要重新发送的那个，由 user_content 代替），移除它
+        if history.last().is_some_and(|m| m.role() == MessageRole::User) {
+            history.pop();
+        }
+
+        Ok(ContextBuilder::most_recent(history, REGENERATE_CONTEXT_MESSAGES)
+            .into_iter()
+            .cloned()
+            .collect())
     }
 
     /// 处理流式响应（不保存用户消息）
@@ -126,6 +314,8 @@ impl RegenerateHandler {
         &self,
         command: RegenerateCommand,
     ) -> Result<(RegenerateResponse, mpsc::Receiver<StreamEvent>), ApplicationError> {
+        command.validate()?;
+
         // 验证会话存在
         let _session = self
             .session_repository
@@ -133,17 +323,29 @@ impl RegenerateHandler {
             .await?
             .ok_or_else(|| ApplicationError::SessionNotFound(command.session_id.to_string()))?;
 
-        // 创建助手消息（初始为空）
-        let assistant_message = Message::new_assistant(command.session_id, "", None);
-
         // 构建上下文（不保存用户消息）
-        let context = self
-            .build_context(command.session_id, &command.user_content)
-            .await?;
+        let (context, replaced) = self.build_context(&command).await?;
+
+        // 创建助手消息（初始为空）
+        let mut assistant_message = Message::new_assistant(command.session_id, "", None);
+        take_place_of(&mut assistant_message, replaced.as_ref());
 
         // 创建补全请求
         let model = command.model.unwrap_or_else(|| self.default_model.clone());
-        let request = CompletionRequest::new(context, model);
+        let prefix =
+            effective_assistant_prefix(self.llm_port.as_ref(), command.assistant_prefix.as_deref());
+        let mut request = apply_stop_sequences(
+            apply_sampling(
+                CompletionRequest::new(context, model).with_request_id(command.request_id),
+                command.temperature,
+                command.max_tokens,
+            ),
+            command.stop_sequences,
+            command.user_name.as_deref(),
+        );
+        if let Some(prefix) = &prefix {
+            request = request.with_assistant_prefix(prefix.clone());
+        }
 
         // 创建响应通道
         let (tx, rx) = mpsc::channel::<StreamEvent>(32);
@@ -152,32 +354,88 @@ impl RegenerateHandler {
         let llm = self.llm_port.clone();
         let message_repo = self.message_repository.clone();
         let emotion_analyzer = self.emotion_analyzer.clone();
+        let content_filter = self.content_filter.clone();
+        let repetition_guard = self.repetition_guard;
         let session_id = command.session_id;
+        let target_message_id = command.target_message_id;
+        let user_name = command.user_name;
         let assistant_msg = assistant_message.clone();
 
         tokio::spawn(async move {
+            let mut timer = PhaseTimer::start();
             let result = llm.complete_stream(request).await;
             match result {
                 Ok(mut stream) => {
                     let mut full_content = String::new();
                     let mut tokens_used = None;
+                    let mut finish_reason = None;
+                    // 内容块在发出前经过过滤，命中的词语不会发给前端
+                    let mut streaming_filter = StreamingContentFilter::new(content_filter.clone());
+
+                    // 预填的开头不会由提供商返回，先补发给前端
+                    if let Some(prefix) = prefix {
+                        full_content.push_str(&prefix);
+                        if let Some(text) = streaming_filter.push(&prefix) {
+                            if tx.send(StreamEvent::Chunk(text)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
 
                     while let Some(chunk_result) = stream.next().await {
                         match chunk_result {
                             Ok(chunk) => {
+                                timer.observe(&chunk);
                                 full_content.push_str(&chunk.content);
                                 if let Some(usage) = &chunk.usage {
                                     tokens_used = Some(usage.total_tokens);
                                 }
-                                let _ = tx.send(StreamEvent::Chunk(chunk.content)).await;
+                                if chunk.finish_reason.is_some() {
+                                    finish_reason = chunk.finish_reason;
+                                }
+                                if let Some(text) = streaming_filter.push(&chunk.content) {
+                                    let _ = tx.send(StreamEvent::Chunk(text)).await;
+                                }
+
+                                if let Some(found) =
+                                    check_repetition(repetition_guard.as_ref(), &full_content)
+                                {
+                                    let _ = tx
+                                        .send(StreamEvent::RepetitionStopped {
+                                            pattern: found.pattern,
+                                            repeats: found.repeats,
+                                        })
+                                        .await;
+                                    break;
+                                }
                             }
                             Err(e) => {
-                                let _ = tx.send(StreamEvent::Error(e.to_string())).await;
+                                let _ = tx.send(StreamEvent::Error(e)).await;
                                 return;
                             }
                         }
                     }
 
+                    if let Some(text) = streaming_filter.finish() {
+                        let _ = tx.send(StreamEvent::Chunk(text)).await;
+                    }
+
+                    // 保存和发布前截掉越界的用户发言并过滤内容
+                    let outcome = finalize_reply(
+                        &full_content,
+                        user_name.as_deref(),
+                        content_filter.as_ref(),
+                    );
+                    if outcome.is_filtered() || outcome.content != full_content {
+                        full_content = outcome.content;
+                        let _ = tx
+                            .send(StreamEvent::ContentFiltered {
+                                content: full_content.clone(),
+                                flagged_terms: outcome.flagged_terms,
+                            })
+                            .await;
+                    }
+
                     // 分析情感
                     let emotion = emotion_analyzer.analyze(&full_content);
 
@@ -185,21 +443,41 @@ impl RegenerateHandler {
                     let mut final_message =
                         Message::new_assistant(session_id, &full_content, emotion);
                     final_message.set_id(assistant_msg.id());
-
-                    if let Err(e) = message_repo.save(&final_message).await {
-                        let _ = tx.send(StreamEvent::Error(e.to_string())).await;
+                    final_message.set_sequence(assistant_msg.sequence());
+                    final_message.set_created_at(assistant_msg.created_at());
+                    final_message.set_finish_reason(finish_reason);
+
+                    if let Err(e) = save_with_retry(message_repo.as_ref(), &final_message).await {
+                        let _ = tx
+                            .send(StreamEvent::SaveFailed {
+                                full_content,
+                                error: format!("Failed to save message: {}", e),
+                            })
+                            .await;
                         return;
                     }
 
+                    // 新回复保存成功后才删除被替换的回复
+                    if let Some(target) = target_message_id {
+                        if let Err(e) = message_repo.delete(target).await {
+                            tracing::warn!(
+                                "Failed to delete regenerated message {}: {}",
+                                target,
+                                e
+                            );
+                        }
+                    }
+
                     let _ = tx
                         .send(StreamEvent::Done {
                             full_content,
                             tokens_used,
+                            timing: timer.finish(),
                         })
                         .await;
                 }
                 Err(e) => {
-                    let _ = tx.send(StreamEvent::Error(e.to_string())).await;
+                    let _ = tx.send(StreamEvent::Error(e)).await;
                 }
             }
         });
@@ -219,6 +497,8 @@ impl CommandHandler<RegenerateCommand, RegenerateResponse> for RegenerateHandler
         &self,
         command: RegenerateCommand,
     ) -> Result<RegenerateResponse, ApplicationError> {
+        command.validate()?;
+
         // 验证会话存在
         let _session = self
             .session_repository
@@ -227,25 +507,321 @@ impl CommandHandler<RegenerateCommand, RegenerateResponse> for RegenerateHandler
             .ok_or_else(|| ApplicationError::SessionNotFound(command.session_id.to_string()))?;
 
         // 构建上下文
-        let context = self
-            .build_context(command.session_id, &command.user_content)
-            .await?;
+        let (context, replaced) = self.build_context(&command).await?;
 
         // 创建补全请求
         let model = command.model.unwrap_or_else(|| self.default_model.clone());
-        let request = CompletionRequest::new(context, model);
+        let prefix =
+            effective_assistant_prefix(self.llm_port.as_ref(), command.assistant_prefix.as_deref());
+        let mut request = apply_stop_sequences(
+            apply_sampling(
+                CompletionRequest::new(context, model).with_request_id(command.request_id),
+                command.temperature,
+                command.max_tokens,
+            ),
+            command.stop_sequences,
+            command.user_name.as_deref(),
+        );
+        if let Some(prefix) = &prefix {
+            request = request.with_assistant_prefix(prefix.clone());
+        }
 
         // 调用 LLM
         let response = self.llm_port.complete(request).await?;
+        let content = match prefix {
+            Some(prefix) => prefix + &response.content,
+            None => response.content,
+        };
+        let content = finalize_reply(
+            &content,
+            command.user_name.as_deref(),
+            self.content_filter.as_ref(),
+        )
+        .content;
 
         // 分析情感
-        let emotion = self.emotion_analyzer.analyze(&response.content);
+        let emotion = self.emotion_analyzer.analyze(&content);
 
         // 创建并保存助手消息
-        let assistant_message =
-            Message::new_assistant(command.session_id, &response.content, emotion);
+        let mut assistant_message = Message::new_assistant(command.session_id, &content, emotion);
+        take_place_of(&mut assistant_message, replaced.as_ref());
+        assistant_message.set_finish_reason(Some(response.finish_reason));
         self.message_repository.save(&assistant_message).await?;
 
+        // 新回复保存成功后才删除被替换的回复
+        if let Some(target) = command.target_message_id {
+            self.message_repository.delete(target).await?;
+        }
+
         Ok(RegenerateResponse { assistant_message })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::Session;
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use crate::modules::chat::test_support::{collect_events, ScriptedLLM};
+
+    struct Fixture {
+        handler: RegenerateHandler,
+        llm: Arc<ScriptedLLM>,
+        message_repo: Arc<InMemoryMessageRepository>,
+        session_id: SessionId,
+        ids: Vec<MessageId>,
+    }
+
+    /// 使用给定历史创建会话
+    async fn fixture(history: &[(MessageRole, &str)]) -> Fixture {
+        fixture_with(ScriptedLLM::replying("Regenerated"), history).await
+    }
+
+    /// 使用给定的 LLM 和历史创建会话
+    async fn fixture_with(llm: ScriptedLLM, history: &[(MessageRole, &str)]) -> Fixture {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(llm);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let mut ids = Vec::new();
+        for (role, content) in history {
+            let message = match role {
+                MessageRole::User => Message::new_user(session_id, *content),
+                _ => Message::new_assistant(session_id, *content, None),
+            };
+            message_repo.save(&message).await.unwrap();
+            ids.push(message.id());
+        }
+
+        let handler =
+            RegenerateHandler::new(session_repo, message_repo.clone(), llm.clone(), "model");
+        Fixture {
+            handler,
+            llm,
+            message_repo,
+            session_id,
+            ids,
+        }
+    }
+
+    /// 最近一次请求的上下文（不含系统提示）
+    fn sent_context(llm: &ScriptedLLM) -> Vec<(String, String)> {
+        let request = llm.requests().pop().unwrap();
+        request
+            .messages
+            .into_iter()
+            .filter(|m| m.role != "system")
+            .map(|m| (m.role, m.content))
+            .collect()
+    }
+
+    fn turn(role: &str, content: &str) -> (String, String) {
+        (role.to_string(), content.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_target_in_middle_ignores_later_messages() {
+        let f = fixture(&[
+            (MessageRole::User, "Q1"),
+            (MessageRole::Assistant, "A1"),
+            (MessageRole::User, "Q2"),
+            (MessageRole::Assistant, "A2"),
+            (MessageRole::User, "Q3"),
+        ])
+        .await;
+
+        // 推断路径把 A2 当作历史保留
+        let command = RegenerateCommand::new(f.session_id, "Q2", None, false);
+        f.handler.handle(command).await.unwrap();
+        assert!(sent_context(&f.llm).contains(&turn("assistant", "A2")));
+
+        let command =
+            RegenerateCommand::new(f.session_id, "Q2", None, true).with_target_message(f.ids[3]);
+        let (response, mut rx) = f.handler.handle_stream(command).await.unwrap();
+        let events = collect_events(&mut rx).await;
+        assert!(matches!(events.last(), Some(StreamEvent::Done { .. })));
+
+        assert_eq!(
+            sent_context(&f.llm),
+            vec![turn("user", "Q1"), turn("assistant", "A1"), turn("user", "Q2")]
+        );
+        // 只删除目标消息
+        assert!(f.message_repo.get(f.ids[3]).await.unwrap().is_none());
+        assert!(f.message_repo.get(f.ids[4]).await.unwrap().is_some());
+        let saved = f
+            .message_repo
+            .get(response.assistant_message.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.content(), "Regenerated");
+
+        // 新回复留在 A2 原来的位置；推断路径的回复追加在末尾
+        let contents: Vec<String> = f
+            .message_repo
+            .find_by_session(f.session_id, Pagination::new(1, 100))
+            .await
+            .unwrap()
+            .items
+            .iter()
+            .map(|m| m.content().to_string())
+            .collect();
+        assert_eq!(
+            contents,
+            vec!["Q1", "A1", "Q2", "Regenerated", "Q3", "Regenerated"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_sent_only_when_set() {
+        let f = fixture(&[(MessageRole::User, "Q1"), (MessageRole::Assistant, "A1")]).await;
+
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, false);
+        f.handler.handle(command).await.unwrap();
+        assert!(f.llm.requests()[0].messages.iter().all(|m| m.role != "system"));
+
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, false)
+            .with_system_prompt("You are Hiyori.");
+        f.handler.handle(command).await.unwrap();
+        let first = &f.llm.requests()[1].messages[0];
+        assert_eq!((first.role.as_str(), first.content.as_str()), ("system", "You are Hiyori."));
+    }
+
+    #[tokio::test]
+    async fn test_assistant_prefix_starts_regenerated_reply() {
+        let f = fixture_with(
+            ScriptedLLM::replying(" Hello!").with_assistant_prefix_support(),
+            &[(MessageRole::User, "Hi"), (MessageRole::Assistant, "Old")],
+        )
+        .await;
+
+        let command = RegenerateCommand::new(f.session_id, "Hi", None, true)
+            .with_target_message(f.ids[1])
+            .with_assistant_prefix("Kizuna: ");
+        let (response, mut rx) = f.handler.handle_stream(command).await.unwrap();
+        let events = collect_events(&mut rx).await;
+
+        assert_eq!(
+            f.llm.requests()[0].assistant_prefix.as_deref(),
+            Some("Kizuna:")
+        );
+        assert!(matches!(&events[0], StreamEvent::Chunk(c) if c == "Kizuna:"));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { full_content, .. }) if full_content == "Kizuna: Hello!"
+        ));
+        let saved = f
+            .message_repo
+            .get(response.assistant_message.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.content(), "Kizuna: Hello!");
+
+        // 非流式路径同样以前缀开头
+        let command = RegenerateCommand::new(f.session_id, "Hi", None, false)
+            .with_assistant_prefix("Kizuna: ");
+        let response = f.handler.handle(command).await.unwrap();
+        assert_eq!(response.assistant_message.content(), "Kizuna: Hello!");
+    }
+
+    #[tokio::test]
+    async fn test_inferred_target_uses_most_recent_messages() {
+        let mut history = Vec::new();
+        for i in 0..REGENERATE_CONTEXT_MESSAGES + 10 {
+            let role = if i % 2 == 0 {
+                MessageRole::User
+            } else {
+                MessageRole::Assistant
+            };
+            history.push((role, format!("message {}", i)));
+        }
+        let history: Vec<(MessageRole, &str)> =
+            history.iter().map(|(role, c)| (*role, c.as_str())).collect();
+        let f = fixture(&history).await;
+
+        let command = RegenerateCommand::new(f.session_id, "last question", None, false);
+        f.handler.handle(command).await.unwrap();
+
+        let context = sent_context(&f.llm);
+        // 末尾的问答由本次重新生成代替，其余保留最近的部分
+        let last = REGENERATE_CONTEXT_MESSAGES + 10 - 3;
+        assert_eq!(context[context.len() - 2].1, format!("message {}", last));
+        assert!(!context.iter().any(|(_, c)| c == "message 0"));
+        assert!(context.len() <= REGENERATE_CONTEXT_MESSAGES + 1);
+    }
+
+    #[tokio::test]
+    async fn test_target_followed_by_unanswered_user_message() {
+        let f = fixture(&[
+            (MessageRole::User, "Q1"),
+            (MessageRole::Assistant, "A1"),
+            (MessageRole::User, "Q2"),
+        ])
+        .await;
+
+        // 推断路径只移除了 Q2，A1 仍在上下文中
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, false);
+        f.handler.handle(command).await.unwrap();
+        assert_eq!(
+            sent_context(&f.llm),
+            vec![turn("user", "Q1"), turn("assistant", "A1"), turn("user", "Q1")]
+        );
+
+        let command =
+            RegenerateCommand::new(f.session_id, "Q1", None, false).with_target_message(f.ids[1]);
+        f.handler.handle(command).await.unwrap();
+        assert_eq!(sent_context(&f.llm), vec![turn("user", "Q1")]);
+        assert!(f.message_repo.get(f.ids[1]).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_target_is_rejected() {
+        let f = fixture(&[(MessageRole::User, "Q1"), (MessageRole::Assistant, "A1")]).await;
+
+        let command =
+            RegenerateCommand::new(f.session_id, "Q1", None, false).with_target_message(f.ids[0]);
+        assert!(matches!(
+            f.handler.handle(command).await,
+            Err(ApplicationError::ValidationError(_))
+        ));
+
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, false)
+            .with_target_message(MessageId::new());
+        assert!(matches!(
+            f.handler.handle(command).await,
+            Err(ApplicationError::MessageNotFound(_))
+        ));
+
+        // 失败时不删除任何消息，也不请求 LLM
+        assert_eq!(f.message_repo.count_by_session(f.session_id).await.unwrap(), 2);
+        assert!(f.llm.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sampling_params_validated_and_forwarded() {
+        let f = fixture(&[(MessageRole::User, "Q1"), (MessageRole::Assistant, "A1")]).await;
+
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, true).with_temperature(3.0);
+        assert!(matches!(
+            f.handler.handle_stream(command).await,
+            Err(ApplicationError::ValidationError(_))
+        ));
+        assert!(f.llm.requests().is_empty());
+
+        let command = RegenerateCommand::new(f.session_id, "Q1", None, false)
+            .with_temperature(1.2)
+            .with_max_tokens(64);
+        f.handler.handle(command).await.unwrap();
+
+        let request = f.llm.requests().pop().unwrap();
+        assert_eq!(request.temperature, Some(1.2));
+        assert_eq!(request.max_tokens, Some(64));
+    }
+}
@@ -1,14 +1,26 @@
 use async_trait::async_trait;
 use futures::StreamExt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 use super::super::{ApplicationError, CommandHandler};
-use crate::modules::chat::domain::{ContextBuilder, EmotionAnalyzer, Message, Session, SessionId};
+use crate::modules::chat::domain::{
+    apply_content_filter, detect_repetition, role_play_stop_sequences, trim_user_turn_leak,
+    ContentFilter, ContextBuilder, EmotionAnalyzer, FilterOutcome, Message, RepetitionMatch,
+    RepetitionPolicy, Session, SessionId, StreamingContentFilter,
+};
 use crate::modules::chat::ports::{
-    CompletionRequest, LLMChatMessage, LLMPort, MessageRepository, SessionRepository,
+    CompletionRequest, CompletionResponse, ImageAttachment, LLMChatMessage, LLMError, LLMPort,
+    MessageRepository, RepositoryError, SessionRepository, StreamChunk, TEMPERATURE_RANGE,
 };
 
+/// 保存助手消息的最大尝试次数
+const SAVE_MAX_ATTEMPTS: u32 = 3;
+
+/// 保存重试的初始退避时间（每次翻倍）
+const SAVE_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
 /// 发送消息命令
 #[derive(Debug, Clone)]
 pub struct SendMessageCommand {
@@ -20,6 +32,28 @@ pub struct SendMessageCommand {
     pub model: Option<String>,
     /// 是否使用流式响应
     pub stream: bool,
+    /// 随用户消息发送的图片（不保存到消息历史）
+    pub images: Vec<ImageAttachment>,
+    /// 助手回复的开头，提供商不支持预填时忽略
+    pub assistant_prefix: Option<String>,
+    /// 系统提示（如会话预设的系统提示词），None 或空白时不发送系统提示
+    pub system_prompt: Option<String>,
+    /// 请求 ID，随补全请求传给提供商，用于取消和事件关联
+    pub request_id: String,
+    /// 最多携带的历史消息条数（不含系统提示和当前消息），None 表示不限制
+    pub context_length: Option<usize>,
+    /// 消息内容的最大字符数，None 表示不限制
+    pub max_content_chars: Option<usize>,
+    /// 温度，None 时使用提供商默认值
+    pub temperature: Option<f32>,
+    /// 最大生成 token 数，None 时使用提供商默认值
+    pub max_tokens: Option<u32>,
+    /// 停止序列，None 时角色扮演模式使用默认的用户发言标签，否则不设置
+    pub stop_sequences: Option<Vec<String>>,
+    /// 角色扮演中用户的名字，设置后启用角色扮演模式（截掉越界生成的用户发言）
+    pub user_name: Option<String>,
+    /// 是否分析并保存用户消息的情感
+    pub analyze_user_emotion: bool,
 }
 
 impl SendMessageCommand {
@@ -34,8 +68,105 @@ impl SendMessageCommand {
             content: content.into(),
             model,
             stream,
+            images: Vec::new(),
+            assistant_prefix: None,
+            system_prompt: None,
+            request_id: uuid::Uuid::new_v4().to_string(),
+            context_length: None,
+            max_content_chars: None,
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            user_name: None,
+            analyze_user_emotion: false,
         }
     }
+
+    /// 附加图片
+    pub fn with_images(mut self, images: Vec<ImageAttachment>) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// 设置助手回复的开头
+    pub fn with_assistant_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.assistant_prefix = Some(prefix.into());
+        self
+    }
+
+    /// 设置系统提示
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// 使用调用方提供的请求 ID（默认随机生成）
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = request_id.into();
+        self
+    }
+
+    /// 限制携带的历史消息条数（对应配置项 llm.context_length）
+    pub fn with_context_length(mut self, context_length: usize) -> Self {
+        self.context_length = Some(context_length);
+        self
+    }
+
+    /// 限制消息内容的字符数（对应配置项 llm.max_user_message_chars）
+    pub fn with_max_content_chars(mut self, max_chars: usize) -> Self {
+        self.max_content_chars = Some(max_chars);
+        self
+    }
+
+    /// 设置温度
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// 设置最大生成 token 数
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// 设置停止序列
+    pub fn with_stop_sequences(mut self, stop_sequences: Vec<String>) -> Self {
+        self.stop_sequences = Some(stop_sequences);
+        self
+    }
+
+    /// 以给定的用户名启用角色扮演模式
+    pub fn with_user_name(mut self, user_name: impl Into<String>) -> Self {
+        self.user_name = Some(user_name.into());
+        self
+    }
+
+    /// 分析用户消息的情感（对应配置项 llm.analyze_user_emotion）
+    pub fn with_user_emotion(mut self) -> Self {
+        self.analyze_user_emotion = true;
+        self
+    }
+
+    /// 校验消息内容：不能为空，且不超过字符数上限；同时校验采样参数
+    pub fn validate(&self) -> Result<(), ApplicationError> {
+        validate_sampling(self.temperature, self.max_tokens)?;
+        if self.content.trim().is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "Message content cannot be empty".to_string(),
+            ));
+        }
+        if let Some(max_chars) = self.max_content_chars {
+            let chars = self.content.chars().count();
+            if chars > max_chars {
+                return Err(ApplicationError::ValidationError(format!(
+                    "Message is too long: {} characters exceeds the limit of {}",
+                    chars, max_chars
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// 发送消息响应
@@ -56,9 +187,244 @@ pub enum StreamEvent {
     Done {
         full_content: String,
         tokens_used: Option<u32>,
+        timing: ResponseTiming,
     },
-    /// 错误
-    Error(String),
+    /// 内容在保存前被修改：过滤器命中或截掉了越界的用户发言（在 Done 之前发送，携带修改后的完整内容）
+    ContentFiltered {
+        content: String,
+        flagged_terms: Vec<String>,
+    },
+    /// 错误，保留原始类型以便调用方区分取消等情况
+    Error(LLMError),
+    /// 生成完成但保存失败（携带完整内容以便前端恢复）
+    SaveFailed { full_content: String, error: String },
+    /// 回复陷入重复循环，已提前停止（在 Done 之前发送，已生成的部分照常保存）
+    RepetitionStopped { pattern: String, repeats: usize },
+}
+
+impl StreamEvent {
+    /// 将非流式补全结果转换为等价的事件：一个内容块和携带用量的完成事件
+    pub fn from_completion(response: &CompletionResponse) -> Vec<Self> {
+        vec![
+            Self::Chunk(response.content.clone()),
+            Self::Done {
+                full_content: response.content.clone(),
+                tokens_used: Some(response.usage.total_tokens),
+                timing: ResponseTiming::default(),
+            },
+        ]
+    }
+}
+
+/// 校验采样参数：温度须在 [`TEMPERATURE_RANGE`] 内，最大 token 数须大于 0
+pub(crate) fn validate_sampling(
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+) -> Result<(), ApplicationError> {
+    if let Some(temperature) = temperature {
+        if !TEMPERATURE_RANGE.contains(&temperature) {
+            return Err(ApplicationError::ValidationError(format!(
+                "Temperature must be between {} and {}, got {}",
+                TEMPERATURE_RANGE.start(),
+                TEMPERATURE_RANGE.end(),
+                temperature
+            )));
+        }
+    }
+    if max_tokens == Some(0) {
+        return Err(ApplicationError::ValidationError(
+            "max_tokens must be greater than 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 将调用方指定的采样参数写入补全请求，未指定的保持提供商默认值
+pub(crate) fn apply_sampling(
+    mut request: CompletionRequest,
+    temperature: Option<f32>,
+    max_tokens: Option<u32>,
+) -> CompletionRequest {
+    if let Some(temperature) = temperature {
+        request = request.with_temperature(temperature);
+    }
+    if let Some(max_tokens) = max_tokens {
+        request = request.with_max_tokens(max_tokens);
+    }
+    request
+}
+
+/// 实际生效的助手前缀
+///
+/// 提供商不支持预填或前缀为空时返回 `None`；去掉末尾空白（Claude 不接受以空白结尾的预填）
+pub(crate) fn effective_assistant_prefix(
+    llm_port: &dyn LLMPort,
+    prefix: Option<&str>,
+) -> Option<String> {
+    let prefix = prefix.map(str::trim_end).filter(|p| !p.is_empty())?;
+    if !llm_port.provider_info().supports_assistant_prefix {
+        tracing::debug!("Provider does not support assistant prefix, ignoring");
+        return None;
+    }
+    Some(prefix.to_string())
+}
+
+/// 写入停止序列：调用方未指定时，角色扮演模式使用默认的用户发言标签
+pub(crate) fn apply_stop_sequences(
+    request: CompletionRequest,
+    stop_sequences: Option<Vec<String>>,
+    user_name: Option<&str>,
+) -> CompletionRequest {
+    match stop_sequences.or_else(|| user_name.map(role_play_stop_sequences)) {
+        Some(stop_sequences) if !stop_sequences.is_empty() => {
+            request.with_stop_sequences(stop_sequences)
+        }
+        _ => request,
+    }
+}
+
+/// 保存前整理助手回复：角色扮演模式下先截掉越界生成的用户发言，再应用内容过滤
+pub(crate) fn finalize_reply(
+    content: &str,
+    user_name: Option<&str>,
+    content_filter: Option<&Arc<dyn ContentFilter>>,
+) -> FilterOutcome {
+    let content = match user_name {
+        Some(user_name) => trim_user_turn_leak(content, user_name),
+        None => content,
+    };
+    apply_content_filter(content_filter, content)
+}
+
+/// 启用了重复检测且回复末尾陷入循环时返回检测结果
+pub(crate) fn check_repetition(
+    guard: Option<&RepetitionPolicy>,
+    content: &str,
+) -> Option<RepetitionMatch> {
+    let found = detect_repetition(content, guard?)?;
+    tracing::warn!(
+        "Stopping generation: {:?} repeated {} times",
+        found.pattern,
+        found.repeats
+    );
+    Some(found)
+}
+
+/// 以非流式补全结果构造事件通道，使非流式路径也能复用流式事件管道
+pub fn completion_events(response: &CompletionResponse) -> mpsc::Receiver<StreamEvent> {
+    let events = StreamEvent::from_completion(response);
+    let (tx, rx) = mpsc::channel(events.len());
+    for event in events {
+        // 容量与事件数相同，不会失败
+        let _ = tx.try_send(event);
+    }
+    rx
+}
+
+/// 响应各阶段耗时
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseTiming {
+    /// 推理阶段耗时（毫秒），模型未输出推理内容时为 None
+    pub reasoning_ms: Option<u64>,
+    /// 回答阶段耗时（毫秒），模型未输出回答内容时为 None
+    pub answer_ms: Option<u64>,
+}
+
+/// 流式响应阶段计时器
+///
+/// 以推理块到内容块的切换点划分推理阶段与回答阶段
+#[derive(Debug)]
+pub(crate) struct PhaseTimer {
+    started_at: Instant,
+    saw_reasoning: bool,
+    answer_started_at: Option<Instant>,
+}
+
+impl PhaseTimer {
+    pub(crate) fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+            saw_reasoning: false,
+            answer_started_at: None,
+        }
+    }
+
+    /// 记录一个流式块
+    pub(crate) fn observe(&mut self, chunk: &StreamChunk) {
+        if chunk.reasoning.as_deref().is_some_and(|r| !r.is_empty()) {
+            self.saw_reasoning = true;
+        }
+        if !chunk.content.is_empty() && self.answer_started_at.is_none() {
+            self.answer_started_at = Some(Instant::now());
+        }
+    }
+
+    pub(crate) fn finish(&self) -> ResponseTiming {
+        let finished_at = Instant::now();
+        let millis = |from: Instant, to: Instant| to.duration_since(from).as_millis() as u64;
+
+        match (self.saw_reasoning, self.answer_started_at) {
+            (true, Some(answer_started_at)) => ResponseTiming {
+                reasoning_ms: Some(millis(self.started_at, answer_started_at)),
+                answer_ms: Some(millis(answer_started_at, finished_at)),
+            },
+            (true, None) => ResponseTiming {
+                reasoning_ms: Some(millis(self.started_at, finished_at)),
+                answer_ms: None,
+            },
+            (false, Some(_)) => ResponseTiming {
+                reasoning_ms: None,
+                answer_ms: Some(millis(self.started_at, finished_at)),
+            },
+            (false, None) => ResponseTiming::default(),
+        }
+    }
+}
+
+/// 带退避重试的消息保存
+///
+/// 用于流式生成结束后的落盘，避免瞬时的存储错误导致生成内容丢失
+pub(crate) async fn save_with_retry(
+    repository: &dyn MessageRepository,
+    message: &Message,
+) -> Result<(), RepositoryError> {
+    let mut delay = SAVE_RETRY_BASE_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match repository.save(message).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < SAVE_MAX_ATTEMPTS => {
+                tracing::warn!(
+                    "Failed to save message {} (attempt {}/{}): {}",
+                    message.id(),
+                    attempt,
+                    SAVE_MAX_ATTEMPTS,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 取得发送给 LLM 的历史消息
+///
+/// 仅保留最近一次上下文断点之后的部分，并过滤空消息
+pub(crate) fn context_history(messages: &[Message]) -> Vec<&Message> {
+    let history = ContextBuilder::after_last_break(messages);
+    let cleaned = ContextBuilder::sanitize(history);
+
+    if cleaned.len() < history.len() {
+        tracing::warn!(
+            "Filtered {} malformed message(s) from context",
+            history.len() - cleaned.len()
+        );
+    }
+    cleaned
 }
 
 /// 发送消息命令处理器
@@ -69,6 +435,8 @@ pub struct SendMessageHandler {
     #[allow(dead_code)] // TODO: 将在后续实现上下文构建时使用
     context_builder: ContextBuilder,
     emotion_analyzer: EmotionAnalyzer,
+    content_filter: Option<Arc<dyn ContentFilter>>,
+    repetition_guard: Option<RepetitionPolicy>,
     default_model: String,
 }
 
@@ -85,40 +453,89 @@ impl SendMessageHandler {
             llm_port,
             context_builder: ContextBuilder::new(),
             emotion_analyzer: EmotionAnalyzer::new(),
+            content_filter: None,
+            repetition_guard: None,
             default_model: default_model.into(),
         }
     }
 
+    /// 设置助手回复的内容过滤器
+    pub fn with_content_filter(mut self, content_filter: Option<Arc<dyn ContentFilter>>) -> Self {
+        self.content_filter = content_filter;
+        self
+    }
+
+    /// 设置流式回复的重复检测，None 表示不检测
+    pub fn with_repetition_guard(mut self, repetition_guard: Option<RepetitionPolicy>) -> Self {
+        self.repetition_guard = repetition_guard;
+        self
+    }
+
+    /// 设置情感分析器（如扩展了表情映射表的分析器）
+    pub fn with_emotion_analyzer(mut self, emotion_analyzer: EmotionAnalyzer) -> Self {
+        self.emotion_analyzer = emotion_analyzer;
+        self
+    }
+
+    /// 创建并保存用户消息，按命令设置分析其情感
+    async fn save_user_message(
+        &self,
+        command: &SendMessageCommand,
+    ) -> Result<Message, ApplicationError> {
+        let mut user_message = Message::new_user(command.session_id, &command.content);
+        if command.analyze_user_emotion {
+            let emotion = self.emotion_analyzer.analyze_message(&user_message);
+            user_message.set_emotion(emotion);
+        }
+        self.message_repository.save(&user_message).await?;
+        Ok(user_message)
+    }
+
     /// 构建聊天上下文
+    ///
+    /// `context_length` 限制携带的历史消息条数，系统提示和当前消息始终保留
     async fn build_context(
         &self,
         session: &Session,
         user_message: &Message,
+        system_prompt: Option<&str>,
+        context_length: Option<usize>,
     ) -> Result<Vec<LLMChatMessage>, ApplicationError> {
-        // 获取历史消息
-        let pagination = crate::modules::chat::ports::Pagination::new(1, 50);
-        let messages = self
+        // 获取全部历史消息，截断在过滤断点和无效消息之后进行
+        let total = self
+            .message_repository
+            .count_by_session(session.id())
+            .await?;
+        let pagination = crate::modules::chat::ports::Pagination::new(1, total.max(1) as u32);
+        let mut messages = self
             .message_repository
             .find_by_session(session.id(), pagination)
             .await?;
+        // 当前消息已保存，单独追加在末尾
+        messages.items.retain(|m| m.id() != user_message.id());
 
         // 构建上下文
         let mut context = Vec::with_capacity(messages.items.len() + 2);
 
-        // 添加系统提示（如果有预设）
-        if let Some(_preset_id) = session.preset_id() {
-            // TODO: 从预设仓储获取系统提示
+        // 添加系统提示
+        if let Some(system_prompt) = system_prompt.filter(|p| !p.trim().is_empty()) {
             context.push(LLMChatMessage {
                 role: "system".to_string(),
-                content: "You are a helpful AI assistant.".to_string(),
+                content: system_prompt.to_string(),
+                images: Vec::new(),
             });
         }
 
-        // 添加历史消息
-        for msg in &messages.items {
+        // 添加历史消息（仅最近一次上下文断点之后的部分）
+        let mut history = context_history(&messages.items);
+        if let Some(max_messages) = context_length {
+            history = ContextBuilder::most_recent(history, max_messages);
+        }
+        for msg in history {
             context.push(LLMChatMessage {
                 role: msg.role().to_openai_role().to_string(),
                 content: msg.content().to_string(),
+                images: Vec::new(),
             });
         }
 
@@ -126,6 +543,7 @@ impl SendMessageHandler {
         context.push(LLMChatMessage {
             role: "user".to_string(),
             content: user_message.content().to_string(),
+            images: Vec::new(),
         });
 
         Ok(context)
@@ -136,6 +554,8 @@ impl SendMessageHandler {
         &self,
         command: SendMessageCommand,
     ) -> Result<(SendMessageResponse, mpsc::Receiver<StreamEvent>), ApplicationError> {
+        command.validate()?;
+
         // 验证会话存在
         let session = self
             .session_repository
@@ -144,18 +564,40 @@ impl SendMessageHandler {
             .ok_or_else(|| ApplicationError::SessionNotFound(command.session_id.to_string()))?;
 
         // 创建用户消息
-        let user_message = Message::new_user(command.session_id, &command.content);
-        self.message_repository.save(&user_message).await?;
+        let user_message = self.save_user_message(&command).await?;
 
         // 创建助手消息（初始为空）
         let assistant_message = Message::new_assistant(command.session_id, "", None);
 
         // 构建上下文
-        let context = self.build_context(&session, &user_message).await?;
+        let mut context = self
+            .build_context(
+                &session,
+                &user_message,
+                command.system_prompt.as_deref(),
+                command.context_length,
+            )
+            .await?;
+        if let Some(current) = context.last_mut() {
+            current.images = command.images;
+        }
 
         // 创建补全请求
         let model = command.model.unwrap_or_else(|| self.default_model.clone());
-        let request = CompletionRequest::new(context, model);
+        let prefix =
+            effective_assistant_prefix(self.llm_port.as_ref(), command.assistant_prefix.as_deref());
+        let mut request = apply_stop_sequences(
+            apply_sampling(
+                CompletionRequest::new(context, model).with_request_id(command.request_id),
+                command.temperature,
+                command.max_tokens,
+            ),
+            command.stop_sequences,
+            command.user_name.as_deref(),
+        );
+        if let Some(prefix) = &prefix {
+            request = request.with_assistant_prefix(prefix.clone());
+        }
 
         // 创建响应通道
         let (tx, rx) = mpsc::channel::<StreamEvent>(32);
@@ -164,46 +606,107 @@ impl SendMessageHandler {
         let llm = self.llm_port.clone();
         let message_repo = self.message_repository.clone();
         let emotion_analyzer = self.emotion_analyzer.clone();
+        let content_filter = self.content_filter.clone();
+        let repetition_guard = self.repetition_guard;
         let _msg_id = assistant_message.id();
         let session_id = command.session_id;
+        let user_name = command.user_name;
 
         tokio::spawn(async move {
+            let mut timer = PhaseTimer::start();
             let result = llm.complete_stream(request).await;
             match result {
                 Ok(mut stream) => {
                     let mut full_content = String::new();
                     let mut tokens_used = None;
+                    let mut finish_reason = None;
+                    // 内容块在发出前经过过滤，命中的词语不会发给前端
+                    let mut streaming_filter = StreamingContentFilter::new(content_filter.clone());
+
+                    // 预填的开头不会由提供商返回，先补发给前端
+                    if let Some(prefix) = prefix {
+                        full_content.push_str(&prefix);
+                        if let Some(text) = streaming_filter.push(&prefix) {
+                            if tx.send(StreamEvent::Chunk(text)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
 
                     while let Some(chunk_result) = stream.next().await {
                         match chunk_result {
                             Ok(chunk) => {
+                                timer.observe(&chunk);
                                 full_content.push_str(&chunk.content);
 
-                                // 发送内容块
-                                if tx.send(StreamEvent::Chunk(chunk.content)).await.is_err() {
-                                    break;
+                                // 发送过滤后的内容块
+                                if let Some(text) = streaming_filter.push(&chunk.content) {
+                                    if tx.send(StreamEvent::Chunk(text)).await.is_err() {
+                                        break;
+                                    }
                                 }
 
-                                // 检查是否完成
+                                // 用量可能在结束原因之后单独返回
+                                if let Some(usage) = &chunk.usage {
+                                    tokens_used = Some(usage.total_tokens);
+                                }
                                 if chunk.finish_reason.is_some() {
-                                    tokens_used = chunk.usage.map(|u| u.total_tokens);
+                                    finish_reason = chunk.finish_reason;
+                                }
+
+                                // 陷入循环时停止接收，保留已生成的部分
+                                if let Some(found) =
+                                    check_repetition(repetition_guard.as_ref(), &full_content)
+                                {
+                                    let _ = tx
+                                        .send(StreamEvent::RepetitionStopped {
+                                            pattern: found.pattern,
+                                            repeats: found.repeats,
+                                        })
+                                        .await;
+                                    break;
                                 }
                             }
                             Err(e) => {
-                                let _ = tx.send(StreamEvent::Error(e.to_string())).await;
+                                let _ = tx.send(StreamEvent::Error(e)).await;
                                 return;
                             }
                         }
                     }
 
+                    if let Some(text) = streaming_filter.finish() {
+                        let _ = tx.send(StreamEvent::Chunk(text)).await;
+                    }
+
+                    // 保存和发布前截掉越界的用户发言并过滤内容
+                    let outcome = finalize_reply(
+                        &full_content,
+                        user_name.as_deref(),
+                        content_filter.as_ref(),
+                    );
+                    if outcome.is_filtered() || outcome.content != full_content {
+                        full_content = outcome.content;
+                        let _ = tx
+                            .send(StreamEvent::ContentFiltered {
+                                content: full_content.clone(),
+                                flagged_terms: outcome.flagged_terms,
+                            })
+                            .await;
+                    }
+
                     // 分析情感
                     let emotion = emotion_analyzer.analyze(&full_content);
 
                     // 保存完整的助手消息
-                    let final_message = Message::new_assistant(session_id, &full_content, emotion);
-                    if let Err(e) = message_repo.save(&final_message).await {
+                    let mut final_message =
+                        Message::new_assistant(session_id, &full_content, emotion);
+                    final_message.set_finish_reason(finish_reason);
+                    if let Err(e) = save_with_retry(message_repo.as_ref(), &final_message).await {
                         let _ = tx
-                            .send(StreamEvent::Error(format!("Failed to save message: {}", e)))
+                            .send(StreamEvent::SaveFailed {
+                                full_content,
+                                error: format!("Failed to save message: {}", e),
+                            })
                             .await;
                         return;
                     }
@@ -213,11 +716,12 @@ impl SendMessageHandler {
                         .send(StreamEvent::Done {
                             full_content,
                             tokens_used,
+                            timing: timer.finish(),
                         })
                         .await;
                 }
                 Err(e) => {
-                    let _ = tx.send(StreamEvent::Error(e.to_string())).await;
+                    let _ = tx.send(StreamEvent::Error(e)).await;
                 }
             }
         });
@@ -239,11 +743,7 @@ impl CommandHandler<SendMessageCommand, SendMessageResponse> for SendMessageHand
         command: SendMessageCommand,
     ) -> Result<SendMessageResponse, ApplicationError> {
         // 验证输入
-        if command.content.trim().is_empty() {
-            return Err(ApplicationError::ValidationError(
-                "Message content cannot be empty".to_string(),
-            ));
-        }
+        command.validate()?;
 
         // 验证会话存在
         let session = self
@@ -253,25 +753,57 @@ impl CommandHandler<SendMessageCommand, SendMessageResponse> for SendMessageHand
             .ok_or_else(|| ApplicationError::SessionNotFound(command.session_id.to_string()))?;
 
         // 创建用户消息
-        let user_message = Message::new_user(command.session_id, &command.content);
-        self.message_repository.save(&user_message).await?;
+        let user_message = self.save_user_message(&command).await?;
 
         // 构建上下文
-        let context = self.build_context(&session, &user_message).await?;
+        let mut context = self
+            .build_context(
+                &session,
+                &user_message,
+                command.system_prompt.as_deref(),
+                command.context_length,
+            )
+            .await?;
+        if let Some(current) = context.last_mut() {
+            current.images = command.images;
+        }
 
         // 创建补全请求
         let model = command.model.unwrap_or_else(|| self.default_model.clone());
-        let request = CompletionRequest::new(context, model);
+        let prefix =
+            effective_assistant_prefix(self.llm_port.as_ref(), command.assistant_prefix.as_deref());
+        let mut request = apply_stop_sequences(
+            apply_sampling(
+                CompletionRequest::new(context, model).with_request_id(command.request_id),
+                command.temperature,
+                command.max_tokens,
+            ),
+            command.stop_sequences,
+            command.user_name.as_deref(),
+        );
+        if let Some(prefix) = &prefix {
+            request = request.with_assistant_prefix(prefix.clone());
+        }
 
         // 非流式：等待完整响应
         let response = self.llm_port.complete(request).await?;
+        let content = match prefix {
+            Some(prefix) => prefix + &response.content,
+            None => response.content,
+        };
+        let content = finalize_reply(
+            &content,
+            command.user_name.as_deref(),
+            self.content_filter.as_ref(),
+        )
+        .content;
 
         // 分析情感
-        let emotion = self.emotion_analyzer.analyze(&response.content);
+        let emotion = self.emotion_analyzer.analyze(&content);
 
         // 创建并保存助手消息
-        let assistant_message =
-            Message::new_assistant(command.session_id, &response.content, emotion);
+        let mut assistant_message = Message::new_assistant(command.session_id, &content, emotion);
+        assistant_message.set_finish_reason(Some(response.finish_reason));
         self.message_repository.save(&assistant_message).await?;
 
         Ok(SendMessageResponse {
@@ -285,14 +817,16 @@ impl CommandHandler<SendMessageCommand, SendMessageResponse> for SendMessageHand
 mod tests {
     use super::*;
     use crate::modules::chat::domain::Session;
+    use crate::modules::chat::domain::{Emotion, MessageId, MessageRole, WordListFilter};
     use crate::modules::chat::infrastructure::{
         InMemoryMessageRepository, InMemorySessionRepository,
     };
     use crate::modules::chat::ports::{
-        CompletionResponse, FinishReason, HealthStatus, LLMError, ModelInfo, ProviderInfo,
-        ProviderType, StreamChunk, TokenUsage,
+        CompletionResponse, FinishReason, HealthStatus, LLMError, ModelInfo, PaginatedResult,
+        Pagination, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
     };
     use std::pin::Pin;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
     /// Mock LLM Port for testing
     struct MockLLMPort;
@@ -309,6 +843,9 @@ mod tests {
                 name: "Mock Provider".to_string(),
                 provider_type: ProviderType::Custom,
                 models: vec![],
+                supports_cancellation: true,
+                supports_model_listing: false,
+                supports_assistant_prefix: false,
             }
         }
 
@@ -338,7 +875,21 @@ mod tests {
             Pin<Box<dyn futures::Stream<Item = Result<StreamChunk, LLMError>> + Send>>,
             LLMError,
         > {
-            Err(LLMError::Unknown("Not implemented".to_string()))
+            let chunks = vec![
+                Ok(StreamChunk {
+                    content: "Hello! ".to_string(),
+                    reasoning: None,
+                    finish_reason: None,
+                    usage: None,
+                }),
+                Ok(StreamChunk {
+                    content: "How can I help you?".to_string(),
+                    reasoning: None,
+                    finish_reason: Some(FinishReason::Stop),
+                    usage: None,
+                }),
+            ];
+            Ok(Box::pin(futures::stream::iter(chunks)))
         }
 
         async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
@@ -354,6 +905,93 @@ mod tests {
         }
     }
 
+    /// 保存助手消息时前 N 次失败的仓储
+    struct FlakyMessageRepository {
+        inner: InMemoryMessageRepository,
+        failures_left: AtomicU32,
+    }
+
+    impl FlakyMessageRepository {
+        fn new(failures: u32) -> Self {
+            Self {
+                inner: InMemoryMessageRepository::new(),
+                failures_left: AtomicU32::new(failures),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MessageRepository for FlakyMessageRepository {
+        async fn get(&self, id: MessageId) -> Result<Option<Message>, RepositoryError> {
+            self.inner.get(id).await
+        }
+
+        async fn save(&self, message: &Message) -> Result<(), RepositoryError> {
+            if message.role() == MessageRole::Assistant {
+                let left = self.failures_left.load(Ordering::SeqCst);
+                if left > 0 {
+                    self.failures_left.store(left - 1, Ordering::SeqCst);
+                    return Err(RepositoryError::DatabaseError("disk busy".to_string()));
+                }
+            }
+            self.inner.save(message).await
+        }
+
+        async fn update(&self, message: &Message) -> Result<(), RepositoryError> {
+            self.inner.update(message).await
+        }
+
+        async fn delete(&self, id: MessageId) -> Result<(), RepositoryError> {
+            self.inner.delete(id).await
+        }
+
+        async fn find_by_session(
+            &self,
+            session_id: SessionId,
+            pagination: Pagination,
+        ) -> Result<PaginatedResult<Message>, RepositoryError> {
+            self.inner.find_by_session(session_id, pagination).await
+        }
+
+        async fn delete_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+            self.inner.delete_by_session(session_id).await
+        }
+
+        async fn find_last_by_session(
+            &self,
+            session_id: SessionId,
+        ) -> Result<Option<Message>, RepositoryError> {
+            self.inner.find_last_by_session(session_id).await
+        }
+
+        async fn count_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+            self.inner.count_by_session(session_id).await
+        }
+
+        async fn count_all_by_session(
+            &self,
+        ) -> Result<std::collections::HashMap<SessionId, usize>, RepositoryError> {
+            self.inner.count_all_by_session().await
+        }
+
+        async fn search(
+            &self,
+            query: &str,
+            session_id: Option<SessionId>,
+            limit: usize,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            self.inner.search(query, session_id, limit).await
+        }
+
+        async fn flush(&self) -> Result<(), RepositoryError> {
+            self.inner.flush().await
+        }
+
+        async fn compact(&self) -> Result<usize, RepositoryError> {
+            self.inner.compact().await
+        }
+    }
+
     #[tokio::test]
     async fn test_send_message() {
         let session_repo = Arc::new(InMemorySessionRepository::new());
@@ -382,6 +1020,35 @@ mod tests {
         assert_eq!(count, 2);
     }
 
+    #[tokio::test]
+    async fn test_user_emotion_recorded_when_enabled() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let handler = SendMessageHandler::new(
+            session_repo,
+            message_repo.clone(),
+            Arc::new(MockLLMPort),
+            "gpt-3.5-turbo",
+        );
+
+        let command = SendMessageCommand::new(session_id, "太好了！我今天很开心！", None, false);
+        let response = handler.handle(command.clone()).await.unwrap();
+        assert_eq!(response.user_message.emotion(), None);
+
+        let response = handler.handle(command.with_user_emotion()).await.unwrap();
+        assert_eq!(response.user_message.emotion(), Some(Emotion::Happy));
+        let saved = message_repo
+            .get(response.user_message.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.emotion(), Some(Emotion::Happy));
+    }
+
     #[tokio::test]
     async fn test_send_empty_message() {
         let session_repo = Arc::new(InMemorySessionRepository::new());
@@ -399,4 +1066,568 @@ mod tests {
 
         assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
     }
+
+    #[tokio::test]
+    async fn test_message_length_limit() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let handler =
+            SendMessageHandler::new(session_repo, message_repo.clone(), llm, "gpt-3.5-turbo");
+
+        // 按字符而不是字节计数
+        let command =
+            SendMessageCommand::new(session_id, "你好世界", None, false).with_max_content_chars(4);
+        assert!(handler.handle(command).await.is_ok());
+
+        let command = SendMessageCommand::new(session_id, "你好，世界", None, false)
+            .with_max_content_chars(4);
+        match handler.handle(command).await {
+            Err(ApplicationError::ValidationError(message)) => {
+                assert!(message.contains("limit of 4"), "{}", message);
+            }
+            other => panic!("expected ValidationError, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(message_repo.count_by_session(session_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_save_retries_until_success() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(FlakyMessageRepository::new(2));
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let handler =
+            SendMessageHandler::new(session_repo, message_repo.clone(), llm, "gpt-3.5-turbo");
+
+        let command = SendMessageCommand::new(session_id, "Hello", None, true);
+        let (_, mut rx) = handler.handle_stream(command).await.unwrap();
+
+        let mut done = false;
+        while let Some(event) = rx.recv().await {
+            match event {
+                StreamEvent::Done { full_content, .. } => {
+                    assert_eq!(full_content, "Hello! How can I help you?");
+                    done = true;
+                }
+                StreamEvent::Error(e) => panic!("unexpected error: {}", e),
+                StreamEvent::SaveFailed { error, .. } => panic!("unexpected error: {}", error),
+                StreamEvent::Chunk(_)
+                | StreamEvent::ContentFiltered { .. }
+                | StreamEvent::RepetitionStopped { .. } => {}
+            }
+        }
+        assert!(done);
+
+        // 两次失败后第三次保存成功
+        let last = message_repo
+            .find_last_by_session(session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.role(), MessageRole::Assistant);
+        assert_eq!(last.content(), "Hello! How can I help you?");
+        assert_eq!(message_repo.count_by_session(session_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_chunks_filtered_before_emitting() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let filter: Arc<dyn ContentFilter> = Arc::new(WordListFilter::new(["help"]));
+        let handler = SendMessageHandler::new(session_repo, message_repo, llm, "gpt-3.5-turbo")
+            .with_content_filter(Some(filter));
+
+        let command = SendMessageCommand::new(session_id, "Hello", None, true);
+        let (_, mut rx) = handler.handle_stream(command).await.unwrap();
+
+        let mut chunks = Vec::new();
+        let mut full = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                StreamEvent::Chunk(chunk) => chunks.push(chunk),
+                StreamEvent::Done { full_content, .. } => full = Some(full_content),
+                _ => {}
+            }
+        }
+
+        assert!(chunks.iter().all(|chunk| !chunk.contains("help")));
+        assert_eq!(chunks.concat(), "Hello! How can I **** you?");
+        assert_eq!(full.as_deref(), Some("Hello! How can I **** you?"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_save_failure_keeps_content() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(FlakyMessageRepository::new(SAVE_MAX_ATTEMPTS));
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        let handler =
+            SendMessageHandler::new(session_repo, message_repo.clone(), llm, "gpt-3.5-turbo");
+
+        let command = SendMessageCommand::new(session_id, "Hello", None, true);
+        let (_, mut rx) = handler.handle_stream(command).await.unwrap();
+
+        let mut recovered = None;
+        while let Some(event) = rx.recv().await {
+            if let StreamEvent::SaveFailed { full_content, .. } = event {
+                recovered = Some(full_content);
+            }
+        }
+
+        assert_eq!(recovered.as_deref(), Some("Hello! How can I help you?"));
+        assert_eq!(message_repo.count_by_session(session_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_build_context_after_context_break() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        message_repo
+            .save(&Message::new_user(session_id, "Old question"))
+            .await
+            .unwrap();
+        message_repo
+            .save(&Message::new_assistant(session_id, "Old answer", None))
+            .await
+            .unwrap();
+        message_repo
+            .save(&Message::new_context_break(session_id))
+            .await
+            .unwrap();
+        message_repo
+            .save(&Message::new_user(session_id, "New question"))
+            .await
+            .unwrap();
+
+        let handler =
+            SendMessageHandler::new(session_repo, message_repo.clone(), llm, "gpt-3.5-turbo");
+
+        let current = Message::new_user(session_id, "Current");
+        let context = handler
+            .build_context(&session, &current, None, None)
+            .await
+            .unwrap();
+
+        let contents: Vec<_> = context.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["New question", "Current"]);
+
+        // 历史记录仍保留断点之前的消息
+        assert_eq!(message_repo.count_by_session(session_id).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_build_context_filters_malformed_history() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(MockLLMPort);
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+
+        for message in [
+            Message::new_assistant(session_id, "Leading greeting", None),
+            Message::new_user(session_id, "Question"),
+            Message::new_assistant(session_id, "", None),
+            Message::new_user(session_id, "Question again"),
+            Message::new_assistant(session_id, "Answer", None),
+        ] {
+            message_repo.save(&message).await.unwrap();
+        }
+
+        let handler = SendMessageHandler::new(session_repo, message_repo, llm, "gpt-3.5-turbo");
+
+        let current = Message::new_user(session_id, "Current");
+        let context = handler
+            .build_context(&session, &current, None, None)
+            .await
+            .unwrap();
+
+        let turns: Vec<_> = context
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                ("assistant", "Leading greeting"),
+                ("user", "Question"),
+                ("user", "Question again"),
+                ("assistant", "Answer"),
+                ("user", "Current"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_reports_reasoning_and_answer_time() {
+        use crate::modules::chat::test_support::{collect_events, ChatTestHarness, ScriptedLLM};
+
+        let llm = ScriptedLLM::new()
+            .reasoning("Let me think")
+            .delay(Duration::from_millis(30))
+            .reasoning(" about it")
+            .chunk("The answer")
+            .delay(Duration::from_millis(30))
+            .chunk(" is 42");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Question").await.unwrap();
+        let events = collect_events(&mut rx).await;
+
+        let Some(StreamEvent::Done {
+            full_content,
+            timing,
+            ..
+        }) = events.last()
+        else {
+            panic!("expected Done event, got {:?}", events.last());
+        };
+
+        // 推理内容不计入回答
+        assert_eq!(full_content, "The answer is 42");
+        assert!(timing.reasoning_ms.is_some_and(|ms| ms >= 30));
+        assert!(timing.answer_ms.is_some_and(|ms| ms >= 30));
+    }
+    #[tokio::test]
+    async fn test_length_finish_reason_is_recorded() {
+        use crate::modules::chat::test_support::{collect_events, ChatTestHarness, ScriptedLLM};
+
+        let llm = ScriptedLLM::replying("Once upon a").finishing_with(FinishReason::Length);
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let response = harness.send(session_id, "Tell a story").await.unwrap();
+        assert_eq!(
+            response.assistant_message.finish_reason(),
+            Some(FinishReason::Length)
+        );
+
+        let (_, mut rx) = harness.send_stream(session_id, "Again").await.unwrap();
+        collect_events(&mut rx).await;
+
+        // 用户消息不记录结束原因，两种方式保存的助手回复都标记为截断
+        let messages = harness.messages(session_id).await;
+        let reasons: Vec<_> = messages.iter().map(|m| m.finish_reason()).collect();
+        assert_eq!(
+            reasons,
+            [
+                None,
+                Some(FinishReason::Length),
+                None,
+                Some(FinishReason::Length)
+            ]
+        );
+        assert!(messages[3].is_truncated());
+    }
+
+    #[tokio::test]
+    async fn test_stream_assistant_prefix() {
+        use crate::modules::chat::test_support::{
+            collect_events, ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+
+        let llm = ScriptedLLM::replying(" Hello!").with_assistant_prefix_support();
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let command =
+            SendMessageCommand::new(session_id, "Hi", None, true).with_assistant_prefix("Kizuna: ");
+        let (_, mut rx) = harness
+            .module
+            .send_message_stream(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+        let events = collect_events(&mut rx).await;
+
+        // 前缀去掉末尾空白后作为首个内容块
+        assert!(matches!(&events[0], StreamEvent::Chunk(c) if c == "Kizuna:"));
+        assert_eq!(
+            harness.llm.requests()[0].assistant_prefix.as_deref(),
+            Some("Kizuna:")
+        );
+        assert_eq!(
+            harness.messages(session_id).await[1].content(),
+            "Kizuna: Hello!"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_role_play_trims_leaked_user_turn() {
+        use crate::modules::chat::test_support::{
+            collect_events, ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+
+        // 提供商未遵守停止序列，继续替用户发言
+        let llm = ScriptedLLM::new()
+            .chunk("Welcome back!\n")
+            .chunk("User: I missed you.");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let command = SendMessageCommand::new(session_id, "Hi", None, true).with_user_name("Alex");
+        let (_, mut rx) = harness
+            .module
+            .send_message_stream(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+        let events = collect_events(&mut rx).await;
+
+        assert_eq!(
+            harness.llm.requests()[0].stop_sequences.as_deref(),
+            Some(&["\nUser:".to_string(), "\nAlex:".to_string()][..])
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            StreamEvent::ContentFiltered { content, flagged_terms }
+                if content == "Welcome back!" && flagged_terms.is_empty()
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { full_content, .. }) if full_content == "Welcome back!"
+        ));
+        assert_eq!(
+            harness.messages(session_id).await[1].content(),
+            "Welcome back!"
+        );
+
+        // 非流式同样截断；显式指定的停止序列优先
+        let command = SendMessageCommand::new(session_id, "Again", None, false)
+            .with_user_name("Alex")
+            .with_stop_sequences(vec!["###".to_string()]);
+        let response = harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+        assert_eq!(response.assistant_message.content(), "Welcome back!");
+        assert_eq!(
+            harness.llm.requests()[1].stop_sequences.as_deref(),
+            Some(&["###".to_string()][..])
+        );
+
+        // 未启用角色扮演模式时保留原文
+        let command = SendMessageCommand::new(session_id, "Once more", None, false);
+        let response = harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.assistant_message.content(),
+            "Welcome back!\nUser: I missed you."
+        );
+        assert!(harness.llm.requests()[2].stop_sequences.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sampling_params_validated_and_forwarded() {
+        use crate::modules::chat::test_support::{
+            ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+        use crate::modules::chat::CreateSessionCommand;
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Hello!")).await;
+        let session_id = harness
+            .module
+            .create_session(CreateSessionCommand::new(None, Some(uuid::Uuid::new_v4())))
+            .await
+            .unwrap()
+            .session
+            .id();
+
+        let command = SendMessageCommand::new(session_id, "Hi", None, false).with_temperature(3.0);
+        assert!(matches!(
+            harness
+                .module
+                .send_message(command, SCRIPTED_PROVIDER_ID)
+                .await,
+            Err(ApplicationError::ValidationError(_))
+        ));
+        let command = SendMessageCommand::new(session_id, "Hi", None, false).with_max_tokens(0);
+        assert!(command.validate().is_err());
+        assert!(harness.llm.requests().is_empty());
+
+        let command = SendMessageCommand::new(session_id, "Hi", None, false)
+            .with_temperature(0.3)
+            .with_max_tokens(128);
+        harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+
+        let request = &harness.llm.requests()[0];
+        assert_eq!(request.temperature, Some(0.3));
+        assert_eq!(request.max_tokens, Some(128));
+    }
+
+    #[tokio::test]
+    async fn test_context_length_trims_history() {
+        use crate::modules::chat::test_support::{
+            ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+        use crate::modules::chat::CreateSessionCommand;
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Noted.")).await;
+        let session_id = harness
+            .module
+            .create_session(CreateSessionCommand::new(None, Some(uuid::Uuid::new_v4())))
+            .await
+            .unwrap()
+            .session
+            .id();
+        for i in 0..30 {
+            let message = if i % 2 == 0 {
+                Message::new_user(session_id, format!("Question {}", i))
+            } else {
+                Message::new_assistant(session_id, format!("Answer {}", i), None)
+            };
+            harness.message_repository.save(&message).await.unwrap();
+        }
+
+        let command = SendMessageCommand::new(session_id, "Current", None, false)
+            .with_system_prompt("You are Hiyori.")
+            .with_context_length(4);
+        harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+
+        let request = &harness.llm.requests()[0];
+        let contents: Vec<_> = request
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(request.messages[0].role, "system");
+        assert_eq!(contents[0], "You are Hiyori.");
+        assert_eq!(
+            contents[1..],
+            [
+                "Question 26",
+                "Answer 27",
+                "Question 28",
+                "Answer 29",
+                "Current"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_greeting_after_clear_is_sent_as_context() {
+        use crate::modules::chat::test_support::{
+            ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+        use crate::modules::chat::ClearSessionMessagesCommand;
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Nice to meet you.")).await;
+        let session_id = harness.create_session().await;
+        harness.send(session_id, "Old topic").await.unwrap();
+
+        harness
+            .module
+            .clear_session_messages(
+                ClearSessionMessagesCommand::new(session_id).with_greeting("Welcome back!"),
+            )
+            .await
+            .unwrap();
+        // 流式生成中断时留下的空占位回复
+        let placeholder = Message::new_assistant(session_id, "", None);
+        harness.message_repository.save(&placeholder).await.unwrap();
+
+        let command = SendMessageCommand::new(session_id, "Hi", None, false);
+        harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+
+        // 开场白保留在上下文开头，空占位被移除
+        let request = harness.llm.requests().pop().unwrap();
+        let sent: Vec<_> = request
+            .messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str()))
+            .collect();
+        assert_eq!(sent, vec![("assistant", "Welcome back!"), ("user", "Hi")]);
+    }
+
+    #[tokio::test]
+    async fn test_assistant_prefix_ignored_without_support() {
+        use crate::modules::chat::test_support::{
+            ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Hello!")).await;
+        let session_id = harness.create_session().await;
+
+        let command =
+            SendMessageCommand::new(session_id, "Hi", None, false).with_assistant_prefix("Kizuna:");
+        let response = harness
+            .module
+            .send_message(command, SCRIPTED_PROVIDER_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(response.assistant_message.content(), "Hello!");
+        assert!(harness.llm.requests()[0].assistant_prefix.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_completion_events_mirror_stream() {
+        use crate::modules::chat::test_support::collect_events;
+
+        let response = CompletionResponse {
+            content: "Hello!".to_string(),
+            finish_reason: FinishReason::Stop,
+            usage: TokenUsage {
+                prompt_tokens: 3,
+                completion_tokens: 2,
+                total_tokens: 5,
+            },
+        };
+
+        let mut rx = completion_events(&response);
+        let events = collect_events(&mut rx).await;
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], StreamEvent::Chunk(content) if content == "Hello!"));
+        assert!(matches!(
+            &events[1],
+            StreamEvent::Done {
+                full_content,
+                tokens_used: Some(5),
+                ..
+            } if full_content == "Hello!"
+        ));
+    }
 }
@@ -1,9 +1,20 @@
 use async_trait::async_trait;
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
 use super::super::{ApplicationError, CommandHandler};
 use crate::modules::chat::domain::{Session, SessionId};
-use crate::modules::chat::ports::SessionRepository;
+use crate::modules::chat::ports::{
+    clamp_sampling_param, SessionRepository, PENALTY_RANGE, TEMPERATURE_RANGE, TOP_P_RANGE,
+};
+
+/// 会话模型配置中的采样参数及其合法范围
+const SAMPLING_PARAMS: [(&str, RangeInclusive<f32>); 4] = [
+    ("temperature", TEMPERATURE_RANGE),
+    ("topP", TOP_P_RANGE),
+    ("presencePenalty", PENALTY_RANGE),
+    ("frequencyPenalty", PENALTY_RANGE),
+];
 
 /// 更新会话命令
 #[derive(Debug, Clone)]
@@ -11,6 +22,10 @@ pub struct UpdateSessionCommand {
     pub session_id: SessionId,
     pub title: Option<String>,
     pub preset_id: Option<Option<uuid::Uuid>>,
+    /// 助手显示名称，`Some(None)` 表示清除
+    pub display_name: Option<Option<String>>,
+    /// 模型配置（模型、采样参数等），`Some(None)` 表示清除
+    pub model_config: Option<Option<serde_json::Value>>,
 }
 
 impl UpdateSessionCommand {
@@ -23,8 +38,56 @@ impl UpdateSessionCommand {
             session_id,
             title,
             preset_id,
+            display_name: None,
+            model_config: None,
         }
     }
+
+    /// 同时更新助手显示名称
+    pub fn with_display_name(mut self, display_name: Option<String>) -> Self {
+        self.display_name = Some(display_name);
+        self
+    }
+
+    /// 同时更新模型配置
+    pub fn with_model_config(mut self, model_config: Option<serde_json::Value>) -> Self {
+        self.model_config = Some(model_config);
+        self
+    }
+}
+
+/// 校验模型配置：必须是对象，采样参数必须是数字，超出范围时截断
+fn normalize_model_config(
+    mut config: serde_json::Value,
+) -> Result<serde_json::Value, ApplicationError> {
+    let fields = config.as_object_mut().ok_or_else(|| {
+        ApplicationError::ValidationError("Model config must be a JSON object".to_string())
+    })?;
+
+    for (name, range) in SAMPLING_PARAMS {
+        let Some(value) = fields.get_mut(name) else {
+            continue;
+        };
+        if value.is_null() {
+            continue;
+        }
+        let number = value.as_f64().ok_or_else(|| {
+            ApplicationError::ValidationError(format!("{} must be a number", name))
+        })? as f32;
+        let clamped = clamp_sampling_param(number, range.clone());
+        if clamped != number {
+            tracing::warn!(
+                "Session {} {} is outside {:?}, clamped to {}",
+                name,
+                number,
+                range,
+                clamped
+            );
+            *value = serde_json::json!(clamped);
+        }
+    }
+
+    Ok(config)
 }
 
 /// 更新会话响应
@@ -50,6 +113,11 @@ impl CommandHandler<UpdateSessionCommand, UpdateSessionResponse> for UpdateSessi
         &self,
         command: UpdateSessionCommand,
     ) -> Result<UpdateSessionResponse, ApplicationError> {
+        let model_config = command
+            .model_config
+            .map(|config| config.map(normalize_model_config).transpose())
+            .transpose()?;
+
         // 获取现有会话
         let mut session = self
             .session_repository
@@ -71,6 +139,16 @@ impl CommandHandler<UpdateSessionCommand, UpdateSessionResponse> for UpdateSessi
             session.update_preset(preset_id);
         }
 
+        if let Some(display_name) = command.display_name {
+            session.set_display_name(display_name);
+        }
+
+        match model_config {
+            Some(Some(config)) => session.set_model_config(config),
+            Some(None) => session.clear_model_config(),
+            None => {}
+        }
+
         // 保存
         self.session_repository.save(&session).await?;
 
@@ -99,4 +177,79 @@ mod tests {
         let response = handler.handle(command).await.unwrap();
         assert_eq!(response.session.title(), "New Title");
     }
+
+    #[tokio::test]
+    async fn test_update_display_name() {
+        let repo = Arc::new(InMemorySessionRepository::new());
+        let handler = UpdateSessionHandler::new(repo.clone());
+
+        let mut session = Session::new(None, None);
+        session.set_display_name(Some("Preset Name".to_string()));
+        let session_id = session.id();
+        repo.save(&session).await.unwrap();
+
+        // 只改标题时保留显示名称
+        let command = UpdateSessionCommand::new(session_id, Some("Renamed".to_string()), None);
+        let response = handler.handle(command).await.unwrap();
+        assert_eq!(response.session.display_name(), Some("Preset Name"));
+
+        let command = UpdateSessionCommand::new(session_id, None, None)
+            .with_display_name(Some("Kizuna".to_string()));
+        handler.handle(command).await.unwrap();
+        let saved = repo.get(session_id).await.unwrap().unwrap();
+        assert_eq!(saved.display_name(), Some("Kizuna"));
+        assert_eq!(saved.title(), "Renamed");
+
+        let command = UpdateSessionCommand::new(session_id, None, None).with_display_name(None);
+        let response = handler.handle(command).await.unwrap();
+        assert_eq!(response.session.display_name(), None);
+    }
+
+    #[tokio::test]
+    async fn test_update_model_config_clamps_sampling_params() {
+        let repo = Arc::new(InMemorySessionRepository::new());
+        let handler = UpdateSessionHandler::new(repo.clone());
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        repo.save(&session).await.unwrap();
+
+        let command = UpdateSessionCommand::new(session_id, None, None).with_model_config(Some(
+            serde_json::json!({
+                "model": "gpt-4o",
+                "temperature": 5.0,
+                "topP": -1.0,
+                "presencePenalty": 0.5,
+                "frequencyPenalty": null,
+            }),
+        ));
+        let response = handler.handle(command).await.unwrap();
+        assert_eq!(
+            response.session.model_config(),
+            Some(&serde_json::json!({
+                "model": "gpt-4o",
+                "temperature": 2.0,
+                "topP": 0.0,
+                "presencePenalty": 0.5,
+                "frequencyPenalty": null,
+            }))
+        );
+
+        // 格式错误时拒绝，已保存的配置不变
+        for invalid in [
+            serde_json::json!("gpt-4o"),
+            serde_json::json!({ "temperature": "hot" }),
+        ] {
+            let command =
+                UpdateSessionCommand::new(session_id, None, None).with_model_config(Some(invalid));
+            let result = handler.handle(command).await;
+            assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+        }
+        let saved = repo.get(session_id).await.unwrap().unwrap();
+        assert_eq!(saved.model_config(), response.session.model_config());
+
+        let command = UpdateSessionCommand::new(session_id, None, None).with_model_config(None);
+        let response = handler.handle(command).await.unwrap();
+        assert_eq!(response.session.model_config(), None);
+    }
 }
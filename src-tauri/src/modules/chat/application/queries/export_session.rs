@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::super::{ApplicationError, QueryHandler};
+use crate::modules::chat::domain::{Message, Session, SessionId};
+use crate::modules::chat::ports::{MessageRepository, Pagination, SessionRepository};
+
+/// 会话导出格式的版本号
+pub const SESSION_EXPORT_VERSION: u32 = 1;
+
+/// 会话导出格式：会话及其全部消息（按会话内顺序）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionExport {
+    pub version: u32,
+    pub session: Session,
+    pub messages: Vec<Message>,
+}
+
+/// 导出会话查询
+#[derive(Debug, Clone)]
+pub struct ExportSessionQuery {
+    pub session_id: SessionId,
+}
+
+impl ExportSessionQuery {
+    pub fn new(session_id: SessionId) -> Self {
+        Self { session_id }
+    }
+}
+
+/// 导出会话查询处理器
+pub struct ExportSessionHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl ExportSessionHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ExportSessionQuery, SessionExport> for ExportSessionHandler {
+    async fn handle(&self, query: ExportSessionQuery) -> Result<SessionExport, ApplicationError> {
+        let session = self
+            .session_repository
+            .get(query.session_id)
+            .await?
+            .ok_or_else(|| ApplicationError::SessionNotFound(query.session_id.to_string()))?;
+
+        let total = self
+            .message_repository
+            .count_by_session(query.session_id)
+            .await?;
+        let messages = self
+            .message_repository
+            .find_by_session(query.session_id, Pagination::new(1, total.max(1) as u32))
+            .await?
+            .items;
+
+        Ok(SessionExport {
+            version: SESSION_EXPORT_VERSION,
+            session,
+            messages,
+        })
+    }
+}
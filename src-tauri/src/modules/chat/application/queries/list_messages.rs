@@ -5,6 +5,12 @@ use super::super::{ApplicationError, QueryHandler};
 use crate::modules::chat::domain::{Message, SessionId};
 use crate::modules::chat::ports::{MessageRepository, PaginatedResult, Pagination};
 
+/// 未指定时每页的消息数
+pub const DEFAULT_MESSAGE_PAGE_SIZE: u32 = 50;
+
+/// 每页消息数上限
+pub const MAX_MESSAGE_PAGE_SIZE: u32 = 200;
+
 /// 列出消息查询
 #[derive(Debug, Clone)]
 pub struct ListMessagesQuery {
@@ -26,7 +32,19 @@ impl ListMessagesQuery {
         Self {
             session_id,
             page: 1,
-            limit: 50,
+            limit: DEFAULT_MESSAGE_PAGE_SIZE,
+        }
+    }
+
+    /// 由可选的分页参数创建：页码默认 1，每页数量默认 [`DEFAULT_MESSAGE_PAGE_SIZE`]，
+    /// 并限制在 1 到 [`MAX_MESSAGE_PAGE_SIZE`] 之间
+    pub fn paged(session_id: SessionId, page: Option<u32>, limit: Option<u32>) -> Self {
+        Self {
+            session_id,
+            page: page.unwrap_or(1).max(1),
+            limit: limit
+                .unwrap_or(DEFAULT_MESSAGE_PAGE_SIZE)
+                .clamp(1, MAX_MESSAGE_PAGE_SIZE),
         }
     }
 }
@@ -129,5 +147,71 @@ mod tests {
         assert_eq!(response.messages.len(), 10);
         assert_eq!(response.total, 15);
         assert!(response.has_more);
+
+        let query = ListMessagesQuery::new(session_id, 2, 10);
+        let response = handler.handle(query).await.unwrap();
+        assert_eq!(response.messages.len(), 5);
+        assert!(!response.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_list_messages_unknown_session_is_empty() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ListMessagesHandler::new(repo);
+
+        let query = ListMessagesQuery::new(SessionId::new(), 1, 10);
+        let response = handler.handle(query).await.unwrap();
+
+        assert!(response.messages.is_empty());
+        assert_eq!(response.total, 0);
+        assert!(!response.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_list_messages_out_of_range_page() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = ListMessagesHandler::new(repo.clone());
+
+        let session_id = SessionId::new();
+        for i in 0..5 {
+            let msg = Message::new_user(session_id, format!("Message {}", i));
+            repo.save(&msg).await.unwrap();
+        }
+
+        let query = ListMessagesQuery::new(session_id, 3, 5);
+        let response = handler.handle(query).await.unwrap();
+        assert!(response.messages.is_empty());
+        assert_eq!(response.total, 5);
+        assert!(!response.has_more);
+
+        // 页码 0 视为第 1 页
+        let query = ListMessagesQuery::new(session_id, 0, 2);
+        let response = handler.handle(query).await.unwrap();
+        assert_eq!(response.messages.len(), 2);
+        assert_eq!(response.messages[0].content(), "Message 0");
+        assert!(response.has_more);
+
+        // 极大的页码不会溢出
+        let query = ListMessagesQuery::new(session_id, u32::MAX, u32::MAX);
+        let response = handler.handle(query).await.unwrap();
+        assert!(response.messages.is_empty());
+        assert_eq!(response.total, 5);
+    }
+
+    #[test]
+    fn test_paged_defaults_and_clamping() {
+        let session_id = SessionId::new();
+
+        let query = ListMessagesQuery::paged(session_id, None, None);
+        assert_eq!(query.page, 1);
+        assert_eq!(query.limit, DEFAULT_MESSAGE_PAGE_SIZE);
+
+        let query = ListMessagesQuery::paged(session_id, Some(0), Some(0));
+        assert_eq!(query.page, 1);
+        assert_eq!(query.limit, 1);
+
+        let query = ListMessagesQuery::paged(session_id, Some(3), Some(10_000));
+        assert_eq!(query.page, 3);
+        assert_eq!(query.limit, MAX_MESSAGE_PAGE_SIZE);
     }
 }
@@ -1,9 +1,15 @@
 // Chat Queries - 查询定义和处理器
 
+mod export_session;
 mod get_session;
 mod list_messages;
 mod list_sessions;
+mod search_messages;
+mod suggest_replies;
 
+pub use export_session::*;
 pub use get_session::*;
 pub use list_messages::*;
 pub use list_sessions::*;
+pub use search_messages::*;
+pub use suggest_replies::*;
@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use super::super::{ApplicationError, QueryHandler};
+use crate::modules::chat::domain::{
+    find_case_insensitive, MessageId, MessageRole, SearchSnippet, SessionId,
+};
+use crate::modules::chat::ports::MessageRepository;
+
+/// 搜索消息查询
+///
+/// 不区分大小写的子串匹配；`session_id` 为 None 时搜索所有会话
+#[derive(Debug, Clone)]
+pub struct SearchMessagesQuery {
+    pub query: String,
+    pub session_id: Option<SessionId>,
+    pub limit: u32,
+}
+
+impl SearchMessagesQuery {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            session_id: None,
+            limit: 50,
+        }
+    }
+
+    pub fn in_session(mut self, session_id: SessionId) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// 单条搜索结果
+#[derive(Debug, Clone)]
+pub struct MessageMatch {
+    pub session_id: SessionId,
+    pub message_id: MessageId,
+    pub role: MessageRole,
+    pub created_at: DateTime<Utc>,
+    pub snippet: SearchSnippet,
+}
+
+/// 搜索消息响应，最新的消息在前
+#[derive(Debug, Clone)]
+pub struct SearchMessagesResponse {
+    pub matches: Vec<MessageMatch>,
+}
+
+/// 搜索消息查询处理器
+pub struct SearchMessagesHandler {
+    message_repository: Arc<dyn MessageRepository>,
+}
+
+impl SearchMessagesHandler {
+    pub fn new(message_repository: Arc<dyn MessageRepository>) -> Self {
+        Self { message_repository }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<SearchMessagesQuery, SearchMessagesResponse> for SearchMessagesHandler {
+    async fn handle(
+        &self,
+        query: SearchMessagesQuery,
+    ) -> Result<SearchMessagesResponse, ApplicationError> {
+        if query.query.trim().is_empty() {
+            return Err(ApplicationError::ValidationError(
+                "Search query cannot be empty".to_string(),
+            ));
+        }
+
+        let messages = self
+            .message_repository
+            .search(&query.query, query.session_id, query.limit as usize)
+            .await?;
+
+        let matches = messages
+            .iter()
+            .filter_map(|m| {
+                let range = find_case_insensitive(m.content(), &query.query)?;
+                Some(MessageMatch {
+                    session_id: m.session_id(),
+                    message_id: m.id(),
+                    role: m.role(),
+                    created_at: m.created_at(),
+                    snippet: SearchSnippet::around(m.content(), range),
+                })
+            })
+            .collect();
+
+        Ok(SearchMessagesResponse { matches })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::Message;
+    use crate::modules::chat::infrastructure::{FileMessageRepository, InMemoryMessageRepository};
+    use tempfile::TempDir;
+
+    async fn seed(repo: &dyn MessageRepository) -> (SessionId, SessionId) {
+        let first = SessionId::new();
+        let second = SessionId::new();
+        for message in [
+            Message::new_user(first, "I love Rust"),
+            Message::new_assistant(first, "RUST is great", None),
+            Message::new_user(second, "Tell me about rust"),
+            Message::new_user(second, "Something else"),
+        ] {
+            repo.save(&message).await.unwrap();
+        }
+        (first, second)
+    }
+
+    #[tokio::test]
+    async fn test_search_is_case_insensitive() {
+        let repo = Arc::new(InMemoryMessageRepository::new());
+        let handler = SearchMessagesHandler::new(repo.clone());
+        seed(repo.as_ref()).await;
+
+        let response = handler
+            .handle(SearchMessagesQuery::new("rUsT"))
+            .await
+            .unwrap();
+        assert_eq!(response.matches.len(), 3);
+        for found in &response.matches {
+            let chars: Vec<char> = found.snippet.text.chars().collect();
+            let highlighted: String = chars
+                [found.snippet.highlight_start..found.snippet.highlight_end]
+                .iter()
+                .collect();
+            assert_eq!(highlighted.to_lowercase(), "rust");
+        }
+
+        let response = handler
+            .handle(SearchMessagesQuery::new("rust").with_limit(1))
+            .await
+            .unwrap();
+        assert_eq!(response.matches.len(), 1);
+
+        let result = handler.handle(SearchMessagesQuery::new("  ")).await;
+        assert!(matches!(result, Err(ApplicationError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_session_scoped_vs_global() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Arc::new(
+            FileMessageRepository::new(temp_dir.path().to_path_buf())
+                .await
+                .unwrap(),
+        );
+        let handler = SearchMessagesHandler::new(repo.clone());
+        let (first, second) = seed(repo.as_ref()).await;
+
+        let global = handler
+            .handle(SearchMessagesQuery::new("rust"))
+            .await
+            .unwrap();
+        assert_eq!(global.matches.len(), 3);
+        assert!(global.matches.iter().any(|m| m.session_id == first));
+        assert!(global.matches.iter().any(|m| m.session_id == second));
+
+        let scoped = handler
+            .handle(SearchMessagesQuery::new("rust").in_session(second))
+            .await
+            .unwrap();
+        assert_eq!(scoped.matches.len(), 1);
+        assert_eq!(scoped.matches[0].session_id, second);
+        assert_eq!(scoped.matches[0].role, MessageRole::User);
+        assert_eq!(scoped.matches[0].snippet.text, "Tell me about rust");
+    }
+}
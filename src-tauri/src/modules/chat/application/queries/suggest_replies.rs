@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use super::super::commands::context_history;
+use super::super::{ApplicationError, QueryHandler};
+use crate::modules::chat::domain::{ContextBuilder, SessionId};
+use crate::modules::chat::ports::{
+    CompletionRequest, LLMChatMessage, LLMPort, MessageRepository, Pagination, SessionRepository,
+};
+
+/// 单次最多建议的回复数
+pub const MAX_SUGGESTED_REPLIES: usize = 5;
+
+/// 生成建议时携带的最近历史消息数
+const SUGGEST_CONTEXT_MESSAGES: usize = 50;
+
+/// 生成建议回复的系统提示
+const SUGGEST_REPLIES_PROMPT: &str = "You suggest what the user might say next in this \
+conversation. Reply only with a JSON array of short messages written from the user's \
+perspective, in the same language as the conversation, with no explanation.";
+
+/// 建议回复查询
+#[derive(Debug, Clone)]
+pub struct SuggestRepliesQuery {
+    pub session_id: SessionId,
+    /// 期望的建议数，超过 [`MAX_SUGGESTED_REPLIES`] 时截断
+    pub count: usize,
+}
+
+impl SuggestRepliesQuery {
+    pub fn new(session_id: SessionId, count: usize) -> Self {
+        Self {
+            session_id,
+            count: count.min(MAX_SUGGESTED_REPLIES),
+        }
+    }
+}
+
+/// 建议回复查询响应
+#[derive(Debug, Clone)]
+pub struct SuggestRepliesResponse {
+    pub suggestions: Vec<String>,
+}
+
+/// 建议回复查询处理器
+///
+/// 使用非流式补全，不保存任何消息
+pub struct SuggestRepliesHandler {
+    session_repository: Arc<dyn SessionRepository>,
+    message_repository: Arc<dyn MessageRepository>,
+    llm_port: Arc<dyn LLMPort>,
+    default_model: String,
+}
+
+impl SuggestRepliesHandler {
+    pub fn new(
+        session_repository: Arc<dyn SessionRepository>,
+        message_repository: Arc<dyn MessageRepository>,
+        llm_port: Arc<dyn LLMPort>,
+        default_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            session_repository,
+            message_repository,
+            llm_port,
+            default_model: default_model.into(),
+        }
+    }
+}
+
+/// 解析模型返回的建议
+///
+/// 优先按 JSON 字符串数组解析，否则按行解析并去掉列表符号和引号；
+/// 结果去除首尾空白、忽略大小写去重，最多保留 `count` 条
+pub fn parse_suggestions(content: &str, count: usize) -> Vec<String> {
+    let from_json = content
+        .find('[')
+        .zip(content.rfind(']'))
+        .filter(|(start, end)| start < end)
+        .and_then(|(start, end)| serde_json::from_str::<Vec<String>>(&content[start..=end]).ok());
+
+    let candidates = from_json.unwrap_or_else(|| {
+        content
+            .lines()
+            .map(|line| {
+                line.trim()
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .trim_start_matches(['-', '*', '•', '.', ')'])
+                    .trim()
+                    .trim_matches(['"', '“', '”'])
+                    .to_string()
+            })
+            .collect()
+    });
+
+    let mut suggestions: Vec<String> = Vec::with_capacity(count);
+    for candidate in candidates {
+        if suggestions.len() >= count {
+            break;
+        }
+        let candidate = candidate.trim();
+        if candidate.is_empty()
+            || suggestions
+                .iter()
+                .any(|s| s.to_lowercase() == candidate.to_lowercase())
+        {
+            continue;
+        }
+        suggestions.push(candidate.to_string());
+    }
+    suggestions
+}
+
+#[async_trait]
+impl QueryHandler<SuggestRepliesQuery, SuggestRepliesResponse> for SuggestRepliesHandler {
+    async fn handle(
+        &self,
+        query: SuggestRepliesQuery,
+    ) -> Result<SuggestRepliesResponse, ApplicationError> {
+        self.session_repository
+            .get(query.session_id)
+            .await?
+            .ok_or_else(|| ApplicationError::SessionNotFound(query.session_id.to_string()))?;
+
+        let count = query.count.min(MAX_SUGGESTED_REPLIES);
+        // 与发送消息一致：先取全部历史，过滤断点后再保留最近的部分
+        let total = self
+            .message_repository
+            .count_by_session(query.session_id)
+            .await?;
+        let messages = self
+            .message_repository
+            .find_by_session(query.session_id, Pagination::new(1, total.max(1) as u32))
+            .await?;
+        let history =
+            ContextBuilder::most_recent(context_history(&messages.items), SUGGEST_CONTEXT_MESSAGES);
+        if count == 0 || history.is_empty() {
+            return Ok(SuggestRepliesResponse {
+                suggestions: Vec::new(),
+            });
+        }
+
+        let mut context = Vec::with_capacity(history.len() + 2);
+        context.push(LLMChatMessage {
+            role: "system".to_string(),
+            content: SUGGEST_REPLIES_PROMPT.to_string(),
+            images: Vec::new(),
+        });
+        for msg in history {
+            context.push(LLMChatMessage {
+                role: msg.role().to_openai_role().to_string(),
+                content: msg.content().to_string(),
+                images: Vec::new(),
+            });
+        }
+        context.push(LLMChatMessage {
+            role: "user".to_string(),
+            content: format!(
+                "Suggest {} short replies I could send next, as a JSON array of strings.",
+                count
+            ),
+            images: Vec::new(),
+        });
+
+        let request = CompletionRequest::new(context, self.default_model.clone());
+        let response = self.llm_port.complete(request).await?;
+
+        Ok(SuggestRepliesResponse {
+            suggestions: parse_suggestions(&response.content, count),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::{Message, Session};
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use crate::modules::chat::test_support::ScriptedLLM;
+
+    #[test]
+    fn test_parse_suggestions_from_lines() {
+        let content = "1. \"Tell me more\"\n- What about tomorrow?\n* tell me more\n\n";
+        assert_eq!(
+            parse_suggestions(content, 5),
+            vec!["Tell me more", "What about tomorrow?"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggest_replies_parses_and_caps() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(ScriptedLLM::replying(
+            r#"Here you go: ["Tell me more", "  tell me more ", "Thanks!", "", "What next?", "A", "B", "C"]"#,
+        ));
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+        message_repo
+            .save(&Message::new_user(session_id, "Hi"))
+            .await
+            .unwrap();
+        message_repo
+            .save(&Message::new_assistant(session_id, "Hello!", None))
+            .await
+            .unwrap();
+
+        let handler =
+            SuggestRepliesHandler::new(session_repo, message_repo.clone(), llm.clone(), "model");
+        let response = handler
+            .handle(SuggestRepliesQuery::new(session_id, 10))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.suggestions,
+            vec!["Tell me more", "Thanks!", "What next?", "A", "B"]
+        );
+        let request = llm.requests().pop().unwrap();
+        let instruction = &request.messages.last().unwrap().content;
+        assert!(instruction.contains("Suggest 5"));
+        // 不保存任何消息
+        assert_eq!(message_repo.count_by_session(session_id).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_replies_uses_most_recent_messages() {
+        let session_repo = Arc::new(InMemorySessionRepository::new());
+        let message_repo = Arc::new(InMemoryMessageRepository::new());
+        let llm = Arc::new(ScriptedLLM::replying(r#"["OK"]"#));
+
+        let session = Session::new(None, None);
+        let session_id = session.id();
+        session_repo.save(&session).await.unwrap();
+        for i in 0..SUGGEST_CONTEXT_MESSAGES + 10 {
+            let message = if i % 2 == 0 {
+                Message::new_user(session_id, format!("message {}", i))
+            } else {
+                Message::new_assistant(session_id, format!("message {}", i), None)
+            };
+            message_repo.save(&message).await.unwrap();
+        }
+
+        let handler = SuggestRepliesHandler::new(session_repo, message_repo, llm.clone(), "model");
+        handler
+            .handle(SuggestRepliesQuery::new(session_id, 1))
+            .await
+            .unwrap();
+
+        let request = llm.requests().pop().unwrap();
+        let contents: Vec<&str> = request
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        let last = format!("message {}", SUGGEST_CONTEXT_MESSAGES + 9);
+        assert!(contents.contains(&last.as_str()));
+        assert!(!contents.contains(&"message 0"));
+        // 系统提示 + 历史 + 指令
+        assert_eq!(contents.len(), SUGGEST_CONTEXT_MESSAGES + 2);
+    }
+}
@@ -1,7 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::super::value_objects::{Emotion, MessageId, SessionId};
+use super::super::services::sanitize_user_content;
+use super::super::value_objects::{Emotion, FinishReason, MessageId, SessionId};
 
 /// 消息角色
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -13,6 +14,9 @@ pub enum MessageRole {
     Assistant,
     /// 系统消息
     System,
+    /// 上下文断点（仅在历史中可见，不参与 LLM 上下文）
+    #[serde(rename = "context_break")]
+    ContextBreak,
 }
 
 impl MessageRole {
@@ -21,7 +25,7 @@ impl MessageRole {
         match self {
             MessageRole::User => "user",
             MessageRole::Assistant => "assistant",
-            MessageRole::System => "system",
+            MessageRole::System | MessageRole::ContextBreak => "system",
         }
     }
 }
@@ -45,20 +49,29 @@ pub struct Message {
     /// 情感（仅 Assistant 消息）
     emotion: Option<Emotion>,
     /// 创建时间
+    #[serde(with = "crate::shared::timestamp")]
     created_at: DateTime<Utc>,
+    /// 会话内单调递增的序号，保存时由仓储分配；0 表示尚未分配（旧数据）
+    #[serde(default)]
+    sequence: u64,
+    /// 结束原因（仅 Assistant 消息，旧数据为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finish_reason: Option<FinishReason>,
 }
 
 impl Message {
-    /// 创建用户消息
+    /// 创建用户消息，内容中的控制字符会被清理
     pub fn new_user(session_id: SessionId, content: impl Into<String>) -> Self {
         Self {
             id: MessageId::new(),
             session_id,
             role: MessageRole::User,
-            content: content.into(),
+            content: sanitize_user_content(&content.into()),
             tokens: None,
             emotion: None,
             created_at: Utc::now(),
+            sequence: 0,
+            finish_reason: None,
         }
     }
 
@@ -76,6 +89,8 @@ impl Message {
             tokens: None,
             emotion,
             created_at: Utc::now(),
+            sequence: 0,
+            finish_reason: None,
         }
     }
 
@@ -89,6 +104,33 @@ impl Message {
             tokens: None,
             emotion: None,
             created_at: Utc::now(),
+            sequence: 0,
+            finish_reason: None,
+        }
+    }
+
+    /// 创建上下文断点标记
+    pub fn new_context_break(session_id: SessionId) -> Self {
+        Self {
+            id: MessageId::new(),
+            session_id,
+            role: MessageRole::ContextBreak,
+            content: String::new(),
+            tokens: None,
+            emotion: None,
+            created_at: Utc::now(),
+            sequence: 0,
+            finish_reason: None,
+        }
+    }
+
+    /// 复制到另一个会话（用于分支），使用新的 ID，序号由仓储重新分配
+    pub fn copy_to_session(&self, session_id: SessionId) -> Self {
+        Self {
+            id: MessageId::new(),
+            session_id,
+            sequence: 0,
+            ..self.clone()
         }
     }
 
@@ -121,11 +163,42 @@ impl Message {
         self.created_at
     }
 
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason
+    }
+
+    /// 回复是否因长度限制被截断
+    pub fn is_truncated(&self) -> bool {
+        self.finish_reason == Some(FinishReason::Length)
+    }
+
+    /// 会话内的排序键：以序号为准，序号相同（如均未分配）时按创建时间
+    pub fn order_key(&self) -> (u64, DateTime<Utc>) {
+        (self.sequence, self.created_at)
+    }
+
+    /// 是否为上下文断点
+    pub fn is_context_break(&self) -> bool {
+        self.role == MessageRole::ContextBreak
+    }
+
     // Setters (内部使用)
     pub fn set_id(&mut self, id: MessageId) {
         self.id = id;
     }
 
+    pub fn set_sequence(&mut self, sequence: u64) {
+        self.sequence = sequence;
+    }
+
+    pub fn set_created_at(&mut self, created_at: DateTime<Utc>) {
+        self.created_at = created_at;
+    }
+
     pub fn set_tokens(&mut self, tokens: u32) {
         self.tokens = Some(tokens);
     }
@@ -134,6 +207,23 @@ impl Message {
         self.emotion = Some(emotion);
     }
 
+    pub fn set_finish_reason(&mut self, finish_reason: Option<FinishReason>) {
+        self.finish_reason = finish_reason;
+    }
+
+    /// 编辑消息内容，原有的 Token 计数随之失效
+    ///
+    /// 用户消息与创建时一样清理控制字符
+    pub fn edit_content(&mut self, content: impl Into<String>) {
+        let content = content.into();
+        self.content = if self.role == MessageRole::User {
+            sanitize_user_content(&content)
+        } else {
+            content
+        };
+        self.tokens = None;
+    }
+
     /// 追加内容（用于流式响应）
     pub fn append_content(&mut self, chunk: &str) {
         self.content.push_str(chunk);
@@ -162,6 +252,16 @@ mod tests {
         assert!(msg.emotion().is_none());
     }
 
+    #[test]
+    fn test_user_content_is_sanitized() {
+        let session_id = SessionId::new();
+        let mut msg = Message::new_user(session_id, "Hi\0 there\u{8}\n你好");
+        assert_eq!(msg.content(), "Hi there\n你好");
+
+        msg.edit_content("again\u{0}");
+        assert_eq!(msg.content(), "again");
+    }
+
     #[test]
     fn test_create_assistant_message_with_emotion() {
         let session_id = SessionId::new();
@@ -179,4 +279,18 @@ mod tests {
 
         assert_eq!(msg.content(), "Hello World!");
     }
+
+    #[test]
+    fn test_legacy_message_has_no_finish_reason() {
+        let session_id = SessionId::new();
+        let mut msg = Message::new_assistant(session_id, "Cut off mid", None);
+        let json = serde_json::to_value(&msg).unwrap();
+        assert!(json.get("finishReason").is_none());
+
+        msg.set_finish_reason(Some(FinishReason::Length));
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains(r#""finishReason":"length""#));
+        let restored: Message = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_truncated());
+    }
 }
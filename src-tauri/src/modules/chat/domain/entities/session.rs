@@ -5,6 +5,9 @@ use uuid::Uuid;
 use super::super::value_objects::SessionId;
 use super::Message;
 
+/// 新会话的默认标题
+pub const DEFAULT_SESSION_TITLE: &str = "新对话";
+
 /// 会话实体 - 聚合根
 ///
 /// Session 是 Chat 模块的聚合根，管理消息集合
@@ -17,11 +20,16 @@ pub struct Session {
     title: String,
     /// 关联的预设 ID（可选）
     preset_id: Option<Uuid>,
+    /// 界面上显示的助手名称，仅用于展示，不参与提示词构建
+    #[serde(default)]
+    display_name: Option<String>,
     /// 模型配置（JSON 格式）
     model_config: Option<serde_json::Value>,
     /// 创建时间
+    #[serde(with = "crate::shared::timestamp")]
     created_at: DateTime<Utc>,
     /// 更新时间
+    #[serde(with = "crate::shared::timestamp")]
     updated_at: DateTime<Utc>,
 }
 
@@ -31,8 +39,9 @@ impl Session {
         let now = Utc::now();
         Self {
             id: SessionId::new(),
-            title: title.unwrap_or_else(|| "新对话".to_string()),
+            title: title.unwrap_or_else(|| DEFAULT_SESSION_TITLE.to_string()),
             preset_id,
+            display_name: None,
             model_config: None,
             created_at: now,
             updated_at: now,
@@ -46,12 +55,24 @@ impl Session {
             id,
             title,
             preset_id,
+            display_name: None,
             model_config: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// 创建分支会话：新的 ID 和时间戳，沿用标题、预设和模型配置
+    pub fn fork(&self) -> Self {
+        let now = Utc::now();
+        Self {
+            id: SessionId::new(),
+            created_at: now,
+            updated_at: now,
+            ..self.clone()
+        }
+    }
+
     // Getters
     pub fn id(&self) -> SessionId {
         self.id
@@ -65,6 +86,10 @@ impl Session {
         self.preset_id
     }
 
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
     pub fn model_config(&self) -> Option<&serde_json::Value> {
         self.model_config.as_ref()
     }
@@ -91,6 +116,14 @@ impl Session {
         self.touch();
     }
 
+    /// 设置助手显示名称，空白名称视为清除
+    pub fn set_display_name(&mut self, display_name: Option<String>) {
+        self.display_name = display_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        self.touch();
+    }
+
     /// 重命名会话
     pub fn rename(&mut self, new_title: impl Into<String>) {
         self.title = new_title.into();
@@ -103,6 +136,17 @@ impl Session {
         self.touch();
     }
 
+    /// 清除模型配置，回到预设和全局设置
+    pub fn clear_model_config(&mut self) {
+        self.model_config = None;
+        self.touch();
+    }
+
+    /// 会话的消息已被清空
+    pub fn mark_messages_cleared(&mut self) {
+        self.touch();
+    }
+
     /// 更新修改时间
     fn touch(&mut self) {
         self.updated_at = Utc::now();
@@ -150,6 +194,18 @@ mod tests {
         assert!(session.updated_at() > old_updated_at);
     }
 
+    #[test]
+    fn test_set_display_name() {
+        let mut session = Session::default();
+        assert_eq!(session.display_name(), None);
+
+        session.set_display_name(Some("  Kizuna ".to_string()));
+        assert_eq!(session.display_name(), Some("Kizuna"));
+
+        session.set_display_name(Some("   ".to_string()));
+        assert_eq!(session.display_name(), None);
+    }
+
     #[test]
     fn test_default_session_title() {
         let session = Session::default();
@@ -7,7 +7,15 @@ pub mod services;
 pub mod value_objects;
 
 // 重导出常用类型
-pub use entities::{Message, MessageRole, Session};
+pub use entities::{Message, MessageRole, Session, DEFAULT_SESSION_TITLE};
 pub use events::*;
-pub use services::{ChatMessage, ContextBuilder, EmotionAnalyzer};
-pub use value_objects::{Emotion, MessageId, SessionId};
+pub use services::{
+    apply_content_filter, detect_repetition, find_case_insensitive, resolve_settings,
+    role_play_stop_sequences, should_generate_title, trim_user_turn_leak, AutoTitlePolicy, ChatMessage, ContentFilter, ContextBuilder,
+    EffectiveSettings, EmojiEmotionTable, EmotionAnalyzer, FilterOutcome, HeuristicTokenEstimator,
+    reconcile, sanitize_user_content, OrphanMessages, RepetitionMatch, RepetitionPolicy, ResolvedSetting, SearchSnippet,
+    SessionOverrides, SettingSource, SettingsLayers, StoreReconciliation, StreamingContentFilter,
+    TokenEstimator,
+    WordListFilter, RECOVERED_SESSION_TITLE,
+};
+pub use value_objects::{Emotion, FinishReason, MessageId, SessionId};
@@ -0,0 +1,82 @@
+use super::super::entities::DEFAULT_SESSION_TITLE;
+
+/// 自动生成会话标题的触发策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoTitlePolicy {
+    /// 不自动生成
+    Off,
+    /// 收到第一条助手回复后生成
+    #[default]
+    AfterFirstReply,
+    /// 会话中的对话消息达到指定数量后生成
+    AfterNMessages(usize),
+}
+
+/// 判断是否应为会话自动生成标题
+///
+/// `message_count` 为会话中用户和助手消息的数量（不含上下文断点和系统消息）；
+/// 只为尚未命名（标题为空或仍是默认标题）的会话生成，用户改过的标题不会被覆盖
+pub fn should_generate_title(policy: AutoTitlePolicy, message_count: usize, title: &str) -> bool {
+    let untitled = title.trim().is_empty() || title == DEFAULT_SESSION_TITLE;
+    if !untitled {
+        return false;
+    }
+
+    match policy {
+        AutoTitlePolicy::Off => false,
+        // 一问一答
+        AutoTitlePolicy::AfterFirstReply => message_count >= 2,
+        AutoTitlePolicy::AfterNMessages(n) => message_count >= n.max(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_off_never_generates() {
+        for count in [0, 2, 100] {
+            assert!(!should_generate_title(
+                AutoTitlePolicy::Off,
+                count,
+                DEFAULT_SESSION_TITLE
+            ));
+        }
+    }
+
+    #[test]
+    fn test_after_first_reply() {
+        let policy = AutoTitlePolicy::AfterFirstReply;
+        assert!(!should_generate_title(policy, 0, DEFAULT_SESSION_TITLE));
+        assert!(!should_generate_title(policy, 1, DEFAULT_SESSION_TITLE));
+        assert!(should_generate_title(policy, 2, DEFAULT_SESSION_TITLE));
+        assert!(should_generate_title(policy, 5, "  "));
+    }
+
+    #[test]
+    fn test_after_n_messages() {
+        let policy = AutoTitlePolicy::AfterNMessages(6);
+        assert!(!should_generate_title(policy, 2, DEFAULT_SESSION_TITLE));
+        assert!(!should_generate_title(policy, 5, DEFAULT_SESSION_TITLE));
+        assert!(should_generate_title(policy, 6, DEFAULT_SESSION_TITLE));
+        assert!(should_generate_title(policy, 9, ""));
+
+        // 0 视为 1，至少要有一条消息
+        assert!(!should_generate_title(
+            AutoTitlePolicy::AfterNMessages(0),
+            0,
+            DEFAULT_SESSION_TITLE
+        ));
+    }
+
+    #[test]
+    fn test_named_sessions_are_never_retitled() {
+        for policy in [
+            AutoTitlePolicy::AfterFirstReply,
+            AutoTitlePolicy::AfterNMessages(1),
+        ] {
+            assert!(!should_generate_title(policy, 10, "Trip planning"));
+        }
+    }
+}
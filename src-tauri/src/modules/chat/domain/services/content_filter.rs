@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+/// 内容过滤结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterOutcome {
+    /// 过滤后的内容
+    pub content: String,
+    /// 命中的词语（按配置中的写法，去重）
+    pub flagged_terms: Vec<String>,
+}
+
+impl FilterOutcome {
+    /// 未命中任何词语的结果
+    pub fn unchanged(content: &str) -> Self {
+        Self {
+            content: content.to_string(),
+            flagged_terms: Vec::new(),
+        }
+    }
+
+    /// 是否有内容被过滤
+    pub fn is_filtered(&self) -> bool {
+        !self.flagged_terms.is_empty()
+    }
+}
+
+/// 内容过滤器
+///
+/// 在助手回复保存和发布之前进行后处理，实现必须是纯本地的
+pub trait ContentFilter: Send + Sync {
+    fn filter(&self, content: &str) -> FilterOutcome;
+
+    /// 最长词语的字符数，流式过滤时末尾需保留这么多字符等待后续内容
+    fn max_term_chars(&self) -> usize;
+}
+
+/// 在可选的过滤器下处理内容
+pub fn apply_content_filter(
+    filter: Option<&Arc<dyn ContentFilter>>,
+    content: &str,
+) -> FilterOutcome {
+    match filter {
+        Some(filter) => filter.filter(content),
+        None => FilterOutcome::unchanged(content),
+    }
+}
+
+/// 基于词表的内容过滤器
+///
+/// 不区分大小写；以 ASCII 字母或数字开头/结尾的词语按整词匹配，
+/// 避免误伤包含该片段的正常单词，其余（如中文）按子串匹配
+#[derive(Debug, Clone, Default)]
+pub struct WordListFilter {
+    terms: Vec<String>,
+    placeholder: Option<String>,
+}
+
+impl WordListFilter {
+    pub fn new(terms: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let terms = terms
+            .into_iter()
+            .map(Into::into)
+            .map(|term: String| term.trim().to_string())
+            .filter(|term| !term.is_empty())
+            .collect();
+        Self {
+            terms,
+            placeholder: None,
+        }
+    }
+
+    /// 命中时用占位文本替换整条回复，而不是只遮盖命中的词语
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// 在 `content[start..]` 开头匹配词语，返回匹配的字节长度
+    fn match_at(content: &str, start: usize, term: &str) -> Option<usize> {
+        let mut rest = content[start..].char_indices();
+        for expected in term.chars() {
+            let (_, actual) = rest.next()?;
+            if !actual.to_lowercase().eq(expected.to_lowercase()) {
+                return None;
+            }
+        }
+        let len = rest
+            .next()
+            .map_or(content.len() - start, |(offset, _)| offset);
+
+        let needs_boundary = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+        let term_start = term.chars().next();
+        let term_end = term.chars().last();
+        let before = content[..start].chars().next_back();
+        let after = content[start + len..].chars().next();
+        if needs_boundary(term_start) && needs_boundary(before)
+            || needs_boundary(term_end) && needs_boundary(after)
+        {
+            return None;
+        }
+        Some(len)
+    }
+}
+
+impl ContentFilter for WordListFilter {
+    fn filter(&self, content: &str) -> FilterOutcome {
+        let mut output = String::with_capacity(content.len());
+        let mut flagged_terms: Vec<String> = Vec::new();
+        let mut pos = 0;
+
+        while pos < content.len() {
+            // 多个词语同时命中时优先遮盖最长的一个
+            let hit = self
+                .terms
+                .iter()
+                .filter_map(|term| Self::match_at(content, pos, term).map(|len| (term, len)))
+                .max_by_key(|(_, len)| *len);
+
+            match hit {
+                Some((term, len)) => {
+                    let masked = content[pos..pos + len].chars().count();
+                    output.extend(std::iter::repeat_n('*', masked));
+                    if !flagged_terms.contains(term) {
+                        flagged_terms.push(term.clone());
+                    }
+                    pos += len;
+                }
+                None => {
+                    let ch = content[pos..].chars().next().unwrap_or_default();
+                    output.push(ch);
+                    pos += ch.len_utf8();
+                }
+            }
+        }
+
+        if flagged_terms.is_empty() {
+            return FilterOutcome::unchanged(content);
+        }
+        FilterOutcome {
+            content: self.placeholder.clone().unwrap_or(output),
+            flagged_terms,
+        }
+    }
+
+    fn max_term_chars(&self) -> usize {
+        self.terms
+            .iter()
+            .map(|term| term.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// 流式回复的增量过滤
+///
+/// 每收到一块内容就重新过滤已接收的全部内容，只发出不会再变化的部分：
+/// 末尾保留最长词语长度的字符，避免跨块的词语在过滤前被发出。
+/// 过滤结果不再以已发出的内容开头时（如整条替换为占位文本）停止发出，
+/// 由最终的 `ContentFiltered` 事件更正
+pub struct StreamingContentFilter {
+    filter: Option<Arc<dyn ContentFilter>>,
+    raw: String,
+    emitted: String,
+    halted: bool,
+}
+
+impl StreamingContentFilter {
+    pub fn new(filter: Option<Arc<dyn ContentFilter>>) -> Self {
+        Self {
+            filter,
+            raw: String::new(),
+            emitted: String::new(),
+            halted: false,
+        }
+    }
+
+    /// 追加一块内容，返回可以发出的部分
+    pub fn push(&mut self, chunk: &str) -> Option<String> {
+        let Some(filter) = &self.filter else {
+            return (!chunk.is_empty()).then(|| chunk.to_string());
+        };
+        self.raw.push_str(chunk);
+
+        let filtered = filter.filter(&self.raw).content;
+        let stable_end = match filter.max_term_chars() {
+            0 => filtered.len(),
+            holdback => filtered
+                .char_indices()
+                .rev()
+                .nth(holdback - 1)
+                .map_or(0, |(i, _)| i),
+        };
+        self.advance(&filtered, stable_end)
+    }
+
+    /// 流结束，返回剩余可以发出的部分
+    pub fn finish(&mut self) -> Option<String> {
+        let filter = self.filter.as_ref()?;
+        let filtered = filter.filter(&self.raw).content;
+        self.advance(&filtered, filtered.len())
+    }
+
+    fn advance(&mut self, filtered: &str, end: usize) -> Option<String> {
+        if self.halted || !filtered.starts_with(self.emitted.as_str()) {
+            self.halted = true;
+            return None;
+        }
+        if end <= self.emitted.len() {
+            return None;
+        }
+        let delta = filtered[self.emitted.len()..end].to_string();
+        self.emitted.push_str(&delta);
+        Some(delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_list_redacts_configured_terms() {
+        let filter = WordListFilter::new(["darn", "坏蛋"]);
+
+        let outcome = filter.filter("Darn it, 你这个坏蛋! darned darn.");
+
+        assert_eq!(outcome.content, "**** it, 你这个**! darned ****.");
+        assert_eq!(outcome.flagged_terms, vec!["darn", "坏蛋"]);
+    }
+
+    #[test]
+    fn test_word_list_placeholder_and_clean_content() {
+        let filter = WordListFilter::new(["darn"]).with_placeholder("[filtered]");
+
+        assert_eq!(filter.filter("oh darn").content, "[filtered]");
+
+        let clean = filter.filter("all good");
+        assert!(!clean.is_filtered());
+        assert_eq!(clean.content, "all good");
+    }
+
+    fn stream(filter: Option<Arc<dyn ContentFilter>>, chunks: &[&str]) -> Vec<String> {
+        let mut streaming = StreamingContentFilter::new(filter);
+        let mut emitted: Vec<String> = chunks
+            .iter()
+            .filter_map(|chunk| streaming.push(chunk))
+            .collect();
+        emitted.extend(streaming.finish());
+        emitted
+    }
+
+    #[test]
+    fn test_streaming_filter_never_emits_split_terms() {
+        let filter: Arc<dyn ContentFilter> = Arc::new(WordListFilter::new(["darn", "坏蛋"]));
+
+        let emitted = stream(Some(filter), &["oh da", "rn it, 你这个坏", "蛋! darned"]);
+        assert_eq!(emitted, ["o", "h **** it, ", "你这个**! da", "rned"]);
+    }
+
+    #[test]
+    fn test_streaming_filter_halts_on_placeholder() {
+        let filter: Arc<dyn ContentFilter> =
+            Arc::new(WordListFilter::new(["darn"]).with_placeholder("[filtered]"));
+
+        let emitted = stream(Some(filter), &["all good so far. ", "oh darn", " it"]);
+        assert_eq!(emitted.concat(), "all good so f");
+    }
+
+    #[test]
+    fn test_streaming_filter_passes_through_without_filter() {
+        assert_eq!(stream(None, &["a", "", "b"]), vec!["a", "b"]);
+    }
+}
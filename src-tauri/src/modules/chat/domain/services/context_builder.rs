@@ -1,4 +1,4 @@
-use super::super::entities::Message;
+use super::super::entities::{Message, MessageRole};
 
 /// 上下文构建器
 ///
@@ -57,7 +57,8 @@ impl ContextBuilder {
             });
         }
 
-        // 添加历史消息（最近的 N 条）
+        // 添加历史消息（最近一次断点之后的最近 N 条）
+        let history = Self::sanitize(Self::after_last_break(history));
         let start = if history.len() > self.max_messages {
             history.len() - self.max_messages
         } else {
@@ -80,6 +81,37 @@ impl ContextBuilder {
         context
     }
 
+    /// 截取最近一次上下文断点之后的消息
+    pub fn after_last_break(history: &[Message]) -> &[Message] {
+        match history.iter().rposition(|m| m.is_context_break()) {
+            Some(index) => &history[index + 1..],
+            None => history,
+        }
+    }
+
+    /// 清理历史消息，移除内容为空的消息（如流式生成时意外保存的占位助手消息）
+    ///
+    /// 第一条用户消息之前的助手消息（预设的开场白）会保留，作为对话的一部分发送
+    pub fn sanitize(history: &[Message]) -> Vec<&Message> {
+        history
+            .iter()
+            .filter(|msg| !msg.content().trim().is_empty())
+            .collect()
+    }
+
+    /// 只保留最近的 `max_messages` 条历史消息
+    ///
+    /// 发生截断时位于开头的助手消息一并移除，保证截断后的对话以用户消息开始；
+    /// 未截断时保留开头的开场白
+    pub fn most_recent(history: Vec<&Message>, max_messages: usize) -> Vec<&Message> {
+        let start = history.len().saturating_sub(max_messages);
+        history
+            .into_iter()
+            .skip(start)
+            .skip_while(|msg| start > 0 && msg.role() == MessageRole::Assistant)
+            .collect()
+    }
+
     /// 估算 Token 数量（粗略估算，1 token ≈ 4 个字符）
     pub fn estimate_tokens(messages: &[ChatMessage]) -> u32 {
         messages
@@ -135,4 +167,92 @@ mod tests {
         // 应该只有 5 条历史 + 1 条当前消息
         assert_eq!(context.len(), 6);
     }
+
+    #[test]
+    fn test_context_break_excludes_earlier_history() {
+        let session_id = SessionId::new();
+        let history = vec![
+            Message::new_user(session_id, "旧话题"),
+            Message::new_assistant(session_id, "旧回复", None),
+            Message::new_context_break(session_id),
+            Message::new_user(session_id, "新话题"),
+            Message::new_context_break(session_id),
+            Message::new_user(session_id, "最新话题"),
+        ];
+        let current = Message::new_user(session_id, "继续");
+
+        let context = ContextBuilder::new().build(&history, &current);
+
+        assert_eq!(context.len(), 2);
+        assert_eq!(context[0].content, "最新话题");
+        assert_eq!(context[1].content, "继续");
+    }
+
+    #[test]
+    fn test_sanitize_removes_empty_messages() {
+        let session_id = SessionId::new();
+        let history = vec![
+            Message::new_user(session_id, "你好"),
+            Message::new_assistant(session_id, "", None),
+            Message::new_user(session_id, "   "),
+            Message::new_assistant(session_id, "你好！", None),
+        ];
+
+        let cleaned = ContextBuilder::sanitize(&history);
+
+        let contents: Vec<_> = cleaned.iter().map(|m| m.content()).collect();
+        assert_eq!(contents, vec!["你好", "你好！"]);
+    }
+
+    #[test]
+    fn test_sanitize_keeps_leading_greeting() {
+        let session_id = SessionId::new();
+        let history = vec![
+            Message::new_assistant(session_id, "开场白", None),
+            Message::new_assistant(session_id, "", None),
+            Message::new_user(session_id, "提问"),
+            Message::new_assistant(session_id, "回答", None),
+        ];
+        let current = Message::new_user(session_id, "继续");
+
+        let context = ContextBuilder::new().build(&history, &current);
+
+        let roles: Vec<_> = context.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["assistant", "user", "assistant", "user"]);
+        assert_eq!(context[0].content, "开场白");
+    }
+
+    #[test]
+    fn test_most_recent_starts_with_user() {
+        let session_id = SessionId::new();
+        let history = [
+            Message::new_user(session_id, "一"),
+            Message::new_assistant(session_id, "二", None),
+            Message::new_user(session_id, "三"),
+            Message::new_assistant(session_id, "四", None),
+        ];
+        let history: Vec<&Message> = history.iter().collect();
+
+        let contents = |messages: Vec<&Message>| -> Vec<String> {
+            messages.iter().map(|m| m.content().to_string()).collect()
+        };
+        assert_eq!(
+            contents(ContextBuilder::most_recent(history.clone(), 2)),
+            vec!["三", "四"]
+        );
+        // 截断点落在助手消息上时跳过该消息
+        assert_eq!(
+            contents(ContextBuilder::most_recent(history.clone(), 3)),
+            vec!["三", "四"]
+        );
+        assert_eq!(contents(ContextBuilder::most_recent(history, 10)).len(), 4);
+
+        // 未截断时保留开头的开场白
+        let greeting = Message::new_assistant(session_id, "开场白", None);
+        let question = Message::new_user(session_id, "提问");
+        assert_eq!(
+            contents(ContextBuilder::most_recent(vec![&greeting, &question], 2)),
+            vec!["开场白", "提问"]
+        );
+    }
 }
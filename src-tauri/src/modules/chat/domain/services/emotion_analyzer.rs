@@ -1,40 +1,174 @@
+use std::collections::HashMap;
+
 use super::super::entities::Message;
 use super::super::value_objects::Emotion;
 
+/// 每个表情符号的权重
+pub const EMOJI_WEIGHT: u32 = 3;
+
+/// 文本关键词的权重
+pub const KEYWORD_WEIGHT: u32 = 1;
+
+/// 表情符号到情感的映射表
+///
+/// 默认包含常见表情，可通过 [`EmojiEmotionTable::insert`] 扩展或覆盖
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmojiEmotionTable {
+    entries: HashMap<String, Emotion>,
+}
+
+impl EmojiEmotionTable {
+    /// 创建空表
+    pub fn empty() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// 添加或覆盖一个表情符号的映射
+    pub fn insert(&mut self, emoji: impl Into<String>, emotion: Emotion) {
+        let emoji = emoji.into();
+        if !emoji.is_empty() {
+            self.entries.insert(emoji, emotion);
+        }
+    }
+
+    /// 查询表情符号对应的情感
+    pub fn get(&self, emoji: &str) -> Option<Emotion> {
+        self.entries.get(emoji).copied()
+    }
+
+    /// 遍历所有映射
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Emotion)> {
+        self.entries
+            .iter()
+            .map(|(emoji, emotion)| (emoji.as_str(), *emotion))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for EmojiEmotionTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for emoji in ["😊", "😄", "😁", "😂", "🥰", "😍"] {
+            table.insert(emoji, Emotion::Happy);
+        }
+        for emoji in ["😢", "😭", "😞", "😔"] {
+            table.insert(emoji, Emotion::Sad);
+        }
+        for emoji in ["😠", "😡", "🤬", "💢"] {
+            table.insert(emoji, Emotion::Angry);
+        }
+        for emoji in ["😮", "😲", "😯", "😱"] {
+            table.insert(emoji, Emotion::Surprised);
+        }
+        for emoji in ["🤔", "🧐"] {
+            table.insert(emoji, Emotion::Thinking);
+        }
+        table
+    }
+}
+
+impl<S: Into<String>> Extend<(S, Emotion)> for EmojiEmotionTable {
+    fn extend<I: IntoIterator<Item = (S, Emotion)>>(&mut self, iter: I) {
+        for (emoji, emotion) in iter {
+            self.insert(emoji, emotion);
+        }
+    }
+}
+
 /// 情感分析服务
 ///
-/// 领域服务：分析消息内容，提取情感信息
+/// 领域服务：分析消息内容，提取情感信息。
+/// 每个表情符号按 [`EMOJI_WEIGHT`] 计分，文本关键词按 [`KEYWORD_WEIGHT`] 计分，
+/// 得分最高的情感胜出；同分时取在文本中先出现的
 #[derive(Debug, Clone, Default)]
-pub struct EmotionAnalyzer;
+pub struct EmotionAnalyzer {
+    emoji_table: EmojiEmotionTable,
+}
 
 impl EmotionAnalyzer {
     /// 创建新的情感分析器
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// 使用自定义表情映射表
+    pub fn with_emoji_table(emoji_table: EmojiEmotionTable) -> Self {
+        Self { emoji_table }
+    }
+
+    /// 当前的表情映射表
+    pub fn emoji_table(&self) -> &EmojiEmotionTable {
+        &self.emoji_table
+    }
+
+    /// 可修改的表情映射表，用于预设或配置追加映射
+    pub fn emoji_table_mut(&mut self) -> &mut EmojiEmotionTable {
+        &mut self.emoji_table
     }
 
     /// 分析文本的情感
     pub fn analyze(&self, text: &str) -> Option<Emotion> {
-        Some(Emotion::detect_from_text(text))
+        Some(self.score(text))
     }
 
     /// 分析单条消息的情感
     pub fn analyze_message(&self, message: &Message) -> Emotion {
-        Emotion::detect_from_text(message.content())
+        self.score(message.content())
     }
 
     /// 分析响应内容的情感（用于流式响应完成后）
     pub fn analyze_text(text: &str) -> Emotion {
-        Emotion::detect_from_text(text)
+        Self::default().score(text)
     }
 
     /// 批量分析消息情感
     pub fn analyze_batch(messages: &[Message]) -> Vec<(Message, Emotion)> {
+        let analyzer = Self::default();
         messages
             .iter()
-            .map(|msg| (msg.clone(), Emotion::detect_from_text(msg.content())))
+            .map(|msg| (msg.clone(), analyzer.analyze_message(msg)))
             .collect()
     }
+
+    /// 按权重计分，返回得分最高的情感
+    fn score(&self, text: &str) -> Emotion {
+        // (情感, 得分, 首次出现的位置)
+        let mut scores: Vec<(Emotion, u32, usize)> = Vec::new();
+        let mut add = |emotion: Emotion, weight: u32, position: usize| {
+            if let Some(entry) = scores.iter_mut().find(|(e, _, _)| *e == emotion) {
+                entry.1 += weight;
+                entry.2 = entry.2.min(position);
+            } else {
+                scores.push((emotion, weight, position));
+            }
+        };
+
+        for (emoji, emotion) in self.emoji_table.iter() {
+            for (position, _) in text.match_indices(emoji) {
+                add(emotion, EMOJI_WEIGHT, position);
+            }
+        }
+
+        let keyword_emotion = Emotion::detect_from_text(text);
+        if keyword_emotion != Emotion::Neutral {
+            add(keyword_emotion, KEYWORD_WEIGHT, text.len());
+        }
+
+        scores
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)))
+            .map(|(emotion, _, _)| emotion)
+            .unwrap_or(Emotion::Neutral)
+    }
 }
 
 #[cfg(test)]
@@ -57,4 +191,31 @@ mod tests {
         let emotion = analyzer.analyze_message(&msg);
         assert_eq!(emotion, Emotion::Neutral);
     }
+
+    #[test]
+    fn test_repeated_emoji_outweighs_keywords() {
+        let analyzer = EmotionAnalyzer::new();
+        assert_eq!(analyzer.analyze("😠😠"), Some(Emotion::Angry));
+        // 两个表情的权重高于一个关键词
+        assert_eq!(analyzer.analyze("太好了 😠😠"), Some(Emotion::Angry));
+    }
+
+    #[test]
+    fn test_mixed_emoji_resolve_by_weight() {
+        let analyzer = EmotionAnalyzer::new();
+        assert_eq!(analyzer.analyze("😢 😊😊"), Some(Emotion::Happy));
+        // 同分时取先出现的
+        assert_eq!(analyzer.analyze("😲 🧐"), Some(Emotion::Surprised));
+        // 关键词打破表情同分
+        assert_eq!(analyzer.analyze("😲 🧐 让我想想"), Some(Emotion::Thinking));
+    }
+
+    #[test]
+    fn test_custom_emoji_mapping() {
+        let mut analyzer = EmotionAnalyzer::new();
+        assert_eq!(analyzer.analyze("🌸"), Some(Emotion::Neutral));
+
+        analyzer.emoji_table_mut().extend([("🌸", Emotion::Happy)]);
+        assert_eq!(analyzer.analyze("🌸"), Some(Emotion::Happy));
+    }
 }
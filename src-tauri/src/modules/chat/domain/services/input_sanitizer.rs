@@ -0,0 +1,32 @@
+/// 清理用户输入内容
+///
+/// 移除 C0 控制字符（如粘贴带入的空字节），保留换行和制表符；
+/// 其余 Unicode 字符（包括多字节字符和 emoji）原样保留
+pub fn sanitize_user_content(content: &str) -> String {
+    content
+        .chars()
+        .filter(|c| !is_stripped_control(*c))
+        .collect()
+}
+
+fn is_stripped_control(c: char) -> bool {
+    c <= '\u{1F}' && c != '\n' && c != '\t'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_characters_are_removed() {
+        assert_eq!(sanitize_user_content("he\0llo\u{7}\u{1B}[0m"), "hello[0m");
+        assert_eq!(sanitize_user_content("line\r\nbreak"), "line\nbreak");
+        assert_eq!(sanitize_user_content("\u{0}\u{1}\u{1F}"), "");
+    }
+
+    #[test]
+    fn test_legitimate_content_is_preserved() {
+        let content = "你好，世界！\n\tこんにちは 🌸 café";
+        assert_eq!(sanitize_user_content(content), content);
+    }
+}
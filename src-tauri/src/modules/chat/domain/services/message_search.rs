@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+/// 片段中匹配位置前后保留的字符数
+pub const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// 不区分大小写地查找子串，返回匹配在 `content` 中的字符区间 `[start, end)`
+///
+/// 逐字符比较小写形式，匹配区间始终落在 `content` 的字符边界上
+pub fn find_case_insensitive(content: &str, query: &str) -> Option<(usize, usize)> {
+    let query: Vec<char> = query.chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let chars: Vec<char> = content.chars().collect();
+    (0..chars.len()).find_map(|start| {
+        let end = start + query.len();
+        let matches = chars
+            .get(start..end)?
+            .iter()
+            .zip(&query)
+            .all(|(actual, expected)| actual.to_lowercase().eq(expected.to_lowercase()));
+        matches.then_some((start, end))
+    })
+}
+
+/// 搜索结果片段
+///
+/// `highlight_start`/`highlight_end` 为匹配部分在 `text` 中的字符偏移
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSnippet {
+    pub text: String,
+    pub highlight_start: usize,
+    pub highlight_end: usize,
+}
+
+impl SearchSnippet {
+    /// 截取匹配位置附近的内容，被截断的一侧以省略号标记
+    pub fn around(content: &str, (start, end): (usize, usize)) -> Self {
+        let chars: Vec<char> = content.chars().collect();
+        let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+        let to = (end + SNIPPET_CONTEXT_CHARS).min(chars.len());
+
+        let mut text = String::new();
+        let mut offset = start - from;
+        if from > 0 {
+            text.push('…');
+            offset += 1;
+        }
+        text.extend(&chars[from..to]);
+        if to < chars.len() {
+            text.push('…');
+        }
+
+        Self {
+            text,
+            highlight_start: offset,
+            highlight_end: offset + (end - start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_case_insensitive_returns_char_offsets() {
+        assert_eq!(find_case_insensitive("Hello World", "WORLD"), Some((6, 11)));
+        assert_eq!(find_case_insensitive("你好 Kizuna", "kizuna"), Some((3, 9)));
+        assert_eq!(find_case_insensitive("Hello", "bye"), None);
+        assert_eq!(find_case_insensitive("Hello", ""), None);
+    }
+
+    #[test]
+    fn test_snippet_highlights_match_in_truncated_text() {
+        let content = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let range = find_case_insensitive(&content, "NEEDLE").unwrap();
+        let snippet = SearchSnippet::around(&content, range);
+
+        let chars: Vec<char> = snippet.text.chars().collect();
+        assert_eq!(chars.first(), Some(&'…'));
+        assert_eq!(chars.last(), Some(&'…'));
+        let highlighted: String = chars[snippet.highlight_start..snippet.highlight_end]
+            .iter()
+            .collect();
+        assert_eq!(highlighted, "needle");
+
+        let short = SearchSnippet::around("Find me", (5, 7));
+        assert_eq!(short.text, "Find me");
+        assert_eq!((short.highlight_start, short.highlight_end), (5, 7));
+    }
+}
@@ -1,8 +1,26 @@
 // Chat Domain - Services
 // 领域服务处理不属于任何实体的业务逻辑
 
+mod auto_title;
+mod content_filter;
 mod context_builder;
 mod emotion_analyzer;
+mod input_sanitizer;
+mod message_search;
+mod repetition_detector;
+mod settings_resolver;
+mod store_reconciler;
+mod token_estimator;
+mod user_turn_trimmer;
 
+pub use auto_title::*;
+pub use content_filter::*;
 pub use context_builder::*;
 pub use emotion_analyzer::*;
+pub use input_sanitizer::*;
+pub use message_search::*;
+pub use repetition_detector::*;
+pub use settings_resolver::*;
+pub use store_reconciler::*;
+pub use token_estimator::*;
+pub use user_turn_trimmer::*;
@@ -0,0 +1,124 @@
+/// 重复检测策略
+///
+/// 回复末尾的同一片段连续重复超过阈值时视为模型陷入循环
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepetitionPolicy {
+    /// 检测的最长片段（字符数）
+    pub max_pattern_len: usize,
+    /// 片段连续出现多少次视为循环
+    pub min_repeats: usize,
+}
+
+impl Default for RepetitionPolicy {
+    fn default() -> Self {
+        Self {
+            max_pattern_len: 50,
+            min_repeats: 10,
+        }
+    }
+}
+
+/// 检测到的循环
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepetitionMatch {
+    /// 重复的片段（末尾的一个周期）
+    pub pattern: String,
+    /// 末尾连续出现的次数
+    pub repeats: usize,
+}
+
+/// 检查文本末尾是否有片段在循环
+///
+/// 从短到长尝试每个周期，返回满足阈值的最短片段。只由标点、符号或空白构成的片段
+/// （如分隔线、表格边框）不视为循环
+pub fn detect_repetition(text: &str, policy: &RepetitionPolicy) -> Option<RepetitionMatch> {
+    if policy.max_pattern_len == 0 || policy.min_repeats < 2 {
+        return None;
+    }
+
+    // 只需检查末尾足以容纳 min_repeats 个最长片段的部分
+    let window = policy.max_pattern_len * policy.min_repeats;
+    let mut tail: Vec<char> = text.chars().rev().take(window).collect();
+    tail.reverse();
+
+    for period in 1..=policy.max_pattern_len.min(tail.len() / policy.min_repeats) {
+        // 从末尾向前数与前一个周期相同的字符数
+        let matching = (period..tail.len())
+            .rev()
+            .take_while(|&i| tail[i] == tail[i - period])
+            .count();
+        let repeats = (matching + period) / period;
+        if repeats < policy.min_repeats {
+            continue;
+        }
+
+        let pattern: String = tail[tail.len() - period..].iter().collect();
+        if pattern.chars().any(char::is_alphanumeric) {
+            return Some(RepetitionMatch { pattern, repeats });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_looping_pattern() {
+        let policy = RepetitionPolicy::default();
+        let text = format!(
+            "Sure, here is the answer. {}",
+            "I will help you. ".repeat(12)
+        );
+
+        let found = detect_repetition(&text, &policy).unwrap();
+        assert_eq!(found.pattern, "I will help you. ");
+        assert!(found.repeats >= 10);
+
+        // 循环在片段中途也能检测到
+        let cut = format!("{}好的好", "好的".repeat(12));
+        let found = detect_repetition(&cut, &policy).unwrap();
+        assert_eq!(found.pattern.chars().count(), 2);
+    }
+
+    #[test]
+    fn test_valid_repetitive_text_is_not_a_loop() {
+        let policy = RepetitionPolicy::default();
+        let texts = [
+            // 少量重复的笑声和强调
+            "Haha, hahaha! That's very very very funny.".to_string(),
+            // 分隔线和表格边框
+            format!("Title\n{}\n", "=".repeat(200)),
+            format!("| a | b |\n{}\n", "|---".repeat(60)),
+            // 结构相似但内容不同的列表
+            (1..=30)
+                .map(|i| format!("{}. item {}\n", i, i))
+                .collect::<String>(),
+            // 低于阈值的重复
+            "I will help you. ".repeat(9),
+        ];
+
+        for text in &texts {
+            assert_eq!(detect_repetition(text, &policy), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_policy_thresholds() {
+        let text = "ok ".repeat(5);
+        let strict = RepetitionPolicy {
+            max_pattern_len: 10,
+            min_repeats: 5,
+        };
+        assert!(detect_repetition(&text, &strict).is_some());
+
+        // 片段比最长检测长度还长
+        let short_window = RepetitionPolicy {
+            max_pattern_len: 2,
+            min_repeats: 5,
+        };
+        assert_eq!(detect_repetition(&text, &short_window), None);
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+/// 设置值的来源层
+///
+/// 优先级从高到低：会话覆盖 > 预设 > 全局配置 > 提供商默认 > 内置默认
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingSource {
+    /// 会话的模型配置覆盖
+    Session,
+    /// 会话关联的预设
+    Preset,
+    /// 全局配置
+    Global,
+    /// 提供商配置（如模型列表中的第一个模型）
+    Provider,
+    /// 未在任何层设置，使用内置默认或交给提供商决定
+    Default,
+}
+
+/// 带来源标注的设置值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedSetting<T> {
+    pub value: T,
+    pub source: SettingSource,
+}
+
+impl<T> ResolvedSetting<T> {
+    fn new(value: T, source: SettingSource) -> Self {
+        Self { value, source }
+    }
+}
+
+/// 会话级覆盖，对应会话的 `model_config`
+///
+/// 提供商由发送请求时选择的提供商决定，会话配置中的提供商不参与合并
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOverrides {
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+impl SessionOverrides {
+    /// 从会话的模型配置解析，格式不符时视为没有覆盖
+    pub fn from_model_config(config: Option<&serde_json::Value>) -> Self {
+        config
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// 参与合并的各层设置
+#[derive(Debug, Clone, Default)]
+pub struct SettingsLayers {
+    pub session: SessionOverrides,
+    /// 预设的系统提示词
+    pub preset_system_prompt: Option<String>,
+    /// 全局默认提供商 ID
+    pub global_provider: Option<String>,
+    /// 全局上下文长度
+    pub global_context_limit: Option<u32>,
+    /// 已配置提供商的模型列表，按提供商 ID 查找
+    pub provider_models: Vec<(String, Vec<String>)>,
+}
+
+/// 合并后的会话设置
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveSettings {
+    pub provider: ResolvedSetting<Option<String>>,
+    pub model: ResolvedSetting<Option<String>>,
+    /// 未设置时由提供商决定
+    pub temperature: ResolvedSetting<Option<f32>>,
+    pub system_prompt: ResolvedSetting<Option<String>>,
+    pub context_limit: ResolvedSetting<u32>,
+}
+
+/// 未配置时的默认上下文长度（消息条数）
+pub const DEFAULT_CONTEXT_LIMIT: u32 = 10;
+
+/// 按优先级合并各层设置
+///
+/// 空字符串视为未设置，继续向下一层查找
+pub fn resolve_settings(layers: &SettingsLayers) -> EffectiveSettings {
+    let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+
+    let provider = if let Some(id) = non_empty(&layers.global_provider) {
+        ResolvedSetting::new(Some(id), SettingSource::Global)
+    } else {
+        ResolvedSetting::new(None, SettingSource::Default)
+    };
+
+    let provider_default_model = provider.value.as_ref().and_then(|id| {
+        layers
+            .provider_models
+            .iter()
+            .find(|(provider_id, _)| provider_id == id)
+            .and_then(|(_, models)| models.first().cloned())
+    });
+    let model = if let Some(model) = non_empty(&layers.session.model) {
+        ResolvedSetting::new(Some(model), SettingSource::Session)
+    } else if let Some(model) = provider_default_model {
+        ResolvedSetting::new(Some(model), SettingSource::Provider)
+    } else {
+        ResolvedSetting::new(None, SettingSource::Default)
+    };
+
+    let temperature = match layers.session.temperature {
+        Some(temperature) => ResolvedSetting::new(Some(temperature), SettingSource::Session),
+        None => ResolvedSetting::new(None, SettingSource::Default),
+    };
+
+    let system_prompt = match non_empty(&layers.preset_system_prompt) {
+        Some(prompt) => ResolvedSetting::new(Some(prompt), SettingSource::Preset),
+        None => ResolvedSetting::new(None, SettingSource::Default),
+    };
+
+    let context_limit = match layers.global_context_limit {
+        Some(limit) => ResolvedSetting::new(limit, SettingSource::Global),
+        None => ResolvedSetting::new(DEFAULT_CONTEXT_LIMIT, SettingSource::Default),
+    };
+
+    EffectiveSettings {
+        provider,
+        model,
+        temperature,
+        system_prompt,
+        context_limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layers() -> SettingsLayers {
+        SettingsLayers {
+            session: SessionOverrides::default(),
+            preset_system_prompt: Some("You are Hiyori.".to_string()),
+            global_provider: Some("openai".to_string()),
+            global_context_limit: Some(20),
+            provider_models: vec![
+                ("openai".to_string(), vec!["gpt-4o".to_string()]),
+                ("ollama".to_string(), vec!["llama3".to_string()]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_resolution_precedence_is_reported() {
+        // 没有会话覆盖：提供商来自全局，模型来自提供商
+        let settings = resolve_settings(&layers());
+        assert_eq!(
+            settings.provider,
+            ResolvedSetting::new(Some("openai".to_string()), SettingSource::Global)
+        );
+        assert_eq!(
+            settings.model,
+            ResolvedSetting::new(Some("gpt-4o".to_string()), SettingSource::Provider)
+        );
+        assert_eq!(settings.temperature.source, SettingSource::Default);
+        assert_eq!(settings.system_prompt.source, SettingSource::Preset);
+        assert_eq!(
+            settings.context_limit,
+            ResolvedSetting::new(20, SettingSource::Global)
+        );
+
+        // 会话配置中的提供商不参与合并（发送时不会使用）
+        let mut with_overrides = layers();
+        with_overrides.session = SessionOverrides::from_model_config(Some(&serde_json::json!({
+            "provider": "ollama",
+            "temperature": 0.2
+        })));
+        let settings = resolve_settings(&with_overrides);
+        assert_eq!(settings.provider.source, SettingSource::Global);
+        assert_eq!(
+            settings.model,
+            ResolvedSetting::new(Some("gpt-4o".to_string()), SettingSource::Provider)
+        );
+        assert_eq!(
+            settings.temperature,
+            ResolvedSetting::new(Some(0.2), SettingSource::Session)
+        );
+
+        // 会话指定的模型优先于提供商默认模型
+        with_overrides.session.model = Some("qwen2".to_string());
+        let settings = resolve_settings(&with_overrides);
+        assert_eq!(
+            settings.model,
+            ResolvedSetting::new(Some("qwen2".to_string()), SettingSource::Session)
+        );
+    }
+
+    #[test]
+    fn test_unset_layers_fall_back_to_defaults() {
+        let settings = resolve_settings(&SettingsLayers {
+            global_provider: Some(String::new()),
+            ..SettingsLayers::default()
+        });
+
+        assert_eq!(
+            settings.provider,
+            ResolvedSetting::new(None, SettingSource::Default)
+        );
+        assert_eq!(
+            settings.model,
+            ResolvedSetting::new(None, SettingSource::Default)
+        );
+        assert_eq!(settings.system_prompt.source, SettingSource::Default);
+        assert_eq!(
+            settings.context_limit,
+            ResolvedSetting::new(DEFAULT_CONTEXT_LIMIT, SettingSource::Default)
+        );
+
+        // 无法解析的会话配置视为没有覆盖
+        let overrides = SessionOverrides::from_model_config(Some(&serde_json::json!("gpt-4")));
+        assert_eq!(overrides, SessionOverrides::default());
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+
+use super::super::entities::Session;
+use super::super::value_objects::SessionId;
+
+/// 为孤立消息创建的占位会话标题
+pub const RECOVERED_SESSION_TITLE: &str = "恢复的对话";
+
+/// 会话不存在的一组消息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanMessages {
+    pub session_id: SessionId,
+    pub message_count: usize,
+}
+
+/// 会话存储与消息存储之间的不一致
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreReconciliation {
+    /// 有消息但会话不存在（如写入会话时崩溃）
+    pub orphan_messages: Vec<OrphanMessages>,
+    /// 存在但没有任何消息的会话（新建会话也属于此类，仅供标记）
+    pub empty_sessions: Vec<SessionId>,
+}
+
+impl StoreReconciliation {
+    /// 两个存储是否一致
+    pub fn is_consistent(&self) -> bool {
+        self.orphan_messages.is_empty() && self.empty_sessions.is_empty()
+    }
+
+    /// 为每组孤立消息生成占位会话，沿用原会话 ID 以便消息重新可见
+    pub fn placeholder_sessions(&self) -> Vec<Session> {
+        self.orphan_messages
+            .iter()
+            .map(|orphan| {
+                Session::from_id(orphan.session_id, RECOVERED_SESSION_TITLE.to_string(), None)
+            })
+            .collect()
+    }
+}
+
+/// 比较会话列表和各会话的消息数量，找出两者不一致之处
+///
+/// `message_counts` 中数量为 0 的条目视为没有消息；结果按会话 ID 排序
+pub fn reconcile(
+    sessions: &[Session],
+    message_counts: &HashMap<SessionId, usize>,
+) -> StoreReconciliation {
+    let known: HashSet<SessionId> = sessions.iter().map(Session::id).collect();
+
+    let mut orphan_messages: Vec<OrphanMessages> = message_counts
+        .iter()
+        .filter(|(id, &count)| count > 0 && !known.contains(id))
+        .map(|(&session_id, &message_count)| OrphanMessages {
+            session_id,
+            message_count,
+        })
+        .collect();
+    orphan_messages.sort_by_key(|o| o.session_id.to_string());
+
+    let mut empty_sessions: Vec<SessionId> = sessions
+        .iter()
+        .map(Session::id)
+        .filter(|id| message_counts.get(id).copied().unwrap_or(0) == 0)
+        .collect();
+    empty_sessions.sort_by_key(SessionId::to_string);
+
+    StoreReconciliation {
+        orphan_messages,
+        empty_sessions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphan_messages_get_placeholder_sessions() {
+        let session = Session::new(Some("Kept".to_string()), None);
+        let orphan = SessionId::new();
+        let counts = HashMap::from([(session.id(), 2), (orphan, 3)]);
+
+        let result = reconcile(std::slice::from_ref(&session), &counts);
+        assert_eq!(
+            result.orphan_messages,
+            vec![OrphanMessages {
+                session_id: orphan,
+                message_count: 3,
+            }]
+        );
+        assert!(result.empty_sessions.is_empty());
+
+        let placeholders = result.placeholder_sessions();
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(placeholders[0].id(), orphan);
+        assert_eq!(placeholders[0].title(), RECOVERED_SESSION_TITLE);
+    }
+
+    #[test]
+    fn test_sessions_without_messages_are_flagged() {
+        let empty = Session::new(None, None);
+        let cleared = Session::new(None, None);
+        let active = Session::new(None, None);
+        let counts = HashMap::from([(cleared.id(), 0), (active.id(), 1)]);
+
+        let result = reconcile(&[empty.clone(), cleared.clone(), active], &counts);
+        assert!(result.orphan_messages.is_empty());
+        let mut expected = vec![empty.id(), cleared.id()];
+        expected.sort_by_key(SessionId::to_string);
+        assert_eq!(result.empty_sessions, expected);
+        assert!(!result.is_consistent());
+
+        assert!(reconcile(&[], &HashMap::new()).is_consistent());
+    }
+}
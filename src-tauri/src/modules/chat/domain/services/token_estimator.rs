@@ -0,0 +1,139 @@
+/// Token 数量估算器
+///
+/// 只提供近似值，用于界面提示和上下文预算；实现必须是纯本地的。
+/// `model` 用于在有条件时按模型细化估算
+pub trait TokenEstimator: Send + Sync {
+    fn estimate(&self, text: &str, model: Option<&str>) -> u32;
+}
+
+/// 默认每个 token 对应的字符数（英文等以空格分词的文字）
+pub const DEFAULT_CHARS_PER_TOKEN: f32 = 4.0;
+
+/// 基于字符数的启发式估算器
+///
+/// 中日韩文字按每字 1 个 token 计，其余字符按每 token 若干字符计；
+/// 可按模型名前缀（不区分大小写）设置不同的字符比例
+#[derive(Debug, Clone)]
+pub struct HeuristicTokenEstimator {
+    default_chars_per_token: f32,
+    /// (模型名前缀, 每 token 字符数)，按最长前缀匹配
+    model_ratios: Vec<(String, f32)>,
+}
+
+impl Default for HeuristicTokenEstimator {
+    fn default() -> Self {
+        Self::new().with_model_ratio("claude", 3.5)
+    }
+}
+
+impl HeuristicTokenEstimator {
+    /// 不含任何模型细化的估算器
+    pub fn new() -> Self {
+        Self {
+            default_chars_per_token: DEFAULT_CHARS_PER_TOKEN,
+            model_ratios: Vec::new(),
+        }
+    }
+
+    /// 为指定前缀的模型设置每 token 字符数，非正数会被忽略
+    pub fn with_model_ratio(
+        mut self,
+        model_prefix: impl Into<String>,
+        chars_per_token: f32,
+    ) -> Self {
+        if chars_per_token > 0.0 {
+            let prefix = model_prefix.into().to_lowercase();
+            self.model_ratios.retain(|(p, _)| *p != prefix);
+            self.model_ratios.push((prefix, chars_per_token));
+        }
+        self
+    }
+
+    fn chars_per_token(&self, model: Option<&str>) -> f32 {
+        let Some(model) = model.map(str::to_lowercase) else {
+            return self.default_chars_per_token;
+        };
+        self.model_ratios
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default_chars_per_token, |(_, ratio)| *ratio)
+    }
+}
+
+/// 是否为通常每字单独成 token 的中日韩文字
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}' // CJK 扩展 A
+        | '\u{4E00}'..='\u{9FFF}' // CJK 统一汉字
+        | '\u{AC00}'..='\u{D7AF}' // 谚文音节
+        | '\u{F900}'..='\u{FAFF}' // CJK 兼容汉字
+        | '\u{FF00}'..='\u{FFEF}' // 全角字符
+    )
+}
+
+impl TokenEstimator for HeuristicTokenEstimator {
+    fn estimate(&self, text: &str, model: Option<&str>) -> u32 {
+        let (cjk, other) = text.chars().fold((0u32, 0u32), |(cjk, other), c| {
+            if is_cjk(c) {
+                (cjk + 1, other)
+            } else {
+                (cjk, other + 1)
+            }
+        });
+        cjk + (other as f32 / self.chars_per_token(model)).ceil() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_grows_monotonically_and_proportionally() {
+        let estimator = HeuristicTokenEstimator::default();
+        assert_eq!(estimator.estimate("", None), 0);
+
+        let sentence = "The quick brown fox jumps over the lazy dog. ";
+        let mut previous = 0;
+        for repeat in 1..=20 {
+            let estimate = estimator.estimate(&sentence.repeat(repeat), None);
+            assert!(estimate >= previous);
+            previous = estimate;
+        }
+
+        // 长度翻十倍，估算值也约为十倍
+        let short = estimator.estimate(&sentence.repeat(10), None) as f32;
+        let long = estimator.estimate(&sentence.repeat(100), None) as f32;
+        assert!((9.5..=10.5).contains(&(long / short)));
+        // 约 4 个字符一个 token
+        let chars = sentence.repeat(100).chars().count() as f32;
+        assert!((long - chars / 4.0).abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_cjk_counts_per_character() {
+        let estimator = HeuristicTokenEstimator::new();
+        assert_eq!(estimator.estimate("今天天气怎么样", None), 7);
+        assert_eq!(estimator.estimate("你好 world", None), 2 + 2);
+    }
+
+    #[test]
+    fn test_model_ratio_refines_estimate() {
+        let estimator = HeuristicTokenEstimator::new()
+            .with_model_ratio("gpt", 4.0)
+            .with_model_ratio("gpt-4o-mini", 2.0);
+        let text = "a".repeat(40);
+
+        assert_eq!(estimator.estimate(&text, None), 10);
+        assert_eq!(estimator.estimate(&text, Some("unknown-model")), 10);
+        assert_eq!(estimator.estimate(&text, Some("GPT-4o")), 10);
+        // 最长前缀优先
+        assert_eq!(estimator.estimate(&text, Some("gpt-4o-mini-2024")), 20);
+        assert!(
+            HeuristicTokenEstimator::default().estimate(&text, Some("claude-3-5-sonnet"))
+                > estimator.estimate(&text, None)
+        );
+    }
+}
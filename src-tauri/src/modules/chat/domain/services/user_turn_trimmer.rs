@@ -0,0 +1,85 @@
+/// 未配置用户名时模型常用的用户发言标签
+pub const DEFAULT_USER_LABEL: &str = "User";
+
+/// 用户发言的标签：默认标签和配置的用户名（忽略大小写去重）
+fn user_labels(user_name: &str) -> Vec<&str> {
+    let mut labels = vec![DEFAULT_USER_LABEL];
+    let user_name = user_name.trim();
+    if !user_name.is_empty() && !user_name.eq_ignore_ascii_case(DEFAULT_USER_LABEL) {
+        labels.push(user_name);
+    }
+    labels
+}
+
+/// 角色扮演模式的默认停止序列：模型开始替用户发言时停止
+pub fn role_play_stop_sequences(user_name: &str) -> Vec<String> {
+    user_labels(user_name)
+        .into_iter()
+        .map(|label| format!("\n{}:", label))
+        .collect()
+}
+
+/// 截掉模型越界生成的用户发言
+///
+/// 从第一个以 `User:` 或 `<用户名>:` 开头的行（标签不区分 ASCII 大小写，也接受全角冒号）
+/// 起全部去掉，并去掉末尾空白。用于提供商未遵守停止序列的情况；
+/// 回复第一行就是用户发言时不截断，避免清空整条回复
+pub fn trim_user_turn_leak<'a>(content: &'a str, user_name: &str) -> &'a str {
+    let labels = user_labels(user_name);
+
+    for (newline, _) in content.match_indices('\n') {
+        let line = content[newline + 1..].trim_start_matches([' ', '\t']);
+        let leaked = labels.iter().any(|label| {
+            line.get(..label.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(label))
+                && (line[label.len()..].starts_with(':') || line[label.len()..].starts_with('：'))
+        });
+        if !leaked {
+            continue;
+        }
+
+        let kept = content[..newline].trim_end();
+        if !kept.is_empty() {
+            return kept;
+        }
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trims_leaked_user_turn() {
+        let content = "Hiyori: Welcome back!\n\nUser: Thanks, I missed you.\nHiyori: Me too.";
+        assert_eq!(trim_user_turn_leak(content, ""), "Hiyori: Welcome back!");
+
+        // 配置的用户名、大小写和全角冒号
+        let content = "欢迎回来！\n小明：我回来了\n";
+        assert_eq!(trim_user_turn_leak(content, "小明"), "欢迎回来！");
+        let content = "Sure thing.\n  user: and then?";
+        assert_eq!(trim_user_turn_leak(content, "Alex"), "Sure thing.");
+        let content = "Sure thing.\nALEX: and then?";
+        assert_eq!(trim_user_turn_leak(content, "Alex"), "Sure thing.");
+    }
+
+    #[test]
+    fn test_keeps_content_without_leak() {
+        // 标签不在行首、未接冒号或只是前缀相同的单词都不截断
+        let content = "Tell the User: hi.\nUsername: kizuna\nUsers: many";
+        assert_eq!(trim_user_turn_leak(content, ""), content);
+
+        // 第一行就是用户发言时保留原文
+        let content = "User: hello\n";
+        assert_eq!(trim_user_turn_leak(content, ""), content);
+    }
+
+    #[test]
+    fn test_role_play_stop_sequences() {
+        assert_eq!(role_play_stop_sequences(""), ["\nUser:"]);
+        assert_eq!(role_play_stop_sequences(" user "), ["\nUser:"]);
+        assert_eq!(role_play_stop_sequences("Alex"), ["\nUser:", "\nAlex:"]);
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// 结束原因
+///
+/// 值对象：LLM 停止生成的原因，助手消息会记录下来，便于界面标记被截断或过滤的回复
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ContentFilter,
+    FunctionCall,
+}
@@ -4,16 +4,22 @@
 
 use async_trait::async_trait;
 use futures::stream::Stream;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::watch;
 use tracing::{debug, error};
 
+use super::openai::OpenAIResponseFormat;
+use super::{build_http_client, cancellable, chunk_stream, extra_header_map, StreamCancellation};
+use crate::infrastructure::CancellationRegistry;
+
 use crate::modules::chat::ports::{
-    CompletionRequest, CompletionResponse, FinishReason, LLMError, LLMPort, ModelInfo,
-    ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+    CompletionRequest, CompletionResponse, FinishReason, HttpClientOptions, LLMError, LLMPort,
+    ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
 };
 
 /// OpenAI API 请求格式
@@ -24,9 +30,17 @@ struct OpenAIRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<OpenAIResponseFormat>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -61,6 +75,7 @@ struct OpenAIUsage {
 #[derive(Debug, Deserialize)]
 struct OpenAIStreamResponse {
     #[allow(dead_code)]
+    #[serde(default)]
     id: String,
     choices: Vec<OpenAIStreamChoice>,
 }
@@ -85,29 +100,38 @@ pub struct OpenAICompatibleConfig {
     pub api_key: String,
     pub model: String,
     pub timeout_secs: u64,
+    /// HTTP 连接调优
+    pub http: HttpClientOptions,
+    /// HTTP 代理地址
+    pub proxy_url: Option<String>,
+    /// 附加到每个请求的请求头
+    pub extra_headers: HashMap<String, String>,
 }
 
 /// OpenAI 兼容适配器基础实现
 pub struct BaseOpenAICompatibleAdapter {
     config: OpenAICompatibleConfig,
     client: Client,
-    cancel_sender: watch::Sender<bool>,
+    extra_headers: HeaderMap,
+    /// 进行中的流式请求，按请求 ID 取消
+    cancellations: Arc<CancellationRegistry>,
 }
 
 impl BaseOpenAICompatibleAdapter {
     /// 创建新的适配器实例
     pub fn new(config: OpenAICompatibleConfig) -> Result<Self, LLMError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .build()
-            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
-
-        let (cancel_sender, _) = watch::channel(false);
+        let client = build_http_client(
+            Some(Duration::from_secs(config.timeout_secs)),
+            &config.http,
+            config.proxy_url.as_deref(),
+        )?;
+        let extra_headers = extra_header_map(&config.extra_headers)?;
 
         Ok(Self {
             config,
             client,
-            cancel_sender,
+            extra_headers,
+            cancellations: Arc::new(CancellationRegistry::new()),
         })
     }
 
@@ -140,8 +164,12 @@ impl BaseOpenAICompatibleAdapter {
             model: self.config.model.clone(),
             messages,
             temperature: request.temperature,
+            top_p: request.top_p,
+            presence_penalty: request.presence_penalty,
+            frequency_penalty: request.frequency_penalty,
             max_tokens: request.max_tokens,
             stream: if stream { Some(true) } else { None },
+            response_format: request.response_format.as_ref().map(Into::into),
         }
     }
 
@@ -160,9 +188,29 @@ impl BaseOpenAICompatibleAdapter {
         serde_json::from_str(data).ok()
     }
 
-    /// 取消当前生成
-    pub fn cancel(&self) {
-        let _ = self.cancel_sender.send(true);
+    /// 将流式响应转换为分块，既无内容也无结束原因的事件被忽略
+    fn to_stream_chunk(response: OpenAIStreamResponse) -> Option<StreamChunk> {
+        let choice = response.choices.into_iter().next()?;
+        if choice.delta.content.is_none() && choice.finish_reason.is_none() {
+            return None;
+        }
+
+        Some(StreamChunk {
+            content: choice.delta.content.unwrap_or_default(),
+            reasoning: None,
+            finish_reason: choice.finish_reason.as_deref().and_then(|r| match r {
+                "stop" => Some(FinishReason::Stop),
+                "length" => Some(FinishReason::Length),
+                "content_filter" => Some(FinishReason::ContentFilter),
+                _ => None,
+            }),
+            usage: None,
+        })
+    }
+
+    /// 取消指定请求的生成
+    pub fn cancel(&self, request_id: &str) {
+        self.cancellations.cancel(request_id);
     }
 
     /// 执行非流式补全
@@ -183,6 +231,7 @@ impl BaseOpenAICompatibleAdapter {
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
             .json(&openai_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
@@ -247,6 +296,8 @@ impl BaseOpenAICompatibleAdapter {
         &self,
         request: CompletionRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
+        let cancellation =
+            StreamCancellation::register(&self.cancellations, request.request_id.as_deref());
         let openai_request = self.to_openai_request(&request, true);
 
         debug!(
@@ -260,6 +311,7 @@ impl BaseOpenAICompatibleAdapter {
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
             .json(&openai_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
@@ -277,60 +329,10 @@ impl BaseOpenAICompatibleAdapter {
             });
         }
 
-        // 使用 unfold 代替 scan 避免生命周期问题
-        use futures::stream::{self, StreamExt};
-
-        let bytes_stream = response.bytes_stream();
-        let buffer = String::new();
-        let stream = stream::unfold(
-            (bytes_stream, buffer),
-            |(mut bytes_stream, mut buffer)| async move {
-                loop {
-                    match bytes_stream.next().await {
-                        Some(Ok(bytes)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&bytes));
-
-                            // 处理所有完整的行
-                            while let Some(pos) = buffer.find('\n') {
-                                let line = buffer[..pos].to_string();
-                                buffer.drain(..=pos);
-
-                                if let Some(sse_response) = Self::parse_sse_line(&line) {
-                                    if let Some(choice) = sse_response.choices.first() {
-                                        if let Some(content) = &choice.delta.content {
-                                            let chunk = StreamChunk {
-                                                content: content.clone(),
-                                                finish_reason: choice
-                                                    .finish_reason
-                                                    .as_ref()
-                                                    .and_then(|r| match r.as_str() {
-                                                        "stop" => Some(FinishReason::Stop),
-                                                        "length" => Some(FinishReason::Length),
-                                                        "content_filter" => {
-                                                            Some(FinishReason::ContentFilter)
-                                                        }
-                                                        _ => None,
-                                                    }),
-                                                usage: None,
-                                            };
-                                            return Some((Ok(chunk), (bytes_stream, buffer)));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Some((
-                                Err(LLMError::NetworkError(e.to_string())),
-                                (bytes_stream, buffer),
-                            ));
-                        }
-                        None => return None,
-                    }
-                }
-            },
-        );
-
+        let stream = chunk_stream(response.bytes_stream(), |line| {
+            Self::parse_sse_line(line).and_then(Self::to_stream_chunk)
+        });
+        let stream = cancellable(stream, cancellation);
         Ok(Box::pin(stream))
     }
 }
@@ -354,6 +356,9 @@ impl LLMPort for BaseOpenAICompatibleAdapter {
                 supports_vision: false,
                 supports_functions: true,
             }],
+            supports_cancellation: true,
+            supports_model_listing: false,
+            supports_assistant_prefix: false,
         }
     }
 
@@ -388,9 +393,35 @@ impl LLMPort for BaseOpenAICompatibleAdapter {
         })
     }
 
-    async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
-        // 发送取消信号
-        let _ = self.cancel_sender.send(true);
+    async fn cancel(&self, request_id: &str) -> Result<(), LLMError> {
+        BaseOpenAICompatibleAdapter::cancel(self, request_id);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_capabilities() {
+        let adapter = BaseOpenAICompatibleAdapter::new(OpenAICompatibleConfig {
+            provider_id: "compat".to_string(),
+            provider_name: "Compatible".to_string(),
+            provider_type: ProviderType::Custom,
+            base_url: "http://localhost:8080/v1".to_string(),
+            api_key: String::new(),
+            model: "local-model".to_string(),
+            timeout_secs: 30,
+            http: HttpClientOptions::default(),
+            proxy_url: None,
+            extra_headers: HashMap::new(),
+        })
+        .unwrap();
+        let info = adapter.provider_info();
+
+        assert!(info.supports_cancellation);
+        assert!(!info.supports_model_listing);
+        assert!(LLMPort::cancel(&adapter, "req-1").await.is_ok());
+    }
+}
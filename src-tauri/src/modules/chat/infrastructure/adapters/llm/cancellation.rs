@@ -0,0 +1,137 @@
+// Stream Cancellation - 流式响应取消
+//
+// 取消信号与读取下一个分块相互竞争：服务器停止发送数据时也能立即结束流，
+// 并丢弃底层字节流，从而及时关闭 HTTP 连接
+
+use futures::stream::{self, Stream, StreamExt};
+use std::sync::Arc;
+
+use crate::infrastructure::{CancelSignal, CancellationRegistry};
+
+/// 流式请求的取消登记
+///
+/// 按请求 ID 登记到适配器的取消注册表，`cancel(request_id)` 只作用于对应的请求；
+/// 流结束或被丢弃时注销。未指定请求 ID 的请求无法单独取消
+pub struct StreamCancellation {
+    signal: Option<CancelSignal>,
+    registration: Option<(Arc<CancellationRegistry>, String)>,
+}
+
+impl StreamCancellation {
+    pub fn register(registry: &Arc<CancellationRegistry>, request_id: Option<&str>) -> Self {
+        match request_id {
+            Some(request_id) => Self {
+                signal: Some(registry.register(request_id)),
+                registration: Some((registry.clone(), request_id.to_string())),
+            },
+            None => Self {
+                signal: None,
+                registration: None,
+            },
+        }
+    }
+
+    /// 等待取消，无法取消时永远挂起
+    async fn cancelled(&mut self) {
+        match &mut self.signal {
+            Some(signal) => signal.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+impl Drop for StreamCancellation {
+    fn drop(&mut self) {
+        if let Some((registry, request_id)) = &self.registration {
+            registry.remove(request_id);
+        }
+    }
+}
+
+/// 让流在取消时立即结束
+///
+/// 结束时底层流随之被丢弃，不必等待下一个分块到达
+pub fn cancellable<S>(
+    inner: S,
+    cancellation: StreamCancellation,
+) -> impl Stream<Item = S::Item> + Send
+where
+    S: Stream + Send + 'static,
+    S::Item: Send,
+{
+    stream::unfold(
+        (Box::pin(inner), cancellation),
+        |(mut inner, mut cancellation)| async move {
+            tokio::select! {
+                biased;
+                _ = cancellation.cancelled() => None,
+                item = inner.next() => item.map(|item| (item, (inner, cancellation))),
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    /// 被丢弃时置位的标记
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_ends_idle_stream_and_drops_inner() {
+        let registry = Arc::new(CancellationRegistry::new());
+        let dropped = Arc::new(AtomicBool::new(false));
+        let flag = DropFlag(dropped.clone());
+        // 先产生一个分块，然后永远不再产生数据
+        let inner = stream::once(async { 1 })
+            .chain(stream::pending())
+            .map(move |n| {
+                let _ = &flag;
+                n
+            });
+        let cancellation = StreamCancellation::register(&registry, Some("req-1"));
+        let mut stream = Box::pin(cancellable(inner, cancellation));
+
+        assert_eq!(stream.next().await, Some(1));
+
+        assert!(registry.cancel("req-1"));
+        let next = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .expect("cancellation should return promptly");
+        assert_eq!(next, None);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_targets_request_id() {
+        let registry = Arc::new(CancellationRegistry::new());
+        let mut first = Box::pin(cancellable(
+            stream::iter([1, 2]),
+            StreamCancellation::register(&registry, Some("req-1")),
+        ));
+        let mut second = Box::pin(cancellable(
+            stream::pending::<i32>(),
+            StreamCancellation::register(&registry, Some("req-2")),
+        ));
+
+        // 只取消第二个请求，第一个请求照常完成
+        assert!(registry.cancel("req-2"));
+        assert_eq!(second.next().await, None);
+        assert_eq!(first.next().await, Some(1));
+        assert_eq!(first.next().await, Some(2));
+        assert_eq!(first.next().await, None);
+
+        // 流结束后注销
+        drop(first);
+        assert!(!registry.cancel("req-1"));
+    }
+}
@@ -0,0 +1,248 @@
+// 提供商能力缓存
+//
+// 首次使用时探测一次提供商能力并缓存，之后的请求直接使用缓存结果
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::modules::chat::infrastructure::CacheCounters;
+use crate::modules::chat::ports::{CapabilityCacheUsage, LLMError, ProviderCapabilities};
+
+/// 探测失败后，在这段时间内直接使用默认能力，不再重新探测
+const FAILED_PROBE_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy)]
+enum CachedCapabilities {
+    Probed(ProviderCapabilities),
+    /// 探测失败，`retry_at` 之前使用默认能力
+    Failed {
+        retry_at: Instant,
+    },
+}
+
+/// 提供商能力缓存
+///
+/// 探测失败时使用保守的默认能力，并在一段时间内不再探测，避免每次请求都额外发送一次
+/// （可能计费的）探测请求。缓存跟随适配器实例，注册表在提供商配置变更时重建适配器，
+/// 缓存随之失效
+#[derive(Debug, Default)]
+pub struct CapabilityCache {
+    cached: Mutex<Option<CachedCapabilities>>,
+    counters: CacheCounters,
+}
+
+impl CapabilityCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用已知的能力创建缓存，不再探测
+    pub fn with_capabilities(capabilities: ProviderCapabilities) -> Self {
+        Self {
+            cached: Mutex::new(Some(CachedCapabilities::Probed(capabilities))),
+            counters: CacheCounters::new(),
+        }
+    }
+
+    /// 获取缓存的能力，尚未探测（或失败后已到重试时间）时执行 `probe`
+    ///
+    /// 探测期间不持有锁，并发的首次调用可能各自探测一次，以先成功的结果为准
+    pub async fn get_or_probe<F, Fut>(&self, probe: F) -> ProviderCapabilities
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ProviderCapabilities, LLMError>>,
+    {
+        let cached = match *self.cached.lock().await {
+            Some(CachedCapabilities::Probed(capabilities)) => Some(capabilities),
+            Some(CachedCapabilities::Failed { retry_at }) if Instant::now() < retry_at => {
+                Some(ProviderCapabilities::default())
+            }
+            _ => None,
+        };
+        if let Some(capabilities) = self.counters.record(cached) {
+            return capabilities;
+        }
+
+        let (entry, capabilities) = match probe().await {
+            Ok(capabilities) => (CachedCapabilities::Probed(capabilities), capabilities),
+            Err(e) => {
+                warn!("Capability probe failed, using defaults: {}", e);
+                let retry_at = Instant::now() + FAILED_PROBE_RETRY_AFTER;
+                (
+                    CachedCapabilities::Failed { retry_at },
+                    ProviderCapabilities::default(),
+                )
+            }
+        };
+
+        let mut cached = self.cached.lock().await;
+        if let Some(CachedCapabilities::Probed(capabilities)) = *cached {
+            return capabilities;
+        }
+        *cached = Some(entry);
+        capabilities
+    }
+
+    /// 获取已探测到的能力
+    pub async fn get(&self) -> Option<ProviderCapabilities> {
+        match *self.cached.lock().await {
+            Some(CachedCapabilities::Probed(capabilities)) => Some(capabilities),
+            _ => None,
+        }
+    }
+
+    /// 清除缓存（包括失败记录），下次使用时重新探测
+    pub async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+
+    /// 命中统计和当前是否有缓存
+    pub async fn usage(&self) -> CapabilityCacheUsage {
+        let cached = self.cached.lock().await.is_some();
+        let stats = self.counters.snapshot("capabilities", usize::from(cached));
+        CapabilityCacheUsage {
+            hits: stats.hits,
+            misses: stats.misses,
+            cached,
+        }
+    }
+
+    /// 清除缓存并重置统计
+    pub async fn clear(&self) {
+        self.invalidate().await;
+        self.counters.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 报告固定能力并记录探测次数的模拟提供商
+    struct ReportingProvider {
+        capabilities: Result<ProviderCapabilities, ()>,
+        probes: AtomicUsize,
+    }
+
+    impl ReportingProvider {
+        fn new(capabilities: Result<ProviderCapabilities, ()>) -> Self {
+            Self {
+                capabilities,
+                probes: AtomicUsize::new(0),
+            }
+        }
+
+        async fn probe(&self) -> Result<ProviderCapabilities, LLMError> {
+            self.probes.fetch_add(1, Ordering::SeqCst);
+            self.capabilities
+                .map_err(|_| LLMError::NetworkError("unreachable".to_string()))
+        }
+
+        fn probes(&self) -> usize {
+            self.probes.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probes_once_and_caches() {
+        let reported = ProviderCapabilities {
+            streaming: true,
+            stream_usage: true,
+        };
+        let provider = ReportingProvider::new(Ok(reported));
+        let cache = CapabilityCache::new();
+        assert_eq!(cache.get().await, None);
+
+        for _ in 0..3 {
+            assert_eq!(cache.get_or_probe(|| provider.probe()).await, reported);
+        }
+        assert_eq!(provider.probes(), 1);
+        assert_eq!(cache.get().await, Some(reported));
+        assert_eq!(
+            cache.usage().await,
+            CapabilityCacheUsage {
+                hits: 2,
+                misses: 1,
+                cached: true
+            }
+        );
+
+        // 失效后重新探测
+        cache.invalidate().await;
+        assert_eq!(cache.get_or_probe(|| provider.probe()).await, reported);
+        assert_eq!(provider.probes(), 2);
+
+        // 清除时一并重置统计
+        cache.clear().await;
+        assert_eq!(cache.usage().await, CapabilityCacheUsage::default());
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_is_cached_until_invalidated() {
+        let provider = ReportingProvider::new(Err(()));
+        let cache = CapabilityCache::new();
+
+        for _ in 0..3 {
+            assert_eq!(
+                cache.get_or_probe(|| provider.probe()).await,
+                ProviderCapabilities::default()
+            );
+        }
+        // 失败结果同样缓存，不会每次请求都重新探测
+        assert_eq!(provider.probes(), 1);
+        assert_eq!(cache.get().await, None);
+
+        cache.invalidate().await;
+        cache.get_or_probe(|| provider.probe()).await;
+        assert_eq!(provider.probes(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lock_is_not_held_while_probing() {
+        let cache = std::sync::Arc::new(CapabilityCache::new());
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let probing = cache.clone();
+        let probe = tokio::spawn(async move {
+            probing
+                .get_or_probe(|| async {
+                    let _ = started_tx.send(());
+                    let _ = release_rx.await;
+                    Ok(ProviderCapabilities {
+                        streaming: true,
+                        stream_usage: true,
+                    })
+                })
+                .await
+        });
+        started_rx.await.unwrap();
+
+        // 探测进行中时读取和清除缓存不会被阻塞
+        let get = tokio::time::timeout(Duration::from_secs(1), cache.get()).await;
+        assert_eq!(get.unwrap(), None);
+        tokio::time::timeout(Duration::from_secs(1), cache.invalidate())
+            .await
+            .unwrap();
+
+        release_tx.send(()).unwrap();
+        assert!(probe.await.unwrap().stream_usage);
+        assert!(cache.get().await.unwrap().stream_usage);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_cache_never_probes() {
+        let provider = ReportingProvider::new(Ok(ProviderCapabilities::default()));
+        let seeded = ProviderCapabilities {
+            streaming: true,
+            stream_usage: false,
+        };
+        let cache = CapabilityCache::with_capabilities(seeded);
+
+        assert_eq!(cache.get_or_probe(|| provider.probe()).await, seeded);
+        assert_eq!(provider.probes(), 0);
+    }
+}
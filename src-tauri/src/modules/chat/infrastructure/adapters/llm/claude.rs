@@ -3,16 +3,26 @@
 // 实现 Claude 的消息 API 适配器
 
 use async_trait::async_trait;
-use futures::stream::{self, Stream};
+use futures::Stream;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 
+use super::{build_http_client, chunk_stream, extra_header_map, retry_after_secs, with_retries};
+
 use crate::modules::chat::ports::{
-    CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMChatMessage, LLMError,
-    LLMPort, LLMProviderConfig, ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+    validate_anthropic_beta, validate_anthropic_version, CompletionRequest, CompletionResponse,
+    FinishReason, HealthStatus, LLMChatMessage, LLMError, LLMPort, LLMProviderConfig, ModelInfo,
+    ProviderInfo, ProviderType, StreamChunk, TokenUsage,
 };
 
+/// 429 响应未携带 retry-after 时的默认等待时间
+const DEFAULT_RATE_LIMIT_RETRY_SECS: u64 = 60;
+
+/// 529（过载）响应未携带 retry-after 时的默认等待时间
+const DEFAULT_OVERLOADED_RETRY_SECS: u64 = 30;
+
 /// Claude API 请求
 #[derive(Debug, Serialize)]
 struct ClaudeRequest {
@@ -22,14 +32,42 @@ struct ClaudeRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stop_sequences: Option<Vec<String>>,
+    /// 系统提示词，Claude 只接受顶层 system 参数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
     stream: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct ClaudeMessage {
     role: String,
-    content: String,
+    content: ClaudeContent,
+}
+
+/// 消息内容（带图片时使用内容块）
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum ClaudeContent {
+    Text(String),
+    Blocks(Vec<ClaudeContentBlock>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClaudeContentBlock {
+    Text { text: String },
+    Image { source: ClaudeImageSource },
+}
+
+#[derive(Debug, Serialize)]
+struct ClaudeImageSource {
+    #[serde(rename = "type")]
+    source_type: &'static str,
+    media_type: String,
+    data: String,
 }
 
 /// Claude API 响应
@@ -94,6 +132,9 @@ struct Delta {
     #[serde(rename = "type")]
     delta_type: String,
     text: Option<String>,
+    /// 扩展思考的推理内容（thinking_delta）
+    #[serde(default)]
+    thinking: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -106,34 +147,135 @@ struct MessageDelta {
 pub struct ClaudeAdapter {
     config: LLMProviderConfig,
     client: Client,
+    extra_headers: HeaderMap,
 }
 
 impl ClaudeAdapter {
     pub fn new(config: LLMProviderConfig) -> Result<Self, LLMError> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(config.timeout_secs))
-            .build()
-            .map_err(|e| LLMError::Unknown(e.to_string()))?;
+        validate_anthropic_version(&config.anthropic_version)?;
+        for feature in &config.anthropic_beta {
+            validate_anthropic_beta(feature)?;
+        }
+
+        let client = build_http_client(
+            Some(std::time::Duration::from_secs(config.timeout_secs)),
+            &config.http,
+            config.proxy_url.as_deref(),
+        )?;
+        let extra_headers = extra_header_map(&config.extra_headers)?;
+
+        Ok(Self {
+            config,
+            client,
+            extra_headers,
+        })
+    }
+
+    /// 构建带认证、版本和 beta 请求头的消息接口请求
+    fn post_messages(&self) -> reqwest::RequestBuilder {
+        let builder = self
+            .client
+            .post(format!("{}/messages", self.config.base_url))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", &self.config.anthropic_version)
+            .header("content-type", "application/json");
+
+        let builder = if self.config.anthropic_beta.is_empty() {
+            builder
+        } else {
+            builder.header("anthropic-beta", self.config.anthropic_beta.join(","))
+        };
+        builder.headers(self.extra_headers.clone())
+    }
+
+    /// 发送一次消息请求，非成功状态转换为对应错误
+    async fn send_messages(
+        &self,
+        claude_request: &ClaudeRequest,
+    ) -> Result<reqwest::Response, LLMError> {
+        let response = self
+            .post_messages()
+            .json(claude_request)
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
 
-        Ok(Self { config, client })
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        Ok(response)
     }
 
-    fn convert_messages(&self, messages: Vec<LLMChatMessage>) -> Vec<ClaudeMessage> {
-        messages
+    /// 转换消息，system 消息合并后单独返回
+    ///
+    /// Claude 不支持 system 消息出现在 messages 数组中，需通过顶层 system 参数传递
+    fn convert_messages(
+        &self,
+        messages: Vec<LLMChatMessage>,
+    ) -> (Option<String>, Vec<ClaudeMessage>) {
+        let (system, messages): (Vec<_>, Vec<_>) =
+            messages.into_iter().partition(|m| m.role == "system");
+        let system = system
+            .into_iter()
+            .map(|m| m.content)
+            .filter(|content| !content.is_empty())
+            .collect::<Vec<_>>();
+        let system = (!system.is_empty()).then(|| system.join("\n\n"));
+
+        let messages = messages
             .into_iter()
-            .filter(|m| m.role != "system") // Claude 不支持 system 消息在 messages 数组中
             .map(|m| ClaudeMessage {
                 role: if m.role == "assistant" {
                     "assistant".to_string()
                 } else {
                     "user".to_string()
                 },
-                content: m.content,
+                content: if m.images.is_empty() {
+                    ClaudeContent::Text(m.content)
+                } else {
+                    let mut blocks: Vec<ClaudeContentBlock> = m
+                        .images
+                        .into_iter()
+                        .map(|image| ClaudeContentBlock::Image {
+                            source: ClaudeImageSource {
+                                source_type: "base64",
+                                media_type: image.mime_type,
+                                data: image.data,
+                            },
+                        })
+                        .collect();
+                    blocks.push(ClaudeContentBlock::Text { text: m.content });
+                    ClaudeContent::Blocks(blocks)
+                },
             })
-            .collect()
+            .collect();
+
+        (system, messages)
     }
 
-    fn map_finish_reason(&self, reason: Option<String>) -> FinishReason {
+    /// 构建请求，设置了助手前缀时追加一条助手消息作为预填
+    fn build_request(&self, request: CompletionRequest, stream: bool) -> ClaudeRequest {
+        let (system, mut messages) = self.convert_messages(request.messages);
+        if let Some(prefix) = request.assistant_prefix {
+            messages.push(ClaudeMessage {
+                role: "assistant".to_string(),
+                content: ClaudeContent::Text(prefix),
+            });
+        }
+
+        ClaudeRequest {
+            model: request.model,
+            messages,
+            max_tokens: request.max_tokens.unwrap_or(4096),
+            temperature: request.temperature,
+            top_p: request.top_p,
+            stop_sequences: request.stop_sequences,
+            system,
+            stream,
+        }
+    }
+
+    fn map_finish_reason(reason: Option<String>) -> FinishReason {
         match reason.as_deref() {
             Some("end_turn") => FinishReason::Stop,
             Some("max_tokens") => FinishReason::Length,
@@ -141,6 +283,35 @@ impl ClaudeAdapter {
             _ => FinishReason::Stop,
         }
     }
+
+    /// 解析一行 SSE 流式响应，只关心 data: 行，event: 行和空行直接跳过
+    fn parse_stream_line(line: &str) -> Option<StreamChunk> {
+        let json_str = line.strip_prefix("data: ")?;
+        match serde_json::from_str::<ClaudeStreamEvent>(json_str).ok()? {
+            ClaudeStreamEvent::ContentBlockDelta { delta } => {
+                if delta.text.is_none() && delta.thinking.is_none() {
+                    return None;
+                }
+                Some(StreamChunk {
+                    content: delta.text.unwrap_or_default(),
+                    reasoning: delta.thinking,
+                    finish_reason: None,
+                    usage: None,
+                })
+            }
+            ClaudeStreamEvent::MessageDelta { delta, usage } => Some(StreamChunk {
+                content: String::new(),
+                reasoning: None,
+                finish_reason: delta.stop_reason.map(|r| Self::map_finish_reason(Some(r))),
+                usage: Some(TokenUsage {
+                    prompt_tokens: usage.input_tokens,
+                    completion_tokens: usage.output_tokens,
+                    total_tokens: usage.input_tokens + usage.output_tokens,
+                }),
+            }),
+            _ => None,
+        }
+    }
 }
 
 #[async_trait]
@@ -177,6 +348,9 @@ impl LLMPort for ClaudeAdapter {
                     supports_functions: true,
                 },
             ],
+            supports_cancellation: false,
+            supports_model_listing: false,
+            supports_assistant_prefix: true,
         }
     }
 
@@ -185,34 +359,14 @@ impl LLMPort for ClaudeAdapter {
     }
 
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
-        let claude_request = ClaudeRequest {
-            model: request.model.clone(),
-            messages: self.convert_messages(request.messages),
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            stop_sequences: request.stop_sequences,
-            stream: false,
-        };
+        self.provider_info().ensure_supports_images(&request)?;
+        request.ensure_text_response("Claude")?;
+        let claude_request = self.build_request(request, false);
 
-        let response = self
-            .client
-            .post(format!("{}/messages", self.config.base_url))
-            .header("x-api-key", &self.config.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&claude_request)
-            .send()
-            .await
-            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(LLMError::ApiError {
-                code: status.to_string(),
-                message: error_text,
-            });
-        }
+        let response = with_retries(self.config.max_retries, || {
+            self.send_messages(&claude_request)
+        })
+        .await?;
 
         let claude_response: ClaudeResponse = response
             .json()
@@ -228,7 +382,7 @@ impl LLMPort for ClaudeAdapter {
 
         Ok(CompletionResponse {
             content,
-            finish_reason: self.map_finish_reason(claude_response.stop_reason),
+            finish_reason: Self::map_finish_reason(claude_response.stop_reason),
             usage: TokenUsage {
                 prompt_tokens: claude_response.usage.input_tokens,
                 completion_tokens: claude_response.usage.output_tokens,
@@ -242,126 +396,24 @@ impl LLMPort for ClaudeAdapter {
         &self,
         request: CompletionRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
-        let claude_request = ClaudeRequest {
-            model: request.model.clone(),
-            messages: self.convert_messages(request.messages),
-            max_tokens: request.max_tokens.unwrap_or(4096),
-            temperature: request.temperature,
-            stop_sequences: request.stop_sequences,
-            stream: true,
-        };
-
-        let response = self
-            .client
-            .post(format!("{}/messages", self.config.base_url))
-            .header("x-api-key", &self.config.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&claude_request)
-            .send()
-            .await
-            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+        self.provider_info().ensure_supports_images(&request)?;
+        request.ensure_text_response("Claude")?;
+        let claude_request = self.build_request(request, true);
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(LLMError::ApiError {
-                code: status.to_string(),
-                message: error_text,
-            });
-        }
+        // 只重试建立连接，已开始输出后不再重试以免重复内容
+        let response = with_retries(self.config.max_retries, || {
+            self.send_messages(&claude_request)
+        })
+        .await?;
 
-        use futures::StreamExt;
-
-        let bytes_stream = response.bytes_stream();
-        let buffer = String::new();
-
-        let stream = stream::unfold(
-            (bytes_stream, buffer),
-            |(mut bytes_stream, mut buffer)| async move {
-                loop {
-                    match bytes_stream.next().await {
-                        Some(Ok(bytes)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&bytes));
-
-                            // 处理所有完整的 SSE 事件
-                            while let Some(pos) = buffer.find("\n\n") {
-                                let block = buffer[..pos].to_string();
-                                buffer.drain(..pos + 2);
-
-                                // 查找 data: 行
-                                for line in block.lines() {
-                                    if let Some(json_str) = line.strip_prefix("data: ") {
-                                        if let Ok(event) =
-                                            serde_json::from_str::<ClaudeStreamEvent>(json_str)
-                                        {
-                                            match event {
-                                                ClaudeStreamEvent::ContentBlockDelta { delta } => {
-                                                    if let Some(text) = delta.text {
-                                                        let chunk = StreamChunk {
-                                                            content: text,
-                                                            finish_reason: None,
-                                                            usage: None,
-                                                        };
-                                                        return Some((
-                                                            Ok(chunk),
-                                                            (bytes_stream, buffer),
-                                                        ));
-                                                    }
-                                                }
-                                                ClaudeStreamEvent::MessageDelta {
-                                                    delta,
-                                                    usage,
-                                                } => {
-                                                    let finish = delta.stop_reason.map(|r| {
-                                                        if r == "end_turn" {
-                                                            FinishReason::Stop
-                                                        } else if r == "max_tokens" {
-                                                            FinishReason::Length
-                                                        } else {
-                                                            FinishReason::Stop
-                                                        }
-                                                    });
-                                                    let chunk = StreamChunk {
-                                                        content: String::new(),
-                                                        finish_reason: finish,
-                                                        usage: Some(TokenUsage {
-                                                            prompt_tokens: usage.input_tokens,
-                                                            completion_tokens: usage.output_tokens,
-                                                            total_tokens: usage.input_tokens
-                                                                + usage.output_tokens,
-                                                        }),
-                                                    };
-                                                    return Some((
-                                                        Ok(chunk),
-                                                        (bytes_stream, buffer),
-                                                    ));
-                                                }
-                                                _ => {}
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Some((
-                                Err(LLMError::NetworkError(e.to_string())),
-                                (bytes_stream, buffer),
-                            ));
-                        }
-                        None => return None,
-                    }
-                }
-            },
-        );
+        let stream = chunk_stream(response.bytes_stream(), Self::parse_stream_line);
 
         Ok(Box::pin(stream))
     }
 
     async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
         // Claude API 不支持取消请求
-        Ok(())
+        Err(LLMError::Unsupported("cancel"))
     }
 
     async fn health_check(&self) -> Result<HealthStatus, LLMError> {
@@ -372,24 +424,17 @@ impl LLMPort for ClaudeAdapter {
             model: self.config.default_model.clone(),
             messages: vec![ClaudeMessage {
                 role: "user".to_string(),
-                content: "Hi".to_string(),
+                content: ClaudeContent::Text("Hi".to_string()),
             }],
             max_tokens: 1,
             temperature: None,
+            top_p: None,
             stop_sequences: None,
+            system: None,
             stream: false,
         };
 
-        match self
-            .client
-            .post(format!("{}/messages", self.config.base_url))
-            .header("x-api-key", &self.config.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&test_request)
-            .send()
-            .await
-        {
+        match self.post_messages().json(&test_request).send().await {
             Ok(response) => {
                 let latency = start.elapsed().as_millis() as u64;
                 if response.status().is_success() {
@@ -414,3 +459,290 @@ impl LLMPort for ClaudeAdapter {
         }
     }
 }
+
+/// 将非成功响应映射为 LLMError
+///
+/// 429（限流）和 529（过载）映射为可重试错误，优先使用 retry-after 头给出的等待时间
+async fn error_from_response(response: reqwest::Response) -> LLMError {
+    let status = response.status();
+    let retry_after = retry_after_secs(&response);
+    let error_text = response.text().await.unwrap_or_default();
+
+    match status.as_u16() {
+        429 => LLMError::RateLimitError {
+            retry_after_secs: retry_after.unwrap_or(DEFAULT_RATE_LIMIT_RETRY_SECS),
+        },
+        529 => LLMError::Overloaded {
+            retry_after_secs: retry_after.unwrap_or(DEFAULT_OVERLOADED_RETRY_SECS),
+        },
+        _ => LLMError::ApiError {
+            code: status.to_string(),
+            message: error_text,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::infrastructure::adapters::llm::{MockHttpResponse, MockHttpServer};
+    use crate::modules::chat::ports::ImageAttachment;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_capabilities() {
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            ..Default::default()
+        })
+        .unwrap();
+        let info = adapter.provider_info();
+
+        assert!(!info.supports_cancellation);
+        assert!(!info.supports_model_listing);
+        assert!(matches!(
+            adapter.cancel("req-1").await,
+            Err(LLMError::Unsupported("cancel"))
+        ));
+    }
+
+    #[test]
+    fn test_assistant_prefix_appends_prefill_message() {
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            ..Default::default()
+        })
+        .unwrap();
+        let request = CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+                images: Vec::new(),
+            }],
+            "claude-3-5-sonnet-20241022",
+        )
+        .with_assistant_prefix("Kizuna:");
+
+        let body = serde_json::to_value(adapter.build_request(request, true)).unwrap();
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "Kizuna:");
+    }
+
+    #[test]
+    fn test_system_messages_become_top_level_system() {
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            ..Default::default()
+        })
+        .unwrap();
+        let message = |role: &str, content: &str| LLMChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            images: Vec::new(),
+        };
+        let request = CompletionRequest::new(
+            vec![
+                message("system", "You are Kizuna."),
+                message("system", "Reply briefly."),
+                message("user", "Hi"),
+            ],
+            "claude-3-5-sonnet-20241022",
+        );
+
+        let body = serde_json::to_value(adapter.build_request(request, false)).unwrap();
+        let messages = body["messages"].as_array().unwrap();
+
+        assert_eq!(body["system"], "You are Kizuna.\n\nReply briefly.");
+        assert_eq!(messages.len(), 1);
+        assert!(messages.iter().all(|m| m["role"] != "system"));
+
+        // 没有 system 消息时不发送 system 字段
+        let request =
+            CompletionRequest::new(vec![message("user", "Hi")], "claude-3-5-sonnet-20241022");
+        let body = serde_json::to_value(adapter.build_request(request, true)).unwrap();
+        assert!(body.get("system").is_none());
+    }
+
+    #[test]
+    fn test_image_message_serialized_as_content_blocks() {
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            ..Default::default()
+        })
+        .unwrap();
+        let request = CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "What is this?".to_string(),
+                images: vec![ImageAttachment {
+                    mime_type: "image/png".to_string(),
+                    data: "aGVsbG8=".to_string(),
+                }],
+            }],
+            "claude-3-5-sonnet-20241022",
+        );
+
+        let body = serde_json::to_value(adapter.build_request(request, false)).unwrap();
+        assert_eq!(
+            body["messages"][0]["content"],
+            serde_json::json!([
+                {
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": "image/png", "data": "aGVsbG8=" }
+                },
+                { "type": "text", "text": "What is this?" }
+            ])
+        );
+    }
+
+    /// 不重试的适配器，便于直接观察错误
+    fn adapter_for(base_url: String) -> ClaudeAdapter {
+        ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            base_url,
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn hello_request() -> CompletionRequest {
+        CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+                images: Vec::new(),
+            }],
+            "claude-3-5-sonnet-20241022",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_configured_version_and_beta_headers_are_sent() {
+        const BODY: &str = r#"{"id":"msg_1","content":[{"type":"text","text":"Hi"}],"stop_reason":"end_turn","usage":{"input_tokens":1,"output_tokens":1}}"#;
+        let server = MockHttpServer::fixed("200 OK", BODY).await;
+
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            base_url: server.url(),
+            anthropic_version: "2024-10-22".to_string(),
+            anthropic_beta: vec![
+                "prompt-caching-2024-07-31".to_string(),
+                "pdfs-2024-09-25".to_string(),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        adapter.complete(hello_request()).await.unwrap();
+
+        let head = &server.requests()[0].head;
+        assert!(head.contains("anthropic-version: 2024-10-22\r\n"));
+        assert!(head.contains("anthropic-beta: prompt-caching-2024-07-31,pdfs-2024-09-25\r\n"));
+    }
+
+    #[test]
+    fn test_invalid_version_or_beta_is_rejected() {
+        for (version, beta) in [
+            ("2023-6-1", vec![]),
+            ("latest", vec![]),
+            ("2023-02-30", vec![]),
+            ("2023-06-01", vec!["".to_string()]),
+            ("2023-06-01", vec!["bad feature".to_string()]),
+        ] {
+            let result = ClaudeAdapter::new(LLMProviderConfig {
+                provider_type: ProviderType::Claude,
+                anthropic_version: version.to_string(),
+                anthropic_beta: beta,
+                ..Default::default()
+            });
+            assert!(matches!(result, Err(LLMError::InvalidRequest(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_overloaded_is_retryable() {
+        const BODY: &str =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let server = MockHttpServer::fixed("529 Site Overloaded", BODY).await;
+        let adapter = adapter_for(server.url());
+
+        let result = adapter.complete(hello_request()).await;
+        assert!(matches!(
+            result,
+            Err(LLMError::Overloaded {
+                retry_after_secs: DEFAULT_OVERLOADED_RETRY_SECS
+            })
+        ));
+
+        let result = adapter.complete_stream(hello_request()).await;
+        assert!(matches!(result, Err(LLMError::Overloaded { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_overloaded_request_is_retried_until_success() {
+        const OVERLOADED: &str =
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        const SUCCESS: &str = r#"{"id":"msg_1","content":[{"type":"text","text":"Hi"}],"stop_reason":"end_turn","usage":{"input_tokens":1,"output_tokens":1}}"#;
+        let overloaded = MockHttpResponse::new("529 Site Overloaded", OVERLOADED)
+            .with_header("retry-after", "0");
+        let server =
+            MockHttpServer::start(vec![overloaded.clone(), MockHttpResponse::ok(SUCCESS)]).await;
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            base_url: server.url(),
+            max_retries: 1,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = adapter.complete(hello_request()).await.unwrap();
+        assert_eq!(response.content, "Hi");
+        assert_eq!(server.request_count(), 2);
+
+        // 流式请求同样重试建立连接
+        let server = MockHttpServer::start(vec![
+            overloaded,
+            MockHttpResponse::ok(
+                "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n",
+            ),
+        ])
+        .await;
+        let adapter = ClaudeAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Claude,
+            base_url: server.url(),
+            max_retries: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let chunks: Vec<_> = adapter
+            .complete_stream(hello_request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].as_ref().unwrap().content, "Hi");
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_honors_retry_after() {
+        let server =
+            MockHttpServer::start(vec![
+                MockHttpResponse::new("429 Too Many Requests", "").with_header("retry-after", "7")
+            ])
+            .await;
+        let adapter = adapter_for(server.url());
+
+        let result = adapter.complete(hello_request()).await;
+        assert!(matches!(
+            result,
+            Err(LLMError::RateLimitError {
+                retry_after_secs: 7
+            })
+        ));
+    }
+}
@@ -0,0 +1,460 @@
+// Streaming Conformance - 流式适配器一致性测试
+//
+// 所有流式适配器共享同一组场景：被网络分块切断的事件重组、结束原因、
+// 截断的响应体、连接失败以及取消/断开。新增适配器只需实现 StreamFixture
+// 并通过 conformance_suite! 注册
+
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use super::{
+    read_request, BaseOpenAICompatibleAdapter, ClaudeAdapter, GeminiAdapter, OllamaAdapter,
+    OpenAIAdapter, OpenAICompatibleConfig,
+};
+use crate::modules::chat::ports::{
+    CompletionRequest, FinishReason, HttpClientOptions, LLMChatMessage, LLMError, LLMPort,
+    LLMProviderConfig, ProviderCapabilities, ProviderType, StreamChunk,
+};
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>;
+
+/// 每次写入的字节数，刻意选得很小以切断事件和多字节字符
+const SPLIT_SIZE: usize = 5;
+/// 等待断开或取消生效的最长时间
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 适配器的流式协议描述
+trait StreamFixture {
+    /// 创建指向给定地址的适配器
+    fn adapter(base_url: String) -> Arc<dyn LLMPort>;
+    /// 流式响应的 Content-Type
+    fn content_type() -> &'static str;
+    /// 编码一个内容增量事件
+    fn delta(text: &str) -> String;
+    /// 编码以 stop 结束的终止事件
+    fn finish() -> String;
+}
+
+struct OpenAIFixture;
+
+impl StreamFixture for OpenAIFixture {
+    fn adapter(base_url: String) -> Arc<dyn LLMPort> {
+        Arc::new(
+            // 预置能力并关闭重试，避免额外请求占用唯一的模拟连接
+            OpenAIAdapter::new(LLMProviderConfig {
+                base_url,
+                max_retries: 0,
+                ..Default::default()
+            })
+            .unwrap()
+            .with_capabilities(ProviderCapabilities::default()),
+        )
+    }
+
+    fn content_type() -> &'static str {
+        "text/event-stream"
+    }
+
+    fn delta(text: &str) -> String {
+        let event = serde_json::json!({ "choices": [{ "delta": { "content": text } }] });
+        format!("data: {}\n\n", event)
+    }
+
+    fn finish() -> String {
+        let event = serde_json::json!({ "choices": [{ "delta": {}, "finish_reason": "stop" }] });
+        format!("data: {}\n\ndata: [DONE]\n\n", event)
+    }
+}
+
+struct BaseCompatibleFixture;
+
+impl StreamFixture for BaseCompatibleFixture {
+    fn adapter(base_url: String) -> Arc<dyn LLMPort> {
+        Arc::new(
+            BaseOpenAICompatibleAdapter::new(OpenAICompatibleConfig {
+                provider_id: "compat".to_string(),
+                provider_name: "Compatible".to_string(),
+                provider_type: ProviderType::Custom,
+                base_url,
+                api_key: String::new(),
+                model: "test-model".to_string(),
+                timeout_secs: 30,
+                http: HttpClientOptions::default(),
+                proxy_url: None,
+                extra_headers: HashMap::new(),
+            })
+            .unwrap(),
+        )
+    }
+
+    fn content_type() -> &'static str {
+        OpenAIFixture::content_type()
+    }
+
+    fn delta(text: &str) -> String {
+        OpenAIFixture::delta(text)
+    }
+
+    fn finish() -> String {
+        OpenAIFixture::finish()
+    }
+}
+
+struct OllamaFixture;
+
+impl StreamFixture for OllamaFixture {
+    fn adapter(base_url: String) -> Arc<dyn LLMPort> {
+        Arc::new(
+            OllamaAdapter::new(LLMProviderConfig {
+                provider_type: ProviderType::Ollama,
+                base_url,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn content_type() -> &'static str {
+        "application/x-ndjson"
+    }
+
+    fn delta(text: &str) -> String {
+        let event = serde_json::json!({
+            "message": { "role": "assistant", "content": text },
+            "done": false
+        });
+        format!("{}\n", event)
+    }
+
+    fn finish() -> String {
+        let event = serde_json::json!({
+            "message": { "role": "assistant", "content": "" },
+            "done": true,
+            "prompt_eval_count": 3,
+            "eval_count": 5
+        });
+        format!("{}\n", event)
+    }
+}
+
+struct ClaudeFixture;
+
+impl StreamFixture for ClaudeFixture {
+    fn adapter(base_url: String) -> Arc<dyn LLMPort> {
+        Arc::new(
+            ClaudeAdapter::new(LLMProviderConfig {
+                provider_type: ProviderType::Claude,
+                base_url,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn content_type() -> &'static str {
+        "text/event-stream"
+    }
+
+    fn delta(text: &str) -> String {
+        let event = serde_json::json!({
+            "type": "content_block_delta",
+            "delta": { "type": "text_delta", "text": text }
+        });
+        format!("event: content_block_delta\ndata: {}\n\n", event)
+    }
+
+    fn finish() -> String {
+        let delta = serde_json::json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": "end_turn" },
+            "usage": { "input_tokens": 3, "output_tokens": 5 }
+        });
+        format!(
+            "event: message_delta\ndata: {}\n\nevent: message_stop\ndata: {{\"type\":\"message_stop\"}}\n\n",
+            delta
+        )
+    }
+}
+
+struct GeminiFixture;
+
+impl StreamFixture for GeminiFixture {
+    fn adapter(base_url: String) -> Arc<dyn LLMPort> {
+        Arc::new(
+            GeminiAdapter::new(LLMProviderConfig {
+                provider_type: ProviderType::Gemini,
+                base_url,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    }
+
+    fn content_type() -> &'static str {
+        "text/event-stream"
+    }
+
+    fn delta(text: &str) -> String {
+        let event = serde_json::json!({
+            "candidates": [{ "content": { "role": "model", "parts": [{ "text": text }] } }]
+        });
+        format!("data: {}\r\n\r\n", event)
+    }
+
+    fn finish() -> String {
+        let event = serde_json::json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "" }] },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": { "promptTokenCount": 3, "candidatesTokenCount": 5, "totalTokenCount": 8 }
+        });
+        format!("data: {}\r\n\r\n", event)
+    }
+}
+
+/// 模拟服务器的响应脚本
+pub(super) struct MockResponse {
+    content_type: &'static str,
+    /// 依次写出的响应体片段
+    parts: Vec<Vec<u8>>,
+    /// 声明的 Content-Length，大于实际长度时响应体不完整
+    declared_len: usize,
+    /// 写完片段后是否立即关闭连接
+    hang_up: bool,
+}
+
+impl MockResponse {
+    pub(super) fn split(content_type: &'static str, body: &str) -> Self {
+        Self {
+            content_type,
+            parts: body
+                .as_bytes()
+                .chunks(SPLIT_SIZE)
+                .map(<[u8]>::to_vec)
+                .collect(),
+            declared_len: body.len(),
+            hang_up: false,
+        }
+    }
+
+    /// 发送部分响应体后断开连接
+    fn truncated(content_type: &'static str, body: &str) -> Self {
+        Self {
+            content_type,
+            parts: vec![body.as_bytes().to_vec()],
+            declared_len: body.len() + 64,
+            hang_up: true,
+        }
+    }
+
+    /// 发送部分响应体后保持连接，不再发送数据
+    fn stalled(content_type: &'static str, body: &str) -> Self {
+        Self {
+            hang_up: false,
+            ..Self::truncated(content_type, body)
+        }
+    }
+}
+
+/// 启动只服务一个连接的服务器，返回地址和"客户端已断开"信号
+pub(super) async fn serve(response: MockResponse) -> (String, oneshot::Receiver<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (disconnected_tx, disconnected_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let Ok((mut socket, _)) = listener.accept().await else {
+            return;
+        };
+        read_request(&mut socket).await;
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            response.content_type, response.declared_len
+        );
+        if socket.write_all(head.as_bytes()).await.is_err() {
+            return;
+        }
+        for part in response.parts {
+            if socket.write_all(&part).await.is_err() {
+                break;
+            }
+            let _ = socket.flush().await;
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+
+        if response.hang_up {
+            return;
+        }
+
+        // 保持连接直到客户端关闭
+        let mut buf = [0u8; 1024];
+        while matches!(socket.read(&mut buf).await, Ok(n) if n > 0) {}
+        let _ = disconnected_tx.send(());
+    });
+
+    (format!("http://{}", addr), disconnected_rx)
+}
+
+pub(super) fn hello_request() -> CompletionRequest {
+    CompletionRequest::new(
+        vec![LLMChatMessage {
+            role: "user".to_string(),
+            content: "Hi".to_string(),
+            images: Vec::new(),
+        }],
+        "test-model",
+    )
+}
+
+async fn open_stream<F: StreamFixture>(
+    response: MockResponse,
+) -> (ChunkStream, oneshot::Receiver<()>) {
+    let (base_url, disconnected) = serve(response).await;
+    let stream = F::adapter(base_url)
+        .complete_stream(hello_request())
+        .await
+        .expect("stream should open");
+    (stream, disconnected)
+}
+
+/// 事件在任意字节处被切断（包括多字节字符中间）时仍能完整重组
+async fn split_events_are_reassembled<F: StreamFixture>() {
+    let body = [F::delta("Hel"), F::delta("lo, 你好"), F::finish()].concat();
+    let (stream, _) = open_stream::<F>(MockResponse::split(F::content_type(), &body)).await;
+
+    let chunks: Vec<StreamChunk> = stream
+        .map(|chunk| chunk.expect("no chunk should fail"))
+        .collect()
+        .await;
+
+    let content: String = chunks.iter().map(|c| c.content.as_str()).collect();
+    assert_eq!(content, "Hello, 你好");
+    assert_eq!(
+        chunks.iter().filter_map(|c| c.finish_reason).next_back(),
+        Some(FinishReason::Stop)
+    );
+}
+
+/// 终止事件之后流正常结束，不产生多余分块
+async fn terminal_event_ends_stream<F: StreamFixture>() {
+    let body = [F::delta("Hi"), F::finish()].concat();
+    let (mut stream, _) = open_stream::<F>(MockResponse::split(F::content_type(), &body)).await;
+
+    let mut finished = false;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.expect("no chunk should fail");
+        assert!(!finished, "no chunks expected after the finish reason");
+        finished = chunk.finish_reason.is_some();
+    }
+    assert!(finished);
+}
+
+/// 响应体在中途断开时报告网络错误，而不是静默结束
+async fn truncated_body_reports_network_error<F: StreamFixture>() {
+    let body = F::delta("Hel");
+    let (stream, _) = open_stream::<F>(MockResponse::truncated(F::content_type(), &body)).await;
+
+    let results: Vec<_> = stream.collect().await;
+    assert!(
+        matches!(results.last(), Some(Err(LLMError::NetworkError(_)))),
+        "expected a trailing network error, got {:?}",
+        results
+    );
+}
+
+/// 无法连接时 complete_stream 直接返回网络错误
+async fn connection_refused_reports_network_error<F: StreamFixture>() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let result = F::adapter(format!("http://{}", addr))
+        .complete_stream(hello_request())
+        .await;
+    assert!(matches!(result, Err(LLMError::NetworkError(_))));
+}
+
+/// 取消（若支持）会立即结束对应请求的空闲流并关闭连接，否则丢弃流会关闭底层连接
+async fn cancel_and_drop_release_connection<F: StreamFixture>() {
+    let body = F::delta("Hi");
+    let (base_url, disconnected) = serve(MockResponse::stalled(F::content_type(), &body)).await;
+    let adapter = F::adapter(base_url);
+    let mut stream = adapter
+        .complete_stream(hello_request().with_request_id("request"))
+        .await
+        .unwrap();
+
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.content, "Hi");
+
+    if adapter.provider_info().supports_cancellation {
+        // 取消其他请求不影响这个流
+        adapter.cancel("other-request").await.unwrap();
+        adapter.cancel("request").await.unwrap();
+        let next = tokio::time::timeout(DISCONNECT_TIMEOUT, stream.next())
+            .await
+            .expect("cancelled stream should end promptly");
+        assert!(next.is_none());
+
+        // 连接在流被丢弃之前就已关闭
+        tokio::time::timeout(DISCONNECT_TIMEOUT, disconnected)
+            .await
+            .expect("cancelling should close the connection")
+            .unwrap();
+        return;
+    }
+
+    drop(stream);
+    tokio::time::timeout(DISCONNECT_TIMEOUT, disconnected)
+        .await
+        .expect("dropping the stream should close the connection")
+        .unwrap();
+}
+
+/// 为一个适配器生成整套一致性测试
+macro_rules! conformance_suite {
+    ($name:ident, $fixture:ty) => {
+        mod $name {
+            use super::*;
+
+            #[tokio::test]
+            async fn test_split_events_are_reassembled() {
+                split_events_are_reassembled::<$fixture>().await;
+            }
+
+            #[tokio::test]
+            async fn test_terminal_event_ends_stream() {
+                terminal_event_ends_stream::<$fixture>().await;
+            }
+
+            #[tokio::test]
+            async fn test_truncated_body_reports_network_error() {
+                truncated_body_reports_network_error::<$fixture>().await;
+            }
+
+            #[tokio::test]
+            async fn test_connection_refused_reports_network_error() {
+                connection_refused_reports_network_error::<$fixture>().await;
+            }
+
+            #[tokio::test]
+            async fn test_cancel_and_drop_release_connection() {
+                cancel_and_drop_release_connection::<$fixture>().await;
+            }
+        }
+    };
+}
+
+conformance_suite!(openai, OpenAIFixture);
+conformance_suite!(base_compatible, BaseCompatibleFixture);
+conformance_suite!(ollama, OllamaFixture);
+conformance_suite!(claude, ClaudeFixture);
+conformance_suite!(gemini, GeminiFixture);
@@ -0,0 +1,657 @@
+// Gemini Adapter - Google Gemini generateContent API
+//
+// 实现 Gemini 的 generateContent / streamGenerateContent 适配器，
+// base_url 形如 https://generativelanguage.googleapis.com/v1beta。
+// 流式请求使用 alt=sse，每个 data 事件是一个完整的 GenerateContentResponse
+
+use async_trait::async_trait;
+use futures::Stream;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+use super::{
+    build_http_client, cancellable, extra_header_map, retry_after_secs, try_chunk_stream,
+    StreamCancellation,
+};
+
+use crate::infrastructure::CancellationRegistry;
+use crate::modules::chat::ports::{
+    CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMChatMessage, LLMError,
+    LLMPort, LLMProviderConfig, ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+};
+
+/// 429 响应未携带 retry-after 时的默认等待时间
+const DEFAULT_RATE_LIMIT_RETRY_SECS: u64 = 60;
+
+/// Gemini API 请求
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "GenerationConfig::is_empty")]
+    generation_config: GenerationConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiContent {
+    /// `user` 或 `model`
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inline_data: Option<GeminiInlineData>,
+    /// 思考模型的推理内容
+    #[serde(default, skip_serializing)]
+    thought: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+}
+
+impl GenerationConfig {
+    fn is_empty(&self) -> bool {
+        self.temperature.is_none()
+            && self.top_p.is_none()
+            && self.max_output_tokens.is_none()
+            && self.stop_sequences.is_none()
+    }
+}
+
+/// Gemini API 响应（流式响应的每个事件也是同样的结构）
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: Option<GeminiUsage>,
+    /// 提示词被拦截时没有候选结果，只有拦截原因
+    #[serde(default)]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    #[serde(default)]
+    content: Option<GeminiContent>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    #[serde(default)]
+    block_reason: Option<String>,
+}
+
+/// Gemini 错误响应
+#[derive(Debug, Deserialize)]
+struct GeminiErrorResponse {
+    error: GeminiError,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiError {
+    #[serde(default)]
+    code: u16,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    status: String,
+}
+
+impl GeminiResponse {
+    /// 第一个候选结果中的回答和推理内容
+    fn texts(&self) -> (String, Option<String>) {
+        let mut content = String::new();
+        let mut reasoning: Option<String> = None;
+        let parts = self
+            .candidates
+            .first()
+            .and_then(|c| c.content.as_ref())
+            .map(|c| c.parts.as_slice())
+            .unwrap_or_default();
+        for part in parts {
+            let Some(text) = &part.text else { continue };
+            if part.thought {
+                reasoning.get_or_insert_with(String::new).push_str(text);
+            } else {
+                content.push_str(text);
+            }
+        }
+        (content, reasoning)
+    }
+
+    /// 结束原因；提示词被拦截时视为内容过滤
+    fn finish_reason(&self) -> Option<FinishReason> {
+        let reason = self
+            .candidates
+            .first()
+            .and_then(|c| c.finish_reason.as_deref());
+        match reason {
+            Some(reason) => Some(GeminiAdapter::map_finish_reason(reason)),
+            None => self
+                .prompt_feedback
+                .as_ref()
+                .and_then(|f| f.block_reason.as_ref())
+                .map(|_| FinishReason::ContentFilter),
+        }
+    }
+
+    fn usage(&self) -> Option<TokenUsage> {
+        self.usage_metadata.as_ref().map(|usage| TokenUsage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.total_token_count,
+        })
+    }
+}
+
+/// Gemini 适配器
+pub struct GeminiAdapter {
+    config: LLMProviderConfig,
+    client: Client,
+    extra_headers: HeaderMap,
+    /// 进行中的流式请求，按请求 ID 取消
+    cancellations: Arc<CancellationRegistry>,
+}
+
+impl GeminiAdapter {
+    pub fn new(config: LLMProviderConfig) -> Result<Self, LLMError> {
+        let client = build_http_client(
+            Some(Duration::from_secs(config.timeout_secs)),
+            &config.http,
+            config.proxy_url.as_deref(),
+        )?;
+        let extra_headers = extra_header_map(&config.extra_headers)?;
+
+        Ok(Self {
+            config,
+            client,
+            extra_headers,
+            cancellations: Arc::new(CancellationRegistry::new()),
+        })
+    }
+
+    /// 构建指定模型方法的请求，例如 `generateContent`
+    fn post_model(&self, model: &str, method: &str) -> reqwest::RequestBuilder {
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        self.client
+            .post(format!(
+                "{}/models/{}:{}",
+                self.config.base_url.trim_end_matches('/'),
+                model,
+                method
+            ))
+            .header("x-goog-api-key", &self.config.api_key)
+            .header("content-type", "application/json")
+            .headers(self.extra_headers.clone())
+    }
+
+    /// 转换消息：assistant 映射为 model，系统提示合并到第一条用户消息之前，
+    /// 相邻的同角色消息合并为一轮
+    fn convert_messages(messages: Vec<LLMChatMessage>) -> Vec<GeminiContent> {
+        let (system, messages): (Vec<_>, Vec<_>) =
+            messages.into_iter().partition(|m| m.role == "system");
+        let system_prompt = system
+            .into_iter()
+            .map(|m| m.content)
+            .filter(|c| !c.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut contents: Vec<GeminiContent> = Vec::new();
+        for message in messages {
+            let role = if message.role == "assistant" {
+                "model"
+            } else {
+                "user"
+            };
+            let mut parts: Vec<GeminiPart> = message
+                .images
+                .into_iter()
+                .map(|image| GeminiPart {
+                    inline_data: Some(GeminiInlineData {
+                        mime_type: image.mime_type,
+                        data: image.data,
+                    }),
+                    ..Default::default()
+                })
+                .collect();
+            parts.push(GeminiPart {
+                text: Some(message.content),
+                ..Default::default()
+            });
+
+            match contents.last_mut() {
+                Some(last) if last.role == role => last.parts.extend(parts),
+                _ => contents.push(GeminiContent {
+                    role: role.to_string(),
+                    parts,
+                }),
+            }
+        }
+
+        if !system_prompt.is_empty() {
+            match contents.iter_mut().find(|c| c.role == "user") {
+                Some(first_user) => {
+                    let text_part = first_user.parts.iter_mut().find(|p| p.text.is_some());
+                    if let Some(text) = text_part.and_then(|p| p.text.as_mut()) {
+                        *text = format!("{}\n\n{}", system_prompt, text);
+                    }
+                }
+                None => contents.insert(
+                    0,
+                    GeminiContent {
+                        role: "user".to_string(),
+                        parts: vec![GeminiPart {
+                            text: Some(system_prompt),
+                            ..Default::default()
+                        }],
+                    },
+                ),
+            }
+        }
+
+        contents
+    }
+
+    fn build_request(request: CompletionRequest) -> (String, GeminiRequest) {
+        let gemini_request = GeminiRequest {
+            contents: Self::convert_messages(request.messages),
+            generation_config: GenerationConfig {
+                temperature: request.temperature,
+                top_p: request.top_p,
+                max_output_tokens: request.max_tokens,
+                stop_sequences: request.stop_sequences,
+            },
+        };
+        (request.model, gemini_request)
+    }
+
+    fn map_finish_reason(reason: &str) -> FinishReason {
+        match reason {
+            "MAX_TOKENS" => FinishReason::Length,
+            "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII"
+            | "IMAGE_SAFETY" => FinishReason::ContentFilter,
+            _ => FinishReason::Stop,
+        }
+    }
+
+    /// 解析一行 SSE 流式响应；流中的错误对象会结束流
+    fn parse_stream_line(line: &str) -> Option<Result<StreamChunk, LLMError>> {
+        let data = line.strip_prefix("data:")?.trim_start();
+        if let Ok(error) = serde_json::from_str::<GeminiErrorResponse>(data) {
+            return Some(Err(Self::map_error_body(error.error)));
+        }
+        let response: GeminiResponse = serde_json::from_str(data).ok()?;
+
+        let (content, reasoning) = response.texts();
+        let finish_reason = response.finish_reason();
+        // 用量随每个事件累计返回，只在最后一个事件上报
+        let usage = finish_reason.and(response.usage());
+        if content.is_empty() && reasoning.is_none() && finish_reason.is_none() {
+            return None;
+        }
+
+        Some(Ok(StreamChunk {
+            content,
+            reasoning,
+            finish_reason,
+            usage,
+        }))
+    }
+
+    fn map_error_body(error: GeminiError) -> LLMError {
+        match error.code {
+            401 | 403 => LLMError::AuthenticationError(error.message),
+            429 => LLMError::RateLimitError {
+                retry_after_secs: DEFAULT_RATE_LIMIT_RETRY_SECS,
+            },
+            _ => LLMError::ApiError {
+                code: if error.status.is_empty() {
+                    error.code.to_string()
+                } else {
+                    error.status
+                },
+                message: error.message,
+            },
+        }
+    }
+
+    /// 将非成功响应映射为 LLMError
+    async fn error_from_response(response: reqwest::Response) -> LLMError {
+        let status = response.status();
+        let retry_after = retry_after_secs(&response);
+        let body = response.text().await.unwrap_or_default();
+        error!("Gemini API error: {} - {}", status, body);
+
+        if status.as_u16() == 429 {
+            return LLMError::RateLimitError {
+                retry_after_secs: retry_after.unwrap_or(DEFAULT_RATE_LIMIT_RETRY_SECS),
+            };
+        }
+        match serde_json::from_str::<GeminiErrorResponse>(&body) {
+            Ok(parsed) => Self::map_error_body(GeminiError {
+                code: status.as_u16(),
+                ..parsed.error
+            }),
+            Err(_) => LLMError::ApiError {
+                code: status.to_string(),
+                message: body,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl LLMPort for GeminiAdapter {
+    fn provider_id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn provider_info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.config.id.clone(),
+            name: self.config.name.clone(),
+            provider_type: ProviderType::Gemini,
+            models: vec![
+                ModelInfo {
+                    id: "gemini-2.5-pro".to_string(),
+                    name: "Gemini 2.5 Pro".to_string(),
+                    context_length: 1048576,
+                    supports_vision: true,
+                    supports_functions: true,
+                },
+                ModelInfo {
+                    id: "gemini-2.5-flash".to_string(),
+                    name: "Gemini 2.5 Flash".to_string(),
+                    context_length: 1048576,
+                    supports_vision: true,
+                    supports_functions: true,
+                },
+                ModelInfo {
+                    id: "gemini-2.0-flash".to_string(),
+                    name: "Gemini 2.0 Flash".to_string(),
+                    context_length: 1048576,
+                    supports_vision: true,
+                    supports_functions: true,
+                },
+            ],
+            supports_cancellation: true,
+            supports_model_listing: false,
+            supports_assistant_prefix: false,
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, LLMError> {
+        Ok(self.provider_info().models)
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
+        request.ensure_text_response("Gemini")?;
+        let (model, gemini_request) = Self::build_request(request);
+        debug!("Sending Gemini completion request: {:?}", model);
+
+        let response = self
+            .post_model(&model, "generateContent")
+            .json(&gemini_request)
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_from_response(response).await);
+        }
+
+        let gemini_response: GeminiResponse = response
+            .json()
+            .await
+            .map_err(|e| LLMError::Unknown(e.to_string()))?;
+
+        let (content, _) = gemini_response.texts();
+        Ok(CompletionResponse {
+            content,
+            finish_reason: gemini_response
+                .finish_reason()
+                .unwrap_or(FinishReason::Stop),
+            usage: gemini_response.usage().unwrap_or(TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+            }),
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
+        request.ensure_text_response("Gemini")?;
+        let cancellation =
+            StreamCancellation::register(&self.cancellations, request.request_id.as_deref());
+        let (model, gemini_request) = Self::build_request(request);
+        debug!("Sending Gemini streaming request: {:?}", model);
+
+        let response = self
+            .post_model(&model, "streamGenerateContent")
+            .query(&[("alt", "sse")])
+            .json(&gemini_request)
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(Self::error_from_response(response).await);
+        }
+
+        let stream = try_chunk_stream(response.bytes_stream(), Self::parse_stream_line);
+        let stream = cancellable(stream, cancellation);
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<(), LLMError> {
+        warn!("Cancelling Gemini request");
+        self.cancellations.cancel(request_id);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus, LLMError> {
+        let start = std::time::Instant::now();
+
+        let result = self
+            .client
+            .get(format!(
+                "{}/models",
+                self.config.base_url.trim_end_matches('/')
+            ))
+            .query(&[("pageSize", "1")])
+            .header("x-goog-api-key", &self.config.api_key)
+            .headers(self.extra_headers.clone())
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => {
+                let latency = start.elapsed().as_millis() as u64;
+                let is_healthy = response.status().is_success();
+                Ok(HealthStatus {
+                    is_healthy,
+                    latency_ms: Some(latency),
+                    error_message: (!is_healthy)
+                        .then(|| format!("API returned {}", response.status())),
+                })
+            }
+            Err(e) => Ok(HealthStatus {
+                is_healthy: false,
+                latency_ms: None,
+                error_message: Some(e.to_string()),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::conformance::{serve, MockResponse};
+    use super::*;
+    use crate::modules::chat::ports::ImageAttachment;
+
+    fn message(role: &str, content: &str) -> LLMChatMessage {
+        LLMChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            images: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_roles_are_mapped_and_system_prompt_folded() {
+        let mut with_image = message("user", "What is this?");
+        with_image.images.push(ImageAttachment {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+        });
+        let request = CompletionRequest::new(
+            vec![
+                message("system", "You are Kizuna."),
+                message("user", "Hi"),
+                message("assistant", "Hello!"),
+                message("user", "Look"),
+                with_image,
+            ],
+            "gemini-2.5-flash",
+        )
+        .with_max_tokens(64);
+
+        let (model, body) = GeminiAdapter::build_request(request);
+        let body = serde_json::to_value(body).unwrap();
+        let contents = body["contents"].as_array().unwrap();
+
+        assert_eq!(model, "gemini-2.5-flash");
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[0]["role"], "user");
+        assert_eq!(contents[0]["parts"][0]["text"], "You are Kizuna.\n\nHi");
+        assert_eq!(contents[1]["role"], "model");
+        assert_eq!(contents[1]["parts"][0]["text"], "Hello!");
+        // 相邻的用户消息合并为一轮
+        assert_eq!(contents[2]["role"], "user");
+        assert_eq!(contents[2]["parts"][0]["text"], "Look");
+        assert_eq!(
+            contents[2]["parts"][1]["inlineData"]["mimeType"],
+            "image/png"
+        );
+        assert_eq!(contents[2]["parts"][2]["text"], "What is this?");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 64);
+        assert!(contents[0]["parts"][0].get("thought").is_none());
+    }
+
+    #[test]
+    fn test_system_only_conversation_becomes_user_turn() {
+        let contents = GeminiAdapter::convert_messages(vec![message("system", "Be brief.")]);
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].role, "user");
+        assert_eq!(contents[0].parts[0].text.as_deref(), Some("Be brief."));
+    }
+
+    #[test]
+    fn test_parse_stream_line() {
+        let line = r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"Let me think","thought":true},{"text":"Hello"}]}}],"usageMetadata":{"promptTokenCount":3,"totalTokenCount":4}}"#;
+        let chunk = GeminiAdapter::parse_stream_line(line).unwrap().unwrap();
+        assert_eq!(chunk.content, "Hello");
+        assert_eq!(chunk.reasoning.as_deref(), Some("Let me think"));
+        assert!(chunk.finish_reason.is_none());
+        assert!(chunk.usage.is_none());
+
+        let line = r#"data: {"candidates":[{"content":{"role":"model","parts":[{"text":"!"}]},"finishReason":"MAX_TOKENS"}],"usageMetadata":{"promptTokenCount":3,"candidatesTokenCount":2,"totalTokenCount":5}}"#;
+        let chunk = GeminiAdapter::parse_stream_line(line).unwrap().unwrap();
+        assert_eq!(chunk.finish_reason, Some(FinishReason::Length));
+        assert_eq!(chunk.usage.unwrap().total_tokens, 5);
+
+        let line = r#"data: {"promptFeedback":{"blockReason":"SAFETY"}}"#;
+        let chunk = GeminiAdapter::parse_stream_line(line).unwrap().unwrap();
+        assert_eq!(chunk.finish_reason, Some(FinishReason::ContentFilter));
+
+        assert!(GeminiAdapter::parse_stream_line("").is_none());
+        let line = r#"data: {"error":{"code":503,"message":"Overloaded","status":"UNAVAILABLE"}}"#;
+        assert!(matches!(
+            GeminiAdapter::parse_stream_line(line),
+            Some(Err(LLMError::ApiError { code, .. })) if code == "UNAVAILABLE"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_complete_parses_candidates() {
+        let body = serde_json::json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [{ "text": "Hello, " }, { "text": "world" }] },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": { "promptTokenCount": 3, "candidatesTokenCount": 2, "totalTokenCount": 5 }
+        })
+        .to_string();
+        let (base_url, _) = serve(MockResponse::split("application/json", &body)).await;
+        let adapter = GeminiAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Gemini,
+            base_url,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = adapter
+            .complete(CompletionRequest::new(
+                vec![message("user", "Hi")],
+                "gemini-2.5-flash",
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hello, world");
+        assert_eq!(response.finish_reason, FinishReason::Stop);
+        assert_eq!(response.usage.total_tokens, 5);
+    }
+}
@@ -0,0 +1,140 @@
+// Provider Health Cache - 提供商健康状态缓存
+//
+// 记录各提供商最近一次的健康状态（来自健康检查或实际请求结果），
+// 供发送前的自动回退判断使用
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::modules::chat::ports::HealthStatus;
+
+/// 健康状态的有效期，超过后视为未知
+pub const HEALTH_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone)]
+struct HealthRecord {
+    healthy: bool,
+    checked_at: Instant,
+    /// 健康检查的完整结果，来自实际请求的记录没有
+    status: Option<HealthStatus>,
+}
+
+/// 提供商健康状态缓存
+pub struct ProviderHealthCache {
+    records: Mutex<HashMap<String, HealthRecord>>,
+    ttl: Duration,
+}
+
+impl ProviderHealthCache {
+    pub fn new() -> Self {
+        Self::with_ttl(HEALTH_TTL)
+    }
+
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            records: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// 记录提供商的健康状态
+    pub fn record(&self, provider_id: &str, healthy: bool) {
+        self.insert(provider_id, healthy, None);
+    }
+
+    /// 记录健康检查结果
+    pub fn record_status(&self, provider_id: &str, status: &HealthStatus) {
+        self.insert(provider_id, status.is_healthy, Some(status.clone()));
+    }
+
+    fn insert(&self, provider_id: &str, healthy: bool, status: Option<HealthStatus>) {
+        self.records.lock().unwrap().insert(
+            provider_id.to_string(),
+            HealthRecord {
+                healthy,
+                checked_at: Instant::now(),
+                status,
+            },
+        );
+    }
+
+    /// 不超过 `max_age` 的最近一次健康检查结果
+    ///
+    /// 最近的记录来自实际请求（没有延迟等详情）时返回 `None`
+    pub fn recent_status(&self, provider_id: &str, max_age: Duration) -> Option<HealthStatus> {
+        self.records
+            .lock()
+            .unwrap()
+            .get(provider_id)
+            .filter(|record| record.checked_at.elapsed() < max_age)
+            .and_then(|record| record.status.clone())
+    }
+
+    /// 最近是否被判定为不健康
+    ///
+    /// 没有记录或记录已过期时返回 false（状态未知时不主动回退）
+    pub fn is_unhealthy(&self, provider_id: &str) -> bool {
+        self.records
+            .lock()
+            .unwrap()
+            .get(provider_id)
+            .is_some_and(|record| !record.healthy && record.checked_at.elapsed() < self.ttl)
+    }
+
+    /// 清除提供商的记录
+    pub fn remove(&self, provider_id: &str) {
+        self.records.lock().unwrap().remove(provider_id);
+    }
+}
+
+impl Default for ProviderHealthCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_provider_is_not_unhealthy() {
+        let cache = ProviderHealthCache::new();
+        assert!(!cache.is_unhealthy("openai"));
+    }
+
+    #[test]
+    fn test_latest_record_wins() {
+        let cache = ProviderHealthCache::new();
+
+        cache.record("openai", false);
+        assert!(cache.is_unhealthy("openai"));
+
+        cache.record("openai", true);
+        assert!(!cache.is_unhealthy("openai"));
+    }
+
+    #[test]
+    fn test_recent_status_only_from_health_checks() {
+        let cache = ProviderHealthCache::new();
+        let max_age = Duration::from_secs(30);
+
+        cache.record_status("openai", &HealthStatus::unhealthy("down"));
+        let status = cache.recent_status("openai", max_age).unwrap();
+        assert_eq!(status.error_message.as_deref(), Some("down"));
+        assert!(cache.recent_status("openai", Duration::ZERO).is_none());
+
+        // 实际请求的结果覆盖检查结果，但不提供详情
+        cache.record("openai", true);
+        assert!(cache.recent_status("openai", max_age).is_none());
+    }
+
+    #[test]
+    fn test_expired_record_is_ignored() {
+        let cache = ProviderHealthCache::with_ttl(Duration::ZERO);
+
+        cache.record("openai", false);
+        assert!(!cache.is_unhealthy("openai"));
+    }
+}
@@ -0,0 +1,145 @@
+// Line Buffer - 流式响应按行切分
+//
+// SSE 和 NDJSON 响应都以行为单位，但网络分块可能在任意字节处切断，
+// 包括多字节 UTF-8 字符的中间。这里缓存原始字节，只在整行到齐后才解码
+
+use futures::stream::{self, Stream, StreamExt};
+use std::fmt::Display;
+
+use crate::modules::chat::ports::{LLMError, StreamChunk};
+
+/// 流式响应行缓冲区
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    buffer: Vec<u8>,
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 追加一个网络分块
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// 取出下一条完整的行（不含行尾的 `\n` 或 `\r\n`），不足一行时返回 None
+    pub fn next_line(&mut self) -> Option<String> {
+        let pos = self.buffer.iter().position(|&b| b == b'\n')?;
+        let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// 流结束时调用：把没有换行结尾的剩余内容作为最后一行，只含空白时丢弃
+    pub fn finish(&mut self) {
+        if self.buffer.iter().all(u8::is_ascii_whitespace) {
+            self.buffer.clear();
+        } else {
+            self.buffer.push(b'\n');
+        }
+    }
+}
+
+/// 将网络字节流按行解析为流式分块
+///
+/// `parse` 对每条完整的行返回分块，返回 None 的行被跳过；
+/// 流正常结束时，缺少结尾换行的最后一行同样会被解析。
+/// 网络错误以 `LLMError::NetworkError` 报告一次，之后流结束
+pub fn chunk_stream<S, B, E, F>(
+    bytes: S,
+    parse: F,
+) -> impl Stream<Item = Result<StreamChunk, LLMError>> + Send
+where
+    S: Stream<Item = Result<B, E>> + Send + Unpin,
+    B: AsRef<[u8]>,
+    E: Display,
+    F: Fn(&str) -> Option<StreamChunk> + Send,
+{
+    try_chunk_stream(bytes, move |line| parse(line).map(Ok))
+}
+
+/// 与 [`chunk_stream`] 相同，但 `parse` 可以把某一行解析为错误（如流中的错误事件），
+/// 错误报告一次后流结束
+pub fn try_chunk_stream<S, B, E, F>(
+    bytes: S,
+    parse: F,
+) -> impl Stream<Item = Result<StreamChunk, LLMError>> + Send
+where
+    S: Stream<Item = Result<B, E>> + Send + Unpin,
+    B: AsRef<[u8]>,
+    E: Display,
+    F: Fn(&str) -> Option<Result<StreamChunk, LLMError>> + Send,
+{
+    stream::unfold(
+        (Some(bytes), LineBuffer::new(), parse),
+        |(mut bytes, mut lines, parse)| async move {
+            loop {
+                while let Some(line) = lines.next_line() {
+                    match parse(&line) {
+                        Some(Ok(chunk)) => return Some((Ok(chunk), (bytes, lines, parse))),
+                        Some(Err(e)) => return Some((Err(e), (None, LineBuffer::new(), parse))),
+                        None => {}
+                    }
+                }
+
+                // 出现错误后不再继续读取
+                match bytes.as_mut()?.next().await {
+                    Some(Ok(data)) => lines.push(data.as_ref()),
+                    Some(Err(e)) => {
+                        return Some((
+                            Err(LLMError::NetworkError(e.to_string())),
+                            (None, lines, parse),
+                        ));
+                    }
+                    None => {
+                        // 解析完剩余内容后结束
+                        lines.finish();
+                        bytes = None;
+                    }
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_split_across_chunks() {
+        let mut buffer = LineBuffer::new();
+        let bytes = "data: 你好\r\ndata: [DONE]\n".as_bytes();
+
+        // 在多字节字符中间切断
+        buffer.push(&bytes[..8]);
+        assert_eq!(buffer.next_line(), None);
+        buffer.push(&bytes[8..]);
+
+        assert_eq!(buffer.next_line().as_deref(), Some("data: 你好"));
+        assert_eq!(buffer.next_line().as_deref(), Some("data: [DONE]"));
+        assert_eq!(buffer.next_line(), None);
+    }
+
+    #[test]
+    fn test_finish_flushes_unterminated_line() {
+        let mut buffer = LineBuffer::new();
+        buffer.push(b"{\"done\":false}\n{\"done\":true}");
+        assert_eq!(buffer.next_line().as_deref(), Some("{\"done\":false}"));
+        assert_eq!(buffer.next_line(), None);
+
+        buffer.finish();
+        assert_eq!(buffer.next_line().as_deref(), Some("{\"done\":true}"));
+        assert_eq!(buffer.next_line(), None);
+
+        // 只剩空白时不产生多余的空行
+        buffer.push(b" \r");
+        buffer.finish();
+        assert_eq!(buffer.next_line(), None);
+    }
+}
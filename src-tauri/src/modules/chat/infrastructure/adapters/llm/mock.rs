@@ -0,0 +1,145 @@
+// Mock LLM Adapter
+//
+// 不访问网络的模拟适配器，回显用户消息
+
+use async_trait::async_trait;
+use futures::Stream;
+use std::pin::Pin;
+
+use crate::modules::chat::ports::{
+    CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMError, LLMPort,
+    ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+};
+
+/// 模拟 LLM 适配器
+///
+/// 用于测试或未配置真实 LLM 时的回退
+pub struct MockLLMAdapter;
+
+impl MockLLMAdapter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for MockLLMAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LLMPort for MockLLMAdapter {
+    fn provider_id(&self) -> &str {
+        "mock"
+    }
+
+    fn provider_info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: "mock".to_string(),
+            name: "Mock Provider (Simulation)".to_string(),
+            provider_type: ProviderType::Custom,
+            models: vec![ModelInfo {
+                id: "mock-model".to_string(),
+                name: "Mock Model".to_string(),
+                context_length: 4096,
+                supports_vision: false,
+                supports_functions: false,
+            }],
+            supports_cancellation: false,
+            supports_model_listing: false,
+            supports_assistant_prefix: false,
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, LLMError> {
+        Ok(self.provider_info().models)
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
+        let user_content = request
+            .messages
+            .last()
+            .map(|m| m.content.as_str())
+            .unwrap_or("");
+
+        let response_content = format!(
+            "你好！我收到了你的消息：「{}」\n\n这是一个模拟的回复。要使用真正的 LLM，请在设置中配置 API Key。",
+            user_content
+        );
+
+        Ok(CompletionResponse {
+            content: response_content,
+            finish_reason: FinishReason::Stop,
+            usage: TokenUsage {
+                prompt_tokens: 10,
+                completion_tokens: 50,
+                total_tokens: 60,
+            },
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
+        let user_content = request
+            .messages
+            .last()
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+
+        let response_content = format!(
+            "你好！我收到了你的消息：「{}」\n\n这是一个模拟的回复。要使用真正的 LLM，请在设置中配置 API Key。",
+            user_content
+        );
+
+        // 将响应分成多个块模拟流式输出
+        let chunks: Vec<String> = response_content
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(5)
+            .map(|c| c.iter().collect::<String>())
+            .collect();
+
+        let stream = futures::stream::iter(chunks.into_iter().enumerate().map(
+            |(i, content)| -> Result<StreamChunk, LLMError> {
+                Ok(StreamChunk {
+                    content,
+                    reasoning: None,
+                    finish_reason: if i == 0 { None } else { None },
+                    usage: None,
+                })
+            },
+        ));
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
+        Err(LLMError::Unsupported("cancel"))
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus, LLMError> {
+        Ok(HealthStatus {
+            is_healthy: true,
+            latency_ms: Some(1),
+            error_message: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_capabilities() {
+        let adapter = MockLLMAdapter::new();
+        let info = adapter.provider_info();
+
+        assert!(!info.supports_cancellation);
+        assert!(!info.supports_model_listing);
+        assert!(adapter.cancel("req-1").await.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+// Mock HTTP Server - 测试用本地 HTTP 服务器
+//
+// 按顺序为每个请求返回预设响应（用完后重复最后一个），并记录收到的请求和连接数，
+// 供各适配器和命令的测试共用
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 预设响应
+#[derive(Debug, Clone)]
+pub struct MockHttpResponse {
+    status: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl MockHttpResponse {
+    /// 指定状态行（如 `"429 Too Many Requests"`）和 JSON 响应体
+    pub fn new(status: &str, body: impl Into<String>) -> Self {
+        Self {
+            status: status.to_string(),
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new("200 OK", body)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\n", self.status);
+        for (name, value) in &self.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "content-length: {}\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        response.into_bytes()
+    }
+}
+
+/// 收到的请求
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// 请求行和请求头（已转为小写）
+    pub head: String,
+    pub body: String,
+}
+
+/// 本地 HTTP 服务器，支持 keep-alive，随测试结束自动停止
+pub struct MockHttpServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockHttpServer {
+    /// 按顺序返回 `responses`，用完后重复最后一个
+    pub async fn start(responses: Vec<MockHttpResponse>) -> Self {
+        assert!(!responses.is_empty(), "mock server needs a response");
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let responses = Arc::new(responses);
+        let recorded = requests.clone();
+        let accepted = connections.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                let responses = responses.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    while let Some(request) = read_request(&mut socket).await {
+                        let index = {
+                            let mut recorded = recorded.lock().unwrap();
+                            recorded.push(request);
+                            recorded.len() - 1
+                        };
+                        let response = &responses[index.min(responses.len() - 1)];
+                        if socket.write_all(&response.to_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            connections,
+        }
+    }
+
+    /// 对每个请求都返回同一个响应
+    pub async fn fixed(status: &str, body: impl Into<String>) -> Self {
+        Self::start(vec![MockHttpResponse::new(status, body)]).await
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    /// 已收到的请求（按时间顺序）
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// 已接受的连接数
+    pub fn connection_count(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+/// 读取完整请求（请求头和 Content-Length 指定的请求体），连接关闭时返回 None
+pub async fn read_request(socket: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&data[..end]).to_lowercase();
+            let body_len = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            let body_start = end + 4;
+            if data.len() >= body_start + body_len {
+                let body =
+                    String::from_utf8_lossy(&data[body_start..body_start + body_len]).into_owned();
+                return Some(RecordedRequest { head, body });
+            }
+        }
+
+        match socket.read(&mut buf).await {
+            Ok(n) if n > 0 => data.extend_from_slice(&buf[..n]),
+            _ => return None,
+        }
+    }
+}
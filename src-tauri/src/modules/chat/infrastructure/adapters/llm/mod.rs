@@ -2,15 +2,37 @@
 // 各种 LLM 提供商的适配器实现
 
 mod base;
+mod cancellation;
+mod capabilities;
 mod claude;
-mod dynamic;
+#[cfg(test)]
+mod conformance;
+mod gemini;
+mod health;
+mod http_client;
+mod line_buffer;
+mod mock;
+#[cfg(test)]
+mod mock_http;
 mod ollama;
 mod openai;
 mod registry;
+mod retry;
+mod scriptable;
 
 pub use base::*;
+pub use cancellation::*;
+pub use capabilities::*;
 pub use claude::*;
-pub use dynamic::*;
+pub use gemini::*;
+pub use health::*;
+pub use http_client::*;
+pub use line_buffer::*;
+pub use mock::*;
+#[cfg(test)]
+pub use mock_http::*;
 pub use ollama::*;
 pub use openai::*;
 pub use registry::*;
+pub use retry::*;
+pub use scriptable::*;
@@ -3,10 +3,16 @@
 // 实现 Ollama 的聊天 API 适配器
 
 use async_trait::async_trait;
-use futures::stream::{self, Stream};
+use futures::Stream;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{build_http_client, chunk_stream, extra_header_map};
 
 use crate::modules::chat::ports::{
     CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMChatMessage, LLMError,
@@ -21,22 +27,36 @@ struct OllamaChatRequest {
     stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<OllamaOptions>,
+    /// 模型在请求后保持加载的时长，数字为秒数，负数表示一直保持
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct OllamaMessage {
     role: String,
     content: String,
+    /// base64 编码的图片（多模态模型使用）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<String>,
 }
 
+/// Ollama 采样选项
+///
+/// `presence_penalty` / `frequency_penalty` 不受支持，请求中的这两个参数会被忽略
 #[derive(Debug, Serialize)]
 struct OllamaOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     num_predict: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
+    /// 上下文窗口大小（token）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
 }
 
 /// Ollama 聊天响应
@@ -67,20 +87,105 @@ struct OllamaModelInfo {
     size: u64,
 }
 
+/// Ollama 默认的模型保活时间，空闲超过该时长后模型会被卸载
+const OLLAMA_KEEP_ALIVE: Duration = Duration::from_secs(5 * 60);
+
+/// 转换 keep_alive 配置：纯数字按秒数发送，其余（如 "5m"）按时长字符串发送
+fn keep_alive_value(keep_alive: &str) -> serde_json::Value {
+    let keep_alive = keep_alive.trim();
+    match keep_alive.parse::<i64>() {
+        Ok(secs) => secs.into(),
+        Err(_) => keep_alive.into(),
+    }
+}
+
 /// Ollama 适配器
 pub struct OllamaAdapter {
     config: LLMProviderConfig,
     client: Client,
+    extra_headers: HeaderMap,
+    /// 各模型最近一次成功请求的时间，用于判断是否需要冷启动
+    warm_models: Mutex<HashMap<String, Instant>>,
 }
 
 impl OllamaAdapter {
     pub fn new(config: LLMProviderConfig) -> Result<Self, LLMError> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(config.timeout_secs))
-            .build()
-            .map_err(|e| LLMError::Unknown(e.to_string()))?;
+        // 超时按请求设置，见 request_timeout
+        let client = build_http_client(None, &config.http, config.proxy_url.as_deref())?;
+        let extra_headers = extra_header_map(&config.extra_headers)?;
+
+        Ok(Self {
+            config,
+            client,
+            extra_headers,
+            warm_models: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// 选择本次请求的超时时间
+    ///
+    /// 模型从未请求过或空闲超过保活时间时视为冷启动，使用冷启动超时
+    fn select_timeout(
+        last_used: Option<Instant>,
+        now: Instant,
+        timeout: Duration,
+        cold_start_timeout: Duration,
+    ) -> Duration {
+        match last_used {
+            Some(at) if now.duration_since(at) < OLLAMA_KEEP_ALIVE => timeout,
+            _ => cold_start_timeout,
+        }
+    }
+
+    /// 构建聊天请求，附带配置的 keep_alive 和 num_ctx
+    fn chat_request(&self, request: CompletionRequest, stream: bool) -> OllamaChatRequest {
+        let num_ctx = self.config.num_ctx;
+        let options = if request.temperature.is_some()
+            || request.top_p.is_some()
+            || request.max_tokens.is_some()
+            || request.stop_sequences.is_some()
+            || num_ctx.is_some()
+        {
+            Some(OllamaOptions {
+                temperature: request.temperature,
+                top_p: request.top_p,
+                num_predict: request.max_tokens,
+                stop: request.stop_sequences,
+                num_ctx,
+            })
+        } else {
+            None
+        };
+
+        OllamaChatRequest {
+            model: request.model,
+            messages: self.convert_messages(request.messages),
+            stream,
+            options,
+            keep_alive: self.config.keep_alive.as_deref().map(keep_alive_value),
+        }
+    }
+
+    fn request_timeout(&self, model: &str) -> Duration {
+        let last_used = self
+            .warm_models
+            .lock()
+            .ok()
+            .and_then(|models| models.get(model).copied());
+
+        Self::select_timeout(
+            last_used,
+            Instant::now(),
+            Duration::from_secs(self.config.timeout_secs),
+            Duration::from_secs(self.config.cold_start_timeout_secs),
+        )
+    }
 
-        Ok(Self { config, client })
+    /// 记录模型已加载
+    fn mark_warm(&self, model: &str) {
+        if let Ok(mut models) = self.warm_models.lock() {
+            models.insert(model.to_string(), Instant::now());
+        }
     }
 
     fn convert_messages(&self, messages: Vec<LLMChatMessage>) -> Vec<OllamaMessage> {
@@ -89,9 +194,38 @@ impl OllamaAdapter {
             .map(|m| OllamaMessage {
                 role: m.role,
                 content: m.content,
+                images: m.images.into_iter().map(|image| image.data).collect(),
             })
             .collect()
     }
+
+    /// 解析一行 NDJSON 流式响应
+    fn parse_stream_line(line: &str) -> Option<StreamChunk> {
+        let response = serde_json::from_str::<OllamaChatResponse>(line).ok()?;
+        if !response.done {
+            // 内容块
+            return Some(StreamChunk {
+                content: response.message.content,
+                reasoning: None,
+                finish_reason: None,
+                usage: None,
+            });
+        }
+
+        // 最后一个块包含统计信息
+        let prompt_tokens = response.prompt_eval_count.unwrap_or(0);
+        let completion_tokens = response.eval_count.unwrap_or(0);
+        Some(StreamChunk {
+            content: String::new(),
+            reasoning: None,
+            finish_reason: Some(FinishReason::Stop),
+            usage: Some(TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            }),
+        })
+    }
 }
 
 #[async_trait]
@@ -128,6 +262,9 @@ impl LLMPort for OllamaAdapter {
                     supports_functions: false,
                 },
             ],
+            supports_cancellation: false,
+            supports_model_listing: true,
+            supports_assistant_prefix: false,
         }
     }
 
@@ -136,6 +273,7 @@ impl LLMPort for OllamaAdapter {
         let response = self
             .client
             .get(format!("{}/api/tags", self.config.base_url))
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
@@ -163,30 +301,15 @@ impl LLMPort for OllamaAdapter {
     }
 
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
-        let options = if request.temperature.is_some()
-            || request.max_tokens.is_some()
-            || request.stop_sequences.is_some()
-        {
-            Some(OllamaOptions {
-                temperature: request.temperature,
-                num_predict: request.max_tokens,
-                stop: request.stop_sequences,
-            })
-        } else {
-            None
-        };
-
-        let ollama_request = OllamaChatRequest {
-            model: request.model.clone(),
-            messages: self.convert_messages(request.messages),
-            stream: false,
-            options,
-        };
+        request.ensure_text_response("Ollama")?;
+        let ollama_request = self.chat_request(request, false);
 
         let response = self
             .client
             .post(format!("{}/api/chat", self.config.base_url))
+            .timeout(self.request_timeout(&ollama_request.model))
             .json(&ollama_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
@@ -200,6 +323,8 @@ impl LLMPort for OllamaAdapter {
             });
         }
 
+        self.mark_warm(&ollama_request.model);
+
         let ollama_response: OllamaChatResponse = response
             .json()
             .await
@@ -221,30 +346,15 @@ impl LLMPort for OllamaAdapter {
         &self,
         request: CompletionRequest,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
-        let options = if request.temperature.is_some()
-            || request.max_tokens.is_some()
-            || request.stop_sequences.is_some()
-        {
-            Some(OllamaOptions {
-                temperature: request.temperature,
-                num_predict: request.max_tokens,
-                stop: request.stop_sequences,
-            })
-        } else {
-            None
-        };
-
-        let ollama_request = OllamaChatRequest {
-            model: request.model.clone(),
-            messages: self.convert_messages(request.messages),
-            stream: true,
-            options,
-        };
+        request.ensure_text_response("Ollama")?;
+        let ollama_request = self.chat_request(request, true);
 
         let response = self
             .client
             .post(format!("{}/api/chat", self.config.base_url))
+            .timeout(self.request_timeout(&ollama_request.model))
             .json(&ollama_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
@@ -258,78 +368,16 @@ impl LLMPort for OllamaAdapter {
             });
         }
 
-        use futures::StreamExt;
+        self.mark_warm(&ollama_request.model);
 
-        let bytes_stream = response.bytes_stream();
-        let buffer = String::new();
-
-        let stream = stream::unfold(
-            (bytes_stream, buffer),
-            |(mut bytes_stream, mut buffer)| async move {
-                loop {
-                    match bytes_stream.next().await {
-                        Some(Ok(bytes)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&bytes));
-
-                            // 处理所有完整的 JSON 行
-                            while let Some(pos) = buffer.find('\n') {
-                                let line = buffer[..pos].to_string();
-                                buffer.drain(..=pos);
-
-                                if !line.is_empty() {
-                                    if let Ok(response) =
-                                        serde_json::from_str::<OllamaChatResponse>(&line)
-                                    {
-                                        if response.done {
-                                            // 最后一个块包含统计信息
-                                            let chunk = StreamChunk {
-                                                content: String::new(),
-                                                finish_reason: Some(FinishReason::Stop),
-                                                usage: Some(TokenUsage {
-                                                    prompt_tokens: response
-                                                        .prompt_eval_count
-                                                        .unwrap_or(0),
-                                                    completion_tokens: response
-                                                        .eval_count
-                                                        .unwrap_or(0),
-                                                    total_tokens: response
-                                                        .prompt_eval_count
-                                                        .unwrap_or(0)
-                                                        + response.eval_count.unwrap_or(0),
-                                                }),
-                                            };
-                                            return Some((Ok(chunk), (bytes_stream, buffer)));
-                                        } else {
-                                            // 内容块
-                                            let chunk = StreamChunk {
-                                                content: response.message.content,
-                                                finish_reason: None,
-                                                usage: None,
-                                            };
-                                            return Some((Ok(chunk), (bytes_stream, buffer)));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Some((
-                                Err(LLMError::NetworkError(e.to_string())),
-                                (bytes_stream, buffer),
-                            ));
-                        }
-                        None => return None,
-                    }
-                }
-            },
-        );
+        let stream = chunk_stream(response.bytes_stream(), Self::parse_stream_line);
 
         Ok(Box::pin(stream))
     }
 
     async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
         // Ollama 不支持取消请求
-        Ok(())
+        Err(LLMError::Unsupported("cancel"))
     }
 
     async fn health_check(&self) -> Result<HealthStatus, LLMError> {
@@ -338,6 +386,7 @@ impl LLMPort for OllamaAdapter {
         match self
             .client
             .get(format!("{}/api/tags", self.config.base_url))
+            .headers(self.extra_headers.clone())
             .send()
             .await
         {
@@ -365,3 +414,153 @@ impl LLMPort for OllamaAdapter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::ports::ResponseFormat;
+
+    #[tokio::test]
+    async fn test_capabilities() {
+        let adapter = OllamaAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Ollama,
+            base_url: "http://localhost:11434".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let info = adapter.provider_info();
+
+        assert!(!info.supports_cancellation);
+        assert!(info.supports_model_listing);
+        assert!(matches!(
+            adapter.cancel("req-1").await,
+            Err(LLMError::Unsupported("cancel"))
+        ));
+    }
+
+    #[test]
+    fn test_keep_alive_and_num_ctx_serialized_only_when_set() {
+        let request = || CompletionRequest::new(vec![], "llama3");
+
+        let adapter = OllamaAdapter::new(LLMProviderConfig::default()).unwrap();
+        let json = serde_json::to_value(adapter.chat_request(request(), false)).unwrap();
+        assert!(json.get("keep_alive").is_none());
+        assert!(json.get("options").is_none());
+
+        let adapter = OllamaAdapter::new(LLMProviderConfig {
+            keep_alive: Some("5m".to_string()),
+            num_ctx: Some(8192),
+            ..Default::default()
+        })
+        .unwrap();
+        let json = serde_json::to_value(adapter.chat_request(request(), true)).unwrap();
+        assert_eq!(json["keep_alive"], "5m");
+        assert_eq!(json["options"], serde_json::json!({ "num_ctx": 8192 }));
+
+        let adapter = OllamaAdapter::new(LLMProviderConfig {
+            keep_alive: Some("-1".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let json =
+            serde_json::to_value(adapter.chat_request(request().with_temperature(0.5), false))
+                .unwrap();
+        assert_eq!(json["keep_alive"], -1);
+        assert!(json["options"].get("num_ctx").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_rejected() {
+        let adapter = OllamaAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Ollama,
+            base_url: "http://localhost:11434".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let request = CompletionRequest::new(vec![], "llama3")
+            .with_response_format(ResponseFormat::JsonObject);
+
+        assert!(matches!(
+            adapter.complete(request.clone()).await,
+            Err(LLMError::InvalidRequest(msg)) if msg.contains("json_object")
+        ));
+        assert!(matches!(
+            adapter.complete_stream(request).await,
+            Err(LLMError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_timeout() {
+        let timeout = Duration::from_secs(60);
+        let cold_start_timeout = Duration::from_secs(300);
+        let now = Instant::now();
+
+        // 首次请求使用冷启动超时
+        assert_eq!(
+            OllamaAdapter::select_timeout(None, now, timeout, cold_start_timeout),
+            cold_start_timeout
+        );
+
+        // 模型已加载时使用普通超时
+        let last_used = now;
+        let later = last_used + Duration::from_secs(30);
+        assert_eq!(
+            OllamaAdapter::select_timeout(Some(last_used), later, timeout, cold_start_timeout),
+            timeout
+        );
+
+        // 空闲超过保活时间后模型被卸载，重新使用冷启动超时
+        let idle = last_used + OLLAMA_KEEP_ALIVE;
+        assert_eq!(
+            OllamaAdapter::select_timeout(Some(last_used), idle, timeout, cold_start_timeout),
+            cold_start_timeout
+        );
+    }
+
+    #[test]
+    fn test_request_timeout_after_first_request() {
+        let adapter = OllamaAdapter::new(LLMProviderConfig {
+            provider_type: ProviderType::Ollama,
+            base_url: "http://localhost:11434".to_string(),
+            timeout_secs: 60,
+            cold_start_timeout_secs: 300,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            adapter.request_timeout("llama3.2"),
+            Duration::from_secs(300)
+        );
+
+        adapter.mark_warm("llama3.2");
+        assert_eq!(adapter.request_timeout("llama3.2"), Duration::from_secs(60));
+        assert_eq!(adapter.request_timeout("qwen2.5"), Duration::from_secs(300));
+    }
+
+    #[tokio::test]
+    async fn test_final_object_without_trailing_newline_is_parsed() {
+        use futures::StreamExt;
+
+        let body = concat!(
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,",
+            "\"prompt_eval_count\":5,\"eval_count\":2}",
+        );
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> = vec![Ok(body.as_bytes().to_vec())];
+
+        let chunks: Vec<StreamChunk> = chunk_stream(
+            futures::stream::iter(chunks),
+            OllamaAdapter::parse_stream_line,
+        )
+        .map(|chunk| chunk.unwrap())
+        .collect()
+        .await;
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].content, "Hi");
+        assert_eq!(chunks[1].finish_reason, Some(FinishReason::Stop));
+        assert_eq!(chunks[1].usage.as_ref().unwrap().total_tokens, 7);
+    }
+}
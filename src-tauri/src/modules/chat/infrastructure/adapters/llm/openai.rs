@@ -1,41 +1,67 @@
 use async_trait::async_trait;
-use futures::{Stream, StreamExt};
-use reqwest::Client;
+use futures::Stream;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Response};
 use serde::{Deserialize, Serialize};
 use std::pin::Pin;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::watch;
 use tracing::{debug, error, warn};
 
+use super::{
+    build_http_client, cancellable, extra_header_map, retry_after_secs, try_chunk_stream,
+    with_retries, CapabilityCache, StreamCancellation,
+};
+
+use crate::infrastructure::CancellationRegistry;
 use crate::modules::chat::ports::{
-    CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMChatMessage, LLMError,
-    LLMPort, LLMProviderConfig, ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+    CapabilityCacheUsage, CompletionRequest, CompletionResponse, FinishReason, HealthStatus,
+    LLMChatMessage, LLMError, LLMPort, LLMProviderConfig, ModelInfo, ProviderCapabilities,
+    ProviderInfo, ProviderType, ResponseFormat, StreamChunk, TokenUsage, MAX_CANDIDATES,
 };
 
+/// 限流响应未给出 Retry-After 时的等待秒数
+const DEFAULT_RATE_LIMIT_RETRY_SECS: u64 = 60;
+
+/// `/models` 接口不提供上下文长度，不在内置列表中的模型使用此值
+const DEFAULT_CONTEXT_LENGTH: u32 = 8192;
+
 /// OpenAI API 适配器
 pub struct OpenAIAdapter {
     client: Client,
     config: LLMProviderConfig,
-    cancel_sender: watch::Sender<bool>,
+    extra_headers: HeaderMap,
+    /// 进行中的流式请求，按请求 ID 取消
+    cancellations: Arc<CancellationRegistry>,
+    /// 首次流式请求时探测的部署能力
+    capabilities: CapabilityCache,
 }
 
 impl OpenAIAdapter {
     /// 创建新的 OpenAI 适配器
     pub fn new(config: LLMProviderConfig) -> Result<Self, LLMError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.timeout_secs))
-            .build()
-            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
-
-        let (cancel_sender, _) = watch::channel(false);
+        let client = build_http_client(
+            Some(Duration::from_secs(config.timeout_secs)),
+            &config.http,
+            config.proxy_url.as_deref(),
+        )?;
+        let extra_headers = extra_header_map(&config.extra_headers)?;
 
         Ok(Self {
             client,
             config,
-            cancel_sender,
+            extra_headers,
+            cancellations: Arc::new(CancellationRegistry::new()),
+            capabilities: CapabilityCache::new(),
         })
     }
 
+    /// 使用已知的部署能力，跳过首次使用时的探测
+    pub fn with_capabilities(mut self, capabilities: ProviderCapabilities) -> Self {
+        self.capabilities = CapabilityCache::with_capabilities(capabilities);
+        self
+    }
+
     /// 获取 API URL
     fn api_url(&self, endpoint: &str) -> String {
         format!(
@@ -52,16 +78,100 @@ impl OpenAIAdapter {
             messages: request
                 .messages
                 .iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role.clone(),
-                    content: m.content.clone(),
-                })
+                .map(OpenAIRequestMessage::from)
                 .collect(),
             max_tokens: request.max_tokens,
             temperature: request.temperature,
+            top_p: request.top_p,
+            presence_penalty: request.presence_penalty,
+            frequency_penalty: request.frequency_penalty,
             stop: request.stop_sequences.clone(),
             stream: Some(stream),
+            stream_options: None,
+            // 流式响应中多个候选会交错返回，只在非流式请求中发送
+            n: if stream {
+                None
+            } else {
+                request.n.map(|n| n.clamp(1, MAX_CANDIDATES))
+            },
+            response_format: request.response_format.as_ref().map(Into::into),
+        }
+    }
+
+    /// 内置的模型列表
+    fn bundled_models() -> Vec<ModelInfo> {
+        vec![
+            ModelInfo {
+                id: "gpt-4o".to_string(),
+                name: "GPT-4o".to_string(),
+                context_length: 128000,
+                supports_vision: true,
+                supports_functions: true,
+            },
+            ModelInfo {
+                id: "gpt-4o-mini".to_string(),
+                name: "GPT-4o Mini".to_string(),
+                context_length: 128000,
+                supports_vision: true,
+                supports_functions: true,
+            },
+            ModelInfo {
+                id: "gpt-4-turbo".to_string(),
+                name: "GPT-4 Turbo".to_string(),
+                context_length: 128000,
+                supports_vision: true,
+                supports_functions: true,
+            },
+            ModelInfo {
+                id: "gpt-3.5-turbo".to_string(),
+                name: "GPT-3.5 Turbo".to_string(),
+                context_length: 16385,
+                supports_vision: false,
+                supports_functions: true,
+            },
+        ]
+    }
+
+    /// 请求 `{base_url}/models`，返回部署提供的模型
+    pub async fn fetch_models(&self) -> Result<Vec<OpenAIModel>, LLMError> {
+        let response = self
+            .client
+            .get(self.api_url("models"))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .headers(self.extra_headers.clone())
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            error!("OpenAI API error: {} - {}", status, error_text);
+
+            if status.as_u16() == 401 {
+                return Err(LLMError::AuthenticationError("Invalid API key".to_string()));
+            }
+            return Err(LLMError::ApiError {
+                code: status.to_string(),
+                message: error_text,
+            });
         }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+        Self::parse_models(&body)
+    }
+
+    /// 解析 `/models` 响应体 `{ "data": [{ "id", "owned_by" }] }`
+    fn parse_models(body: &str) -> Result<Vec<OpenAIModel>, LLMError> {
+        if let Some(error) = Self::parse_error_body(body) {
+            return Err(error);
+        }
+        let response: OpenAIModelsResponse = serde_json::from_str(body)
+            .map_err(|e| LLMError::Unknown(format!("Failed to parse models: {}", e)))?;
+        Ok(response.data)
     }
 
     /// 解析 SSE 行
@@ -76,84 +186,102 @@ impl OpenAIAdapter {
             None
         }
     }
-}
 
-#[async_trait]
-impl LLMPort for OpenAIAdapter {
-    fn provider_id(&self) -> &str {
-        &self.config.id
+    /// 解析响应体中的 `error` 对象
+    ///
+    /// 部分 OpenAI 兼容服务在出错时仍返回 200，错误信息放在响应体里
+    fn parse_error_body(body: &str) -> Option<LLMError> {
+        let envelope: OpenAIErrorEnvelope = serde_json::from_str(body).ok()?;
+        let error = envelope.error;
+        let code = match error.code {
+            Some(serde_json::Value::String(code)) => Some(code),
+            Some(serde_json::Value::Null) | None => None,
+            Some(code) => Some(code.to_string()),
+        };
+
+        Some(LLMError::ApiError {
+            code: code.or(error.kind).unwrap_or_else(|| "error".to_string()),
+            message: error.message,
+        })
     }
 
-    fn provider_info(&self) -> ProviderInfo {
-        ProviderInfo {
-            id: self.config.id.clone(),
-            name: self.config.name.clone(),
-            provider_type: ProviderType::OpenAI,
-            models: vec![
-                ModelInfo {
-                    id: "gpt-4o".to_string(),
-                    name: "GPT-4o".to_string(),
-                    context_length: 128000,
-                    supports_vision: true,
-                    supports_functions: true,
-                },
-                ModelInfo {
-                    id: "gpt-4o-mini".to_string(),
-                    name: "GPT-4o Mini".to_string(),
-                    context_length: 128000,
-                    supports_vision: true,
-                    supports_functions: true,
-                },
-                ModelInfo {
-                    id: "gpt-4-turbo".to_string(),
-                    name: "GPT-4 Turbo".to_string(),
-                    context_length: 128000,
-                    supports_vision: true,
-                    supports_functions: true,
-                },
-                ModelInfo {
-                    id: "gpt-3.5-turbo".to_string(),
-                    name: "GPT-3.5 Turbo".to_string(),
-                    context_length: 16385,
-                    supports_vision: false,
-                    supports_functions: true,
-                },
-            ],
+    /// 解析流中的一行，`error` 事件被转换为错误
+    fn parse_stream_line(line: &str) -> Option<Result<StreamChunk, LLMError>> {
+        let data = line.strip_prefix("data: ")?;
+        if let Some(error) = Self::parse_error_body(data) {
+            error!("OpenAI stream error: {}", data);
+            return Some(Err(error));
         }
+        Self::parse_sse_line(line)
+            .and_then(Self::to_stream_chunk)
+            .map(Ok)
     }
 
-    async fn list_models(&self) -> Result<Vec<ModelInfo>, LLMError> {
-        // 返回预定义的模型列表
-        // 实际应用中可以调用 /v1/models 接口
-        Ok(self.provider_info().models)
+    /// 映射结束原因
+    fn map_finish_reason(reason: Option<&str>) -> FinishReason {
+        match reason {
+            Some("stop") => FinishReason::Stop,
+            Some("length") => FinishReason::Length,
+            Some("content_filter") => FinishReason::ContentFilter,
+            Some("function_call") | Some("tool_calls") => FinishReason::FunctionCall,
+            _ => FinishReason::Stop,
+        }
     }
 
-    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
-        let openai_request = self.to_openai_request(&request, false);
+    /// 将流式响应转换为分块，既无内容、结束原因也无用量的事件被忽略
+    ///
+    /// 请求了 `stream_options.include_usage` 时，用量在 `choices` 为空的最后一个事件中返回
+    fn to_stream_chunk(response: OpenAIStreamResponse) -> Option<StreamChunk> {
+        let usage = response.usage.map(OpenAIUsage::into_token_usage);
+        let (delta, finish_reason) = match response.choices.into_iter().next() {
+            Some(choice) => (choice.delta, choice.finish_reason),
+            None => (OpenAIDelta::default(), None),
+        };
+        if delta.content.is_none()
+            && delta.reasoning_content.is_none()
+            && finish_reason.is_none()
+            && usage.is_none()
+        {
+            return None;
+        }
 
-        debug!(
-            "Sending OpenAI completion request: {:?}",
-            openai_request.model
-        );
+        Some(StreamChunk {
+            content: delta.content.unwrap_or_default(),
+            reasoning: delta.reasoning_content,
+            finish_reason: finish_reason
+                .as_deref()
+                .map(|r| Self::map_finish_reason(Some(r))),
+            usage,
+        })
+    }
 
+    /// 发送一次非流式请求，返回全部候选（至少一个）
+    ///
+    /// 用量属于整个请求，只记在第一个候选上
+    async fn send_completion(
+        &self,
+        openai_request: &OpenAIRequest,
+    ) -> Result<Vec<CompletionResponse>, LLMError> {
         let response = self
             .client
             .post(self.api_url("chat/completions"))
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
-            .json(&openai_request)
+            .json(openai_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(&response);
             let error_text = response.text().await.unwrap_or_default();
             error!("OpenAI API error: {} - {}", status, error_text);
 
             if status.as_u16() == 429 {
                 return Err(LLMError::RateLimitError {
-                    retry_after_secs: 60,
+                    retry_after_secs: retry_after.unwrap_or(DEFAULT_RATE_LIMIT_RETRY_SECS),
                 });
             }
             if status.as_u16() == 401 {
@@ -166,62 +294,56 @@ impl LLMPort for OpenAIAdapter {
             });
         }
 
-        let openai_response: OpenAIResponse = response
-            .json()
+        let body = response
+            .text()
             .await
-            .map_err(|e| LLMError::Unknown(e.to_string()))?;
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+        if let Some(error) = Self::parse_error_body(&body) {
+            error!("OpenAI API error: {} - {}", status, body);
+            return Err(error);
+        }
 
-        let choice = openai_response
-            .choices
-            .first()
-            .ok_or_else(|| LLMError::Unknown("No choices in response".to_string()))?;
-
-        Ok(CompletionResponse {
-            content: choice.message.content.clone(),
-            finish_reason: match choice.finish_reason.as_deref() {
-                Some("stop") => FinishReason::Stop,
-                Some("length") => FinishReason::Length,
-                Some("content_filter") => FinishReason::ContentFilter,
-                Some("function_call") | Some("tool_calls") => FinishReason::FunctionCall,
-                _ => FinishReason::Stop,
-            },
-            usage: TokenUsage {
-                prompt_tokens: openai_response.usage.prompt_tokens,
-                completion_tokens: openai_response.usage.completion_tokens,
-                total_tokens: openai_response.usage.total_tokens,
-            },
-        })
-    }
+        let openai_response: OpenAIResponse =
+            serde_json::from_str(&body).map_err(|e| LLMError::Unknown(e.to_string()))?;
 
-    async fn complete_stream(
-        &self,
-        request: CompletionRequest,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
-        let openai_request = self.to_openai_request(&request, true);
+        if openai_response.choices.is_empty() {
+            return Err(LLMError::Unknown("No choices in response".to_string()));
+        }
 
-        debug!(
-            "Sending OpenAI streaming request: {:?}",
-            openai_request.model
-        );
+        let mut usage = Some(openai_response.usage.into_token_usage());
+        Ok(openai_response
+            .choices
+            .into_iter()
+            .map(|choice| CompletionResponse {
+                content: choice.message.content,
+                finish_reason: Self::map_finish_reason(choice.finish_reason.as_deref()),
+                usage: usage.take().unwrap_or_default(),
+            })
+            .collect())
+    }
 
+    /// 建立一次流式连接，只有成功开始返回 SSE 时才交出响应
+    async fn open_stream(&self, openai_request: &OpenAIRequest) -> Result<Response, LLMError> {
         let response = self
             .client
             .post(self.api_url("chat/completions"))
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
-            .json(&openai_request)
+            .json(openai_request)
+            .headers(self.extra_headers.clone())
             .send()
             .await
             .map_err(|e| LLMError::NetworkError(e.to_string()))?;
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_secs(&response);
             let error_text = response.text().await.unwrap_or_default();
             error!("OpenAI API error: {} - {}", status, error_text);
 
             if status.as_u16() == 429 {
                 return Err(LLMError::RateLimitError {
-                    retry_after_secs: 60,
+                    retry_after_secs: retry_after.unwrap_or(DEFAULT_RATE_LIMIT_RETRY_SECS),
                 });
             }
 
@@ -231,52 +353,191 @@ impl LLMPort for OpenAIAdapter {
             });
         }
 
-        let cancel_receiver = self.cancel_sender.subscribe();
-        let byte_stream = response.bytes_stream();
+        // 没有按 SSE 返回时，响应体可能是一个完整的错误对象
+        let is_json = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+        if is_json {
+            let body = response
+                .text()
+                .await
+                .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+            error!("OpenAI API error: {} - {}", status, body);
+            return Err(Self::parse_error_body(&body).unwrap_or_else(|| {
+                LLMError::Unknown(format!("Unexpected non-streaming response: {}", body))
+            }));
+        }
 
-        let stream = byte_stream
-            .map(move |result| result.map_err(|e| LLMError::NetworkError(e.to_string())))
-            .take_while(move |_| {
-                let cancelled = *cancel_receiver.borrow();
-                async move { !cancelled }
-            })
-            .flat_map(|result| {
-                futures::stream::iter(match result {
-                    Ok(bytes) => {
-                        let text = String::from_utf8_lossy(&bytes);
-                        let chunks: Vec<Result<StreamChunk, LLMError>> = text
-                            .lines()
-                            .filter_map(Self::parse_sse_line)
-                            .filter_map(|response| {
-                                response.choices.first().and_then(|choice| {
-                                    choice.delta.content.as_ref().map(|content| {
-                                        Ok(StreamChunk {
-                                            content: content.clone(),
-                                            finish_reason: choice.finish_reason.as_deref().map(
-                                                |r| match r {
-                                                    "stop" => FinishReason::Stop,
-                                                    "length" => FinishReason::Length,
-                                                    _ => FinishReason::Stop,
-                                                },
-                                            ),
-                                            usage: None,
-                                        })
-                                    })
-                                })
-                            })
-                            .collect();
-                        chunks
-                    }
-                    Err(e) => vec![Err(e)],
-                })
+        Ok(response)
+    }
+
+    /// 发送最小的流式请求，检查是否按 SSE 返回以及是否支持流式用量
+    async fn probe(&self) -> Result<ProviderCapabilities, LLMError> {
+        let request = CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+                images: Vec::new(),
+            }],
+            self.config.default_model.clone(),
+        )
+        .with_max_tokens(1);
+        let mut openai_request = self.to_openai_request(&request, true);
+        openai_request.stream_options = Some(OpenAIStreamOptions {
+            include_usage: true,
+        });
+
+        let response = self
+            .client
+            .post(self.api_url("chat/completions"))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Content-Type", "application/json")
+            .json(&openai_request)
+            .headers(self.extra_headers.clone())
+            .send()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+
+        let status = response.status();
+        // 不认识 stream_options 的部署通常以参数错误拒绝请求
+        if matches!(status.as_u16(), 400 | 422) {
+            return Ok(ProviderCapabilities {
+                streaming: true,
+                stream_usage: false,
+            });
+        }
+        if !status.is_success() {
+            return Err(LLMError::ApiError {
+                code: status.to_string(),
+                message: response.text().await.unwrap_or_default(),
             });
+        }
+
+        let streaming = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/event-stream"));
+        let body = response
+            .text()
+            .await
+            .map_err(|e| LLMError::NetworkError(e.to_string()))?;
+        let stream_usage = body
+            .lines()
+            .filter_map(Self::parse_sse_line)
+            .any(|event| event.usage.is_some());
+
+        debug!(
+            "Probed OpenAI capabilities for {}: streaming={}, stream_usage={}",
+            self.config.id, streaming, stream_usage
+        );
+        Ok(ProviderCapabilities {
+            streaming,
+            stream_usage,
+        })
+    }
+}
+
+#[async_trait]
+impl LLMPort for OpenAIAdapter {
+    fn provider_id(&self) -> &str {
+        &self.config.id
+    }
+
+    fn provider_info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.config.id.clone(),
+            name: self.config.name.clone(),
+            provider_type: ProviderType::OpenAI,
+            models: Self::bundled_models(),
+            supports_cancellation: true,
+            supports_model_listing: true,
+            supports_assistant_prefix: false,
+        }
+    }
+
+    /// 从 `/models` 接口获取模型，获取失败时返回错误，由调用方决定是否使用内置列表
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, LLMError> {
+        let bundled = Self::bundled_models();
+        Ok(self
+            .fetch_models()
+            .await?
+            .into_iter()
+            .map(|model| model.into_model_info(&bundled))
+            .collect())
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
+        self.provider_info().ensure_supports_images(&request)?;
+        let openai_request = self.to_openai_request(&request, false);
+
+        debug!(
+            "Sending OpenAI completion request: {:?}",
+            openai_request.model
+        );
+
+        let mut choices = with_retries(self.config.max_retries, || {
+            self.send_completion(&openai_request)
+        })
+        .await?;
+        Ok(choices.swap_remove(0))
+    }
+
+    async fn complete_n(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Vec<CompletionResponse>, LLMError> {
+        self.provider_info().ensure_supports_images(&request)?;
+        let openai_request = self.to_openai_request(&request, false);
+
+        debug!(
+            "Sending OpenAI completion request for {:?} choices: {:?}",
+            openai_request.n, openai_request.model
+        );
+
+        with_retries(self.config.max_retries, || {
+            self.send_completion(&openai_request)
+        })
+        .await
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
+        self.provider_info().ensure_supports_images(&request)?;
+        let capabilities = self.capabilities.get_or_probe(|| self.probe()).await;
+        let cancellation =
+            StreamCancellation::register(&self.cancellations, request.request_id.as_deref());
+        let mut openai_request = self.to_openai_request(&request, true);
+        if capabilities.stream_usage {
+            openai_request.stream_options = Some(OpenAIStreamOptions {
+                include_usage: true,
+            });
+        }
+
+        debug!(
+            "Sending OpenAI streaming request: {:?}",
+            openai_request.model
+        );
+
+        // 只重试建立连接，已开始输出后不再重试以免重复内容
+        let response = with_retries(self.config.max_retries, || {
+            self.open_stream(&openai_request)
+        })
+        .await?;
+
+        let stream = try_chunk_stream(response.bytes_stream(), Self::parse_stream_line);
+        let stream = cancellable(stream, cancellation);
 
         Ok(Box::pin(stream))
     }
 
-    async fn cancel(&self, _request_id: &str) -> Result<(), LLMError> {
+    async fn cancel(&self, request_id: &str) -> Result<(), LLMError> {
         warn!("Cancelling OpenAI request");
-        let _ = self.cancel_sender.send(true);
+        self.cancellations.cancel(request_id);
         Ok(())
     }
 
@@ -288,12 +549,15 @@ impl LLMPort for OpenAIAdapter {
             vec![LLMChatMessage {
                 role: "user".to_string(),
                 content: "Hi".to_string(),
+                images: Vec::new(),
             }],
             "gpt-3.5-turbo",
         )
         .with_max_tokens(1);
 
-        match self.complete(request).await {
+        // 只尝试一次：重试会掩盖故障并拖慢检查
+        let openai_request = self.to_openai_request(&request, false);
+        match self.send_completion(&openai_request).await {
             Ok(_) => Ok(HealthStatus {
                 is_healthy: true,
                 latency_ms: Some(start.elapsed().as_millis() as u64),
@@ -306,22 +570,167 @@ impl LLMPort for OpenAIAdapter {
             }),
         }
     }
+
+    async fn capability_cache_usage(&self) -> Option<CapabilityCacheUsage> {
+        Some(self.capabilities.usage().await)
+    }
+
+    async fn clear_capability_cache(&self) {
+        self.capabilities.clear().await;
+    }
+
+    async fn probe_capabilities(&self) -> Result<ProviderCapabilities, LLMError> {
+        self.probe().await
+    }
 }
 
 // OpenAI API 类型定义
 
+/// `/models` 接口返回的模型
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAIModel {
+    pub id: String,
+    #[serde(default)]
+    pub owned_by: Option<String>,
+}
+
+impl OpenAIModel {
+    /// 转换为模型信息，内置列表中有同名模型时沿用其上下文长度和功能支持
+    ///
+    /// `/models` 接口不返回功能信息，其余模型的功能字段没有意义，
+    /// 查询功能支持时应以内置列表为准（见 `ModelSupport::resolve`）
+    fn into_model_info(self, bundled: &[ModelInfo]) -> ModelInfo {
+        match bundled.iter().find(|known| known.id == self.id) {
+            Some(known) => known.clone(),
+            None => ModelInfo {
+                name: self.id.clone(),
+                id: self.id,
+                context_length: DEFAULT_CONTEXT_LENGTH,
+                supports_vision: false,
+                supports_functions: false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIModelsResponse {
+    data: Vec<OpenAIModel>,
+}
+
 #[derive(Debug, Serialize)]
 struct OpenAIRequest {
     model: String,
-    messages: Vec<OpenAIMessage>,
+    messages: Vec<OpenAIRequestMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<OpenAIResponseFormat>,
+}
+
+/// OpenAI `response_format` 参数（OpenAI 兼容的适配器共用）
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum OpenAIResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: OpenAIJsonSchema },
+}
+
+#[derive(Debug, Serialize)]
+pub(super) struct OpenAIJsonSchema {
+    name: &'static str,
+    schema: serde_json::Value,
+}
+
+impl From<&ResponseFormat> for OpenAIResponseFormat {
+    fn from(format: &ResponseFormat) -> Self {
+        match format {
+            ResponseFormat::Text => Self::Text,
+            ResponseFormat::JsonObject => Self::JsonObject,
+            ResponseFormat::JsonSchema { schema } => Self::JsonSchema {
+                json_schema: OpenAIJsonSchema {
+                    name: "response",
+                    schema: schema.clone(),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIStreamOptions {
+    include_usage: bool,
+}
+
+/// 请求消息（带图片时使用多段内容）
+#[derive(Debug, Serialize)]
+struct OpenAIRequestMessage {
+    role: String,
+    content: OpenAIContent,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIImageUrl {
+    url: String,
+}
+
+impl From<&LLMChatMessage> for OpenAIRequestMessage {
+    fn from(message: &LLMChatMessage) -> Self {
+        let content = if message.images.is_empty() {
+            OpenAIContent::Text(message.content.clone())
+        } else {
+            let mut parts = vec![OpenAIContentPart::Text {
+                text: message.content.clone(),
+            }];
+            parts.extend(
+                message
+                    .images
+                    .iter()
+                    .map(|image| OpenAIContentPart::ImageUrl {
+                        image_url: OpenAIImageUrl {
+                            url: image.data_url(),
+                        },
+                    }),
+            );
+            OpenAIContent::Parts(parts)
+        };
+
+        Self {
+            role: message.role.clone(),
+            content,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -349,9 +758,38 @@ struct OpenAIUsage {
     total_tokens: u32,
 }
 
+impl OpenAIUsage {
+    fn into_token_usage(self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            total_tokens: self.total_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIErrorEnvelope {
+    error: OpenAIErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIErrorBody {
+    #[serde(default)]
+    message: String,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    /// 可能是字符串或数字
+    #[serde(default)]
+    code: Option<serde_json::Value>,
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenAIStreamResponse {
+    #[serde(default)]
     choices: Vec<OpenAIStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -360,14 +798,113 @@ struct OpenAIStreamChoice {
     finish_reason: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct OpenAIDelta {
     content: Option<String>,
+    /// 推理模型的思考内容（如 DeepSeek 的 reasoning_content）
+    #[serde(default)]
+    reasoning_content: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::conformance::{hello_request, serve, MockResponse};
     use super::*;
+    use crate::modules::chat::infrastructure::adapters::llm::{MockHttpResponse, MockHttpServer};
+    use crate::modules::chat::ports::ImageAttachment;
+    use futures::StreamExt;
+    use std::collections::HashMap;
+    use tokio::net::TcpListener;
+
+    const ERROR_BODY: &str =
+        r#"{"error":{"message":"Model overloaded","type":"server_error","code":"overloaded"}}"#;
+
+    fn adapter(base_url: String) -> OpenAIAdapter {
+        OpenAIAdapter::new(LLMProviderConfig {
+            base_url,
+            max_retries: 0,
+            ..Default::default()
+        })
+        .unwrap()
+        .with_capabilities(ProviderCapabilities::default())
+    }
+
+    fn assert_overloaded(result: Result<impl std::fmt::Debug, LLMError>) {
+        match result {
+            Err(LLMError::ApiError { code, message }) => {
+                assert_eq!(code, "overloaded");
+                assert_eq!(message, "Model overloaded");
+            }
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_reassembles_characters_split_across_chunks() {
+        let text = "你好，世界";
+        let body = format!(
+            "data: {{\"choices\":[{{\"delta\":{{\"content\":\"{}\"}}}}]}}\n\ndata: [DONE]\n\n",
+            text
+        );
+        let bytes = body.as_bytes();
+        // 逐字节切分，每个多字节字符都被拆到多个分块中
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> =
+            bytes.iter().map(|b| Ok(vec![*b])).collect();
+
+        let content: String = try_chunk_stream(
+            futures::stream::iter(chunks),
+            OpenAIAdapter::parse_stream_line,
+        )
+        .map(|chunk| chunk.unwrap().content)
+        .collect()
+        .await;
+
+        assert_eq!(content, text);
+    }
+
+    #[tokio::test]
+    async fn test_complete_maps_error_body_with_ok_status() {
+        let (base_url, _) = serve(MockResponse::split("application/json", ERROR_BODY)).await;
+        let result = adapter(base_url).complete(hello_request()).await;
+        assert_overloaded(result);
+    }
+
+    #[tokio::test]
+    async fn test_complete_stream_maps_error_body_with_ok_status() {
+        let (base_url, _) = serve(MockResponse::split("application/json", ERROR_BODY)).await;
+        let result = adapter(base_url).complete_stream(hello_request()).await;
+        assert_overloaded(result.map(|_| ()));
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_event_ends_stream() {
+        let body = format!(
+            "data: {}\n\ndata: {}\n\n",
+            r#"{"choices":[{"delta":{"content":"Hi"}}]}"#, ERROR_BODY
+        );
+        let (base_url, _) = serve(MockResponse::split("text/event-stream", &body)).await;
+        let stream = adapter(base_url)
+            .complete_stream(hello_request())
+            .await
+            .unwrap();
+
+        let mut results: Vec<_> = stream.collect().await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().content, "Hi");
+        assert_overloaded(results.pop().unwrap());
+    }
+
+    #[test]
+    fn test_parse_error_body_numeric_code_and_fallbacks() {
+        let error = OpenAIAdapter::parse_error_body(r#"{"error":{"message":"Bad","code":400}}"#);
+        assert!(matches!(error, Some(LLMError::ApiError { code, .. }) if code == "400"));
+
+        let error =
+            OpenAIAdapter::parse_error_body(r#"{"error":{"message":"Bad","type":"invalid"}}"#);
+        assert!(matches!(error, Some(LLMError::ApiError { code, .. }) if code == "invalid"));
+
+        assert!(OpenAIAdapter::parse_error_body(r#"{"choices":[]}"#).is_none());
+    }
 
     #[test]
     fn test_parse_sse_line() {
@@ -382,4 +919,448 @@ mod tests {
         let result = OpenAIAdapter::parse_sse_line(line);
         assert!(result.is_none());
     }
+
+    #[tokio::test]
+    async fn test_capabilities() {
+        let adapter = OpenAIAdapter::new(LLMProviderConfig::default()).unwrap();
+        let info = adapter.provider_info();
+
+        assert_eq!(info.provider_type, ProviderType::OpenAI);
+        assert!(info.supports_cancellation);
+        assert!(info.supports_model_listing);
+        assert!(adapter.cancel("req-1").await.is_ok());
+    }
+
+    #[test]
+    fn test_request_message_with_images() {
+        let message = LLMChatMessage {
+            role: "user".to_string(),
+            content: "What is this?".to_string(),
+            images: vec![ImageAttachment {
+                mime_type: "image/png".to_string(),
+                data: "iVBORw0KGgo=".to_string(),
+            }],
+        };
+
+        let json = serde_json::to_value(OpenAIRequestMessage::from(&message)).unwrap();
+
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][0]["text"], "What is this?");
+        assert_eq!(json["content"][1]["type"], "image_url");
+        assert_eq!(
+            json["content"][1]["image_url"]["url"],
+            "data:image/png;base64,iVBORw0KGgo="
+        );
+    }
+
+    #[test]
+    fn test_request_message_text_only() {
+        let message = LLMChatMessage {
+            role: "user".to_string(),
+            content: "Hi".to_string(),
+            images: Vec::new(),
+        };
+
+        let json = serde_json::to_value(OpenAIRequestMessage::from(&message)).unwrap();
+        assert_eq!(json["content"], "Hi");
+    }
+
+    #[tokio::test]
+    async fn test_images_rejected_for_non_vision_model() {
+        let request = CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "What is this?".to_string(),
+                images: vec![ImageAttachment {
+                    mime_type: "image/png".to_string(),
+                    data: "iVBORw0KGgo=".to_string(),
+                }],
+            }],
+            "gpt-3.5-turbo",
+        );
+        // 地址不可达，返回 InvalidRequest 说明请求未被发出
+        let adapter = adapter("http://127.0.0.1:1".to_string());
+
+        assert!(matches!(
+            adapter.complete(request.clone()).await,
+            Err(LLMError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            adapter.complete_stream(request).await,
+            Err(LLMError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_probe_detects_stream_usage() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"H\"},\"finish_reason\":\"length\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":8,\"completion_tokens\":1,\"total_tokens\":9}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (base_url, _) = serve(MockResponse::split("text/event-stream", body)).await;
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let capabilities = adapter.probe_capabilities().await.unwrap();
+        assert_eq!(
+            capabilities,
+            ProviderCapabilities {
+                streaming: true,
+                stream_usage: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_stream_options_serialized_only_when_set() {
+        let adapter = OpenAIAdapter::new(LLMProviderConfig::default()).unwrap();
+        let mut request = adapter.to_openai_request(&hello_request(), true);
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("stream_options").is_none());
+
+        request.stream_options = Some(OpenAIStreamOptions {
+            include_usage: true,
+        });
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn test_response_format_serialized() {
+        let adapter = OpenAIAdapter::new(LLMProviderConfig::default()).unwrap();
+
+        let json = serde_json::to_value(adapter.to_openai_request(&hello_request(), true)).unwrap();
+        assert!(json.get("response_format").is_none());
+
+        let request = hello_request().with_response_format(ResponseFormat::JsonObject);
+        let body = serde_json::to_string(&adapter.to_openai_request(&request, false)).unwrap();
+        assert!(body.contains(r#""response_format":{"type":"json_object"}"#));
+
+        let schema = serde_json::json!({ "type": "object" });
+        let request = hello_request().with_response_format(ResponseFormat::JsonSchema {
+            schema: schema.clone(),
+        });
+        let json = serde_json::to_value(adapter.to_openai_request(&request, false)).unwrap();
+        assert_eq!(json["response_format"]["type"], "json_schema");
+        assert_eq!(json["response_format"]["json_schema"]["schema"], schema);
+    }
+
+    #[test]
+    fn test_penalties_serialized_only_when_set() {
+        let adapter = OpenAIAdapter::new(LLMProviderConfig::default()).unwrap();
+
+        let json = serde_json::to_value(adapter.to_openai_request(&hello_request(), true)).unwrap();
+        for field in ["top_p", "presence_penalty", "frequency_penalty"] {
+            assert!(json.get(field).is_none(), "{} should be omitted", field);
+        }
+
+        let request = hello_request()
+            .with_top_p(0.5)
+            .with_presence_penalty(0.25)
+            .with_frequency_penalty(-1.0);
+        let json = serde_json::to_value(adapter.to_openai_request(&request, true)).unwrap();
+        assert_eq!(json["top_p"], 0.5);
+        assert_eq!(json["presence_penalty"], 0.25);
+        assert_eq!(json["frequency_penalty"], -1.0);
+    }
+
+    #[test]
+    fn test_n_serialized_only_for_non_streaming_requests() {
+        let adapter = OpenAIAdapter::new(LLMProviderConfig::default()).unwrap();
+        let request = hello_request().with_n(3);
+
+        let json = serde_json::to_value(adapter.to_openai_request(&request, false)).unwrap();
+        assert_eq!(json["n"], 3);
+        let json = serde_json::to_value(adapter.to_openai_request(&request, true)).unwrap();
+        assert!(json.get("n").is_none());
+        let json =
+            serde_json::to_value(adapter.to_openai_request(&hello_request(), false)).unwrap();
+        assert!(json.get("n").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_complete_n_parses_all_choices() {
+        let body = serde_json::json!({
+            "choices": [
+                { "index": 0, "message": { "role": "assistant", "content": "Hi!" }, "finish_reason": "stop" },
+                { "index": 1, "message": { "role": "assistant", "content": "Hello there" }, "finish_reason": "length" }
+            ],
+            "usage": { "prompt_tokens": 3, "completion_tokens": 6, "total_tokens": 9 }
+        })
+        .to_string();
+        let server = MockHttpServer::fixed("200 OK", body).await;
+
+        let responses = adapter(server.url())
+            .complete_n(hello_request().with_n(2))
+            .await
+            .unwrap();
+
+        let contents: Vec<_> = responses.iter().map(|r| r.content.as_str()).collect();
+        assert_eq!(contents, ["Hi!", "Hello there"]);
+        assert_eq!(responses[1].finish_reason, FinishReason::Length);
+        assert_eq!(responses[0].usage.total_tokens, 9);
+        assert_eq!(responses[1].usage.total_tokens, 0);
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[test]
+    fn test_usage_only_event_becomes_chunk() {
+        let line = r#"data: {"choices":[],"usage":{"prompt_tokens":8,"completion_tokens":4,"total_tokens":12}}"#;
+        let chunk = OpenAIAdapter::parse_sse_line(line)
+            .and_then(OpenAIAdapter::to_stream_chunk)
+            .unwrap();
+
+        assert!(chunk.content.is_empty());
+        assert!(chunk.finish_reason.is_none());
+        assert_eq!(chunk.usage.unwrap().total_tokens, 12);
+    }
+
+    #[tokio::test]
+    async fn test_stream_surfaces_usage_on_terminating_chunk() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":8,\"completion_tokens\":2,\"total_tokens\":10}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (base_url, _) = serve(MockResponse::split("text/event-stream", body)).await;
+        let stream = adapter(base_url)
+            .with_capabilities(ProviderCapabilities {
+                streaming: true,
+                stream_usage: true,
+            })
+            .complete_stream(hello_request())
+            .await
+            .unwrap();
+
+        let chunks: Vec<StreamChunk> = stream.map(|chunk| chunk.unwrap()).collect().await;
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].finish_reason, Some(FinishReason::Stop));
+        // 用量事件没有候选，不能被当作空事件丢弃
+        let usage = chunks.last().and_then(|c| c.usage.as_ref()).unwrap();
+        assert_eq!(usage.prompt_tokens, 8);
+        assert_eq!(usage.total_tokens, 10);
+    }
+
+    /// 返回一次成功响应的服务器
+    async fn serve_success() -> MockHttpServer {
+        let body = serde_json::json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": "Hi" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+        });
+        MockHttpServer::fixed("200 OK", body.to_string()).await
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_sent_with_request() {
+        let server = serve_success().await;
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url: server.url(),
+            api_key: "sk-test".to_string(),
+            extra_headers: HashMap::from([
+                ("X-Title".to_string(), "Kizuna".to_string()),
+                ("HTTP-Referer".to_string(), "https://kizuna.app".to_string()),
+            ]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        adapter.complete(hello_request()).await.unwrap();
+        let head = &server.requests()[0].head;
+        assert!(head.contains("\r\nx-title: kizuna"), "{}", head);
+        assert!(head.contains("\r\nhttp-referer: https://kizuna.app"));
+        // 未配置的标准请求头保持不变
+        assert!(head.contains("\r\nauthorization: bearer sk-test"));
+        assert!(head.contains("\r\ncontent-type: application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_override_standard_headers_when_set() {
+        let server = serve_success().await;
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url: server.url(),
+            api_key: "sk-test".to_string(),
+            extra_headers: HashMap::from([(
+                "Authorization".to_string(),
+                "Token gateway".to_string(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        adapter.complete(hello_request()).await.unwrap();
+        let head = &server.requests()[0].head;
+        assert!(head.contains("\r\nauthorization: token gateway"));
+        assert!(!head.contains("bearer sk-test"));
+        assert_eq!(head.matches("\r\nauthorization:").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_complete_retries_rate_limit_until_success() {
+        let success = serde_json::json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": "Hello!" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 3, "completion_tokens": 2, "total_tokens": 5 }
+        })
+        .to_string();
+        let rate_limited = MockHttpResponse::new(
+            "429 Too Many Requests",
+            r#"{"error":{"message":"Slow down","code":"rate_limit"}}"#,
+        )
+        .with_header("retry-after", "0");
+        let server = MockHttpServer::start(vec![
+            rate_limited.clone(),
+            rate_limited,
+            MockHttpResponse::ok(success),
+        ])
+        .await;
+
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url: server.url(),
+            max_retries: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        let response = adapter.complete(hello_request()).await.unwrap();
+
+        assert_eq!(response.content, "Hello!");
+        assert_eq!(response.usage.total_tokens, 5);
+        assert_eq!(server.request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_does_not_retry() {
+        let server = MockHttpServer::start(vec![MockHttpResponse::new(
+            "429 Too Many Requests",
+            r#"{"error":{"message":"Slow down"}}"#,
+        )
+        .with_header("retry-after", "0")])
+        .await;
+
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url: server.url(),
+            max_retries: 3,
+            ..Default::default()
+        })
+        .unwrap();
+        let status = adapter.health_check().await.unwrap();
+
+        assert!(!status.is_healthy);
+        assert_eq!(server.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_complete_stops_after_max_retries() {
+        let server = MockHttpServer::start(vec![MockHttpResponse::new(
+            "429 Too Many Requests",
+            r#"{"error":{"message":"Slow down"}}"#,
+        )
+        .with_header("retry-after", "0")])
+        .await;
+
+        let adapter = OpenAIAdapter::new(LLMProviderConfig {
+            base_url: server.url(),
+            max_retries: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let result = adapter.complete(hello_request()).await;
+
+        assert!(matches!(
+            result,
+            Err(LLMError::RateLimitError {
+                retry_after_secs: 0
+            })
+        ));
+        assert_eq!(server.request_count(), 2);
+    }
+
+    #[test]
+    fn test_adapter_with_proxy() {
+        let with_proxy = |proxy_url: &str| {
+            OpenAIAdapter::new(LLMProviderConfig {
+                proxy_url: Some(proxy_url.to_string()),
+                ..Default::default()
+            })
+        };
+
+        assert!(with_proxy("http://127.0.0.1:7890").is_ok());
+        assert!(with_proxy("socks5://127.0.0.1:1080").is_ok());
+        assert!(matches!(
+            with_proxy("ftp://127.0.0.1:21"),
+            Err(LLMError::InvalidRequest(_))
+        ));
+    }
+
+    const MODELS_BODY: &str = r#"{
+        "object": "list",
+        "data": [
+            { "id": "gpt-4o", "object": "model", "created": 1715367049, "owned_by": "system" },
+            { "id": "qwen2.5-72b-instruct", "object": "model", "owned_by": "alibaba" },
+            { "id": "local-model", "object": "model" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_models_response() {
+        let models = OpenAIAdapter::parse_models(MODELS_BODY).unwrap();
+        let parsed: Vec<(&str, Option<&str>)> = models
+            .iter()
+            .map(|m| (m.id.as_str(), m.owned_by.as_deref()))
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("gpt-4o", Some("system")),
+                ("qwen2.5-72b-instruct", Some("alibaba")),
+                ("local-model", None),
+            ]
+        );
+
+        assert!(matches!(
+            OpenAIAdapter::parse_models(ERROR_BODY),
+            Err(LLMError::ApiError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_models_uses_models_endpoint() {
+        let server = MockHttpServer::fixed("200 OK", MODELS_BODY).await;
+        let models = adapter(server.url()).list_models().await.unwrap();
+        assert_eq!(server.request_count(), 1);
+
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["gpt-4o", "qwen2.5-72b-instruct", "local-model"]);
+        // 内置列表中的模型沿用已知信息，其余使用默认上下文长度
+        assert_eq!(models[0].context_length, 128000);
+        assert!(models[0].supports_vision);
+        assert_eq!(models[1].context_length, DEFAULT_CONTEXT_LENGTH);
+    }
+
+    #[tokio::test]
+    async fn test_list_models_returns_fetch_errors() {
+        // 没有服务监听的端口
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        assert!(matches!(
+            adapter(base_url).list_models().await,
+            Err(LLMError::NetworkError(_))
+        ));
+
+        let server = MockHttpServer::fixed("404 Not Found", "{}").await;
+        assert!(matches!(
+            adapter(server.url()).list_models().await,
+            Err(LLMError::ApiError { code, .. }) if code.starts_with("404")
+        ));
+    }
 }
@@ -1,10 +1,26 @@
+use futures::StreamExt;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-use crate::modules::chat::ports::{LLMError, LLMPort, LLMProviderConfig, ProviderType};
+use crate::modules::chat::infrastructure::{CacheCounters, CacheStats};
+use crate::modules::chat::ports::{
+    HealthStatus, LLMError, LLMPort, LLMProviderConfig, ProviderType,
+};
 
-use super::{ClaudeAdapter, OllamaAdapter, OpenAIAdapter};
+use super::{
+    ClaudeAdapter, GeminiAdapter, OllamaAdapter, OpenAIAdapter, ProviderHealthCache, ScriptedLLM,
+};
+
+/// 提供商的健康状态概况
+#[derive(Debug, Clone)]
+pub struct ProviderHealthSummary {
+    pub provider_id: String,
+    /// 提供商显示名称
+    pub name: String,
+    pub status: HealthStatus,
+}
 
 /// LLM 适配器注册表
 ///
@@ -14,6 +30,10 @@ pub struct LLMAdapterRegistry {
     instances: RwLock<HashMap<String, Arc<dyn LLMPort>>>,
     /// 提供商配置
     configs: RwLock<HashMap<String, LLMProviderConfig>>,
+    /// 提供商健康状态
+    health: ProviderHealthCache,
+    /// 适配器实例查找的命中统计
+    cache: CacheCounters,
 }
 
 impl LLMAdapterRegistry {
@@ -22,6 +42,8 @@ impl LLMAdapterRegistry {
         Self {
             instances: RwLock::new(HashMap::new()),
             configs: RwLock::new(HashMap::new()),
+            health: ProviderHealthCache::new(),
+            cache: CacheCounters::new(),
         }
     }
 
@@ -43,11 +65,25 @@ impl LLMAdapterRegistry {
         Ok(())
     }
 
+    /// 注册已创建的适配器实例（用于自定义或测试用适配器）
+    pub async fn register_adapter(&self, config: LLMProviderConfig, adapter: Arc<dyn LLMPort>) {
+        let id = config.id.clone();
+
+        {
+            let mut instances = self.instances.write().await;
+            instances.insert(id.clone(), adapter);
+        }
+        {
+            let mut configs = self.configs.write().await;
+            configs.insert(id, config);
+        }
+    }
+
     /// 获取适配器（同步访问缓存）
     pub fn get(&self, provider_id: &str) -> Option<Arc<dyn LLMPort>> {
         // 使用 try_read 进行非阻塞访问
         if let Ok(instances) = self.instances.try_read() {
-            instances.get(provider_id).cloned()
+            self.cache.record(instances.get(provider_id).cloned())
         } else {
             None
         }
@@ -56,7 +92,23 @@ impl LLMAdapterRegistry {
     /// 获取适配器（异步）
     pub async fn get_async(&self, provider_id: &str) -> Option<Arc<dyn LLMPort>> {
         let instances = self.instances.read().await;
-        instances.get(provider_id).cloned()
+        self.cache.record(instances.get(provider_id).cloned())
+    }
+
+    /// 获取适配器及其默认模型（异步）
+    ///
+    /// 调用方持有返回的适配器 `Arc`，之后注册表中的变更（如删除提供商）不影响已取得的实例
+    pub async fn resolve(&self, provider_id: &str) -> Option<(Arc<dyn LLMPort>, Option<String>)> {
+        let adapter = self
+            .cache
+            .record(self.instances.read().await.get(provider_id).cloned())?;
+        let default_model = self
+            .configs
+            .read()
+            .await
+            .get(provider_id)
+            .map(|c| c.default_model.clone());
+        Some((adapter, default_model))
     }
 
     /// 获取默认模型
@@ -69,21 +121,31 @@ impl LLMAdapterRegistry {
     }
 
     /// 获取或创建适配器实例
+    ///
+    /// 配置与缓存实例创建时的配置不同（如修改了地址、模型、代理或请求头）时重建适配器，
+    /// 旧实例的 HTTP 客户端、能力缓存和健康状态随之丢弃
     pub async fn get_or_create(
         &self,
         config: &LLMProviderConfig,
     ) -> Result<Arc<dyn LLMPort>, LLMError> {
         // 检查缓存
-        {
+        let changed = {
             let instances = self.instances.read().await;
-            if let Some(instance) = instances.get(&config.id) {
-                return Ok(instance.clone());
+            let configs = self.configs.read().await;
+            let unchanged = configs.get(&config.id) == Some(config);
+            let cached = instances.get(&config.id).filter(|_| unchanged).cloned();
+            if let Some(instance) = self.cache.record(cached) {
+                return Ok(instance);
             }
-        }
+            configs.contains_key(&config.id)
+        };
 
         // 创建新实例
         let adapter = self.create_adapter(config)?;
         let adapter: Arc<dyn LLMPort> = Arc::from(adapter);
+        if changed {
+            self.health.remove(&config.id);
+        }
 
         // 缓存
         {
@@ -104,23 +166,181 @@ impl LLMAdapterRegistry {
             ProviderType::OpenAI => Ok(Box::new(OpenAIAdapter::new(config.clone())?)),
             ProviderType::Claude => Ok(Box::new(ClaudeAdapter::new(config.clone())?)),
             ProviderType::Ollama => Ok(Box::new(OllamaAdapter::new(config.clone())?)),
+            ProviderType::Gemini => Ok(Box::new(GeminiAdapter::new(config.clone())?)),
             ProviderType::Custom => {
                 // 自定义提供商使用与 OpenAI 兼容的 API
                 Ok(Box::new(OpenAIAdapter::new(config.clone())?))
             }
+            ProviderType::Demo => Ok(Box::new(ScriptedLLM::demo().with_id(config.id.clone()))),
         }
     }
 
-    /// 清除指定提供商的缓存
-    pub async fn invalidate(&self, provider_id: &str) {
-        {
-            let mut instances = self.instances.write().await;
-            instances.remove(provider_id);
+    /// 获取健康状态缓存
+    pub fn health(&self) -> &ProviderHealthCache {
+        &self.health
+    }
+
+    /// 对提供商执行健康检查并记录结果
+    pub async fn check_health(&self, provider_id: &str) -> Result<HealthStatus, LLMError> {
+        let adapter = self
+            .get_async(provider_id)
+            .await
+            .ok_or_else(|| LLMError::ProviderNotAvailable(provider_id.to_string()))?;
+
+        let status = adapter
+            .health_check()
+            .await
+            .unwrap_or_else(|e| HealthStatus::unhealthy(e.to_string()));
+        self.health.record_status(provider_id, &status);
+        Ok(status)
+    }
+
+    /// 并发检查所有已注册的提供商并记录结果，按提供商 ID 排序返回
+    ///
+    /// 每个检查单独限时，出错或超时的提供商报告为不健康
+    pub async fn health_check_all(&self, timeout: Duration) -> Vec<(String, HealthStatus)> {
+        let mut adapters: Vec<(String, Arc<dyn LLMPort>)> = self
+            .instances
+            .read()
+            .await
+            .iter()
+            .map(|(id, adapter)| (id.clone(), adapter.clone()))
+            .collect();
+        adapters.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let checks = adapters.into_iter().map(|(id, adapter)| async move {
+            let status = self
+                .timed_health_check(&id, adapter.as_ref(), timeout)
+                .await;
+            (id, status)
+        });
+        futures::future::join_all(checks).await
+    }
+
+    /// 汇总已配置提供商的健康状态，按提供商 ID 排序返回
+    ///
+    /// 已有适配器实例的提供商直接使用该实例，尚未使用过的提供商按配置创建；
+    /// 不超过 `max_age` 的检查结果直接取自缓存，其余提供商最多 `concurrency` 个同时检查，
+    /// 每个检查单独限时。无法创建适配器的提供商报告为不健康
+    pub async fn providers_health(
+        &self,
+        mut configured: Vec<LLMProviderConfig>,
+        timeout: Duration,
+        max_age: Duration,
+        concurrency: usize,
+    ) -> Vec<ProviderHealthSummary> {
+        configured.sort_by(|a, b| a.id.cmp(&b.id));
+
+        // 先创建全部检查（不会立即执行），由 buffered 限制同时进行的数量
+        let checks: Vec<_> = configured
+            .into_iter()
+            .map(|config| async move {
+                let status = match self.health.recent_status(&config.id, max_age) {
+                    Some(status) => status,
+                    None => match self.configured_adapter(&config).await {
+                        Ok(adapter) => {
+                            self.timed_health_check(&config.id, adapter.as_ref(), timeout)
+                                .await
+                        }
+                        Err(e) => HealthStatus::unhealthy(e.to_string()),
+                    },
+                };
+                let name = if config.name.is_empty() {
+                    config.id.clone()
+                } else {
+                    config.name
+                };
+                ProviderHealthSummary {
+                    provider_id: config.id,
+                    name,
+                    status,
+                }
+            })
+            .collect();
+        futures::stream::iter(checks)
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// 已配置提供商的适配器：优先使用已有实例（可能由带有更多选项的请求配置创建），没有时按配置创建
+    async fn configured_adapter(
+        &self,
+        config: &LLMProviderConfig,
+    ) -> Result<Arc<dyn LLMPort>, LLMError> {
+        let existing = self.instances.read().await.get(&config.id).cloned();
+        match existing {
+            Some(adapter) => Ok(adapter),
+            None => self.get_or_create(config).await,
         }
+    }
+
+    /// 限时执行健康检查并记录结果，出错或超时报告为不健康
+    async fn timed_health_check(
+        &self,
+        provider_id: &str,
+        adapter: &dyn LLMPort,
+        timeout: Duration,
+    ) -> HealthStatus {
+        let status = match tokio::time::timeout(timeout, adapter.health_check()).await {
+            Ok(Ok(status)) => status,
+            Ok(Err(e)) => HealthStatus::unhealthy(e.to_string()),
+            Err(_) => HealthStatus::unhealthy(format!(
+                "Health check timed out after {}ms",
+                timeout.as_millis()
+            )),
+        };
+        self.health.record_status(provider_id, &status);
+        status
+    }
+
+    /// 首选提供商最近不健康时，按 `candidates` 的顺序返回第一个可用的替代提供商
+    ///
+    /// `candidates` 为用户配置的提供商 ID（按显示顺序），只考虑其中已创建适配器且不是
+    /// 最近不健康的提供商。首选提供商健康或状态未知、或没有可用替代时返回 `None`
+    pub async fn healthy_fallback(&self, preferred: &str, candidates: &[String]) -> Option<String> {
+        if !self.health.is_unhealthy(preferred) {
+            return None;
+        }
+
+        let instances = self.instances.read().await;
+        candidates
+            .iter()
+            .find(|id| {
+                id.as_str() != preferred
+                    && instances.contains_key(id.as_str())
+                    && !self.health.is_unhealthy(id)
+            })
+            .cloned()
+    }
+
+    /// 通知所有已缓存的适配器取消指定请求，关闭其进行中的流式连接
+    ///
+    /// 只有持有该请求的适配器会响应，其余适配器的取消是空操作
+    pub async fn cancel_request(&self, request_id: &str) {
+        let adapters: Vec<Arc<dyn LLMPort>> =
+            self.instances.read().await.values().cloned().collect();
+        for adapter in adapters {
+            if let Err(e) = adapter.cancel(request_id).await {
+                tracing::debug!("Failed to cancel request {} on adapter: {}", request_id, e);
+            }
+        }
+    }
+
+    /// 清除指定提供商的缓存，下次使用时重新创建适配器和 HTTP 客户端
+    ///
+    /// 返回是否确实移除了缓存的适配器
+    pub async fn invalidate(&self, provider_id: &str) -> bool {
+        self.health.remove(provider_id);
+        let removed = {
+            let mut instances = self.instances.write().await;
+            instances.remove(provider_id).is_some()
+        };
         {
             let mut configs = self.configs.write().await;
             configs.remove(provider_id);
         }
+        removed
     }
 
     /// 清除所有缓存
@@ -141,6 +361,48 @@ impl LLMAdapterRegistry {
         instances.len()
     }
 
+    /// 适配器实例缓存的命中统计
+    pub async fn cache_stats(&self) -> CacheStats {
+        self.cache.snapshot("llm_adapters", self.count().await)
+    }
+
+    /// 重置命中统计
+    ///
+    /// 缓存的实例即已注册的提供商，不会被清除
+    pub fn reset_cache_stats(&self) {
+        self.cache.reset();
+    }
+
+    /// 各适配器能力探测缓存的汇总统计，条目数为已缓存探测结果的适配器数
+    pub async fn capability_cache_stats(&self) -> CacheStats {
+        let mut stats = CacheStats {
+            name: "capabilities".to_string(),
+            hits: 0,
+            misses: 0,
+            entries: 0,
+        };
+        for adapter in self.adapters().await {
+            if let Some(usage) = adapter.capability_cache_usage().await {
+                stats.hits += usage.hits;
+                stats.misses += usage.misses;
+                stats.entries += usize::from(usage.cached);
+            }
+        }
+        stats
+    }
+
+    /// 清除各适配器缓存的能力探测结果，下次使用时重新探测
+    pub async fn clear_capability_caches(&self) {
+        for adapter in self.adapters().await {
+            adapter.clear_capability_cache().await;
+        }
+    }
+
+    /// 当前的适配器实例（不持有锁，供逐个等待的操作使用）
+    async fn adapters(&self) -> Vec<Arc<dyn LLMPort>> {
+        self.instances.read().await.values().cloned().collect()
+    }
+
     /// 列出所有提供商 ID
     pub async fn list_providers(&self) -> Vec<String> {
         let configs = self.configs.read().await;
@@ -157,6 +419,9 @@ impl Default for LLMAdapterRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::modules::chat::infrastructure::adapters::llm::{MockHttpServer, RecordedRequest};
+    use crate::modules::chat::infrastructure::DEMO_MODEL;
+    use crate::modules::chat::ports::HttpClientOptions;
 
     #[tokio::test]
     async fn test_registry_caching() {
@@ -169,7 +434,15 @@ mod tests {
             api_key: "test-key".to_string(),
             default_model: "gpt-3.5-turbo".to_string(),
             timeout_secs: 60,
+            cold_start_timeout_secs: 300,
             max_retries: 3,
+            http: HttpClientOptions::default(),
+            proxy_url: None,
+            extra_headers: HashMap::new(),
+            keep_alive: None,
+            num_ctx: None,
+            anthropic_version: "2023-06-01".to_string(),
+            anthropic_beta: Vec::new(),
         };
 
         // 第一次获取
@@ -180,6 +453,201 @@ mod tests {
         // 验证是同一个实例（通过 provider_id）
         assert_eq!(adapter1.provider_id(), adapter2.provider_id());
         assert_eq!(registry.count().await, 1);
+
+        let stats = registry.cache_stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        assert!(registry.get_async("missing").await.is_none());
+        assert!(registry.resolve("test").await.is_some());
+        let stats = registry.cache_stats().await;
+        assert_eq!((stats.hits, stats.misses), (2, 2));
+
+        registry.reset_cache_stats();
+        let stats = registry.cache_stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_capability_caches_reported_and_cleared() {
+        use crate::modules::chat::ports::ProviderCapabilities;
+
+        let registry = LLMAdapterRegistry::new();
+        for id in ["probed", "unprobed"] {
+            let config = LLMProviderConfig {
+                id: id.to_string(),
+                ..Default::default()
+            };
+            let adapter = OpenAIAdapter::new(config.clone()).unwrap();
+            let adapter = if id == "probed" {
+                adapter.with_capabilities(ProviderCapabilities::default())
+            } else {
+                adapter
+            };
+            registry.register_adapter(config, Arc::new(adapter)).await;
+        }
+        // 不缓存能力的适配器不计入
+        registry
+            .register_adapter(
+                LLMProviderConfig {
+                    id: "scripted".to_string(),
+                    ..Default::default()
+                },
+                Arc::new(ScriptedLLM::new()),
+            )
+            .await;
+
+        let stats = registry.capability_cache_stats().await;
+        assert_eq!(stats.name, "capabilities");
+        assert_eq!(stats.entries, 1);
+
+        registry.clear_capability_caches().await;
+        assert_eq!(registry.capability_cache_stats().await.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_changed_config_rebuilds_adapter() {
+        use crate::modules::chat::ports::CompletionRequest;
+
+        const BODY: &str = r#"{"choices":[{"message":{"role":"assistant","content":"Hi"},"finish_reason":"stop"}],"usage":{"prompt_tokens":1,"completion_tokens":1,"total_tokens":2}}"#;
+        let old_server = MockHttpServer::fixed("200 OK", BODY).await;
+        let new_server = MockHttpServer::fixed("200 OK", BODY).await;
+        let registry = LLMAdapterRegistry::new();
+        let mut config = LLMProviderConfig {
+            id: "test".to_string(),
+            provider_type: ProviderType::OpenAI,
+            base_url: old_server.url(),
+            default_model: "old-model".to_string(),
+            ..Default::default()
+        };
+
+        let first = registry.get_or_create(&config).await.unwrap();
+        registry
+            .health()
+            .record_status("test", &HealthStatus::unhealthy("old endpoint down"));
+
+        config.base_url = new_server.url();
+        config.default_model = "new-model".to_string();
+        let second = registry.get_or_create(&config).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(registry.count().await, 1);
+        assert_eq!(
+            registry.get_default_model("test").as_deref(),
+            Some("new-model")
+        );
+        // 旧地址的健康状态不再适用
+        assert!(!registry.health().is_unhealthy("test"));
+
+        second
+            .complete(CompletionRequest::new(Vec::new(), "new-model"))
+            .await
+            .unwrap();
+        assert_eq!(old_server.request_count(), 0);
+        assert_eq!(new_server.request_count(), 1);
+
+        // 配置不变时复用实例
+        let third = registry.get_or_create(&config).await.unwrap();
+        assert!(Arc::ptr_eq(&second, &third));
+    }
+
+    /// 通过注册表取得适配器并发送一次请求（忽略结果），返回服务器收到的最后一个请求
+    async fn send_through(
+        registry: &LLMAdapterRegistry,
+        config: &LLMProviderConfig,
+        server: &MockHttpServer,
+    ) -> RecordedRequest {
+        use crate::modules::chat::ports::{CompletionRequest, LLMChatMessage};
+
+        let adapter = registry.get_or_create(config).await.unwrap();
+        let request = CompletionRequest::new(
+            vec![LLMChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+                images: Vec::new(),
+            }],
+            "test-model",
+        );
+        let _ = adapter.complete(request).await;
+        server.requests().pop().expect("server received no request")
+    }
+
+    #[tokio::test]
+    async fn test_each_changed_option_applies_to_next_request() {
+        let server = MockHttpServer::fixed("200 OK", "{}").await;
+        let proxy = MockHttpServer::fixed("200 OK", "{}").await;
+        let registry = LLMAdapterRegistry::new();
+        let base = |provider_type| LLMProviderConfig {
+            id: "test".to_string(),
+            provider_type,
+            base_url: server.url(),
+            max_retries: 0,
+            ..Default::default()
+        };
+
+        // 额外请求头
+        let mut config = base(ProviderType::OpenAI);
+        send_through(&registry, &config, &server).await;
+        config.extra_headers = HashMap::from([("X-Title".to_string(), "Kizuna".to_string())]);
+        let request = send_through(&registry, &config, &server).await;
+        assert!(request.head.contains("\r\nx-title: kizuna"));
+
+        // HTTP 客户端选项
+        config.http.http2_prior_knowledge = true;
+        let request = send_through(&registry, &config, &server).await;
+        assert!(request.head.starts_with("pri * http/2.0"));
+        config.http.http2_prior_knowledge = false;
+
+        // 代理
+        config.proxy_url = Some(proxy.url());
+        let request = send_through(&registry, &config, &proxy).await;
+        assert!(request.head.starts_with(&format!("post {}/", server.url())));
+
+        // Ollama 的 keep_alive 和 num_ctx
+        let mut config = base(ProviderType::Ollama);
+        let request = send_through(&registry, &config, &server).await;
+        assert!(!request.body.contains("keep_alive"));
+        config.keep_alive = Some("10m".to_string());
+        config.num_ctx = Some(8192);
+        let request = send_through(&registry, &config, &server).await;
+        assert!(request.body.contains(r#""keep_alive":"10m""#));
+        assert!(request.body.contains(r#""num_ctx":8192"#));
+
+        // Anthropic 版本和 beta 功能
+        let mut config = base(ProviderType::Claude);
+        send_through(&registry, &config, &server).await;
+        config.anthropic_version = "2024-10-22".to_string();
+        config.anthropic_beta = vec!["prompt-caching-2024-07-31".to_string()];
+        let request = send_through(&registry, &config, &server).await;
+        assert!(request
+            .head
+            .contains("\r\nanthropic-version: 2024-10-22\r\n"));
+        assert!(request
+            .head
+            .contains("\r\nanthropic-beta: prompt-caching-2024-07-31"));
+    }
+
+    #[tokio::test]
+    async fn test_demo_provider_replies_offline() {
+        use crate::modules::chat::ports::CompletionRequest;
+
+        let registry = LLMAdapterRegistry::new();
+        let config = LLMProviderConfig {
+            id: "demo-provider".to_string(),
+            provider_type: ProviderType::Demo,
+            default_model: DEMO_MODEL.to_string(),
+            ..Default::default()
+        };
+
+        // 无需 base_url 和 API Key
+        let adapter = registry.get_or_create(&config).await.unwrap();
+        assert_eq!(adapter.provider_id(), "demo-provider");
+        assert!(adapter.provider_info().is_type(ProviderType::Demo));
+
+        let response = adapter
+            .complete(CompletionRequest::new(Vec::new(), DEMO_MODEL))
+            .await
+            .unwrap();
+        assert!(response.content.contains("演示模式"));
+        assert!(response.usage.total_tokens > 0);
     }
 
     #[tokio::test]
@@ -197,4 +665,141 @@ mod tests {
         registry.invalidate("test").await;
         assert_eq!(registry.count().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_invalidate_rebuilds_adapter_on_next_use() {
+        let registry = LLMAdapterRegistry::new();
+        let config = LLMProviderConfig {
+            id: "test".to_string(),
+            provider_type: ProviderType::OpenAI,
+            ..Default::default()
+        };
+
+        let first = registry.get_or_create(&config).await.unwrap();
+        assert!(registry.invalidate("test").await);
+        assert!(registry.get_async("test").await.is_none());
+        // 没有缓存时不算移除
+        assert!(!registry.invalidate("test").await);
+
+        let second = registry.get_or_create(&config).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(registry.count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_all_times_out_each_provider() {
+        let registry = LLMAdapterRegistry::new();
+        let providers = [
+            ("healthy", ScriptedLLM::new()),
+            ("hanging", ScriptedLLM::new().delay(Duration::from_secs(30))),
+            (
+                "failing",
+                ScriptedLLM::new().error(LLMError::NetworkError("down".into())),
+            ),
+        ];
+        for (id, llm) in providers {
+            let config = LLMProviderConfig {
+                id: id.to_string(),
+                provider_type: ProviderType::Custom,
+                ..Default::default()
+            };
+            registry.register_adapter(config, Arc::new(llm)).await;
+        }
+
+        let started = std::time::Instant::now();
+        let results = registry.health_check_all(Duration::from_millis(100)).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["failing", "hanging", "healthy"]);
+        assert!(!results[0].1.is_healthy);
+        assert!(!results[1].1.is_healthy);
+        assert!(results[1]
+            .1
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("timed out"));
+        assert!(results[2].1.is_healthy);
+        assert!(registry.health().is_unhealthy("hanging"));
+        assert!(!registry.health().is_unhealthy("healthy"));
+    }
+
+    #[tokio::test]
+    async fn test_providers_health_reports_each_provider() {
+        let registry = LLMAdapterRegistry::new();
+        let providers = [
+            ("healthy", "Local Model", ScriptedLLM::new()),
+            (
+                "failing",
+                "",
+                ScriptedLLM::new().error(LLMError::NetworkError("down".into())),
+            ),
+            ("removed", "Removed", ScriptedLLM::new()),
+        ];
+        let mut configured = Vec::new();
+        for (id, name, llm) in providers {
+            let config = LLMProviderConfig {
+                id: id.to_string(),
+                name: name.to_string(),
+                provider_type: ProviderType::Custom,
+                ..Default::default()
+            };
+            if id != "removed" {
+                configured.push(config.clone());
+            }
+            registry.register_adapter(config, Arc::new(llm)).await;
+        }
+        // 已配置但尚未使用过的提供商
+        configured.push(LLMProviderConfig {
+            id: "unused".to_string(),
+            name: "Unused".to_string(),
+            provider_type: ProviderType::Demo,
+            ..Default::default()
+        });
+
+        let max_age = Duration::from_secs(30);
+        let summaries = registry
+            .providers_health(configured.clone(), Duration::from_secs(5), max_age, 1)
+            .await;
+        let rows: Vec<(&str, &str, bool)> = summaries
+            .iter()
+            .map(|s| (s.provider_id.as_str(), s.name.as_str(), s.status.is_healthy))
+            .collect();
+        // 未设置名称时使用提供商 ID；未配置的实例不报告
+        assert_eq!(
+            rows,
+            vec![
+                ("failing", "failing", false),
+                ("healthy", "Local Model", true),
+                ("unused", "Unused", true)
+            ]
+        );
+        assert!(registry.get_async("unused").await.is_some());
+        assert!(summaries[0]
+            .status
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("down"));
+        assert_eq!(summaries[1].status.latency_ms, Some(0));
+
+        // 有效期内直接使用缓存的检查结果
+        registry
+            .health()
+            .record_status("failing", &HealthStatus::unhealthy("cached"));
+        let summaries = registry
+            .providers_health(configured.clone(), Duration::from_secs(5), max_age, 2)
+            .await;
+        assert_eq!(summaries[0].status.error_message.as_deref(), Some("cached"));
+        let summaries = registry
+            .providers_health(configured, Duration::from_secs(5), Duration::ZERO, 2)
+            .await;
+        assert!(summaries[0]
+            .status
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("down"));
+    }
 }
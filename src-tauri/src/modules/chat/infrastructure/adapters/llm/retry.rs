@@ -0,0 +1,203 @@
+// Request Retry - 请求重试
+//
+// 对网络错误、限流和服务过载按指数退避重试：500ms、1s、2s……并叠加随机抖动。
+// 限流和过载错误优先使用服务器给出的等待时间，超过退避上限时不再重试，直接返回该错误
+
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::modules::chat::ports::LLMError;
+
+/// 首次重试前的等待时间
+pub const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// 退避等待时间上限
+pub const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// 是否为可重试的错误
+pub fn is_retryable(error: &LLMError) -> bool {
+    matches!(
+        error,
+        LLMError::NetworkError(_) | LLMError::RateLimitError { .. } | LLMError::Overloaded { .. }
+    )
+}
+
+/// 第 `attempt` 次重试（从 0 开始）前的退避时间，不含抖动
+pub fn backoff_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RETRY_MAX_DELAY)
+}
+
+/// 0 到 delay/4 之间的随机抖动，避免多个请求同时重试
+fn jitter(delay: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let max_millis = (delay.as_millis() / 4) as u64;
+    Duration::from_millis(random % (max_millis + 1))
+}
+
+/// 出错后再次尝试前应等待的时间
+///
+/// 服务器要求的等待超过 [`RETRY_MAX_DELAY`] 时返回 `None`，不在请求内长时间挂起
+fn retry_delay(error: &LLMError, attempt: u32) -> Option<Duration> {
+    match error {
+        LLMError::RateLimitError { retry_after_secs }
+        | LLMError::Overloaded { retry_after_secs } => {
+            let delay = Duration::from_secs(*retry_after_secs);
+            (delay <= RETRY_MAX_DELAY).then_some(delay)
+        }
+        _ => {
+            let delay = backoff_delay(attempt);
+            Some(delay + jitter(delay))
+        }
+    }
+}
+
+/// 执行 `operation`，可重试的错误最多重试 `max_retries` 次
+pub async fn with_retries<T, F, Fut>(max_retries: u32, mut operation: F) -> Result<T, LLMError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, LLMError>>,
+{
+    let mut attempt = 0;
+    loop {
+        let error = match operation().await {
+            Err(e) if attempt < max_retries && is_retryable(&e) => e,
+            result => return result,
+        };
+        let Some(delay) = retry_delay(&error, attempt) else {
+            return Err(error);
+        };
+        warn!(
+            "Request failed ({}), retrying in {:?} ({}/{})",
+            error,
+            delay,
+            attempt + 1,
+            max_retries
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// 读取响应的 Retry-After 头，换算为等待秒数
+pub fn retry_after_secs(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, Utc::now()))
+}
+
+/// 解析 Retry-After 的值：秒数或 HTTP 日期（如 `Wed, 21 Oct 2015 07:28:00 GMT`）
+///
+/// 日期已过去时返回 0，无法解析时返回 `None`
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = retry_at.with_timezone(&Utc) - now;
+    Some(wait.num_seconds().max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_parse_retry_after_seconds_and_http_date() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(parse_retry_after("120", now), Some(120));
+        assert_eq!(parse_retry_after(" 7 ", now), Some(7));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(30)
+        );
+        // 已过去的日期不再等待
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+        assert_eq!(parse_retry_after("-5", now), None);
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        assert_eq!(backoff_delay(0), Duration::from_millis(500));
+        assert_eq!(backoff_delay(1), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2), Duration::from_secs(2));
+        assert_eq!(backoff_delay(40), RETRY_MAX_DELAY);
+
+        let delay = retry_delay(&LLMError::NetworkError("reset".to_string()), 1).unwrap();
+        assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_millis(1250));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries_and_skips_fatal_errors() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retries(2, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(LLMError::RateLimitError {
+                retry_after_secs: 0,
+            })
+        })
+        .await;
+        assert!(matches!(result, Err(LLMError::RateLimitError { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retries(2, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(LLMError::AuthenticationError("bad key".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(LLMError::AuthenticationError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retries_overloaded_provider() {
+        let calls = AtomicU32::new(0);
+        let result = with_retries(2, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(LLMError::Overloaded {
+                    retry_after_secs: 0,
+                })
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_returns_rate_limit_error() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), _> = with_retries(3, || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(LLMError::RateLimitError {
+                retry_after_secs: 60,
+            })
+        })
+        .await;
+        assert!(matches!(
+            result,
+            Err(LLMError::RateLimitError {
+                retry_after_secs: 60
+            })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
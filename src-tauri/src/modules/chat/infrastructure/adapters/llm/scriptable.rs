@@ -0,0 +1,405 @@
+// Scripted LLM - 脚本化 LLM 适配器
+//
+// 按预设的步骤脚本回复，可模拟延迟、推理、错误、限流和 Token 用量，
+// 供自动化测试和演示模式（Demo 提供商）确定性地复现各种场景
+
+use async_trait::async_trait;
+use futures::stream::{self, Stream};
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{cancellable, StreamCancellation};
+use crate::infrastructure::CancellationRegistry;
+use crate::modules::chat::ports::{
+    CompletionRequest, CompletionResponse, FinishReason, HealthStatus, LLMError, LLMPort,
+    ModelInfo, ProviderInfo, ProviderType, StreamChunk, TokenUsage,
+};
+
+/// 脚本化 LLM 的默认提供商 ID
+pub const SCRIPTED_PROVIDER_ID: &str = "scripted";
+
+/// 脚本化 LLM 的默认模型
+pub const SCRIPTED_MODEL: &str = "scripted-model";
+
+/// 演示模式的模型
+pub const DEMO_MODEL: &str = "demo";
+
+/// 演示模式的回复，逐块输出以模拟打字
+const DEMO_REPLY: &[&str] = &[
+    "你好！",
+    "这是演示模式，",
+    "回复由预设脚本生成，",
+    "不会连接任何提供商。",
+    "\n\n",
+    "要开始真正的对话，",
+    "请在设置中添加 LLM 提供商。",
+];
+
+/// 演示模式每个块之间的间隔
+const DEMO_CHUNK_DELAY: Duration = Duration::from_millis(80);
+
+/// 脚本步骤
+#[derive(Debug, Clone)]
+pub enum ScriptStep {
+    /// 输出一个内容块
+    Chunk(String),
+    /// 输出一个推理块
+    Reasoning(String),
+    /// 等待一段时间
+    Delay(Duration),
+    /// 返回错误并结束
+    Error(LLMError),
+    /// 报告 Token 用量
+    Usage(TokenUsage),
+}
+
+/// 按脚本回复的 LLM
+///
+/// 每次请求都会完整重放脚本；`cancel` 按请求 ID 立即结束对应的流，不影响其他请求。
+/// 健康检查同样经过脚本中的等待，遇到错误步骤时返回该错误
+pub struct ScriptedLLM {
+    id: String,
+    steps: Vec<ScriptStep>,
+    finish_reason: FinishReason,
+    supports_assistant_prefix: bool,
+    /// 进行中的流式请求，按请求 ID 取消
+    cancellations: Arc<CancellationRegistry>,
+    #[cfg(test)]
+    requests: Mutex<Vec<CompletionRequest>>,
+}
+
+impl ScriptedLLM {
+    pub fn new() -> Self {
+        Self {
+            id: SCRIPTED_PROVIDER_ID.to_string(),
+            steps: Vec::new(),
+            finish_reason: FinishReason::Stop,
+            supports_assistant_prefix: false,
+            cancellations: Arc::new(CancellationRegistry::new()),
+            #[cfg(test)]
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 以单个内容块回复
+    pub fn replying(content: impl Into<String>) -> Self {
+        Self::new().chunk(content)
+    }
+
+    /// 演示模式：不需要网络和 API Key，逐块输出一段固定的说明
+    pub fn demo() -> Self {
+        let mut llm = Self::new();
+        for chunk in DEMO_REPLY {
+            llm = llm.delay(DEMO_CHUNK_DELAY).chunk(*chunk);
+        }
+        let completion_tokens = DEMO_REPLY.len() as u32;
+        llm.usage(TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens,
+            total_tokens: completion_tokens,
+        })
+    }
+
+    /// 使用指定的提供商 ID（同时注册多个脚本化提供商时使用）
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    pub fn chunk(mut self, content: impl Into<String>) -> Self {
+        self.steps.push(ScriptStep::Chunk(content.into()));
+        self
+    }
+
+    pub fn reasoning(mut self, content: impl Into<String>) -> Self {
+        self.steps.push(ScriptStep::Reasoning(content.into()));
+        self
+    }
+
+    pub fn delay(mut self, duration: Duration) -> Self {
+        self.steps.push(ScriptStep::Delay(duration));
+        self
+    }
+
+    pub fn error(mut self, error: LLMError) -> Self {
+        self.steps.push(ScriptStep::Error(error));
+        self
+    }
+
+    pub fn usage(mut self, usage: TokenUsage) -> Self {
+        self.steps.push(ScriptStep::Usage(usage));
+        self
+    }
+
+    /// 回复的结束原因，流式时随最后一个内容块返回；默认 [`FinishReason::Stop`]
+    pub fn finishing_with(mut self, finish_reason: FinishReason) -> Self {
+        self.finish_reason = finish_reason;
+        self
+    }
+
+    /// 声明支持助手前缀预填
+    pub fn with_assistant_prefix_support(mut self) -> Self {
+        self.supports_assistant_prefix = true;
+        self
+    }
+
+    /// 已收到的请求（按时间顺序）
+    #[cfg(test)]
+    pub fn requests(&self) -> Vec<CompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 记录收到的请求供测试检查；演示模式长期运行，不保留请求
+    #[cfg(test)]
+    fn record(&self, request: CompletionRequest) {
+        self.requests.lock().unwrap().push(request);
+    }
+
+    #[cfg(not(test))]
+    fn record(&self, _request: CompletionRequest) {}
+}
+
+impl Default for ScriptedLLM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LLMPort for ScriptedLLM {
+    fn provider_id(&self) -> &str {
+        &self.id
+    }
+
+    fn provider_info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.id.clone(),
+            name: "Demo".to_string(),
+            provider_type: ProviderType::Demo,
+            models: vec![],
+            supports_cancellation: true,
+            supports_model_listing: false,
+            supports_assistant_prefix: self.supports_assistant_prefix,
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>, LLMError> {
+        Ok(vec![])
+    }
+
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError> {
+        self.record(request);
+
+        let mut content = String::new();
+        let mut usage = TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        };
+        for step in &self.steps {
+            match step {
+                ScriptStep::Chunk(chunk) => content.push_str(chunk),
+                ScriptStep::Reasoning(_) => {}
+                ScriptStep::Delay(duration) => tokio::time::sleep(*duration).await,
+                ScriptStep::Error(e) => return Err(e.clone()),
+                ScriptStep::Usage(reported) => usage = *reported,
+            }
+        }
+
+        Ok(CompletionResponse {
+            content,
+            finish_reason: self.finish_reason,
+            usage,
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamChunk, LLMError>> + Send>>, LLMError> {
+        let cancellation =
+            StreamCancellation::register(&self.cancellations, request.request_id.as_deref());
+        self.record(request);
+
+        let steps = Arc::new(self.steps.clone());
+        let finish_reason = self.finish_reason;
+        let last_chunk = steps
+            .iter()
+            .rposition(|step| matches!(step, ScriptStep::Chunk(_)));
+        // 超出步骤数的索引表示流已结束
+        let finished = steps.len() + 1;
+
+        let stream = stream::unfold(0, move |mut index| {
+            let steps = steps.clone();
+            async move {
+                loop {
+                    if index >= finished {
+                        return None;
+                    }
+
+                    let empty = StreamChunk {
+                        content: String::new(),
+                        reasoning: None,
+                        finish_reason: None,
+                        usage: None,
+                    };
+                    match steps.get(index) {
+                        None => return None,
+                        Some(ScriptStep::Delay(duration)) => {
+                            tokio::time::sleep(*duration).await;
+                            index += 1;
+                        }
+                        Some(ScriptStep::Chunk(content)) => {
+                            let chunk = StreamChunk {
+                                content: content.clone(),
+                                finish_reason: (Some(index) == last_chunk).then_some(finish_reason),
+                                ..empty
+                            };
+                            return Some((Ok(chunk), index + 1));
+                        }
+                        Some(ScriptStep::Reasoning(reasoning)) => {
+                            let chunk = StreamChunk {
+                                reasoning: Some(reasoning.clone()),
+                                ..empty
+                            };
+                            return Some((Ok(chunk), index + 1));
+                        }
+                        Some(ScriptStep::Usage(usage)) => {
+                            let chunk = StreamChunk {
+                                usage: Some(*usage),
+                                ..empty
+                            };
+                            return Some((Ok(chunk), index + 1));
+                        }
+                        Some(ScriptStep::Error(e)) => return Some((Err(e.clone()), finished)),
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(cancellable(stream, cancellation)))
+    }
+
+    async fn cancel(&self, request_id: &str) -> Result<(), LLMError> {
+        self.cancellations.cancel(request_id);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<HealthStatus, LLMError> {
+        for step in &self.steps {
+            match step {
+                ScriptStep::Delay(duration) => tokio::time::sleep(*duration).await,
+                ScriptStep::Error(e) => return Err(e.clone()),
+                ScriptStep::Chunk(_) | ScriptStep::Reasoning(_) | ScriptStep::Usage(_) => {}
+            }
+        }
+        Ok(HealthStatus {
+            is_healthy: true,
+            latency_ms: Some(0),
+            error_message: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::application::StreamEvent;
+    use crate::modules::chat::test_support::{collect_events, ChatTestHarness};
+    use futures::StreamExt;
+
+    fn request() -> CompletionRequest {
+        CompletionRequest::new(vec![], SCRIPTED_MODEL)
+    }
+
+    #[tokio::test]
+    async fn test_stream_follows_script() {
+        let usage = TokenUsage {
+            prompt_tokens: 3,
+            completion_tokens: 2,
+            total_tokens: 5,
+        };
+        let llm = ScriptedLLM::new()
+            .chunk("Hel")
+            .delay(Duration::from_millis(10))
+            .chunk("lo")
+            .usage(usage)
+            .finishing_with(FinishReason::Length);
+
+        let chunks: Vec<StreamChunk> = llm
+            .complete_stream(request())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        let content: String = chunks.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(content, "Hello");
+        assert_eq!(chunks[1].finish_reason, Some(FinishReason::Length));
+        assert_eq!(chunks[2].usage.unwrap().total_tokens, 5);
+
+        let response = llm.complete(request()).await.unwrap();
+        assert_eq!(response.content, "Hello");
+        assert_eq!(response.finish_reason, FinishReason::Length);
+        assert_eq!(response.usage.total_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_ends_only_the_targeted_stream() {
+        let llm = ScriptedLLM::new()
+            .chunk("Partial")
+            .delay(Duration::from_millis(200))
+            .chunk(" rest");
+        let mut first = llm
+            .complete_stream(request().with_request_id("req-1"))
+            .await
+            .unwrap();
+        let mut second = llm
+            .complete_stream(request().with_request_id("req-2"))
+            .await
+            .unwrap();
+        assert_eq!(first.next().await.unwrap().unwrap().content, "Partial");
+        assert_eq!(second.next().await.unwrap().unwrap().content, "Partial");
+
+        // 不等待脚本中的延迟，立即结束
+        llm.cancel("req-2").await.unwrap();
+        let next = tokio::time::timeout(Duration::from_millis(100), second.next())
+            .await
+            .expect("cancelled stream should end promptly");
+        assert!(next.is_none());
+
+        let rest: Vec<StreamChunk> = first.map(Result::unwrap).collect().await;
+        assert_eq!(rest[0].content, " rest");
+    }
+
+    #[tokio::test]
+    async fn test_handler_surfaces_scripted_error() {
+        let llm = ScriptedLLM::new()
+            .chunk("Partial ")
+            .chunk("reply")
+            .error(LLMError::RateLimitError {
+                retry_after_secs: 7,
+            })
+            .chunk("never sent");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Hi").await.unwrap();
+        let events = collect_events(&mut rx).await;
+
+        assert!(matches!(&events[0], StreamEvent::Chunk(c) if c == "Partial "));
+        assert!(matches!(&events[1], StreamEvent::Chunk(c) if c == "reply"));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Error(LLMError::RateLimitError {
+                retry_after_secs: 7
+            }))
+        ));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::Chunk(c) if c == "never sent")));
+    }
+}
@@ -0,0 +1,184 @@
+// Attachments - 本地图片附件
+//
+// 读取前端传入的本地文件路径，校验图片类型和大小后编码为 base64
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::modules::chat::ports::ImageAttachment;
+
+/// 单个附件的最大大小（5 MB）
+pub const MAX_ATTACHMENT_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 附件错误
+#[derive(Debug, Error)]
+pub enum AttachmentError {
+    #[error("Failed to read {path}: {message}")]
+    Io { path: String, message: String },
+
+    #[error("Unsupported file type: {0} (expected PNG, JPEG, GIF or WebP)")]
+    UnsupportedType(String),
+
+    #[error("File too large: {path} ({size} bytes, max {max} bytes)")]
+    TooLarge { path: String, size: u64, max: u64 },
+
+    #[error("Access to {0} is not allowed")]
+    NotAllowed(String),
+}
+
+/// 根据文件头识别图片 MIME 类型
+pub fn sniff_image_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// 读取本地图片文件作为附件
+///
+/// 按文件内容（而非扩展名）识别类型，超过 `max_bytes` 的文件在读取前即被拒绝
+pub async fn read_image_attachment(
+    path: &Path,
+    max_bytes: u64,
+) -> Result<ImageAttachment, AttachmentError> {
+    let display = path.display().to_string();
+    let io_error = |e: std::io::Error| AttachmentError::Io {
+        path: display.clone(),
+        message: e.to_string(),
+    };
+
+    let size = tokio::fs::metadata(path).await.map_err(io_error)?.len();
+    if size > max_bytes {
+        return Err(AttachmentError::TooLarge {
+            path: display,
+            size,
+            max: max_bytes,
+        });
+    }
+
+    let bytes = tokio::fs::read(path).await.map_err(io_error)?;
+    let mime_type = sniff_image_mime(&bytes).ok_or(AttachmentError::UnsupportedType(display))?;
+
+    Ok(ImageAttachment {
+        mime_type: mime_type.to_string(),
+        data: STANDARD.encode(&bytes),
+    })
+}
+
+/// 在访问范围内读取本地图片附件
+///
+/// `is_allowed` 判断路径是否允许访问（如用户通过对话框选择或拖入的文件），
+/// 不允许时不触碰文件系统
+pub async fn read_scoped_image_attachment(
+    path: &Path,
+    max_bytes: u64,
+    is_allowed: impl FnOnce(&Path) -> bool,
+) -> Result<ImageAttachment, AttachmentError> {
+    if !is_allowed(path) {
+        return Err(AttachmentError::NotAllowed(path.display().to_string()));
+    }
+    read_image_attachment(path, max_bytes).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn test_sniff_image_mime() {
+        assert_eq!(sniff_image_mime(PNG_HEADER), Some("image/png"));
+        assert_eq!(
+            sniff_image_mime(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(sniff_image_mime(b"GIF89a...."), Some("image/gif"));
+        assert_eq!(
+            sniff_image_mime(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+
+        assert_eq!(sniff_image_mime(b"%PDF-1.7"), None);
+        assert_eq!(sniff_image_mime(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(sniff_image_mime(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_image_attachment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.dat");
+        std::fs::write(&path, PNG_HEADER).unwrap();
+
+        let attachment = read_image_attachment(&path, MAX_ATTACHMENT_BYTES)
+            .await
+            .unwrap();
+
+        assert_eq!(attachment.mime_type, "image/png");
+        assert_eq!(STANDARD.decode(&attachment.data).unwrap(), PNG_HEADER);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unsupported_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.png");
+        std::fs::write(&path, b"%PDF-1.7").unwrap();
+
+        let result = read_image_attachment(&path, MAX_ATTACHMENT_BYTES).await;
+        assert!(matches!(result, Err(AttachmentError::UnsupportedType(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("large.png");
+        let mut bytes = PNG_HEADER.to_vec();
+        bytes.resize(64, 0);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let result = read_image_attachment(&path, 32).await;
+        assert!(matches!(
+            result,
+            Err(AttachmentError::TooLarge {
+                size: 64,
+                max: 32,
+                ..
+            })
+        ));
+
+        // 恰好等于上限时允许
+        assert!(read_image_attachment(&path, 64).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_scoped_read_rejects_paths_outside_scope() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.png");
+        std::fs::write(&path, PNG_HEADER).unwrap();
+
+        let result = read_scoped_image_attachment(&path, MAX_ATTACHMENT_BYTES, |_| false).await;
+        assert!(matches!(result, Err(AttachmentError::NotAllowed(_))));
+
+        let allowed = dir.path().to_path_buf();
+        let attachment =
+            read_scoped_image_attachment(&path, MAX_ATTACHMENT_BYTES, |p| p.starts_with(&allowed))
+                .await
+                .unwrap();
+        assert_eq!(attachment.mime_type, "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let result = read_image_attachment(Path::new("/nonexistent/image.png"), 1024).await;
+        assert!(matches!(result, Err(AttachmentError::Io { .. })));
+    }
+}
@@ -0,0 +1,60 @@
+// 缓存统计
+//
+// 各缓存的命中 / 未命中计数，供诊断命令查询
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 缓存统计快照
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    /// 缓存名称
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// 当前缓存的条目数
+    pub entries: usize,
+}
+
+/// 缓存命中计数器
+///
+/// 只使用原子操作，可以在持有缓存锁之外更新
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按查找结果记录一次命中或未命中，原样返回结果
+    pub fn record<T>(&self, lookup: Option<T>) -> Option<T> {
+        let counter = if lookup.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        lookup
+    }
+
+    /// 生成统计快照
+    pub fn snapshot(&self, name: &str, entries: usize) -> CacheStats {
+        CacheStats {
+            name: name.to_string(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries,
+        }
+    }
+
+    /// 计数归零
+    pub fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
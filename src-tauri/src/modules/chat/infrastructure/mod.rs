@@ -2,13 +2,21 @@
 // 基础设施层包含端口的具体实现
 
 pub mod adapters;
+pub mod attachments;
+pub mod cache_stats;
 pub mod repositories;
 
 // 重导出常用类型
 pub use adapters::llm::{
-    DynamicLLMAdapter, DynamicLLMConfig, LLMAdapterRegistry, MockLLMAdapter, OpenAIAdapter,
+    LLMAdapterRegistry, MockLLMAdapter, OpenAIAdapter,
+    ProviderHealthSummary, ScriptStep, ScriptedLLM, DEMO_MODEL, SCRIPTED_MODEL,
+    SCRIPTED_PROVIDER_ID,
 };
+pub use attachments::{
+    read_image_attachment, read_scoped_image_attachment, AttachmentError, MAX_ATTACHMENT_BYTES,
+};
+pub use cache_stats::{CacheCounters, CacheStats};
 pub use repositories::{
-    FileMessageRepository, FileSessionRepository, InMemoryMessageRepository,
-    InMemorySessionRepository,
+    CachedMessageRepository, CachedSessionRepository, FileMessageRepository,
+    FileSessionRepository, InMemoryMessageRepository, InMemorySessionRepository,
 };
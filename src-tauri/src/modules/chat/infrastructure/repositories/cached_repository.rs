@@ -0,0 +1,728 @@
+// Cached Repositories - 带 LRU 缓存的仓储装饰器
+//
+// 包装任意会话/消息仓储，缓存最近读取的结果，写入时使相关缓存失效。
+// 底层仓储为数据库时可避免热点会话的重复查询
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use crate::modules::chat::domain::{Message, MessageId, Session, SessionId};
+use crate::modules::chat::infrastructure::{CacheCounters, CacheStats};
+use crate::modules::chat::ports::{
+    MessageRepository, PaginatedResult, Pagination, RepositoryError, SessionRepository,
+};
+
+/// 默认缓存条目数
+pub const DEFAULT_CACHE_CAPACITY: usize = 64;
+
+/// 固定容量的 LRU 缓存
+///
+/// 容量很小，淘汰时线性查找最久未访问的条目即可
+struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    tick: u64,
+    /// 每次失效（移除或清空）时递增
+    ///
+    /// 未命中时先记下版本再读取底层仓储，回填前版本已变说明读取期间有写入，
+    /// 读到的可能是写入前的旧值，此时不回填
+    generation: u64,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            tick: 0,
+            generation: 0,
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let tick = self.next_tick();
+        self.entries.get_mut(key).map(|(value, used)| {
+            *used = tick;
+            value
+        })
+    }
+
+    /// 获取条目，不存在时插入 `default()` 的结果
+    fn get_or_insert_with(&mut self, key: K, default: impl FnOnce() -> V) -> &mut V {
+        if !self.entries.contains_key(&key) {
+            self.insert(key.clone(), default());
+        }
+        self.get_mut(&key).expect("entry was just inserted")
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        let tick = self.next_tick();
+        self.entries.insert(key, (value, tick));
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// 仅当缓存自 `generation` 以来没有失效时插入
+    fn insert_if_current(&mut self, generation: u64, key: K, value: V) {
+        if self.generation == generation {
+            self.insert(key, value);
+        }
+    }
+
+    fn remove(&mut self, key: &K) {
+        self.generation += 1;
+        self.entries.remove(key);
+    }
+
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.entries.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// 带缓存的会话仓储
+///
+/// 缓存 `get` 的结果（包括不存在的会话），保存和删除时使该会话的缓存失效。
+/// 列表和计数直接查询底层仓储
+pub struct CachedSessionRepository<R> {
+    inner: R,
+    sessions: Mutex<LruCache<SessionId, Option<Session>>>,
+    counters: CacheCounters,
+}
+
+impl<R: SessionRepository> CachedSessionRepository<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_capacity(inner: R, capacity: usize) -> Self {
+        Self {
+            inner,
+            sessions: Mutex::new(LruCache::new(capacity)),
+            counters: CacheCounters::new(),
+        }
+    }
+
+    fn invalidate(&self, id: SessionId) {
+        self.sessions.lock().unwrap().remove(&id);
+    }
+
+    /// 缓存命中统计
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.sessions.lock().unwrap().len();
+        self.counters.snapshot("sessions", entries)
+    }
+
+    /// 清空缓存并重置统计
+    pub fn clear(&self) {
+        self.sessions.lock().unwrap().clear();
+        self.counters.reset();
+    }
+}
+
+#[async_trait]
+impl<R: SessionRepository> SessionRepository for CachedSessionRepository<R> {
+    async fn get(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+        let (cached, generation) = {
+            let mut sessions = self.sessions.lock().unwrap();
+            (sessions.get_mut(&id).cloned(), sessions.generation())
+        };
+        if let Some(cached) = self.counters.record(cached) {
+            return Ok(cached);
+        }
+
+        let session = self.inner.get(id).await?;
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert_if_current(generation, id, session.clone());
+        Ok(session)
+    }
+
+    async fn save(&self, session: &Session) -> Result<(), RepositoryError> {
+        // 写入完成后失效；与之交错的读取因版本变化不会回填旧值
+        let result = self.inner.save(session).await;
+        self.invalidate(session.id());
+        result
+    }
+
+    async fn delete(&self, id: SessionId) -> Result<(), RepositoryError> {
+        let result = self.inner.delete(id).await;
+        self.invalidate(id);
+        result
+    }
+
+    async fn find_all(
+        &self,
+        pagination: Pagination,
+    ) -> Result<PaginatedResult<Session>, RepositoryError> {
+        self.inner.find_all(pagination).await
+    }
+
+    async fn exists(&self, id: SessionId) -> Result<bool, RepositoryError> {
+        let cached = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get_mut(&id)
+            .map(|cached| cached.is_some());
+        if let Some(exists) = self.counters.record(cached) {
+            return Ok(exists);
+        }
+        self.inner.exists(id).await
+    }
+
+    async fn count(&self) -> Result<usize, RepositoryError> {
+        self.inner.count().await
+    }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        self.inner.flush().await
+    }
+}
+
+/// 单个会话的查询缓存
+#[derive(Default)]
+struct SessionQueries {
+    pages: HashMap<(u32, u32), PaginatedResult<Message>>,
+    last: Option<Option<Message>>,
+    count: Option<usize>,
+}
+
+/// 带缓存的消息仓储
+///
+/// 按消息缓存 `get`，按会话缓存分页查询、最后一条消息和消息数量。
+/// 任何写入都会使所涉及会话的全部查询缓存失效
+pub struct CachedMessageRepository<R> {
+    inner: R,
+    messages: Mutex<LruCache<MessageId, Option<Message>>>,
+    sessions: Mutex<LruCache<SessionId, SessionQueries>>,
+    counters: CacheCounters,
+}
+
+impl<R: MessageRepository> CachedMessageRepository<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// `capacity` 同时限制缓存的消息数和会话数
+    pub fn with_capacity(inner: R, capacity: usize) -> Self {
+        Self {
+            inner,
+            messages: Mutex::new(LruCache::new(capacity)),
+            sessions: Mutex::new(LruCache::new(capacity)),
+            counters: CacheCounters::new(),
+        }
+    }
+
+    /// 缓存命中统计，条目数为缓存的消息数与会话数之和
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.messages.lock().unwrap().len() + self.sessions.lock().unwrap().len();
+        self.counters.snapshot("messages", entries)
+    }
+
+    /// 清空缓存并重置统计
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+        self.sessions.lock().unwrap().clear();
+        self.counters.reset();
+    }
+
+    fn invalidate_session(&self, session_id: SessionId) {
+        self.sessions.lock().unwrap().remove(&session_id);
+    }
+
+    /// 使消息及其所在会话的缓存失效，会话未知时清空全部会话查询缓存
+    fn invalidate_message(&self, id: MessageId, session_id: Option<SessionId>) {
+        self.messages.lock().unwrap().remove(&id);
+        match session_id {
+            Some(session_id) => self.invalidate_session(session_id),
+            None => self.sessions.lock().unwrap().clear(),
+        }
+    }
+
+    /// 查询会话缓存，未命中时返回当前的缓存版本供回填时使用
+    fn cached_query<T: Clone>(
+        &self,
+        session_id: SessionId,
+        read: impl FnOnce(&SessionQueries) -> Option<T>,
+    ) -> Result<T, u64> {
+        let (cached, generation) = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let cached = sessions
+                .get_mut(&session_id)
+                .and_then(|queries| read(queries));
+            (cached, sessions.generation())
+        };
+        self.counters.record(cached).ok_or(generation)
+    }
+
+    /// 回填会话查询缓存，缓存自 `generation` 以来失效过时放弃
+    fn store_query(
+        &self,
+        session_id: SessionId,
+        generation: u64,
+        write: impl FnOnce(&mut SessionQueries),
+    ) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.generation() == generation {
+            write(sessions.get_or_insert_with(session_id, SessionQueries::default));
+        }
+    }
+}
+
+#[async_trait]
+impl<R: MessageRepository> MessageRepository for CachedMessageRepository<R> {
+    async fn get(&self, id: MessageId) -> Result<Option<Message>, RepositoryError> {
+        let (cached, generation) = {
+            let mut messages = self.messages.lock().unwrap();
+            (messages.get_mut(&id).cloned(), messages.generation())
+        };
+        if let Some(cached) = self.counters.record(cached) {
+            return Ok(cached);
+        }
+
+        let message = self.inner.get(id).await?;
+        self.messages
+            .lock()
+            .unwrap()
+            .insert_if_current(generation, id, message.clone());
+        Ok(message)
+    }
+
+    async fn save(&self, message: &Message) -> Result<(), RepositoryError> {
+        // 写入完成后失效；与之交错的读取因版本变化不会回填旧值
+        let result = self.inner.save(message).await;
+        self.invalidate_message(message.id(), Some(message.session_id()));
+        result
+    }
+
+    async fn update(&self, message: &Message) -> Result<(), RepositoryError> {
+        let result = self.inner.update(message).await;
+        self.invalidate_message(message.id(), Some(message.session_id()));
+        result
+    }
+
+    async fn delete(&self, id: MessageId) -> Result<(), RepositoryError> {
+        let session_id = self
+            .messages
+            .lock()
+            .unwrap()
+            .get_mut(&id)
+            .and_then(|message| message.as_ref().map(Message::session_id));
+        let result = self.inner.delete(id).await;
+        self.invalidate_message(id, session_id);
+        result
+    }
+
+    async fn find_by_session(
+        &self,
+        session_id: SessionId,
+        pagination: Pagination,
+    ) -> Result<PaginatedResult<Message>, RepositoryError> {
+        let key = (pagination.page, pagination.limit);
+        let generation = match self.cached_query(session_id, |q| q.pages.get(&key).cloned()) {
+            Ok(page) => return Ok(page),
+            Err(generation) => generation,
+        };
+
+        let page = self.inner.find_by_session(session_id, pagination).await?;
+        self.store_query(session_id, generation, |q| {
+            q.pages.insert(key, page.clone());
+        });
+        Ok(page)
+    }
+
+    async fn delete_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+        let result = self.inner.delete_by_session(session_id).await;
+        // 无法得知删除了哪些消息，清空消息缓存
+        self.messages.lock().unwrap().clear();
+        self.invalidate_session(session_id);
+        result
+    }
+
+    async fn find_last_by_session(
+        &self,
+        session_id: SessionId,
+    ) -> Result<Option<Message>, RepositoryError> {
+        let generation = match self.cached_query(session_id, |q| q.last.clone()) {
+            Ok(last) => return Ok(last),
+            Err(generation) => generation,
+        };
+
+        let last = self.inner.find_last_by_session(session_id).await?;
+        self.store_query(session_id, generation, |q| q.last = Some(last.clone()));
+        Ok(last)
+    }
+
+    async fn count_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+        let generation = match self.cached_query(session_id, |q| q.count) {
+            Ok(count) => return Ok(count),
+            Err(generation) => generation,
+        };
+
+        let count = self.inner.count_by_session(session_id).await?;
+        self.store_query(session_id, generation, |q| q.count = Some(count));
+        Ok(count)
+    }
+
+    async fn count_all_by_session(&self) -> Result<HashMap<SessionId, usize>, RepositoryError> {
+        self.inner.count_all_by_session().await
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        session_id: Option<SessionId>,
+        limit: usize,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        self.inner.search(query, session_id, limit).await
+    }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        self.inner.flush().await
+    }
+
+    async fn compact(&self) -> Result<usize, RepositoryError> {
+        self.inner.compact().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::infrastructure::{
+        InMemoryMessageRepository, InMemorySessionRepository,
+    };
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+
+    /// 记录读取次数的仓储包装
+    struct Counting<R> {
+        inner: R,
+        reads: AtomicUsize,
+    }
+
+    impl<R> Counting<R> {
+        fn new(inner: R) -> Self {
+            Self {
+                inner,
+                reads: AtomicUsize::new(0),
+            }
+        }
+
+        fn read(&self) -> &R {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            &self.inner
+        }
+
+        fn reads(&self) -> usize {
+            self.reads.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl<R: SessionRepository> SessionRepository for Counting<R> {
+        async fn get(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+            self.read().get(id).await
+        }
+
+        async fn save(&self, session: &Session) -> Result<(), RepositoryError> {
+            self.inner.save(session).await
+        }
+
+        async fn delete(&self, id: SessionId) -> Result<(), RepositoryError> {
+            self.inner.delete(id).await
+        }
+
+        async fn find_all(
+            &self,
+            pagination: Pagination,
+        ) -> Result<PaginatedResult<Session>, RepositoryError> {
+            self.read().find_all(pagination).await
+        }
+
+        async fn exists(&self, id: SessionId) -> Result<bool, RepositoryError> {
+            self.read().exists(id).await
+        }
+
+        async fn count(&self) -> Result<usize, RepositoryError> {
+            self.read().count().await
+        }
+
+        async fn flush(&self) -> Result<(), RepositoryError> {
+            self.inner.flush().await
+        }
+    }
+
+    #[async_trait]
+    impl<R: MessageRepository> MessageRepository for Counting<R> {
+        async fn get(&self, id: MessageId) -> Result<Option<Message>, RepositoryError> {
+            self.read().get(id).await
+        }
+
+        async fn save(&self, message: &Message) -> Result<(), RepositoryError> {
+            self.inner.save(message).await
+        }
+
+        async fn update(&self, message: &Message) -> Result<(), RepositoryError> {
+            self.inner.update(message).await
+        }
+
+        async fn delete(&self, id: MessageId) -> Result<(), RepositoryError> {
+            self.inner.delete(id).await
+        }
+
+        async fn find_by_session(
+            &self,
+            session_id: SessionId,
+            pagination: Pagination,
+        ) -> Result<PaginatedResult<Message>, RepositoryError> {
+            self.read().find_by_session(session_id, pagination).await
+        }
+
+        async fn delete_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+            self.inner.delete_by_session(session_id).await
+        }
+
+        async fn find_last_by_session(
+            &self,
+            session_id: SessionId,
+        ) -> Result<Option<Message>, RepositoryError> {
+            self.read().find_last_by_session(session_id).await
+        }
+
+        async fn count_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
+            self.read().count_by_session(session_id).await
+        }
+
+        async fn count_all_by_session(&self) -> Result<HashMap<SessionId, usize>, RepositoryError> {
+            self.read().count_all_by_session().await
+        }
+
+        async fn search(
+            &self,
+            query: &str,
+            session_id: Option<SessionId>,
+            limit: usize,
+        ) -> Result<Vec<Message>, RepositoryError> {
+            self.read().search(query, session_id, limit).await
+        }
+
+        async fn flush(&self) -> Result<(), RepositoryError> {
+            self.inner.flush().await
+        }
+
+        async fn compact(&self) -> Result<usize, RepositoryError> {
+            self.inner.compact().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_cache_hits_and_invalidation() {
+        let repo = CachedSessionRepository::new(Counting::new(InMemorySessionRepository::new()));
+        let mut session = Session::new(Some("Before".to_string()), None);
+        let id = session.id();
+        repo.save(&session).await.unwrap();
+
+        for _ in 0..3 {
+            assert_eq!(repo.get(id).await.unwrap().unwrap().title(), "Before");
+        }
+        assert!(repo.exists(id).await.unwrap());
+        assert_eq!(repo.inner.reads(), 1);
+
+        // 写入后重新读取底层仓储
+        session.update_title("After".to_string());
+        repo.save(&session).await.unwrap();
+        assert_eq!(repo.get(id).await.unwrap().unwrap().title(), "After");
+        assert_eq!(repo.inner.reads(), 2);
+
+        repo.delete(id).await.unwrap();
+        assert!(repo.get(id).await.unwrap().is_none());
+        assert!(!repo.exists(id).await.unwrap());
+        assert_eq!(repo.inner.reads(), 3);
+    }
+
+    /// 第一次 `get` 读到结果后暂停，直到测试放行
+    struct PausedRead<R> {
+        inner: R,
+        pause: AtomicBool,
+        reading: Notify,
+        resume: Notify,
+    }
+
+    #[async_trait]
+    impl<R: SessionRepository> SessionRepository for PausedRead<R> {
+        async fn get(&self, id: SessionId) -> Result<Option<Session>, RepositoryError> {
+            let session = self.inner.get(id).await;
+            if self.pause.swap(false, Ordering::SeqCst) {
+                self.reading.notify_one();
+                self.resume.notified().await;
+            }
+            session
+        }
+
+        async fn save(&self, session: &Session) -> Result<(), RepositoryError> {
+            self.inner.save(session).await
+        }
+
+        async fn delete(&self, id: SessionId) -> Result<(), RepositoryError> {
+            self.inner.delete(id).await
+        }
+
+        async fn find_all(
+            &self,
+            pagination: Pagination,
+        ) -> Result<PaginatedResult<Session>, RepositoryError> {
+            self.inner.find_all(pagination).await
+        }
+
+        async fn exists(&self, id: SessionId) -> Result<bool, RepositoryError> {
+            self.inner.exists(id).await
+        }
+
+        async fn count(&self) -> Result<usize, RepositoryError> {
+            self.inner.count().await
+        }
+
+        async fn flush(&self) -> Result<(), RepositoryError> {
+            self.inner.flush().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_racing_write_does_not_cache_stale_value() {
+        let repo = CachedSessionRepository::new(PausedRead {
+            inner: InMemorySessionRepository::new(),
+            pause: AtomicBool::new(true),
+            reading: Notify::new(),
+            resume: Notify::new(),
+        });
+        let mut session = Session::new(Some("Before".to_string()), None);
+        let id = session.id();
+        repo.save(&session).await.unwrap();
+
+        // 读取拿到旧值后，写入在其回填缓存之前完成
+        let (stale, _) = tokio::join!(repo.get(id), async {
+            repo.inner.reading.notified().await;
+            session.update_title("After".to_string());
+            repo.save(&session).await.unwrap();
+            repo.inner.resume.notify_one();
+        });
+        assert_eq!(stale.unwrap().unwrap().title(), "Before");
+
+        assert_eq!(repo.get(id).await.unwrap().unwrap().title(), "After");
+    }
+
+    #[tokio::test]
+    async fn test_message_cache_hits_and_invalidation() {
+        let repo = CachedMessageRepository::new(Counting::new(InMemoryMessageRepository::new()));
+        let session_id = SessionId::new();
+        let first = Message::new_user(session_id, "Hi");
+        repo.save(&first).await.unwrap();
+
+        let pagination = Pagination::new(1, 50);
+        for _ in 0..3 {
+            repo.get(first.id()).await.unwrap();
+            assert_eq!(
+                repo.find_by_session(session_id, pagination)
+                    .await
+                    .unwrap()
+                    .items
+                    .len(),
+                1
+            );
+            assert_eq!(repo.count_by_session(session_id).await.unwrap(), 1);
+            repo.find_last_by_session(session_id).await.unwrap();
+        }
+        assert_eq!(repo.inner.reads(), 4);
+
+        // 新消息使会话查询失效，但不影响其他消息的缓存
+        let second = Message::new_user(session_id, "Hello");
+        repo.save(&second).await.unwrap();
+        assert_eq!(repo.count_by_session(session_id).await.unwrap(), 2);
+        assert_eq!(
+            repo.find_last_by_session(session_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id(),
+            second.id()
+        );
+        repo.get(first.id()).await.unwrap();
+        assert_eq!(repo.inner.reads(), 6);
+
+        // 删除消息使该消息和所在会话的缓存失效
+        repo.delete(first.id()).await.unwrap();
+        assert!(repo.get(first.id()).await.unwrap().is_none());
+        assert_eq!(repo.count_by_session(session_id).await.unwrap(), 1);
+        assert_eq!(repo.inner.reads(), 8);
+
+        repo.delete_by_session(session_id).await.unwrap();
+        assert!(repo.get(second.id()).await.unwrap().is_none());
+        assert_eq!(repo.count_by_session(session_id).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_cache_stats_count_hits_and_reset_on_clear() {
+        let repo = CachedMessageRepository::new(InMemoryMessageRepository::new());
+        let session_id = SessionId::new();
+        let message = Message::new_user(session_id, "Hi");
+        repo.save(&message).await.unwrap();
+
+        repo.get(message.id()).await.unwrap();
+        repo.get(message.id()).await.unwrap();
+        repo.count_by_session(session_id).await.unwrap();
+        repo.count_by_session(session_id).await.unwrap();
+        repo.count_by_session(session_id).await.unwrap();
+
+        let stats = repo.stats();
+        assert_eq!(stats.name, "messages");
+        assert_eq!((stats.hits, stats.misses), (3, 2));
+        assert_eq!(stats.entries, 2);
+
+        repo.clear();
+        let stats = repo.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (0, 0, 0));
+
+        // 清空后重新读取底层仓储
+        repo.get(message.id()).await.unwrap();
+        assert_eq!(repo.stats().misses, 1);
+    }
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.get_mut(&"a");
+        cache.insert("c", 3);
+
+        assert!(cache.get_mut(&"a").is_some());
+        assert!(cache.get_mut(&"b").is_none());
+        assert!(cache.get_mut(&"c").is_some());
+    }
+}
@@ -10,6 +10,7 @@ use std::path::PathBuf;
 use tokio::fs;
 use tokio::sync::RwLock;
 
+use super::{search_messages, upsert_message};
 use crate::modules::chat::domain::{Message, MessageId, SessionId};
 use crate::modules::chat::ports::{
     MessageRepository, PaginatedResult, Pagination, RepositoryError,
@@ -100,14 +101,22 @@ impl MessageRepository for FileMessageRepository {
                 .messages_by_session
                 .entry(session_key)
                 .or_insert_with(Vec::new);
+            upsert_message(messages, message);
+        }
+        self.persist().await
+    }
 
-            // 检查是否是更新操作
-            let id_str = message.id().to_string();
-            if let Some(pos) = messages.iter().position(|m| m.id().to_string() == id_str) {
-                messages[pos] = message.clone();
-            } else {
-                messages.push(message.clone());
-            }
+    async fn update(&self, message: &Message) -> Result<(), RepositoryError> {
+        {
+            let mut store = self.store.write().await;
+            let session_key = message.session_id().to_string();
+
+            let messages = store
+                .messages_by_session
+                .get_mut(&session_key)
+                .filter(|messages| messages.iter().any(|m| m.id() == message.id()))
+                .ok_or_else(|| RepositoryError::NotFound(message.id().to_string()))?;
+            upsert_message(messages, message);
         }
         self.persist().await
     }
@@ -141,19 +150,12 @@ impl MessageRepository for FileMessageRepository {
             .cloned()
             .unwrap_or_default();
 
-        // 按创建时间排序（最早的在前）
+        // 按会话内序号排序（最早的在前），旧数据没有序号时按创建时间
         let mut sorted_messages = messages;
-        sorted_messages.sort_by(|a, b| a.created_at().cmp(&b.created_at()));
+        sorted_messages.sort_by_key(Message::order_key);
 
         let total = sorted_messages.len();
-        let offset = pagination.offset() as usize;
-        let limit = pagination.limit as usize;
-
-        let items = if offset < total {
-            sorted_messages[offset..total.min(offset + limit)].to_vec()
-        } else {
-            Vec::new()
-        };
+        let items = sorted_messages[pagination.range(total)].to_vec();
 
         Ok(PaginatedResult::new(items, total, pagination))
     }
@@ -186,7 +188,7 @@ impl MessageRepository for FileMessageRepository {
         let messages = store.messages_by_session.get(&session_key);
 
         Ok(messages
-            .and_then(|msgs| msgs.iter().max_by_key(|m| m.created_at()))
+            .and_then(|msgs| msgs.iter().max_by_key(|m| m.order_key()))
             .cloned())
     }
 
@@ -200,12 +202,69 @@ impl MessageRepository for FileMessageRepository {
             .map(|v| v.len())
             .unwrap_or(0))
     }
+
+    async fn count_all_by_session(&self) -> Result<HashMap<SessionId, usize>, RepositoryError> {
+        let store = self.store.read().await;
+
+        let mut counts = HashMap::new();
+        for (session_key, messages) in &store.messages_by_session {
+            if messages.is_empty() {
+                continue;
+            }
+            match SessionId::parse(session_key) {
+                Ok(session_id) => {
+                    counts.insert(session_id, messages.len());
+                }
+                Err(e) => tracing::warn!("Skipping invalid session key {}: {}", session_key, e),
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        session_id: Option<SessionId>,
+        limit: usize,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        let store = self.store.read().await;
+
+        Ok(match session_id {
+            Some(session_id) => search_messages(
+                store
+                    .messages_by_session
+                    .get(&session_id.to_string())
+                    .into_iter()
+                    .flatten(),
+                query,
+                limit,
+            ),
+            None => search_messages(store.messages_by_session.values().flatten(), query, limit),
+        })
+    }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        self.persist().await
+    }
+
+    async fn compact(&self) -> Result<usize, RepositoryError> {
+        let removed;
+        {
+            let mut store = self.store.write().await;
+            let before = store.messages_by_session.len();
+            store
+                .messages_by_session
+                .retain(|_, messages| !messages.is_empty());
+            removed = before - store.messages_by_session.len();
+        }
+        self.persist().await?;
+        Ok(removed)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::modules::chat::domain::MessageRole;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -216,7 +275,7 @@ mod tests {
             .unwrap();
 
         let session_id = SessionId::new();
-        let message = Message::new(session_id, MessageRole::User, "Hello".to_string());
+        let message = Message::new_user(session_id, "Hello".to_string());
         let id = message.id();
 
         repo.save(&message).await.unwrap();
@@ -237,7 +296,7 @@ mod tests {
 
         for i in 0..5 {
             let message =
-                Message::new(session_id, MessageRole::User, format!("Message {}", i));
+                Message::new_user(session_id, format!("Message {}", i));
             repo.save(&message).await.unwrap();
         }
 
@@ -248,6 +307,39 @@ mod tests {
 
         assert_eq!(result.items.len(), 3);
         assert_eq!(result.total, 5);
+
+        // 最后一页只返回剩余消息
+        let result = repo
+            .find_by_session(session_id, Pagination::new(2, 3))
+            .await
+            .unwrap();
+        assert_eq!(result.items.len(), 2);
+        assert_eq!(result.items[1].content(), "Message 4");
+
+        // 超出范围的页返回空列表，total 保持正确
+        let result = repo
+            .find_by_session(session_id, Pagination::new(3, 3))
+            .await
+            .unwrap();
+        assert!(result.items.is_empty());
+        assert_eq!(result.total, 5);
+    }
+
+    #[tokio::test]
+    async fn test_find_by_unknown_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = FileMessageRepository::new(temp_dir.path().to_path_buf())
+            .await
+            .unwrap();
+
+        let result = repo
+            .find_by_session(SessionId::new(), Pagination::default())
+            .await
+            .unwrap();
+
+        assert!(result.items.is_empty());
+        assert_eq!(result.total, 0);
+        assert!(!result.has_next());
     }
 
     #[tokio::test]
@@ -261,7 +353,7 @@ mod tests {
 
         for i in 0..3 {
             let message =
-                Message::new(session_id, MessageRole::User, format!("Message {}", i));
+                Message::new_user(session_id, format!("Message {}", i));
             repo.save(&message).await.unwrap();
         }
 
@@ -271,4 +363,55 @@ mod tests {
         let count = repo.count_by_session(session_id).await.unwrap();
         assert_eq!(count, 0);
     }
+
+    #[tokio::test]
+    async fn test_created_at_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+
+        let session_id = SessionId::new();
+        let message = Message::new_user(session_id, "Hello".to_string());
+        {
+            let repo = FileMessageRepository::new(path.clone()).await.unwrap();
+            repo.save(&message).await.unwrap();
+        }
+
+        let content = std::fs::read_to_string(path.join("messages.json")).unwrap();
+        assert!(content.contains(&crate::shared::timestamp::format(&message.created_at())));
+
+        let repo = FileMessageRepository::new(path).await.unwrap();
+        let loaded = repo.get(message.id()).await.unwrap().unwrap();
+        assert_eq!(loaded.created_at(), message.created_at());
+    }
+
+    #[tokio::test]
+    async fn test_same_instant_messages_keep_order_after_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+        let session_id = SessionId::new();
+
+        // 所有消息的创建时间完全相同
+        let template = Message::new_user(session_id, "Message ");
+        let mut expected = Vec::new();
+        {
+            let repo = FileMessageRepository::new(path.clone()).await.unwrap();
+            for i in 0..5 {
+                let mut message = template.clone();
+                message.set_id(MessageId::new());
+                message.append_content(&i.to_string());
+                repo.save(&message).await.unwrap();
+                expected.push(message.id());
+            }
+        }
+
+        let repo = FileMessageRepository::new(path).await.unwrap();
+        let result = repo
+            .find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap();
+        let ids: Vec<MessageId> = result.items.iter().map(Message::id).collect();
+        assert_eq!(ids, expected);
+        let sequences: Vec<u64> = result.items.iter().map(Message::sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+    }
 }
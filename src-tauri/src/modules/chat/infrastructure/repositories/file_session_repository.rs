@@ -111,14 +111,7 @@ impl SessionRepository for FileSessionRepository {
         all_sessions.sort_by(|a, b| b.updated_at().cmp(&a.updated_at()));
 
         let total = all_sessions.len();
-        let offset = pagination.offset() as usize;
-        let limit = pagination.limit as usize;
-
-        let items = if offset < total {
-            all_sessions[offset..total.min(offset + limit)].to_vec()
-        } else {
-            Vec::new()
-        };
+        let items = all_sessions[pagination.range(total)].to_vec();
 
         Ok(PaginatedResult::new(items, total, pagination))
     }
@@ -132,6 +125,10 @@ impl SessionRepository for FileSessionRepository {
         let store = self.store.read().await;
         Ok(store.sessions.len())
     }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        self.persist().await
+    }
 }
 
 #[cfg(test)]
@@ -196,4 +193,61 @@ mod tests {
         repo.delete(id).await.unwrap();
         assert!(!repo.exists(id).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_timestamps_round_trip_as_utc() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+
+        let session = Session::new(Some("Timestamps".to_string()), None);
+        let id = session.id();
+        {
+            let repo = FileSessionRepository::new(path.clone()).await.unwrap();
+            repo.save(&session).await.unwrap();
+        }
+
+        // 文件中以 RFC3339 UTC 存储
+        let content = std::fs::read_to_string(path.join("sessions.json")).unwrap();
+        let created_at = crate::shared::timestamp::format(&session.created_at());
+        assert!(created_at.ends_with('Z'));
+        assert!(content.contains(&created_at));
+
+        let repo = FileSessionRepository::new(path).await.unwrap();
+        let loaded = repo.get(id).await.unwrap().unwrap();
+        assert_eq!(loaded.created_at(), session.created_at());
+        assert_eq!(loaded.updated_at(), session.updated_at());
+    }
+
+    #[tokio::test]
+    async fn test_loads_legacy_timestamps() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_path_buf();
+        let id = SessionId::new();
+
+        // 旧数据：不带时区 / 带本地时区偏移
+        let legacy = serde_json::json!({
+            "sessions": {
+                id.to_string(): {
+                    "id": id,
+                    "title": "Legacy",
+                    "presetId": null,
+                    "modelConfig": null,
+                    "createdAt": "2024-01-02T03:04:05",
+                    "updatedAt": "2024-01-02T11:04:05+08:00"
+                }
+            }
+        });
+        std::fs::write(path.join("sessions.json"), legacy.to_string()).unwrap();
+
+        let repo = FileSessionRepository::new(path).await.unwrap();
+        let loaded = repo
+            .get(id)
+            .await
+            .unwrap()
+            .expect("legacy session should load");
+
+        let expected = crate::shared::timestamp::parse("2024-01-02T03:04:05Z").unwrap();
+        assert_eq!(loaded.created_at(), expected);
+        assert_eq!(loaded.updated_at(), expected);
+    }
 }
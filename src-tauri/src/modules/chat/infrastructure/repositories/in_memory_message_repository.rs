@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 
+use super::{search_messages, upsert_message};
 use crate::modules::chat::domain::{Message, MessageId, SessionId};
 use crate::modules::chat::ports::{
     MessageRepository, PaginatedResult, Pagination, RepositoryError,
@@ -46,13 +47,18 @@ impl MessageRepository for InMemoryMessageRepository {
     async fn save(&self, message: &Message) -> Result<(), RepositoryError> {
         let mut messages = self.messages.write().await;
         let session_messages = messages.entry(message.session_id()).or_default();
+        upsert_message(session_messages, message);
 
-        // 检查是否已存在（更新）
-        if let Some(existing) = session_messages.iter_mut().find(|m| m.id() == message.id()) {
-            *existing = message.clone();
-        } else {
-            session_messages.push(message.clone());
-        }
+        Ok(())
+    }
+
+    async fn update(&self, message: &Message) -> Result<(), RepositoryError> {
+        let mut messages = self.messages.write().await;
+        let session_messages = messages
+            .get_mut(&message.session_id())
+            .filter(|msgs| msgs.iter().any(|m| m.id() == message.id()))
+            .ok_or_else(|| RepositoryError::NotFound(message.id().to_string()))?;
+        upsert_message(session_messages, message);
 
         Ok(())
     }
@@ -75,19 +81,11 @@ impl MessageRepository for InMemoryMessageRepository {
         let messages = self.messages.read().await;
 
         let session_messages = messages.get(&session_id);
-        let all_messages: Vec<Message> = session_messages
-            .map(|msgs| msgs.clone())
-            .unwrap_or_default();
+        let mut all_messages: Vec<Message> = session_messages.cloned().unwrap_or_default();
+        all_messages.sort_by_key(Message::order_key);
 
         let total = all_messages.len();
-        let offset = pagination.offset() as usize;
-        let limit = pagination.limit as usize;
-
-        let items = if offset < total {
-            all_messages[offset..total.min(offset + limit)].to_vec()
-        } else {
-            Vec::new()
-        };
+        let items = all_messages[pagination.range(total)].to_vec();
 
         Ok(PaginatedResult::new(items, total, pagination))
     }
@@ -110,7 +108,8 @@ impl MessageRepository for InMemoryMessageRepository {
 
         Ok(messages
             .get(&session_id)
-            .and_then(|msgs| msgs.last().cloned()))
+            .and_then(|msgs| msgs.iter().max_by_key(|m| m.order_key()))
+            .cloned())
     }
 
     async fn count_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError> {
@@ -121,6 +120,46 @@ impl MessageRepository for InMemoryMessageRepository {
             .map(|msgs| msgs.len())
             .unwrap_or(0))
     }
+
+    async fn count_all_by_session(&self) -> Result<HashMap<SessionId, usize>, RepositoryError> {
+        let messages = self.messages.read().await;
+
+        Ok(messages
+            .iter()
+            .filter(|(_, msgs)| !msgs.is_empty())
+            .map(|(session_id, msgs)| (*session_id, msgs.len()))
+            .collect())
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        session_id: Option<SessionId>,
+        limit: usize,
+    ) -> Result<Vec<Message>, RepositoryError> {
+        let messages = self.messages.read().await;
+
+        Ok(match session_id {
+            Some(session_id) => search_messages(
+                messages.get(&session_id).into_iter().flatten(),
+                query,
+                limit,
+            ),
+            None => search_messages(messages.values().flatten(), query, limit),
+        })
+    }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        Ok(())
+    }
+
+    async fn compact(&self) -> Result<usize, RepositoryError> {
+        let mut messages = self.messages.write().await;
+
+        let before = messages.len();
+        messages.retain(|_, session_messages| !session_messages.is_empty());
+        Ok(before - messages.len())
+    }
 }
 
 #[cfg(test)]
@@ -141,6 +180,29 @@ mod tests {
         assert_eq!(retrieved.unwrap().content(), "Hello");
     }
 
+    #[tokio::test]
+    async fn test_compact_removes_empty_session_entries() {
+        let repo = InMemoryMessageRepository::new();
+        let kept = SessionId::new();
+        repo.save(&Message::new_user(kept, "Kept")).await.unwrap();
+
+        // 删除最后一条消息后会话分组仍保留为空条目
+        for _ in 0..2 {
+            let message = Message::new_user(SessionId::new(), "Deleted");
+            repo.save(&message).await.unwrap();
+            repo.delete(message.id()).await.unwrap();
+        }
+        assert_eq!(repo.messages.read().await.len(), 3);
+
+        assert_eq!(repo.compact().await.unwrap(), 2);
+        let messages = repo.messages.read().await;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[&kept].len(), 1);
+        drop(messages);
+
+        assert_eq!(repo.compact().await.unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_find_by_session() {
         let repo = InMemoryMessageRepository::new();
@@ -161,6 +223,26 @@ mod tests {
         assert_eq!(result.total, 5);
     }
 
+    #[tokio::test]
+    async fn test_update_requires_existing_message() {
+        let repo = InMemoryMessageRepository::new();
+        let session_id = SessionId::new();
+        let mut message = Message::new_user(session_id, "Hello");
+
+        assert!(matches!(
+            repo.update(&message).await,
+            Err(RepositoryError::NotFound(_))
+        ));
+
+        repo.save(&message).await.unwrap();
+        message.edit_content("Hello again");
+        repo.update(&message).await.unwrap();
+
+        let stored = repo.get(message.id()).await.unwrap().unwrap();
+        assert_eq!(stored.content(), "Hello again");
+        assert_eq!(repo.count_by_session(session_id).await.unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn test_delete_by_session() {
         let repo = InMemoryMessageRepository::new();
@@ -177,4 +259,43 @@ mod tests {
         let count = repo.count_by_session(session_id).await.unwrap();
         assert_eq!(count, 0);
     }
+
+    #[tokio::test]
+    async fn test_same_instant_messages_keep_save_order() {
+        let repo = InMemoryMessageRepository::new();
+        let session_id = SessionId::new();
+
+        // 所有消息的创建时间完全相同
+        let template = Message::new_user(session_id, "Message ");
+        let mut saved = Vec::new();
+        for i in 0..5 {
+            let mut msg = template.clone();
+            msg.set_id(MessageId::new());
+            msg.append_content(&i.to_string());
+            repo.save(&msg).await.unwrap();
+            saved.push(msg);
+        }
+
+        // 更新已有消息不改变其位置
+        saved[0].append_content(" (edited)");
+        repo.save(&saved[0]).await.unwrap();
+
+        let result = repo
+            .find_by_session(session_id, Pagination::new(1, 10))
+            .await
+            .unwrap();
+        let ids: Vec<MessageId> = result.items.iter().map(Message::id).collect();
+        let expected: Vec<MessageId> = saved.iter().map(Message::id).collect();
+        assert_eq!(ids, expected);
+        let sequences: Vec<u64> = result.items.iter().map(Message::sequence).collect();
+        assert_eq!(sequences, vec![1, 2, 3, 4, 5]);
+        assert_eq!(result.items[0].content(), "Message 0 (edited)");
+
+        let last = repo
+            .find_last_by_session(session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(last.id(), saved[4].id());
+    }
 }
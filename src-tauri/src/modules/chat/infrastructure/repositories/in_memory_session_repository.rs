@@ -58,14 +58,7 @@ impl SessionRepository for InMemorySessionRepository {
         all_sessions.sort_by(|a, b| b.updated_at().cmp(&a.updated_at()));
 
         let total = all_sessions.len();
-        let offset = pagination.offset() as usize;
-        let limit = pagination.limit as usize;
-
-        let items = if offset < total {
-            all_sessions[offset..total.min(offset + limit)].to_vec()
-        } else {
-            Vec::new()
-        };
+        let items = all_sessions[pagination.range(total)].to_vec();
 
         Ok(PaginatedResult::new(items, total, pagination))
     }
@@ -79,6 +72,10 @@ impl SessionRepository for InMemorySessionRepository {
         let sessions = self.sessions.read().await;
         Ok(sessions.len())
     }
+
+    async fn flush(&self) -> Result<(), RepositoryError> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -3,13 +3,52 @@
 // 仓储实现：
 // - InMemory*Repository: 内存仓储，用于开发和测试
 // - File*Repository: 文件持久化仓储，用于生产环境
+// - Cached*Repository: 包装任意仓储的 LRU 缓存装饰器
 
+mod cached_repository;
 mod file_message_repository;
 mod file_session_repository;
 mod in_memory_message_repository;
 mod in_memory_session_repository;
 
+pub use cached_repository::*;
 pub use file_message_repository::*;
 pub use file_session_repository::*;
 pub use in_memory_message_repository::*;
 pub use in_memory_session_repository::*;
+
+use crate::modules::chat::domain::{find_case_insensitive, Message};
+
+/// 插入或更新会话内的消息
+///
+/// 新消息分配会话内下一个序号，已指定序号的新消息（如替换原有回复）保留其序号；
+/// 更新时保留已分配的序号
+fn upsert_message(messages: &mut Vec<Message>, message: &Message) {
+    if let Some(existing) = messages.iter_mut().find(|m| m.id() == message.id()) {
+        let sequence = existing.sequence();
+        *existing = message.clone();
+        existing.set_sequence(sequence);
+    } else {
+        let mut message = message.clone();
+        if message.sequence() == 0 {
+            let next = messages.iter().map(Message::sequence).max().unwrap_or(0) + 1;
+            message.set_sequence(next);
+        }
+        messages.push(message);
+    }
+}
+
+/// 在消息中查找包含关键词的消息（不区分大小写），最新的在前，最多返回 `limit` 条
+fn search_messages<'a>(
+    messages: impl Iterator<Item = &'a Message>,
+    query: &str,
+    limit: usize,
+) -> Vec<Message> {
+    let mut matches: Vec<Message> = messages
+        .filter(|m| find_case_insensitive(m.content(), query).is_some())
+        .cloned()
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse((m.created_at(), m.sequence())));
+    matches.truncate(limit);
+    matches
+}
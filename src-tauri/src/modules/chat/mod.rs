@@ -11,23 +11,52 @@ pub mod domain;
 pub mod infrastructure;
 pub mod ports;
 
+#[cfg(test)]
+pub mod test_support;
+
 // 重新导出常用类型
 pub use application::{
     // Traits
     ApplicationError,
     CommandHandler,
     // Commands
+    ClearSessionMessagesCommand,
+    ClearSessionMessagesHandler,
+    ClearSessionMessagesResponse,
+    CompactStoresCommand,
+    CompactStoresHandler,
+    CompactStoresResponse,
     CreateSessionCommand,
     CreateSessionHandler,
     CreateSessionResponse,
+    DeleteMessageCommand,
+    DeleteMessageHandler,
+    DeleteMessageResponse,
     DeleteSessionCommand,
     DeleteSessionHandler,
     DeleteSessionResponse,
+    EditMessageCommand,
+    EditMessageHandler,
+    EditMessageResponse,
+    ForkSessionCommand,
+    ForkSessionHandler,
+    ForkSessionResponse,
+    ImportSessionCommand,
+    ImportSessionHandler,
+    ImportSessionResponse,
+    InsertContextBreakCommand,
+    InsertContextBreakHandler,
+    InsertContextBreakResponse,
+    ReconcileStoresCommand,
+    ReconcileStoresHandler,
+    ReconcileStoresResponse,
     // Regenerate
     RegenerateCommand,
     RegenerateHandler,
     RegenerateResponse,
     // Queries
+    ExportSessionHandler,
+    ExportSessionQuery,
     GetSessionHandler,
     GetSessionQuery,
     GetSessionResponse,
@@ -38,23 +67,34 @@ pub use application::{
     ListSessionsQuery,
     ListSessionsResponse,
     QueryHandler,
+    ResponseTiming,
+    SearchMessagesHandler,
+    SearchMessagesQuery,
+    SearchMessagesResponse,
+    SessionExport,
     SendMessageCommand,
     SendMessageHandler,
     SendMessageResponse,
     StreamEvent,
+    completion_events,
+    SuggestRepliesHandler,
+    SuggestRepliesQuery,
+    SuggestRepliesResponse,
     UpdateSessionCommand,
     UpdateSessionHandler,
     UpdateSessionResponse,
 };
 
 pub use domain::{
-    ContextBuilder, Emotion, EmotionAnalyzer, Message, MessageId, MessageRole, Session, SessionId,
+    AutoTitlePolicy, ContentFilter, ContextBuilder, EmojiEmotionTable, Emotion, EmotionAnalyzer,
+    HeuristicTokenEstimator, Message, MessageId, MessageRole, RepetitionPolicy, Session, SessionId,
+    TokenEstimator, WordListFilter,
 };
 
 pub use infrastructure::{
-    DynamicLLMAdapter, DynamicLLMConfig, FileMessageRepository, FileSessionRepository,
-    InMemoryMessageRepository, InMemorySessionRepository, LLMAdapterRegistry, MockLLMAdapter,
-    OpenAIAdapter,
+    CacheStats, CachedMessageRepository, CachedSessionRepository, FileMessageRepository,
+    FileSessionRepository, InMemoryMessageRepository, InMemorySessionRepository,
+    LLMAdapterRegistry, MockLLMAdapter, OpenAIAdapter, ScriptStep, ScriptedLLM,
 };
 
 pub use ports::{
@@ -72,15 +112,33 @@ pub struct ChatModule {
     // Repositories
     session_repository: Arc<dyn SessionRepository>,
     message_repository: Arc<dyn MessageRepository>,
+    // 持久化仓储的缓存层（内存仓储不缓存）
+    session_cache: Option<Arc<CachedSessionRepository<FileSessionRepository>>>,
+    message_cache: Option<Arc<CachedMessageRepository<FileMessageRepository>>>,
     // LLM
     llm_registry: Arc<LLMAdapterRegistry>,
+    // 助手回复后处理
+    content_filter: Option<Arc<dyn ContentFilter>>,
+    repetition_guard: Option<RepetitionPolicy>,
+    emotion_analyzer: EmotionAnalyzer,
+    token_estimator: Arc<dyn TokenEstimator>,
     // Handlers
     create_session_handler: CreateSessionHandler,
     delete_session_handler: DeleteSessionHandler,
     update_session_handler: UpdateSessionHandler,
+    insert_context_break_handler: InsertContextBreakHandler,
+    clear_session_messages_handler: ClearSessionMessagesHandler,
+    edit_message_handler: EditMessageHandler,
+    delete_message_handler: DeleteMessageHandler,
+    fork_session_handler: ForkSessionHandler,
+    import_session_handler: ImportSessionHandler,
+    reconcile_stores_handler: ReconcileStoresHandler,
+    compact_stores_handler: CompactStoresHandler,
+    export_session_handler: ExportSessionHandler,
     get_session_handler: GetSessionHandler,
     list_sessions_handler: ListSessionsHandler,
     list_messages_handler: ListMessagesHandler,
+    search_messages_handler: SearchMessagesHandler,
 }
 
 impl ChatModule {
@@ -110,17 +168,22 @@ impl ChatModule {
         data_dir: std::path::PathBuf,
         llm_registry: Arc<LLMAdapterRegistry>,
     ) -> Result<Self, RepositoryError> {
-        // 创建持久化仓储
-        let session_repository: Arc<dyn SessionRepository> =
-            Arc::new(FileSessionRepository::new(data_dir.clone()).await?);
-        let message_repository: Arc<dyn MessageRepository> =
-            Arc::new(FileMessageRepository::new(data_dir).await?);
-
-        Ok(Self::with_repositories(
-            session_repository,
-            message_repository,
+        // 创建持久化仓储，读取经由 LRU 缓存
+        let session_cache = Arc::new(CachedSessionRepository::new(
+            FileSessionRepository::new(data_dir.clone()).await?,
+        ));
+        let message_cache = Arc::new(CachedMessageRepository::new(
+            FileMessageRepository::new(data_dir).await?,
+        ));
+
+        let mut module = Self::with_repositories(
+            session_cache.clone(),
+            message_cache.clone(),
             llm_registry,
-        ))
+        );
+        module.session_cache = Some(session_cache);
+        module.message_cache = Some(message_cache);
+        Ok(module)
     }
 
     /// 使用自定义仓储创建 ChatModule
@@ -133,23 +196,83 @@ impl ChatModule {
         let delete_session_handler =
             DeleteSessionHandler::new(session_repository.clone(), message_repository.clone());
         let update_session_handler = UpdateSessionHandler::new(session_repository.clone());
+        let insert_context_break_handler =
+            InsertContextBreakHandler::new(session_repository.clone(), message_repository.clone());
+        let clear_session_messages_handler = ClearSessionMessagesHandler::new(
+            session_repository.clone(),
+            message_repository.clone(),
+        );
+        let edit_message_handler = EditMessageHandler::new(message_repository.clone());
+        let delete_message_handler = DeleteMessageHandler::new(message_repository.clone());
+        let fork_session_handler =
+            ForkSessionHandler::new(session_repository.clone(), message_repository.clone());
+        let import_session_handler =
+            ImportSessionHandler::new(session_repository.clone(), message_repository.clone());
+        let reconcile_stores_handler =
+            ReconcileStoresHandler::new(session_repository.clone(), message_repository.clone());
+        let compact_stores_handler =
+            CompactStoresHandler::new(session_repository.clone(), message_repository.clone());
+        let export_session_handler =
+            ExportSessionHandler::new(session_repository.clone(), message_repository.clone());
         let get_session_handler = GetSessionHandler::new(session_repository.clone());
         let list_sessions_handler = ListSessionsHandler::new(session_repository.clone());
         let list_messages_handler = ListMessagesHandler::new(message_repository.clone());
+        let search_messages_handler = SearchMessagesHandler::new(message_repository.clone());
 
         Self {
             session_repository,
             message_repository,
+            session_cache: None,
+            message_cache: None,
             llm_registry,
+            content_filter: None,
+            repetition_guard: None,
+            emotion_analyzer: EmotionAnalyzer::new(),
+            token_estimator: Arc::new(HeuristicTokenEstimator::default()),
             create_session_handler,
             delete_session_handler,
             update_session_handler,
+            insert_context_break_handler,
+            clear_session_messages_handler,
+            edit_message_handler,
+            delete_message_handler,
+            fork_session_handler,
+            import_session_handler,
+            reconcile_stores_handler,
+            compact_stores_handler,
+            export_session_handler,
             get_session_handler,
             list_sessions_handler,
             list_messages_handler,
+            search_messages_handler,
         }
     }
 
+    /// 设置助手回复的内容过滤器，None 表示不过滤
+    pub fn set_content_filter(&mut self, content_filter: Option<Arc<dyn ContentFilter>>) {
+        self.content_filter = content_filter;
+    }
+
+    /// 设置流式回复的重复检测，陷入循环时提前停止；None 表示不检测
+    pub fn set_repetition_guard(&mut self, repetition_guard: Option<RepetitionPolicy>) {
+        self.repetition_guard = repetition_guard;
+    }
+
+    /// 追加或覆盖表情到情感的映射，影响之后生成的助手回复
+    pub fn extend_emoji_emotions(&mut self, entries: impl IntoIterator<Item = (String, Emotion)>) {
+        self.emotion_analyzer.emoji_table_mut().extend(entries);
+    }
+
+    /// 替换 Token 估算器
+    pub fn set_token_estimator(&mut self, token_estimator: Arc<dyn TokenEstimator>) {
+        self.token_estimator = token_estimator;
+    }
+
+    /// 估算文本的 Token 数量（近似值）
+    pub fn estimate_tokens(&self, text: &str, model: Option<&str>) -> u32 {
+        self.token_estimator.estimate(text, model)
+    }
+
     // Command handlers
 
     /// 创建会话
@@ -176,27 +299,93 @@ impl ChatModule {
         self.update_session_handler.handle(command).await
     }
 
+    /// 插入上下文断点
+    pub async fn insert_context_break(
+        &self,
+        command: InsertContextBreakCommand,
+    ) -> Result<InsertContextBreakResponse, ApplicationError> {
+        self.insert_context_break_handler.handle(command).await
+    }
+
+    /// 清空会话消息，保留会话本身
+    pub async fn clear_session_messages(
+        &self,
+        command: ClearSessionMessagesCommand,
+    ) -> Result<ClearSessionMessagesResponse, ApplicationError> {
+        self.clear_session_messages_handler.handle(command).await
+    }
+
+    /// 编辑消息内容
+    pub async fn edit_message(
+        &self,
+        command: EditMessageCommand,
+    ) -> Result<EditMessageResponse, ApplicationError> {
+        self.edit_message_handler.handle(command).await
+    }
+
+    /// 在指定消息处分支出新会话
+    pub async fn fork_session(
+        &self,
+        command: ForkSessionCommand,
+    ) -> Result<ForkSessionResponse, ApplicationError> {
+        self.fork_session_handler.handle(command).await
+    }
+
+    /// 从导出的 JSON 导入会话
+    pub async fn import_session(
+        &self,
+        command: ImportSessionCommand,
+    ) -> Result<ImportSessionResponse, ApplicationError> {
+        self.import_session_handler.handle(command).await
+    }
+
+    /// 检查会话与消息存储是否一致，可选为孤立消息创建占位会话
+    pub async fn reconcile_stores(
+        &self,
+        command: ReconcileStoresCommand,
+    ) -> Result<ReconcileStoresResponse, ApplicationError> {
+        self.reconcile_stores_handler.handle(command).await
+    }
+
+    /// 压缩会话与消息存储，移除空条目
+    pub async fn compact_stores(
+        &self,
+        command: CompactStoresCommand,
+    ) -> Result<CompactStoresResponse, ApplicationError> {
+        self.compact_stores_handler.handle(command).await
+    }
+
+    /// 将会话和消息写入磁盘
+    pub async fn flush(&self) -> Result<(), ApplicationError> {
+        self.session_repository.flush().await?;
+        self.message_repository.flush().await?;
+        Ok(())
+    }
+
+    /// 删除单条消息
+    pub async fn delete_message(
+        &self,
+        command: DeleteMessageCommand,
+    ) -> Result<DeleteMessageResponse, ApplicationError> {
+        self.delete_message_handler.handle(command).await
+    }
+
     /// 发送消息（创建临时处理器）
     pub async fn send_message(
         &self,
         command: SendMessageCommand,
         provider_id: &str,
     ) -> Result<SendMessageResponse, ApplicationError> {
-        let llm = self.llm_registry.get(provider_id).ok_or_else(|| {
-            ApplicationError::LLMError(LLMError::ProviderNotAvailable(provider_id.to_string()))
-        })?;
-
-        let default_model = self
-            .llm_registry
-            .get_default_model(provider_id)
-            .unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+        let (llm, default_model) = self.resolve_provider(provider_id).await?;
 
         let handler = SendMessageHandler::new(
             self.session_repository.clone(),
             self.message_repository.clone(),
             llm,
             default_model,
-        );
+        )
+        .with_content_filter(self.content_filter.clone())
+        .with_emotion_analyzer(self.emotion_analyzer.clone());
 
         handler.handle(command).await
     }
@@ -213,21 +402,17 @@ impl ChatModule {
         ),
         ApplicationError,
     > {
-        let llm = self.llm_registry.get(provider_id).ok_or_else(|| {
-            ApplicationError::LLMError(LLMError::ProviderNotAvailable(provider_id.to_string()))
-        })?;
-
-        let default_model = self
-            .llm_registry
-            .get_default_model(provider_id)
-            .unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+        let (llm, default_model) = self.resolve_provider(provider_id).await?;
 
         let handler = SendMessageHandler::new(
             self.session_repository.clone(),
             self.message_repository.clone(),
             llm,
             default_model,
-        );
+        )
+        .with_content_filter(self.content_filter.clone())
+        .with_repetition_guard(self.repetition_guard)
+        .with_emotion_analyzer(self.emotion_analyzer.clone());
 
         handler.handle_stream(command).await
     }
@@ -237,34 +422,68 @@ impl ChatModule {
         &self,
         command: RegenerateCommand,
         provider_id: &str,
-    ) -> Result<
-        (
-            RegenerateResponse,
-            tokio::sync::mpsc::Receiver<StreamEvent>,
-        ),
-        ApplicationError,
-    > {
-        let llm = self.llm_registry.get(provider_id).ok_or_else(|| {
-            ApplicationError::LLMError(LLMError::ProviderNotAvailable(provider_id.to_string()))
-        })?;
-
-        let default_model = self
-            .llm_registry
-            .get_default_model(provider_id)
-            .unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+    ) -> Result<(RegenerateResponse, tokio::sync::mpsc::Receiver<StreamEvent>), ApplicationError>
+    {
+        let (llm, default_model) = self.resolve_provider(provider_id).await?;
 
         let handler = RegenerateHandler::new(
             self.session_repository.clone(),
             self.message_repository.clone(),
             llm,
             default_model,
-        );
+        )
+        .with_content_filter(self.content_filter.clone())
+        .with_repetition_guard(self.repetition_guard)
+        .with_emotion_analyzer(self.emotion_analyzer.clone());
 
         handler.handle_stream(command).await
     }
 
+    /// 根据对话生成建议回复（不保存任何消息）
+    pub async fn suggest_replies(
+        &self,
+        query: SuggestRepliesQuery,
+        provider_id: &str,
+    ) -> Result<SuggestRepliesResponse, ApplicationError> {
+        let (llm, default_model) = self.resolve_provider(provider_id).await?;
+
+        let handler = SuggestRepliesHandler::new(
+            self.session_repository.clone(),
+            self.message_repository.clone(),
+            llm,
+            default_model,
+        );
+
+        handler.handle(query).await
+    }
+
+    /// 从注册表解析提供商的适配器和默认模型
+    ///
+    /// 流式任务持有此处返回的适配器直至结束，因此生成过程中删除提供商不会中断当前流；
+    /// 之后对已删除提供商的请求返回 `ProviderNotAvailable`
+    async fn resolve_provider(
+        &self,
+        provider_id: &str,
+    ) -> Result<(Arc<dyn LLMPort>, String), ApplicationError> {
+        let resolved = self.llm_registry.resolve(provider_id).await;
+        let (llm, default_model) = resolved.ok_or_else(|| {
+            ApplicationError::LLMError(LLMError::ProviderNotAvailable(provider_id.to_string()))
+        })?;
+        let default_model = default_model.unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+
+        Ok((llm, default_model))
+    }
+
     // Query handlers
 
+    /// 导出会话及其全部消息
+    pub async fn export_session(
+        &self,
+        query: ExportSessionQuery,
+    ) -> Result<SessionExport, ApplicationError> {
+        self.export_session_handler.handle(query).await
+    }
+
     /// 获取会话
     pub async fn get_session(
         &self,
@@ -289,6 +508,44 @@ impl ChatModule {
         self.list_messages_handler.handle(query).await
     }
 
+    /// 搜索消息内容
+    pub async fn search_messages(
+        &self,
+        query: SearchMessagesQuery,
+    ) -> Result<SearchMessagesResponse, ApplicationError> {
+        self.search_messages_handler.handle(query).await
+    }
+
+    /// 按策略判断是否应为会话自动生成标题
+    pub async fn should_auto_title(
+        &self,
+        session_id: SessionId,
+        policy: AutoTitlePolicy,
+    ) -> Result<bool, ApplicationError> {
+        let session = self
+            .session_repository
+            .get(session_id)
+            .await?
+            .ok_or_else(|| ApplicationError::SessionNotFound(session_id.to_string()))?;
+
+        let total = self.message_repository.count_by_session(session_id).await?;
+        let messages = self
+            .message_repository
+            .find_by_session(session_id, Pagination::new(1, total.max(1) as u32))
+            .await?;
+        let conversation = messages
+            .items
+            .iter()
+            .filter(|m| matches!(m.role(), MessageRole::User | MessageRole::Assistant))
+            .count();
+
+        Ok(domain::should_generate_title(
+            policy,
+            conversation,
+            session.title(),
+        ))
+    }
+
     // Accessors
 
     /// 获取 LLM 注册表
@@ -296,6 +553,23 @@ impl ChatModule {
         &self.llm_registry
     }
 
+    /// 仓储缓存的命中统计，未启用缓存时为空
+    pub fn cache_stats(&self) -> Vec<CacheStats> {
+        let sessions = self.session_cache.as_ref().map(|cache| cache.stats());
+        let messages = self.message_cache.as_ref().map(|cache| cache.stats());
+        sessions.into_iter().chain(messages).collect()
+    }
+
+    /// 清空仓储缓存并重置统计
+    pub fn clear_caches(&self) {
+        if let Some(cache) = &self.session_cache {
+            cache.clear();
+        }
+        if let Some(cache) = &self.message_cache {
+            cache.clear();
+        }
+    }
+
     /// 获取会话仓储
     pub fn session_repository(&self) -> &Arc<dyn SessionRepository> {
         &self.session_repository
@@ -346,4 +620,199 @@ mod tests {
 
         assert_eq!(list_resp.total, 0);
     }
+
+    #[tokio::test]
+    async fn test_persistent_module_caches_repository_reads() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let module = ChatModule::new_with_persistence(
+            temp_dir.path().to_path_buf(),
+            Arc::new(LLMAdapterRegistry::new()),
+        )
+        .await
+        .unwrap();
+        let session_id = module
+            .create_session(CreateSessionCommand::new(None, None))
+            .await
+            .unwrap()
+            .session
+            .id();
+
+        for _ in 0..2 {
+            module
+                .get_session(GetSessionQuery::new(session_id))
+                .await
+                .unwrap();
+        }
+        let stats = module.cache_stats();
+        let names: Vec<&str> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["sessions", "messages"]);
+        assert_eq!((stats[0].hits, stats[0].misses, stats[0].entries), (1, 1, 1));
+
+        module.clear_caches();
+        let stats = module.cache_stats();
+        assert_eq!((stats[0].hits, stats[0].misses, stats[0].entries), (0, 0, 0));
+
+        // 内存仓储不缓存
+        assert!(ChatModule::new(Arc::new(LLMAdapterRegistry::new()))
+            .cache_stats()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_provider_deleted_while_streaming() {
+        use crate::modules::chat::test_support::{
+            collect_events, ChatTestHarness, ScriptedLLM, SCRIPTED_PROVIDER_ID,
+        };
+        use std::time::Duration;
+
+        let llm = ScriptedLLM::new()
+            .chunk("Hello, ")
+            .delay(Duration::from_millis(50))
+            .chunk("world");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Hi").await.unwrap();
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Chunk(c)) if c == "Hello, "));
+
+        // 生成过程中删除提供商
+        harness
+            .module
+            .llm_registry()
+            .invalidate(SCRIPTED_PROVIDER_ID)
+            .await;
+
+        // 当前流正常完成并保存
+        let events = collect_events(&mut rx).await;
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { full_content, .. }) if full_content == "Hello, world"
+        ));
+        let messages = harness.messages(session_id).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].content(), "Hello, world");
+
+        // 新请求明确失败
+        let result = harness.send_stream(session_id, "Again").await;
+        assert!(matches!(
+            result,
+            Err(ApplicationError::LLMError(LLMError::ProviderNotAvailable(id)))
+                if id == SCRIPTED_PROVIDER_ID
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_default_falls_back_to_healthy_provider() {
+        use crate::modules::chat::test_support::{collect_events, ScriptedLLM};
+
+        let default_llm = Arc::new(ScriptedLLM::replying("From default"));
+        let alternate_llm = Arc::new(ScriptedLLM::replying("From alternate"));
+
+        let earlier_llm = Arc::new(ScriptedLLM::replying("From earlier"));
+
+        let registry = Arc::new(LLMAdapterRegistry::new());
+        for (id, llm) in [
+            ("default", &default_llm),
+            ("alternate", &alternate_llm),
+            ("earlier", &earlier_llm),
+        ] {
+            registry
+                .register_adapter(
+                    LLMProviderConfig {
+                        id: id.to_string(),
+                        ..Default::default()
+                    },
+                    llm.clone(),
+                )
+                .await;
+        }
+        let module = ChatModule::new(registry.clone());
+        let session_id = module
+            .create_session(CreateSessionCommand::new(None, None))
+            .await
+            .unwrap()
+            .session
+            .id();
+
+        // 按用户配置的顺序回退：未创建适配器的提供商跳过，"earlier" 按 ID 排在前面但顺序靠后
+        let order: Vec<String> = ["default", "unregistered", "alternate", "earlier"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        // 默认提供商健康时不回退
+        assert_eq!(registry.healthy_fallback("default", &order).await, None);
+
+        registry.health().record("default", false);
+        registry.health().record("alternate", true);
+        let provider_id = registry
+            .healthy_fallback("default", &order)
+            .await
+            .expect("should fall back");
+        assert_eq!(provider_id, "alternate");
+
+        let command = SendMessageCommand::new(session_id, "Hi", None, true);
+        let (_, mut rx) = module
+            .send_message_stream(command, &provider_id)
+            .await
+            .unwrap();
+        let events = collect_events(&mut rx).await;
+
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { full_content, .. }) if full_content == "From alternate"
+        ));
+        assert!(default_llm.requests().is_empty());
+        assert_eq!(alternate_llm.requests().len(), 1);
+
+        // 不健康的提供商跳过，继续按顺序查找
+        registry.health().record("alternate", false);
+        assert_eq!(
+            registry.healthy_fallback("default", &order).await.as_deref(),
+            Some("earlier")
+        );
+
+        // 没有健康的替代时不回退
+        registry.health().record("earlier", false);
+        assert_eq!(registry.healthy_fallback("default", &order).await, None);
+
+        // 已创建适配器但不在配置中的提供商不参与回退
+        registry.health().record("earlier", true);
+        let configured = ["default".to_string(), "alternate".to_string()];
+        assert_eq!(registry.healthy_fallback("default", &configured).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_repetition_guard_stops_looping_stream() {
+        use crate::modules::chat::test_support::{collect_events, ChatTestHarness, ScriptedLLM};
+
+        let mut llm = ScriptedLLM::new().chunk("Sure. ");
+        for _ in 0..20 {
+            llm = llm.chunk("again and ");
+        }
+        let mut harness = ChatTestHarness::new(llm.chunk("never reached")).await;
+        harness.module.set_repetition_guard(Some(RepetitionPolicy {
+            max_pattern_len: 20,
+            min_repeats: 5,
+        }));
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Hi").await.unwrap();
+        let events = collect_events(&mut rx).await;
+
+        // 停止通知紧接在 Done 之前
+        let [.., stopped, StreamEvent::Done { full_content, .. }] = events.as_slice() else {
+            panic!("expected Done, got {:?}", events);
+        };
+        let StreamEvent::RepetitionStopped { pattern, .. } = stopped else {
+            panic!("expected RepetitionStopped, got {:?}", stopped);
+        };
+        assert!(pattern.contains("again"));
+        assert!(!full_content.contains("never reached"));
+
+        // 已生成的部分照常保存
+        let messages = harness.messages(session_id).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].content(), full_content);
+    }
 }
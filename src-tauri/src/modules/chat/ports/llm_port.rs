@@ -1,11 +1,15 @@
 use async_trait::async_trait;
-use futures::Stream;
+use futures::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::pin::Pin;
 use thiserror::Error;
 
+pub use crate::modules::chat::domain::FinishReason;
+
 /// LLM 错误类型
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum LLMError {
     #[error("Network error: {0}")]
     NetworkError(String),
@@ -16,6 +20,9 @@ pub enum LLMError {
     #[error("Rate limit exceeded, retry after {retry_after_secs}s")]
     RateLimitError { retry_after_secs: u64 },
 
+    #[error("Provider overloaded, retry after {retry_after_secs}s")]
+    Overloaded { retry_after_secs: u64 },
+
     #[error("Authentication failed: {0}")]
     AuthenticationError(String),
 
@@ -34,18 +41,97 @@ pub enum LLMError {
     #[error("Provider not available: {0}")]
     ProviderNotAvailable(String),
 
+    #[error("Operation not supported: {0}")]
+    Unsupported(&'static str),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
 /// LLM 提供商类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ProviderType {
     OpenAI,
     Claude,
     Ollama,
+    Gemini,
+    #[default]
     Custom,
+    /// 演示模式，按预设脚本离线回复
+    Demo,
+}
+
+impl ProviderType {
+    /// 从字符串解析提供商类型（不区分大小写），无法识别时为 Custom
+    pub fn resolve(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai" => Self::OpenAI,
+            "claude" | "anthropic" => Self::Claude,
+            "ollama" => Self::Ollama,
+            "gemini" | "google" => Self::Gemini,
+            "demo" => Self::Demo,
+            _ => Self::Custom,
+        }
+    }
+
+    /// 根据 base_url 的主机名推测提供商类型，无法判断时返回 None
+    pub fn detect_from_url(url: &str) -> Option<Self> {
+        let url = url.trim().to_ascii_lowercase();
+        let without_scheme = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+        let authority = without_scheme.split('/').next().unwrap_or_default();
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        };
+
+        if host == "anthropic.com" || host.ends_with(".anthropic.com") {
+            Some(Self::Claude)
+        } else if host == "generativelanguage.googleapis.com" {
+            Some(Self::Gemini)
+        } else if host == "api.openai.com" {
+            Some(Self::OpenAI)
+        } else if port == Some("11434") {
+            Some(Self::Ollama)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenAI => "openai",
+            Self::Claude => "claude",
+            Self::Ollama => "ollama",
+            Self::Gemini => "gemini",
+            Self::Custom => "custom",
+            Self::Demo => "demo",
+        }
+    }
+}
+
+/// 通过探测得到的提供商能力
+///
+/// 同一类型提供商的不同部署（官方 API、代理、自建网关）支持的功能可能不同；
+/// 默认值为保守假设，即不依赖任何可选功能
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    /// 是否按 SSE 返回流式响应
+    pub streaming: bool,
+    /// 流式响应末尾是否返回 Token 用量（如 OpenAI 的 stream_options.include_usage）
+    pub stream_usage: bool,
+}
+
+/// 能力探测缓存的使用情况
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CapabilityCacheUsage {
+    /// 直接使用缓存结果的次数
+    pub hits: u64,
+    /// 需要探测的次数
+    pub misses: u64,
+    /// 当前是否缓存了探测结果（包括探测失败的记录）
+    pub cached: bool,
 }
 
 /// 提供商信息
@@ -56,6 +142,35 @@ pub struct ProviderInfo {
     pub name: String,
     pub provider_type: ProviderType,
     pub models: Vec<ModelInfo>,
+    /// 是否支持取消进行中的请求
+    pub supports_cancellation: bool,
+    /// 是否能从服务端获取真实的模型列表（否则为内置列表）
+    pub supports_model_listing: bool,
+    /// 是否支持预填助手回复开头（assistant prefill）
+    pub supports_assistant_prefix: bool,
+}
+
+impl ProviderInfo {
+    /// 是否为指定类型的提供商
+    pub fn is_type(&self, provider_type: ProviderType) -> bool {
+        self.provider_type == provider_type
+    }
+
+    /// 请求包含图片而目标模型已知不支持视觉输入时返回错误
+    ///
+    /// 不在模型列表中的模型（如自定义部署）不做限制
+    pub fn ensure_supports_images(&self, request: &CompletionRequest) -> Result<(), LLMError> {
+        if request.messages.iter().all(|m| m.images.is_empty()) {
+            return Ok(());
+        }
+        match self.models.iter().find(|model| model.id == request.model) {
+            Some(model) if !model.supports_vision => Err(LLMError::InvalidRequest(format!(
+                "Model {} does not support image input",
+                model.id
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// 模型信息
@@ -69,11 +184,152 @@ pub struct ModelInfo {
     pub supports_functions: bool,
 }
 
+impl ModelInfo {
+    /// 是否支持指定功能
+    pub fn supports(&self, feature: ModelFeature) -> bool {
+        match feature {
+            ModelFeature::Vision => self.supports_vision,
+            ModelFeature::Functions => self.supports_functions,
+        }
+    }
+}
+
+/// 模型的可选功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModelFeature {
+    /// 图片输入
+    Vision,
+    /// 函数调用
+    Functions,
+}
+
+/// 模型能力信息的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CapabilitySource {
+    /// 从服务端获取的模型列表
+    Fetched,
+    /// 适配器内置的模型列表
+    Bundled,
+    /// 模型不在列表中，无法确定
+    Unknown,
+}
+
+/// 模型功能的支持情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSupport {
+    pub supported: bool,
+    pub source: CapabilitySource,
+}
+
+impl ModelSupport {
+    /// 在 `source` 来源的模型列表中查询功能支持情况，不在列表中的模型视为不支持
+    pub fn resolve(
+        models: &[ModelInfo],
+        model: &str,
+        feature: ModelFeature,
+        source: CapabilitySource,
+    ) -> Self {
+        match models.iter().find(|m| m.id == model) {
+            Some(info) => Self {
+                supported: info.supports(feature),
+                source,
+            },
+            None => Self {
+                supported: false,
+                source: CapabilitySource::Unknown,
+            },
+        }
+    }
+}
+
 /// 聊天消息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMChatMessage {
     pub role: String,
     pub content: String,
+    /// 随消息发送的图片（需要模型支持视觉输入）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
+}
+
+/// 图片附件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAttachment {
+    /// MIME 类型，如 image/png
+    pub mime_type: String,
+    /// base64 编码的图片数据
+    pub data: String,
+}
+
+impl ImageAttachment {
+    /// data URL 形式（OpenAI 兼容接口使用）
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+}
+
+/// 温度参数的合法范围
+pub const TEMPERATURE_RANGE: RangeInclusive<f32> = 0.0..=2.0;
+
+/// top_p 参数的合法范围
+pub const TOP_P_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+
+/// presence_penalty / frequency_penalty 参数的合法范围
+pub const PENALTY_RANGE: RangeInclusive<f32> = -2.0..=2.0;
+
+/// 单次请求的候选回复数量上限
+pub const MAX_CANDIDATES: u32 = 8;
+
+/// 模拟 `n` 时同时进行的单候选请求数
+const EMULATED_N_CONCURRENCY: usize = 2;
+
+/// 将采样参数限制在合法范围内（NaN 视为下界）
+pub fn clamp_sampling_param(value: f32, range: RangeInclusive<f32>) -> f32 {
+    if value.is_nan() {
+        return *range.start();
+    }
+    value.clamp(*range.start(), *range.end())
+}
+
+fn clamp_logged(name: &str, value: f32, range: RangeInclusive<f32>) -> f32 {
+    let clamped = clamp_sampling_param(value, range.clone());
+    if clamped != value {
+        tracing::warn!(
+            "{} {} is outside {:?}, clamped to {}",
+            name,
+            value,
+            range,
+            clamped
+        );
+    }
+    clamped
+}
+
+/// 回复格式
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ResponseFormat {
+    /// 普通文本
+    #[default]
+    Text,
+    /// 任意 JSON 对象
+    JsonObject,
+    /// 符合指定 JSON Schema 的 JSON
+    JsonSchema { schema: serde_json::Value },
+}
+
+impl ResponseFormat {
+    /// 格式名称，与 OpenAI `response_format.type` 一致
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::JsonObject => "json_object",
+            Self::JsonSchema { .. } => "json_schema",
+        }
+    }
 }
 
 /// 补全请求
@@ -87,10 +343,22 @@ pub struct CompletionRequest {
     pub max_tokens: Option<u32>,
     /// 温度参数 (0.0 - 2.0)
     pub temperature: Option<f32>,
+    /// 核采样参数 (0.0 - 1.0)
+    pub top_p: Option<f32>,
+    /// 存在惩罚 (-2.0 - 2.0)，正值鼓励谈论新话题
+    pub presence_penalty: Option<f32>,
+    /// 频率惩罚 (-2.0 - 2.0)，正值减少逐字重复
+    pub frequency_penalty: Option<f32>,
     /// 停止序列
     pub stop_sequences: Option<Vec<String>>,
     /// 请求 ID（用于取消）
     pub request_id: Option<String>,
+    /// 助手回复的开头（仅支持预填的提供商使用）
+    pub assistant_prefix: Option<String>,
+    /// 候选回复数量，通过 [`LLMPort::complete_n`] 获取全部候选
+    pub n: Option<u32>,
+    /// 回复格式（结构化输出），仅 OpenAI 兼容的提供商支持
+    pub response_format: Option<ResponseFormat>,
 }
 
 impl CompletionRequest {
@@ -100,8 +368,14 @@ impl CompletionRequest {
             model: model.into(),
             max_tokens: None,
             temperature: None,
+            top_p: None,
+            presence_penalty: None,
+            frequency_penalty: None,
             stop_sequences: None,
             request_id: None,
+            assistant_prefix: None,
+            n: None,
+            response_format: None,
         }
     }
 
@@ -110,8 +384,27 @@ impl CompletionRequest {
         self
     }
 
+    /// 设置温度，超出 [`TEMPERATURE_RANGE`] 时截断
     pub fn with_temperature(mut self, temp: f32) -> Self {
-        self.temperature = Some(temp);
+        self.temperature = Some(clamp_logged("temperature", temp, TEMPERATURE_RANGE));
+        self
+    }
+
+    /// 设置 top_p，超出 [`TOP_P_RANGE`] 时截断
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(clamp_logged("top_p", top_p, TOP_P_RANGE));
+        self
+    }
+
+    /// 设置存在惩罚，超出 [`PENALTY_RANGE`] 时截断
+    pub fn with_presence_penalty(mut self, penalty: f32) -> Self {
+        self.presence_penalty = Some(clamp_logged("presence_penalty", penalty, PENALTY_RANGE));
+        self
+    }
+
+    /// 设置频率惩罚，超出 [`PENALTY_RANGE`] 时截断
+    pub fn with_frequency_penalty(mut self, penalty: f32) -> Self {
+        self.frequency_penalty = Some(clamp_logged("frequency_penalty", penalty, PENALTY_RANGE));
         self
     }
 
@@ -119,6 +412,48 @@ impl CompletionRequest {
         self.request_id = Some(id.into());
         self
     }
+
+    pub fn with_assistant_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.assistant_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_stop_sequences(mut self, stop_sequences: Vec<String>) -> Self {
+        self.stop_sequences = Some(stop_sequences);
+        self
+    }
+
+    /// 设置候选回复数量，限制在 1 到 [`MAX_CANDIDATES`] 之间
+    pub fn with_n(mut self, n: u32) -> Self {
+        let clamped = n.clamp(1, MAX_CANDIDATES);
+        if clamped != n {
+            tracing::warn!(
+                "n {} is outside 1..={}, clamped to {}",
+                n,
+                MAX_CANDIDATES,
+                clamped
+            );
+        }
+        self.n = Some(clamped);
+        self
+    }
+
+    pub fn with_response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = Some(format);
+        self
+    }
+
+    /// 请求了 JSON 等结构化输出时返回错误（供不支持的提供商调用）
+    pub fn ensure_text_response(&self, provider: &str) -> Result<(), LLMError> {
+        match &self.response_format {
+            None | Some(ResponseFormat::Text) => Ok(()),
+            Some(format) => Err(LLMError::InvalidRequest(format!(
+                "{} does not support structured output ({}); use an OpenAI-compatible provider",
+                provider,
+                format.kind()
+            ))),
+        }
+    }
 }
 
 /// 补全响应
@@ -136,24 +471,17 @@ pub struct CompletionResponse {
 pub struct StreamChunk {
     /// 内容块
     pub content: String,
+    /// 推理内容块（思考模型在正式回答前输出）
+    #[serde(default)]
+    pub reasoning: Option<String>,
     /// 结束原因（最后一个块才有）
     pub finish_reason: Option<FinishReason>,
     /// Token 使用情况（最后一个块才有）
     pub usage: Option<TokenUsage>,
 }
 
-/// 结束原因
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum FinishReason {
-    Stop,
-    Length,
-    ContentFilter,
-    FunctionCall,
-}
-
 /// Token 使用统计
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenUsage {
     pub prompt_tokens: u32,
@@ -170,6 +498,17 @@ pub struct HealthStatus {
     pub error_message: Option<String>,
 }
 
+impl HealthStatus {
+    /// 检查失败时的不健康状态
+    pub fn unhealthy(error: impl Into<String>) -> Self {
+        Self {
+            is_healthy: false,
+            latency_ms: None,
+            error_message: Some(error.into()),
+        }
+    }
+}
+
 /// LLM 服务端口 - 核心抽象接口
 ///
 /// 所有 LLM 提供商适配器都必须实现此 trait
@@ -187,6 +526,23 @@ pub trait LLMPort: Send + Sync {
     /// 单次补全请求
     async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse, LLMError>;
 
+    /// 生成多个候选回复，数量由 `request.n` 指定（默认 1）
+    ///
+    /// 默认实现发送多次单候选请求，最多同时进行 `EMULATED_N_CONCURRENCY` 个，
+    /// 数量不超过 [`MAX_CANDIDATES`]；原生支持 `n` 的适配器应在一次请求中完成
+    async fn complete_n(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<Vec<CompletionResponse>, LLMError> {
+        let n = request.n.unwrap_or(1).clamp(1, MAX_CANDIDATES);
+        let single = CompletionRequest { n: None, ..request };
+        futures::stream::iter(0..n)
+            .map(|_| self.complete(single.clone()))
+            .buffered(EMULATED_N_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
     /// 流式补全请求
     async fn complete_stream(
         &self,
@@ -198,6 +554,21 @@ pub trait LLMPort: Send + Sync {
 
     /// 健康检查
     async fn health_check(&self) -> Result<HealthStatus, LLMError>;
+
+    /// 探测当前部署实际支持的能力
+    ///
+    /// 适配器在首次使用时探测一次并缓存结果，不支持探测的适配器返回 `Unsupported`
+    async fn probe_capabilities(&self) -> Result<ProviderCapabilities, LLMError> {
+        Err(LLMError::Unsupported("probe_capabilities"))
+    }
+
+    /// 能力探测缓存的使用情况，不缓存能力的适配器返回 None
+    async fn capability_cache_usage(&self) -> Option<CapabilityCacheUsage> {
+        None
+    }
+
+    /// 清除缓存的能力和使用统计，下次使用时重新探测
+    async fn clear_capability_cache(&self) {}
 }
 
 /// LLM 端口工厂 trait
@@ -212,7 +583,7 @@ pub trait LLMPortFactory: Send + Sync {
 }
 
 /// LLM 提供商配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LLMProviderConfig {
     pub id: String,
@@ -222,7 +593,72 @@ pub struct LLMProviderConfig {
     pub api_key: String,
     pub default_model: String,
     pub timeout_secs: u64,
+    /// 冷启动超时（秒），用于模型尚未加载时的首次请求（目前仅 Ollama 使用）
+    #[serde(default = "default_cold_start_timeout_secs")]
+    pub cold_start_timeout_secs: u64,
     pub max_retries: u32,
+    /// HTTP 连接调优，未设置的项使用 reqwest 默认值
+    #[serde(default)]
+    pub http: HttpClientOptions,
+    /// HTTP 代理地址（http、https 或 socks5），未设置时沿用系统代理环境变量
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// 附加到每个请求的请求头（如网关要求的 HTTP-Referer、X-Title），同名时覆盖适配器的标准请求头
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+    /// 模型在请求后保持加载的时长（仅 Ollama 使用），如 "5m"，"-1" 表示一直保持
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// 上下文窗口大小（仅 Ollama 使用），未设置时使用模型默认值
+    #[serde(default)]
+    pub num_ctx: Option<u32>,
+    /// anthropic-version 请求头（仅 Claude 使用），格式为 YYYY-MM-DD
+    #[serde(default = "default_anthropic_version")]
+    pub anthropic_version: String,
+    /// anthropic-beta 请求头中启用的 beta 功能（仅 Claude 使用）
+    #[serde(default)]
+    pub anthropic_beta: Vec<String>,
+}
+
+fn default_cold_start_timeout_secs() -> u64 {
+    300
+}
+
+/// 默认的 Anthropic API 版本
+pub const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+
+fn default_anthropic_version() -> String {
+    DEFAULT_ANTHROPIC_VERSION.to_string()
+}
+
+/// 校验 anthropic-version 的格式（YYYY-MM-DD）
+pub fn validate_anthropic_version(version: &str) -> Result<(), LLMError> {
+    chrono::NaiveDate::parse_from_str(version, "%Y-%m-%d")
+        .ok()
+        .filter(|_| version.len() == 10)
+        .map(|_| ())
+        .ok_or_else(|| {
+            LLMError::InvalidRequest(format!(
+                "anthropic-version must be a date in YYYY-MM-DD format, got {:?}",
+                version
+            ))
+        })
+}
+
+/// 校验 anthropic-beta 功能名：非空，且只包含字母、数字、`-`、`_` 和 `.`
+pub fn validate_anthropic_beta(feature: &str) -> Result<(), LLMError> {
+    let valid = !feature.is_empty()
+        && feature
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(LLMError::InvalidRequest(format!(
+            "Invalid anthropic-beta feature {:?}",
+            feature
+        )))
+    }
 }
 
 impl Default for LLMProviderConfig {
@@ -235,7 +671,238 @@ impl Default for LLMProviderConfig {
             api_key: String::new(),
             default_model: "gpt-3.5-turbo".to_string(),
             timeout_secs: 60,
+            cold_start_timeout_secs: default_cold_start_timeout_secs(),
             max_retries: 3,
+            http: HttpClientOptions::default(),
+            proxy_url: None,
+            extra_headers: HashMap::new(),
+            keep_alive: None,
+            num_ctx: None,
+            anthropic_version: default_anthropic_version(),
+            anthropic_beta: Vec::new(),
+        }
+    }
+}
+
+/// 连接池空闲超时与 TCP keepalive 允许的最大值（秒）
+pub const MAX_HTTP_TUNING_SECS: u64 = 24 * 60 * 60;
+
+/// HTTP 客户端连接调优
+///
+/// 用于需要 HTTP/2 多路复用、或在不稳定网络和特殊网关下需要调整连接保活的提供商
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HttpClientOptions {
+    /// 跳过协商，直接使用 HTTP/2 (prior knowledge)
+    pub http2_prior_knowledge: bool,
+    /// 连接池中空闲连接的保留时间（秒）
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// TCP keepalive 间隔（秒）
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+impl HttpClientOptions {
+    /// 校验配置，超时必须在 1 秒到 [`MAX_HTTP_TUNING_SECS`] 之间
+    pub fn validate(&self) -> Result<(), LLMError> {
+        for (name, value) in [
+            ("pool idle timeout", self.pool_idle_timeout_secs),
+            ("TCP keepalive", self.tcp_keepalive_secs),
+        ] {
+            if let Some(secs) = value {
+                if secs == 0 || secs > MAX_HTTP_TUNING_SECS {
+                    return Err(LLMError::InvalidRequest(format!(
+                        "{} must be between 1 and {} seconds, got {}",
+                        name, MAX_HTTP_TUNING_SECS, secs
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_support_resolve() {
+        let models = vec![
+            ModelInfo {
+                id: "vision".to_string(),
+                name: "Vision".to_string(),
+                context_length: 8192,
+                supports_vision: true,
+                supports_functions: false,
+            },
+            ModelInfo {
+                id: "text".to_string(),
+                name: "Text".to_string(),
+                context_length: 8192,
+                supports_vision: false,
+                supports_functions: true,
+            },
+        ];
+        let resolve = |model, feature| {
+            ModelSupport::resolve(&models, model, feature, CapabilitySource::Fetched)
+        };
+
+        assert!(resolve("vision", ModelFeature::Vision).supported);
+        assert!(!resolve("vision", ModelFeature::Functions).supported);
+        assert!(!resolve("text", ModelFeature::Vision).supported);
+        assert_eq!(
+            resolve("text", ModelFeature::Functions),
+            ModelSupport {
+                supported: true,
+                source: CapabilitySource::Fetched,
+            }
+        );
+        assert_eq!(
+            resolve("missing", ModelFeature::Vision).source,
+            CapabilitySource::Unknown
+        );
+    }
+
+    #[test]
+    fn test_clamp_sampling_param_in_range() {
+        assert_eq!(clamp_sampling_param(0.0, TEMPERATURE_RANGE), 0.0);
+        assert_eq!(clamp_sampling_param(0.7, TEMPERATURE_RANGE), 0.7);
+        assert_eq!(clamp_sampling_param(2.0, TEMPERATURE_RANGE), 2.0);
+        assert_eq!(clamp_sampling_param(0.9, TOP_P_RANGE), 0.9);
+    }
+
+    #[test]
+    fn test_clamp_sampling_param_out_of_range() {
+        assert_eq!(clamp_sampling_param(5.0, TEMPERATURE_RANGE), 2.0);
+        assert_eq!(clamp_sampling_param(-0.5, TEMPERATURE_RANGE), 0.0);
+        assert_eq!(clamp_sampling_param(1.5, TOP_P_RANGE), 1.0);
+        assert_eq!(clamp_sampling_param(-1.0, TOP_P_RANGE), 0.0);
+        assert_eq!(clamp_sampling_param(f32::NAN, TOP_P_RANGE), 0.0);
+    }
+
+    #[test]
+    fn test_builders_clamp() {
+        let request = CompletionRequest::new(vec![], "model")
+            .with_temperature(5.0)
+            .with_top_p(-0.1)
+            .with_presence_penalty(3.0)
+            .with_frequency_penalty(-0.5);
+
+        assert_eq!(request.temperature, Some(2.0));
+        assert_eq!(request.top_p, Some(0.0));
+        assert_eq!(request.presence_penalty, Some(2.0));
+        assert_eq!(request.frequency_penalty, Some(-0.5));
+    }
+
+    #[test]
+    fn test_images_rejected_for_non_vision_model() {
+        let model = |id: &str, supports_vision| ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            context_length: 4096,
+            supports_vision,
+            supports_functions: false,
+        };
+        let info = ProviderInfo {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            provider_type: ProviderType::OpenAI,
+            models: vec![model("text-only", false), model("vision", true)],
+            supports_cancellation: false,
+            supports_model_listing: false,
+            supports_assistant_prefix: false,
+        };
+        let message = LLMChatMessage {
+            role: "user".to_string(),
+            content: "What is this?".to_string(),
+            images: vec![ImageAttachment {
+                mime_type: "image/png".to_string(),
+                data: "aGVsbG8=".to_string(),
+            }],
+        };
+
+        let request = CompletionRequest::new(vec![message.clone()], "text-only");
+        assert!(matches!(
+            info.ensure_supports_images(&request),
+            Err(LLMError::InvalidRequest(_))
+        ));
+        for model in ["vision", "custom-deployment"] {
+            let request = CompletionRequest::new(vec![message.clone()], model);
+            assert!(info.ensure_supports_images(&request).is_ok());
+        }
+
+        // 不含图片的请求不受模型能力限制
+        let text = LLMChatMessage {
+            images: Vec::new(),
+            ..message
+        };
+        let request = CompletionRequest::new(vec![text], "text-only");
+        assert!(info.ensure_supports_images(&request).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_provider_type() {
+        assert_eq!(ProviderType::resolve("OpenAI"), ProviderType::OpenAI);
+        assert_eq!(ProviderType::resolve(" anthropic "), ProviderType::Claude);
+        assert_eq!(ProviderType::resolve("ollama"), ProviderType::Ollama);
+        assert_eq!(ProviderType::resolve("Google"), ProviderType::Gemini);
+        assert_eq!(ProviderType::resolve("deepseek"), ProviderType::Custom);
+
+        for provider_type in [
+            ProviderType::OpenAI,
+            ProviderType::Claude,
+            ProviderType::Ollama,
+            ProviderType::Gemini,
+            ProviderType::Custom,
+            ProviderType::Demo,
+        ] {
+            assert_eq!(ProviderType::resolve(provider_type.as_str()), provider_type);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_complete_n_emulated_with_bounded_requests() {
+        use crate::modules::chat::test_support::ScriptedLLM;
+
+        let llm = ScriptedLLM::replying("Hello!");
+        let request = CompletionRequest::new(Vec::new(), "scripted-model").with_n(3);
+        let responses = llm.complete_n(request).await.unwrap();
+
+        assert_eq!(responses.len(), 3);
+        assert!(responses.iter().all(|r| r.content == "Hello!"));
+        let requests = llm.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.n.is_none()));
+
+        // 数量有上限，直接设置字段也一样
+        let mut request = CompletionRequest::new(Vec::new(), "scripted-model");
+        request.n = Some(1000);
+        let responses = llm.complete_n(request).await.unwrap();
+        assert_eq!(responses.len(), MAX_CANDIDATES as usize);
+        assert_eq!(
+            CompletionRequest::new(Vec::new(), "m").with_n(1000).n,
+            Some(MAX_CANDIDATES)
+        );
+        assert_eq!(CompletionRequest::new(Vec::new(), "m").with_n(0).n, Some(1));
+    }
+
+    #[test]
+    fn test_detect_provider_type_from_url() {
+        let cases = [
+            ("https://api.anthropic.com", Some(ProviderType::Claude)),
+            ("https://API.Anthropic.com/v1/", Some(ProviderType::Claude)),
+            (
+                "https://generativelanguage.googleapis.com/v1beta",
+                Some(ProviderType::Gemini),
+            ),
+            ("https://api.openai.com/v1", Some(ProviderType::OpenAI)),
+            ("http://localhost:11434", Some(ProviderType::Ollama)),
+            ("https://api.deepseek.com/v1", None),
+            ("https://proxy.example.com/anthropic.com", None),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(ProviderType::detect_from_url(url), expected, "{}", url);
         }
     }
 }
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
 
 use super::super::domain::{Message, MessageId, SessionId};
 use super::session_repository::{PaginatedResult, Pagination, RepositoryError};
@@ -14,6 +15,9 @@ pub trait MessageRepository: Send + Sync {
     /// 保存消息
     async fn save(&self, message: &Message) -> Result<(), RepositoryError>;
 
+    /// 更新已存在的消息，消息不存在时返回 `RepositoryError::NotFound`
+    async fn update(&self, message: &Message) -> Result<(), RepositoryError>;
+
     /// 删除消息
     async fn delete(&self, id: MessageId) -> Result<(), RepositoryError>;
 
@@ -35,4 +39,21 @@ pub trait MessageRepository: Send + Sync {
 
     /// 获取会话的消息数量
     async fn count_by_session(&self, session_id: SessionId) -> Result<usize, RepositoryError>;
+
+    /// 获取每个有消息的会话的消息数量（用于检查与会话存储是否一致）
+    async fn count_all_by_session(&self) -> Result<HashMap<SessionId, usize>, RepositoryError>;
+
+    /// 不区分大小写地搜索消息内容，可限定会话，最新的在前
+    async fn search(
+        &self,
+        query: &str,
+        session_id: Option<SessionId>,
+        limit: usize,
+    ) -> Result<Vec<Message>, RepositoryError>;
+
+    /// 将内存中的数据写入持久存储，无持久化的实现直接返回
+    async fn flush(&self) -> Result<(), RepositoryError>;
+
+    /// 压缩存储：移除已没有消息的会话分组并重写持久存储，返回移除的分组数
+    async fn compact(&self) -> Result<usize, RepositoryError>;
 }
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use std::ops::Range;
 use thiserror::Error;
 
 use super::super::domain::{Session, SessionId};
@@ -20,6 +21,8 @@ pub enum RepositoryError {
 }
 
 /// 分页参数
+///
+/// 页码从 1 开始，0 视为第 1 页
 #[derive(Debug, Clone, Copy)]
 pub struct Pagination {
     pub page: u32,
@@ -31,8 +34,15 @@ impl Pagination {
         Self { page, limit }
     }
 
-    pub fn offset(&self) -> u32 {
-        (self.page.saturating_sub(1)) * self.limit
+    pub fn offset(&self) -> usize {
+        (self.page.saturating_sub(1) as usize).saturating_mul(self.limit as usize)
+    }
+
+    /// 当前页在 `total` 条记录中的下标范围，超出范围时为空
+    pub fn range(&self, total: usize) -> Range<usize> {
+        let start = self.offset().min(total);
+        let end = start.saturating_add(self.limit as usize).min(total);
+        start..end
     }
 }
 
@@ -62,7 +72,7 @@ impl<T> PaginatedResult<T> {
     }
 
     pub fn has_next(&self) -> bool {
-        (self.page as usize * self.limit as usize) < self.total
+        (self.page.max(1) as usize).saturating_mul(self.limit as usize) < self.total
     }
 
     pub fn has_prev(&self) -> bool {
@@ -70,7 +80,10 @@ impl<T> PaginatedResult<T> {
     }
 
     pub fn total_pages(&self) -> u32 {
-        ((self.total as f64) / (self.limit as f64)).ceil() as u32
+        if self.limit == 0 {
+            return 0;
+        }
+        self.total.div_ceil(self.limit as usize) as u32
     }
 }
 
@@ -99,4 +112,7 @@ pub trait SessionRepository: Send + Sync {
 
     /// 获取会话总数
     async fn count(&self) -> Result<usize, RepositoryError>;
+
+    /// 将内存中的数据写入持久存储，无持久化的实现直接返回
+    async fn flush(&self) -> Result<(), RepositoryError>;
 }
@@ -0,0 +1,194 @@
+// Chat Test Support - 聊天管道测试工具
+//
+// 提供内存仓储 + 脚本化 LLM 组装好的 ChatModule，
+// 便于测试完整的发送 / 流式 / 取消流程
+
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+use super::application::{
+    ApplicationError, CreateSessionCommand, SendMessageCommand, SendMessageResponse, StreamEvent,
+};
+use super::domain::{Message, SessionId};
+use super::infrastructure::{
+    InMemoryMessageRepository, InMemorySessionRepository, LLMAdapterRegistry,
+};
+use super::ports::{LLMProviderConfig, MessageRepository, Pagination, ProviderType};
+use super::ChatModule;
+
+pub use super::infrastructure::{ScriptStep, ScriptedLLM, SCRIPTED_MODEL, SCRIPTED_PROVIDER_ID};
+
+/// 聊天管道测试工具
+///
+/// 使用内存仓储和 [`ScriptedLLM`] 组装 [`ChatModule`]
+pub struct ChatTestHarness {
+    pub module: ChatModule,
+    pub llm: Arc<ScriptedLLM>,
+    pub session_repository: Arc<InMemorySessionRepository>,
+    pub message_repository: Arc<InMemoryMessageRepository>,
+}
+
+impl ChatTestHarness {
+    pub async fn new(llm: ScriptedLLM) -> Self {
+        let llm = Arc::new(llm);
+        let session_repository = Arc::new(InMemorySessionRepository::new());
+        let message_repository = Arc::new(InMemoryMessageRepository::new());
+
+        let registry = Arc::new(LLMAdapterRegistry::new());
+        registry
+            .register_adapter(
+                LLMProviderConfig {
+                    id: SCRIPTED_PROVIDER_ID.to_string(),
+                    name: "Scripted".to_string(),
+                    provider_type: ProviderType::Custom,
+                    default_model: SCRIPTED_MODEL.to_string(),
+                    ..Default::default()
+                },
+                llm.clone(),
+            )
+            .await;
+
+        let module = ChatModule::with_repositories(
+            session_repository.clone(),
+            message_repository.clone(),
+            registry,
+        );
+
+        Self {
+            module,
+            llm,
+            session_repository,
+            message_repository,
+        }
+    }
+
+    /// 创建空会话
+    pub async fn create_session(&self) -> SessionId {
+        self.module
+            .create_session(CreateSessionCommand::new(None, None))
+            .await
+            .unwrap()
+            .session
+            .id()
+    }
+
+    /// 发送消息（非流式）
+    pub async fn send(
+        &self,
+        session_id: SessionId,
+        content: &str,
+    ) -> Result<SendMessageResponse, ApplicationError> {
+        self.module
+            .send_message(
+                SendMessageCommand::new(session_id, content, None, false),
+                SCRIPTED_PROVIDER_ID,
+            )
+            .await
+    }
+
+    /// 发送消息（流式）
+    pub async fn send_stream(
+        &self,
+        session_id: SessionId,
+        content: &str,
+    ) -> Result<(SendMessageResponse, mpsc::Receiver<StreamEvent>), ApplicationError> {
+        self.module
+            .send_message_stream(
+                SendMessageCommand::new(session_id, content, None, true),
+                SCRIPTED_PROVIDER_ID,
+            )
+            .await
+    }
+
+    /// 会话中已保存的全部消息
+    pub async fn messages(&self, session_id: SessionId) -> Vec<Message> {
+        self.message_repository
+            .find_by_session(session_id, Pagination::new(1, 1000))
+            .await
+            .unwrap()
+            .items
+    }
+}
+
+/// 收集流中剩余的全部事件
+pub async fn collect_events(rx: &mut mpsc::Receiver<StreamEvent>) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        events.push(event);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::chat::domain::MessageRole;
+    use crate::modules::chat::ports::LLMPort;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_send_and_assert() {
+        let harness = ChatTestHarness::new(ScriptedLLM::replying("Hi there")).await;
+        let session_id = harness.create_session().await;
+
+        let response = harness.send(session_id, "Hello").await.unwrap();
+        assert_eq!(response.assistant_message.content(), "Hi there");
+
+        let messages = harness.messages(session_id).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role(), MessageRole::User);
+        assert_eq!(messages[1].content(), "Hi there");
+
+        let requests = harness.llm.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].model, SCRIPTED_MODEL);
+    }
+
+    #[tokio::test]
+    async fn test_stream_chunks() {
+        let llm = ScriptedLLM::new().chunk("Hello, ").chunk("world");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Hi").await.unwrap();
+        let events = collect_events(&mut rx).await;
+
+        assert!(matches!(&events[0], StreamEvent::Chunk(c) if c == "Hello, "));
+        assert!(matches!(&events[1], StreamEvent::Chunk(c) if c == "world"));
+        assert!(matches!(
+            &events[2],
+            StreamEvent::Done { full_content, .. } if full_content == "Hello, world"
+        ));
+        assert_eq!(harness.messages(session_id).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_midstream() {
+        let llm = ScriptedLLM::new()
+            .chunk("Partial")
+            .delay(Duration::from_millis(200))
+            .chunk(" never sent");
+        let harness = ChatTestHarness::new(llm).await;
+        let session_id = harness.create_session().await;
+
+        let (_, mut rx) = harness.send_stream(session_id, "Hi").await.unwrap();
+
+        // 收到第一个块后取消
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Chunk(c)) if c == "Partial"));
+        let request_id = harness.llm.requests()[0].request_id.clone().unwrap();
+        harness.llm.cancel(&request_id).await.unwrap();
+
+        // 流立即结束，保留已生成的部分
+        let events = collect_events(&mut rx).await;
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::Done { full_content, .. } if full_content == "Partial"
+        ));
+
+        let messages = harness.messages(session_id).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].role(), MessageRole::Assistant);
+        assert_eq!(messages[1].content(), "Partial");
+    }
+}
@@ -5,7 +5,9 @@
 use async_trait::async_trait;
 use std::sync::Arc;
 
-use crate::modules::config::domain::{AppConfig, PartialAppConfig};
+use crate::modules::config::domain::{
+    AppConfig, LLMProviderConfig, PartialAppConfig, PartialProviderConfig, QuietHours,
+};
 use crate::modules::config::ports::{ConfigError, ConfigRepository};
 
 /// 命令处理器 trait
@@ -211,6 +213,243 @@ impl CommandHandler<DeleteConfigValueCommand> for DeleteConfigValueHandler {
     }
 }
 
+// ============================================================================
+// Clone Provider Command
+// ============================================================================
+
+/// 复制提供商配置命令
+#[derive(Debug, Clone)]
+pub struct CloneProviderCommand {
+    pub source_id: String,
+}
+
+impl CloneProviderCommand {
+    pub fn new(source_id: impl Into<String>) -> Self {
+        Self {
+            source_id: source_id.into(),
+        }
+    }
+}
+
+/// 复制提供商配置响应
+#[derive(Debug, Clone)]
+pub struct CloneProviderResponse {
+    pub provider: LLMProviderConfig,
+}
+
+/// 复制提供商配置命令处理器
+pub struct CloneProviderHandler {
+    repository: Arc<dyn ConfigRepository>,
+}
+
+impl CloneProviderHandler {
+    pub fn new(repository: Arc<dyn ConfigRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<CloneProviderCommand> for CloneProviderHandler {
+    type Output = CloneProviderResponse;
+    type Error = ConfigError;
+
+    async fn handle(&self, command: CloneProviderCommand) -> Result<Self::Output, Self::Error> {
+        let mut config = self.repository.load().await?;
+
+        let source = config
+            .llm
+            .providers
+            .get(&command.source_id)
+            .ok_or_else(|| {
+                ConfigError::NotFound(format!("Provider not found: {}", command.source_id))
+            })?;
+
+        let mut provider = source.duplicate(uuid::Uuid::new_v4().to_string());
+        provider.sort_order = config.llm.next_sort_order();
+        config
+            .llm
+            .providers
+            .insert(provider.id.clone(), provider.clone());
+
+        self.repository.save(&config).await?;
+
+        Ok(CloneProviderResponse { provider })
+    }
+}
+
+// ============================================================================
+// Reorder Providers Command
+// ============================================================================
+
+/// 重排提供商显示顺序命令
+#[derive(Debug, Clone)]
+pub struct ReorderProvidersCommand {
+    pub ordered_ids: Vec<String>,
+}
+
+impl ReorderProvidersCommand {
+    pub fn new(ordered_ids: Vec<String>) -> Self {
+        Self { ordered_ids }
+    }
+}
+
+/// 重排提供商显示顺序响应
+#[derive(Debug, Clone)]
+pub struct ReorderProvidersResponse {
+    pub providers: Vec<LLMProviderConfig>,
+}
+
+/// 重排提供商显示顺序命令处理器
+pub struct ReorderProvidersHandler {
+    repository: Arc<dyn ConfigRepository>,
+}
+
+impl ReorderProvidersHandler {
+    pub fn new(repository: Arc<dyn ConfigRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<ReorderProvidersCommand> for ReorderProvidersHandler {
+    type Output = ReorderProvidersResponse;
+    type Error = ConfigError;
+
+    async fn handle(&self, command: ReorderProvidersCommand) -> Result<Self::Output, Self::Error> {
+        let mut config = self.repository.load().await?;
+
+        config
+            .llm
+            .reorder_providers(&command.ordered_ids)
+            .map_err(ConfigError::Invalid)?;
+
+        self.repository.save(&config).await?;
+
+        let providers = config
+            .llm
+            .ordered_providers()
+            .into_iter()
+            .cloned()
+            .collect();
+        Ok(ReorderProvidersResponse { providers })
+    }
+}
+
+// ============================================================================
+// Update Provider Command
+// ============================================================================
+
+/// 部分更新提供商配置命令
+#[derive(Debug, Clone)]
+pub struct UpdateProviderCommand {
+    pub id: String,
+    pub partial: PartialProviderConfig,
+}
+
+impl UpdateProviderCommand {
+    pub fn new(id: impl Into<String>, partial: PartialProviderConfig) -> Self {
+        Self {
+            id: id.into(),
+            partial,
+        }
+    }
+}
+
+/// 部分更新提供商配置响应
+#[derive(Debug, Clone)]
+pub struct UpdateProviderResponse {
+    pub provider: LLMProviderConfig,
+}
+
+/// 部分更新提供商配置命令处理器
+pub struct UpdateProviderHandler {
+    repository: Arc<dyn ConfigRepository>,
+}
+
+impl UpdateProviderHandler {
+    pub fn new(repository: Arc<dyn ConfigRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<UpdateProviderCommand> for UpdateProviderHandler {
+    type Output = UpdateProviderResponse;
+    type Error = ConfigError;
+
+    async fn handle(&self, command: UpdateProviderCommand) -> Result<Self::Output, Self::Error> {
+        let mut config = self.repository.load().await?;
+
+        let provider =
+            config.llm.providers.get_mut(&command.id).ok_or_else(|| {
+                ConfigError::NotFound(format!("Provider not found: {}", command.id))
+            })?;
+        provider.merge(command.partial);
+        let provider = provider.clone();
+
+        self.repository.save(&config).await?;
+
+        Ok(UpdateProviderResponse { provider })
+    }
+}
+
+// ============================================================================
+// Set Quiet Hours Command
+// ============================================================================
+
+/// 设置免打扰时段命令，`None` 表示取消
+#[derive(Debug, Clone)]
+pub struct SetQuietHoursCommand {
+    pub quiet_hours: Option<QuietHours>,
+}
+
+impl SetQuietHoursCommand {
+    pub fn new(quiet_hours: Option<QuietHours>) -> Self {
+        Self { quiet_hours }
+    }
+}
+
+/// 设置免打扰时段响应
+#[derive(Debug, Clone)]
+pub struct SetQuietHoursResponse {
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// 设置免打扰时段命令处理器
+pub struct SetQuietHoursHandler {
+    repository: Arc<dyn ConfigRepository>,
+}
+
+impl SetQuietHoursHandler {
+    pub fn new(repository: Arc<dyn ConfigRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl CommandHandler<SetQuietHoursCommand> for SetQuietHoursHandler {
+    type Output = SetQuietHoursResponse;
+    type Error = ConfigError;
+
+    async fn handle(&self, command: SetQuietHoursCommand) -> Result<Self::Output, Self::Error> {
+        if let Some(hours) = command.quiet_hours {
+            if hours.start == hours.end {
+                return Err(ConfigError::Invalid(
+                    "Quiet hours start and end must differ".to_string(),
+                ));
+            }
+        }
+
+        let mut config = self.repository.load().await?;
+        config.general.quiet_hours = command.quiet_hours;
+        self.repository.save(&config).await?;
+
+        Ok(SetQuietHoursResponse {
+            quiet_hours: config.general.quiet_hours,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -258,4 +497,201 @@ mod tests {
 
         assert!(!response.config.general.auto_start);
     }
+
+    #[tokio::test]
+    async fn test_clone_provider() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+
+        let mut source = LLMProviderConfig::new("openai-main", "OpenAI", "openai");
+        source.base_url = "https://api.openai.com/v1".to_string();
+        source.is_default = true;
+
+        let mut config = repo.load().await.unwrap();
+        config
+            .llm
+            .providers
+            .insert(source.id.clone(), source.clone());
+        repo.save(&config).await.unwrap();
+
+        let handler = CloneProviderHandler::new(repo.clone());
+        let response = handler
+            .handle(CloneProviderCommand::new("openai-main"))
+            .await
+            .unwrap();
+
+        let clone = response.provider;
+        assert_ne!(clone.id, source.id);
+        assert!(!clone.is_default);
+        assert_eq!(clone.name, "OpenAI (copy)");
+        assert_eq!(clone.base_url, source.base_url);
+
+        let providers = repo.load().await.unwrap().llm.providers;
+        assert_eq!(providers.len(), 2);
+        assert!(providers["openai-main"].is_default);
+    }
+
+    #[tokio::test]
+    async fn test_clone_unknown_provider() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let handler = CloneProviderHandler::new(repo);
+
+        let result = handler.handle(CloneProviderCommand::new("missing")).await;
+        assert!(matches!(result, Err(ConfigError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update_provider_keeps_omitted_api_key() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+
+        let mut source = LLMProviderConfig::new("openai-main", "OpenAI", "openai");
+        source.api_key = "sk-secret".to_string();
+        source.models = vec!["gpt-4o".to_string()];
+
+        let mut config = repo.load().await.unwrap();
+        config.llm.providers.insert(source.id.clone(), source);
+        repo.save(&config).await.unwrap();
+
+        let handler = UpdateProviderHandler::new(repo.clone());
+        let response = handler
+            .handle(UpdateProviderCommand::new(
+                "openai-main",
+                PartialProviderConfig {
+                    name: Some("OpenAI Work".to_string()),
+                    base_url: Some("https://proxy.example.com/v1".to_string()),
+                    ..Default::default()
+                },
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.provider.name, "OpenAI Work");
+        assert_eq!(response.provider.base_url, "https://proxy.example.com/v1");
+
+        let stored = &repo.load().await.unwrap().llm.providers["openai-main"];
+        assert_eq!(stored.api_key, "sk-secret");
+        assert_eq!(stored.models, vec!["gpt-4o".to_string()]);
+        assert_eq!(stored.name, "OpenAI Work");
+
+        // 显式提供时才更新 API Key
+        handler
+            .handle(UpdateProviderCommand::new(
+                "openai-main",
+                PartialProviderConfig {
+                    api_key: Some("sk-rotated".to_string()),
+                    ..Default::default()
+                },
+            ))
+            .await
+            .unwrap();
+        let stored = &repo.load().await.unwrap().llm.providers["openai-main"];
+        assert_eq!(stored.api_key, "sk-rotated");
+    }
+
+    #[tokio::test]
+    async fn test_reorder_providers() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+
+        let mut config = repo.load().await.unwrap();
+        for id in ["claude", "ollama", "openai"] {
+            config
+                .llm
+                .providers
+                .insert(id.to_string(), LLMProviderConfig::new(id, id, id));
+        }
+        repo.save(&config).await.unwrap();
+
+        let ordered_ids: Vec<String> = ["openai", "claude", "ollama"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let handler = ReorderProvidersHandler::new(repo.clone());
+        let response = handler
+            .handle(ReorderProvidersCommand::new(ordered_ids.clone()))
+            .await
+            .unwrap();
+
+        let ids: Vec<String> = response.providers.into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ordered_ids);
+
+        // 顺序已持久化
+        let config = repo.load().await.unwrap();
+        let ids: Vec<&str> = config
+            .llm
+            .ordered_providers()
+            .into_iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        assert_eq!(ids, ["openai", "claude", "ollama"]);
+    }
+
+    #[tokio::test]
+    async fn test_reorder_rejects_mismatched_ids() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+
+        let mut config = repo.load().await.unwrap();
+        for id in ["claude", "openai"] {
+            config
+                .llm
+                .providers
+                .insert(id.to_string(), LLMProviderConfig::new(id, id, id));
+        }
+        repo.save(&config).await.unwrap();
+
+        let handler = ReorderProvidersHandler::new(repo);
+        for ids in [
+            vec!["openai"],
+            vec!["openai", "claude", "ollama"],
+            vec!["openai", "ollama"],
+            vec!["openai", "openai"],
+        ] {
+            let ids = ids.into_iter().map(String::from).collect();
+            let result = handler.handle(ReorderProvidersCommand::new(ids)).await;
+            assert!(matches!(result, Err(ConfigError::Invalid(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_unknown_provider() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let handler = UpdateProviderHandler::new(repo);
+
+        let result = handler
+            .handle(UpdateProviderCommand::new(
+                "missing",
+                PartialProviderConfig::default(),
+            ))
+            .await;
+        assert!(matches!(result, Err(ConfigError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_quiet_hours() {
+        let repo = Arc::new(InMemoryConfigRepository::new());
+        let handler = SetQuietHoursHandler::new(repo.clone());
+
+        let start = chrono::NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        let end = chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap();
+        let hours = QuietHours::new(start, end);
+        let response = handler
+            .handle(SetQuietHoursCommand::new(Some(hours)))
+            .await
+            .unwrap();
+        assert_eq!(response.quiet_hours, Some(hours));
+        assert_eq!(repo.load().await.unwrap().general.quiet_hours, Some(hours));
+
+        // 开始等于结束的时段被拒绝，原设置保持不变
+        let result = handler
+            .handle(SetQuietHoursCommand::new(Some(QuietHours::new(
+                start, start,
+            ))))
+            .await;
+        assert!(matches!(result, Err(ConfigError::Invalid(_))));
+        assert_eq!(repo.load().await.unwrap().general.quiet_hours, Some(hours));
+
+        handler
+            .handle(SetQuietHoursCommand::new(None))
+            .await
+            .unwrap();
+        assert_eq!(repo.load().await.unwrap().general.quiet_hours, None);
+    }
 }
@@ -5,7 +5,7 @@
 use async_trait::async_trait;
 use std::sync::Arc;
 
-use crate::modules::config::domain::AppConfig;
+use crate::modules::config::domain::{AppConfig, LLMProviderConfig};
 use crate::modules::config::ports::{ConfigError, ConfigRepository};
 
 /// 查询处理器 trait
@@ -102,14 +102,23 @@ impl QueryHandler<GetConfigValueQuery> for GetConfigValueHandler {
 // ============================================================================
 
 /// 检查配置是否存在查询
+///
+/// `key` 为 `None` 时检查配置文件本身是否已持久化
 #[derive(Debug, Clone)]
 pub struct ConfigExistsQuery {
-    pub key: String,
+    pub key: Option<String>,
 }
 
 impl ConfigExistsQuery {
     pub fn new(key: impl Into<String>) -> Self {
-        Self { key: key.into() }
+        Self {
+            key: Some(key.into()),
+        }
+    }
+
+    /// 检查配置文件是否存在
+    pub fn config_file() -> Self {
+        Self { key: None }
     }
 }
 
@@ -136,10 +145,53 @@ impl QueryHandler<ConfigExistsQuery> for ConfigExistsHandler {
     type Error = ConfigError;
 
     async fn handle(&self, query: ConfigExistsQuery) -> Result<Self::Output, Self::Error> {
-        let value = self.repository.get_value(&query.key).await?;
-        Ok(ConfigExistsResponse {
-            exists: value.is_some(),
-        })
+        let exists = match query.key {
+            Some(key) => self.repository.get_value(&key).await?.is_some(),
+            None => self.repository.exists().await?,
+        };
+        Ok(ConfigExistsResponse { exists })
+    }
+}
+
+// ============================================================================
+// List Providers Query
+// ============================================================================
+
+/// 按显示顺序列出提供商查询
+#[derive(Debug, Clone, Default)]
+pub struct ListProvidersQuery;
+
+/// 列出提供商响应
+#[derive(Debug, Clone)]
+pub struct ListProvidersResponse {
+    pub providers: Vec<LLMProviderConfig>,
+}
+
+/// 列出提供商查询处理器
+pub struct ListProvidersHandler {
+    repository: Arc<dyn ConfigRepository>,
+}
+
+impl ListProvidersHandler {
+    pub fn new(repository: Arc<dyn ConfigRepository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl QueryHandler<ListProvidersQuery> for ListProvidersHandler {
+    type Output = ListProvidersResponse;
+    type Error = ConfigError;
+
+    async fn handle(&self, _query: ListProvidersQuery) -> Result<Self::Output, Self::Error> {
+        let config = self.repository.load().await?;
+        let providers = config
+            .llm
+            .ordered_providers()
+            .into_iter()
+            .cloned()
+            .collect();
+        Ok(ListProvidersResponse { providers })
     }
 }
 
@@ -3,16 +3,36 @@
 // 配置服务门面，提供统一的 API
 
 use async_trait::async_trait;
-use std::sync::Arc;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
 
 use super::{
-    CommandHandler, DeleteConfigValueCommand, DeleteConfigValueHandler, GetAllConfigHandler,
-    GetAllConfigQuery, GetConfigValueHandler, GetConfigValueQuery, QueryHandler,
+    CloneProviderCommand, CloneProviderHandler, CommandHandler, ConfigExistsHandler,
+    ConfigExistsQuery, DeleteConfigValueCommand, DeleteConfigValueHandler, GetAllConfigHandler,
+    GetAllConfigQuery, GetConfigValueHandler, GetConfigValueQuery, ListProvidersHandler,
+    ListProvidersQuery, QueryHandler, ReorderProvidersCommand, ReorderProvidersHandler,
     ResetConfigCommand, ResetConfigHandler, SetConfigValueCommand, SetConfigValueHandler,
-    UpdateConfigCommand, UpdateConfigHandler,
+    SetQuietHoursCommand, SetQuietHoursHandler, UpdateConfigCommand, UpdateConfigHandler,
+    UpdateProviderCommand, UpdateProviderHandler,
 };
-use crate::modules::config::domain::{AppConfig, PartialAppConfig};
-use crate::modules::config::ports::{ConfigError, ConfigPort, ConfigRepository};
+use crate::modules::config::domain::{
+    AppConfig, ConfigChangedEvent, LLMProviderConfig, PartialAppConfig, PartialProviderConfig,
+    QuietHours,
+};
+use crate::modules::config::ports::{ConfigError, ConfigObserver, ConfigPort, ConfigRepository};
+
+/// 首次运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstRunStatus {
+    /// 配置文件尚未保存过
+    pub is_first_run: bool,
+    /// 是否已配置任何 LLM 提供商
+    pub has_provider: bool,
+}
+
+/// 提供商增改、复制和重排时通知的配置键
+const PROVIDERS_KEY: &str = "llm.providers";
 
 /// 配置服务实现
 pub struct ConfigService {
@@ -20,10 +40,17 @@ pub struct ConfigService {
     // Handlers
     get_all_handler: GetAllConfigHandler,
     get_value_handler: GetConfigValueHandler,
+    exists_handler: ConfigExistsHandler,
     update_handler: UpdateConfigHandler,
     reset_handler: ResetConfigHandler,
     set_value_handler: SetConfigValueHandler,
     delete_value_handler: DeleteConfigValueHandler,
+    clone_provider_handler: CloneProviderHandler,
+    update_provider_handler: UpdateProviderHandler,
+    list_providers_handler: ListProvidersHandler,
+    reorder_providers_handler: ReorderProvidersHandler,
+    set_quiet_hours_handler: SetQuietHoursHandler,
+    observers: RwLock<Vec<Arc<dyn ConfigObserver>>>,
 }
 
 impl ConfigService {
@@ -31,11 +58,34 @@ impl ConfigService {
         Self {
             get_all_handler: GetAllConfigHandler::new(repository.clone()),
             get_value_handler: GetConfigValueHandler::new(repository.clone()),
+            exists_handler: ConfigExistsHandler::new(repository.clone()),
             update_handler: UpdateConfigHandler::new(repository.clone()),
             reset_handler: ResetConfigHandler::new(repository.clone()),
             set_value_handler: SetConfigValueHandler::new(repository.clone()),
             delete_value_handler: DeleteConfigValueHandler::new(repository.clone()),
+            clone_provider_handler: CloneProviderHandler::new(repository.clone()),
+            update_provider_handler: UpdateProviderHandler::new(repository.clone()),
+            list_providers_handler: ListProvidersHandler::new(repository.clone()),
+            reorder_providers_handler: ReorderProvidersHandler::new(repository.clone()),
+            set_quiet_hours_handler: SetQuietHoursHandler::new(repository.clone()),
             repository,
+            observers: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// 注册配置观察者，update / set / delete / reset 成功后通知
+    pub fn add_observer(&self, observer: Arc<dyn ConfigObserver>) {
+        self.observers
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(observer);
+    }
+
+    fn notify(&self, key: &str) {
+        let event = ConfigChangedEvent::new(key);
+        let observers = self.observers.read().unwrap_or_else(|e| e.into_inner());
+        for observer in observers.iter() {
+            observer.on_config_changed(&event);
         }
     }
 
@@ -43,6 +93,79 @@ impl ConfigService {
     pub fn repository(&self) -> &Arc<dyn ConfigRepository> {
         &self.repository
     }
+
+    /// 复制提供商配置，返回新配置
+    pub async fn clone_provider(&self, source_id: &str) -> Result<LLMProviderConfig, ConfigError> {
+        let response = self
+            .clone_provider_handler
+            .handle(CloneProviderCommand::new(source_id))
+            .await?;
+        self.notify(PROVIDERS_KEY);
+        Ok(response.provider)
+    }
+
+    /// 部分更新提供商配置，返回更新后的配置
+    pub async fn update_provider(
+        &self,
+        id: &str,
+        partial: PartialProviderConfig,
+    ) -> Result<LLMProviderConfig, ConfigError> {
+        let response = self
+            .update_provider_handler
+            .handle(UpdateProviderCommand::new(id, partial))
+            .await?;
+        self.notify(PROVIDERS_KEY);
+        Ok(response.provider)
+    }
+
+    /// 按显示顺序列出提供商
+    pub async fn list_providers(&self) -> Result<Vec<LLMProviderConfig>, ConfigError> {
+        let response = self
+            .list_providers_handler
+            .handle(ListProvidersQuery)
+            .await?;
+        Ok(response.providers)
+    }
+
+    /// 重排提供商显示顺序，返回重排后的列表
+    pub async fn reorder_providers(
+        &self,
+        ordered_ids: Vec<String>,
+    ) -> Result<Vec<LLMProviderConfig>, ConfigError> {
+        let response = self
+            .reorder_providers_handler
+            .handle(ReorderProvidersCommand::new(ordered_ids))
+            .await?;
+        self.notify(PROVIDERS_KEY);
+        Ok(response.providers)
+    }
+
+    /// 设置免打扰时段，`None` 表示取消
+    pub async fn set_quiet_hours(
+        &self,
+        quiet_hours: Option<QuietHours>,
+    ) -> Result<Option<QuietHours>, ConfigError> {
+        let response = self
+            .set_quiet_hours_handler
+            .handle(SetQuietHoursCommand::new(quiet_hours))
+            .await?;
+        self.notify("general.quietHours");
+        Ok(response.quiet_hours)
+    }
+
+    /// 检测是否首次运行（配置文件不存在即为首次运行）
+    pub async fn first_run_status(&self) -> Result<FirstRunStatus, ConfigError> {
+        let response = self
+            .exists_handler
+            .handle(ConfigExistsQuery::config_file())
+            .await?;
+        let config = self.get_all().await?;
+
+        Ok(FirstRunStatus {
+            is_first_run: !response.exists,
+            has_provider: !config.llm.providers.is_empty(),
+        })
+    }
 }
 
 #[async_trait]
@@ -79,14 +202,21 @@ impl ConfigPort for ConfigService {
         self.set_value_handler
             .handle(SetConfigValueCommand::new(key, json_value))
             .await?;
+        self.notify(key);
         Ok(())
     }
 
     async fn update(&self, partial: PartialAppConfig) -> Result<AppConfig, ConfigError> {
+        // 只更新了一个配置段时报告该段，否则视为整体变化
+        let key = match partial.sections().as_slice() {
+            [section] => *section,
+            _ => "*",
+        };
         let response = self
             .update_handler
             .handle(UpdateConfigCommand::new(partial))
             .await?;
+        self.notify(key);
         Ok(response.config)
     }
 
@@ -94,28 +224,43 @@ impl ConfigPort for ConfigService {
         self.delete_value_handler
             .handle(DeleteConfigValueCommand::new(key))
             .await?;
+        self.notify(key);
         Ok(())
     }
 
     async fn reset(&self) -> Result<AppConfig, ConfigError> {
         let response = self.reset_handler.handle(ResetConfigCommand).await?;
+        self.notify("*");
         Ok(response.config)
     }
 
     async fn exists(&self, key: &str) -> Result<bool, ConfigError> {
-        let value = self
-            .get_value_handler
-            .handle(GetConfigValueQuery::new(key))
+        let response = self
+            .exists_handler
+            .handle(ConfigExistsQuery::new(key))
             .await?;
-        Ok(value.value.is_some())
+        Ok(response.exists)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::modules::config::domain::{PartialGeneralConfig, Theme};
+    use crate::modules::config::domain::{PartialBackupConfig, PartialGeneralConfig, Theme};
     use crate::modules::config::infrastructure::InMemoryConfigRepository;
+    use std::sync::Mutex;
+
+    /// 记录收到的变更键
+    #[derive(Default)]
+    struct RecordingObserver {
+        keys: Mutex<Vec<String>>,
+    }
+
+    impl ConfigObserver for RecordingObserver {
+        fn on_config_changed(&self, event: &ConfigChangedEvent) {
+            self.keys.lock().unwrap().push(event.key.clone());
+        }
+    }
 
     #[tokio::test]
     async fn test_config_service() {
@@ -163,4 +308,78 @@ mod tests {
         // 删除值
         service.delete("custom.key").await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_observers_notified_once_per_change() {
+        let service = ConfigService::new(Arc::new(InMemoryConfigRepository::new()));
+        let observer = Arc::new(RecordingObserver::default());
+        service.add_observer(observer.clone());
+
+        let general = PartialGeneralConfig {
+            theme: Some(Theme::Dark),
+            ..Default::default()
+        };
+        service
+            .update(PartialAppConfig {
+                general: Some(general.clone()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        service
+            .update(PartialAppConfig {
+                general: Some(general),
+                backup: Some(PartialBackupConfig::default()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        service.set("custom.key", &1).await.unwrap();
+
+        // 提供商的增改和重排
+        let mut config = service.get_all().await.unwrap();
+        config.llm.providers.insert(
+            "openai".to_string(),
+            LLMProviderConfig::new("openai", "OpenAI", "openai"),
+        );
+        service.repository().save(&config).await.unwrap();
+        let copy = service.clone_provider("openai").await.unwrap();
+        service
+            .update_provider(
+                "openai",
+                PartialProviderConfig {
+                    name: Some("OpenAI Work".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        service
+            .reorder_providers(vec![copy.id, "openai".to_string()])
+            .await
+            .unwrap();
+        // 失败的修改不触发通知
+        assert!(service
+            .update_provider("missing", PartialProviderConfig::default())
+            .await
+            .is_err());
+
+        service.reset().await.unwrap();
+
+        // 读取不触发通知
+        service.get_all().await.unwrap();
+
+        assert_eq!(
+            *observer.keys.lock().unwrap(),
+            vec![
+                "general",
+                "*",
+                "custom.key",
+                PROVIDERS_KEY,
+                PROVIDERS_KEY,
+                PROVIDERS_KEY,
+                "*"
+            ]
+        );
+    }
 }
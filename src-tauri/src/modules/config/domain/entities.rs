@@ -3,8 +3,11 @@
 // 配置领域实体定义
 
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
-use super::value_objects::{Language, PositionStrategy, Shortcut, Size, Theme, WindowModeConfig};
+use super::value_objects::{
+    Language, PositionStrategy, QuietHours, Shortcut, Size, Theme, WindowModeConfig,
+};
 
 /// 通用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +17,9 @@ pub struct GeneralConfig {
     pub theme: Theme,
     pub auto_start: bool,
     pub minimize_to_tray: bool,
+    /// 免打扰时段，未设置时不限制
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
 }
 
 impl Default for GeneralConfig {
@@ -23,6 +29,7 @@ impl Default for GeneralConfig {
             theme: Theme::default(),
             auto_start: false,
             minimize_to_tray: true,
+            quiet_hours: None,
         }
     }
 }
@@ -34,6 +41,13 @@ pub struct WindowConfig {
     pub default_mode: WindowModeConfig,
     pub pet_mode_size: Size,
     pub pet_mode_position: PositionStrategy,
+    /// 除主窗口外最多同时打开的窗口数
+    #[serde(default = "default_max_windows")]
+    pub max_windows: u32,
+}
+
+fn default_max_windows() -> u32 {
+    5
 }
 
 impl Default for WindowConfig {
@@ -42,6 +56,7 @@ impl Default for WindowConfig {
             default_mode: WindowModeConfig::default(),
             pet_mode_size: Size::new(300, 400),
             pet_mode_position: PositionStrategy::default(),
+            max_windows: default_max_windows(),
         }
     }
 }
@@ -72,6 +87,28 @@ pub struct LLMConfig {
     pub default_provider: String,
     pub stream_response: bool,
     pub context_length: u32,
+    /// 提供商配置（按 ID 索引）
+    #[serde(default)]
+    pub providers: HashMap<String, LLMProviderConfig>,
+    /// 默认提供商最近不健康时自动改用其他健康的提供商
+    #[serde(default)]
+    pub auto_fallback: bool,
+    /// 单条用户消息的最大字符数，超出时拒绝发送
+    #[serde(default = "default_max_user_message_chars")]
+    pub max_user_message_chars: u32,
+    /// 何时自动生成会话标题
+    #[serde(default)]
+    pub auto_title: AutoTitleConfig,
+    /// 分析用户消息的情感，使形象在回复前做出反应
+    #[serde(default)]
+    pub analyze_user_emotion: bool,
+    /// 流式输出时将文本、音素和情感变化合并为一个 `llm:frame` 事件
+    #[serde(default)]
+    pub combined_stream_frames: bool,
+}
+
+fn default_max_user_message_chars() -> u32 {
+    100_000
 }
 
 impl Default for LLMConfig {
@@ -80,7 +117,66 @@ impl Default for LLMConfig {
             default_provider: String::new(),
             stream_response: true,
             context_length: 10,
+            providers: HashMap::new(),
+            auto_fallback: false,
+            max_user_message_chars: default_max_user_message_chars(),
+            auto_title: AutoTitleConfig::default(),
+            analyze_user_emotion: false,
+            combined_stream_frames: false,
+        }
+    }
+}
+
+/// 自动生成会话标题的触发策略
+///
+/// 序列化为 `"off"`、`"afterFirstReply"` 或 `{ "afterNMessages": n }`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AutoTitleConfig {
+    /// 不自动生成
+    Off,
+    /// 收到第一条助手回复后生成
+    #[default]
+    AfterFirstReply,
+    /// 对话消息达到指定数量后生成
+    AfterNMessages(u32),
+}
+
+impl LLMConfig {
+    /// 按显示顺序返回提供商（顺序相同时按 ID 排序）
+    pub fn ordered_providers(&self) -> Vec<&LLMProviderConfig> {
+        let mut providers: Vec<_> = self.providers.values().collect();
+        providers.sort_by(|a, b| (a.sort_order, &a.id).cmp(&(b.sort_order, &b.id)));
+        providers
+    }
+
+    /// 排在所有提供商之后的顺序值
+    pub fn next_sort_order(&self) -> u32 {
+        self.providers
+            .values()
+            .map(|provider| provider.sort_order + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 按给定 ID 顺序重排提供商
+    ///
+    /// ID 集合必须与现有提供商完全一致（不能缺少、多出或重复）
+    pub fn reorder_providers(&mut self, ordered_ids: &[String]) -> Result<(), String> {
+        let unique: HashSet<&String> = ordered_ids.iter().collect();
+        if unique.len() != ordered_ids.len()
+            || ordered_ids.len() != self.providers.len()
+            || !ordered_ids.iter().all(|id| self.providers.contains_key(id))
+        {
+            return Err("Provider ids must match the configured providers exactly".to_string());
         }
+
+        for (index, id) in ordered_ids.iter().enumerate() {
+            if let Some(provider) = self.providers.get_mut(id) {
+                provider.sort_order = index as u32;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -95,6 +191,12 @@ pub struct LLMProviderConfig {
     pub api_key: String,
     pub models: Vec<String>,
     pub is_default: bool,
+    /// 显示顺序，越小越靠前
+    #[serde(default)]
+    pub sort_order: u32,
+    /// 已保存的 API Key 无法解密（如密钥文件丢失或配置来自其他设备），需要重新输入
+    #[serde(default, skip_deserializing)]
+    pub api_key_unreadable: bool,
 }
 
 impl LLMProviderConfig {
@@ -111,6 +213,43 @@ impl LLMProviderConfig {
             api_key: String::new(),
             models: Vec::new(),
             is_default: false,
+            sort_order: 0,
+            api_key_unreadable: false,
+        }
+    }
+
+    /// 以新 ID 复制提供商配置
+    ///
+    /// 名称追加 "(copy)"，且不标记为默认提供商
+    pub fn duplicate(&self, id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: format!("{} (copy)", self.name),
+            is_default: false,
+            ..self.clone()
+        }
+    }
+
+    /// 合并部分更新，未提供的字段（包括 API Key）保持不变
+    pub fn merge(&mut self, partial: PartialProviderConfig) {
+        if let Some(name) = partial.name {
+            self.name = name;
+        }
+        if let Some(provider_type) = partial.provider_type {
+            self.provider_type = provider_type;
+        }
+        if let Some(base_url) = partial.base_url {
+            self.base_url = base_url;
+        }
+        if let Some(api_key) = partial.api_key {
+            self.api_key_unreadable &= api_key.is_empty();
+            self.api_key = api_key;
+        }
+        if let Some(models) = partial.models {
+            self.models = models;
+        }
+        if let Some(is_default) = partial.is_default {
+            self.is_default = is_default;
         }
     }
 }
@@ -134,6 +273,85 @@ impl Default for ModelConfig {
     }
 }
 
+/// 备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupConfig {
+    /// 是否启用定期备份
+    pub enabled: bool,
+    /// 备份间隔（分钟）
+    pub interval_minutes: u32,
+    /// 保留的备份数量
+    pub keep: u32,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 60,
+            keep: 5,
+        }
+    }
+}
+
+/// 审计日志配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogConfig {
+    /// 是否将领域事件写入 events.log
+    pub enabled: bool,
+    /// 单个日志文件的最大大小（KB），超出后轮转
+    pub max_size_kb: u32,
+}
+
+impl Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_size_kb: 5120,
+        }
+    }
+}
+
+/// 内容过滤配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentFilterConfig {
+    /// 是否在保存和发布前过滤助手回复
+    pub enabled: bool,
+    /// 需要屏蔽的词语（不区分大小写）
+    #[serde(default)]
+    pub terms: Vec<String>,
+    /// 命中时替换整条回复的占位文本，为空时只遮盖命中的词语
+    #[serde(default)]
+    pub placeholder: Option<String>,
+}
+
+/// 重复检测配置
+///
+/// 流式回复末尾的同一片段连续重复超过阈值时提前停止生成
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepetitionGuardConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 检测的最长片段（字符数）
+    pub max_pattern_len: u32,
+    /// 片段连续出现多少次视为循环
+    pub min_repeats: u32,
+}
+
+impl Default for RepetitionGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pattern_len: 50,
+            min_repeats: 10,
+        }
+    }
+}
+
 /// 应用配置聚合根
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -143,6 +361,14 @@ pub struct AppConfig {
     pub shortcuts: ShortcutConfig,
     pub llm: LLMConfig,
     pub model: ModelConfig,
+    #[serde(default)]
+    pub backup: BackupConfig,
+    #[serde(default)]
+    pub audit_log: AuditLogConfig,
+    #[serde(default)]
+    pub content_filter: ContentFilterConfig,
+    #[serde(default)]
+    pub repetition_guard: RepetitionGuardConfig,
 }
 
 impl AppConfig {
@@ -168,6 +394,33 @@ impl AppConfig {
             }
         }
 
+        if let Some(window) = partial.window {
+            if let Some(default_mode) = window.default_mode {
+                self.window.default_mode = default_mode;
+            }
+            if let Some(pet_mode_size) = window.pet_mode_size {
+                self.window.pet_mode_size = pet_mode_size;
+            }
+            if let Some(pet_mode_position) = window.pet_mode_position {
+                self.window.pet_mode_position = pet_mode_position;
+            }
+            if let Some(max_windows) = window.max_windows {
+                self.window.max_windows = max_windows;
+            }
+        }
+
+        if let Some(shortcuts) = partial.shortcuts {
+            if let Some(toggle_window) = shortcuts.toggle_window {
+                self.shortcuts.toggle_window = toggle_window;
+            }
+            if let Some(toggle_pet_mode) = shortcuts.toggle_pet_mode {
+                self.shortcuts.toggle_pet_mode = toggle_pet_mode;
+            }
+            if let Some(new_chat) = shortcuts.new_chat {
+                self.shortcuts.new_chat = new_chat;
+            }
+        }
+
         if let Some(llm) = partial.llm {
             if let Some(default_provider) = llm.default_provider {
                 self.llm.default_provider = default_provider;
@@ -178,6 +431,21 @@ impl AppConfig {
             if let Some(context_length) = llm.context_length {
                 self.llm.context_length = context_length;
             }
+            if let Some(auto_fallback) = llm.auto_fallback {
+                self.llm.auto_fallback = auto_fallback;
+            }
+            if let Some(max_chars) = llm.max_user_message_chars {
+                self.llm.max_user_message_chars = max_chars;
+            }
+            if let Some(auto_title) = llm.auto_title {
+                self.llm.auto_title = auto_title;
+            }
+            if let Some(analyze_user_emotion) = llm.analyze_user_emotion {
+                self.llm.analyze_user_emotion = analyze_user_emotion;
+            }
+            if let Some(combined_stream_frames) = llm.combined_stream_frames {
+                self.llm.combined_stream_frames = combined_stream_frames;
+            }
         }
 
         if let Some(model) = partial.model {
@@ -191,6 +459,51 @@ impl AppConfig {
                 self.model.physics_enabled = physics_enabled;
             }
         }
+
+        if let Some(backup) = partial.backup {
+            if let Some(enabled) = backup.enabled {
+                self.backup.enabled = enabled;
+            }
+            if let Some(interval_minutes) = backup.interval_minutes {
+                self.backup.interval_minutes = interval_minutes;
+            }
+            if let Some(keep) = backup.keep {
+                self.backup.keep = keep;
+            }
+        }
+
+        if let Some(audit_log) = partial.audit_log {
+            if let Some(enabled) = audit_log.enabled {
+                self.audit_log.enabled = enabled;
+            }
+            if let Some(max_size_kb) = audit_log.max_size_kb {
+                self.audit_log.max_size_kb = max_size_kb;
+            }
+        }
+
+        if let Some(content_filter) = partial.content_filter {
+            if let Some(enabled) = content_filter.enabled {
+                self.content_filter.enabled = enabled;
+            }
+            if let Some(terms) = content_filter.terms {
+                self.content_filter.terms = terms;
+            }
+            if let Some(placeholder) = content_filter.placeholder {
+                self.content_filter.placeholder = Some(placeholder).filter(|p| !p.is_empty());
+            }
+        }
+
+        if let Some(repetition_guard) = partial.repetition_guard {
+            if let Some(enabled) = repetition_guard.enabled {
+                self.repetition_guard.enabled = enabled;
+            }
+            if let Some(max_pattern_len) = repetition_guard.max_pattern_len {
+                self.repetition_guard.max_pattern_len = max_pattern_len;
+            }
+            if let Some(min_repeats) = repetition_guard.min_repeats {
+                self.repetition_guard.min_repeats = min_repeats;
+            }
+        }
     }
 
     /// 验证配置是否有效
@@ -209,6 +522,31 @@ impl AppConfig {
         if self.llm.context_length == 0 || self.llm.context_length > 100 {
             errors.push("Context length must be between 1 and 100".to_string());
         }
+        if self.llm.auto_title == AutoTitleConfig::AfterNMessages(0) {
+            errors.push("Auto title message count must be at least 1".to_string());
+        }
+
+        // 验证备份设置
+        if self.backup.interval_minutes == 0 {
+            errors.push("Backup interval must be at least 1 minute".to_string());
+        }
+        if self.backup.keep == 0 {
+            errors.push("Backup keep count must be at least 1".to_string());
+        }
+
+        // 验证审计日志设置
+        if self.audit_log.max_size_kb == 0 {
+            errors.push("Audit log max size must be at least 1 KB".to_string());
+        }
+
+        // 验证重复检测设置
+        if self.repetition_guard.max_pattern_len == 0 || self.repetition_guard.max_pattern_len > 200
+        {
+            errors.push("Repetition pattern length must be between 1 and 200".to_string());
+        }
+        if self.repetition_guard.min_repeats < 2 {
+            errors.push("Repetition threshold must be at least 2".to_string());
+        }
 
         if errors.is_empty() {
             Ok(())
@@ -223,8 +561,34 @@ impl AppConfig {
 #[serde(rename_all = "camelCase")]
 pub struct PartialAppConfig {
     pub general: Option<PartialGeneralConfig>,
+    pub window: Option<PartialWindowConfig>,
+    pub shortcuts: Option<PartialShortcutConfig>,
     pub llm: Option<PartialLLMConfig>,
     pub model: Option<PartialModelConfig>,
+    pub backup: Option<PartialBackupConfig>,
+    pub audit_log: Option<PartialAuditLogConfig>,
+    pub content_filter: Option<PartialContentFilterConfig>,
+    pub repetition_guard: Option<PartialRepetitionGuardConfig>,
+}
+
+impl PartialAppConfig {
+    /// 包含更新内容的配置段名称（与序列化后的键一致）
+    pub fn sections(&self) -> Vec<&'static str> {
+        [
+            ("general", self.general.is_some()),
+            ("window", self.window.is_some()),
+            ("shortcuts", self.shortcuts.is_some()),
+            ("llm", self.llm.is_some()),
+            ("model", self.model.is_some()),
+            ("backup", self.backup.is_some()),
+            ("auditLog", self.audit_log.is_some()),
+            ("contentFilter", self.content_filter.is_some()),
+            ("repetitionGuard", self.repetition_guard.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| present.then_some(name))
+        .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -236,12 +600,45 @@ pub struct PartialGeneralConfig {
     pub minimize_to_tray: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialWindowConfig {
+    pub default_mode: Option<WindowModeConfig>,
+    pub pet_mode_size: Option<Size>,
+    pub pet_mode_position: Option<PositionStrategy>,
+    pub max_windows: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialShortcutConfig {
+    pub toggle_window: Option<Shortcut>,
+    pub toggle_pet_mode: Option<Shortcut>,
+    pub new_chat: Option<Shortcut>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PartialLLMConfig {
     pub default_provider: Option<String>,
     pub stream_response: Option<bool>,
     pub context_length: Option<u32>,
+    pub auto_fallback: Option<bool>,
+    pub max_user_message_chars: Option<u32>,
+    pub auto_title: Option<AutoTitleConfig>,
+    pub analyze_user_emotion: Option<bool>,
+    pub combined_stream_frames: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialProviderConfig {
+    pub name: Option<String>,
+    pub provider_type: Option<String>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub models: Option<Vec<String>>,
+    pub is_default: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -252,6 +649,38 @@ pub struct PartialModelConfig {
     pub physics_enabled: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialBackupConfig {
+    pub enabled: Option<bool>,
+    pub interval_minutes: Option<u32>,
+    pub keep: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialAuditLogConfig {
+    pub enabled: Option<bool>,
+    pub max_size_kb: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialContentFilterConfig {
+    pub enabled: Option<bool>,
+    pub terms: Option<Vec<String>>,
+    /// 空字符串表示清除占位文本
+    pub placeholder: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialRepetitionGuardConfig {
+    pub enabled: Option<bool>,
+    pub max_pattern_len: Option<u32>,
+    pub min_repeats: Option<u32>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -281,6 +710,34 @@ mod tests {
         assert_eq!(config.general.language.code(), "zh-CN");
     }
 
+    #[test]
+    fn test_app_config_merge_window_and_shortcuts() {
+        let mut config = AppConfig::default();
+        let defaults = AppConfig::default();
+        config.merge(PartialAppConfig {
+            window: Some(PartialWindowConfig {
+                pet_mode_size: Some(Size::new(200, 260)),
+                ..Default::default()
+            }),
+            shortcuts: Some(PartialShortcutConfig {
+                toggle_window: Some(Shortcut::new("Alt+K")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(config.window.pet_mode_size, Size::new(200, 260));
+        assert_eq!(config.shortcuts.toggle_window, Shortcut::new("Alt+K"));
+        // 其他字段保持不变
+        assert_eq!(config.window.default_mode, defaults.window.default_mode);
+        assert_eq!(config.window.max_windows, defaults.window.max_windows);
+        assert_eq!(
+            config.shortcuts.toggle_pet_mode,
+            defaults.shortcuts.toggle_pet_mode
+        );
+        assert_eq!(config.shortcuts.new_chat, defaults.shortcuts.new_chat);
+    }
+
     #[test]
     fn test_app_config_validate() {
         let config = AppConfig::default();
@@ -290,4 +747,21 @@ mod tests {
         invalid_config.llm.context_length = 0;
         assert!(invalid_config.validate().is_err());
     }
+
+    #[test]
+    fn test_auto_title_config_serde() {
+        let config = LLMConfig::default();
+        assert_eq!(config.auto_title, AutoTitleConfig::AfterFirstReply);
+
+        let parsed: AutoTitleConfig = serde_json::from_str(r#"{"afterNMessages":6}"#).unwrap();
+        assert_eq!(parsed, AutoTitleConfig::AfterNMessages(6));
+        assert_eq!(
+            serde_json::to_string(&AutoTitleConfig::Off).unwrap(),
+            r#""off""#
+        );
+
+        let mut invalid_config = AppConfig::default();
+        invalid_config.llm.auto_title = AutoTitleConfig::AfterNMessages(0);
+        assert!(invalid_config.validate().is_err());
+    }
 }
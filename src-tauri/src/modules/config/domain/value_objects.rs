@@ -2,8 +2,11 @@
 //
 // 配置相关的值对象定义
 
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 
+use crate::shared::Clock;
+
 /// 主题类型
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -145,9 +148,88 @@ impl Default for Size {
     }
 }
 
+/// 免打扰时段（本地时间，含开始不含结束）
+///
+/// 开始晚于结束时表示跨越午夜，如 22:00-07:00；开始等于结束的时段为空
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    #[serde(with = "hour_minute")]
+    pub start: NaiveTime,
+    #[serde(with = "hour_minute")]
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    /// 是否跨越午夜
+    pub fn wraps_midnight(&self) -> bool {
+        self.start > self.end
+    }
+
+    /// 给定时间是否处于免打扰时段
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.wraps_midnight() {
+            time >= self.start || time < self.end
+        } else {
+            self.start <= time && time < self.end
+        }
+    }
+}
+
+/// 当前是否处于免打扰时段，通知和主动消息发送前统一检查
+pub fn is_quiet_now(quiet_hours: Option<&QuietHours>, clock: &dyn Clock) -> bool {
+    quiet_hours.is_some_and(|hours| hours.contains(clock.now_local().time()))
+}
+
+/// 以 `HH:MM` 格式序列化时间，反序列化时兼容 `HH:MM:SS`
+mod hour_minute {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%H:%M";
+
+    pub fn serialize<S>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&time.format(FORMAT).to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveTime, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&value, FORMAT)
+            .or_else(|_| NaiveTime::parse_from_str(&value, "%H:%M:%S"))
+            .map_err(|_| serde::de::Error::custom(format!("Invalid time: {}", value)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDateTime;
+
+    struct FixedClock(NaiveDateTime);
+
+    impl Clock for FixedClock {
+        fn now_local(&self) -> NaiveDateTime {
+            self.0
+        }
+    }
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    fn clock_at(value: &str) -> FixedClock {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        FixedClock(date.and_time(time(value)))
+    }
 
     #[test]
     fn test_theme_from_str() {
@@ -163,4 +245,57 @@ mod tests {
         assert!(Shortcut::new("F1").is_valid());
         assert!(!Shortcut::new("").is_valid());
     }
+
+    #[test]
+    fn test_quiet_hours_within_day() {
+        let hours = QuietHours::new(time("13:00"), time("14:30"));
+        assert!(!hours.wraps_midnight());
+        assert!(hours.contains(time("13:00")));
+        assert!(hours.contains(time("14:29")));
+        assert!(!hours.contains(time("14:30")));
+        assert!(!hours.contains(time("12:59")));
+    }
+
+    #[test]
+    fn test_quiet_hours_wrap_past_midnight() {
+        let quiet_hours = QuietHours::new(time("22:00"), time("07:00"));
+        assert!(quiet_hours.wraps_midnight());
+
+        let hours = Some(quiet_hours);
+
+        assert!(is_quiet_now(hours.as_ref(), &clock_at("22:00")));
+        assert!(is_quiet_now(hours.as_ref(), &clock_at("23:59")));
+        assert!(is_quiet_now(hours.as_ref(), &clock_at("00:00")));
+        assert!(is_quiet_now(hours.as_ref(), &clock_at("06:59")));
+        assert!(!is_quiet_now(hours.as_ref(), &clock_at("07:00")));
+        assert!(!is_quiet_now(hours.as_ref(), &clock_at("12:00")));
+        assert!(!is_quiet_now(hours.as_ref(), &clock_at("21:59")));
+    }
+
+    #[test]
+    fn test_quiet_hours_unset_or_empty() {
+        assert!(!is_quiet_now(None, &clock_at("03:00")));
+
+        let empty = QuietHours::new(time("08:00"), time("08:00"));
+        assert!(!is_quiet_now(Some(&empty), &clock_at("08:00")));
+    }
+
+    #[test]
+    fn test_quiet_hours_serde() {
+        let hours = QuietHours::new(time("22:00"), time("07:30"));
+        let json = serde_json::to_value(hours).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "start": "22:00", "end": "07:30" })
+        );
+
+        let parsed: QuietHours =
+            serde_json::from_value(serde_json::json!({ "start": "22:00:00", "end": "07:30" }))
+                .unwrap();
+        assert_eq!(parsed, hours);
+        assert!(serde_json::from_value::<QuietHours>(
+            serde_json::json!({ "start": "25:00", "end": "07:00" })
+        )
+        .is_err());
+    }
 }
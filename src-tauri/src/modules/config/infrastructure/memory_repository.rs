@@ -104,6 +104,10 @@ impl ConfigRepository for InMemoryConfigRepository {
         values.remove(key);
         Ok(())
     }
+
+    async fn flush(&self) -> Result<(), ConfigError> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -3,7 +3,9 @@
 // 配置模块的基础设施实现
 
 pub mod memory_repository;
+pub mod secret_cipher;
 pub mod store_repository;
 
 pub use memory_repository::*;
+pub use secret_cipher::*;
 pub use store_repository::*;
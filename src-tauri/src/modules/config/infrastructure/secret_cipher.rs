@@ -0,0 +1,144 @@
+// Secret Cipher
+//
+// 使用应用本地密钥加密配置文件中的敏感字段（如 API Key）
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io;
+use std::path::Path;
+
+use crate::modules::config::ports::ConfigError;
+
+/// 密钥文件名（位于应用数据目录下，与配置文件分开保存）
+pub const SECRET_KEY_FILE_NAME: &str = "secret.key";
+
+/// 加密值的前缀，用于区分旧版本写入的明文
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// 密钥长度（ChaCha20-Poly1305）
+const KEY_LEN: usize = 32;
+
+/// 敏感字段加解密器
+///
+/// 加密结果为 `enc:v1:` 加 base64 编码的随机 nonce 和密文
+pub struct SecretCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretCipher {
+    /// 读取密钥文件，不存在时生成新密钥并写入
+    pub fn load_or_create(path: &Path) -> Result<Self, ConfigError> {
+        let rng = SystemRandom::new();
+        let key = match std::fs::read(path) {
+            Ok(key) => key,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let mut key = vec![0u8; KEY_LEN];
+                rng.fill(&mut key)
+                    .map_err(|_| ConfigError::StorageError("Failed to generate key".into()))?;
+                write_key_file(path, &key).map_err(|e| ConfigError::StorageError(e.to_string()))?;
+                tracing::info!("Created secret key at {:?}", path);
+                key
+            }
+            Err(e) => return Err(ConfigError::StorageError(e.to_string())),
+        };
+
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+            .map_err(|_| ConfigError::Invalid(format!("Invalid secret key file: {:?}", path)))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng,
+        })
+    }
+
+    /// 是否为加密后的值
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// 加密明文
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, ConfigError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| ConfigError::StorageError("Failed to generate nonce".into()))?;
+
+        let mut ciphertext = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::empty(),
+                &mut ciphertext,
+            )
+            .map_err(|_| ConfigError::StorageError("Failed to encrypt secret".into()))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+    }
+
+    /// 解密 [`encrypt`](Self::encrypt) 的结果
+    pub fn decrypt(&self, value: &str) -> Result<String, ConfigError> {
+        let invalid = || ConfigError::Invalid("Invalid encrypted secret".to_string());
+
+        let encoded = value.strip_prefix(ENCRYPTED_PREFIX).ok_or_else(invalid)?;
+        let mut payload = STANDARD.decode(encoded).map_err(|_| invalid())?;
+        if payload.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let mut ciphertext = payload.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&payload).map_err(|_| invalid())?;
+
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut ciphertext)
+            .map_err(|_| invalid())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| invalid())
+    }
+}
+
+/// 写入密钥文件，Unix 上仅当前用户可读写
+fn write_key_file(path: &Path, key: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encrypt_round_trip_with_persisted_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(SECRET_KEY_FILE_NAME);
+
+        let cipher = SecretCipher::load_or_create(&path).unwrap();
+        let encrypted = cipher.encrypt("sk-secret").unwrap();
+        assert!(SecretCipher::is_encrypted(&encrypted));
+        assert!(!encrypted.contains("sk-secret"));
+        // 每次加密使用新的 nonce
+        assert_ne!(cipher.encrypt("sk-secret").unwrap(), encrypted);
+
+        let reloaded = SecretCipher::load_or_create(&path).unwrap();
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "sk-secret");
+        assert!(reloaded.decrypt("enc:v1:AAAA").is_err());
+
+        let other_dir = TempDir::new().unwrap();
+        let other = SecretCipher::load_or_create(&other_dir.path().join("key")).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+    }
+}
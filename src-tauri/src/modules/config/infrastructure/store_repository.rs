@@ -3,10 +3,12 @@
 // 基于 Tauri Store 插件的配置仓储实现
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{OnceCell, RwLock};
 
+use super::secret_cipher::{SecretCipher, SECRET_KEY_FILE_NAME};
 use crate::modules::config::domain::AppConfig;
 use crate::modules::config::ports::{ConfigError, ConfigRepository};
 
@@ -16,12 +18,19 @@ const CONFIG_KEY: &str = "app_config";
 
 /// Tauri Store 配置仓储
 ///
-/// 使用 tauri-plugin-store 持久化配置
+/// 使用 tauri-plugin-store 持久化配置；提供商的 API Key 加密后写入文件，
+/// 密钥单独保存在应用数据目录下
 pub struct StoreConfigRepository {
     /// 配置文件路径
     config_path: PathBuf,
+    /// 密钥文件路径
+    key_path: PathBuf,
+    /// API Key 加解密器，首次读写时加载
+    cipher: OnceCell<SecretCipher>,
     /// 内存缓存
     cache: Arc<RwLock<Option<AppConfig>>>,
+    /// 无法解密的 API Key 密文（按提供商 ID），保存时原样写回，直到用户输入新的 Key
+    unreadable_keys: RwLock<HashMap<String, String>>,
 }
 
 impl StoreConfigRepository {
@@ -32,10 +41,19 @@ impl StoreConfigRepository {
     pub fn new(app_data_dir: PathBuf) -> Self {
         Self {
             config_path: app_data_dir.join(CONFIG_FILE_NAME),
+            key_path: app_data_dir.join(SECRET_KEY_FILE_NAME),
+            cipher: OnceCell::new(),
             cache: Arc::new(RwLock::new(None)),
+            unreadable_keys: RwLock::new(HashMap::new()),
         }
     }
 
+    async fn cipher(&self) -> Result<&SecretCipher, ConfigError> {
+        self.cipher
+            .get_or_try_init(|| async { SecretCipher::load_or_create(&self.key_path) })
+            .await
+    }
+
     /// 从文件加载配置
     async fn load_from_file(&self) -> Result<Option<AppConfig>, ConfigError> {
         if !self.config_path.exists() {
@@ -46,12 +64,47 @@ impl StoreConfigRepository {
             .await
             .map_err(|e| ConfigError::StorageError(e.to_string()))?;
 
-        let config: AppConfig = serde_json::from_str(&content)
+        let mut config: AppConfig = serde_json::from_str(&content)
             .map_err(|e| ConfigError::SerializationError(e.to_string()))?;
 
+        // 解密 API Key；旧版本写入的明文在解密后立即以加密形式重写。
+        // 无法解密的密文保留下来原样写回，不会被空值覆盖
+        let cipher = self.cipher().await?;
+        let mut has_plaintext = false;
+        let mut unreadable_keys = HashMap::new();
+        for provider in config.llm.providers.values_mut() {
+            if SecretCipher::is_encrypted(&provider.api_key) {
+                match cipher.decrypt(&provider.api_key) {
+                    Ok(api_key) => provider.api_key = api_key,
+                    Err(e) => {
+                        tracing::warn!("Failed to decrypt API key of {}: {}", provider.id, e);
+                        let ciphertext = std::mem::take(&mut provider.api_key);
+                        unreadable_keys.insert(provider.id.clone(), ciphertext);
+                    }
+                }
+            } else if !provider.api_key.is_empty() {
+                has_plaintext = true;
+            }
+        }
+        *self.unreadable_keys.write().await = unreadable_keys;
+        if has_plaintext {
+            tracing::info!("Migrating plaintext API keys to encrypted storage");
+            self.save_to_file(&config).await?;
+        }
+
         Ok(Some(config))
     }
 
+    /// 标记 API Key 无法解密、需要重新输入的提供商
+    async fn mark_unreadable_keys(&self, mut config: AppConfig) -> AppConfig {
+        let unreadable_keys = self.unreadable_keys.read().await;
+        for provider in config.llm.providers.values_mut() {
+            provider.api_key_unreadable =
+                provider.api_key.is_empty() && unreadable_keys.contains_key(&provider.id);
+        }
+        config
+    }
+
     /// 保存配置到文件
     async fn save_to_file(&self, config: &AppConfig) -> Result<(), ConfigError> {
         // 确保目录存在
@@ -61,7 +114,20 @@ impl StoreConfigRepository {
                 .map_err(|e| ConfigError::StorageError(e.to_string()))?;
         }
 
-        let content = serde_json::to_string_pretty(config)
+        let cipher = self.cipher().await?;
+        let mut stored = config.clone();
+        let mut unreadable_keys = self.unreadable_keys.write().await;
+        for provider in stored.llm.providers.values_mut() {
+            if !provider.api_key.is_empty() {
+                provider.api_key = cipher.encrypt(&provider.api_key)?;
+                unreadable_keys.remove(&provider.id);
+            } else if let Some(ciphertext) = unreadable_keys.get(&provider.id) {
+                provider.api_key = ciphertext.clone();
+            }
+        }
+        drop(unreadable_keys);
+
+        let content = serde_json::to_string_pretty(&stored)
             .map_err(|e| ConfigError::SerializationError(e.to_string()))?;
 
         tokio::fs::write(&self.config_path, content)
@@ -79,7 +145,7 @@ impl ConfigRepository for StoreConfigRepository {
         {
             let cache = self.cache.read().await;
             if let Some(ref config) = *cache {
-                return Ok(config.clone());
+                return Ok(self.mark_unreadable_keys(config.clone()).await);
             }
         }
 
@@ -92,7 +158,7 @@ impl ConfigRepository for StoreConfigRepository {
             *cache = Some(config.clone());
         }
 
-        Ok(config)
+        Ok(self.mark_unreadable_keys(config).await)
     }
 
     async fn save(&self, config: &AppConfig) -> Result<(), ConfigError> {
@@ -176,6 +242,15 @@ impl ConfigRepository for StoreConfigRepository {
 
         Ok(())
     }
+
+    async fn flush(&self) -> Result<(), ConfigError> {
+        // 尚未加载过的配置与文件一致，无需写入
+        let cache = self.cache.read().await;
+        match cache.as_ref() {
+            Some(config) => self.save_to_file(config).await,
+            None => Ok(()),
+        }
+    }
 }
 
 /// 设置嵌套的 JSON 值
@@ -243,3 +318,87 @@ fn delete_nested_value(json: &mut serde_json::Value, parts: &[&str]) -> Result<(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::config::domain::LLMProviderConfig;
+    use tempfile::TempDir;
+
+    const RAW_KEY: &str = "sk-raw-secret-123";
+
+    fn config_with_key() -> AppConfig {
+        let mut config = AppConfig::default();
+        let mut provider = LLMProviderConfig::new("openai", "OpenAI", "openai");
+        provider.api_key = RAW_KEY.to_string();
+        config.llm.providers.insert(provider.id.clone(), provider);
+        config
+    }
+
+    fn read_config_file(dir: &TempDir) -> String {
+        std::fs::read_to_string(dir.path().join(CONFIG_FILE_NAME)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_api_keys_encrypted_on_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        repo.save(&config_with_key()).await.unwrap();
+
+        assert!(!read_config_file(&temp_dir).contains(RAW_KEY));
+        assert!(temp_dir.path().join(SECRET_KEY_FILE_NAME).exists());
+
+        let reloaded = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        let config = reloaded.load().await.unwrap();
+        assert_eq!(config.llm.providers["openai"].api_key, RAW_KEY);
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_api_keys_migrated_on_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let plaintext = serde_json::to_string_pretty(&config_with_key()).unwrap();
+        std::fs::write(temp_dir.path().join(CONFIG_FILE_NAME), plaintext).unwrap();
+
+        let repo = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        let config = repo.load().await.unwrap();
+        assert_eq!(config.llm.providers["openai"].api_key, RAW_KEY);
+        assert!(!read_config_file(&temp_dir).contains(RAW_KEY));
+    }
+
+    #[tokio::test]
+    async fn test_undecryptable_api_key_kept_on_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        repo.save(&config_with_key()).await.unwrap();
+        let encrypted = read_config_file(&temp_dir);
+
+        // 密钥文件丢失后重新生成，原密文无法解密
+        std::fs::remove_file(temp_dir.path().join(SECRET_KEY_FILE_NAME)).unwrap();
+        let repo = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        let mut config = repo.load().await.unwrap();
+        let provider = &config.llm.providers["openai"];
+        assert!(provider.api_key.is_empty());
+        assert!(provider.api_key_unreadable);
+
+        // 保存其他修改时原密文保持不变
+        config.general.minimize_to_tray = false;
+        repo.save(&config).await.unwrap();
+        let stored: AppConfig = serde_json::from_str(&read_config_file(&temp_dir)).unwrap();
+        let original: AppConfig = serde_json::from_str(&encrypted).unwrap();
+        assert_eq!(
+            stored.llm.providers["openai"].api_key,
+            original.llm.providers["openai"].api_key
+        );
+        assert!(repo.load().await.unwrap().llm.providers["openai"].api_key_unreadable);
+
+        // 输入新的 Key 后替换密文
+        let mut config = repo.load().await.unwrap();
+        let provider = config.llm.providers.get_mut("openai").unwrap();
+        provider.api_key = "sk-new".to_string();
+        repo.save(&config).await.unwrap();
+        let reloaded = StoreConfigRepository::new(temp_dir.path().to_path_buf());
+        let provider = &reloaded.load().await.unwrap().llm.providers["openai"];
+        assert_eq!(provider.api_key, "sk-new");
+        assert!(!provider.api_key_unreadable);
+    }
+}
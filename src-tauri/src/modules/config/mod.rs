@@ -17,9 +17,12 @@ pub mod ports;
 
 // Domain
 pub use domain::{
-    AppConfig, GeneralConfig, LLMConfig, LLMProviderConfig, Language, ModelConfig,
-    PartialAppConfig, PartialGeneralConfig, PartialLLMConfig, PartialModelConfig, PositionStrategy,
-    Shortcut, ShortcutConfig, Size, Theme, WindowConfig, WindowModeConfig,
+    AppConfig, AuditLogConfig, AutoTitleConfig, BackupConfig, ContentFilterConfig, GeneralConfig,
+    LLMConfig, LLMProviderConfig, Language, ModelConfig, PartialAppConfig, PartialAuditLogConfig,
+    PartialBackupConfig, PartialContentFilterConfig, PartialGeneralConfig, PartialLLMConfig,
+    PartialModelConfig, PartialProviderConfig, PartialRepetitionGuardConfig, PartialShortcutConfig, PartialWindowConfig, PositionStrategy,
+    QuietHours, RepetitionGuardConfig, Shortcut, ShortcutConfig, Size, Theme, WindowConfig, WindowModeConfig,
+    is_quiet_now,
 };
 
 pub use domain::{
@@ -34,12 +37,16 @@ pub use infrastructure::{InMemoryConfigRepository, StoreConfigRepository};
 
 // Application
 pub use application::{
-    CommandHandler, ConfigExistsHandler, ConfigExistsQuery, ConfigExistsResponse, ConfigService,
-    DeleteConfigValueCommand, DeleteConfigValueHandler, DeleteConfigValueResponse,
+    CloneProviderCommand, CloneProviderHandler, CloneProviderResponse, CommandHandler,
+    ConfigExistsHandler, ConfigExistsQuery, ConfigExistsResponse, ConfigService,
+    DeleteConfigValueCommand, DeleteConfigValueHandler, DeleteConfigValueResponse, FirstRunStatus,
     GetAllConfigHandler, GetAllConfigQuery, GetAllConfigResponse, GetConfigValueHandler,
-    GetConfigValueQuery, GetConfigValueResponse, QueryHandler, ResetConfigCommand,
-    ResetConfigHandler, ResetConfigResponse, SetConfigValueCommand, SetConfigValueHandler,
-    SetConfigValueResponse, UpdateConfigCommand, UpdateConfigHandler, UpdateConfigResponse,
+    GetConfigValueQuery, GetConfigValueResponse, ListProvidersHandler, ListProvidersQuery,
+    ListProvidersResponse, QueryHandler, ReorderProvidersCommand, ReorderProvidersHandler,
+    ReorderProvidersResponse, ResetConfigCommand, ResetConfigHandler, ResetConfigResponse, SetConfigValueCommand, SetConfigValueHandler,
+    SetConfigValueResponse, SetQuietHoursCommand, SetQuietHoursHandler, SetQuietHoursResponse,
+    UpdateConfigCommand, UpdateConfigHandler, UpdateConfigResponse,
+    UpdateProviderCommand, UpdateProviderHandler, UpdateProviderResponse,
 };
 
 use std::sync::Arc;
@@ -85,6 +92,11 @@ impl ConfigModule {
         self.service.get_all().await
     }
 
+    /// 将配置写入磁盘
+    pub async fn flush(&self) -> Result<(), ConfigError> {
+        self.service.repository().flush().await
+    }
+
     /// 更新配置
     pub async fn update(&self, partial: PartialAppConfig) -> Result<AppConfig, ConfigError> {
         self.service.update(partial).await
@@ -111,6 +123,46 @@ impl ConfigModule {
     ) -> Result<(), ConfigError> {
         self.service.set(key, value).await
     }
+
+    /// 复制提供商配置
+    pub async fn clone_provider(&self, source_id: &str) -> Result<LLMProviderConfig, ConfigError> {
+        self.service.clone_provider(source_id).await
+    }
+
+    /// 部分更新提供商配置
+    pub async fn update_provider(
+        &self,
+        id: &str,
+        partial: PartialProviderConfig,
+    ) -> Result<LLMProviderConfig, ConfigError> {
+        self.service.update_provider(id, partial).await
+    }
+
+    /// 按显示顺序列出提供商
+    pub async fn list_providers(&self) -> Result<Vec<LLMProviderConfig>, ConfigError> {
+        self.service.list_providers().await
+    }
+
+    /// 重排提供商显示顺序
+    pub async fn reorder_providers(
+        &self,
+        ordered_ids: Vec<String>,
+    ) -> Result<Vec<LLMProviderConfig>, ConfigError> {
+        self.service.reorder_providers(ordered_ids).await
+    }
+
+    /// 设置免打扰时段
+    pub async fn set_quiet_hours(
+        &self,
+        quiet_hours: Option<QuietHours>,
+    ) -> Result<Option<QuietHours>, ConfigError> {
+        self.service.set_quiet_hours(quiet_hours).await
+    }
+
+    /// 检测是否首次运行
+    pub async fn first_run_status(&self) -> Result<FirstRunStatus, ConfigError> {
+        self.service.first_run_status().await
+    }
 }
 
 #[cfg(test)]
@@ -146,4 +198,19 @@ mod tests {
         assert_eq!(reset.general.theme, Theme::System);
         assert!(!reset.general.auto_start);
     }
+
+    #[tokio::test]
+    async fn test_first_run_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let module = ConfigModule::new_with_store(dir.path().to_path_buf());
+
+        let status = module.first_run_status().await.unwrap();
+        assert!(status.is_first_run);
+        assert!(!status.has_provider);
+
+        module.update(PartialAppConfig::default()).await.unwrap();
+
+        let status = module.first_run_status().await.unwrap();
+        assert!(!status.is_first_run);
+    }
 }
@@ -5,7 +5,7 @@
 use async_trait::async_trait;
 use thiserror::Error;
 
-use crate::modules::config::domain::{AppConfig, PartialAppConfig};
+use crate::modules::config::domain::{AppConfig, ConfigChangedEvent, PartialAppConfig};
 
 /// 配置错误类型
 #[derive(Error, Debug)]
@@ -66,6 +66,6 @@ pub trait ConfigPort: Send + Sync {
 
 /// 配置观察者 - 用于监听配置变化
 pub trait ConfigObserver: Send + Sync {
-    /// 配置变化时调用
-    fn on_config_changed(&self, key: &str, new_value: &serde_json::Value);
+    /// 配置变化时调用，`event.key` 为变化的配置段或键，整体变化时为 `*`
+    fn on_config_changed(&self, event: &ConfigChangedEvent);
 }
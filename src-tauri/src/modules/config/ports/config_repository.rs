@@ -30,4 +30,7 @@ pub trait ConfigRepository: Send + Sync {
 
     /// 删除单个配置项
     async fn delete_value(&self, key: &str) -> Result<(), ConfigError>;
+
+    /// 将内存中的配置写入持久存储，无持久化的实现直接返回
+    async fn flush(&self) -> Result<(), ConfigError>;
 }
@@ -16,6 +16,9 @@ pub struct WindowConfig {
     pub size: WindowSize,
     pub position: Option<WindowPosition>,
     pub always_on_top: bool,
+    /// 始终位于其他窗口之下，与 `always_on_top` 互斥
+    #[serde(default)]
+    pub always_on_bottom: bool,
     pub decorations: bool,
     pub transparent: bool,
     pub skip_taskbar: bool,
@@ -33,6 +36,7 @@ impl WindowConfig {
             size: WindowSize::new(1200, 800),
             position: None,
             always_on_top: false,
+            always_on_bottom: false,
             decorations: true,
             transparent: false,
             skip_taskbar: false,
@@ -50,6 +54,7 @@ impl WindowConfig {
             size: WindowSize::new(300, 400),
             position: None,
             always_on_top: true,
+            always_on_bottom: false,
             decorations: false,
             transparent: true,
             skip_taskbar: true,
@@ -67,6 +72,7 @@ impl WindowConfig {
             size: WindowSize::new(400, 600),
             position: None,
             always_on_top: true,
+            always_on_bottom: false,
             decorations: false,
             transparent: false,
             skip_taskbar: false,
@@ -75,6 +81,26 @@ impl WindowConfig {
         }
     }
 
+    /// 指定模式的默认配置
+    pub fn for_mode(mode: WindowMode) -> Self {
+        match mode {
+            WindowMode::Normal => Self::main_window(),
+            WindowMode::Pet => Self::pet_mode(),
+            WindowMode::Compact => Self::compact_mode(),
+            WindowMode::Desktop => {
+                // 桌面模式与宠物模式同为无边框透明的小窗口
+                let mut config = Self::pet_mode();
+                config.apply_mode(mode);
+                config
+            }
+            WindowMode::Fullscreen => {
+                let mut config = Self::main_window();
+                config.apply_mode(mode);
+                config
+            }
+        }
+    }
+
     /// 应用模式预设
     pub fn apply_mode(&mut self, mode: WindowMode) {
         self.mode = mode;
@@ -83,6 +109,7 @@ impl WindowConfig {
             WindowMode::Normal => {
                 self.decorations = true;
                 self.always_on_top = false;
+                self.always_on_bottom = false;
                 self.transparent = false;
                 self.skip_taskbar = false;
                 self.resizable = true;
@@ -90,6 +117,7 @@ impl WindowConfig {
             WindowMode::Pet => {
                 self.decorations = false;
                 self.always_on_top = true;
+                self.always_on_bottom = false;
                 self.transparent = true;
                 self.skip_taskbar = true;
                 self.resizable = false;
@@ -97,6 +125,7 @@ impl WindowConfig {
             WindowMode::Compact => {
                 self.decorations = false;
                 self.always_on_top = true;
+                self.always_on_bottom = false;
                 self.transparent = false;
                 self.skip_taskbar = false;
                 self.resizable = true;
@@ -104,10 +133,19 @@ impl WindowConfig {
             WindowMode::Fullscreen => {
                 self.decorations = false;
                 self.always_on_top = false;
+                self.always_on_bottom = false;
                 self.transparent = false;
                 self.skip_taskbar = false;
                 self.resizable = false;
             }
+            WindowMode::Desktop => {
+                self.decorations = false;
+                self.always_on_top = false;
+                self.always_on_bottom = true;
+                self.transparent = true;
+                self.skip_taskbar = true;
+                self.resizable = false;
+            }
         }
     }
 }
@@ -130,6 +168,9 @@ pub struct WindowState {
     pub is_maximized: bool,
     pub current_size: WindowSize,
     pub current_position: WindowPosition,
+    /// 窗口是否以透明背景创建
+    #[serde(default)]
+    pub is_transparent: bool,
 }
 
 impl WindowState {
@@ -143,6 +184,7 @@ impl WindowState {
             is_maximized: false,
             current_size: WindowSize::default(),
             current_position: WindowPosition::default(),
+            is_transparent: false,
         }
     }
 }
@@ -173,6 +215,7 @@ impl ModeSizeConfig {
             WindowMode::Pet => self.pet,
             WindowMode::Compact => self.compact,
             WindowMode::Fullscreen => self.normal, // 全屏使用系统尺寸
+            WindowMode::Desktop => self.pet,
         }
     }
 }
@@ -191,6 +234,38 @@ mod tests {
         assert!(!config.decorations);
         assert!(config.always_on_top);
         assert!(config.transparent);
+
+        config.apply_mode(WindowMode::Desktop);
+        assert!(!config.always_on_top);
+        assert!(config.always_on_bottom);
+        assert!(config.transparent);
+
+        config.apply_mode(WindowMode::Normal);
+        assert!(!config.always_on_bottom);
+    }
+
+    #[test]
+    fn test_for_mode_starts_from_mode_defaults() {
+        for mode in [
+            WindowMode::Normal,
+            WindowMode::Pet,
+            WindowMode::Compact,
+            WindowMode::Fullscreen,
+            WindowMode::Desktop,
+        ] {
+            assert_eq!(WindowConfig::for_mode(mode).mode, mode);
+        }
+        assert_eq!(
+            WindowConfig::for_mode(WindowMode::Pet).size,
+            WindowConfig::pet_mode().size
+        );
+        assert_eq!(
+            WindowConfig::for_mode(WindowMode::Compact).size,
+            WindowConfig::compact_mode().size
+        );
+        let desktop = WindowConfig::for_mode(WindowMode::Desktop);
+        assert!(desktop.always_on_bottom);
+        assert_eq!(desktop.size, WindowConfig::pet_mode().size);
     }
 
     #[test]
@@ -14,6 +14,8 @@ pub enum WindowMode {
     Pet,
     Compact,
     Fullscreen,
+    /// 固定在桌面：宠物窗口始终位于其他窗口之下
+    Desktop,
 }
 
 impl WindowMode {
@@ -23,11 +25,13 @@ impl WindowMode {
             WindowMode::Pet => "pet",
             WindowMode::Compact => "compact",
             WindowMode::Fullscreen => "fullscreen",
+            WindowMode::Desktop => "desktop",
         }
     }
 
+    /// 是否以透明窗口显示角色（包括固定在桌面的宠物）
     pub fn is_pet_mode(&self) -> bool {
-        matches!(self, WindowMode::Pet)
+        matches!(self, WindowMode::Pet | WindowMode::Desktop)
     }
 
     pub fn requires_decorations(&self) -> bool {
@@ -37,6 +41,16 @@ impl WindowMode {
     pub fn requires_always_on_top(&self) -> bool {
         matches!(self, WindowMode::Pet | WindowMode::Compact)
     }
+
+    /// 该模式下窗口是否需要始终位于其他窗口之下
+    pub fn requires_always_on_bottom(&self) -> bool {
+        matches!(self, WindowMode::Desktop)
+    }
+
+    /// 该模式下是否允许拖拽移动窗口
+    pub fn is_draggable(&self) -> bool {
+        !matches!(self, WindowMode::Fullscreen)
+    }
 }
 
 impl From<&str> for WindowMode {
@@ -45,6 +59,7 @@ impl From<&str> for WindowMode {
             "pet" => WindowMode::Pet,
             "compact" => WindowMode::Compact,
             "fullscreen" => WindowMode::Fullscreen,
+            "desktop" => WindowMode::Desktop,
             _ => WindowMode::Normal,
         }
     }
@@ -121,6 +136,11 @@ impl WindowLabel {
     pub fn settings() -> Self {
         Self("settings".to_string())
     }
+
+    /// 是否为主窗口
+    pub fn is_main(&self) -> bool {
+        self.0 == "main"
+    }
 }
 
 impl Default for WindowLabel {
@@ -141,6 +161,96 @@ impl std::fmt::Display for WindowLabel {
     }
 }
 
+/// 拖拽守卫
+///
+/// 宠物模式的透明窗口只允许从角色区域发起拖拽，
+/// 指针是否位于角色上由前端命中测试后告知
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DragGuard {
+    /// 位置已锁定，任何模式下都不允许拖拽
+    pub locked: bool,
+    /// 指针是否位于可拖拽区域（角色像素）
+    pub in_drag_region: bool,
+}
+
+impl DragGuard {
+    /// 当前是否允许在给定模式下发起拖拽
+    pub fn allows(&self, mode: WindowMode) -> bool {
+        if self.locked || !mode.is_draggable() {
+            return false;
+        }
+        !mode.is_pet_mode() || self.in_drag_region
+    }
+}
+
+/// 显示器信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub position: WindowPosition,
+    pub size: WindowSize,
+    /// 工作区（去除任务栏 / Dock 后的可用区域）
+    pub work_area_position: WindowPosition,
+    pub work_area_size: WindowSize,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+impl MonitorInfo {
+    /// 将窗口位置从 `from` 显示器的工作区平移到 `to` 显示器的工作区
+    ///
+    /// 按窗口在可移动范围内的相对比例换算，保证窗口完整落在目标工作区内
+    pub fn translate_position(
+        position: WindowPosition,
+        window_size: WindowSize,
+        from: &MonitorInfo,
+        to: &MonitorInfo,
+    ) -> WindowPosition {
+        let x = translate_axis(
+            position.x,
+            window_size.width,
+            from.work_area_position.x,
+            from.work_area_size.width,
+            to.work_area_position.x,
+            to.work_area_size.width,
+        );
+        let y = translate_axis(
+            position.y,
+            window_size.height,
+            from.work_area_position.y,
+            from.work_area_size.height,
+            to.work_area_position.y,
+            to.work_area_size.height,
+        );
+
+        WindowPosition::new(x, y)
+    }
+}
+
+/// 单轴坐标换算
+fn translate_axis(
+    value: i32,
+    window_len: u32,
+    from_origin: i32,
+    from_len: u32,
+    to_origin: i32,
+    to_len: u32,
+) -> i32 {
+    let from_free = from_len.saturating_sub(window_len) as f64;
+    let to_free = to_len.saturating_sub(window_len) as f64;
+
+    let ratio = if from_free > 0.0 {
+        ((value - from_origin) as f64 / from_free).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    to_origin + (ratio * to_free).round() as i32
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +261,27 @@ mod tests {
         assert_eq!(WindowMode::from("normal"), WindowMode::Normal);
         assert!(WindowMode::Pet.is_pet_mode());
         assert!(!WindowMode::Normal.is_pet_mode());
+
+        assert_eq!(WindowMode::from("desktop"), WindowMode::Desktop);
+        assert!(WindowMode::Desktop.is_pet_mode());
+        assert!(WindowMode::Desktop.requires_always_on_bottom());
+        assert!(!WindowMode::Desktop.requires_always_on_top());
+    }
+
+    #[test]
+    fn test_drag_guard() {
+        let mut guard = DragGuard::default();
+        assert!(guard.allows(WindowMode::Normal));
+        assert!(!guard.allows(WindowMode::Fullscreen));
+
+        // 宠物模式只允许从角色区域拖拽
+        assert!(!guard.allows(WindowMode::Pet));
+        guard.in_drag_region = true;
+        assert!(guard.allows(WindowMode::Pet));
+
+        guard.locked = true;
+        assert!(!guard.allows(WindowMode::Pet));
+        assert!(!guard.allows(WindowMode::Normal));
     }
 
     #[test]
@@ -163,4 +294,56 @@ mod tests {
         assert_eq!(clamped.width, 200);
         assert_eq!(clamped.height, 200);
     }
+
+    fn monitor(index: usize, x: i32, y: i32, width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo {
+            index,
+            name: None,
+            position: WindowPosition::new(x, y),
+            size: WindowSize::new(width, height),
+            work_area_position: WindowPosition::new(x, y),
+            work_area_size: WindowSize::new(width, height),
+            scale_factor: 1.0,
+            is_primary: index == 0,
+        }
+    }
+
+    #[test]
+    fn test_translate_position_between_resolutions() {
+        let from = monitor(0, 0, 0, 1920, 1080);
+        let to = monitor(1, 1920, 0, 3840, 2160);
+        let window = WindowSize::new(320, 480);
+
+        // 右下角 -> 右下角
+        let pos =
+            MonitorInfo::translate_position(WindowPosition::new(1600, 600), window, &from, &to);
+        assert_eq!(pos, WindowPosition::new(1920 + 3520, 1680));
+
+        // 中点 -> 中点
+        let pos =
+            MonitorInfo::translate_position(WindowPosition::new(800, 300), window, &from, &to);
+        assert_eq!(pos, WindowPosition::new(1920 + 1760, 840));
+
+        // 左上角 -> 左上角
+        let pos = MonitorInfo::translate_position(WindowPosition::new(0, 0), window, &from, &to);
+        assert_eq!(pos, WindowPosition::new(1920, 0));
+    }
+
+    #[test]
+    fn test_translate_position_clamps_into_work_area() {
+        let from = monitor(0, 0, 0, 2560, 1440);
+        let mut to = monitor(1, -1280, 0, 1280, 1024);
+        to.work_area_size = WindowSize::new(1280, 984);
+        let window = WindowSize::new(300, 400);
+
+        // 源窗口部分超出屏幕，换算后仍应落在目标工作区内
+        let pos =
+            MonitorInfo::translate_position(WindowPosition::new(2500, -50), window, &from, &to);
+        assert_eq!(pos, WindowPosition::new(-300, 0));
+
+        // 窗口比目标工作区还大时贴左上角
+        let huge = WindowSize::new(2000, 1200);
+        let pos = MonitorInfo::translate_position(WindowPosition::new(100, 100), huge, &from, &to);
+        assert_eq!(pos, WindowPosition::new(-1280, 0));
+    }
 }
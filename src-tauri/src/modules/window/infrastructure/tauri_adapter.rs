@@ -9,15 +9,124 @@ use tauri::{AppHandle, Manager, WebviewWindow};
 use tokio::sync::RwLock;
 
 use crate::modules::window::domain::{
-    WindowConfig, WindowLabel, WindowMode, WindowPosition, WindowSize, WindowState,
+    MonitorInfo, WindowConfig, WindowLabel, WindowMode, WindowPosition, WindowSize, WindowState,
 };
 use crate::modules::window::ports::{WindowError, WindowModeRegistry, WindowPort};
 
+/// 检查新建窗口是否超出数量上限
+///
+/// 主窗口不受限制，也不计入已打开的窗口数
+fn check_window_limit<'a>(
+    label: &WindowLabel,
+    open_labels: impl IntoIterator<Item = &'a str>,
+    max: usize,
+) -> Result<(), WindowError> {
+    if label.is_main() {
+        return Ok(());
+    }
+
+    let open = open_labels
+        .into_iter()
+        .filter(|open| !WindowLabel::from(*open).is_main())
+        .count();
+    if open >= max {
+        return Err(WindowError::LimitExceeded { max });
+    }
+    Ok(())
+}
+
+/// 透明窗口的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transparency {
+    /// 使用透明背景
+    Enabled,
+    /// 未请求透明背景
+    Disabled,
+    /// 请求了透明背景但当前环境不支持，退回不透明窗口
+    Unsupported,
+}
+
+impl Transparency {
+    fn decide(requested: bool, supported: bool) -> Self {
+        match (requested, supported) {
+            (false, _) => Self::Disabled,
+            (true, true) => Self::Enabled,
+            (true, false) => Self::Unsupported,
+        }
+    }
+
+    fn is_enabled(self) -> bool {
+        self == Self::Enabled
+    }
+}
+
+/// 检测当前环境是否支持透明窗口
+///
+/// 默认查询平台的窗口合成能力；检测不准时可通过 `KIZUNA_DISABLE_TRANSPARENCY`
+/// 环境变量覆盖：设为 `0` 或 `false` 强制启用透明，其他值强制使用不透明窗口
+fn detect_transparency_support() -> bool {
+    let override_value = std::env::var("KIZUNA_DISABLE_TRANSPARENCY").ok();
+    transparency_supported(override_value.as_deref(), compositor_available)
+}
+
+fn transparency_supported(override_value: Option<&str>, compositor: impl FnOnce() -> bool) -> bool {
+    match override_value.map(str::trim) {
+        Some("0") | Some("false") => true,
+        Some(_) => false,
+        None => compositor(),
+    }
+}
+
+/// 查询窗口系统是否启用了合成器
+///
+/// Linux/X11 在没有合成器时透明窗口会创建失败或渲染为黑色；GTK 尚未在主线程
+/// 初始化时无法查询，按支持处理
+#[cfg(target_os = "linux")]
+fn compositor_available() -> bool {
+    if !gtk::is_initialized_main_thread() {
+        return true;
+    }
+    gtk::gdk::Screen::default().is_none_or(|screen| screen.is_composited())
+}
+
+/// Windows 8 起和 macOS 始终启用窗口合成
+#[cfg(not(target_os = "linux"))]
+fn compositor_available() -> bool {
+    true
+}
+
+/// 检测当前平台是否支持将窗口固定在其他窗口之下
+///
+/// Wayland 不允许客户端控制窗口层级，移动平台没有窗口层级的概念
+fn detect_always_on_bottom_support() -> bool {
+    if cfg!(any(target_os = "android", target_os = "ios")) {
+        return false;
+    }
+    !(cfg!(target_os = "linux") && std::env::var_os("WAYLAND_DISPLAY").is_some())
+}
+
+/// 检查窗口配置请求的层级在当前平台上是否可用
+fn check_always_on_bottom(config: &WindowConfig, supported: bool) -> Result<(), WindowError> {
+    if config.always_on_bottom && !supported {
+        return Err(WindowError::PlatformNotSupported(format!(
+            "{} mode requires keeping the window below others",
+            config.mode.as_str()
+        )));
+    }
+    Ok(())
+}
+
 /// Tauri 窗口适配器
 pub struct TauriWindowAdapter {
     app_handle: AppHandle,
     mode_registry: WindowModeRegistry,
     states: Arc<RwLock<HashMap<String, WindowState>>>,
+    /// 除主窗口外最多同时打开的窗口数，None 表示不限制
+    max_windows: Option<usize>,
+    /// 当前环境是否支持透明窗口
+    transparency_supported: bool,
+    /// 当前平台是否支持将窗口固定在其他窗口之下
+    always_on_bottom_supported: bool,
 }
 
 impl TauriWindowAdapter {
@@ -26,6 +135,9 @@ impl TauriWindowAdapter {
             app_handle,
             mode_registry: WindowModeRegistry::new(),
             states: Arc::new(RwLock::new(HashMap::new())),
+            max_windows: None,
+            transparency_supported: detect_transparency_support(),
+            always_on_bottom_supported: detect_always_on_bottom_support(),
         }
     }
 
@@ -34,9 +146,51 @@ impl TauriWindowAdapter {
             app_handle,
             mode_registry,
             states: Arc::new(RwLock::new(HashMap::new())),
+            max_windows: None,
+            transparency_supported: detect_transparency_support(),
+            always_on_bottom_supported: detect_always_on_bottom_support(),
         }
     }
 
+    /// 限制除主窗口外最多同时打开的窗口数
+    pub fn with_max_windows(mut self, max_windows: usize) -> Self {
+        self.max_windows = Some(max_windows);
+        self
+    }
+
+    /// 创建 Tauri 窗口
+    fn build_window(
+        &self,
+        config: &WindowConfig,
+        effective_config: &WindowConfig,
+        transparent: bool,
+    ) -> tauri::Result<WebviewWindow> {
+        let builder = tauri::WebviewWindowBuilder::new(
+            &self.app_handle,
+            config.label.as_str(),
+            tauri::WebviewUrl::App("index.html".into()),
+        )
+        .title(&config.title)
+        .inner_size(
+            effective_config.size.width as f64,
+            effective_config.size.height as f64,
+        )
+        .decorations(effective_config.decorations)
+        .always_on_top(effective_config.always_on_top)
+        .always_on_bottom(effective_config.always_on_bottom)
+        .resizable(effective_config.resizable)
+        .skip_taskbar(effective_config.skip_taskbar)
+        .center();
+
+        // macOS 上的透明窗口依赖 macos-private-api 特性
+        #[cfg(not(target_os = "macos"))]
+        let builder = builder.transparent(transparent);
+        #[cfg(target_os = "macos")]
+        let _ = transparent;
+
+        builder.build()
+    }
+
     /// 获取 Tauri 窗口句柄
     fn get_window(&self, label: &WindowLabel) -> Result<WebviewWindow, WindowError> {
         self.app_handle
@@ -50,6 +204,7 @@ impl TauriWindowAdapter {
         window: &WebviewWindow,
         label: WindowLabel,
         mode: WindowMode,
+        is_transparent: bool,
     ) -> Result<WindowState, WindowError> {
         let size = window
             .outer_size()
@@ -79,10 +234,29 @@ impl TauriWindowAdapter {
             is_maximized,
             current_size: WindowSize::new(size.width, size.height),
             current_position: WindowPosition::new(position.x, position.y),
+            is_transparent,
         };
 
         Ok(state)
     }
+
+    /// 将 Tauri 显示器转换为领域模型
+    fn to_monitor_info(index: usize, monitor: &tauri::Monitor, is_primary: bool) -> MonitorInfo {
+        let position = monitor.position();
+        let size = monitor.size();
+        let work_area = monitor.work_area();
+
+        MonitorInfo {
+            index,
+            name: monitor.name().cloned(),
+            position: WindowPosition::new(position.x, position.y),
+            size: WindowSize::new(size.width, size.height),
+            work_area_position: WindowPosition::new(work_area.position.x, work_area.position.y),
+            work_area_size: WindowSize::new(work_area.size.width, work_area.size.height),
+            scale_factor: monitor.scale_factor(),
+            is_primary,
+        }
+    }
 }
 
 #[async_trait]
@@ -97,29 +271,43 @@ impl WindowPort for TauriWindowAdapter {
             return Err(WindowError::AlreadyExists(config.label.to_string()));
         }
 
+        // 检查窗口数量上限
+        if let Some(max) = self.max_windows {
+            let open_windows = self.app_handle.webview_windows();
+            check_window_limit(&config.label, open_windows.keys().map(String::as_str), max)?;
+        }
+
         // 获取模式配置
         let mut effective_config = config.clone();
         let mode = effective_config.mode;
         self.mode_registry.apply_mode(&mut effective_config, mode)?;
+        check_always_on_bottom(&effective_config, self.always_on_bottom_supported)?;
+
+        // 创建新窗口，不支持透明时退回不透明窗口
+        let mut transparency =
+            Transparency::decide(effective_config.transparent, self.transparency_supported);
+        if transparency == Transparency::Unsupported {
+            tracing::warn!(
+                "[Window] Transparency unsupported, creating {} as an opaque window",
+                config.label
+            );
+        }
 
-        // 创建新窗口
-        let window = tauri::WebviewWindowBuilder::new(
-            &self.app_handle,
-            config.label.as_str(),
-            tauri::WebviewUrl::App("index.html".into()),
-        )
-        .title(&config.title)
-        .inner_size(
-            effective_config.size.width as f64,
-            effective_config.size.height as f64,
-        )
-        .decorations(effective_config.decorations)
-        .always_on_top(effective_config.always_on_top)
-        .resizable(effective_config.resizable)
-        .skip_taskbar(effective_config.skip_taskbar)
-        .center()
-        .build()
-        .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+        let window = match self.build_window(&config, &effective_config, transparency.is_enabled())
+        {
+            Ok(window) => window,
+            Err(e) if transparency.is_enabled() => {
+                tracing::warn!(
+                    "[Window] Failed to create transparent window {}, retrying opaque: {}",
+                    config.label,
+                    e
+                );
+                transparency = Transparency::Unsupported;
+                self.build_window(&config, &effective_config, false)
+                    .map_err(|e| WindowError::OperationFailed(e.to_string()))?
+            }
+            Err(e) => return Err(WindowError::OperationFailed(e.to_string())),
+        };
 
         // 如果指定了位置，设置窗口位置
         if let Some(position) = config.position {
@@ -132,7 +320,12 @@ impl WindowPort for TauriWindowAdapter {
         }
 
         let state = self
-            .create_state_from_window(&window, config.label.clone(), effective_config.mode)
+            .create_state_from_window(
+                &window,
+                config.label.clone(),
+                effective_config.mode,
+                transparency.is_enabled(),
+            )
             .await?;
 
         let mut states = self.states.write().await;
@@ -159,8 +352,8 @@ impl WindowPort for TauriWindowAdapter {
         let window = self.get_window(label)?;
 
         // 获取模式配置
-        let mut config = WindowConfig::main_window();
-        self.mode_registry.apply_mode(&mut config, mode)?;
+        let config = self.mode_registry.config_for(label.clone(), mode)?;
+        check_always_on_bottom(&config, self.always_on_bottom_supported)?;
 
         // 应用窗口设置
         window
@@ -171,6 +364,10 @@ impl WindowPort for TauriWindowAdapter {
             .set_always_on_top(config.always_on_top)
             .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
 
+        window
+            .set_always_on_bottom(config.always_on_bottom)
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+
         window
             .set_size(tauri::Size::Physical(tauri::PhysicalSize {
                 width: config.size.width,
@@ -185,12 +382,15 @@ impl WindowPort for TauriWindowAdapter {
                 .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
         }
 
-        // 更新状态
+        // 更新状态（透明背景只能在创建时指定，保持不变）
+        let mut states = self.states.write().await;
+        let is_transparent = states
+            .get(label.as_str())
+            .is_some_and(|state| state.is_transparent);
         let state = self
-            .create_state_from_window(&window, label.clone(), mode)
+            .create_state_from_window(&window, label.clone(), mode, is_transparent)
             .await?;
 
-        let mut states = self.states.write().await;
         states.insert(label.to_string(), state.clone());
 
         Ok(state)
@@ -246,6 +446,14 @@ impl WindowPort for TauriWindowAdapter {
         Ok(())
     }
 
+    async fn set_title(&self, label: &WindowLabel, title: &str) -> Result<(), WindowError> {
+        let window = self.get_window(label)?;
+        window
+            .set_title(title)
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+        Ok(())
+    }
+
     async fn show(&self, label: &WindowLabel) -> Result<(), WindowError> {
         let window = self.get_window(label)?;
         window
@@ -321,4 +529,136 @@ impl WindowPort for TauriWindowAdapter {
             .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
         Ok(())
     }
+
+    async fn list_monitors(&self) -> Result<Vec<MonitorInfo>, WindowError> {
+        let monitors = self
+            .app_handle
+            .available_monitors()
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+        let primary = self
+            .app_handle
+            .primary_monitor()
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+
+        Ok(monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| {
+                let is_primary = primary.as_ref().is_some_and(|p| {
+                    p.position() == monitor.position() && p.name() == monitor.name()
+                });
+                Self::to_monitor_info(index, monitor, is_primary)
+            })
+            .collect())
+    }
+
+    async fn move_to_monitor(
+        &self,
+        label: &WindowLabel,
+        monitor_index: usize,
+    ) -> Result<WindowPosition, WindowError> {
+        let window = self.get_window(label)?;
+        let monitors = self.list_monitors().await?;
+
+        let target = monitors.get(monitor_index).ok_or_else(|| {
+            WindowError::InvalidConfig(format!("Monitor index out of range: {}", monitor_index))
+        })?;
+
+        let current = window
+            .current_monitor()
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?
+            .map(|m| Self::to_monitor_info(0, &m, false))
+            .unwrap_or_else(|| target.clone());
+
+        let size = window
+            .outer_size()
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+        let position = window
+            .outer_position()
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+
+        let new_position = MonitorInfo::translate_position(
+            WindowPosition::new(position.x, position.y),
+            WindowSize::new(size.width, size.height),
+            &current,
+            target,
+        );
+
+        window
+            .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+                x: new_position.x,
+                y: new_position.y,
+            }))
+            .map_err(|e| WindowError::OperationFailed(e.to_string()))?;
+
+        let mut states = self.states.write().await;
+        if let Some(state) = states.get_mut(label.as_str()) {
+            state.current_position = new_position;
+        }
+
+        Ok(new_position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_limit() {
+        let mut open = vec!["main".to_string()];
+
+        for i in 0..3 {
+            let label = WindowLabel::new(format!("chat-{}", i));
+            check_window_limit(&label, open.iter().map(String::as_str), 3).unwrap();
+            open.push(label.to_string());
+        }
+
+        let result = check_window_limit(
+            &WindowLabel::new("chat-3"),
+            open.iter().map(String::as_str),
+            3,
+        );
+        assert!(matches!(result, Err(WindowError::LimitExceeded { max: 3 })));
+
+        // 主窗口始终不受限制
+        check_window_limit(&WindowLabel::main(), open.iter().map(String::as_str), 3).unwrap();
+    }
+
+    #[test]
+    fn test_transparency_fallback_decision() {
+        assert_eq!(Transparency::decide(true, true), Transparency::Enabled);
+        assert_eq!(Transparency::decide(false, true), Transparency::Disabled);
+        assert_eq!(Transparency::decide(false, false), Transparency::Disabled);
+
+        // 不支持透明时退回不透明窗口，而不是创建失败
+        let transparency = Transparency::decide(true, false);
+        assert_eq!(transparency, Transparency::Unsupported);
+        assert!(!transparency.is_enabled());
+    }
+
+    #[test]
+    fn test_transparency_override_takes_precedence() {
+        assert!(transparency_supported(None, || true));
+        assert!(!transparency_supported(None, || false));
+
+        // 环境变量覆盖合成器检测结果
+        assert!(!transparency_supported(Some("1"), || true));
+        assert!(transparency_supported(Some("0"), || false));
+        assert!(transparency_supported(Some("false"), || false));
+    }
+
+    #[test]
+    fn test_always_on_bottom_requires_platform_support() {
+        let mut config = WindowConfig::pet_mode();
+        config.apply_mode(WindowMode::Desktop);
+
+        check_always_on_bottom(&config, true).unwrap();
+        let result = check_always_on_bottom(&config, false);
+        assert!(matches!(result, Err(WindowError::PlatformNotSupported(_))));
+
+        // 其他模式不受影响
+        config.apply_mode(WindowMode::Pet);
+        check_always_on_bottom(&config, false).unwrap();
+    }
 }
@@ -15,21 +15,21 @@ pub mod ports;
 
 // Domain
 pub use domain::{
-    ModeSizeConfig, WindowClosedEvent, WindowConfig, WindowCreatedEvent, WindowFocusChangedEvent,
-    WindowLabel, WindowMode, WindowModeChangedEvent, WindowMovedEvent, WindowPosition,
-    WindowResizedEvent, WindowSize, WindowState, WindowVisibilityChangedEvent,
+    DragGuard, ModeSizeConfig, MonitorInfo, WindowClosedEvent, WindowConfig, WindowCreatedEvent,
+    WindowFocusChangedEvent, WindowLabel, WindowMode, WindowModeChangedEvent, WindowMovedEvent,
+    WindowPosition, WindowResizedEvent, WindowSize, WindowState, WindowVisibilityChangedEvent,
 };
 
 // Ports
 pub use ports::{
-    CompactModeStrategy, NormalModeStrategy, PetModeStrategy, WindowError, WindowModeRegistry,
-    WindowModeStrategy, WindowPort,
+    CompactModeStrategy, DesktopModeStrategy, NormalModeStrategy, PetModeStrategy, WindowError,
+    WindowModeRegistry, WindowModeStrategy, WindowPort,
 };
 
 // Infrastructure
 pub use infrastructure::TauriWindowAdapter;
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tauri::AppHandle;
 
 /// Window 模块容器
@@ -38,6 +38,7 @@ use tauri::AppHandle;
 pub struct WindowModule {
     adapter: Arc<dyn WindowPort>,
     mode_registry: WindowModeRegistry,
+    drag_guard: RwLock<DragGuard>,
 }
 
 impl WindowModule {
@@ -49,6 +50,7 @@ impl WindowModule {
         Self {
             adapter,
             mode_registry,
+            drag_guard: RwLock::new(DragGuard::default()),
         }
     }
 
@@ -57,6 +59,7 @@ impl WindowModule {
         Self {
             adapter,
             mode_registry: WindowModeRegistry::new(),
+            drag_guard: RwLock::new(DragGuard::default()),
         }
     }
 
@@ -109,6 +112,13 @@ impl WindowModule {
             .await
     }
 
+    /// 切换到固定在桌面模式
+    pub async fn switch_to_desktop_mode(&self) -> Result<WindowState, WindowError> {
+        self.adapter
+            .switch_mode(&WindowLabel::main(), WindowMode::Desktop)
+            .await
+    }
+
     /// 切换到普通模式
     pub async fn switch_to_normal_mode(&self) -> Result<WindowState, WindowError> {
         self.adapter
@@ -125,9 +135,49 @@ impl WindowModule {
         self.adapter.set_always_on_top(label, always_on_top).await
     }
 
+    /// 修改窗口标题
+    pub async fn set_title(&self, label: &WindowLabel, title: &str) -> Result<(), WindowError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(WindowError::InvalidConfig(
+                "Window title cannot be empty".to_string(),
+            ));
+        }
+        self.adapter.set_title(label, title).await
+    }
+
     /// 开始拖拽
-    pub async fn start_dragging(&self, label: &WindowLabel) -> Result<(), WindowError> {
-        self.adapter.start_dragging(label).await
+    ///
+    /// 不满足拖拽守卫时不做任何操作并返回 false，可以在每次按下鼠标时重复调用
+    pub async fn start_dragging(&self, label: &WindowLabel) -> Result<bool, WindowError> {
+        let mode = self
+            .adapter
+            .get_state(label)
+            .await?
+            .map(|state| state.mode)
+            .unwrap_or_default();
+
+        if !self.drag_guard().allows(mode) {
+            return Ok(false);
+        }
+
+        self.adapter.start_dragging(label).await?;
+        Ok(true)
+    }
+
+    /// 获取当前拖拽守卫
+    pub fn drag_guard(&self) -> DragGuard {
+        *self.drag_guard.read().unwrap()
+    }
+
+    /// 锁定或解锁窗口位置
+    pub fn set_drag_locked(&self, locked: bool) {
+        self.drag_guard.write().unwrap().locked = locked;
+    }
+
+    /// 更新指针是否位于可拖拽区域
+    pub fn set_drag_region(&self, in_drag_region: bool) {
+        self.drag_guard.write().unwrap().in_drag_region = in_drag_region;
     }
 
     /// 显示窗口
@@ -149,11 +199,181 @@ impl WindowModule {
     pub async fn center(&self, label: &WindowLabel) -> Result<(), WindowError> {
         self.adapter.center(label).await
     }
+
+    /// 列出所有显示器
+    pub async fn list_monitors(&self) -> Result<Vec<MonitorInfo>, WindowError> {
+        self.adapter.list_monitors().await
+    }
+
+    /// 将窗口移动到指定显示器
+    pub async fn move_to_monitor(
+        &self,
+        label: &WindowLabel,
+        monitor_index: usize,
+    ) -> Result<WindowPosition, WindowError> {
+        self.adapter.move_to_monitor(label, monitor_index).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// 记录拖拽次数和标题修改的窗口端口，其余操作均为空操作
+    struct RecordingPort {
+        mode: WindowMode,
+        drags: AtomicUsize,
+        titles: Mutex<Vec<(String, String)>>,
+    }
+
+    impl RecordingPort {
+        fn new(mode: WindowMode) -> Arc<Self> {
+            Arc::new(Self {
+                mode,
+                drags: AtomicUsize::new(0),
+                titles: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl WindowPort for RecordingPort {
+        async fn create(&self, config: WindowConfig) -> Result<WindowState, WindowError> {
+            Ok(WindowState::new(config.label))
+        }
+        async fn get_state(&self, label: &WindowLabel) -> Result<Option<WindowState>, WindowError> {
+            Ok(Some(WindowState {
+                mode: self.mode,
+                ..WindowState::new(label.clone())
+            }))
+        }
+        async fn list_windows(&self) -> Result<Vec<WindowState>, WindowError> {
+            Ok(Vec::new())
+        }
+        async fn switch_mode(
+            &self,
+            label: &WindowLabel,
+            mode: WindowMode,
+        ) -> Result<WindowState, WindowError> {
+            Ok(WindowState {
+                mode,
+                ..WindowState::new(label.clone())
+            })
+        }
+        async fn set_size(&self, _: &WindowLabel, _: WindowSize) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn set_position(
+            &self,
+            _: &WindowLabel,
+            _: WindowPosition,
+        ) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn set_always_on_top(&self, _: &WindowLabel, _: bool) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn set_decorations(&self, _: &WindowLabel, _: bool) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn set_title(&self, label: &WindowLabel, title: &str) -> Result<(), WindowError> {
+            self.titles
+                .lock()
+                .unwrap()
+                .push((label.to_string(), title.to_string()));
+            Ok(())
+        }
+        async fn show(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn hide(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn close(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn minimize(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn maximize(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn unmaximize(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn center(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn start_dragging(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            self.drags.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        async fn set_focus(&self, _: &WindowLabel) -> Result<(), WindowError> {
+            Ok(())
+        }
+        async fn list_monitors(&self) -> Result<Vec<MonitorInfo>, WindowError> {
+            Ok(Vec::new())
+        }
+        async fn move_to_monitor(
+            &self,
+            _: &WindowLabel,
+            _: usize,
+        ) -> Result<WindowPosition, WindowError> {
+            Ok(WindowPosition::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_dragging_is_noop_when_locked() {
+        let port = RecordingPort::new(WindowMode::Normal);
+        let module = WindowModule::with_adapter(port.clone());
+        let label = WindowLabel::main();
+
+        module.set_drag_locked(true);
+        assert!(!module.start_dragging(&label).await.unwrap());
+        assert!(!module.start_dragging(&label).await.unwrap());
+        assert_eq!(port.drags.load(Ordering::SeqCst), 0);
+
+        module.set_drag_locked(false);
+        assert!(module.start_dragging(&label).await.unwrap());
+        assert!(module.start_dragging(&label).await.unwrap());
+        assert_eq!(port.drags.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pet_mode_drags_only_from_drag_region() {
+        let port = RecordingPort::new(WindowMode::Pet);
+        let module = WindowModule::with_adapter(port.clone());
+        let label = WindowLabel::main();
+
+        assert!(!module.start_dragging(&label).await.unwrap());
+
+        module.set_drag_region(true);
+        assert!(module.start_dragging(&label).await.unwrap());
+        assert_eq!(port.drags.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_title_forwards_to_port() {
+        let port = RecordingPort::new(WindowMode::Normal);
+        let module = WindowModule::with_adapter(port.clone());
+        let label = WindowLabel::new("chat-1");
+
+        module.set_title(&label, "  Session A ").await.unwrap();
+        assert!(matches!(
+            module.set_title(&label, "   ").await,
+            Err(WindowError::InvalidConfig(_))
+        ));
+
+        let titles = port.titles.lock().unwrap();
+        assert_eq!(
+            *titles,
+            vec![("chat-1".to_string(), "Session A".to_string())]
+        );
+    }
 
     #[test]
     fn test_window_mode_registry() {
@@ -166,5 +386,69 @@ mod tests {
         let pet = registry.get(WindowMode::Pet);
         assert!(pet.is_some());
         assert!(pet.unwrap().requires_transparent());
+
+        let desktop = registry.get(WindowMode::Desktop);
+        assert!(desktop.is_some());
+        assert!(desktop.unwrap().requires_transparent());
+
+        let config = registry
+            .config_for(WindowLabel::main(), WindowMode::Desktop)
+            .unwrap();
+        assert!(config.always_on_bottom);
+        assert!(!config.always_on_top);
+    }
+
+    #[test]
+    fn test_config_for_starts_from_mode_defaults() {
+        // 只设置尺寸的策略，其余字段应来自该模式自身的默认配置
+        struct SizeOnlyStrategy(WindowMode);
+
+        impl WindowModeStrategy for SizeOnlyStrategy {
+            fn mode(&self) -> WindowMode {
+                self.0
+            }
+            fn apply(&self, config: &mut WindowConfig) {
+                config.size = WindowSize::new(100, 100);
+            }
+            fn default_size(&self) -> WindowSize {
+                WindowSize::new(100, 100)
+            }
+            fn requires_transparent(&self) -> bool {
+                false
+            }
+        }
+
+        let mut registry = WindowModeRegistry::new();
+        registry.register(Box::new(SizeOnlyStrategy(WindowMode::Pet)));
+        let config = registry
+            .config_for(WindowLabel::new("pet"), WindowMode::Pet)
+            .unwrap();
+
+        assert_eq!(config.label, WindowLabel::new("pet"));
+        assert_eq!(config.mode, WindowMode::Pet);
+        assert!(config.transparent);
+        assert!(config.skip_taskbar);
+        assert!(!config.decorations);
+    }
+
+    #[test]
+    fn test_switch_applies_custom_mode_size() {
+        let sizes = ModeSizeConfig {
+            pet: WindowSize::new(240, 360),
+            ..Default::default()
+        };
+        let registry = WindowModeRegistry::with_sizes(&sizes);
+
+        let config = registry
+            .config_for(WindowLabel::main(), WindowMode::Pet)
+            .unwrap();
+        assert_eq!(config.size, WindowSize::new(240, 360));
+        assert!(config.transparent);
+
+        // 未自定义的模式保持默认尺寸
+        let config = registry
+            .config_for(WindowLabel::main(), WindowMode::Compact)
+            .unwrap();
+        assert_eq!(config.size, ModeSizeConfig::default().compact);
     }
 }
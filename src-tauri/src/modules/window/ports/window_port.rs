@@ -6,7 +6,8 @@ use async_trait::async_trait;
 use thiserror::Error;
 
 use crate::modules::window::domain::{
-    WindowConfig, WindowLabel, WindowMode, WindowPosition, WindowSize, WindowState,
+    ModeSizeConfig, MonitorInfo, WindowConfig, WindowLabel, WindowMode, WindowPosition, WindowSize,
+    WindowState,
 };
 
 /// 窗口错误类型
@@ -26,6 +27,9 @@ pub enum WindowError {
 
     #[error("Window already exists: {0}")]
     AlreadyExists(String),
+
+    #[error("Too many windows: at most {max} windows besides the main window")]
+    LimitExceeded { max: usize },
 }
 
 /// 窗口管理端口
@@ -71,6 +75,9 @@ pub trait WindowPort: Send + Sync {
         decorations: bool,
     ) -> Result<(), WindowError>;
 
+    /// 设置窗口标题
+    async fn set_title(&self, label: &WindowLabel, title: &str) -> Result<(), WindowError>;
+
     /// 显示窗口
     async fn show(&self, label: &WindowLabel) -> Result<(), WindowError>;
 
@@ -97,6 +104,16 @@ pub trait WindowPort: Send + Sync {
 
     /// 设置窗口焦点
     async fn set_focus(&self, label: &WindowLabel) -> Result<(), WindowError>;
+
+    /// 列出所有显示器
+    async fn list_monitors(&self) -> Result<Vec<MonitorInfo>, WindowError>;
+
+    /// 将窗口移动到指定显示器（保持在工作区内的相对位置）
+    async fn move_to_monitor(
+        &self,
+        label: &WindowLabel,
+        monitor_index: usize,
+    ) -> Result<WindowPosition, WindowError>;
 }
 
 /// 窗口模式策略 trait
@@ -147,6 +164,7 @@ impl WindowModeStrategy for NormalModeStrategy {
         config.size = self.size;
         config.decorations = true;
         config.always_on_top = false;
+        config.always_on_bottom = false;
         config.transparent = false;
         config.skip_taskbar = false;
         config.resizable = true;
@@ -194,6 +212,57 @@ impl WindowModeStrategy for PetModeStrategy {
         config.size = self.size;
         config.decorations = false;
         config.always_on_top = true;
+        config.always_on_bottom = false;
+        config.transparent = true;
+        config.skip_taskbar = true;
+        config.resizable = false;
+    }
+
+    fn default_size(&self) -> WindowSize {
+        self.size
+    }
+
+    fn requires_transparent(&self) -> bool {
+        true
+    }
+}
+
+/// 固定在桌面模式策略
+///
+/// 与桌面宠物模式外观相同，但窗口始终位于其他窗口之下
+pub struct DesktopModeStrategy {
+    size: WindowSize,
+}
+
+impl DesktopModeStrategy {
+    pub fn new() -> Self {
+        Self {
+            size: WindowSize::new(300, 400),
+        }
+    }
+
+    pub fn with_size(size: WindowSize) -> Self {
+        Self { size }
+    }
+}
+
+impl Default for DesktopModeStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowModeStrategy for DesktopModeStrategy {
+    fn mode(&self) -> WindowMode {
+        WindowMode::Desktop
+    }
+
+    fn apply(&self, config: &mut WindowConfig) {
+        config.mode = WindowMode::Desktop;
+        config.size = self.size;
+        config.decorations = false;
+        config.always_on_top = false;
+        config.always_on_bottom = true;
         config.transparent = true;
         config.skip_taskbar = true;
         config.resizable = false;
@@ -241,6 +310,7 @@ impl WindowModeStrategy for CompactModeStrategy {
         config.size = self.size;
         config.decorations = false;
         config.always_on_top = true;
+        config.always_on_bottom = false;
         config.transparent = false;
         config.skip_taskbar = false;
         config.resizable = true;
@@ -270,6 +340,21 @@ impl WindowModeRegistry {
         registry.register(Box::new(NormalModeStrategy::new()));
         registry.register(Box::new(PetModeStrategy::new()));
         registry.register(Box::new(CompactModeStrategy::new()));
+        registry.register(Box::new(DesktopModeStrategy::new()));
+
+        registry
+    }
+
+    /// 使用各模式的自定义尺寸创建注册表
+    pub fn with_sizes(sizes: &ModeSizeConfig) -> Self {
+        let mut registry = Self {
+            strategies: std::collections::HashMap::new(),
+        };
+
+        registry.register(Box::new(NormalModeStrategy::with_size(sizes.normal)));
+        registry.register(Box::new(PetModeStrategy::with_size(sizes.pet)));
+        registry.register(Box::new(CompactModeStrategy::with_size(sizes.compact)));
+        registry.register(Box::new(DesktopModeStrategy::with_size(sizes.pet)));
 
         registry
     }
@@ -295,6 +380,20 @@ impl WindowModeRegistry {
         strategy.apply(config);
         Ok(())
     }
+
+    /// 生成窗口切换到指定模式后的配置
+    pub fn config_for(
+        &self,
+        label: WindowLabel,
+        mode: WindowMode,
+    ) -> Result<WindowConfig, WindowError> {
+        let mut config = WindowConfig {
+            label,
+            ..WindowConfig::for_mode(mode)
+        };
+        self.apply_mode(&mut config, mode)?;
+        Ok(config)
+    }
 }
 
 impl Default for WindowModeRegistry {
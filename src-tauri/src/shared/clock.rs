@@ -0,0 +1,21 @@
+// Clock - 时钟抽象
+//
+// 依赖当前时间的判断通过注入的 Clock 读取时间，保持为纯函数，测试时可固定时间
+
+use chrono::{Local, NaiveDateTime};
+
+/// 时钟
+pub trait Clock: Send + Sync {
+    /// 当前本地时间
+    fn now_local(&self) -> NaiveDateTime;
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_local(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+}
@@ -0,0 +1,119 @@
+//! 代码块追踪 - 跨流式分块追踪 Markdown 围栏代码块状态
+//!
+//! 流式分块会在任意位置切断代码围栏，这里按行累积内容，
+//! 为每个分块给出"分块结束时是否处于代码块内"的渲染提示
+
+/// 围栏代码块状态追踪器
+#[derive(Debug, Default)]
+pub struct CodeFenceTracker {
+    /// 当前打开的围栏（围栏字符、长度）
+    open_fence: Option<(char, usize)>,
+    /// 尚未遇到换行的当前行
+    pending_line: String,
+}
+
+impl CodeFenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一个分块，返回分块结束时是否处于代码块内
+    ///
+    /// 未结束的当前行若已构成围栏，也会计入状态，
+    /// 这样以围栏结尾（未跟换行）的分块能立即得到正确的结果
+    pub fn feed(&mut self, chunk: &str) -> bool {
+        for ch in chunk.chars() {
+            if ch == '\n' {
+                let line = std::mem::take(&mut self.pending_line);
+                self.open_fence = next_state(self.open_fence, &line);
+            } else {
+                self.pending_line.push(ch);
+            }
+        }
+
+        next_state(self.open_fence, &self.pending_line).is_some()
+    }
+
+    /// 当前是否处于代码块内（不含未结束的当前行）
+    pub fn in_code_block(&self) -> bool {
+        self.open_fence.is_some()
+    }
+}
+
+/// 根据一行内容计算新的围栏状态
+fn next_state(open_fence: Option<(char, usize)>, line: &str) -> Option<(char, usize)> {
+    match (open_fence, parse_fence(line)) {
+        // 关闭围栏：同一字符、长度不短于开启围栏，且后面不能有信息字符串
+        (Some((open_char, open_len)), Some((fence_char, fence_len, info)))
+            if fence_char == open_char && fence_len >= open_len && info.is_empty() =>
+        {
+            None
+        }
+        (Some(open), _) => Some(open),
+        (None, Some((fence_char, fence_len, _))) => Some((fence_char, fence_len)),
+        (None, None) => None,
+    }
+}
+
+/// 解析围栏行，返回（围栏字符、长度、信息字符串）
+fn parse_fence(line: &str) -> Option<(char, usize, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+
+    let rest = &line[indent..];
+    let fence_char = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence_len = rest.chars().take_while(|c| *c == fence_char).count();
+    if fence_len < 3 {
+        return None;
+    }
+
+    let info = rest[fence_len..].trim();
+    // 反引号围栏的信息字符串中不能再出现反引号
+    if fence_char == '`' && info.contains('`') {
+        return None;
+    }
+    Some((fence_char, fence_len, info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_block_split_across_chunks() {
+        let mut tracker = CodeFenceTracker::new();
+        let chunks = [
+            ("Here is code:\n`", false),
+            ("``rust\nfn main", true),
+            ("() {}\n", true),
+            ("``", true),
+            ("`\nDone.", false),
+        ];
+
+        for (chunk, expected) in chunks {
+            assert_eq!(tracker.feed(chunk), expected, "chunk {:?}", chunk);
+        }
+        assert!(!tracker.in_code_block());
+    }
+
+    #[test]
+    fn test_closing_fence_without_trailing_newline() {
+        let mut tracker = CodeFenceTracker::new();
+
+        assert!(tracker.feed("~~~~\nlet x = 1;\n"));
+        // 较短或不同字符的围栏不能关闭代码块
+        assert!(tracker.feed("~~~\n```\n"));
+        assert!(!tracker.feed("~~~~"));
+    }
+
+    #[test]
+    fn test_inline_code_is_not_a_fence() {
+        let mut tracker = CodeFenceTracker::new();
+
+        assert!(!tracker.feed("Use `x` or ``y``.\n"));
+        assert!(!tracker.feed("```not`a fence\n"));
+        assert!(!tracker.feed("    ```\n"));
+    }
+}
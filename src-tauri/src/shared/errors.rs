@@ -26,6 +26,12 @@ pub enum AppError {
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Invalid attachment: {0}")]
+    InvalidAttachment(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -1,7 +1,12 @@
+pub mod clock;
+pub mod code_fence;
 pub mod errors;
 pub mod lip_sync;
+pub mod timestamp;
 pub mod types;
 
+pub use clock::*;
+pub use code_fence::*;
 pub use errors::*;
 pub use lip_sync::*;
 pub use types::*;
@@ -0,0 +1,82 @@
+// Timestamp - 时间戳序列化
+//
+// 统一以 RFC3339 UTC（`Z` 结尾）格式存储和传输时间戳。
+// 反序列化时兼容带其他时区偏移或不带时区的旧数据，统一转换为 UTC，
+// 旧数据在下次保存时即被规范化
+
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// 不带时区的旧格式（视为 UTC）
+const NAIVE_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// 格式化为 RFC3339 UTC 字符串
+///
+/// 保留原始精度，保证保存后再读取得到相同的时间
+pub fn format(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// 解析时间戳并转换为 UTC
+pub fn parse(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+
+    NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+pub fn serialize<S: Serializer>(
+    timestamp: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format(timestamp))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse(&value).ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp: {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde::Serialize;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Record {
+        #[serde(with = "super")]
+        at: DateTime<Utc>,
+    }
+
+    #[test]
+    fn test_serializes_as_rfc3339_utc() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let json = serde_json::to_string(&Record { at }).unwrap();
+
+        assert_eq!(json, r#"{"at":"2024-01-02T03:04:05Z"}"#);
+    }
+
+    #[test]
+    fn test_round_trip_keeps_precision() {
+        let at = Utc::now();
+        let json = serde_json::to_string(&Record { at }).unwrap();
+        let record: Record = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(record.at, at);
+    }
+
+    #[test]
+    fn test_normalizes_offsets_and_naive_values() {
+        let expected = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        assert_eq!(parse("2024-01-02T11:04:05+08:00"), Some(expected));
+        assert_eq!(parse("2024-01-02T03:04:05"), Some(expected));
+        assert_eq!(parse("2024-01-02 03:04:05"), Some(expected));
+        assert_eq!(parse("yesterday"), None);
+    }
+}
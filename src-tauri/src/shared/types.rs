@@ -8,8 +8,13 @@ pub struct Session {
     pub id: Uuid,
     pub title: String,
     pub preset_id: Option<Uuid>,
+    /// 界面上显示的助手名称
+    #[serde(default)]
+    pub display_name: Option<String>,
     pub model_config: Option<serde_json::Value>,
+    #[serde(with = "crate::shared::timestamp")]
     pub created_at: DateTime<Utc>,
+    #[serde(with = "crate::shared::timestamp")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -20,6 +25,7 @@ impl Session {
             id: Uuid::new_v4(),
             title: title.unwrap_or_else(|| "新对话".to_string()),
             preset_id,
+            display_name: None,
             model_config: None,
             created_at: now,
             updated_at: now,
@@ -33,9 +39,11 @@ pub enum MessageRole {
     User,
     Assistant,
     System,
+    #[serde(rename = "context_break")]
+    ContextBreak,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Emotion {
     Neutral,
@@ -46,6 +54,16 @@ pub enum Emotion {
     Thinking,
 }
 
+/// 助手回复的结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ContentFilter,
+    FunctionCall,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Message {
@@ -55,7 +73,11 @@ pub struct Message {
     pub content: String,
     pub tokens: Option<u32>,
     pub emotion: Option<Emotion>,
+    #[serde(with = "crate::shared::timestamp")]
     pub created_at: DateTime<Utc>,
+    /// 为 None 表示不适用（用户消息）或旧数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
 }
 
 impl Message {
@@ -68,6 +90,7 @@ impl Message {
             tokens: None,
             emotion: None,
             created_at: Utc::now(),
+            finish_reason: None,
         }
     }
 
@@ -80,6 +103,7 @@ impl Message {
             tokens: None,
             emotion,
             created_at: Utc::now(),
+            finish_reason: None,
         }
     }
 }
@@ -93,6 +117,9 @@ pub struct MessageChunk {
     /// 口型音素序列 (A/E/I/O/U/N/closed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub phonemes: Option<Vec<String>>,
+    /// 分块结束时是否处于围栏代码块内，前端可据此推迟语法高亮
+    #[serde(default)]
+    pub in_code_block: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +129,7 @@ pub enum WindowMode {
     Pet,
     Compact,
     Fullscreen,
+    Desktop,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,6 +142,17 @@ pub struct Preset {
     pub model_path: String,
     pub default_expression: String,
     pub emotion_mapping: serde_json::Value,
+    /// 助手回复的开头（如角色名），用于续写式提示
+    #[serde(default)]
+    pub assistant_prefix: Option<String>,
+    /// 开场白，清空会话消息后作为第一条助手消息重新插入
+    #[serde(default)]
+    pub greeting: Option<String>,
+    /// 角色扮演中用户的名字，设置后发送消息时启用角色扮演模式：
+    /// 默认以用户发言标签作为停止序列，并截掉模型越界生成的用户发言
+    #[serde(default)]
+    pub user_name: Option<String>,
+    #[serde(with = "crate::shared::timestamp")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -128,7 +167,33 @@ impl Preset {
             model_path: String::new(),
             default_expression: "neutral".to_string(),
             emotion_mapping: serde_json::json!({}),
+            assistant_prefix: None,
+            greeting: None,
+            user_name: None,
             created_at: Utc::now(),
         }
     }
+
+    /// 校验导入的预设：名称和系统提示词不能为空，模型类型必须可识别
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.system_prompt.trim().is_empty() {
+            return Err(format!(
+                "Preset \"{}\" has an empty system prompt",
+                self.name
+            ));
+        }
+        if !PRESET_MODEL_TYPES.contains(&self.model_type.as_str()) {
+            return Err(format!(
+                "Preset \"{}\" has unknown model type \"{}\"",
+                self.name, self.model_type
+            ));
+        }
+        Ok(())
+    }
 }
+
+/// 预设可用的模型类型，与前端 `ModelType` 保持一致
+pub const PRESET_MODEL_TYPES: [&str; 5] = ["live2d", "vrm", "gltf", "fbx", "mmd"];
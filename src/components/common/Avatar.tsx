@@ -1,7 +1,7 @@
 import React from "react";
 
 interface AvatarProps {
-  role: "user" | "assistant" | "system";
+  role: "user" | "assistant" | "system" | "context_break";
   src?: string;
   alt?: string;
   className?: string;
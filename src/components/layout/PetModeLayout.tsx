@@ -2,9 +2,16 @@ import React from "react";
 import { ModelViewer } from "@/components/model/ModelViewer";
 import { windowService } from "@/services";
 
+/** 指针是否落在角色画布上（透明留白处不允许拖拽） */
+const isOverCharacter = (event: React.MouseEvent): boolean => {
+  const target = document.elementFromPoint(event.clientX, event.clientY);
+  return target instanceof HTMLCanvasElement;
+};
+
 export const PetModeLayout: React.FC = () => {
-  const handleMouseDown = async () => {
+  const handleMouseDown = async (event: React.MouseEvent) => {
     try {
+      await windowService.setDragRegion(isOverCharacter(event));
       await windowService.startDragging();
     } catch (error) {
       console.error("Failed to start dragging:", error);
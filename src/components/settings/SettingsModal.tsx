@@ -35,12 +35,24 @@ const PROVIDER_TYPES: { id: ProviderType; name: string; description: string; def
     description: "Local LLM",
     defaultBaseUrl: "http://localhost:11434",
   },
+  { 
+    id: "gemini", 
+    name: "Gemini (Google)", 
+    description: "Gemini 2.5, Gemini 2.0",
+    defaultBaseUrl: "https://generativelanguage.googleapis.com/v1beta",
+  },
   { 
     id: "custom", 
     name: "Custom (OpenAI Compatible)", 
     description: "OpenAI API Compatible",
     defaultBaseUrl: "",
   },
+  { 
+    id: "demo", 
+    name: "Demo", 
+    description: "Offline scripted replies",
+    defaultBaseUrl: "",
+  },
 ];
 
 // 预设模型列表
@@ -48,7 +60,9 @@ const PRESET_MODELS: Record<ProviderType, string[]> = {
   openai: ["gpt-4o", "gpt-4o-mini", "gpt-4-turbo", "gpt-4", "gpt-3.5-turbo"],
   claude: ["claude-sonnet-4-20250514", "claude-3-5-sonnet-20241022", "claude-3-opus-20240229", "claude-3-haiku-20240307"],
   ollama: ["llama3.3", "qwen2.5:32b", "qwen2.5:14b", "qwen2.5-coder", "deepseek-r1", "gemma2"],
+  gemini: ["gemini-2.5-pro", "gemini-2.5-flash", "gemini-2.0-flash"],
   custom: [],
+  demo: ["demo"],
 };
 
 export const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose }) => {
@@ -163,12 +177,12 @@ export const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose })
       providerType: provider.providerType 
     });
     
-    if (!provider.baseUrl) {
+    if (!provider.baseUrl && provider.providerType !== "demo") {
       console.log("[SettingsModal] No baseUrl, aborting");
       return;
     }
     
-    if (!provider.apiKey && provider.providerType !== "ollama") {
+    if (!provider.apiKey && provider.providerType !== "ollama" && provider.providerType !== "demo") {
       console.log("[SettingsModal] No apiKey and not ollama, aborting");
       return;
     }
@@ -536,6 +550,9 @@ export const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose })
                 {currentProvider.providerType === "custom" && (
                   <p>{t.settings.llm.customHelp}</p>
                 )}
+                {currentProvider.providerType === "demo" && (
+                  <p>{t.settings.llm.demoHelp}</p>
+                )}
               </div>
             </div>
           )}
@@ -91,6 +91,7 @@ export const enUS: Translation = {
       claudeHelp: "Anthropic API Key required for Claude API. Supports Claude 3.5 Sonnet, Claude 3 Opus, etc.",
       openaiHelp: "Supports GPT-4o, GPT-4 Turbo, GPT-3.5 Turbo, etc.",
       customHelp: "Supports any OpenAI API compatible service, such as Azure OpenAI, various proxy services, etc.",
+      demoHelp: "Demo mode replies with a scripted message offline. No API key or network connection is needed.",
     },
     model: {
       title: "Model Settings",
@@ -91,6 +91,7 @@ export const jaJP: Translation = {
       claudeHelp: "Claude APIを使用するにはAnthropic APIキーが必要です。Claude 3.5 Sonnet、Claude 3 Opusなどをサポートしています。",
       openaiHelp: "GPT-4o、GPT-4 Turbo、GPT-3.5 Turboなどをサポートしています。",
       customHelp: "Azure OpenAIや各種プロキシサービスなど、OpenAI API互換のサービスをサポートしています。",
+      demoHelp: "デモモードはオフラインで定型メッセージを返します。APIキーやネットワーク接続は不要です。",
     },
     model: {
       title: "モデル設定",
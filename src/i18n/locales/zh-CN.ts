@@ -91,6 +91,7 @@ export const zhCN: Translation = {
       claudeHelp: "使用 Claude API 需要 Anthropic API Key。支持 Claude 3.5 Sonnet、Claude 3 Opus 等模型。",
       openaiHelp: "支持 GPT-4o、GPT-4 Turbo、GPT-3.5 Turbo 等模型。",
       customHelp: "支持任何兼容 OpenAI API 格式的服务，如 Azure OpenAI、各种代理服务等。",
+      demoHelp: "演示模式离线回复预设的消息，不需要 API Key 和网络连接。",
     },
     model: {
       title: "模型设置",
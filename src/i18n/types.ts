@@ -89,6 +89,7 @@ export interface Translation {
       claudeHelp: string;
       openaiHelp: string;
       customHelp: string;
+      demoHelp: string;
     };
     model: {
       title: string;
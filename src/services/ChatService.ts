@@ -1,27 +1,191 @@
 import { commandBus, createSafeSubscriber } from "./ipc";
-import type { Message, MessageChunk, Emotion, ProviderConfig } from "@/types";
+import type { Message, MessageChunk, StreamFrame, Emotion, ProviderConfig } from "@/types";
 import { logger } from "@/utils/logger";
 
+export interface MessageStartEvent {
+  sessionId: string;
+  /** 本次生成的请求 ID，可用于 stopGeneration */
+  requestId: string;
+}
+
+export interface MessageCompleteEvent {
+  sessionId: string;
+  requestId: string;
+  messageId: string;
+  emotion?: Emotion;
+  /** 推理阶段耗时（毫秒） */
+  reasoningMs?: number | null;
+  /** 回答阶段耗时（毫秒） */
+  answerMs?: number | null;
+}
+
+export interface ContentFilteredEvent {
+  sessionId: string;
+  messageId: string;
+  /** 过滤后的完整内容，用于替换已流式显示的文本 */
+  content: string;
+  flaggedTerms: string[];
+}
+
+export interface RepetitionStoppedEvent {
+  sessionId: string;
+  messageId: string;
+  /** 末尾循环的片段 */
+  pattern: string;
+  repeats: number;
+}
+
+/** 用户消息的情感（启用用户情感分析时，在回复开始前发送） */
+export interface UserEmotionEvent {
+  sessionId: string;
+  emotion: Emotion;
+}
+
+/** 设置值的来源层，优先级从高到低 */
+export interface ProviderHealth {
+  providerId: string;
+  isHealthy: boolean;
+  latencyMs: number | null;
+  errorMessage: string | null;
+}
+
+/** 健康状态面板中的一行 */
+export interface ProviderStatus {
+  providerId: string;
+  name: string;
+  isHealthy: boolean;
+  latencyMs: number | null;
+  error: string | null;
+}
+
+export type SettingSource = "session" | "preset" | "global" | "provider" | "default";
+
+export interface ResolvedSetting<T> {
+  value: T;
+  source: SettingSource;
+}
+
+/** 会话合并各层后实际生效的设置 */
+export interface EffectiveSettings {
+  provider: ResolvedSetting<string | null>;
+  model: ResolvedSetting<string | null>;
+  /** 为 null 时由提供商决定 */
+  temperature: ResolvedSetting<number | null>;
+  systemPrompt: ResolvedSetting<string | null>;
+  contextLimit: ResolvedSetting<number>;
+}
+
+/** 消息搜索结果，highlightStart/highlightEnd 为匹配部分在 text 中的字符偏移 */
+export interface MessageSearchResult {
+  sessionId: string;
+  messageId: string;
+  role: Message["role"];
+  createdAt: string;
+  snippet: {
+    text: string;
+    highlightStart: number;
+    highlightEnd: number;
+  };
+}
+
+/** 采样参数，未指定的使用提供商默认值 */
+export interface SamplingOptions {
+  /** 0.0 - 2.0 */
+  temperature?: number;
+  maxTokens?: number;
+  /** 停止序列，未指定时角色扮演模式使用默认的用户发言标签 */
+  stopSequences?: string[];
+  /** 角色扮演中用户的名字（通常取自预设），设置后截掉模型越界生成的用户发言 */
+  userName?: string;
+}
+
 export interface IChatService {
-  sendMessage(sessionId: string, content: string, providerConfig?: ProviderConfig): Promise<string>;
-  regenerate(sessionId: string, userContent: string, providerConfig?: ProviderConfig): Promise<string>;
-  stopGeneration(sessionId: string): Promise<void>;
+  sendMessage(
+    sessionId: string,
+    content: string,
+    providerConfig?: ProviderConfig,
+    assistantPrefix?: string,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string>;
+  sendMessageWithFiles(
+    sessionId: string,
+    content: string,
+    filePaths: string[],
+    providerConfig?: ProviderConfig,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string>;
+  regenerate(
+    sessionId: string,
+    userContent: string,
+    providerConfig?: ProviderConfig,
+    messageId?: string,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string>;
+  stopGeneration(sessionId: string, requestId?: string): Promise<void>;
   getMessages(sessionId: string, page?: number, limit?: number): Promise<Message[]>;
+  /** 清空会话消息（保留会话），返回删除的消息数量 */
+  clearSessionMessages(sessionId: string): Promise<number>;
+  /** 编辑已保存的消息内容，返回编辑后的消息 */
+  editMessage(messageId: string, content: string): Promise<Message>;
+  /** 删除单条消息，cascade 为 true 时一并删除用户消息之后的助手回复 */
+  deleteMessage(messageId: string, cascade?: boolean): Promise<boolean>;
+  /** 全文搜索消息（不区分大小写），未指定 sessionId 时搜索所有会话 */
+  searchMessages(query: string, sessionId?: string, limit?: number): Promise<MessageSearchResult[]>;
+  suggestReplies(sessionId: string, providerId: string, count?: number): Promise<string[]>;
+  /** 获取会话实际生效的设置及其来源 */
+  getEffectiveSettings(sessionId: string): Promise<EffectiveSettings>;
+  /** 丢弃提供商缓存的适配器，返回是否确实移除了缓存 */
+  resetProvider(providerId: string): Promise<boolean>;
+  /** 并发检查所有已注册提供商的健康状态 */
+  healthCheckAll(): Promise<ProviderHealth[]>;
+  /** 汇总所有提供商的健康状态（最近检查过的使用缓存结果） */
+  providersHealth(): Promise<ProviderStatus[]>;
+  /** 估算文本的 Token 数量（近似值） */
+  estimateTokens(text: string, model?: string): Promise<number>;
   onMessageChunk(callback: (chunk: MessageChunk) => void): () => void;
-  onMessageComplete(
-    callback: (data: { sessionId: string; messageId: string; emotion?: Emotion }) => void,
+  /** 合并的流式帧（需启用 llm.combinedStreamFrames） */
+  onStreamFrame(callback: (frame: StreamFrame) => void): () => void;
+  onMessageStart(callback: (data: MessageStartEvent) => void): () => void;
+  onMessageComplete(callback: (data: MessageCompleteEvent) => void): () => void;
+  onMessageError(
+    callback: (data: { sessionId: string; requestId: string; error: string; content?: string | null }) => void,
   ): () => void;
-  onMessageError(callback: (data: { sessionId: string; error: string }) => void): () => void;
+  onProviderFellBack(callback: (data: { sessionId: string; from: string; to: string }) => void): () => void;
+  onContentFiltered(callback: (data: ContentFilteredEvent) => void): () => void;
+  onRepetitionStopped(callback: (data: RepetitionStoppedEvent) => void): () => void;
+  onUserEmotion(callback: (data: UserEmotionEvent) => void): () => void;
+  /** 请求新建对话（`--new-chat` 或 `kizuna://new-chat?preset=<id>`） */
+  onNewChatRequested(callback: (data: { presetId: string | null }) => void): () => void;
+  /** 再次启动应用时请求打开会话（`kizuna://session/<id>`） */
+  onOpenSessionRequested(callback: (data: { sessionId: string }) => void): () => void;
 }
 
 class ChatServiceImpl implements IChatService {
-  async sendMessage(sessionId: string, content: string, providerConfig?: ProviderConfig): Promise<string> {
+  async sendMessage(
+    sessionId: string,
+    content: string,
+    providerConfig?: ProviderConfig,
+    assistantPrefix?: string,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string> {
     logger.debug(`[ChatService] sendMessage called`, { sessionId, content, providerConfig: providerConfig ? '(configured)' : '(none)' });
     try {
       const result = await commandBus.dispatch<
-        { request: { sessionId: string; content: string; providerConfig?: ProviderConfig } },
-        { messageId: string }
-      >("chat:send_message", { request: { sessionId, content, providerConfig } });
+        {
+          request: {
+            sessionId: string;
+            content: string;
+            providerConfig?: ProviderConfig;
+            assistantPrefix?: string;
+            requestId?: string;
+          } & SamplingOptions;
+        },
+        { messageId: string; requestId: string }
+      >("chat:send_message", { request: { sessionId, content, providerConfig, assistantPrefix, requestId, ...sampling } });
       logger.debug(`[ChatService] sendMessage success`, result);
       return result.messageId;
     } catch (error) {
@@ -30,13 +194,58 @@ class ChatServiceImpl implements IChatService {
     }
   }
 
-  async regenerate(sessionId: string, userContent: string, providerConfig?: ProviderConfig): Promise<string> {
-    logger.debug(`[ChatService] regenerate called`, { sessionId, userContent, providerConfig: providerConfig ? '(configured)' : '(none)' });
+  async sendMessageWithFiles(
+    sessionId: string,
+    content: string,
+    filePaths: string[],
+    providerConfig?: ProviderConfig,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string> {
+    logger.debug(`[ChatService] sendMessageWithFiles called`, { sessionId, content, filePaths });
     try {
       const result = await commandBus.dispatch<
-        { request: { sessionId: string; userContent: string; providerConfig?: ProviderConfig } },
-        { messageId: string }
-      >("chat:regenerate", { request: { sessionId, userContent, providerConfig } });
+        {
+          request: {
+            sessionId: string;
+            content: string;
+            filePaths: string[];
+            providerConfig?: ProviderConfig;
+            requestId?: string;
+          } & SamplingOptions;
+        },
+        { messageId: string; requestId: string }
+      >("chat:send_message_with_files", { request: { sessionId, content, filePaths, providerConfig, requestId, ...sampling } });
+      logger.debug(`[ChatService] sendMessageWithFiles success`, result);
+      return result.messageId;
+    } catch (error) {
+      console.error(`[ChatService] sendMessageWithFiles failed`, error);
+      throw error;
+    }
+  }
+
+  async regenerate(
+    sessionId: string,
+    userContent: string,
+    providerConfig?: ProviderConfig,
+    messageId?: string,
+    requestId?: string,
+    sampling?: SamplingOptions,
+  ): Promise<string> {
+    logger.debug(`[ChatService] regenerate called`, { sessionId, userContent, messageId, providerConfig: providerConfig ? '(configured)' : '(none)' });
+    try {
+      const result = await commandBus.dispatch<
+        {
+          request: {
+            sessionId: string;
+            userContent: string;
+            providerConfig?: ProviderConfig;
+            messageId?: string;
+            requestId?: string;
+          } & SamplingOptions;
+        },
+        { messageId: string; requestId: string }
+      >("chat:regenerate", { request: { sessionId, userContent, providerConfig, messageId, requestId, ...sampling } });
       logger.debug(`[ChatService] regenerate success`, result);
       return result.messageId;
     } catch (error) {
@@ -45,13 +254,78 @@ class ChatServiceImpl implements IChatService {
     }
   }
 
-  async stopGeneration(sessionId: string): Promise<void> {
-    await commandBus.dispatch("chat:stop_generation", { request: { sessionId } });
+  async stopGeneration(sessionId: string, requestId?: string): Promise<void> {
+    await commandBus.dispatch("chat:stop_generation", { request: { sessionId, requestId } });
+  }
+
+  async clearSessionMessages(sessionId: string): Promise<number> {
+    return await commandBus.dispatch<{ request: { sessionId: string } }, number>(
+      "chat:clear_session_messages",
+      { request: { sessionId } },
+    );
+  }
+
+  async editMessage(messageId: string, content: string): Promise<Message> {
+    return await commandBus.dispatch<{ request: { messageId: string; content: string } }, Message>(
+      "chat:edit_message",
+      { request: { messageId, content } },
+    );
+  }
+
+  async deleteMessage(messageId: string, cascade = false): Promise<boolean> {
+    return await commandBus.dispatch<
+      { request: { messageId: string; cascade: boolean } },
+      boolean
+    >("chat:delete_message", { request: { messageId, cascade } });
+  }
+
+  async searchMessages(query: string, sessionId?: string, limit?: number): Promise<MessageSearchResult[]> {
+    return await commandBus.dispatch<
+      { request: { query: string; sessionId?: string; limit?: number } },
+      MessageSearchResult[]
+    >("chat:search_messages", { request: { query, sessionId, limit } });
+  }
+
+  async suggestReplies(sessionId: string, providerId: string, count = 3): Promise<string[]> {
+    return await commandBus.dispatch<
+      { request: { sessionId: string; providerId: string; count: number } },
+      string[]
+    >("chat:suggest_replies", { request: { sessionId, providerId, count } });
+  }
+
+  async getEffectiveSettings(sessionId: string): Promise<EffectiveSettings> {
+    return await commandBus.dispatch<{ request: { sessionId: string } }, EffectiveSettings>(
+      "chat:get_effective_settings",
+      { request: { sessionId } },
+    );
+  }
+
+  async resetProvider(providerId: string): Promise<boolean> {
+    return await commandBus.dispatch<{ request: { providerId: string } }, boolean>(
+      "chat:reset_provider",
+      { request: { providerId } },
+    );
   }
 
-  async getMessages(sessionId: string, page = 1, limit = 50): Promise<Message[]> {
+  async healthCheckAll(): Promise<ProviderHealth[]> {
+    return await commandBus.dispatch<void, ProviderHealth[]>("chat:health_check_all");
+  }
+
+  async providersHealth(): Promise<ProviderStatus[]> {
+    return await commandBus.dispatch<void, ProviderStatus[]>("chat:providers_health");
+  }
+
+  async estimateTokens(text: string, model?: string): Promise<number> {
+    return await commandBus.dispatch<{ request: { text: string; model?: string } }, number>(
+      "chat:estimate_tokens",
+      { request: { text, model } },
+    );
+  }
+
+  async getMessages(sessionId: string, page?: number, limit?: number): Promise<Message[]> {
+    // 未指定时由后端使用默认分页
     const messages = await commandBus.dispatch<
-      { request: { sessionId: string; page: number; limit: number } },
+      { request: { sessionId: string; page?: number; limit?: number } },
       any[]
     >("chat:get_messages", { request: { sessionId, page, limit } });
 
@@ -76,11 +350,24 @@ class ChatServiceImpl implements IChatService {
     });
   }
 
-  onMessageComplete(
-    callback: (data: { sessionId: string; messageId: string; emotion?: Emotion }) => void,
-  ): () => void {
+  onStreamFrame(callback: (frame: StreamFrame) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:frame`);
+    return createSafeSubscriber<StreamFrame>("llm:frame", (frame) => {
+      callback(frame);
+    });
+  }
+
+  onMessageStart(callback: (data: MessageStartEvent) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:start`);
+    return createSafeSubscriber<MessageStartEvent>("llm:start", (data) => {
+      logger.debug(`[ChatService] Received start:`, data);
+      callback(data);
+    });
+  }
+
+  onMessageComplete(callback: (data: MessageCompleteEvent) => void): () => void {
     logger.debug(`[ChatService] Subscribing to llm:complete`);
-    return createSafeSubscriber<{ sessionId: string; messageId: string; emotion?: Emotion }>(
+    return createSafeSubscriber<MessageCompleteEvent>(
       "llm:complete",
       (data) => {
         logger.debug(`[ChatService] Received complete:`, data);
@@ -89,10 +376,72 @@ class ChatServiceImpl implements IChatService {
     );
   }
 
-  onMessageError(callback: (data: { sessionId: string; error: string }) => void): () => void {
+  onMessageError(
+    callback: (data: { sessionId: string; requestId: string; error: string; content?: string | null }) => void,
+  ): () => void {
     logger.debug(`[ChatService] Subscribing to llm:error`);
-    return createSafeSubscriber<{ sessionId: string; error: string }>("llm:error", (data) => {
-      logger.debug(`[ChatService] Received error:`, data);
+    return createSafeSubscriber<{ sessionId: string; requestId: string; error: string; content?: string | null }>(
+      "llm:error",
+      (data) => {
+        logger.debug(`[ChatService] Received error:`, data);
+        callback(data);
+      },
+    );
+  }
+
+  onProviderFellBack(callback: (data: { sessionId: string; from: string; to: string }) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:provider_fell_back`);
+    return createSafeSubscriber<{ sessionId: string; from: string; to: string }>(
+      "llm:provider_fell_back",
+      (data) => {
+        logger.debug(`[ChatService] Provider fell back:`, data);
+        callback(data);
+      },
+    );
+  }
+
+  onContentFiltered(callback: (data: ContentFilteredEvent) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:content_filtered`);
+    return createSafeSubscriber<ContentFilteredEvent>(
+      "llm:content_filtered",
+      (data) => {
+        logger.debug(`[ChatService] Content filtered:`, data);
+        callback(data);
+      },
+    );
+  }
+
+  onRepetitionStopped(callback: (data: RepetitionStoppedEvent) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:repetition_stopped`);
+    return createSafeSubscriber<RepetitionStoppedEvent>(
+      "llm:repetition_stopped",
+      (data) => {
+        logger.debug(`[ChatService] Generation stopped on repetition:`, data);
+        callback(data);
+      },
+    );
+  }
+
+  onUserEmotion(callback: (data: UserEmotionEvent) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to llm:user_emotion`);
+    return createSafeSubscriber<UserEmotionEvent>("llm:user_emotion", (data) => {
+      logger.debug(`[ChatService] Received user emotion:`, data);
+      callback(data);
+    });
+  }
+
+  onNewChatRequested(callback: (data: { presetId: string | null }) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to app:new_chat`);
+    return createSafeSubscriber<{ presetId: string | null }>("app:new_chat", (data) => {
+      logger.debug(`[ChatService] New chat requested:`, data);
+      callback(data);
+    });
+  }
+
+  onOpenSessionRequested(callback: (data: { sessionId: string }) => void): () => void {
+    logger.debug(`[ChatService] Subscribing to app:open_session`);
+    return createSafeSubscriber<{ sessionId: string }>("app:open_session", (data) => {
+      logger.debug(`[ChatService] Open session requested:`, data);
       callback(data);
     });
   }
@@ -1,5 +1,5 @@
-import { commandBus } from "./ipc";
-import type { AppConfig, ProviderConfig, Preset } from "@/types";
+import { commandBus, createSafeSubscriber } from "./ipc";
+import type { AppConfig, ProviderConfig, Preset, QuietHours } from "@/types";
 
 export interface ModelInfo {
   id: string;
@@ -7,20 +7,60 @@ export interface ModelInfo {
   ownedBy?: string;
 }
 
+/** 模型的可选功能 */
+export type ModelFeature = "vision" | "functions";
+
+export interface ModelSupport {
+  supported: boolean;
+  /** fetched：服务端模型列表；bundled：内置列表；unknown：模型不在列表中 */
+  source: "fetched" | "bundled" | "unknown";
+}
+
+export interface FirstRunStatus {
+  isFirstRun: boolean;
+  hasProvider: boolean;
+}
+
+/** 导入时与已有预设同名的处理方式 */
+export type PresetConflictPolicy = "keepBoth" | "replace" | "skip";
+
+export interface ImportPresetsResult {
+  imported: Preset[];
+  skipped: string[];
+}
+
 export interface IConfigService {
   getConfig(): Promise<AppConfig>;
+  isFirstRun(): Promise<FirstRunStatus>;
+  /** 启用或禁用开机自启动，返回系统中的实际状态 */
+  setAutostart(enabled: boolean): Promise<boolean>;
+  /** 查询系统中的自启动状态（同时同步到配置） */
+  isAutostartEnabled(): Promise<boolean>;
   setConfig<K extends keyof AppConfig>(key: K, value: AppConfig[K]): Promise<void>;
   resetConfig(): Promise<void>;
   listProviders(): Promise<ProviderConfig[]>;
   addProvider(provider: Omit<ProviderConfig, "id">): Promise<ProviderConfig>;
   updateProvider(id: string, provider: Partial<ProviderConfig>): Promise<void>;
   deleteProvider(id: string): Promise<void>;
+  reorderProviders(orderedIds: string[]): Promise<ProviderConfig[]>;
+  /** 设置免打扰时段，传 null 取消 */
+  setQuietHours(quietHours: QuietHours | null): Promise<QuietHours | null>;
+  cloneProvider(sourceId: string): Promise<ProviderConfig>;
   testConnection(providerId: string): Promise<{ success: boolean; error?: string }>;
-  fetchModels(providerConfig: ProviderConfig): Promise<ModelInfo[]>;
+  fetchModels(providerConfig: ProviderConfig, requestId?: string): Promise<ModelInfo[]>;
+  cancelFetchModels(requestId: string): Promise<void>;
+  /** 查询模型是否支持指定功能 */
+  modelSupports(providerConfig: ProviderConfig, model: string, feature: ModelFeature): Promise<ModelSupport>;
   listPresets(): Promise<Preset[]>;
   createPreset(preset: Omit<Preset, "id" | "createdAt">): Promise<Preset>;
   updatePreset(id: string, preset: Partial<Preset>): Promise<void>;
   deletePreset(id: string): Promise<void>;
+  /** 导出全部预设为 JSON 数组 */
+  exportPresets(): Promise<string>;
+  /** 导入预设，导入的预设会分配新的 ID */
+  importPresets(json: string, onConflict?: PresetConflictPolicy): Promise<ImportPresetsResult>;
+  /** 配置变更通知，key 为变化的配置段或键，整体变化时为 "*" */
+  onConfigChanged(callback: (data: { key: string }) => void): () => void;
 }
 
 class ConfigServiceImpl implements IConfigService {
@@ -37,7 +77,21 @@ class ConfigServiceImpl implements IConfigService {
   }
 
   async listProviders(): Promise<ProviderConfig[]> {
-    return await commandBus.dispatch<void, ProviderConfig[]>("llm:list_providers");
+    return await commandBus.dispatch<void, ProviderConfig[]>("config:list_providers");
+  }
+
+  async reorderProviders(orderedIds: string[]): Promise<ProviderConfig[]> {
+    return await commandBus.dispatch<{ request: { orderedIds: string[] } }, ProviderConfig[]>(
+      "config:reorder_providers",
+      { request: { orderedIds } },
+    );
+  }
+
+  async setQuietHours(quietHours: QuietHours | null): Promise<QuietHours | null> {
+    return await commandBus.dispatch<
+      { request: { quietHours: QuietHours | null } },
+      QuietHours | null
+    >("config:set_quiet_hours", { request: { quietHours } });
   }
 
   async addProvider(provider: Omit<ProviderConfig, "id">): Promise<ProviderConfig> {
@@ -47,14 +101,38 @@ class ConfigServiceImpl implements IConfigService {
     );
   }
 
+  /** 只发送需要修改的字段，省略 apiKey 时保留已保存的密钥 */
   async updateProvider(id: string, provider: Partial<ProviderConfig>): Promise<void> {
-    await commandBus.dispatch("llm:update_provider", { id, ...provider });
+    const { id: _id, ...partial } = provider;
+    await commandBus.dispatch("config:update_provider", { request: { id, partial } });
   }
 
   async deleteProvider(id: string): Promise<void> {
     await commandBus.dispatch("llm:delete_provider", { id });
   }
 
+  async isFirstRun(): Promise<FirstRunStatus> {
+    return await commandBus.dispatch<void, FirstRunStatus>("config:is_first_run");
+  }
+
+  async setAutostart(enabled: boolean): Promise<boolean> {
+    return await commandBus.dispatch<{ request: { enabled: boolean } }, boolean>(
+      "autostart:set",
+      { request: { enabled } },
+    );
+  }
+
+  async isAutostartEnabled(): Promise<boolean> {
+    return await commandBus.dispatch<void, boolean>("autostart:is_enabled");
+  }
+
+  async cloneProvider(sourceId: string): Promise<ProviderConfig> {
+    return await commandBus.dispatch<{ request: { sourceId: string } }, ProviderConfig>(
+      "config:clone_provider",
+      { request: { sourceId } },
+    );
+  }
+
   async testConnection(providerId: string): Promise<{ success: boolean; error?: string }> {
     return await commandBus.dispatch<
       { providerId: string },
@@ -62,13 +140,13 @@ class ConfigServiceImpl implements IConfigService {
     >("llm:test_connection", { providerId });
   }
 
-  async fetchModels(providerConfig: ProviderConfig): Promise<ModelInfo[]> {
+  async fetchModels(providerConfig: ProviderConfig, requestId?: string): Promise<ModelInfo[]> {
     console.log("[ConfigService] fetchModels called with:", { ...providerConfig, apiKey: "***" });
     try {
       const result = await commandBus.dispatch<
-        { request: { providerConfig: ProviderConfig } },
+        { request: { providerConfig: ProviderConfig; requestId?: string } },
         ModelInfo[]
-      >("chat:fetch_models", { request: { providerConfig } });
+      >("chat:fetch_models", { request: { providerConfig, requestId } });
       console.log("[ConfigService] fetchModels result:", result);
       return result;
     } catch (error) {
@@ -77,6 +155,21 @@ class ConfigServiceImpl implements IConfigService {
     }
   }
 
+  async cancelFetchModels(requestId: string): Promise<void> {
+    await commandBus.dispatch("chat:cancel_fetch_models", { request: { requestId } });
+  }
+
+  async modelSupports(
+    providerConfig: ProviderConfig,
+    model: string,
+    feature: ModelFeature,
+  ): Promise<ModelSupport> {
+    return await commandBus.dispatch<
+      { request: { providerConfig: ProviderConfig; model: string; feature: ModelFeature } },
+      ModelSupport
+    >("chat:model_supports", { request: { providerConfig, model, feature } });
+  }
+
   async listPresets(): Promise<Preset[]> {
     return await commandBus.dispatch<void, Preset[]>("preset:list");
   }
@@ -95,6 +188,24 @@ class ConfigServiceImpl implements IConfigService {
   async deletePreset(id: string): Promise<void> {
     await commandBus.dispatch("preset:delete", { id });
   }
+
+  async exportPresets(): Promise<string> {
+    return await commandBus.dispatch<void, string>("preset:export");
+  }
+
+  async importPresets(
+    json: string,
+    onConflict: PresetConflictPolicy = "keepBoth",
+  ): Promise<ImportPresetsResult> {
+    return await commandBus.dispatch<
+      { request: { json: string; onConflict: PresetConflictPolicy } },
+      ImportPresetsResult
+    >("preset:import", { request: { json, onConflict } });
+  }
+
+  onConfigChanged(callback: (data: { key: string }) => void): () => void {
+    return createSafeSubscriber<{ key: string }>("config:changed", callback);
+  }
 }
 
 export const configService: IConfigService = new ConfigServiceImpl();
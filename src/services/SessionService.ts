@@ -7,6 +7,15 @@ export interface ISessionService {
   getSession(id: string): Promise<Session>;
   deleteSession(id: string): Promise<void>;
   renameSession(id: string, title: string): Promise<void>;
+  setDisplayName(id: string, displayName: string | null): Promise<Session>;
+  /** 在指定消息处分支出新会话（包含该消息） */
+  forkSession(sessionId: string, upToMessageId: string): Promise<Session>;
+  /** 导出会话及其全部消息，返回 JSON 字符串 */
+  exportSession(sessionId: string): Promise<string>;
+  /** 从导出的 JSON 导入会话，返回新创建的会话 */
+  importSession(json: string): Promise<Session>;
+  /** 按配置的自动标题策略判断是否应为会话生成标题 */
+  shouldAutoTitle(sessionId: string): Promise<boolean>;
 }
 
 class SessionServiceImpl implements ISessionService {
@@ -39,6 +48,40 @@ class SessionServiceImpl implements ISessionService {
   async renameSession(id: string, title: string): Promise<void> {
     await commandBus.dispatch("session:rename", { request: { id, title } });
   }
+
+  async setDisplayName(id: string, displayName: string | null): Promise<Session> {
+    return await commandBus.dispatch<
+      { request: { id: string; displayName: string | null } },
+      Session
+    >("session:set_display_name", { request: { id, displayName } });
+  }
+
+  async forkSession(sessionId: string, upToMessageId: string): Promise<Session> {
+    return await commandBus.dispatch<
+      { request: { sessionId: string; upToMessageId: string } },
+      Session
+    >("session:fork", { request: { sessionId, upToMessageId } });
+  }
+
+  async exportSession(sessionId: string): Promise<string> {
+    return await commandBus.dispatch<{ request: { sessionId: string } }, string>(
+      "session:export",
+      { request: { sessionId } },
+    );
+  }
+
+  async importSession(json: string): Promise<Session> {
+    return await commandBus.dispatch<{ request: { json: string } }, Session>("session:import", {
+      request: { json },
+    });
+  }
+
+  async shouldAutoTitle(sessionId: string): Promise<boolean> {
+    return await commandBus.dispatch<{ request: { sessionId: string } }, boolean>(
+      "session:should_auto_title",
+      { request: { sessionId } },
+    );
+  }
 }
 
 export const sessionService: ISessionService = new SessionServiceImpl();
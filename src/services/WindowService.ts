@@ -8,6 +8,7 @@ export interface WindowInfo {
   isFocused: boolean;
   width: number;
   height: number;
+  isTransparent: boolean;
 }
 
 export interface CreateWindowOptions {
@@ -15,17 +16,22 @@ export interface CreateWindowOptions {
   title?: string;
   width?: number;
   height?: number;
-  mode?: "normal" | "pet" | "compact";
+  mode?: "normal" | "pet" | "compact" | "desktop";
 }
 
 export interface IWindowService {
   togglePetMode(): Promise<{ isPetMode: boolean }>;
   setAlwaysOnTop(value: boolean): Promise<void>;
-  startDragging(): Promise<void>;
+  setDesktopMode(enabled: boolean): Promise<void>;
+  startDragging(): Promise<boolean>;
+  setDragRegion(inDragRegion: boolean): Promise<void>;
+  setDragLocked(locked: boolean): Promise<void>;
   createWindow(options: CreateWindowOptions): Promise<WindowInfo>;
   listWindows(): Promise<WindowInfo[]>;
   closeWindow(label: string): Promise<void>;
+  setTitle(title: string, label?: string): Promise<void>;
   onModeChanged(callback: (data: { mode: WindowMode }) => void): () => void;
+  onTransparencyUnavailable(callback: (data: { label: string }) => void): () => void;
 }
 
 class WindowServiceImpl implements IWindowService {
@@ -37,8 +43,24 @@ class WindowServiceImpl implements IWindowService {
     await commandBus.dispatch("window:set_always_on_top", { request: { value } });
   }
 
-  async startDragging(): Promise<void> {
-    await commandBus.dispatch("window:start_dragging");
+  async setDesktopMode(enabled: boolean): Promise<void> {
+    await commandBus.dispatch("window:set_desktop_mode", { request: { enabled } });
+  }
+
+  async startDragging(): Promise<boolean> {
+    return await commandBus.dispatch<void, boolean>("window:start_dragging");
+  }
+
+  async setDragRegion(inDragRegion: boolean): Promise<void> {
+    await commandBus.dispatch("window:set_drag_region", { request: { inDragRegion } });
+  }
+
+  async setDragLocked(locked: boolean): Promise<void> {
+    await commandBus.dispatch("window:set_drag_locked", { request: { locked } });
+  }
+
+  async setTitle(title: string, label?: string): Promise<void> {
+    await commandBus.dispatch("window:set_title", { request: { label, title } });
   }
 
   async createWindow(options: CreateWindowOptions): Promise<WindowInfo> {
@@ -56,6 +78,10 @@ class WindowServiceImpl implements IWindowService {
   onModeChanged(callback: (data: { mode: WindowMode }) => void): () => void {
     return createSafeSubscriber<{ mode: WindowMode }>("window:mode_changed", callback);
   }
+
+  onTransparencyUnavailable(callback: (data: { label: string }) => void): () => void {
+    return createSafeSubscriber<{ label: string }>("window:transparency_unavailable", callback);
+  }
 }
 
 export const windowService: IWindowService = new WindowServiceImpl();
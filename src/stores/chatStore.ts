@@ -15,6 +15,8 @@ interface ChatState {
   currentSession: Session | null;
   messages: Message[];
   isGenerating: boolean;
+  /** 进行中的生成请求 ID，用于停止生成 */
+  activeRequestId: string | null;
   streamContent: string;
   error: string | null;
   lastTokenUsage: TokenUsage | null;
@@ -60,6 +62,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
   currentSession: null,
   messages: [],
   isGenerating: false,
+  activeRequestId: null,
   streamContent: "",
   error: null,
   lastTokenUsage: null,
@@ -92,6 +95,16 @@ export const useChatStore = create<ChatState>((set, get) => ({
       return;
     }
 
+    // 超出字符数上限的消息在发送前拒绝（按 Unicode 字符计数，与后端一致）
+    const { maxUserMessageChars } = useConfigStore.getState().config.llm;
+    const length = Array.from(content).length;
+    if (maxUserMessageChars && length > maxUserMessageChars) {
+      set({
+        error: `Message is too long: ${length} characters exceeds the limit of ${maxUserMessageChars}`,
+      });
+      return;
+    }
+
     const userMessage: Message = {
       id: crypto.randomUUID(),
       sessionId: currentSession.id,
@@ -101,9 +114,11 @@ export const useChatStore = create<ChatState>((set, get) => ({
     };
     logger.debug(`[ChatStore] Created user message:`, userMessage);
 
+    const requestId = crypto.randomUUID();
     set((state) => ({
       messages: [...state.messages, userMessage],
       isGenerating: true,
+      activeRequestId: requestId,
       streamContent: "",
       error: null,
     }));
@@ -114,7 +129,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
       logger.debug(`[ChatStore] Provider config:`, providerConfig ? '(configured)' : '(none)');
       
       logger.debug(`[ChatStore] Calling chatService.sendMessage...`);
-      await chatService.sendMessage(currentSession.id, content, providerConfig);
+      await chatService.sendMessage(currentSession.id, content, providerConfig, undefined, requestId);
       logger.debug(`[ChatStore] chatService.sendMessage completed`);
     } catch (error) {
       console.error(`[ChatStore] sendMessage error:`, error);
@@ -126,11 +141,11 @@ export const useChatStore = create<ChatState>((set, get) => ({
   },
 
   stopGeneration: () => {
-    const { currentSession } = get();
+    const { currentSession, activeRequestId } = get();
     if (currentSession) {
-      chatService.stopGeneration(currentSession.id);
+      chatService.stopGeneration(currentSession.id, activeRequestId ?? undefined);
     }
-    set({ isGenerating: false });
+    set({ isGenerating: false, activeRequestId: null });
   },
 
   appendStreamContent: (content: string) => {
@@ -163,6 +178,7 @@ export const useChatStore = create<ChatState>((set, get) => ({
       messages: [...state.messages, assistantMessage],
       streamContent: "",
       isGenerating: false,
+      activeRequestId: null,
       lastTokenUsage: {
         inputTokens,
         outputTokens,
@@ -186,6 +202,9 @@ export const useChatStore = create<ChatState>((set, get) => ({
         msg.id === messageId ? { ...msg, content: newContent } : msg
       ),
     }));
+    chatService.editMessage(messageId, newContent).catch((error) => {
+      logger.error("[ChatStore] Failed to persist edited message:", error);
+    });
   },
 
   // 删除单条消息
@@ -193,6 +212,9 @@ export const useChatStore = create<ChatState>((set, get) => ({
     set((state) => ({
       messages: state.messages.filter((msg) => msg.id !== messageId),
     }));
+    chatService.deleteMessage(messageId).catch((error) => {
+      logger.error("[ChatStore] Failed to delete message:", error);
+    });
   },
 
   // 删除从某条消息开始的所有后续消息（包含该消息）
@@ -234,9 +256,11 @@ export const useChatStore = create<ChatState>((set, get) => ({
     // 删除从用户消息之后的所有消息（保留用户消息）
     const remainingMessages = messages.slice(0, userMessageIndex + 1);
     
+    const requestId = crypto.randomUUID();
     set({
       messages: remainingMessages,
       isGenerating: true,
+      activeRequestId: requestId,
       streamContent: "",
       error: null,
     });
@@ -244,7 +268,15 @@ export const useChatStore = create<ChatState>((set, get) => ({
     try {
       const providerConfig = getLLMConfig();
       // 调用 regenerate（不会在后端创建新的用户消息）
-      await chatService.regenerate(currentSession.id, userMessage.content, providerConfig);
+      // 重新生成 AI 消息时传入其 ID，后端据此精确删除并构建上下文
+      const targetId = targetMessage.role === "assistant" ? targetMessage.id : undefined;
+      await chatService.regenerate(
+        currentSession.id,
+        userMessage.content,
+        providerConfig,
+        targetId,
+        requestId,
+      );
     } catch (error) {
       set({
         error: error instanceof Error ? error.message : "Failed to regenerate",
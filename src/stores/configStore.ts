@@ -20,11 +20,13 @@ const defaultConfig: AppConfig = {
     theme: "system",
     autoStart: false,
     minimizeToTray: true,
+    quietHours: null,
   },
   window: {
     defaultMode: "normal",
     petModeSize: { width: 300, height: 400 },
     petModePosition: "remember",
+    maxWindows: 5,
   },
   shortcuts: {
     toggleWindow: "CommandOrControl+Shift+K",
@@ -36,6 +38,11 @@ const defaultConfig: AppConfig = {
     streamResponse: true,
     contextLength: 10,
     providers: {},
+    autoFallback: false,
+    maxUserMessageChars: 100000,
+    autoTitle: "afterFirstReply",
+    analyzeUserEmotion: false,
+    combinedStreamFrames: false,
   },
   model: {
     defaultType: "live2d",
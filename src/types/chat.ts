@@ -1,17 +1,23 @@
 export interface Message {
   id: string;
   sessionId: string;
-  role: "user" | "assistant" | "system";
+  role: "user" | "assistant" | "system" | "context_break";
   content: string;
   tokens?: number;
   emotion?: Emotion;
   createdAt: string;
+  /** 助手回复的结束原因，"length" 表示因长度限制被截断 */
+  finishReason?: FinishReason;
 }
 
+export type FinishReason = "stop" | "length" | "content_filter" | "function_call";
+
 export interface Session {
   id: string;
   title: string;
   presetId?: string;
+  /** 界面上显示的助手名称（仅用于展示） */
+  displayName?: string | null;
   modelConfig?: LLMConfig;
   createdAt: string;
   updatedAt: string;
@@ -38,6 +44,19 @@ export interface MessageChunk {
   tokens?: number;
   /** 口型音素序列 (A/E/I/O/U/N/closed) - 由后端 rust-pinyin 生成 */
   phonemes?: string[];
+  /** 分块结束时是否处于围栏代码块内，可据此推迟语法高亮 */
+  inCodeBlock: boolean;
+}
+
+/** 合并的流式帧（启用 llm.combinedStreamFrames 时代替 MessageChunk） */
+export interface StreamFrame {
+  sessionId: string;
+  content: string;
+  /** 口型音素序列 */
+  phonemes: string[];
+  /** 相对上一帧变化后的情感，未变化时为 null */
+  emotion: Emotion | null;
+  inCodeBlock: boolean;
 }
 
 export interface SendMessageRequest {
@@ -11,15 +11,25 @@ export interface GeneralConfig {
   theme: "light" | "dark" | "system";
   autoStart: boolean;
   minimizeToTray: boolean;
+  /** 免打扰时段，未设置时为 null */
+  quietHours: QuietHours | null;
+}
+
+/** 免打扰时段（本地时间 HH:MM），开始晚于结束时跨越午夜 */
+export interface QuietHours {
+  start: string;
+  end: string;
 }
 
 export interface WindowConfig {
   defaultMode: WindowMode;
   petModeSize: { width: number; height: number };
   petModePosition: "remember" | { x: number; y: number };
+  /** 除主窗口外最多同时打开的窗口数 */
+  maxWindows: number;
 }
 
-export type WindowMode = "normal" | "pet" | "compact" | "fullscreen";
+export type WindowMode = "normal" | "pet" | "compact" | "fullscreen" | "desktop";
 
 export interface ShortcutConfig {
   toggleWindow: string;
@@ -32,10 +42,22 @@ export interface LLMSettings {
   streamResponse: boolean;
   contextLength: number;
   providers: Record<string, ProviderConfig>;
+  autoFallback?: boolean;
+  /** 单条用户消息的最大字符数 */
+  maxUserMessageChars?: number;
+  /** 何时自动生成会话标题 */
+  autoTitle?: AutoTitlePolicy;
+  /** 分析用户消息的情感，使形象在回复前做出反应 */
+  analyzeUserEmotion?: boolean;
+  /** 流式输出时改为发送合并的 `llm:frame` 事件（文本、音素和情感变化） */
+  combinedStreamFrames?: boolean;
 }
 
+/** 自动生成会话标题的触发策略 */
+export type AutoTitlePolicy = "off" | "afterFirstReply" | { afterNMessages: number };
+
 /** LLM 提供商类型 */
-export type ProviderType = "openai" | "claude" | "ollama" | "custom";
+export type ProviderType = "openai" | "claude" | "ollama" | "gemini" | "custom" | "demo";
 
 export interface ProviderConfig {
   id: string;
@@ -43,8 +65,40 @@ export interface ProviderConfig {
   providerType: ProviderType;
   baseUrl: string;
   apiKey: string;
+  /** 已保存的 API Key 无法解密，需要重新输入 */
+  apiKeyUnreadable?: boolean;
   models: string[];
   isDefault: boolean;
+  /** 显示顺序，越小越靠前 */
+  sortOrder?: number;
+  /** 请求超时（秒） */
+  timeoutSecs?: number;
+  /** 模型冷启动时首次请求的超时（秒），目前仅 Ollama 使用 */
+  coldStartTimeoutSecs?: number;
+  /** HTTP 连接调优，未设置的项使用默认值 */
+  http?: HttpClientOptions;
+  /** HTTP 代理地址（http、https 或 socks5），未设置时使用系统代理 */
+  proxyUrl?: string;
+  /** 附加到每个请求的请求头（如 HTTP-Referer、X-Title），同名时覆盖默认请求头 */
+  extraHeaders?: Record<string, string>;
+  /** 模型保持加载的时长（如 "5m"，"-1" 表示一直保持），仅 Ollama 使用 */
+  keepAlive?: string;
+  /** 上下文窗口大小（token），仅 Ollama 使用 */
+  numCtx?: number;
+  /** anthropic-version 请求头（YYYY-MM-DD），仅 Claude 使用 */
+  anthropicVersion?: string;
+  /** 启用的 anthropic-beta 功能，仅 Claude 使用 */
+  anthropicBeta?: string[];
+}
+
+/** HTTP 客户端连接调优 */
+export interface HttpClientOptions {
+  /** 直接使用 HTTP/2，跳过协商 */
+  http2PriorKnowledge?: boolean;
+  /** 连接池空闲连接保留时间（秒） */
+  poolIdleTimeoutSecs?: number;
+  /** TCP keepalive 间隔（秒） */
+  tcpKeepaliveSecs?: number;
 }
 
 export interface ModelConfig {
@@ -63,6 +117,11 @@ export interface Preset {
   modelPath: string;
   defaultExpression: string;
   emotionMapping: Record<string, EmotionMapping>;
+  assistantPrefix?: string;
+  /** 开场白，清空会话消息后重新插入 */
+  greeting?: string;
+  /** 角色扮演中用户的名字，设置后启用默认停止序列并截掉越界的用户发言 */
+  userName?: string;
   createdAt: string;
 }
 